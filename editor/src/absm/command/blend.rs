@@ -63,6 +63,20 @@ define_set_collection_element_command!(
     }
 );
 
+define_set_collection_element_command!(
+    SetBlendAdditivePoseSourceCommand<Handle<PoseNode>, Handle<PoseNode>>(self, context) {
+        let machine = fetch_machine(context, self.node_handle);
+        if let PoseNode::BlendAdditive(ref mut definition) = machine.layers_mut()[self.layer_index].nodes_mut()[self.handle] {
+            let pose_source = if self.index == 0 {
+                &mut definition.base_pose
+            } else {
+                &mut definition.additive_pose
+            };
+            std::mem::swap(pose_source, &mut self.value);
+        }
+    }
+);
+
 define_set_collection_element_command!(
     SetBlendSpacePointPositionCommand<Handle<PoseNode>, Vector2<f32>>(self, context) {
         let machine = fetch_machine(context, self.node_handle);
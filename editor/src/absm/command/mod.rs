@@ -1,6 +1,8 @@
 use crate::{command::Command, scene::commands::SceneContext};
 use fyrox::{
-    animation::machine::{LayerMask, Machine, MachineLayer, PoseNode, State, Transition},
+    animation::machine::{
+        LayerBlendMode, LayerMask, Machine, MachineLayer, PoseNode, State, Transition,
+    },
     core::{
         algebra::Vector2,
         pool::{Handle, Ticket},
@@ -767,6 +769,11 @@ define_absm_swap_command!(SetStateRootPoseCommand<Handle<State>, Handle<PoseNode
     &mut machine.layers_mut()[self.layer_index].states_mut()[self.handle].root
 });
 
+define_absm_swap_command!(SetStateSubMachineCommand<Handle<State>, Option<usize>>[layer_index: usize](self, context) {
+    let machine = fetch_machine(context, self.node_handle);
+    &mut machine.layers_mut()[self.layer_index].states_mut()[self.handle].sub_machine
+});
+
 #[derive(Debug)]
 pub struct SetLayerNameCommand {
     pub absm_node_handle: Handle<Node>,
@@ -823,6 +830,8 @@ pub struct RemoveLayerCommand {
     pub absm_node_handle: Handle<Node>,
     pub layer_index: usize,
     pub layer: Option<MachineLayer>,
+    // States (layer index and handle) that used the layer as a sub-machine.
+    sub_machine_users: Vec<(usize, Handle<State>)>,
 }
 
 impl RemoveLayerCommand {
@@ -831,6 +840,7 @@ impl RemoveLayerCommand {
             absm_node_handle,
             layer_index,
             layer: None,
+            sub_machine_users: Default::default(),
         }
     }
 }
@@ -841,13 +851,30 @@ impl Command for RemoveLayerCommand {
     }
 
     fn execute(&mut self, context: &mut SceneContext) {
-        self.layer =
-            Some(fetch_machine(context, self.absm_node_handle).remove_layer(self.layer_index));
+        let machine = fetch_machine(context, self.absm_node_handle);
+
+        self.sub_machine_users.clear();
+        for (layer_index, layer) in machine.layers().iter().enumerate() {
+            for (handle, state) in layer.states().pair_iter() {
+                if state.sub_machine == Some(self.layer_index) {
+                    self.sub_machine_users.push((layer_index, handle));
+                }
+            }
+        }
+
+        self.layer = Some(machine.remove_layer(self.layer_index));
     }
 
     fn revert(&mut self, context: &mut SceneContext) {
-        fetch_machine(context, self.absm_node_handle)
-            .insert_layer(self.layer_index, self.layer.take().unwrap());
+        let machine = fetch_machine(context, self.absm_node_handle);
+
+        machine.insert_layer(self.layer_index, self.layer.take().unwrap());
+
+        // Removal of the layer unlinks it from the states, that used it as a sub-machine.
+        for (layer_index, handle) in self.sub_machine_users.drain(..) {
+            machine.layers_mut()[layer_index].states_mut()[handle].sub_machine =
+                Some(self.layer_index);
+        }
     }
 }
 
@@ -880,3 +907,33 @@ impl Command for SetLayerMaskCommand {
         self.swap(context)
     }
 }
+
+#[derive(Debug)]
+pub struct SetLayerBlendModeCommand {
+    pub absm_node_handle: Handle<Node>,
+    pub layer_index: usize,
+    pub blend_mode: LayerBlendMode,
+}
+
+impl SetLayerBlendModeCommand {
+    fn swap(&mut self, context: &mut SceneContext) {
+        let layer =
+            &mut fetch_machine(context, self.absm_node_handle).layers_mut()[self.layer_index];
+        let old = layer.blend_mode();
+        layer.set_blend_mode(std::mem::replace(&mut self.blend_mode, old));
+    }
+}
+
+impl Command for SetLayerBlendModeCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Set Layer Blend Mode".to_string()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context)
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context)
+    }
+}
@@ -10,7 +10,7 @@ use crate::{
         toolbar::{Toolbar, ToolbarAction},
     },
     message::MessageSender,
-    scene::{EditorScene, Selection},
+    scene::{commands::ChangeSelectionCommand, EditorScene, Selection},
     Message,
 };
 use fyrox::{
@@ -488,13 +488,31 @@ impl AbsmEditor {
                             .query_component::<AbsmNode<State>>()
                         {
                             if let Some(layer_index) = selection.layer {
-                                self.state_viewer.set_state(
-                                    node.model_handle,
-                                    absm_node,
-                                    layer_index,
-                                    ui,
-                                );
-                                sender.send(Message::ForceSync);
+                                let machine = absm_node.machine();
+                                let sub_machine_layer = machine.layers()[layer_index]
+                                    .states()
+                                    .try_borrow(node.model_handle)
+                                    .and_then(|state| state.sub_machine)
+                                    .filter(|index| *index < machine.layers().len());
+
+                                if let Some(sub_machine_layer) = sub_machine_layer {
+                                    // Entering a sub-machine state opens the layer with its graph.
+                                    let mut new_selection = selection.clone();
+                                    new_selection.layer = Some(sub_machine_layer);
+                                    new_selection.entities.clear();
+                                    sender.do_scene_command(ChangeSelectionCommand::new(
+                                        Selection::Absm(new_selection),
+                                        editor_scene.selection.clone(),
+                                    ));
+                                } else {
+                                    self.state_viewer.set_state(
+                                        node.model_handle,
+                                        absm_node,
+                                        layer_index,
+                                        ui,
+                                    );
+                                    sender.send(Message::ForceSync);
+                                }
                             }
                         }
                     }
@@ -523,7 +541,7 @@ impl AbsmEditor {
                                     [node.model_handle];
 
                                 match model_ref {
-                                    PoseNode::PlayAnimation(_) => {
+                                    PoseNode::PlayAnimation(_) | PoseNode::MotionMatching(_) => {
                                         // No input sockets
                                     }
                                     PoseNode::BlendAnimations(_) => {
@@ -550,6 +568,9 @@ impl AbsmEditor {
                                             BlendSpacePoint::default(),
                                         ));
                                     }
+                                    PoseNode::BlendAdditive(_) => {
+                                        // Fixed set of input sockets.
+                                    }
                                 }
                             }
                         }
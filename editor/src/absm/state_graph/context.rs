@@ -2,8 +2,8 @@ use crate::{
     absm::{
         canvas::{AbsmCanvasMessage, Mode},
         command::{
-            AddStateCommand, DeleteStateCommand, DeleteTransitionCommand,
-            SetMachineEntryStateCommand,
+            AddLayerCommand, AddStateCommand, DeleteStateCommand, DeleteTransitionCommand,
+            SetMachineEntryStateCommand, SetStateSubMachineCommand,
         },
         node::{AbsmNode, AbsmNodeMessage},
         selection::SelectedEntity,
@@ -17,7 +17,7 @@ use crate::{
     },
 };
 use fyrox::{
-    animation::machine::State,
+    animation::machine::{MachineLayer, State},
     core::pool::Handle,
     gui::{
         menu::MenuItemMessage,
@@ -80,6 +80,7 @@ impl CanvasContextMenu {
                         on_enter_actions: Default::default(),
                         on_leave_actions: Default::default(),
                         root: Default::default(),
+                        sub_machine: Default::default(),
                     },
                 ));
             }
@@ -92,6 +93,7 @@ pub struct NodeContextMenu {
     remove: Handle<UiNode>,
     set_as_entry_state: Handle<UiNode>,
     enter_state: Handle<UiNode>,
+    create_sub_machine: Handle<UiNode>,
     pub menu: RcUiNodeHandle,
     pub canvas: Handle<UiNode>,
     placement_target: Handle<UiNode>,
@@ -103,6 +105,7 @@ impl NodeContextMenu {
         let remove;
         let set_as_entry_state;
         let enter_state;
+        let create_sub_machine;
         let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
             .with_content(
                 StackPanelBuilder::new(
@@ -123,6 +126,11 @@ impl NodeContextMenu {
                         .with_child({
                             enter_state = create_menu_item("Enter State", vec![], ctx);
                             enter_state
+                        })
+                        .with_child({
+                            create_sub_machine =
+                                create_menu_item("Create Sub-Machine", vec![], ctx);
+                            create_sub_machine
                         }),
                 )
                 .build(ctx),
@@ -138,6 +146,7 @@ impl NodeContextMenu {
             placement_target: Default::default(),
             set_as_entry_state,
             enter_state,
+            create_sub_machine,
         }
     }
 
@@ -234,6 +243,40 @@ impl NodeContextMenu {
                     self.placement_target,
                     MessageDirection::FromWidget,
                 ));
+            } else if message.destination == self.create_sub_machine {
+                let state_handle = ui
+                    .node(self.placement_target)
+                    .query_component::<AbsmNode<State>>()
+                    .unwrap()
+                    .model_handle;
+
+                let state = &machine.layers()[layer_index].states()[state_handle];
+                if !state.is_sub_machine() {
+                    // Make sure that the name of the new layer is unique.
+                    let mut name = state.name.clone();
+                    let mut counter = 1;
+                    while machine.find_layer_by_name_ref(&name).is_some() {
+                        name = format!("{} {}", state.name, counter);
+                        counter += 1;
+                    }
+
+                    let mut layer = MachineLayer::new();
+                    layer.set_name(&name);
+
+                    sender.do_scene_command(CommandGroup::from(vec![
+                        SceneCommand::new(AddLayerCommand {
+                            absm_node_handle,
+                            layer: Some(layer),
+                        }),
+                        SceneCommand::new(SetStateSubMachineCommand {
+                            node_handle: absm_node_handle,
+                            handle: state_handle,
+                            // The new layer will be added to the end of the list.
+                            value: Some(machine.layers().len()),
+                            layer_index,
+                        }),
+                    ]));
+                }
             }
         } else if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
             if message.destination() == *self.menu {
@@ -2,7 +2,7 @@ use crate::{
     absm::{
         command::{
             blend::{
-                SetBlendAnimationByIndexInputPoseSourceCommand,
+                SetBlendAdditivePoseSourceCommand, SetBlendAnimationByIndexInputPoseSourceCommand,
                 SetBlendAnimationsPoseSourceCommand, SetBlendSpacePoseSourceCommand,
             },
             AddPoseNodeCommand, DeletePoseNodeCommand, SetStateRootPoseCommand,
@@ -24,7 +24,8 @@ use fyrox::{
             blendspace::{BlendSpace, BlendSpacePoint},
             BasePoseNode,
         },
-        BlendAnimations, BlendAnimationsByIndex, MachineLayer, PlayAnimation, PoseNode, State,
+        BlendAdditive, BlendAnimations, BlendAnimationsByIndex, MachineLayer, MotionMatching,
+        PlayAnimation, PoseNode, PoseWeight, State,
     },
     core::{algebra::Vector2, pool::Handle},
     gui::{
@@ -43,6 +44,8 @@ pub struct CanvasContextMenu {
     create_blend_animations: Handle<UiNode>,
     create_blend_by_index: Handle<UiNode>,
     create_blend_space: Handle<UiNode>,
+    create_blend_additive: Handle<UiNode>,
+    create_motion_matching: Handle<UiNode>,
     pub menu: RcUiNodeHandle,
     pub canvas: Handle<UiNode>,
     pub node_context_menu: Option<RcUiNodeHandle>,
@@ -54,6 +57,8 @@ impl CanvasContextMenu {
         let create_blend_animations;
         let create_blend_by_index;
         let create_blend_space;
+        let create_blend_additive;
+        let create_motion_matching;
         let menu = PopupBuilder::new(
             WidgetBuilder::new()
                 .with_enabled(false) // Disabled by default.
@@ -77,6 +82,14 @@ impl CanvasContextMenu {
                     .with_child({
                         create_blend_space = create_menu_item("Blend Space", vec![], ctx);
                         create_blend_space
+                    })
+                    .with_child({
+                        create_blend_additive = create_menu_item("Blend Additive", vec![], ctx);
+                        create_blend_additive
+                    })
+                    .with_child({
+                        create_motion_matching = create_menu_item("Motion Matching", vec![], ctx);
+                        create_motion_matching
                     }),
             )
             .build(ctx),
@@ -89,6 +102,8 @@ impl CanvasContextMenu {
             create_blend_animations,
             create_blend_by_index,
             create_blend_space,
+            create_blend_additive,
+            create_motion_matching,
             menu,
             canvas: Default::default(),
             node_context_menu: Default::default(),
@@ -164,6 +179,24 @@ impl CanvasContextMenu {
                 ]);
 
                 Some(PoseNode::BlendSpace(blend_space))
+            } else if message.destination() == self.create_blend_additive {
+                Some(PoseNode::BlendAdditive(BlendAdditive {
+                    base: BasePoseNode {
+                        position,
+                        parent_state: current_state,
+                    },
+                    base_pose: Default::default(),
+                    additive_pose: Default::default(),
+                    weight: PoseWeight::Constant(1.0),
+                    output_pose: Default::default(),
+                }))
+            } else if message.destination() == self.create_motion_matching {
+                let mut motion_matching = MotionMatching::default();
+
+                motion_matching.position = position;
+                motion_matching.parent_state = current_state;
+
+                Some(PoseNode::MotionMatching(motion_matching))
             } else {
                 None
             };
@@ -332,7 +365,7 @@ impl ConnectionContextMenu {
 
                 let model_handle = dest_node_ref.model_handle;
                 match machine_layer.node(model_handle) {
-                    PoseNode::PlayAnimation(_) => {
+                    PoseNode::PlayAnimation(_) | PoseNode::MotionMatching(_) => {
                         // No connections
                     }
                     PoseNode::BlendAnimations(_) => {
@@ -362,6 +395,15 @@ impl ConnectionContextMenu {
                             value: Default::default(),
                         })
                     }
+                    PoseNode::BlendAdditive(_) => {
+                        sender.do_scene_command(SetBlendAdditivePoseSourceCommand {
+                            node_handle: absm_node_handle,
+                            layer_index,
+                            handle: model_handle,
+                            index,
+                            value: Default::default(),
+                        })
+                    }
                 }
             }
         } else if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
@@ -4,7 +4,7 @@ use crate::{
         canvas::{AbsmCanvasBuilder, AbsmCanvasMessage},
         command::{
             blend::{
-                SetBlendAnimationByIndexInputPoseSourceCommand,
+                SetBlendAdditivePoseSourceCommand, SetBlendAnimationByIndexInputPoseSourceCommand,
                 SetBlendAnimationsPoseSourceCommand, SetBlendSpacePoseSourceCommand,
             },
             MovePoseNodeCommand,
@@ -133,6 +133,11 @@ fn make_pose_node_name(
         PoseNode::BlendSpace(blend_space) => {
             format!("Blend Space: {:?} animations", blend_space.points().len())
         }
+        PoseNode::BlendAdditive(_) => "Blend Additive".to_owned(),
+        PoseNode::MotionMatching(motion_matching) => format!(
+            "Motion Matching: {} animations",
+            motion_matching.animations.len()
+        ),
     }
 }
 
@@ -312,7 +317,7 @@ impl StateViewer {
 
                             let dest_node_ref = &layer.nodes()[dest_node];
                             match dest_node_ref {
-                                PoseNode::PlayAnimation(_) => {}
+                                PoseNode::PlayAnimation(_) | PoseNode::MotionMatching(_) => {}
                                 PoseNode::BlendAnimations(_) => {
                                     sender.do_scene_command(SetBlendAnimationsPoseSourceCommand {
                                         node_handle: absm_node_handle,
@@ -342,6 +347,15 @@ impl StateViewer {
                                         value: source_node,
                                     });
                                 }
+                                PoseNode::BlendAdditive(_) => {
+                                    sender.do_scene_command(SetBlendAdditivePoseSourceCommand {
+                                        node_handle: absm_node_handle,
+                                        layer_index,
+                                        handle: dest_node,
+                                        index: dest_socket_ref.index,
+                                        value: source_node,
+                                    });
+                                }
                             }
                         }
                         _ => (),
@@ -457,6 +471,10 @@ impl StateViewer {
                                         // No input sockets
                                         (0, "Play Animation", false, false)
                                     }
+                                    PoseNode::MotionMatching(_) => {
+                                        // No input sockets
+                                        (0, "Motion Matching", false, false)
+                                    }
                                     PoseNode::BlendAnimations(blend_animations) => (
                                         blend_animations.pose_sources.len(),
                                         "Blend Animations",
@@ -472,6 +490,10 @@ impl StateViewer {
                                     PoseNode::BlendSpace(blend_space) => {
                                         (blend_space.points().len(), "Blend Space", true, true)
                                     }
+                                    PoseNode::BlendAdditive(_) => {
+                                        // Base and additive poses.
+                                        (2, "Blend Additive", false, false)
+                                    }
                                 };
 
                             let node_view = AbsmNodeBuilder::new(
@@ -1,7 +1,10 @@
 use crate::message::MessageSender;
 use crate::{
     absm::{
-        command::{AddLayerCommand, RemoveLayerCommand, SetLayerMaskCommand, SetLayerNameCommand},
+        command::{
+            AddLayerCommand, RemoveLayerCommand, SetLayerBlendModeCommand, SetLayerMaskCommand,
+            SetLayerNameCommand,
+        },
         fetch_selection,
         selection::AbsmSelection,
     },
@@ -15,7 +18,7 @@ use crate::{
     send_sync_message,
 };
 use fyrox::{
-    animation::machine::{LayerMask, MachineLayer},
+    animation::machine::{LayerBlendMode, LayerMask, MachineLayer},
     core::pool::Handle,
     fxhash::FxHashSet,
     gui::{
@@ -47,6 +50,7 @@ pub struct Toolbar {
     pub add_layer: Handle<UiNode>,
     pub remove_layer: Handle<UiNode>,
     pub edit_mask: Handle<UiNode>,
+    pub additive: Handle<UiNode>,
     pub node_selector: Handle<UiNode>,
 }
 
@@ -64,6 +68,7 @@ impl Toolbar {
         let add_layer;
         let remove_layer;
         let edit_mask;
+        let additive;
         let panel = StackPanelBuilder::new(
             WidgetBuilder::new()
                 .with_child({
@@ -146,6 +151,26 @@ impl Toolbar {
                     )
                     .build(ctx);
                     edit_mask
+                })
+                .with_child({
+                    additive = CheckBoxBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(1.0))
+                            .with_tooltip(make_simple_tooltip(
+                                ctx,
+                                "Add the pose of the layer on top of the previous layers \
+                                instead of overriding them. Animations of the layer must be additive.",
+                            )),
+                    )
+                    .with_content(
+                        TextBuilder::new(
+                            WidgetBuilder::new().with_vertical_alignment(VerticalAlignment::Center),
+                        )
+                        .with_text("Additive")
+                        .build(ctx),
+                    )
+                    .build(ctx);
+                    additive
                 }),
         )
         .with_orientation(Orientation::Horizontal)
@@ -159,6 +184,7 @@ impl Toolbar {
             add_layer,
             remove_layer,
             edit_mask,
+            additive,
             node_selector: Handle::NONE,
         }
     }
@@ -182,6 +208,20 @@ impl Toolbar {
                 } else {
                     ToolbarAction::LeavePreviewMode
                 };
+            } else if message.destination() == self.additive
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(layer_index) = selection.layer {
+                    sender.do_scene_command(SetLayerBlendModeCommand {
+                        absm_node_handle: selection.absm_node_handle,
+                        layer_index,
+                        blend_mode: if *value {
+                            LayerBlendMode::Additive
+                        } else {
+                            LayerBlendMode::Override
+                        },
+                    });
+                }
             }
         } else if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
             if message.destination() == self.layers
@@ -383,6 +423,15 @@ impl Toolbar {
                         layer.name().to_string(),
                     ),
                 );
+
+                send_sync_message(
+                    ui,
+                    CheckBoxMessage::checked(
+                        self.additive,
+                        MessageDirection::ToWidget,
+                        Some(layer.blend_mode() == LayerBlendMode::Additive),
+                    ),
+                );
             }
         }
     }
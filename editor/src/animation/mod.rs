@@ -33,9 +33,9 @@ use fyrox::{
 };
 
 pub mod command;
-mod ruler;
+pub mod ruler;
 pub mod selection;
-mod thumb;
+pub mod thumb;
 mod toolbar;
 mod track;
 
@@ -266,12 +266,11 @@ impl AnimationEditor {
                                 sender.do_scene_command(AddAnimationSignal {
                                     animation_player_handle: selection.animation_player,
                                     animation_handle: selection.animation,
-                                    signal: Some(AnimationSignal {
-                                        id: Uuid::new_v4(),
-                                        name: "Unnamed".to_string(),
-                                        time: *time,
-                                        enabled: true,
-                                    }),
+                                    signal: Some(AnimationSignal::new(
+                                        Uuid::new_v4(),
+                                        "Unnamed",
+                                        *time,
+                                    )),
                                 });
                             }
                             RulerMessage::RemoveSignal(id) => {
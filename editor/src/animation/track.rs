@@ -26,7 +26,7 @@ use fyrox::{
     animation::{
         container::{TrackDataContainer, TrackValueKind},
         track::Track,
-        value::{ValueBinding, ValueType},
+        value::{is_discrete_type, ValueBinding, ValueType},
         Animation,
     },
     core::{
@@ -211,6 +211,10 @@ fn type_id_to_supported_type(property_type: TypeId) -> Option<(TrackValueKind, V
         Some((TrackValueKind::UnitQuaternion, ValueType::UnitQuaternionF32))
     } else if property_type == TypeId::of::<UnitQuaternion<f64>>() {
         Some((TrackValueKind::UnitQuaternion, ValueType::UnitQuaternionF64))
+    } else if property_type == TypeId::of::<Color>() {
+        Some((TrackValueKind::Vector4, ValueType::Color))
+    } else if is_discrete_type(property_type) {
+        Some((TrackValueKind::Discrete, ValueType::Discrete))
     } else {
         None
     }
@@ -16,19 +16,23 @@ use crate::{
 };
 use fyrox::{
     animation::{
+        compression::AnimationCompressionSettings,
         machine::{
+            ik::{IkChain, IkSolver, IkSolverKind, IkTarget, LookAtIk, TwoBoneIk},
             node::{
                 blendspace::{BlendSpace, BlendSpacePoint},
                 BasePoseNode,
             },
             state::{AnimationHandleWrapper, StateAction, StateActionWrapper},
             transition::{AndNode, LogicNode, NotNode, OrNode, XorNode},
-            BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Machine,
-            PlayAnimation, PoseNode, PoseWeight, State,
+            BlendAdditive, BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput,
+            LayerBlendMode, Machine, MotionMatching, MotionMatchingWeights, PlayAnimation,
+            PoseNode, PoseWeight, State, TransitionInterruption,
         },
-        AnimationContainer,
+        Animation, AnimationContainer, AnimationSignalAction, AnimationSignalPayload,
     },
     core::{
+        curve::Curve,
         futures::executor::block_on,
         parking_lot::Mutex,
         pool::{ErasedHandle, Handle},
@@ -45,13 +49,20 @@ use fyrox::{
     renderer::framework::state::PolygonFillMode,
     resource::{
         curve::{CurveResource, CurveResourceState},
-        model::{MaterialSearchOptions, Model, ModelResource},
+        model::{AdditiveAnimationOptions, MaterialSearchOptions, Model, ModelResource},
         texture::{
             CompressionOptions, MipFilter, TextureMagnificationFilter, TextureMinificationFilter,
             TextureResource, TextureWrapMode,
         },
     },
     scene::{
+        animation::{
+            absm::RootMotionTarget,
+            sequencer::{
+                AnimationClip, AudioCue, CameraCut, SequencerEvent, SequencerTrack,
+                SequencerTrackKind,
+            },
+        },
         base::{
             Base, LevelOfDetail, LodControlledObject, LodGroup, Mobility, Property, PropertyValue,
         },
@@ -96,7 +107,9 @@ use fyrox::{
         },
         terrain::{Chunk, Layer},
         transform::Transform,
+        vehicle::{Engine, Gearbox, Wheel},
     },
+    utils::navmesh::bake::NavmeshBakeSettings,
 };
 use std::rc::Rc;
 
@@ -151,6 +164,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_vec_collection::<LevelOfDetail>();
     container.register_inheritable_vec_collection::<ErasedHandle>();
     container.register_inheritable_vec_collection::<Property>();
+    container.register_inheritable_vec_collection::<String>();
     container.register_inheritable_vec_collection::<LodControlledObject>();
     container.register_inheritable_vec_collection::<GeometrySource>();
 
@@ -217,6 +231,17 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<dim2::joint::PrismaticJoint>();
 
     container.register_inheritable_inspectable::<Base>();
+
+    container.insert(InspectablePropertyEditorDefinition::<Curve>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<f32>::new());
+    container.register_inheritable_inspectable::<Wheel>();
+    container.register_inheritable_vec_collection::<Wheel>();
+    container.register_inheritable_inspectable::<Engine>();
+    container.register_inheritable_inspectable::<Gearbox>();
+    container.insert(InspectablePropertyEditorDefinition::<NavmeshBakeSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<
+        AnimationCompressionSettings,
+    >::new());
     container.register_inheritable_inspectable::<BaseLight>();
 
     container.insert(EnumPropertyEditorDefinition::<Effect>::new());
@@ -269,6 +294,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_enum::<Exposure, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
+    container.register_inheritable_enum::<AdditiveAnimationOptions, _>();
     container.register_inheritable_enum::<DistanceModel, _>();
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
@@ -307,11 +333,26 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(InspectablePropertyEditorDefinition::<BlendPose>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<BlendPose>::new());
     container.insert(EnumPropertyEditorDefinition::<PoseWeight>::new());
+    container.insert(InspectablePropertyEditorDefinition::<IkSolver>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<IkSolver>::new());
+    container.insert(EnumPropertyEditorDefinition::<IkSolverKind>::new());
+    container.insert(EnumPropertyEditorDefinition::<IkTarget>::new());
+    container.insert(InspectablePropertyEditorDefinition::<TwoBoneIk>::new());
+    container.insert(InspectablePropertyEditorDefinition::<IkChain>::new());
+    container.insert(InspectablePropertyEditorDefinition::<LookAtIk>::new());
     container.insert(EnumPropertyEditorDefinition::<StateAction>::new());
     container.insert(InspectablePropertyEditorDefinition::<StateActionWrapper>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<StateActionWrapper>::new());
     container.insert(InspectablePropertyEditorDefinition::<BlendAnimationsByIndex>::new());
     container.insert(InspectablePropertyEditorDefinition::<BlendAnimations>::new());
+    container.insert(InspectablePropertyEditorDefinition::<BlendAdditive>::new());
+    container.insert(InspectablePropertyEditorDefinition::<MotionMatching>::new());
+    container.insert(InspectablePropertyEditorDefinition::<MotionMatchingWeights>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<Handle<Animation>>::new());
+    container.insert(EnumPropertyEditorDefinition::<LayerBlendMode>::new());
+    container.insert(EnumPropertyEditorDefinition::<TransitionInterruption>::new());
+    container.insert(EnumPropertyEditorDefinition::<AnimationSignalPayload>::new());
+    container.insert(EnumPropertyEditorDefinition::<AnimationSignalAction>::new());
     container.insert(InspectablePropertyEditorDefinition::<BlendSpace>::new());
     container.insert(InspectablePropertyEditorDefinition::<PlayAnimation>::new());
     container.insert(InspectablePropertyEditorDefinition::<AnimationHandleWrapper>::new());
@@ -329,6 +370,21 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.insert(MachinePropertyEditorDefinition);
     container.insert(InheritablePropertyEditorDefinition::<Machine>::new());
+    container.insert(InspectablePropertyEditorDefinition::<RootMotionTarget>::new());
+    container.insert(InheritablePropertyEditorDefinition::<RootMotionTarget>::new());
+
+    container.insert(InspectablePropertyEditorDefinition::<SequencerTrack>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<SequencerTrack>::new());
+    container.insert(InheritablePropertyEditorDefinition::<Vec<SequencerTrack>>::new());
+    container.insert(EnumPropertyEditorDefinition::<SequencerTrackKind>::new());
+    container.insert(InspectablePropertyEditorDefinition::<CameraCut>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<CameraCut>::new());
+    container.insert(InspectablePropertyEditorDefinition::<AudioCue>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<AudioCue>::new());
+    container.insert(InspectablePropertyEditorDefinition::<SequencerEvent>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<SequencerEvent>::new());
+    container.insert(InspectablePropertyEditorDefinition::<AnimationClip>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<AnimationClip>::new());
 
     container.insert(EnumPropertyEditorDefinition::<LogicNode>::new());
     container.insert(InspectablePropertyEditorDefinition::<AndNode>::new());
@@ -12,7 +12,7 @@ use crate::{
         commands::{
             navmesh::{
                 AddNavmeshEdgeCommand, ConnectNavmeshEdgesCommand, DeleteNavmeshVertexCommand,
-                MoveNavmeshVertexCommand,
+                MoveNavmeshVertexCommand, SetNavmeshCommand,
             },
             ChangeSelectionCommand, CommandGroup, SceneCommand,
         },
@@ -26,6 +26,7 @@ use fyrox::{
     core::{
         algebra::{Vector2, Vector3},
         color::Color,
+        log::Log,
         math::{ray::CylinderKind, TriangleEdge},
         pool::Handle,
         scope_profile,
@@ -35,21 +36,46 @@ use fyrox::{
         button::{ButtonBuilder, ButtonMessage},
         grid::{Column, GridBuilder, Row},
         message::{KeyCode, MessageDirection, UiMessage},
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
         stack_panel::StackPanelBuilder,
+        utils::make_simple_tooltip,
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, Orientation, Thickness, UiNode, UserInterface,
     },
-    scene::{camera::Camera, navmesh::NavigationalMesh, node::Node},
-    utils::astar::PathVertex,
+    scene::{camera::Camera, navmesh::NavigationalMesh, node::Node, Scene},
+    utils::{
+        astar::PathVertex,
+        lightmap::CancellationToken,
+        navmesh::{
+            bake::{bake_triangles, collect_geometry, NavmeshBakeError, NavmeshBakeProgress},
+            Navmesh,
+        },
+    },
+};
+use std::{
+    collections::HashMap,
+    sync::mpsc::{channel, Receiver, TryRecvError},
 };
-use std::collections::HashMap;
 
 pub mod selection;
 
+// Navmesh baking that runs on the task pool.
+struct BakeTask {
+    scene: Handle<Scene>,
+    navmesh_node: Handle<Node>,
+    cancellation_token: CancellationToken,
+    progress: NavmeshBakeProgress,
+    receiver: Receiver<Result<Navmesh, NavmeshBakeError>>,
+}
+
 pub struct NavmeshPanel {
     pub window: Handle<UiNode>,
     connect_edges: Handle<UiNode>,
+    bake: Handle<UiNode>,
+    cancel_bake: Handle<UiNode>,
+    bake_progress: Handle<UiNode>,
+    bake_task: Option<BakeTask>,
     sender: MessageSender,
 }
 
@@ -69,20 +95,59 @@ fn fetch_selection(editor_selection: &Selection) -> Option<NavmeshSelection> {
 impl NavmeshPanel {
     pub fn new(ctx: &mut BuildContext, sender: MessageSender) -> Self {
         let connect_edges;
+        let bake;
+        let cancel_bake;
+        let bake_progress;
         let window = WindowBuilder::new(WidgetBuilder::new())
             .open(false)
             .with_title(WindowTitle::text("Navmesh"))
             .with_content(
                 GridBuilder::new(
                     WidgetBuilder::new().with_child(
-                        StackPanelBuilder::new(WidgetBuilder::new().with_child({
-                            connect_edges = ButtonBuilder::new(
-                                WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
-                            )
-                            .with_text("Connect Edges")
-                            .build(ctx);
-                            connect_edges
-                        }))
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child({
+                                    connect_edges = ButtonBuilder::new(
+                                        WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Connect Edges")
+                                    .build(ctx);
+                                    connect_edges
+                                })
+                                .with_child({
+                                    bake = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_margin(Thickness::uniform(1.0))
+                                            .with_tooltip(make_simple_tooltip(
+                                                ctx,
+                                                "Bakes navigational mesh from scene geometry \
+                                                using bake settings of the node.",
+                                            )),
+                                    )
+                                    .with_text("Bake")
+                                    .build(ctx);
+                                    bake
+                                })
+                                .with_child({
+                                    cancel_bake = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_enabled(false)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Cancel")
+                                    .build(ctx);
+                                    cancel_bake
+                                })
+                                .with_child({
+                                    bake_progress = ProgressBarBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_width(100.0)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .build(ctx);
+                                    bake_progress
+                                }),
+                        )
                         .with_orientation(Orientation::Horizontal)
                         .build(ctx),
                     ),
@@ -97,10 +162,151 @@ impl NavmeshPanel {
             window,
             sender,
             connect_edges,
+            bake,
+            cancel_bake,
+            bake_progress,
+            bake_task: None,
         }
     }
 
-    pub fn handle_message(&mut self, message: &UiMessage, editor_scene: &EditorScene) {
+    fn set_baking(&self, baking: bool, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::enabled(
+            self.bake,
+            MessageDirection::ToWidget,
+            !baking,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.cancel_bake,
+            MessageDirection::ToWidget,
+            baking,
+        ));
+        ui.send_message(ProgressBarMessage::progress(
+            self.bake_progress,
+            MessageDirection::ToWidget,
+            0.0,
+        ));
+    }
+
+    // Copies the geometry of the scene and bakes the navmesh on the task pool, so the editor won't
+    // freeze while baking.
+    fn start_baking(
+        &mut self,
+        editor_scene: &EditorScene,
+        navmesh_node: Handle<Node>,
+        engine: &Engine,
+    ) {
+        let graph = &engine.scenes[editor_scene.scene].graph;
+        let settings = match graph.try_get_of_type::<NavigationalMesh>(navmesh_node) {
+            Some(navmesh) => navmesh.bake_settings().clone(),
+            None => return,
+        };
+
+        let triangles = match collect_geometry(graph, &settings) {
+            Ok(triangles) => triangles,
+            Err(e) => {
+                Log::err(format!("Failed to bake navmesh. Reason: {e}"));
+                return;
+            }
+        };
+
+        let (sender, receiver) = channel();
+        let cancellation_token = CancellationToken::new();
+        let progress = NavmeshBakeProgress::new();
+
+        let task_cancellation_token = cancellation_token.clone();
+        let task_progress = progress.clone();
+        engine.task_pool().inner().spawn_task(async move {
+            let result = bake_triangles(
+                &triangles,
+                &settings,
+                task_cancellation_token,
+                task_progress,
+            );
+            // The receiver could be already destroyed, there's nothing to do in this case.
+            let _ = sender.send(result);
+        });
+
+        self.bake_task = Some(BakeTask {
+            scene: editor_scene.scene,
+            navmesh_node,
+            cancellation_token,
+            progress,
+            receiver,
+        });
+
+        self.set_baking(true, &engine.user_interface);
+    }
+
+    pub fn update(&mut self, editor_scene: &EditorScene, engine: &Engine) {
+        let task = match self.bake_task.as_ref() {
+            Some(task) => task,
+            None => return,
+        };
+
+        match task.receiver.try_recv() {
+            Ok(result) => self.apply_bake_result(result, editor_scene, engine),
+            Err(TryRecvError::Empty) => {
+                engine
+                    .user_interface
+                    .send_message(ProgressBarMessage::progress(
+                        self.bake_progress,
+                        MessageDirection::ToWidget,
+                        task.progress.progress_percent() as f32 / 100.0,
+                    ));
+                return;
+            }
+            Err(TryRecvError::Disconnected) => {
+                Log::err("Navmesh baking task was terminated unexpectedly.")
+            }
+        }
+
+        self.bake_task = None;
+        self.set_baking(false, &engine.user_interface);
+    }
+
+    fn apply_bake_result(
+        &self,
+        result: Result<Navmesh, NavmeshBakeError>,
+        editor_scene: &EditorScene,
+        engine: &Engine,
+    ) {
+        let task = match self.bake_task.as_ref() {
+            Some(task) => task,
+            None => return,
+        };
+
+        match result {
+            Ok(navmesh) => {
+                // The scene could be switched or the node could be deleted while baking.
+                let is_node_alive = engine
+                    .scenes
+                    .try_get(task.scene)
+                    .and_then(|scene| {
+                        scene
+                            .graph
+                            .try_get_of_type::<NavigationalMesh>(task.navmesh_node)
+                    })
+                    .is_some();
+
+                if task.scene == editor_scene.scene && is_node_alive {
+                    self.sender
+                        .do_scene_command(SetNavmeshCommand::new(task.navmesh_node, navmesh));
+                } else {
+                    Log::warn(
+                        "Baked navmesh was discarded, because its scene or node has changed.",
+                    );
+                }
+            }
+            Err(e) => Log::err(format!("Failed to bake navmesh. Reason: {e}")),
+        }
+    }
+
+    pub fn handle_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &Engine,
+    ) {
         scope_profile!();
 
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
@@ -124,6 +330,16 @@ impl NavmeshPanel {
                             [vertices[0], vertices[1]],
                         ));
                 }
+            } else if message.destination() == self.bake {
+                if self.bake_task.is_none() {
+                    if let Some(selection) = fetch_selection(&editor_scene.selection) {
+                        self.start_baking(editor_scene, selection.navmesh_node(), engine);
+                    }
+                }
+            } else if message.destination() == self.cancel_bake {
+                if let Some(task) = self.bake_task.as_ref() {
+                    task.cancellation_token.cancel();
+                }
             }
         }
     }
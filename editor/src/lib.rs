@@ -30,8 +30,10 @@ mod message;
 mod overlay;
 mod particle;
 mod preview;
+mod profiler;
 mod scene;
 mod scene_viewer;
+mod sequencer;
 mod settings;
 mod utils;
 mod world;
@@ -63,6 +65,7 @@ use crate::{
     message::MessageSender,
     overlay::OverlayRenderPass,
     particle::ParticleSystemPreviewControlPanel,
+    profiler::FrameProfilerPanel,
     scene::{
         commands::{
             graph::AddModelCommand, make_delete_selection_command, mesh::SetMeshTextureCommand,
@@ -72,6 +75,7 @@ use crate::{
         EditorScene, Selection,
     },
     scene_viewer::SceneViewer,
+    sequencer::SequencerEditor,
     settings::{camera::SceneCameraSettings, Settings},
     utils::{doc::DocWindow, path_fixer::PathFixer},
     world::{graph::selection::GraphSelection, WorldViewer},
@@ -654,11 +658,13 @@ pub struct Editor {
     build_profile: BuildProfile,
     scene_settings: SceneSettingsWindow,
     animation_editor: AnimationEditor,
+    sequencer_editor: SequencerEditor,
     particle_system_control_panel: ParticleSystemPreviewControlPanel,
     camera_control_panel: CameraPreviewControlPanel,
     overlay_pass: Rc<RefCell<OverlayRenderPass>>,
     audio_preview_panel: AudioPreviewPanel,
     doc_window: DocWindow,
+    frame_profiler: FrameProfilerPanel,
     engine: Engine,
 }
 
@@ -789,11 +795,13 @@ impl Editor {
         let log = LogPanel::new(ctx, log_message_receiver);
         let inspector = Inspector::new(ctx, message_sender.clone());
         let animation_editor = AnimationEditor::new(ctx);
+        let sequencer_editor = SequencerEditor::new(ctx);
         let absm_editor = AbsmEditor::new(ctx, message_sender.clone());
         let particle_system_control_panel = ParticleSystemPreviewControlPanel::new(ctx);
         let camera_control_panel = CameraPreviewControlPanel::new(ctx);
         let audio_preview_panel = AudioPreviewPanel::new(ctx);
         let doc_window = DocWindow::new(ctx);
+        let frame_profiler = FrameProfilerPanel::new(ctx);
 
         let root_grid = GridBuilder::new(
             WidgetBuilder::new()
@@ -882,12 +890,14 @@ impl Editor {
                     }))
                     .with_floating_windows(vec![
                         animation_editor.window,
+                        sequencer_editor.window,
                         absm_editor.window,
                         particle_system_control_panel.window,
                         camera_control_panel.window,
                         audio_preview_panel.window,
                         navmesh_panel.window,
                         doc_window.window,
+                        frame_profiler.window,
                     ])
                     .build(ctx),
                 ),
@@ -933,6 +943,7 @@ impl Editor {
 
         let editor = Self {
             animation_editor,
+            sequencer_editor,
             engine,
             navmesh_panel,
             scene_viewer,
@@ -972,6 +983,7 @@ impl Editor {
             overlay_pass,
             audio_preview_panel,
             doc_window,
+            frame_profiler,
         };
 
         if let Some(data) = startup_data {
@@ -1202,6 +1214,7 @@ impl Editor {
                     asset_window: self.asset_browser.window,
                     light_panel: self.light_panel.window,
                     log_panel: self.log.window,
+                    frame_profiler: self.frame_profiler.window,
                     navmesh_panel: self.navmesh_panel.window,
                     audio_panel: self.audio_panel.window,
                     configurator_window: self.configurator.window,
@@ -1211,6 +1224,7 @@ impl Editor {
                     command_stack_panel: self.command_stack_viewer.window,
                     scene_settings: &self.scene_settings,
                     animation_editor: &self.animation_editor,
+                    sequencer_editor: &self.sequencer_editor,
                 },
                 settings: &mut self.settings,
             },
@@ -1219,6 +1233,7 @@ impl Editor {
         self.build_window
             .handle_ui_message(message, &self.message_sender, &engine.user_interface);
         self.log.handle_ui_message(message, engine);
+        self.frame_profiler.handle_ui_message(message, engine);
         self.asset_browser
             .handle_ui_message(message, engine, self.message_sender.clone());
         self.command_stack_viewer.handle_ui_message(message);
@@ -1244,6 +1259,12 @@ impl Editor {
             engine,
             &self.message_sender,
         );
+        self.sequencer_editor.handle_ui_message(
+            message,
+            current_scene_entry.as_mut().map(|e| &mut e.editor_scene),
+            engine,
+            &self.message_sender,
+        );
 
         if let Some(current_scene_entry) = current_scene_entry {
             let editor_scene = &mut current_scene_entry.editor_scene;
@@ -1262,7 +1283,8 @@ impl Editor {
             self.scene_settings
                 .handle_ui_message(message, &self.message_sender);
 
-            self.navmesh_panel.handle_message(message, editor_scene);
+            self.navmesh_panel
+                .handle_message(message, editor_scene, engine);
 
             self.inspector
                 .handle_ui_message(message, editor_scene, engine, &self.message_sender);
@@ -1463,6 +1485,7 @@ impl Editor {
         if let Some(current_scene_entry) = self.scenes.current_scene_entry_mut() {
             let editor_scene = &mut current_scene_entry.editor_scene;
             self.animation_editor.sync_to_model(editor_scene, engine);
+            self.sequencer_editor.sync_to_model(editor_scene, engine);
             self.absm_editor.sync_to_model(editor_scene, engine);
             self.scene_settings.sync_to_model(editor_scene, engine);
             self.inspector.sync_to_model(editor_scene, engine);
@@ -1612,6 +1635,8 @@ impl Editor {
                 .leave_preview_mode(editor_scene, engine);
             self.animation_editor
                 .try_leave_preview_mode(editor_scene, engine);
+            self.sequencer_editor
+                .try_leave_preview_mode(editor_scene, engine);
             self.absm_editor
                 .try_leave_preview_mode(editor_scene, engine);
         }
@@ -1826,6 +1851,9 @@ impl Editor {
     fn update(&mut self, dt: f32) {
         scope_profile!();
 
+        #[cfg(feature = "hot_reload")]
+        self.reload_dynamic_plugins();
+
         match self.mode {
             Mode::Play {
                 ref mut process,
@@ -1873,12 +1901,15 @@ impl Editor {
         }
 
         self.log.update(&mut self.engine);
+        self.frame_profiler.update(dt, &mut self.engine);
         self.material_editor.update(&mut self.engine);
         self.asset_browser.update(&mut self.engine);
 
         if let Some(scene) = self.scenes.current_editor_scene_ref() {
             self.animation_editor.update(scene, &self.engine);
+            self.sequencer_editor.update(scene, &self.engine);
             self.audio_preview_panel.update(scene, &self.engine);
+            self.navmesh_panel.update(scene, &self.engine);
         }
 
         self.overlay_pass.borrow_mut().pictogram_size = self.settings.debugging.pictogram_size;
@@ -1927,6 +1958,8 @@ impl Editor {
                     );
                     self.animation_editor
                         .handle_message(&message, editor_scene, &mut self.engine);
+                    self.sequencer_editor
+                        .handle_message(&message, editor_scene, &mut self.engine);
                     self.absm_editor
                         .handle_message(&message, editor_scene, &mut self.engine);
                 }
@@ -2165,6 +2198,32 @@ impl Editor {
         self.engine.add_plugin_constructor(plugin)
     }
 
+    /// Loads a game plugin from a dynamic library. Scripts of the plugin will be reloaded with
+    /// state preservation every time when the library changes.
+    #[cfg(feature = "hot_reload")]
+    pub fn add_dynamic_game_plugin<P>(
+        &mut self,
+        path: P,
+    ) -> Result<(), fyrox::plugin::dynamic::DynamicPluginError>
+    where
+        P: AsRef<Path>,
+    {
+        self.engine.add_dynamic_plugin(path)
+    }
+
+    #[cfg(feature = "hot_reload")]
+    fn reload_dynamic_plugins(&mut self) {
+        if self.engine.reload_dynamic_plugins() {
+            // Re-create property editors, the inspector could hold the editors for the scripts from
+            // the old library.
+            if let Some(editor_scene) = self.scenes.current_editor_scene_ref() {
+                self.message_sender.send(Message::SelectionChanged {
+                    old_selection: editor_scene.selection.clone(),
+                });
+            }
+        }
+    }
+
     pub fn run(mut self, event_loop: EventLoop<()>) -> ! {
         event_loop.run(move |event, _, control_flow| match event {
             Event::MainEventsCleared => {
@@ -4,7 +4,10 @@ use fyrox::{
     core::pool::Handle,
     gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode},
     scene::{
-        animation::{absm::AnimationBlendingStateMachineBuilder, AnimationPlayerBuilder},
+        animation::{
+            absm::AnimationBlendingStateMachineBuilder, sequencer::SequencerBuilder,
+            AnimationPlayerBuilder,
+        },
         base::BaseBuilder,
         node::Node,
     },
@@ -14,12 +17,14 @@ pub struct AnimationMenu {
     pub menu: Handle<UiNode>,
     create_animation_player: Handle<UiNode>,
     create_absm: Handle<UiNode>,
+    create_sequencer: Handle<UiNode>,
 }
 
 impl AnimationMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let create_animation_player;
         let create_absm;
+        let create_sequencer;
 
         let menu = create_menu_item(
             "Animation",
@@ -32,6 +37,10 @@ impl AnimationMenu {
                     create_absm = create_menu_item("Animation Blending State Machine", vec![], ctx);
                     create_absm
                 },
+                {
+                    create_sequencer = create_menu_item("Sequencer", vec![], ctx);
+                    create_sequencer
+                },
             ],
             ctx,
         );
@@ -40,6 +49,7 @@ impl AnimationMenu {
             menu,
             create_animation_player,
             create_absm,
+            create_sequencer,
         }
     }
 
@@ -64,6 +74,10 @@ impl AnimationMenu {
                 .with_machine(machine)
                 .build_node();
                 Some(node)
+            } else if message.destination() == self.create_sequencer {
+                let node =
+                    SequencerBuilder::new(BaseBuilder::new().with_name("Sequencer")).build_node();
+                Some(node)
             } else {
                 None
             }
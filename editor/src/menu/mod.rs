@@ -7,6 +7,7 @@ use crate::{
     message::MessageSender,
     scene::EditorScene,
     send_sync_message,
+    sequencer::SequencerEditor,
     settings::Settings,
     AbsmEditor, CurveEditorWindow, Engine, Mode, SceneSettingsWindow,
 };
@@ -45,6 +46,7 @@ pub struct Menu {
 pub struct Panels<'b> {
     pub light_panel: Handle<UiNode>,
     pub log_panel: Handle<UiNode>,
+    pub frame_profiler: Handle<UiNode>,
     pub navmesh_panel: Handle<UiNode>,
     pub audio_panel: Handle<UiNode>,
     pub command_stack_panel: Handle<UiNode>,
@@ -57,6 +59,7 @@ pub struct Panels<'b> {
     pub absm_editor: &'b AbsmEditor,
     pub scene_settings: &'b SceneSettingsWindow,
     pub animation_editor: &'b AnimationEditor,
+    pub sequencer_editor: &'b SequencerEditor,
 }
 
 pub struct MenuContext<'a, 'b> {
@@ -15,6 +15,7 @@ pub struct UtilsMenu {
     open_curve_editor: Handle<UiNode>,
     absm_editor: Handle<UiNode>,
     animation_editor: Handle<UiNode>,
+    sequencer_editor: Handle<UiNode>,
 }
 
 impl UtilsMenu {
@@ -23,6 +24,7 @@ impl UtilsMenu {
         let open_curve_editor;
         let absm_editor;
         let animation_editor;
+        let sequencer_editor;
         let menu = create_root_menu_item(
             "Utils",
             vec![
@@ -42,6 +44,10 @@ impl UtilsMenu {
                     animation_editor = create_menu_item("Animation Editor", vec![], ctx);
                     animation_editor
                 },
+                {
+                    sequencer_editor = create_menu_item("Sequencer Editor", vec![], ctx);
+                    sequencer_editor
+                },
             ],
             ctx,
         );
@@ -52,6 +58,7 @@ impl UtilsMenu {
             open_curve_editor,
             absm_editor,
             animation_editor,
+            sequencer_editor,
         }
     }
 
@@ -69,6 +76,8 @@ impl UtilsMenu {
                 panels.absm_editor.open(ui);
             } else if message.destination() == self.animation_editor {
                 panels.animation_editor.open(ui);
+            } else if message.destination() == self.sequencer_editor {
+                panels.sequencer_editor.open(ui);
             }
         }
     }
@@ -0,0 +1,485 @@
+//! Frame profiler panel shows scopes captured by the built-in profiler (see
+//! [`fyrox::core::profiler`]) on a timeline, every thread is shown separately.
+
+use crate::{utils::create_file_selector, Brush, Color, Engine};
+use fyrox::{
+    core::{
+        algebra::Vector2,
+        log::Log,
+        pool::Handle,
+        profiler::{self, FrameCapture},
+        scope_profile,
+    },
+    gui::{
+        border::BorderBuilder,
+        button::{ButtonBuilder, ButtonMessage},
+        canvas::CanvasBuilder,
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        file_browser::{FileBrowserMode, FileSelectorMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        scroll_viewer::{ScrollViewerBuilder, ScrollViewerMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        utils::make_simple_tooltip,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Orientation, Thickness, UiNode, VerticalAlignment,
+    },
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const ROW_HEIGHT: f32 = 18.0;
+const THREAD_HEADER_HEIGHT: f32 = 20.0;
+const THREAD_SPACING: f32 = 6.0;
+// How often the timeline shows the latest frame, in seconds.
+const REFRESH_INTERVAL: f32 = 0.5;
+
+const PALETTE: [Color; 8] = [
+    Color::opaque(86, 130, 168),
+    Color::opaque(168, 110, 86),
+    Color::opaque(98, 150, 96),
+    Color::opaque(150, 96, 150),
+    Color::opaque(160, 150, 80),
+    Color::opaque(80, 150, 150),
+    Color::opaque(170, 90, 110),
+    Color::opaque(120, 120, 170),
+];
+
+fn scope_color(name: &str) -> Color {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    PALETTE[(hasher.finish() % PALETTE.len() as u64) as usize]
+}
+
+fn millis(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+fn make_check_box(ctx: &mut BuildContext, text: &str, tooltip: &str) -> Handle<UiNode> {
+    CheckBoxBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_content(
+        TextBuilder::new(
+            WidgetBuilder::new()
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_margin(Thickness::left(2.0)),
+        )
+        .with_text(text)
+        .build(ctx),
+    )
+    .checked(Some(false))
+    .build(ctx)
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str, tooltip: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_width(70.0)
+            .with_margin(Thickness::uniform(1.0))
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+// Creates a canvas with every scope of the frame. Scopes are placed on rows by their depth.
+fn build_timeline(
+    frame: &FrameCapture,
+    pixels_per_ms: f32,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    let mut children = Vec::new();
+    let mut width = millis(frame.duration) * pixels_per_ms;
+    let mut y = 0.0;
+
+    for thread in frame.threads.iter() {
+        children.push(
+            TextBuilder::new(WidgetBuilder::new().with_desired_position(Vector2::new(2.0, y)))
+                .with_text(format!(
+                    "{} ({} scopes)",
+                    thread.thread_name,
+                    thread.scopes.len()
+                ))
+                .build(ctx),
+        );
+        y += THREAD_HEADER_HEIGHT;
+
+        let mut max_depth = 0;
+        for scope in thread.scopes.iter() {
+            max_depth = max_depth.max(scope.depth);
+
+            // A scope could be started in one of the previous frames.
+            let begin = scope.start.max(frame.start);
+            let end = scope.start + scope.duration;
+            let x = millis(begin.saturating_sub(frame.start)) * pixels_per_ms;
+            let scope_width = millis(end.saturating_sub(begin)) * pixels_per_ms;
+            width = width.max(x + scope_width);
+
+            // There's no need to create widgets for scopes that can't be seen anyway.
+            if scope_width < 1.0 {
+                continue;
+            }
+
+            let tooltip = format!(
+                "{}\nLine: {}\nTime: {:.3} ms",
+                scope.name(),
+                scope.line,
+                millis(scope.duration)
+            );
+            children.push(
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_desired_position(Vector2::new(x, y + scope.depth as f32 * ROW_HEIGHT))
+                        .with_width(scope_width)
+                        .with_height(ROW_HEIGHT - 1.0)
+                        .with_background(Brush::Solid(scope_color(scope.name())))
+                        .with_foreground(Brush::Solid(Color::opaque(30, 30, 30)))
+                        .with_tooltip(make_simple_tooltip(ctx, &tooltip))
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::left(2.0))
+                                    .with_vertical_alignment(VerticalAlignment::Center),
+                            )
+                            .with_text(scope.name().rsplit("::").next().unwrap_or_default())
+                            .build(ctx),
+                        ),
+                )
+                .with_stroke_thickness(Thickness::uniform(1.0))
+                .build(ctx),
+            );
+        }
+
+        if !thread.scopes.is_empty() {
+            y += (max_depth + 1) as f32 * ROW_HEIGHT;
+        }
+        y += THREAD_SPACING;
+    }
+
+    CanvasBuilder::new(
+        WidgetBuilder::new()
+            .with_width(width)
+            .with_height(y)
+            .with_children(children),
+    )
+    .build(ctx)
+}
+
+fn save_trace(path: &Path) {
+    match profiler::save_chrome_trace(path, &profiler::captured_frames()) {
+        Ok(_) => Log::info(format!(
+            "Profiling results were saved to {}",
+            path.display()
+        )),
+        Err(e) => Log::err(format!("Unable to save profiling results. Reason: {e}")),
+    }
+}
+
+pub struct FrameProfilerPanel {
+    pub window: Handle<UiNode>,
+    record: Handle<UiNode>,
+    pause: Handle<UiNode>,
+    zoom: Handle<UiNode>,
+    export: Handle<UiNode>,
+    save_file_selector: Handle<UiNode>,
+    clear: Handle<UiNode>,
+    frame_selector: Handle<UiNode>,
+    frame_info: Handle<UiNode>,
+    timeline: Handle<UiNode>,
+    pixels_per_ms: f32,
+    // A snapshot of captured frames, that is used when the panel is paused.
+    paused_frames: Option<Vec<FrameCapture>>,
+    selected_frame: usize,
+    shown_frame: Option<u64>,
+    refresh_timer: f32,
+}
+
+impl FrameProfilerPanel {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let record = make_check_box(
+            ctx,
+            "Record",
+            "Capture scopes of every thread per frame. Capturing slows down the editor a bit.",
+        );
+        let pause = make_check_box(
+            ctx,
+            "Pause",
+            "Stop showing the latest frame and inspect recorded frames.",
+        );
+        let export = make_button(
+            ctx,
+            "Export",
+            "Save every recorded frame to a file in Chrome trace format. The file could be \
+            opened in chrome://tracing or in Perfetto UI.",
+        );
+        let save_file_selector = create_file_selector(
+            ctx,
+            "json",
+            FileBrowserMode::Save {
+                default_file_name: PathBuf::from("profile.json"),
+            },
+        );
+        let clear = make_button(ctx, "Clear", "Remove every recorded frame.");
+        let zoom = NumericUpDownBuilder::new(
+            WidgetBuilder::new()
+                .with_width(70.0)
+                .with_margin(Thickness::uniform(1.0))
+                .with_tooltip(make_simple_tooltip(ctx, "Pixels per millisecond.")),
+        )
+        .with_min_value(1.0)
+        .with_max_value(10000.0)
+        .with_value(50.0)
+        .with_precision(0)
+        .build(ctx);
+        let frame_selector = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_enabled(false)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_min(0.0)
+        .with_max(0.0)
+        .with_step(1.0)
+        .build(ctx);
+        let frame_info = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_margin(Thickness::left(5.0)),
+        )
+        .with_text(if cfg!(feature = "enable_profiler") {
+            "Enable recording to capture frames."
+        } else {
+            "Compile the editor with \"enable_profiler\" feature to capture frames."
+        })
+        .build(ctx);
+        let timeline = ScrollViewerBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_horizontal_scroll_allowed(true)
+        .with_vertical_scroll_allowed(true)
+        .build(ctx);
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(700.0).with_height(400.0))
+            .can_minimize(false)
+            .open(false)
+            .with_title(WindowTitle::text("Frame Profiler"))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_child(record)
+                                    .with_child(pause)
+                                    .with_child(export)
+                                    .with_child(clear)
+                                    .with_child(zoom)
+                                    .with_child(frame_info),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        )
+                        .with_child(frame_selector)
+                        .with_child(timeline),
+                )
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(18.0))
+                .add_row(Row::stretch())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            record,
+            pause,
+            zoom,
+            export,
+            save_file_selector,
+            clear,
+            frame_selector,
+            frame_info,
+            timeline,
+            pixels_per_ms: 50.0,
+            paused_frames: None,
+            selected_frame: 0,
+            shown_frame: None,
+            refresh_timer: 0.0,
+        }
+    }
+
+    fn show_frame(&mut self, frame: Option<&FrameCapture>, engine: &mut Engine) {
+        let ui = &mut engine.user_interface;
+
+        let (content, info) = match frame {
+            Some(frame) => (
+                build_timeline(frame, self.pixels_per_ms, &mut ui.build_ctx()),
+                format!(
+                    "Frame {} - {:.3} ms - {} thread(s)",
+                    frame.index,
+                    millis(frame.duration),
+                    frame.threads.len()
+                ),
+            ),
+            None => (
+                CanvasBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx()),
+                "No frames recorded.".to_owned(),
+            ),
+        };
+
+        self.shown_frame = frame.map(|f| f.index);
+
+        ui.send_message(ScrollViewerMessage::content(
+            self.timeline,
+            MessageDirection::ToWidget,
+            content,
+        ));
+        ui.send_message(TextMessage::text(
+            self.frame_info,
+            MessageDirection::ToWidget,
+            info,
+        ));
+    }
+
+    fn set_paused(&mut self, paused: bool, engine: &mut Engine) {
+        let ui = &engine.user_interface;
+
+        if paused {
+            let frames = profiler::captured_frames();
+            self.selected_frame = frames.len().saturating_sub(1);
+            ui.send_message(ScrollBarMessage::max_value(
+                self.frame_selector,
+                MessageDirection::ToWidget,
+                self.selected_frame as f32,
+            ));
+            ui.send_message(ScrollBarMessage::value(
+                self.frame_selector,
+                MessageDirection::ToWidget,
+                self.selected_frame as f32,
+            ));
+            self.paused_frames = Some(frames);
+        } else {
+            self.paused_frames = None;
+        }
+
+        ui.send_message(WidgetMessage::enabled(
+            self.frame_selector,
+            MessageDirection::ToWidget,
+            paused,
+        ));
+
+        self.refresh(engine);
+    }
+
+    fn open_save_file_dialog(&self, engine: &Engine) {
+        let ui = &engine.user_interface;
+
+        ui.send_message(FileSelectorMessage::root(
+            self.save_file_selector,
+            MessageDirection::ToWidget,
+            std::env::current_dir().ok(),
+        ));
+
+        ui.send_message(WindowMessage::open_modal(
+            self.save_file_selector,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn refresh(&mut self, engine: &mut Engine) {
+        match self.paused_frames.take() {
+            Some(frames) => {
+                self.show_frame(frames.get(self.selected_frame), engine);
+                self.paused_frames = Some(frames);
+            }
+            None => {
+                let frame = profiler::last_captured_frame();
+                self.show_frame(frame.as_ref(), engine);
+            }
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut Engine) {
+        scope_profile!();
+
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.record {
+                profiler::set_capture_enabled(*value);
+            } else if message.destination() == self.pause {
+                self.set_paused(*value, engine);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.export {
+                self.open_save_file_dialog(engine);
+            } else if message.destination() == self.clear {
+                profiler::clear_captured_frames();
+                if self.paused_frames.is_some() {
+                    self.set_paused(true, engine);
+                } else {
+                    self.refresh(engine);
+                }
+            }
+        } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
+            if message.destination() == self.save_file_selector {
+                save_trace(path);
+            }
+        } else if let Some(&NumericUpDownMessage::Value(value)) =
+            message.data::<NumericUpDownMessage<f32>>()
+        {
+            if message.destination() == self.zoom && value != self.pixels_per_ms {
+                self.pixels_per_ms = value;
+                self.refresh(engine);
+            }
+        } else if let Some(&ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.frame_selector {
+                let selected_frame = value.round() as usize;
+                if selected_frame != self.selected_frame {
+                    self.selected_frame = selected_frame;
+                    self.refresh(engine);
+                }
+            }
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, engine: &mut Engine) {
+        if self.paused_frames.is_some() || !profiler::is_capture_enabled() {
+            return;
+        }
+
+        // Rebuilding the timeline every frame is too expensive, so the latest frame is shown
+        // periodically.
+        self.refresh_timer -= dt;
+        if self.refresh_timer <= 0.0 {
+            self.refresh_timer = REFRESH_INTERVAL;
+
+            if !engine.user_interface.node(self.window).visibility() {
+                return;
+            }
+
+            let frame = profiler::last_captured_frame();
+            if frame.as_ref().map(|f| f.index) != self.shown_frame {
+                self.show_frame(frame.as_ref(), engine);
+            }
+        }
+    }
+}
@@ -477,5 +477,10 @@ define_universal_commands!(
     ctx,
     handle,
     self,
-    { &mut ctx.scene.graph[self.handle] as &mut dyn Reflect },
+    {
+        // Properties could be changed bypassing setters of the node (for example, items of its tags),
+        // so the indices of the graph must check the node again.
+        ctx.scene.graph.mark_node_changed(self.handle);
+        &mut ctx.scene.graph[self.handle] as &mut dyn Reflect
+    },
 );
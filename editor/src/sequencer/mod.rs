@@ -0,0 +1,745 @@
+//! Sequencer editor allows you to edit tracks of sequencer nodes on a timeline, scrub and preview them. Properties
+//! of keys (cameras, sounds, animations, event names) are edited in the Inspector.
+
+use crate::{
+    animation::{
+        ruler::{RulerBuilder, RulerMessage, SignalView},
+        thumb::{ThumbBuilder, ThumbMessage},
+    },
+    command::Command,
+    gui::make_dropdown_list_option,
+    message::MessageSender,
+    scene::{commands::SceneContext, EditorScene, Selection},
+    send_sync_message, Message,
+};
+use fyrox::{
+    core::{log::Log, pool::Handle, uuid::Uuid},
+    engine::Engine,
+    fxhash::FxHashSet,
+    gui::{
+        border::BorderBuilder,
+        button::{ButtonBuilder, ButtonMessage},
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        dropdown_list::{DropdownList, DropdownListBuilder},
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        utils::make_simple_tooltip,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment, BRUSH_DARK,
+    },
+    scene::{
+        animation::{
+            sequencer::{Sequencer, SequencerTrack, SequencerTrackKind},
+            AnimationPlayer,
+        },
+        node::Node,
+        Scene,
+    },
+};
+use std::str::FromStr;
+use strum::VariantNames;
+
+#[derive(Debug)]
+pub struct SetSequencerTracksCommand {
+    pub sequencer: Handle<Node>,
+    pub tracks: Vec<SequencerTrack>,
+}
+
+impl SetSequencerTracksCommand {
+    fn swap(&mut self, context: &mut SceneContext) {
+        let sequencer = context.scene.graph[self.sequencer]
+            .query_component_mut::<Sequencer>()
+            .unwrap();
+        let old = sequencer.tracks().to_vec();
+        sequencer.set_tracks(std::mem::replace(&mut self.tracks, old));
+    }
+}
+
+impl Command for SetSequencerTracksCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Change Sequencer Tracks".to_string()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context)
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context)
+    }
+}
+
+// Keys of a track have no identifiers, so their indices are used as identifiers of ruler markers.
+fn key_id(index: usize) -> Uuid {
+    Uuid::from_u128(index as u128)
+}
+
+fn key_index(id: &Uuid) -> usize {
+    id.as_u128() as usize
+}
+
+macro_rules! for_each_key {
+    ($kind:expr, |$keys:ident| $body:expr) => {
+        match $kind {
+            SequencerTrackKind::CameraCut($keys) => $body,
+            SequencerTrackKind::Audio($keys) => $body,
+            SequencerTrackKind::Event($keys) => $body,
+            SequencerTrackKind::Animation($keys) => $body,
+        }
+    };
+}
+
+fn add_key(kind: &mut SequencerTrackKind, time: f32) {
+    for_each_key!(kind, |keys| {
+        keys.push(Default::default());
+        keys.last_mut().unwrap().time = time;
+        keys.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    })
+}
+
+fn remove_key(kind: &mut SequencerTrackKind, index: usize) {
+    for_each_key!(kind, |keys| {
+        if index < keys.len() {
+            keys.remove(index);
+        }
+    })
+}
+
+fn move_key(kind: &mut SequencerTrackKind, index: usize, time: f32) {
+    for_each_key!(kind, |keys| {
+        if let Some(key) = keys.get_mut(index) {
+            key.time = time.max(0.0);
+        }
+        keys.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    })
+}
+
+// Collects every node, that could be modified by the sequencer during the preview.
+fn collect_affected_nodes(
+    sequencer_handle: Handle<Node>,
+    sequencer: &Sequencer,
+    scene: &Scene,
+) -> FxHashSet<Handle<Node>> {
+    let mut nodes = FxHashSet::default();
+    nodes.insert(sequencer_handle);
+
+    for track in sequencer.tracks() {
+        match track.kind {
+            SequencerTrackKind::CameraCut(ref cuts) => {
+                nodes.extend(cuts.iter().map(|c| c.camera));
+            }
+            SequencerTrackKind::Audio(ref cues) => {
+                nodes.extend(cues.iter().map(|c| c.sound));
+            }
+            SequencerTrackKind::Event(_) => (),
+            SequencerTrackKind::Animation(ref clips) => {
+                for clip in clips {
+                    nodes.insert(clip.animation_player);
+                    if let Some(animation) = scene
+                        .graph
+                        .try_get_of_type::<AnimationPlayer>(clip.animation_player)
+                        .and_then(|p| p.animations().try_get(clip.animation))
+                    {
+                        nodes.extend(animation.tracks().iter().map(|t| t.target()));
+                    }
+                }
+            }
+        }
+    }
+
+    nodes.retain(|n| scene.graph.is_valid_handle(*n));
+    nodes
+}
+
+struct PreviewModeData {
+    nodes: Vec<(Handle<Node>, Node)>,
+}
+
+pub struct SequencerEditor {
+    pub window: Handle<UiNode>,
+    content: Handle<UiNode>,
+    tracks: Handle<UiNode>,
+    track_kind: Handle<UiNode>,
+    add_track: Handle<UiNode>,
+    remove_track: Handle<UiNode>,
+    preview: Handle<UiNode>,
+    play_pause: Handle<UiNode>,
+    stop: Handle<UiNode>,
+    ruler: Handle<UiNode>,
+    thumb: Handle<UiNode>,
+    selected_track: Option<usize>,
+    track_names: Vec<String>,
+    length: f32,
+    preview_mode_data: Option<PreviewModeData>,
+}
+
+fn fetch_sequencer(editor_selection: &Selection) -> Handle<Node> {
+    if let Selection::Graph(ref selection) = editor_selection {
+        selection.nodes.first().cloned().unwrap_or_default()
+    } else {
+        Default::default()
+    }
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str, tooltip: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl SequencerEditor {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let tracks;
+        let track_kind;
+        let add_track;
+        let remove_track;
+        let preview;
+        let play_pause;
+        let stop;
+        let ruler;
+        let thumb;
+
+        let toolbar = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_child({
+                    track_kind = DropdownListBuilder::new(
+                        WidgetBuilder::new()
+                            .with_width(110.0)
+                            .with_margin(Thickness::uniform(1.0)),
+                    )
+                    .with_items(
+                        SequencerTrackKind::VARIANTS
+                            .iter()
+                            .map(|v| make_dropdown_list_option(ctx, v))
+                            .collect::<Vec<_>>(),
+                    )
+                    .with_selected(0)
+                    .build(ctx);
+                    track_kind
+                })
+                .with_child({
+                    add_track = make_button(ctx, "Add Track", "Add a track of the selected kind.");
+                    add_track
+                })
+                .with_child({
+                    remove_track = make_button(ctx, "Remove Track", "Remove the selected track.");
+                    remove_track
+                })
+                .with_child({
+                    preview = CheckBoxBuilder::new(WidgetBuilder::new().with_margin(Thickness {
+                        left: 10.0,
+                        top: 1.0,
+                        right: 5.0,
+                        bottom: 1.0,
+                    }))
+                    .with_content(
+                        TextBuilder::new(
+                            WidgetBuilder::new().with_vertical_alignment(VerticalAlignment::Center),
+                        )
+                        .with_text("Preview")
+                        .build(ctx),
+                    )
+                    .checked(Some(false))
+                    .build(ctx);
+                    preview
+                })
+                .with_child({
+                    play_pause = make_button(ctx, "Play/Pause", "Play or pause the sequence.");
+                    play_pause
+                })
+                .with_child({
+                    stop = make_button(ctx, "Stop", "Stop and rewind the sequence.");
+                    stop
+                }),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+
+        let payload = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_child({
+                    tracks = ListViewBuilder::new(WidgetBuilder::new().on_column(0)).build(ctx);
+                    tracks
+                })
+                .with_child(
+                    BorderBuilder::new(
+                        WidgetBuilder::new()
+                            .on_column(1)
+                            .with_background(BRUSH_DARK)
+                            .with_child(
+                                GridBuilder::new(WidgetBuilder::new().with_child({
+                                    ruler = RulerBuilder::new(WidgetBuilder::new().on_row(0))
+                                        .with_value(0.0)
+                                        .build(ctx);
+                                    ruler
+                                }))
+                                .add_row(Row::strict(22.0))
+                                .add_row(Row::stretch())
+                                .add_column(Column::stretch())
+                                .build(ctx),
+                            )
+                            .with_child({
+                                thumb = ThumbBuilder::new(WidgetBuilder::new()).build(ctx);
+                                thumb
+                            }),
+                    )
+                    .build(ctx),
+                ),
+        )
+        .add_row(Row::stretch())
+        .add_column(Column::strict(200.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        let content = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_child(toolbar)
+                .with_child(payload),
+        )
+        .add_row(Row::strict(26.0))
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(600.0).with_height(300.0))
+            .with_content(content)
+            .open(false)
+            .with_title(WindowTitle::text("Sequencer Editor"))
+            .build(ctx);
+
+        Self {
+            window,
+            content,
+            tracks,
+            track_kind,
+            add_track,
+            remove_track,
+            preview,
+            play_pause,
+            stop,
+            ruler,
+            thumb,
+            selected_track: None,
+            track_names: Default::default(),
+            length: 0.0,
+            preview_mode_data: None,
+        }
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn change_tracks<F>(
+        &self,
+        sequencer_handle: Handle<Node>,
+        sequencer: &Sequencer,
+        func: F,
+        sender: &MessageSender,
+    ) where
+        F: FnOnce(&mut Vec<SequencerTrack>),
+    {
+        let mut tracks = sequencer.tracks().to_vec();
+        func(&mut tracks);
+        sender.do_scene_command(SetSequencerTracksCommand {
+            sequencer: sequencer_handle,
+            tracks,
+        });
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: Option<&mut EditorScene>,
+        engine: &mut Engine,
+        sender: &MessageSender,
+    ) {
+        let editor_scene = match editor_scene {
+            Some(editor_scene) => editor_scene,
+            None => return,
+        };
+
+        let sequencer_handle = fetch_sequencer(&editor_scene.selection);
+        let scene = &mut engine.scenes[editor_scene.scene];
+
+        let sequencer = match scene.graph.try_get_of_type::<Sequencer>(sequencer_handle) {
+            Some(sequencer) => sequencer,
+            None => return,
+        };
+
+        if let Some(ListViewMessage::SelectionChanged(selection)) = message.data() {
+            if message.destination() == self.tracks
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.selected_track = *selection;
+                self.sync_keys(sequencer, &engine.user_interface);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.add_track {
+                let kind = engine
+                    .user_interface
+                    .node(self.track_kind)
+                    .query_component::<DropdownList>()
+                    .and_then(|d| d.selection())
+                    .and_then(|i| SequencerTrackKind::VARIANTS.get(i))
+                    .and_then(|name| SequencerTrackKind::from_str(name).ok());
+                if let Some(kind) = kind {
+                    self.change_tracks(
+                        sequencer_handle,
+                        sequencer,
+                        |tracks| tracks.push(SequencerTrack::new(kind.as_ref(), kind)),
+                        sender,
+                    );
+                }
+            } else if message.destination() == self.remove_track {
+                if let Some(index) = self.selected_track {
+                    if index < sequencer.tracks().len() {
+                        self.change_tracks(
+                            sequencer_handle,
+                            sequencer,
+                            |tracks| {
+                                tracks.remove(index);
+                            },
+                            sender,
+                        );
+                    }
+                }
+            } else if message.destination() == self.play_pause {
+                if self.preview_mode_data.is_some() {
+                    if let Some(sequencer) = scene
+                        .graph
+                        .try_get_mut_of_type::<Sequencer>(sequencer_handle)
+                    {
+                        let playing = sequencer.is_playing();
+                        sequencer.set_playing(!playing);
+                    }
+                }
+            } else if message.destination() == self.stop && self.preview_mode_data.is_some() {
+                if let Some(sequencer) = scene
+                    .graph
+                    .try_get_mut_of_type::<Sequencer>(sequencer_handle)
+                {
+                    sequencer.stop();
+                }
+            }
+        } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.preview
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if *value {
+                    if self.preview_mode_data.is_none() {
+                        self.enter_preview_mode(sequencer_handle, scene, editor_scene);
+                    }
+                } else {
+                    self.try_leave_preview_mode(editor_scene, engine);
+                }
+            }
+        } else if let Some(msg) = message.data::<RulerMessage>() {
+            if message.destination() == self.ruler
+                && message.direction() == MessageDirection::FromWidget
+            {
+                match msg {
+                    RulerMessage::Value(value) => {
+                        if self.preview_mode_data.is_some() {
+                            if let Some(sequencer) = scene
+                                .graph
+                                .try_get_mut_of_type::<Sequencer>(sequencer_handle)
+                            {
+                                sequencer.set_time_position(*value);
+                            }
+                        }
+                    }
+                    RulerMessage::AddSignal(time) => {
+                        if let Some(index) = self.selected_track {
+                            let time = *time;
+                            self.change_tracks(
+                                sequencer_handle,
+                                sequencer,
+                                |tracks| {
+                                    if let Some(track) = tracks.get_mut(index) {
+                                        add_key(&mut track.kind, time.max(0.0));
+                                    }
+                                },
+                                sender,
+                            );
+                        }
+                    }
+                    RulerMessage::RemoveSignal(id) => {
+                        if let Some(index) = self.selected_track {
+                            let key = key_index(id);
+                            self.change_tracks(
+                                sequencer_handle,
+                                sequencer,
+                                |tracks| {
+                                    if let Some(track) = tracks.get_mut(index) {
+                                        remove_key(&mut track.kind, key);
+                                    }
+                                },
+                                sender,
+                            );
+                        }
+                    }
+                    RulerMessage::MoveSignal { id, new_position } => {
+                        if let Some(index) = self.selected_track {
+                            let (key, time) = (key_index(id), *new_position);
+                            self.change_tracks(
+                                sequencer_handle,
+                                sequencer,
+                                |tracks| {
+                                    if let Some(track) = tracks.get_mut(index) {
+                                        move_key(&mut track.kind, key, time);
+                                    }
+                                },
+                                sender,
+                            );
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    fn enter_preview_mode(
+        &mut self,
+        sequencer_handle: Handle<Node>,
+        scene: &mut Scene,
+        editor_scene: &mut EditorScene,
+    ) {
+        let node_overrides = editor_scene.graph_switches.node_overrides.as_mut().unwrap();
+
+        let sequencer = match scene.graph.try_get_of_type::<Sequencer>(sequencer_handle) {
+            Some(sequencer) => sequencer,
+            None => return,
+        };
+
+        let mut data = PreviewModeData { nodes: Vec::new() };
+        for handle in collect_affected_nodes(sequencer_handle, sequencer, scene) {
+            if node_overrides.insert(handle) {
+                data.nodes.push((handle, scene.graph[handle].clone_box()));
+            } else {
+                Log::warn(format!(
+                    "Node {} is already previewed by another tool!",
+                    scene.graph[handle].name()
+                ));
+            }
+        }
+
+        // Every change of the state of the nodes will be reverted at the exit of the preview mode.
+        if let Some(sequencer) = scene
+            .graph
+            .try_get_mut_of_type::<Sequencer>(sequencer_handle)
+        {
+            sequencer.set_playing(false);
+        }
+
+        self.preview_mode_data = Some(data);
+    }
+
+    fn leave_preview_mode(
+        &mut self,
+        scene: &mut Scene,
+        node_overrides: &mut FxHashSet<Handle<Node>>,
+    ) {
+        let preview_data = self
+            .preview_mode_data
+            .take()
+            .expect("Unable to leave sequencer preview mode!");
+
+        for (handle, node) in preview_data.nodes {
+            node_overrides.remove(&handle);
+            scene.graph[handle] = node;
+        }
+    }
+
+    pub fn try_leave_preview_mode(&mut self, editor_scene: &mut EditorScene, engine: &mut Engine) {
+        if self.preview_mode_data.is_some() {
+            let scene = &mut engine.scenes[editor_scene.scene];
+
+            self.leave_preview_mode(
+                scene,
+                editor_scene.graph_switches.node_overrides.as_mut().unwrap(),
+            );
+
+            send_sync_message(
+                &engine.user_interface,
+                CheckBoxMessage::checked(self.preview, MessageDirection::ToWidget, Some(false)),
+            );
+        }
+    }
+
+    pub fn handle_message(
+        &mut self,
+        message: &Message,
+        editor_scene: &mut EditorScene,
+        engine: &mut Engine,
+    ) {
+        // Leave preview mode before execution of any scene command.
+        if let Message::DoSceneCommand(_) | Message::UndoSceneCommand | Message::RedoSceneCommand =
+            message
+        {
+            self.try_leave_preview_mode(editor_scene, engine);
+        }
+    }
+
+    pub fn update(&mut self, editor_scene: &EditorScene, engine: &Engine) {
+        let scene = &engine.scenes[editor_scene.scene];
+
+        if let Some(sequencer) = scene
+            .graph
+            .try_get_of_type::<Sequencer>(fetch_sequencer(&editor_scene.selection))
+        {
+            engine.user_interface.send_message(ThumbMessage::position(
+                self.thumb,
+                MessageDirection::ToWidget,
+                sequencer.time_position(),
+            ));
+        }
+    }
+
+    fn sync_keys(&self, sequencer: &Sequencer, ui: &UserInterface) {
+        let keys = self
+            .selected_track
+            .and_then(|i| sequencer.tracks().get(i))
+            .map(|track| {
+                track
+                    .kind
+                    .key_times()
+                    .enumerate()
+                    .map(|(i, time)| SignalView {
+                        id: key_id(i),
+                        time,
+                        selected: false,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        send_sync_message(
+            ui,
+            RulerMessage::sync_signals(self.ruler, MessageDirection::ToWidget, keys),
+        );
+    }
+
+    pub fn sync_to_model(&mut self, editor_scene: &EditorScene, engine: &mut Engine) {
+        let scene = &engine.scenes[editor_scene.scene];
+        let ui = &mut engine.user_interface;
+
+        let sequencer = scene
+            .graph
+            .try_get_of_type::<Sequencer>(fetch_sequencer(&editor_scene.selection));
+
+        send_sync_message(
+            ui,
+            WidgetMessage::visibility(
+                self.content,
+                MessageDirection::ToWidget,
+                sequencer.is_some(),
+            ),
+        );
+
+        let sequencer = match sequencer {
+            Some(sequencer) => sequencer,
+            None => {
+                self.track_names.clear();
+                self.selected_track = None;
+                return;
+            }
+        };
+
+        let track_names = sequencer
+            .tracks()
+            .iter()
+            .map(|t| format!("{} ({})", t.name, t.kind.as_ref()))
+            .collect::<Vec<_>>();
+        if track_names != self.track_names {
+            let items = track_names
+                .iter()
+                .map(|name| make_dropdown_list_option(&mut ui.build_ctx(), name))
+                .collect::<Vec<_>>();
+            send_sync_message(
+                ui,
+                ListViewMessage::items(self.tracks, MessageDirection::ToWidget, items),
+            );
+            self.track_names = track_names;
+        }
+
+        self.selected_track = self.selected_track.filter(|i| *i < self.track_names.len());
+        send_sync_message(
+            ui,
+            ListViewMessage::selection(
+                self.tracks,
+                MessageDirection::ToWidget,
+                self.selected_track,
+            ),
+        );
+
+        if self.length != sequencer.length() {
+            self.length = sequencer.length();
+
+            let width = ui.node(self.ruler).actual_local_size().x;
+            let zoom = width / self.length.max(f32::EPSILON);
+            let view_position = 0.5 * (width - self.length);
+            send_sync_message(
+                ui,
+                RulerMessage::zoom(self.ruler, MessageDirection::ToWidget, zoom),
+            );
+            send_sync_message(
+                ui,
+                RulerMessage::view_position(self.ruler, MessageDirection::ToWidget, view_position),
+            );
+            send_sync_message(
+                ui,
+                ThumbMessage::zoom(self.thumb, MessageDirection::ToWidget, zoom),
+            );
+            send_sync_message(
+                ui,
+                ThumbMessage::view_position(self.thumb, MessageDirection::ToWidget, view_position),
+            );
+        }
+
+        self.sync_keys(sequencer, ui);
+
+        send_sync_message(
+            ui,
+            CheckBoxMessage::checked(
+                self.preview,
+                MessageDirection::ToWidget,
+                Some(self.preview_mode_data.is_some()),
+            ),
+        );
+        for widget in [self.play_pause, self.stop] {
+            send_sync_message(
+                ui,
+                WidgetMessage::enabled(
+                    widget,
+                    MessageDirection::ToWidget,
+                    self.preview_mode_data.is_some(),
+                ),
+            );
+        }
+        send_sync_message(
+            ui,
+            WidgetMessage::enabled(
+                self.remove_track,
+                MessageDirection::ToWidget,
+                self.selected_track.is_some(),
+            ),
+        );
+    }
+}
@@ -1,15 +1,27 @@
 //! Built-in scoped profiler. You must compile with feature "enable_profiler" to
 //! force profiler gather info! It is disabled by default because it is not cheap
 //! and takes 3-5% of performance for internal needs.
+//!
+//! The profiler collects aggregated timings of every scope (see [`print`] and [`print_hot_path`]).
+//! It also could capture individual scopes of every thread per frame, see [`set_capture_enabled`]
+//! docs for more info. Captured frames could be exported in Chrome trace format using
+//! [`chrome_trace`], such traces could be viewed in `chrome://tracing` or in Perfetto UI.
 
 #![allow(dead_code)]
 
 use fxhash::{FxHashMap, FxHashSet, FxHasher};
 use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
     fmt,
     fmt::Write,
     hash::{Hash, Hasher},
-    sync::{Arc, Mutex},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 pub fn print() -> Result<String, fmt::Error> {
@@ -231,11 +243,315 @@ impl Profiler {
 
 lazy_static! {
     static ref PROFILER: Arc<Mutex<Profiler>> = Arc::new(Mutex::new(Profiler::default()));
+    static ref CAPTURE: Mutex<Capture> = Mutex::new(Capture::default());
+    static ref EPOCH: Instant = Instant::now();
+}
+
+/// Default amount of frames stored in the ring buffer of captured frames.
+pub const DEFAULT_FRAME_CAPACITY: usize = 300;
+
+static CAPTURE_ENABLED: AtomicBool = AtomicBool::new(false);
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD_BUFFER: RefCell<Option<Arc<Mutex<ThreadBuffer>>>> = RefCell::new(None);
+    static SCOPE_DEPTH: Cell<u32> = Cell::new(0);
+}
+
+/// A single scope captured on a thread.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapturedScope {
+    /// Full name of the function in which the scope was defined.
+    pub function_name: &'static str,
+    /// Line at which the scope was defined.
+    pub line: u32,
+    /// Time at which the scope was entered, relative to the start of the profiler.
+    pub start: Duration,
+    /// Time spent in the scope.
+    pub duration: Duration,
+    /// Amount of scopes this scope is nested in.
+    pub depth: u32,
+}
+
+impl CapturedScope {
+    /// Returns the name of the function without the name of the internal marker function.
+    pub fn name(&self) -> &'static str {
+        self.function_name
+            .strip_suffix("::scope")
+            .unwrap_or(self.function_name)
+    }
+}
+
+/// A set of scopes captured on a thread during a frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadCapture {
+    /// Unique id of the thread. Ids start from 1 and are not reused.
+    pub thread_id: u64,
+    /// Name of the thread.
+    pub thread_name: String,
+    /// Scopes that were finished during the frame, sorted by their start time.
+    pub scopes: Vec<CapturedScope>,
+}
+
+/// Scopes of every thread captured during a frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameCapture {
+    /// Index of the frame.
+    pub index: u64,
+    /// Time at which the frame was started, relative to the start of the profiler.
+    pub start: Duration,
+    /// Duration of the frame.
+    pub duration: Duration,
+    /// Scopes of every thread, that were finished during the frame.
+    pub threads: Vec<ThreadCapture>,
+}
+
+#[derive(Debug)]
+struct ThreadBuffer {
+    thread_id: u64,
+    thread_name: String,
+    scopes: Vec<CapturedScope>,
+}
+
+#[derive(Debug)]
+struct Capture {
+    capacity: usize,
+    frames: VecDeque<FrameCapture>,
+    threads: Vec<Arc<Mutex<ThreadBuffer>>>,
+    frame_index: u64,
+    frame_start: Duration,
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_FRAME_CAPACITY,
+            frames: Default::default(),
+            threads: Default::default(),
+            frame_index: 0,
+            frame_start: EPOCH.elapsed(),
+        }
+    }
+}
+
+impl Capture {
+    fn register_thread(&mut self) -> Arc<Mutex<ThreadBuffer>> {
+        let thread_id = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+        let thread_name = std::thread::current()
+            .name()
+            .map(|name| name.to_owned())
+            .unwrap_or_else(|| format!("Thread {}", thread_id));
+        let buffer = Arc::new(Mutex::new(ThreadBuffer {
+            thread_id,
+            thread_name,
+            scopes: Default::default(),
+        }));
+        self.threads.push(buffer.clone());
+        buffer
+    }
+
+    fn next_frame(&mut self) {
+        let now = EPOCH.elapsed();
+
+        let mut threads = Vec::new();
+        for buffer in self.threads.iter() {
+            let mut buffer = buffer.lock().unwrap();
+            if !buffer.scopes.is_empty() {
+                let mut scopes = std::mem::take(&mut buffer.scopes);
+                scopes.sort_by_key(|scope| scope.start);
+                threads.push(ThreadCapture {
+                    thread_id: buffer.thread_id,
+                    thread_name: buffer.thread_name.clone(),
+                    scopes,
+                });
+            }
+        }
+        threads.sort_by_key(|thread| thread.thread_id);
+
+        if CAPTURE_ENABLED.load(Ordering::Relaxed) {
+            self.frames.push_back(FrameCapture {
+                index: self.frame_index,
+                start: self.frame_start,
+                duration: now.saturating_sub(self.frame_start),
+                threads,
+            });
+            while self.frames.len() > self.capacity {
+                self.frames.pop_front();
+            }
+        }
+
+        // Buffers of finished threads are referenced only by the capture.
+        self.threads.retain(|buffer| Arc::strong_count(buffer) > 1);
+
+        self.frame_index += 1;
+        self.frame_start = now;
+    }
+}
+
+fn capture_scope(scope: CapturedScope) {
+    // The buffer could be destroyed already if the scope is dropped during thread shutdown.
+    let _ = THREAD_BUFFER.try_with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        let buffer = buffer.get_or_insert_with(|| CAPTURE.lock().unwrap().register_thread());
+        buffer.lock().unwrap().scopes.push(scope);
+    });
+}
+
+/// Enables or disables per-frame capture of scopes. When enabled, every scope (see
+/// [`crate::scope_profile`]) of every thread is recorded and then stored in a ring buffer of frames
+/// on [`next_frame`] call. Capture works only if the crate is compiled with "enable_profiler" feature.
+pub fn set_capture_enabled(enabled: bool) {
+    CAPTURE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if per-frame capture of scopes is enabled, `false` - otherwise.
+pub fn is_capture_enabled() -> bool {
+    CAPTURE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Sets max amount of frames stored in the ring buffer, the oldest frames are removed first.
+pub fn set_frame_capacity(capacity: usize) {
+    let mut capture = CAPTURE.lock().unwrap();
+    capture.capacity = capacity;
+    while capture.frames.len() > capacity {
+        capture.frames.pop_front();
+    }
+}
+
+/// Returns max amount of frames stored in the ring buffer.
+pub fn frame_capacity() -> usize {
+    CAPTURE.lock().unwrap().capacity
+}
+
+/// Finishes current frame and starts a new one. Scopes that were finished since the previous call are
+/// stored as a frame in the ring buffer (if the capture is enabled). The engine calls this method at the
+/// end of every rendered frame, so you don't need to call it manually.
+pub fn next_frame() {
+    #[cfg(feature = "enable_profiler")]
+    {
+        CAPTURE.lock().unwrap().next_frame();
+    }
+}
+
+/// Returns a copy of every frame from the ring buffer, the oldest frames go first.
+pub fn captured_frames() -> Vec<FrameCapture> {
+    CAPTURE.lock().unwrap().frames.iter().cloned().collect()
+}
+
+/// Returns a copy of the latest captured frame (if any).
+pub fn last_captured_frame() -> Option<FrameCapture> {
+    CAPTURE.lock().unwrap().frames.back().cloned()
+}
+
+/// Removes every frame from the ring buffer.
+pub fn clear_captured_frames() {
+    CAPTURE.lock().unwrap().frames.clear();
+}
+
+fn write_json_string(buffer: &mut String, string: &str) -> fmt::Result {
+    buffer.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(buffer, "\\u{:04x}", c as u32)?,
+            c => buffer.push(c),
+        }
+    }
+    buffer.push('"');
+    Ok(())
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000_000.0
+}
+
+/// Converts the frames into [Chrome trace format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU).
+/// The result could be opened in `chrome://tracing` or in [Perfetto UI](https://ui.perfetto.dev). Every
+/// frame is shown as a separate track, every scope is shown on the track of its thread.
+pub fn chrome_trace(frames: &[FrameCapture]) -> Result<String, fmt::Error> {
+    let mut threads = FxHashMap::default();
+    for thread in frames.iter().flat_map(|frame| frame.threads.iter()) {
+        threads.insert(thread.thread_id, thread.thread_name.as_str());
+    }
+    let mut threads = threads.into_iter().collect::<Vec<_>>();
+    threads.sort_unstable();
+
+    let mut events = Vec::new();
+
+    let mut event = String::new();
+    write!(
+        event,
+        r#"{{"name":"thread_name","ph":"M","pid":0,"tid":0,"args":{{"name":"Frames"}}}}"#
+    )?;
+    events.push(event);
+
+    for (thread_id, thread_name) in threads {
+        let mut event = String::new();
+        write!(
+            event,
+            r#"{{"name":"thread_name","ph":"M","pid":0,"tid":{},"args":{{"name":"#,
+            thread_id
+        )?;
+        write_json_string(&mut event, thread_name)?;
+        event.push_str("}}");
+        events.push(event);
+    }
+
+    for frame in frames {
+        let mut event = String::new();
+        write!(
+            event,
+            r#"{{"name":"Frame {}","cat":"frame","ph":"X","ts":{:.3},"dur":{:.3},"pid":0,"tid":0}}"#,
+            frame.index,
+            micros(frame.start),
+            micros(frame.duration)
+        )?;
+        events.push(event);
+
+        for thread in frame.threads.iter() {
+            for scope in thread.scopes.iter() {
+                let mut event = String::new();
+                event.push_str(r#"{"name":"#);
+                write_json_string(&mut event, scope.name())?;
+                write!(
+                    event,
+                    r#","cat":"scope","ph":"X","ts":{:.3},"dur":{:.3},"pid":0,"tid":{},"args":{{"line":{}}}}}"#,
+                    micros(scope.start),
+                    micros(scope.duration),
+                    thread.thread_id,
+                    scope.line
+                )?;
+                events.push(event);
+            }
+        }
+    }
+
+    let mut buffer = String::new();
+    write!(
+        buffer,
+        r#"{{"displayTimeUnit":"ms","traceEvents":[{}]}}"#,
+        events.join(",\n")
+    )?;
+    Ok(buffer)
+}
+
+/// Saves the frames in Chrome trace format to the file at the given path. See [`chrome_trace`] docs
+/// for more info.
+pub fn save_chrome_trace<P: AsRef<Path>>(path: P, frames: &[FrameCapture]) -> std::io::Result<()> {
+    let trace =
+        chrome_trace(frames).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    std::fs::write(path, trace)
 }
 
 pub struct ScopeDefinition {
     scope: ScopeMark,
     start_time: std::time::Instant,
+    // Some(depth) if the scope is captured.
+    captured_depth: Option<u32>,
 }
 
 impl ScopeDefinition {
@@ -250,9 +566,20 @@ impl ScopeDefinition {
 
         PROFILER.lock().unwrap().enter_scope(&mut scope);
 
+        let captured_depth = if is_capture_enabled() {
+            Some(SCOPE_DEPTH.with(|depth| {
+                let current = depth.get();
+                depth.set(current + 1);
+                current
+            }))
+        } else {
+            None
+        };
+
         Self {
             scope,
             start_time: std::time::Instant::now(),
+            captured_depth,
         }
     }
 
@@ -266,6 +593,17 @@ impl Drop for ScopeDefinition {
     fn drop(&mut self) {
         let elapsed = self.elapsed();
         PROFILER.lock().unwrap().leave_scope(self.scope, elapsed);
+
+        if let Some(depth) = self.captured_depth {
+            let _ = SCOPE_DEPTH.try_with(|d| d.set(depth));
+            capture_scope(CapturedScope {
+                function_name: self.scope.function_name,
+                line: self.scope.line,
+                start: self.start_time.saturating_duration_since(*EPOCH),
+                duration: Duration::from_secs_f64(elapsed),
+                depth,
+            });
+        }
     }
 }
 
@@ -323,4 +661,81 @@ mod test {
 
         println!("{:?}", profiler::print());
     }
+
+    #[test]
+    fn test_chrome_trace() {
+        let frame = profiler::FrameCapture {
+            index: 3,
+            start: Duration::from_millis(10),
+            duration: Duration::from_millis(16),
+            threads: vec![profiler::ThreadCapture {
+                thread_id: 1,
+                thread_name: "main \"thread\"".to_string(),
+                scopes: vec![profiler::CapturedScope {
+                    function_name: "game::update::scope",
+                    line: 42,
+                    start: Duration::from_millis(11),
+                    duration: Duration::from_micros(1500),
+                    depth: 0,
+                }],
+            }],
+        };
+
+        let trace = profiler::chrome_trace(&[frame]).unwrap();
+        assert!(trace.starts_with(r#"{"displayTimeUnit":"ms","traceEvents":["#));
+        assert!(trace.contains(r#""args":{"name":"main \"thread\""}"#));
+        assert!(trace.contains(
+            r#"{"name":"Frame 3","cat":"frame","ph":"X","ts":10000.000,"dur":16000.000,"pid":0,"tid":0}"#
+        ));
+        assert!(trace.contains(
+            r#"{"name":"game::update","cat":"scope","ph":"X","ts":11000.000,"dur":1500.000,"pid":0,"tid":1,"args":{"line":42}}"#
+        ));
+    }
+
+    #[cfg(feature = "enable_profiler")]
+    #[test]
+    fn test_frame_capture() {
+        profiler::set_capture_enabled(true);
+        profiler::next_frame();
+
+        some_func();
+        std::thread::Builder::new()
+            .name("Worker".to_string())
+            .spawn(nested_func)
+            .unwrap()
+            .join()
+            .unwrap();
+
+        profiler::next_frame();
+        profiler::set_capture_enabled(false);
+
+        let frame = profiler::last_captured_frame().unwrap();
+        let worker = frame
+            .threads
+            .iter()
+            .find(|t| t.thread_name == "Worker")
+            .unwrap();
+        assert_eq!(worker.scopes.len(), 1);
+        assert!(worker.scopes[0].name().ends_with("nested_func"));
+
+        // Other tests could run in parallel, so look for the thread of this test only.
+        let current = std::thread::current();
+        let main = frame
+            .threads
+            .iter()
+            .find(|t| Some(t.thread_name.as_str()) == current.name())
+            .unwrap();
+        let outer = main
+            .scopes
+            .iter()
+            .find(|s| s.name().ends_with("some_func"))
+            .unwrap();
+        let inner = main
+            .scopes
+            .iter()
+            .find(|s| s.name().ends_with("nested_func"))
+            .unwrap();
+        assert_eq!(inner.depth, outer.depth + 1);
+        assert!(inner.start >= outer.start && inner.duration <= outer.duration);
+    }
 }
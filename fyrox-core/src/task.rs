@@ -0,0 +1,183 @@
+//! Task pool allows you to run async tasks (futures) on a thread pool (or as JS micro-tasks on
+//! WebAssembly) and fetch their results later on the main thread. See [`TaskPool`] docs for more info.
+
+#![warn(missing_docs)]
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{futures::executor::ThreadPool, log::Log};
+use crate::{parking_lot::Mutex, uuid::Uuid};
+use std::{
+    any::Any,
+    future::Future,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+/// A result of an async task. It is automatically implemented for every type that could be sent
+/// between threads.
+pub trait AsyncTaskResult: Any + Send + 'static {
+    /// Converts the boxed result into `Box<dyn Any>`, it is used for type casting.
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T> AsyncTaskResult for T
+where
+    T: Any + Send + 'static,
+{
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl dyn AsyncTaskResult {
+    /// Tries to convert the result into the given type. Returns the result back on failure.
+    pub fn downcast<T: AsyncTaskResult>(self: Box<Self>) -> Result<Box<T>, Box<dyn Any>> {
+        self.into_any().downcast()
+    }
+}
+
+/// An async task that produces a result of the given type.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait AsyncTask<R: AsyncTaskResult>: Future<Output = R> + Send + 'static {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T, R> AsyncTask<R> for T
+where
+    T: Future<Output = R> + Send + 'static,
+    R: AsyncTaskResult,
+{
+}
+
+/// An async task that produces a result of the given type.
+#[cfg(target_arch = "wasm32")]
+pub trait AsyncTask<R: AsyncTaskResult>: Future<Output = R> + 'static {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T, R> AsyncTask<R> for T
+where
+    T: Future<Output = R> + 'static,
+    R: AsyncTaskResult,
+{
+}
+
+/// A result of a finished task.
+pub struct TaskResult {
+    /// An id of the task, that was returned by [`TaskPool::spawn_with_result`].
+    pub id: Uuid,
+    /// The actual result of the task.
+    pub payload: Box<dyn AsyncTaskResult>,
+}
+
+/// Task pool allows you to run async tasks (futures) on a thread pool (or as JS micro-tasks on
+/// WebAssembly). Results of the tasks could be fetched later using [`TaskPool::next_task_result`].
+///
+/// ```rust
+/// use fyrox_core::task::TaskPool;
+///
+/// let task_pool = TaskPool::new();
+///
+/// let id = task_pool.spawn_with_result(async { 2 + 2 });
+///
+/// // Somewhere in your game loop.
+/// loop {
+///     if let Some(result) = task_pool.next_task_result() {
+///         assert_eq!(result.id, id);
+///         assert_eq!(*result.payload.downcast::<i32>().unwrap(), 4);
+///         break;
+///     }
+/// }
+/// ```
+pub struct TaskPool {
+    // `None` if the thread pool could not be created, every task is executed on a separate thread
+    // in this case.
+    #[cfg(not(target_arch = "wasm32"))]
+    thread_pool: Option<ThreadPool>,
+    sender: Sender<TaskResult>,
+    receiver: Mutex<Receiver<TaskResult>>,
+}
+
+impl Default for TaskPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskPool {
+    /// Creates new task pool. If the underlying thread pool cannot be created, the error is logged
+    /// and every task will be executed on its own thread instead.
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            thread_pool: match ThreadPool::new() {
+                Ok(thread_pool) => Some(thread_pool),
+                Err(e) => {
+                    Log::err(format!(
+                        "Unable to create a thread pool for async tasks. Reason: {e}. \
+                        Tasks will be executed on separate threads."
+                    ));
+                    None
+                }
+            },
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+
+    /// Spawns a task, the result of the task is ignored.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_task<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match self.thread_pool.as_ref() {
+            Some(thread_pool) => thread_pool.spawn_ok(future),
+            None => {
+                if let Err(e) = std::thread::Builder::new()
+                    .spawn(move || crate::futures::executor::block_on(future))
+                {
+                    Log::err(format!(
+                        "Unable to spawn a thread for an async task. Reason: {e}"
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Spawns a task, the result of the task is ignored.
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawn_task<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        crate::wasm_bindgen_futures::spawn_local(future);
+    }
+
+    /// Spawns a task and returns its unique id. The result of the task could be fetched later
+    /// using [`Self::next_task_result`].
+    pub fn spawn_with_result<F, T>(&self, future: F) -> Uuid
+    where
+        F: AsyncTask<T>,
+        T: AsyncTaskResult,
+    {
+        let id = Uuid::new_v4();
+        let sender = self.sender.clone();
+        self.spawn_task(async move {
+            let payload = Box::new(future.await);
+            // The receiver could be already destroyed, there's nothing to do in this case.
+            let _ = sender.send(TaskResult { id, payload });
+        });
+        id
+    }
+
+    /// Returns a sender, that could be used to deliver results of the tasks that are running
+    /// outside of the task pool (for example on the main thread).
+    pub fn result_sender(&self) -> Sender<TaskResult> {
+        self.sender.clone()
+    }
+
+    /// Tries to fetch the next result of a finished task. Returns `None` if there is no finished
+    /// tasks.
+    pub fn next_task_result(&self) -> Option<TaskResult> {
+        self.receiver.lock().try_recv().ok()
+    }
+}
@@ -0,0 +1,386 @@
+//! Animation compression reduces memory footprint of animations by removing redundant keys and by storing
+//! rotations in a compact form. See [`AnimationCompressionSettings`] docs for more info.
+
+use crate::core::{
+    algebra::{Quaternion, UnitQuaternion},
+    curve::{Curve, CurveKey, CurveKeyKind},
+    math::lerpf,
+    reflect::prelude::*,
+    visitor::{prelude::*, PodVecView},
+};
+use serde::{Deserialize, Serialize};
+
+/// A set of tolerances for every kind of animated values, that defines how much the compressed animation could
+/// deviate from the source animation. Larger values mean better compression, but lower quality.
+///
+/// Compression is performed in two steps:
+///
+/// 1. Key reduction - every key that could be restored by linear interpolation of its neighbours within the
+/// given tolerance is removed. This step gives the most significant gain for densely sampled animations, such
+/// as motion capture clips, where every frame is a key.
+/// 2. Rotation quantization (optional) - rotation tracks are converted from three curves of Euler angles to
+/// a packed list of quaternions, where each quaternion takes 8 bytes. Key reduction of such tracks is done in
+/// quaternion space, the same way as they're interpolated.
+#[derive(Clone, Debug, Serialize, Deserialize, Visit, Reflect)]
+pub struct AnimationCompressionSettings {
+    /// Maximum allowed error for position tracks (in units of the scene).
+    #[serde(default = "default_tolerance")]
+    pub position_tolerance: f32,
+
+    /// Maximum allowed error for rotation tracks (in radians).
+    #[serde(default = "default_tolerance")]
+    pub rotation_tolerance: f32,
+
+    /// Maximum allowed error for scale tracks.
+    #[serde(default = "default_tolerance")]
+    pub scale_tolerance: f32,
+
+    /// Maximum allowed error for tracks of arbitrary numeric properties.
+    #[serde(default = "default_tolerance")]
+    pub property_tolerance: f32,
+
+    /// Whether to store rotations as quantized quaternions or not. Quantized tracks could not be edited in
+    /// the curve editor, unless they're converted back (see
+    /// [`super::container::TrackDataContainer::dequantize_rotations`]).
+    #[serde(default = "default_quantize_rotations")]
+    pub quantize_rotations: bool,
+}
+
+fn default_tolerance() -> f32 {
+    0.0005
+}
+
+fn default_quantize_rotations() -> bool {
+    true
+}
+
+impl Default for AnimationCompressionSettings {
+    fn default() -> Self {
+        Self {
+            position_tolerance: default_tolerance(),
+            rotation_tolerance: default_tolerance(),
+            scale_tolerance: default_tolerance(),
+            property_tolerance: default_tolerance(),
+            quantize_rotations: default_quantize_rotations(),
+        }
+    }
+}
+
+// Tolerances are compared bitwise, so the settings could be `Eq` (import options must be comparable to
+// detect changes).
+impl PartialEq for AnimationCompressionSettings {
+    fn eq(&self, other: &Self) -> bool {
+        self.position_tolerance.to_bits() == other.position_tolerance.to_bits()
+            && self.rotation_tolerance.to_bits() == other.rotation_tolerance.to_bits()
+            && self.scale_tolerance.to_bits() == other.scale_tolerance.to_bits()
+            && self.property_tolerance.to_bits() == other.property_tolerance.to_bits()
+            && self.quantize_rotations == other.quantize_rotations
+    }
+}
+
+impl Eq for AnimationCompressionSettings {}
+
+fn is_linear(key: &CurveKey) -> bool {
+    matches!(key.kind, CurveKeyKind::Linear)
+}
+
+fn can_skip_keys(keys: &[CurveKey], from: usize, to: usize, tolerance: f32) -> bool {
+    let (begin, end) = (&keys[from], &keys[to]);
+    let span = end.location() - begin.location();
+
+    // Only linear segments could be replaced safely, the maximum error of a piecewise-linear curve is always
+    // at its keys, so it is enough to check the keys only.
+    span > 0.0
+        && is_linear(begin)
+        && keys[from + 1..to].iter().all(|key| {
+            let t = (key.location() - begin.location()) / span;
+            is_linear(key) && (lerpf(begin.value, end.value, t) - key.value).abs() <= tolerance
+        })
+}
+
+/// Removes every key of the curve, that could be restored by linear interpolation of its neighbours with the
+/// given tolerance. First and last keys are always preserved, as well as non-linear keys.
+pub fn reduce_curve_keys(curve: &mut Curve, tolerance: f32) {
+    let keys = curve.keys().to_vec();
+    if keys.len() <= 2 {
+        return;
+    }
+
+    curve.clear();
+    curve.add_key(keys[0].clone());
+
+    let mut anchor = 0;
+    for i in 1..keys.len() - 1 {
+        if !can_skip_keys(&keys, anchor, i + 1, tolerance) {
+            curve.add_key(keys[i].clone());
+            anchor = i;
+        }
+    }
+
+    curve.add_key(keys[keys.len() - 1].clone());
+}
+
+// Normalized linear interpolation of rotations by the shortest arc, quantized tracks are interpolated
+// in the same way.
+fn nlerp_shortest(a: &UnitQuaternion<f32>, b: &UnitQuaternion<f32>, t: f32) -> UnitQuaternion<f32> {
+    if a.coords.dot(&b.coords) < 0.0 {
+        a.nlerp(&UnitQuaternion::new_unchecked(-b.into_inner()), t)
+    } else {
+        a.nlerp(b, t)
+    }
+}
+
+fn can_skip_rotations(
+    samples: &[(f32, UnitQuaternion<f32>)],
+    from: usize,
+    to: usize,
+    tolerance: f32,
+) -> bool {
+    let ((begin_location, begin), (end_location, end)) = (&samples[from], &samples[to]);
+    let span = end_location - begin_location;
+
+    // Unlike linear curves, the error of interpolated rotations could be anywhere between the samples, so
+    // every sample of the span must be checked.
+    span > 0.0
+        && samples[from + 1..to].iter().all(|(location, rotation)| {
+            let t = (location - begin_location) / span;
+            nlerp_shortest(begin, end, t).angle_to(rotation) <= tolerance
+        })
+}
+
+/// Removes every sample of the rotation track, that could be restored by normalized linear interpolation
+/// of its neighbours with the given tolerance (in radians). First and last samples are always preserved.
+/// The samples must be sorted by location.
+pub fn reduce_rotation_samples(
+    samples: &[(f32, UnitQuaternion<f32>)],
+    tolerance: f32,
+) -> Vec<(f32, UnitQuaternion<f32>)> {
+    if samples.len() <= 2 {
+        return samples.to_vec();
+    }
+
+    let mut reduced = vec![samples[0]];
+
+    let mut anchor = 0;
+    for i in 1..samples.len() - 1 {
+        if !can_skip_rotations(samples, anchor, i + 1, tolerance) {
+            reduced.push(samples[i]);
+            anchor = i;
+        }
+    }
+
+    reduced.push(samples[samples.len() - 1]);
+    reduced
+}
+
+const QUANTIZATION_RANGE: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+fn quantize_component(value: f32) -> u64 {
+    let normalized = (value / QUANTIZATION_RANGE).clamp(-1.0, 1.0) * 0.5 + 0.5;
+    (normalized * u16::MAX as f32).round() as u64
+}
+
+fn dequantize_component(value: u64) -> f32 {
+    ((value & 0xFFFF) as f32 / u16::MAX as f32 * 2.0 - 1.0) * QUANTIZATION_RANGE
+}
+
+/// Packs the given quaternion into 64 bits using "smallest three" method - the largest component is dropped
+/// (it could be restored, because the quaternion has unit length) and the rest of the components are stored as
+/// 16-bit numbers. The index of the dropped component takes 2 more bits.
+pub fn quantize_rotation(rotation: &UnitQuaternion<f32>) -> u64 {
+    let coords = rotation.coords;
+    let mut largest = 0;
+    for i in 1..4 {
+        if coords[i].abs() > coords[largest].abs() {
+            largest = i;
+        }
+    }
+
+    // q and -q represent the same rotation, make the dropped component positive.
+    let sign = if coords[largest] < 0.0 { -1.0 } else { 1.0 };
+
+    let mut packed = largest as u64;
+    for (i, component) in coords.iter().enumerate() {
+        if i != largest {
+            packed = (packed << 16) | quantize_component(*component * sign);
+        }
+    }
+    packed
+}
+
+/// Unpacks a quaternion, that was packed by [`quantize_rotation`].
+pub fn dequantize_rotation(packed: u64) -> UnitQuaternion<f32> {
+    let largest = ((packed >> 48) & 0b11) as usize;
+    let mut coords = [0.0f32; 4];
+    let mut slot = 0;
+    let mut sum_squared = 0.0;
+    for (i, coord) in coords.iter_mut().enumerate() {
+        if i != largest {
+            *coord = dequantize_component(packed >> (32 - 16 * slot));
+            sum_squared += *coord * *coord;
+            slot += 1;
+        }
+    }
+    coords[largest] = (1.0 - sum_squared).max(0.0).sqrt();
+
+    // Coordinates are stored in (x, y, z, w) order.
+    UnitQuaternion::from_quaternion(Quaternion::new(coords[3], coords[0], coords[1], coords[2]))
+}
+
+/// A compact storage for rotation keys. See [`AnimationCompressionSettings`] docs for more info.
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(hide_all)]
+pub struct QuantizedRotationTrack {
+    locations: Vec<f32>,
+    rotations: Vec<u64>,
+}
+
+impl Visit for QuantizedRotationTrack {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        PodVecView::from_pod_vec(&mut self.locations).visit("Locations", &mut region)?;
+        PodVecView::from_pod_vec(&mut self.rotations).visit("Rotations", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl QuantizedRotationTrack {
+    /// Creates new track from a set of `(location, rotation)` pairs. The pairs must be sorted by location.
+    pub fn from_samples<I>(samples: I) -> Self
+    where
+        I: IntoIterator<Item = (f32, UnitQuaternion<f32>)>,
+    {
+        let mut track = Self::default();
+        for (location, rotation) in samples {
+            track.locations.push(location);
+            track.rotations.push(quantize_rotation(&rotation));
+        }
+        track
+    }
+
+    /// Returns a list of locations of every key of the track.
+    pub fn locations(&self) -> &[f32] {
+        &self.locations
+    }
+
+    /// Returns an iterator that yields `(location, rotation)` pairs for every key of the track.
+    pub fn samples(&self) -> impl Iterator<Item = (f32, UnitQuaternion<f32>)> + '_ {
+        self.locations
+            .iter()
+            .zip(self.rotations.iter())
+            .map(|(location, rotation)| (*location, dequantize_rotation(*rotation)))
+    }
+
+    /// Returns location of the last key of the track.
+    pub fn time_length(&self) -> f32 {
+        self.locations.last().cloned().unwrap_or_default()
+    }
+
+    /// Calculates an interpolated rotation at the given location. Returns `None` if the track is empty.
+    pub fn fetch(&self, location: f32) -> Option<UnitQuaternion<f32>> {
+        let first = *self.locations.first()?;
+        let last = *self.locations.last()?;
+        if location <= first {
+            return Some(dequantize_rotation(self.rotations[0]));
+        } else if location >= last {
+            return Some(dequantize_rotation(*self.rotations.last()?));
+        }
+
+        let right = self.locations.partition_point(|l| *l < location);
+        let left = right - 1;
+        let span = self.locations[right] - self.locations[left];
+        let t = if span > 0.0 {
+            (location - self.locations[left]) / span
+        } else {
+            0.0
+        };
+        Some(nlerp_shortest(
+            &dequantize_rotation(self.rotations[left]),
+            &dequantize_rotation(self.rotations[right]),
+            t,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::compression::{
+            dequantize_rotation, quantize_rotation, reduce_curve_keys, reduce_rotation_samples,
+            QuantizedRotationTrack,
+        },
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            curve::{Curve, CurveKey, CurveKeyKind},
+        },
+    };
+
+    #[test]
+    fn test_reduce_linear_curve() {
+        let mut curve = Curve::from(
+            (0..100)
+                .map(|i| CurveKey::new(i as f32, i as f32 * 2.0, CurveKeyKind::Linear))
+                .collect::<Vec<_>>(),
+        );
+
+        reduce_curve_keys(&mut curve, 0.001);
+
+        assert_eq!(curve.keys().len(), 2);
+        assert_eq!(curve.value_at(50.0), 100.0);
+    }
+
+    #[test]
+    fn test_reduce_keeps_peaks() {
+        let mut curve = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 0.5, CurveKeyKind::Linear),
+            CurveKey::new(2.0, 1.0, CurveKeyKind::Linear),
+            CurveKey::new(3.0, 0.0, CurveKeyKind::Linear),
+        ]);
+
+        reduce_curve_keys(&mut curve, 0.001);
+
+        assert_eq!(curve.keys().len(), 3);
+        assert_eq!(curve.value_at(2.0), 1.0);
+    }
+
+    #[test]
+    fn test_rotation_quantization() {
+        for axis in [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.6, -0.8),
+        ] {
+            for angle in [-3.0f32, -1.0, 0.0, 0.3, 2.5] {
+                let rotation = UnitQuaternion::from_scaled_axis(axis.scale(angle));
+                let restored = dequantize_rotation(quantize_rotation(&rotation));
+                assert!(rotation.angle_to(&restored) < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduce_rotation_samples() {
+        let tolerance = 0.01;
+        let samples = (0..100)
+            .map(|i| {
+                let t = i as f32 / 99.0;
+                (
+                    t,
+                    UnitQuaternion::from_axis_angle(&Vector3::y_axis(), t * std::f32::consts::PI),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let reduced = reduce_rotation_samples(&samples, tolerance);
+        // Nlerp of distant rotations deviates from uniform rotation, so some samples must be kept.
+        assert!(reduced.len() > 2 && reduced.len() < samples.len());
+
+        let track = QuantizedRotationTrack::from_samples(reduced);
+        for (location, rotation) in samples {
+            assert!(track.fetch(location).unwrap().angle_to(&rotation) <= tolerance + 0.001);
+        }
+    }
+}
@@ -2,17 +2,20 @@
 //! of parametric curves. See [`TrackDataContainer`] docs for more info.
 
 use crate::{
-    animation::value::TrackValue,
+    animation::{
+        compression::{reduce_curve_keys, reduce_rotation_samples, QuantizedRotationTrack},
+        value::{DiscreteValue, TrackValue},
+    },
     core::{
-        algebra::{Vector2, Vector3, Vector4},
-        curve::Curve,
+        algebra::{UnitQuaternion, Vector2, Vector3, Vector4},
+        curve::{Curve, CurveKey, CurveKeyKind},
         math::{quat_from_euler, RotationOrder},
         reflect::prelude::*,
         visitor::prelude::*,
     },
 };
 
-/// The kind of track output value, the animation system works only with numeric properties and the number
+/// The kind of track output value, the animation system works mostly with numeric properties and the number
 /// of variants is small.
 #[derive(Clone, Copy, Debug, Visit, Reflect, PartialEq, Eq)]
 pub enum TrackValueKind {
@@ -32,6 +35,11 @@ pub enum TrackValueKind {
     /// `ZAngle = 2`. The order of rotations is `XYZ`. This triple of curves forms Euler angles which are interpolated
     /// and then converted to a quaternion.
     UnitQuaternion,
+
+    /// A non-numeric value (see [`DiscreteValue`]). Requires 1 parametric curve, which value is rounded and used as
+    /// an index in the list of discrete values of the container. Usually the curve consists of keys with
+    /// [`CurveKeyKind::Constant`] kind, this way the values are switched instantly.
+    Discrete,
 }
 
 impl TrackValueKind {
@@ -47,6 +55,7 @@ impl TrackValueKind {
                 // Euler angles
                 3
             }
+            TrackValueKind::Discrete => 1,
         }
     }
 }
@@ -68,6 +77,11 @@ impl Default for TrackValueKind {
 pub struct TrackDataContainer {
     curves: Vec<Curve>,
     kind: TrackValueKind,
+    #[visit(optional)]
+    #[reflect(hidden)]
+    quantized: Option<QuantizedRotationTrack>,
+    #[visit(optional)]
+    discrete_values: Vec<DiscreteValue>,
 }
 
 impl TrackDataContainer {
@@ -84,6 +98,8 @@ impl TrackDataContainer {
             curves: (0..kind.components_count())
                 .map(|_| Curve::default())
                 .collect(),
+            quantized: None,
+            discrete_values: Default::default(),
         }
     }
 
@@ -124,6 +140,34 @@ impl TrackDataContainer {
         self.kind
     }
 
+    /// Returns a reference to the list of discrete values, that are selected by the index curve of a container
+    /// with [`TrackValueKind::Discrete`] kind.
+    pub fn discrete_values(&self) -> &[DiscreteValue] {
+        &self.discrete_values
+    }
+
+    /// Returns a mutable reference to the list of discrete values. See [`Self::discrete_values`] for more info.
+    pub fn discrete_values_mut(&mut self) -> &mut Vec<DiscreteValue> {
+        &mut self.discrete_values
+    }
+
+    /// Adds a key, that switches the output of a discrete container to the given value at the given time. The
+    /// value is added to the list of discrete values, if it is not there yet.
+    pub fn add_discrete_key(&mut self, time: f32, value: DiscreteValue) {
+        let index = match self.discrete_values.iter().position(|v| *v == value) {
+            Some(index) => index,
+            None => {
+                self.discrete_values.push(value);
+                self.discrete_values.len() - 1
+            }
+        };
+
+        if self.curves.is_empty() {
+            self.curves.push(Curve::default());
+        }
+        self.curves[0].add_key(CurveKey::new(time, index as f32, CurveKeyKind::Constant));
+    }
+
     /// Tries to get a value at a given time. The method could fail if the internal set of curves is malformed
     /// and cannot produce a desired value (for example, [`Vector3`] can be fetched only if the amount of curves
     /// is 3).
@@ -146,6 +190,10 @@ impl TrackDataContainer {
                 self.curves.get(3)?.value_at(time),
             ))),
             TrackValueKind::UnitQuaternion => {
+                if let Some(quantized) = self.quantized.as_ref() {
+                    return quantized.fetch(time).map(TrackValue::UnitQuaternion);
+                }
+
                 // Convert Euler angles to quaternion
                 let x = self.curves.get(0)?.value_at(time);
                 let y = self.curves.get(1)?.value_at(time);
@@ -156,13 +204,238 @@ impl TrackDataContainer {
                     RotationOrder::XYZ,
                 )))
             }
+            TrackValueKind::Discrete => {
+                let index = self.curves.get(0)?.value_at(time).round().max(0.0) as usize;
+                self.discrete_values
+                    .get(index.min(self.discrete_values.len().checked_sub(1)?))
+                    .cloned()
+                    .map(TrackValue::Discrete)
+            }
+        }
+    }
+
+    /// Converts the container to additive form - every value of the container becomes a difference between the
+    /// value and the value at the given reference time (see [`TrackValue::difference`]). Rotation curves are
+    /// resampled with linear keys at every key location of every curve, because differences of rotations cannot
+    /// be calculated per Euler angle.
+    pub fn make_additive(&mut self, reference_time: f32) {
+        // Discrete values cannot be added to each other.
+        if self.kind == TrackValueKind::Discrete {
+            return;
+        }
+
+        // Differences are calculated on curves, so quantized rotations must be restored first.
+        self.dequantize_rotations();
+
+        let reference = match self.fetch(reference_time) {
+            Some(reference) => reference,
+            None => return,
+        };
+
+        if let TrackValue::UnitQuaternion(_) = reference {
+            let samples = self
+                .key_locations()
+                .into_iter()
+                .filter_map(|location| {
+                    match self
+                        .fetch(location)
+                        .and_then(|value| value.difference(&reference))
+                    {
+                        Some(TrackValue::UnitQuaternion(delta)) => Some((location, delta)),
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            self.set_rotation_samples(samples);
+        } else {
+            for curve in self.curves.iter_mut() {
+                let reference = curve.value_at(reference_time);
+                for value in curve.keys_values() {
+                    *value -= reference;
+                }
+            }
+        }
+    }
+
+    /// Returns sorted list of unique key locations of every curve in the container (or of quantized rotations,
+    /// if the container is quantized).
+    pub fn key_locations(&self) -> Vec<f32> {
+        let mut locations = self
+            .curves
+            .iter()
+            .flat_map(|c| c.keys().iter().map(|k| k.location()))
+            .chain(
+                self.quantized
+                    .iter()
+                    .flat_map(|q| q.locations().iter().cloned()),
+            )
+            .collect::<Vec<_>>();
+        locations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        locations.dedup();
+        locations
+    }
+
+    // Replaces rotation curves with linear keys of Euler angles built from the given samples.
+    fn set_rotation_samples(&mut self, samples: Vec<(f32, UnitQuaternion<f32>)>) {
+        let mut previous = Vector3::<f32>::zeros();
+        let samples = samples
+            .into_iter()
+            .map(|(location, rotation)| {
+                let (x, y, z) = rotation.euler_angles();
+                let mut angles = Vector3::new(x, y, z);
+                // Prevent sudden jumps between -pi and pi, they'll break interpolation.
+                for (angle, prev_angle) in angles.iter_mut().zip(previous.iter()) {
+                    while *angle - *prev_angle > std::f32::consts::PI {
+                        *angle -= 2.0 * std::f32::consts::PI;
+                    }
+                    while *angle - *prev_angle < -std::f32::consts::PI {
+                        *angle += 2.0 * std::f32::consts::PI;
+                    }
+                }
+                previous = angles;
+                (location, angles)
+            })
+            .collect::<Vec<_>>();
+
+        for (i, curve) in self.curves.iter_mut().enumerate().take(3) {
+            curve.clear();
+            for (location, angles) in samples.iter() {
+                curve.add_key(CurveKey::new(*location, angles[i], CurveKeyKind::Linear));
+            }
+        }
+    }
+
+    /// Transforms every value of the container using the given function. The container is sampled at every
+    /// key location (see [`Self::key_locations`]) and the curves are re-created with linear keys, that hold
+    /// transformed values. Quantized rotations stay quantized. Discrete containers transform their list of
+    /// discrete values instead.
+    pub fn map_values<F>(&mut self, mut func: F)
+    where
+        F: FnMut(TrackValue) -> TrackValue,
+    {
+        if self.kind == TrackValueKind::Discrete {
+            for value in self.discrete_values.iter_mut() {
+                if let TrackValue::Discrete(mapped) = func(TrackValue::Discrete(value.clone())) {
+                    *value = mapped;
+                }
+            }
+            return;
+        }
+
+        let samples = self
+            .key_locations()
+            .into_iter()
+            .filter_map(|location| self.fetch(location).map(|value| (location, func(value))))
+            .collect::<Vec<_>>();
+
+        if self.kind == TrackValueKind::UnitQuaternion {
+            let rotations = samples
+                .into_iter()
+                .filter_map(|(location, value)| match value {
+                    TrackValue::UnitQuaternion(rotation) => Some((location, rotation)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            if self.quantized.is_some() {
+                self.quantized = Some(QuantizedRotationTrack::from_samples(rotations));
+            } else {
+                self.set_rotation_samples(rotations);
+            }
+        } else {
+            for curve in self.curves.iter_mut() {
+                curve.clear();
+            }
+            for (location, value) in samples {
+                let components = match value {
+                    TrackValue::Real(v) => vec![v],
+                    TrackValue::Vector2(v) => v.as_slice().to_vec(),
+                    TrackValue::Vector3(v) => v.as_slice().to_vec(),
+                    TrackValue::Vector4(v) => v.as_slice().to_vec(),
+                    TrackValue::UnitQuaternion(_) | TrackValue::Discrete(_) => continue,
+                };
+                for (curve, component) in self.curves.iter_mut().zip(components) {
+                    curve.add_key(CurveKey::new(location, component, CurveKeyKind::Linear));
+                }
+            }
+        }
+    }
+
+    /// Removes every key, that could be restored by linear interpolation of its neighbours within the given
+    /// tolerance. See [`reduce_curve_keys`] docs for more info.
+    pub fn reduce_keys(&mut self, tolerance: f32) {
+        // Keys of discrete containers are indices, they must stay untouched.
+        if self.kind == TrackValueKind::Discrete {
+            return;
+        }
+
+        for curve in self.curves.iter_mut() {
+            reduce_curve_keys(curve, tolerance);
         }
     }
 
+    /// Converts rotation curves to a compact list of quantized quaternions (see [`QuantizedRotationTrack`]).
+    /// The curves are sampled at every key location and then cleared. Returns `false` if the container does
+    /// not store rotations or it is already quantized.
+    pub fn quantize_rotations(&mut self) -> bool {
+        self.quantize_rotations_internal(None)
+    }
+
+    /// Same as [`Self::quantize_rotations`], but also removes every rotation sample that could be restored by
+    /// normalized linear interpolation of its neighbours with the given tolerance (in radians). Unlike
+    /// [`Self::reduce_keys`], the error is measured in the same way as the quantized track is interpolated,
+    /// so it is never larger than the tolerance (plus a tiny quantization error) at the source key locations.
+    pub fn compress_rotations(&mut self, tolerance: f32) -> bool {
+        self.quantize_rotations_internal(Some(tolerance))
+    }
+
+    fn quantize_rotations_internal(&mut self, tolerance: Option<f32>) -> bool {
+        if self.kind != TrackValueKind::UnitQuaternion || self.quantized.is_some() {
+            return false;
+        }
+
+        let mut samples = self
+            .key_locations()
+            .into_iter()
+            .filter_map(|location| match self.fetch(location) {
+                Some(TrackValue::UnitQuaternion(rotation)) => Some((location, rotation)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(tolerance) = tolerance {
+            samples = reduce_rotation_samples(&samples, tolerance);
+        }
+
+        self.quantized = Some(QuantizedRotationTrack::from_samples(samples));
+        for curve in self.curves.iter_mut() {
+            curve.clear();
+        }
+
+        true
+    }
+
+    /// Converts quantized rotations back to curves of Euler angles, so they could be edited. Does nothing if
+    /// the container is not quantized.
+    pub fn dequantize_rotations(&mut self) {
+        if let Some(quantized) = self.quantized.take() {
+            self.set_rotation_samples(quantized.samples().collect());
+        }
+    }
+
+    /// Returns `true` if the container stores its rotations in quantized form, `false` - otherwise.
+    pub fn is_quantized(&self) -> bool {
+        self.quantized.is_some()
+    }
+
     /// Find a right-most key on one of the curves in the container and returns its position. This position
     /// can be treated as a maximum "length" of the container.
     pub fn time_length(&self) -> f32 {
-        let mut length = 0.0;
+        let mut length = self
+            .quantized
+            .as_ref()
+            .map_or(0.0, |quantized| quantized.time_length());
         for curve in self.curves.iter() {
             let max_location = curve.max_location();
             if max_location > length {
@@ -172,3 +445,108 @@ impl TrackDataContainer {
         length
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            container::{TrackDataContainer, TrackValueKind},
+            value::{DiscreteValue, TrackValue},
+        },
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            curve::{CurveKey, CurveKeyKind},
+        },
+    };
+
+    fn add_keys(container: &mut TrackDataContainer, keys: &[(f32, Vector3<f32>)]) {
+        for (location, value) in keys {
+            for (i, curve) in container.curves_mut().iter_mut().enumerate() {
+                curve.add_key(CurveKey::new(*location, value[i], CurveKeyKind::Linear));
+            }
+        }
+    }
+
+    #[test]
+    fn test_make_additive_vector() {
+        let mut container = TrackDataContainer::new(TrackValueKind::Vector3);
+        add_keys(
+            &mut container,
+            &[
+                (0.0, Vector3::new(1.0, 2.0, 3.0)),
+                (1.0, Vector3::new(2.0, 4.0, 6.0)),
+            ],
+        );
+
+        container.make_additive(0.0);
+
+        assert_eq!(
+            container.fetch(0.0),
+            Some(TrackValue::Vector3(Vector3::new(0.0, 0.0, 0.0)))
+        );
+        assert_eq!(
+            container.fetch(1.0),
+            Some(TrackValue::Vector3(Vector3::new(1.0, 2.0, 3.0)))
+        );
+    }
+
+    #[test]
+    fn test_make_additive_rotation() {
+        let mut container = TrackDataContainer::new(TrackValueKind::UnitQuaternion);
+        add_keys(
+            &mut container,
+            &[
+                (0.0, Vector3::new(0.0, 0.5, 0.0)),
+                (1.0, Vector3::new(0.3, 1.0, 0.2)),
+            ],
+        );
+        let original = container.fetch(1.0).unwrap();
+        let reference = container.fetch(0.0).unwrap();
+
+        container.make_additive(0.0);
+
+        let mut restored = reference;
+        restored.add_with(&container.fetch(1.0).unwrap(), 1.0);
+        match (restored, original) {
+            (TrackValue::UnitQuaternion(restored), TrackValue::UnitQuaternion(original)) => {
+                assert!(restored.angle_to(&original) < 0.0001);
+            }
+            _ => unreachable!(),
+        }
+        match container.fetch(0.0) {
+            Some(TrackValue::UnitQuaternion(delta)) => {
+                assert!(delta.angle_to(&UnitQuaternion::identity()) < 0.0001);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_discrete_keys() {
+        let mut container = TrackDataContainer::new(TrackValueKind::Discrete);
+        container.add_discrete_key(0.0, DiscreteValue::String("Idle".to_string()));
+        container.add_discrete_key(1.0, DiscreteValue::String("Run".to_string()));
+        container.add_discrete_key(2.0, DiscreteValue::String("Idle".to_string()));
+
+        // Equal values share the same slot in the palette.
+        assert_eq!(container.discrete_values().len(), 2);
+        assert_eq!(
+            container.fetch(0.5),
+            Some(TrackValue::Discrete(DiscreteValue::String(
+                "Idle".to_string()
+            )))
+        );
+        assert_eq!(
+            container.fetch(1.5),
+            Some(TrackValue::Discrete(DiscreteValue::String(
+                "Run".to_string()
+            )))
+        );
+        assert_eq!(
+            container.fetch(2.5),
+            Some(TrackValue::Discrete(DiscreteValue::String(
+                "Idle".to_string()
+            )))
+        );
+    }
+}
@@ -0,0 +1,712 @@
+//! Inverse kinematics solvers that post-process final pose of an animation blending state machine. See [`IkSolver`]
+//! docs for more info.
+
+use crate::{
+    animation::{
+        machine::{Parameter, ParameterContainer, PoseWeight},
+        value::{BoundValue, TrackValue, ValueBinding},
+        AnimationPose, NodePose,
+    },
+    core::{
+        algebra::{Matrix3, Matrix4, Point3, Rotation3, UnitQuaternion, Vector3},
+        math::Matrix4Ext,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{
+        collider::InteractionGroups,
+        graph::{
+            physics::{PhysicsWorld, RayCastOptions},
+            NodePool,
+        },
+        node::Node,
+        transform::Transform,
+    },
+    utils::NameProvider,
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// A source of a world-space position, which the end of an IK chain will try to reach.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, EnumVariantNames, EnumString, AsRefStr)]
+pub enum IkTarget {
+    /// Fixed position in world coordinates. It could be changed from code every frame, for example to put
+    /// a hand on a door handle.
+    Position(Vector3<f32>),
+
+    /// Global position of a scene node.
+    Node(Handle<Node>),
+
+    /// Ground surface right below the animated position of the end of the chain. This target is used for
+    /// foot placement - a ray is cast downwards from the animated foot position and the foot is moved to the
+    /// intersection point (offset along the surface normal). If there's nothing under the foot, the chain
+    /// keeps its animated pose. Terrains could be used as the ground as well, as long as they have a
+    /// heightfield collider.
+    Ground {
+        /// A distance above the animated foot position from which the ray will be cast. It should be large
+        /// enough to find a ground that is higher than the animated foot (for example a step of stairs).
+        probe_height: f32,
+        /// A distance below the animated foot position, at which the ray cast will stop.
+        max_depth: f32,
+        /// A distance between the end of the chain (usually an ankle) and the ground.
+        foot_height: f32,
+        /// Collision groups, that will be used for ray casting. Use them to exclude the colliders of the
+        /// character itself.
+        collision_groups: InteractionGroups,
+    },
+}
+
+impl Default for IkTarget {
+    fn default() -> Self {
+        Self::Position(Default::default())
+    }
+}
+
+/// Analytic solver for chains of exactly three nodes (for example: hip, knee, ankle or shoulder, elbow, wrist).
+/// It is the fastest and the most stable solver, it should be preferred for limbs.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct TwoBoneIk {
+    /// The first node of the chain (for example a hip).
+    pub root: Handle<Node>,
+    /// The middle node of the chain (for example a knee). It must be a child of the root node.
+    pub middle: Handle<Node>,
+    /// The end of the chain (for example an ankle). It must be a child of the middle node.
+    pub end: Handle<Node>,
+    /// An optional node, that defines the direction in which the middle joint will bend. If not set, the
+    /// bend direction of the animated pose is preserved.
+    pub pole: Handle<Node>,
+}
+
+/// An arbitrary chain of nodes, that is solved iteratively.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct IkChain {
+    /// A list of nodes of the chain, starting from the root. Each node must be a child of the previous one.
+    pub nodes: Vec<Handle<Node>>,
+    /// Maximum amount of iterations of the solver.
+    pub iterations: u32,
+    /// Maximum distance between the end of the chain and the target at which the chain is considered solved.
+    pub tolerance: f32,
+}
+
+impl Default for IkChain {
+    fn default() -> Self {
+        Self {
+            nodes: Default::default(),
+            iterations: 10,
+            tolerance: 0.001,
+        }
+    }
+}
+
+/// Rotates a single node so its aim axis points towards a target. Could be used to make a character look at
+/// something or to aim a weapon.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct LookAtIk {
+    /// A node to rotate (for example a head).
+    pub node: Handle<Node>,
+    /// An axis in local coordinates of the node, that should point towards the target.
+    pub aim_axis: Vector3<f32>,
+    /// Maximum angle (in radians) by which the node could be rotated away from its animated orientation.
+    pub max_angle: f32,
+}
+
+impl Default for LookAtIk {
+    fn default() -> Self {
+        Self {
+            node: Default::default(),
+            aim_axis: Vector3::z(),
+            max_angle: std::f32::consts::FRAC_PI_2,
+        }
+    }
+}
+
+/// A method that will be used to solve an IK problem.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, EnumVariantNames, EnumString, AsRefStr)]
+pub enum IkSolverKind {
+    /// See [`TwoBoneIk`] docs.
+    TwoBone(TwoBoneIk),
+    /// Forward and backward reaching inverse kinematics. Converges quickly and produces natural poses for
+    /// long chains, such as tails or tentacles.
+    Fabrik(IkChain),
+    /// Cyclic coordinate descent. Tends to curl the chain towards its end.
+    Ccd(IkChain),
+    /// See [`LookAtIk`] docs.
+    LookAt(LookAtIk),
+}
+
+impl Default for IkSolverKind {
+    fn default() -> Self {
+        Self::TwoBone(Default::default())
+    }
+}
+
+/// Inverse kinematics solver modifies the final pose of a state machine, so the end of a chain of nodes reaches
+/// some target. Solvers are applied after the state machine has evaluated its pose and before the pose is
+/// applied to the scene graph, so the solvers always work on top of the current animation.
+///
+/// Every solver has a weight, which defines how much the solved pose will be blended with the animated one. The
+/// weight could be a constant or a `Weight` parameter of the state machine, this way the influence of a solver
+/// could be driven from game code - for example foot placement should be disabled when a character is jumping.
+///
+/// Solvers are applied in the order they're stored in the state machine, which means that a solver could take
+/// results of the previous ones into account (for example a look-at solver for a head after a spine chain).
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct IkSolver {
+    /// Name of the solver.
+    pub name: String,
+    /// Whether the solver is enabled or not.
+    pub enabled: bool,
+    /// Weight of the solver, where 0.0 means that the solver has no effect and 1.0 means that the solved pose
+    /// fully replaces the animated pose.
+    pub weight: PoseWeight,
+    /// A target for the solver.
+    pub target: IkTarget,
+    /// Actual solving method.
+    pub kind: IkSolverKind,
+}
+
+impl Default for IkSolver {
+    fn default() -> Self {
+        Self {
+            name: "IkSolver".to_string(),
+            enabled: true,
+            weight: PoseWeight::Constant(1.0),
+            target: Default::default(),
+            kind: Default::default(),
+        }
+    }
+}
+
+impl NameProvider for IkSolver {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+struct Joint {
+    node: Handle<Node>,
+    transform: Transform,
+    global: Matrix4<f32>,
+}
+
+struct Chain {
+    parent_rotation: UnitQuaternion<f32>,
+    joints: Vec<Joint>,
+}
+
+impl Chain {
+    fn positions(&self) -> Vec<Vector3<f32>> {
+        self.joints.iter().map(|j| j.global.position()).collect()
+    }
+}
+
+pub(crate) fn rotation_of(matrix: &Matrix4<f32>) -> UnitQuaternion<f32> {
+    let basis = matrix.basis();
+    let normalized = Matrix3::from_columns(&[
+        basis.column(0).normalize(),
+        basis.column(1).normalize(),
+        basis.column(2).normalize(),
+    ]);
+    UnitQuaternion::from(Rotation3::from_matrix_eps(
+        &normalized,
+        f32::EPSILON,
+        16,
+        Rotation3::identity(),
+    ))
+}
+
+/// Returns local transform of a node with the values of the pose applied to it.
+fn posed_local_transform(
+    nodes: &NodePool,
+    pose: &AnimationPose,
+    handle: Handle<Node>,
+) -> Option<Transform> {
+    let node = nodes.try_borrow(handle)?;
+    let mut transform = node.local_transform().clone();
+    if let Some(node_pose) = pose.poses().get(&handle) {
+        for bound_value in node_pose.values.values.iter() {
+            match (&bound_value.binding, &bound_value.value) {
+                (ValueBinding::Position, TrackValue::Vector3(position)) => {
+                    transform.set_position(*position);
+                }
+                (ValueBinding::Scale, TrackValue::Vector3(scale)) => {
+                    transform.set_scale(*scale);
+                }
+                (ValueBinding::Rotation, TrackValue::UnitQuaternion(rotation)) => {
+                    transform.set_rotation(*rotation);
+                }
+                _ => (),
+            }
+        }
+    }
+    Some(transform)
+}
+
+/// Calculates global transform of a node as if the pose was already applied to the graph. Global transforms of
+/// the graph could not be used directly, because they're one frame behind the pose.
+fn posed_global_transform(
+    nodes: &NodePool,
+    pose: &AnimationPose,
+    handle: Handle<Node>,
+) -> Matrix4<f32> {
+    let mut global = Matrix4::identity();
+    let mut current = handle;
+    while let Some(node) = nodes.try_borrow(current) {
+        if let Some(local) = posed_local_transform(nodes, pose, current) {
+            global = local.matrix() * global;
+        }
+        current = node.parent();
+    }
+    global
+}
+
+fn build_chain(nodes: &NodePool, pose: &AnimationPose, handles: &[Handle<Node>]) -> Option<Chain> {
+    let first = nodes.try_borrow(*handles.first()?)?;
+    let mut parent_global = posed_global_transform(nodes, pose, first.parent());
+    let parent_rotation = rotation_of(&parent_global);
+
+    let mut joints = Vec::with_capacity(handles.len());
+    for (i, handle) in handles.iter().enumerate() {
+        let node = nodes.try_borrow(*handle)?;
+        if i > 0 && node.parent() != handles[i - 1] {
+            // Chain is broken, solving it makes no sense.
+            return None;
+        }
+        let transform = posed_local_transform(nodes, pose, *handle)?;
+        let global = parent_global * transform.matrix();
+        parent_global = global;
+        joints.push(Joint {
+            node: *handle,
+            transform,
+            global,
+        });
+    }
+
+    Some(Chain {
+        parent_rotation,
+        joints,
+    })
+}
+
+fn set_pose_rotation(pose: &mut AnimationPose, node: Handle<Node>, rotation: UnitQuaternion<f32>) {
+    let node_pose = pose.poses_mut().entry(node).or_insert_with(|| NodePose {
+        node,
+        values: Default::default(),
+    });
+    if let Some(bound_value) = node_pose
+        .values
+        .values
+        .iter_mut()
+        .find(|v| v.binding == ValueBinding::Rotation)
+    {
+        bound_value.value = TrackValue::UnitQuaternion(rotation);
+    } else {
+        node_pose.values.values.push(BoundValue {
+            binding: ValueBinding::Rotation,
+            value: TrackValue::UnitQuaternion(rotation),
+        });
+    }
+}
+
+/// Converts a world-space rotation of a joint to a new local rotation of the joint.
+fn local_rotation(
+    joint: &Joint,
+    parent_rotation: UnitQuaternion<f32>,
+    world_delta: UnitQuaternion<f32>,
+) -> UnitQuaternion<f32> {
+    let basis = parent_rotation * **joint.transform.pre_rotation();
+    basis.inverse() * world_delta * basis * **joint.transform.rotation()
+}
+
+/// Rotates joints of the chain, so their origins match the given positions as close as possible. Only rotations
+/// are modified, bone lengths are never changed.
+fn apply_positions(
+    chain: &Chain,
+    positions: &[Vector3<f32>],
+    weight: f32,
+    pose: &mut AnimationPose,
+) {
+    let mut parent_rotation = chain.parent_rotation;
+    let mut accumulated = UnitQuaternion::identity();
+    for (i, pair) in chain.joints.windows(2).enumerate() {
+        let (joint, child) = (&pair[0], &pair[1]);
+
+        let current_direction = accumulated * (child.global.position() - joint.global.position());
+        let desired_direction = positions[i + 1] - positions[i];
+        let world_delta = UnitQuaternion::rotation_between(&current_direction, &desired_direction)
+            .unwrap_or_else(UnitQuaternion::identity);
+
+        let animated = **joint.transform.rotation();
+        let solved = local_rotation(joint, parent_rotation, world_delta);
+        let blended = animated.nlerp(&solved, weight);
+        set_pose_rotation(pose, joint.node, blended);
+
+        let new_global_rotation = parent_rotation
+            * **joint.transform.pre_rotation()
+            * blended
+            * **joint.transform.post_rotation();
+        accumulated = new_global_rotation * rotation_of(&joint.global).inverse();
+        parent_rotation = new_global_rotation;
+    }
+}
+
+fn solve_two_bone(
+    positions: &[Vector3<f32>],
+    target: Vector3<f32>,
+    pole: Option<Vector3<f32>>,
+) -> Vec<Vector3<f32>> {
+    let (a, b, c) = (positions[0], positions[1], positions[2]);
+    let upper_length = (b - a).norm();
+    let lower_length = (c - b).norm();
+    let total_length = upper_length + lower_length;
+    let to_target = target - a;
+    let distance = to_target.norm();
+    if upper_length <= f32::EPSILON || lower_length <= f32::EPSILON || distance <= f32::EPSILON {
+        return positions.to_vec();
+    }
+
+    let direction = to_target.scale(1.0 / distance);
+    let margin = total_length * 0.0001;
+    let distance = distance
+        .min(total_length - margin)
+        .max((upper_length - lower_length).abs() + margin);
+
+    let cos_angle = ((upper_length * upper_length + distance * distance
+        - lower_length * lower_length)
+        / (2.0 * upper_length * distance))
+        .clamp(-1.0, 1.0);
+    let sin_angle = (1.0 - cos_angle * cos_angle).sqrt();
+
+    let bend_hint = pole.map(|p| p - a).unwrap_or(b - a);
+    let bend = (bend_hint - direction.scale(bend_hint.dot(&direction)))
+        .try_normalize(f32::EPSILON)
+        .or_else(|| direction.cross(&Vector3::x()).try_normalize(f32::EPSILON))
+        .or_else(|| direction.cross(&Vector3::y()).try_normalize(f32::EPSILON))
+        .unwrap_or_else(Vector3::z);
+
+    vec![
+        a,
+        a + direction.scale(upper_length * cos_angle) + bend.scale(upper_length * sin_angle),
+        a + direction.scale(distance),
+    ]
+}
+
+fn bone_lengths(positions: &[Vector3<f32>]) -> Vec<f32> {
+    positions.windows(2).map(|p| (p[1] - p[0]).norm()).collect()
+}
+
+fn solve_fabrik(positions: &mut [Vector3<f32>], target: Vector3<f32>, chain: &IkChain) {
+    let count = positions.len();
+    let lengths = bone_lengths(positions);
+    let total_length = lengths.iter().sum::<f32>();
+    let root = positions[0];
+
+    if (target - root).norm() >= total_length {
+        // Target is unreachable, stretch the chain towards it.
+        let direction = (target - root)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default();
+        for i in 1..count {
+            positions[i] = positions[i - 1] + direction.scale(lengths[i - 1]);
+        }
+        return;
+    }
+
+    for _ in 0..chain.iterations {
+        if (positions[count - 1] - target).norm() <= chain.tolerance {
+            break;
+        }
+
+        // Backward pass.
+        positions[count - 1] = target;
+        for i in (0..count - 1).rev() {
+            let direction = (positions[i] - positions[i + 1])
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default();
+            positions[i] = positions[i + 1] + direction.scale(lengths[i]);
+        }
+
+        // Forward pass.
+        positions[0] = root;
+        for i in 1..count {
+            let direction = (positions[i] - positions[i - 1])
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default();
+            positions[i] = positions[i - 1] + direction.scale(lengths[i - 1]);
+        }
+    }
+}
+
+fn solve_ccd(positions: &mut [Vector3<f32>], target: Vector3<f32>, chain: &IkChain) {
+    let count = positions.len();
+    for _ in 0..chain.iterations {
+        if (positions[count - 1] - target).norm() <= chain.tolerance {
+            break;
+        }
+
+        for i in (0..count - 1).rev() {
+            let pivot = positions[i];
+            if let Some(rotation) =
+                UnitQuaternion::rotation_between(&(positions[count - 1] - pivot), &(target - pivot))
+            {
+                for position in positions[(i + 1)..].iter_mut() {
+                    *position = pivot + rotation * (*position - pivot);
+                }
+            }
+        }
+    }
+}
+
+impl IkSolver {
+    /// Sets new target of the solver.
+    pub fn set_target(&mut self, target: IkTarget) {
+        self.target = target;
+    }
+
+    fn weight(&self, parameters: &ParameterContainer) -> f32 {
+        let weight = match self.weight {
+            PoseWeight::Constant(value) => value,
+            PoseWeight::Parameter(ref param_id) => {
+                if let Some(Parameter::Weight(weight)) = parameters.get(param_id) {
+                    *weight
+                } else {
+                    0.0
+                }
+            }
+        };
+        weight.clamp(0.0, 1.0)
+    }
+
+    fn target_position(
+        &self,
+        nodes: &NodePool,
+        physics: &PhysicsWorld,
+        end_position: Vector3<f32>,
+    ) -> Option<Vector3<f32>> {
+        match self.target {
+            IkTarget::Position(position) => Some(position),
+            IkTarget::Node(node) => nodes.try_borrow(node).map(|n| n.global_position()),
+            IkTarget::Ground {
+                probe_height,
+                max_depth,
+                foot_height,
+                collision_groups,
+            } => {
+                let mut intersections = Vec::new();
+                physics.cast_ray(
+                    RayCastOptions {
+                        ray_origin: Point3::from(
+                            end_position + Vector3::new(0.0, probe_height, 0.0),
+                        ),
+                        ray_direction: Vector3::new(0.0, -1.0, 0.0),
+                        max_len: probe_height + max_depth,
+                        groups: collision_groups,
+                        sort_results: true,
+                    },
+                    &mut intersections,
+                );
+                intersections.first().map(|intersection| {
+                    intersection.position.coords + intersection.normal.scale(foot_height)
+                })
+            }
+        }
+    }
+
+    fn chain_nodes(&self) -> Vec<Handle<Node>> {
+        match self.kind {
+            IkSolverKind::TwoBone(ref two_bone) => {
+                vec![two_bone.root, two_bone.middle, two_bone.end]
+            }
+            IkSolverKind::Fabrik(ref chain) | IkSolverKind::Ccd(ref chain) => chain.nodes.clone(),
+            IkSolverKind::LookAt(ref look_at) => vec![look_at.node],
+        }
+    }
+
+    /// Modifies the given pose, so the chain of the solver will reach its target. Does nothing if the solver is
+    /// disabled, its weight is zero, or the chain is invalid.
+    pub fn solve(
+        &self,
+        pose: &mut AnimationPose,
+        nodes: &NodePool,
+        physics: &PhysicsWorld,
+        parameters: &ParameterContainer,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        let weight = self.weight(parameters);
+        if weight <= 0.0 {
+            return;
+        }
+
+        let chain = match build_chain(nodes, pose, &self.chain_nodes()) {
+            Some(chain) => chain,
+            None => return,
+        };
+
+        let mut positions = chain.positions();
+        let end_position = match positions.last() {
+            Some(position) => *position,
+            None => return,
+        };
+
+        let target = match self.target_position(nodes, physics, end_position) {
+            Some(target) => target,
+            None => return,
+        };
+
+        match self.kind {
+            IkSolverKind::TwoBone(ref two_bone) => {
+                let pole = nodes
+                    .try_borrow(two_bone.pole)
+                    .map(|pole| pole.global_position());
+                let solved = solve_two_bone(&positions, target, pole);
+                apply_positions(&chain, &solved, weight, pose);
+            }
+            IkSolverKind::Fabrik(ref ik_chain) => {
+                if positions.len() > 1 {
+                    solve_fabrik(&mut positions, target, ik_chain);
+                    apply_positions(&chain, &positions, weight, pose);
+                }
+            }
+            IkSolverKind::Ccd(ref ik_chain) => {
+                if positions.len() > 1 {
+                    solve_ccd(&mut positions, target, ik_chain);
+                    apply_positions(&chain, &positions, weight, pose);
+                }
+            }
+            IkSolverKind::LookAt(ref look_at) => {
+                let joint = &chain.joints[0];
+                let aim_direction = rotation_of(&joint.global) * look_at.aim_axis;
+                let mut world_delta =
+                    UnitQuaternion::rotation_between(&aim_direction, &(target - end_position))
+                        .unwrap_or_else(UnitQuaternion::identity);
+                if let Some((axis, angle)) = world_delta.axis_angle() {
+                    if angle > look_at.max_angle {
+                        world_delta = UnitQuaternion::from_axis_angle(&axis, look_at.max_angle);
+                    }
+                }
+                let animated = **joint.transform.rotation();
+                let solved = local_rotation(joint, chain.parent_rotation, world_delta);
+                set_pose_rotation(pose, joint.node, animated.nlerp(&solved, weight));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder, graph::Graph, pivot::PivotBuilder, transform::TransformBuilder,
+        },
+    };
+
+    fn make_leg(graph: &mut Graph) -> (Handle<Node>, Handle<Node>, Handle<Node>) {
+        let end = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, -1.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(graph);
+        let middle = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_children(&[end])
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -1.0, 0.0))
+                        .build(),
+                ),
+        )
+        .build(graph);
+        let root = PivotBuilder::new(BaseBuilder::new().with_children(&[middle])).build(graph);
+        (root, middle, end)
+    }
+
+    fn solve_and_apply(graph: &mut Graph, solver: &IkSolver) {
+        let mut pose = AnimationPose::default();
+        solver.solve(
+            &mut pose,
+            graph.node_pool(),
+            &graph.physics,
+            &ParameterContainer::default(),
+        );
+        pose.apply(graph);
+        graph.update_hierarchical_data();
+    }
+
+    #[test]
+    fn test_two_bone_reaches_target() {
+        let mut graph = Graph::new();
+        let (root, middle, end) = make_leg(&mut graph);
+        graph.update_hierarchical_data();
+
+        let target = Vector3::new(0.5, -1.2, 0.5);
+        let solver = IkSolver {
+            target: IkTarget::Position(target),
+            kind: IkSolverKind::TwoBone(TwoBoneIk {
+                root,
+                middle,
+                end,
+                pole: Default::default(),
+            }),
+            ..Default::default()
+        };
+        solve_and_apply(&mut graph, &solver);
+
+        assert!((graph[end].global_position() - target).norm() < 0.001);
+        // Bone lengths must be preserved.
+        assert!(
+            ((graph[middle].global_position() - graph[root].global_position()).norm() - 1.0).abs()
+                < 0.001
+        );
+    }
+
+    #[test]
+    fn test_fabrik_reaches_target() {
+        let mut graph = Graph::new();
+        let (root, middle, end) = make_leg(&mut graph);
+        graph.update_hierarchical_data();
+
+        let target = Vector3::new(1.0, -1.0, 0.0);
+        let solver = IkSolver {
+            target: IkTarget::Position(target),
+            kind: IkSolverKind::Fabrik(IkChain {
+                nodes: vec![root, middle, end],
+                iterations: 32,
+                tolerance: 0.0001,
+            }),
+            ..Default::default()
+        };
+        solve_and_apply(&mut graph, &solver);
+
+        assert!((graph[end].global_position() - target).norm() < 0.01);
+    }
+
+    #[test]
+    fn test_zero_weight_keeps_pose() {
+        let mut graph = Graph::new();
+        let (root, middle, end) = make_leg(&mut graph);
+        graph.update_hierarchical_data();
+
+        let solver = IkSolver {
+            weight: PoseWeight::Constant(0.0),
+            target: IkTarget::Position(Vector3::new(1.0, -1.0, 0.0)),
+            kind: IkSolverKind::TwoBone(TwoBoneIk {
+                root,
+                middle,
+                end,
+                pole: Default::default(),
+            }),
+            ..Default::default()
+        };
+        solve_and_apply(&mut graph, &solver);
+
+        assert_eq!(graph[end].global_position(), Vector3::new(0.0, -2.0, 0.0));
+    }
+}
@@ -4,8 +4,8 @@
 use crate::{
     animation::{
         machine::{
-            event::FixedEventQueue, Event, LayerMask, ParameterContainer, PoseNode, State,
-            Transition,
+            event::FixedEventQueue, transition::TransitionInterruption, Event, LayerMask,
+            ParameterContainer, PoseNode, State, Transition,
         },
         Animation, AnimationContainer, AnimationPose,
     },
@@ -17,6 +17,116 @@ use crate::{
     },
     utils::{self, NameProvider},
 };
+use fxhash::FxHashMap;
+use std::{cell::Ref, cmp::Ordering, ops::Deref};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Defines how the pose of a layer is combined with the poses of the previous layers.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Visit,
+    Reflect,
+    EnumVariantNames,
+    EnumString,
+    AsRefStr,
+)]
+pub enum LayerBlendMode {
+    /// The pose of the layer is blended with the poses of the previous layers using the weight of the layer,
+    /// values of the layer override the values of the previous layers (with weight of 1.0).
+    #[default]
+    Override,
+
+    /// The pose of the layer is added on top of the poses of the previous layers using the weight of the layer.
+    /// Animations of such layer must be additive (see [`crate::animation::Animation::make_additive`]). Additive
+    /// layers are useful for breathing, recoil, leaning and similar animations, that should work on top of any
+    /// other animation.
+    Additive,
+}
+
+// Normalized time position of an animation, that keeps growing when a looping animation wraps around. It is
+// used to check exit time of transitions, so a transition could leave a looping state after a full cycle.
+#[derive(Default, Debug, Clone, PartialEq)]
+struct AnimationProgress {
+    time: f32,
+    loops: u32,
+}
+
+impl AnimationProgress {
+    fn unwrapped_time(&self) -> f32 {
+        self.loops as f32 + self.time
+    }
+}
+
+fn normalized_time_of_animation(animation: &Animation) -> f32 {
+    let length = animation.length();
+    if length > 0.0 {
+        (animation.time_position() - animation.time_slice().start) / length
+    } else {
+        1.0
+    }
+}
+
+// Pose of a state - either a pose of its root node, or a pose of its sub-machine.
+enum StatePose<'a> {
+    Node(Ref<'a, AnimationPose>),
+    SubMachine(&'a AnimationPose),
+}
+
+impl<'a> Deref for StatePose<'a> {
+    type Target = AnimationPose;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            StatePose::Node(pose) => pose,
+            StatePose::SubMachine(pose) => pose,
+        }
+    }
+}
+
+/// Poses of the layers of a state machine, that are available for a layer that is being evaluated. The layer
+/// itself is excluded, so the rest layers are split into two parts - before and after the layer.
+pub(super) struct SubMachinePoses<'a> {
+    before: &'a [MachineLayer],
+    after: &'a [MachineLayer],
+}
+
+impl<'a> SubMachinePoses<'a> {
+    pub(super) fn new(before: &'a [MachineLayer], after: &'a [MachineLayer]) -> Self {
+        Self { before, after }
+    }
+
+    fn get(&self, layer_index: usize) -> Option<&'a AnimationPose> {
+        match layer_index.cmp(&self.before.len()) {
+            Ordering::Less => Some(self.before[layer_index].pose()),
+            // A layer cannot be a sub-machine of itself.
+            Ordering::Equal => None,
+            Ordering::Greater => self
+                .after
+                .get(layer_index - self.before.len() - 1)
+                .map(|layer| layer.pose()),
+        }
+    }
+}
+
+fn state_pose<'a>(
+    states: &Pool<State>,
+    nodes: &'a Pool<PoseNode>,
+    sub_machine_poses: &SubMachinePoses<'a>,
+    state: Handle<State>,
+) -> Option<StatePose<'a>> {
+    let state = states.try_borrow(state)?;
+    match state.sub_machine {
+        Some(sub_machine) => sub_machine_poses
+            .get(sub_machine)
+            .map(StatePose::SubMachine),
+        None => state.pose(nodes).map(StatePose::Node),
+    }
+}
 
 /// Layer is a separate state graph. Layers mainly used to animate different parts of humanoid (but not only) characters. For
 /// example there could a layer for upper body and a layer for lower body. Upper body layer could contain animations for aiming,
@@ -70,6 +180,9 @@ pub struct MachineLayer {
 
     mask: LayerMask,
 
+    #[visit(optional)]
+    blend_mode: LayerBlendMode,
+
     #[reflect(hidden)]
     nodes: Pool<PoseNode>,
 
@@ -88,6 +201,22 @@ pub struct MachineLayer {
     #[reflect(hidden)]
     active_transition: Handle<Transition>,
 
+    #[visit(optional)]
+    #[reflect(hidden)]
+    transition_source: Handle<State>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    interrupted_pose: Option<AnimationPose>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    entered_sub_machines: Vec<usize>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    animation_progress: FxHashMap<Handle<Animation>, AnimationProgress>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     final_pose: AnimationPose,
@@ -120,10 +249,15 @@ impl MachineLayer {
             active_state: Default::default(),
             entry_state: Default::default(),
             active_transition: Default::default(),
+            transition_source: Default::default(),
+            interrupted_pose: None,
+            entered_sub_machines: Default::default(),
+            animation_progress: Default::default(),
             weight: 1.0,
             events: FixedEventQueue::new(2048),
             debug: false,
             mask: Default::default(),
+            blend_mode: Default::default(),
         }
     }
 
@@ -234,6 +368,10 @@ impl MachineLayer {
         }
 
         self.active_state = self.entry_state;
+        self.active_transition = Handle::NONE;
+        self.transition_source = Handle::NONE;
+        self.interrupted_pose = None;
+        self.animation_progress.clear();
     }
 
     /// Tries to borrow a node by its handle, panics if the handle is invalid.
@@ -384,6 +522,18 @@ impl MachineLayer {
         &self.mask
     }
 
+    /// Sets new blend mode of the layer. See [`LayerBlendMode`] docs for more info.
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: LayerBlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Returns current blend mode of the layer.
+    #[inline]
+    pub fn blend_mode(&self) -> LayerBlendMode {
+        self.blend_mode
+    }
+
     /// Returns final pose of the layer.
     #[inline]
     pub fn pose(&self) -> &AnimationPose {
@@ -421,11 +571,184 @@ impl MachineLayer {
             .all(|a| a.has_ended())
     }
 
+    // Normalized time position of the "slowest" animation of the given state. Unlike time position of an
+    // animation, it is not wrapped for looping animations, so it could be larger than 1.0.
+    fn normalized_time_of_state(
+        &self,
+        state: Handle<State>,
+        animations: &AnimationContainer,
+    ) -> f32 {
+        self.animations_of_state(state)
+            .filter_map(|a| {
+                animations.try_get(a).map(|animation| {
+                    self.animation_progress.get(&a).map_or_else(
+                        || normalized_time_of_animation(animation),
+                        |p| p.unwrapped_time(),
+                    )
+                })
+            })
+            .fold(None, |min: Option<f32>, time| {
+                Some(min.map_or(time, |min| min.min(time)))
+            })
+            .unwrap_or(1.0)
+    }
+
+    fn update_animation_progress(&mut self, animations: &AnimationContainer) {
+        for node in self.nodes.iter() {
+            if let PoseNode::PlayAnimation(play_animation) = node {
+                if let Some(animation) = animations.try_get(play_animation.animation) {
+                    let time = normalized_time_of_animation(animation);
+                    let progress = self
+                        .animation_progress
+                        .entry(play_animation.animation)
+                        .or_insert_with(|| AnimationProgress { time, loops: 0 });
+                    // A looping animation has wrapped around if its time went in the opposite direction.
+                    let wrapped = if animation.speed() >= 0.0 {
+                        time < progress.time
+                    } else {
+                        time > progress.time
+                    };
+                    if animation.is_loop() && wrapped {
+                        progress.loops += 1;
+                    }
+                    progress.time = time;
+                }
+            }
+        }
+    }
+
+    fn find_transition(
+        &self,
+        from: Handle<State>,
+        exclude: Handle<Transition>,
+        parameters: &ParameterContainer,
+        animations: &AnimationContainer,
+    ) -> Option<Handle<Transition>> {
+        if from.is_none() {
+            return None;
+        }
+
+        let source_time = self.normalized_time_of_state(from, animations);
+
+        self.transitions
+            .pair_iter()
+            .find(|(handle, transition)| {
+                *handle != exclude
+                    && transition.can_start_from(from)
+                    && transition.is_ready(parameters, animations, source_time)
+            })
+            .map(|(handle, _)| handle)
+    }
+
+    fn find_interrupting_transition(
+        &self,
+        parameters: &ParameterContainer,
+        animations: &AnimationContainer,
+    ) -> Option<Handle<Transition>> {
+        let active_transition = &self.transitions[self.active_transition];
+        let source = self.transition_source(active_transition);
+        let sources = match active_transition.interruption() {
+            TransitionInterruption::None => return None,
+            TransitionInterruption::SourceState => [source, Handle::NONE],
+            TransitionInterruption::DestinationState => [active_transition.dest(), Handle::NONE],
+            TransitionInterruption::SourceThenDestination => [source, active_transition.dest()],
+        };
+
+        sources.iter().find_map(|from| {
+            self.find_transition(*from, self.active_transition, parameters, animations)
+                .filter(|t| self.transitions[*t].dest() != active_transition.dest())
+        })
+    }
+
+    fn transition_source(&self, transition: &Transition) -> Handle<State> {
+        if self.transition_source.is_some() {
+            self.transition_source
+        } else {
+            transition.source()
+        }
+    }
+
+    fn begin_transition(
+        &mut self,
+        handle: Handle<Transition>,
+        from: Handle<State>,
+        animations: &mut AnimationContainer,
+    ) {
+        let dest = self.transitions[handle].dest();
+
+        if let Some(from_state) = self.states.try_borrow(from) {
+            for action in from_state.on_leave_actions.iter() {
+                action.apply(animations);
+            }
+
+            self.events.push(Event::StateLeave(from));
+            if self.debug {
+                Log::writeln(
+                    MessageKind::Information,
+                    format!("Leaving state: {}", from_state.name),
+                );
+            }
+        }
+
+        if let Some(dest_state) = self.states.try_borrow(dest) {
+            for action in dest_state.on_enter_actions.iter() {
+                action.apply(animations);
+            }
+
+            if let Some(sub_machine) = dest_state.sub_machine {
+                self.entered_sub_machines.push(sub_machine);
+            }
+
+            self.events.push(Event::StateEnter(dest));
+            if self.debug {
+                Log::writeln(
+                    MessageKind::Information,
+                    format!("Entering state: {}", dest_state.name),
+                );
+            }
+        }
+
+        // Exit time of the transitions from the state is counted from the moment of entering.
+        for animation in self.animations_of_state(dest).collect::<Vec<_>>() {
+            self.animation_progress.remove(&animation);
+        }
+
+        self.active_state = Handle::NONE;
+        self.transition_source = from;
+
+        self.active_transition = handle;
+        self.events
+            .push(Event::ActiveTransitionChanged(self.active_transition));
+    }
+
+    pub(super) fn take_entered_sub_machines(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.entered_sub_machines)
+    }
+
+    // Changes indices of sub-machine layers of every state, `None` returned by the closure unlinks the sub-machine.
+    pub(super) fn remap_sub_machines<F>(&mut self, remap: &mut F)
+    where
+        F: FnMut(usize) -> Option<usize>,
+    {
+        for state in self.states.iter_mut() {
+            if let Some(sub_machine) = state.sub_machine {
+                state.sub_machine = remap(sub_machine);
+            }
+        }
+
+        self.entered_sub_machines = self
+            .entered_sub_machines
+            .iter()
+            .filter_map(|sub_machine| remap(*sub_machine))
+            .collect();
+    }
+
     #[inline]
     pub(super) fn evaluate_pose(
         &mut self,
         animations: &mut AnimationContainer,
         parameters: &ParameterContainer,
+        sub_machine_poses: &SubMachinePoses,
         dt: f32,
     ) -> &AnimationPose {
         self.final_pose.reset();
@@ -436,84 +759,63 @@ impl MachineLayer {
                 state.update(&self.nodes, parameters, animations, dt);
             }
 
-            if self.active_transition.is_none() {
-                // Find transition.
-                for (handle, transition) in self.transitions.pair_iter_mut() {
-                    if transition.dest() == self.active_state
-                        || transition.source() != self.active_state
-                    {
-                        continue;
-                    }
+            self.update_animation_progress(animations);
 
-                    if transition.condition.calculate_value(parameters, animations) {
-                        if let Some(active_state) = self.states.try_borrow(self.active_state) {
-                            for action in active_state.on_leave_actions.iter() {
-                                action.apply(animations);
-                            }
-                        }
-
-                        self.events.push(Event::StateLeave(self.active_state));
-                        if self.debug {
-                            Log::writeln(
-                                MessageKind::Information,
-                                format!("Leaving state: {}", self.states[self.active_state].name),
-                            );
-                        }
-
-                        if let Some(source) = self.states.try_borrow(transition.dest()) {
-                            for action in source.on_enter_actions.iter() {
-                                action.apply(animations);
-                            }
-                        }
-
-                        self.events.push(Event::StateEnter(transition.dest()));
-                        if self.debug {
-                            Log::writeln(
-                                MessageKind::Information,
-                                format!("Entering state: {}", self.states[transition.dest()].name),
-                            );
-                        }
-
-                        self.active_state = Handle::NONE;
-
-                        self.active_transition = handle;
-                        self.events
-                            .push(Event::ActiveTransitionChanged(self.active_transition));
-
-                        break;
-                    }
+            if self.active_transition.is_none() {
+                if let Some(transition) =
+                    self.find_transition(self.active_state, Handle::NONE, parameters, animations)
+                {
+                    self.begin_transition(transition, self.active_state, animations);
                 }
             }
 
             // Double check for active transition because we can have empty machine.
             if self.active_transition.is_some() {
-                let transition = &mut self.transitions[self.active_transition];
+                let transition = &self.transitions[self.active_transition];
+                let source = self.transition_source(transition);
 
-                // Blend between source and dest states.
-                if let Some(source_pose) = self.states[transition.source()].pose(&self.nodes) {
+                // Blend between source and dest states. Interrupted transitions blend from the pose, that was
+                // frozen at the moment of interruption.
+                if let Some(interrupted_pose) = self.interrupted_pose.as_ref() {
+                    self.final_pose
+                        .blend_with(interrupted_pose, 1.0 - transition.blend_factor());
+                } else if let Some(source_pose) =
+                    state_pose(&self.states, &self.nodes, sub_machine_poses, source)
+                {
                     self.final_pose
                         .blend_with(&source_pose, 1.0 - transition.blend_factor());
                 }
-                if let Some(dest_pose) = self.states[transition.dest()].pose(&self.nodes) {
+                if let Some(dest_pose) = state_pose(
+                    &self.states,
+                    &self.nodes,
+                    sub_machine_poses,
+                    transition.dest(),
+                ) {
                     self.final_pose
                         .blend_with(&dest_pose, transition.blend_factor());
                 }
 
+                let transition = &mut self.transitions[self.active_transition];
                 transition.update(dt);
 
                 if transition.is_done() {
                     transition.reset();
 
+                    let dest = transition.dest();
+
                     self.active_transition = Handle::NONE;
                     self.events
                         .push(Event::ActiveTransitionChanged(self.active_transition));
 
-                    self.active_state = transition.dest();
+                    self.active_state = dest;
                     self.events.push(Event::ActiveStateChanged {
-                        prev: transition.source(),
-                        new: transition.dest(),
+                        prev: source,
+                        new: dest,
                     });
 
+                    self.interrupted_pose = None;
+                    self.transition_source = Handle::NONE;
+
                     if self.debug {
                         Log::writeln(
                             MessageKind::Information,
@@ -523,13 +825,23 @@ impl MachineLayer {
                             ),
                         );
                     }
+                } else if let Some(interrupting) =
+                    self.find_interrupting_transition(parameters, animations)
+                {
+                    let from = self.transitions[self.active_transition].dest();
+                    self.transitions[self.active_transition].reset();
+                    self.interrupted_pose = Some(self.final_pose.clone());
+                    self.begin_transition(interrupting, from, animations);
                 }
-            } else {
+            } else if let Some(active_state_pose) = state_pose(
+                &self.states,
+                &self.nodes,
+                sub_machine_poses,
+                self.active_state,
+            ) {
                 // We must have active state all the time when we do not have any active transition.
                 // Just get pose from active state.
-                if let Some(active_state_pose) = self.states[self.active_state].pose(&self.nodes) {
-                    active_state_pose.clone_into(&mut self.final_pose);
-                }
+                active_state_pose.clone_into(&mut self.final_pose);
             }
         }
 
@@ -540,3 +852,155 @@ impl MachineLayer {
         &self.final_pose
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            container::{TrackDataContainer, TrackValueKind},
+            machine::{
+                layer::SubMachinePoses, MachineLayer, Parameter, ParameterContainer, PlayAnimation,
+                PoseNode, State, Transition, TransitionInterruption,
+            },
+            track::Track,
+            value::ValueBinding,
+            Animation, AnimationContainer,
+        },
+        core::{
+            curve::{CurveKey, CurveKeyKind},
+            pool::Handle,
+        },
+        scene::node::Node,
+    };
+
+    // One second long looping animation of the given node.
+    fn make_animation(target: Handle<Node>) -> Animation {
+        let mut position = TrackDataContainer::new(TrackValueKind::Vector3);
+        for curve in position.curves_mut() {
+            curve.add_key(CurveKey::new(0.0, 0.0, CurveKeyKind::Linear));
+            curve.add_key(CurveKey::new(1.0, 1.0, CurveKeyKind::Linear));
+        }
+        let mut track = Track::new(position, ValueBinding::Position);
+        track.set_target(target);
+
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation.fit_length_to_content();
+        animation
+    }
+
+    fn add_state(
+        layer: &mut MachineLayer,
+        animations: &mut AnimationContainer,
+        name: &str,
+    ) -> Handle<State> {
+        let animation = animations.add(make_animation(Handle::new(1, 1)));
+        let node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(animation)));
+        let state = layer.add_state(State::new(name, node));
+        layer.node_mut(node).parent_state = state;
+        state
+    }
+
+    fn step(
+        layer: &mut MachineLayer,
+        animations: &mut AnimationContainer,
+        parameters: &ParameterContainer,
+        dt: f32,
+    ) {
+        for animation in animations.iter_mut() {
+            animation.tick(dt);
+        }
+        layer.evaluate_pose(animations, parameters, &SubMachinePoses::new(&[], &[]), dt);
+    }
+
+    #[test]
+    fn test_exit_time_of_looping_state() {
+        let mut animations = AnimationContainer::new();
+        let mut layer = MachineLayer::new();
+        let idle = add_state(&mut layer, &mut animations, "Idle");
+        let fidget = add_state(&mut layer, &mut animations, "Fidget");
+
+        // The transition is driven by the exit time only, the default exit time is the end of the first cycle.
+        let mut transition = Transition::new("IdleToFidget", idle, fidget, 0.5, "");
+        transition.set_has_exit_time(true);
+        let transition = layer.add_transition(transition);
+
+        let parameters = ParameterContainer::default();
+        for _ in 0..3 {
+            step(&mut layer, &mut animations, &parameters, 0.3);
+            assert_eq!(layer.active_state(), idle);
+            assert!(layer.active_transition().is_none());
+        }
+
+        // The animation wraps around on this step.
+        step(&mut layer, &mut animations, &parameters, 0.3);
+        assert_eq!(layer.active_transition(), transition);
+    }
+
+    #[test]
+    fn test_any_state_transition() {
+        let mut animations = AnimationContainer::new();
+        let mut layer = MachineLayer::new();
+        let idle = add_state(&mut layer, &mut animations, "Idle");
+        let walk = add_state(&mut layer, &mut animations, "Walk");
+        let death = add_state(&mut layer, &mut animations, "Death");
+
+        layer.add_transition(Transition::new("IdleToWalk", idle, walk, 0.1, "Walk"));
+        let mut any_to_death = Transition::new("AnyToDeath", Handle::NONE, death, 0.1, "Dead");
+        any_to_death.set_from_any_state(true);
+        layer.add_transition(any_to_death);
+
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Walk", Parameter::Rule(true));
+        parameters.add("Dead", Parameter::Rule(false));
+
+        step(&mut layer, &mut animations, &parameters, 0.3);
+        assert_eq!(layer.active_state(), walk);
+
+        *parameters.get_mut("Dead").unwrap() = Parameter::Rule(true);
+        step(&mut layer, &mut animations, &parameters, 0.3);
+        assert_eq!(layer.active_state(), death);
+
+        // Any-state transition must not be activated from its destination state.
+        step(&mut layer, &mut animations, &parameters, 0.3);
+        assert_eq!(layer.active_state(), death);
+        assert!(layer.active_transition().is_none());
+    }
+
+    #[test]
+    fn test_transition_interruption() {
+        for (interruption, interrupted) in [
+            (TransitionInterruption::None, false),
+            (TransitionInterruption::SourceState, true),
+        ] {
+            let mut animations = AnimationContainer::new();
+            let mut layer = MachineLayer::new();
+            let idle = add_state(&mut layer, &mut animations, "Idle");
+            let walk = add_state(&mut layer, &mut animations, "Walk");
+            let run = add_state(&mut layer, &mut animations, "Run");
+
+            let mut idle_to_walk = Transition::new("IdleToWalk", idle, walk, 1.0, "Walk");
+            idle_to_walk.set_interruption(interruption);
+            let idle_to_walk = layer.add_transition(idle_to_walk);
+            let idle_to_run =
+                layer.add_transition(Transition::new("IdleToRun", idle, run, 1.0, "Run"));
+
+            let mut parameters = ParameterContainer::default();
+            parameters.add("Walk", Parameter::Rule(true));
+            parameters.add("Run", Parameter::Rule(false));
+
+            step(&mut layer, &mut animations, &parameters, 0.1);
+            assert_eq!(layer.active_transition(), idle_to_walk);
+
+            *parameters.get_mut("Run").unwrap() = Parameter::Rule(true);
+            step(&mut layer, &mut animations, &parameters, 0.1);
+            if interrupted {
+                assert_eq!(layer.active_transition(), idle_to_run);
+                // Interrupted transition must start over next time.
+                assert_eq!(layer.transition(idle_to_walk).blend_factor(), 0.0);
+            } else {
+                assert_eq!(layer.active_transition(), idle_to_walk);
+            }
+        }
+    }
+}
@@ -11,22 +11,28 @@ use crate::{
         reflect::prelude::*,
         visitor::{Visit, VisitResult, Visitor},
     },
+    scene::graph::{physics::PhysicsWorld, NodePool},
     utils,
 };
+use layer::SubMachinePoses;
+use std::cmp::Ordering;
 
 pub use event::Event;
-pub use layer::MachineLayer;
+pub use ik::{IkChain, IkSolver, IkSolverKind, IkTarget, LookAtIk, TwoBoneIk};
+pub use layer::{LayerBlendMode, MachineLayer};
 pub use mask::LayerMask;
 pub use node::{
-    blend::{BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput},
+    blend::{BlendAdditive, BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput},
+    motion::{MotionDatabase, MotionMatching, MotionMatchingWeights},
     play::PlayAnimation,
     EvaluatePose, PoseNode,
 };
 pub use parameter::{Parameter, ParameterContainer, PoseWeight};
 pub use state::State;
-pub use transition::Transition;
+pub use transition::{Transition, TransitionInterruption};
 
 pub mod event;
+pub mod ik;
 pub mod layer;
 pub mod mask;
 pub mod node;
@@ -44,14 +50,19 @@ pub mod transition;
 /// will be used in parent state.
 /// `State` - is a final source of animation for blending. There could be any number of states, for example typical
 /// states are: `run`, `idle`, `jump` etc. A state could be marked as _entry_ state - it will be active at the first frame
-/// when using the machine. There is always one state active.
+/// when using the machine. There is always one state active. A state could use another layer as its source of
+/// animation (a sub-state-machine), see [`State::sub_machine`] for more info.
 /// `Transition` - is a connection between states that has transition time, a link to a parameter that defines whether the
 /// transition should be performed or not. Transition is directional; there could be any number of transitions between any
-/// number of states (loops are allowed).
+/// number of states (loops are allowed). A transition could also be marked as "any state" transition, it could be then
+/// activated from any state of a layer (see [`Transition`] docs for more info about exit time and interruption rules).
 /// `Parameter` - is a named variable of a fixed type (see `Parameters` section for more info).
-/// `Layer` - is a separate state graph, there could be any number of layers - each with its own mask.
+/// `Layer` - is a separate state graph, there could be any number of layers - each with its own mask. Layers could either
+/// override poses of the previous layers or be added on top of them (see [`LayerBlendMode`]).
 /// `Mask` - a set of handles to nodes which will be excluded from animation on a layer.
 /// `Pose` - a final result of blending multiple animation into one.
+/// `IK solver` - a post-processing step that modifies the final pose, so a chain of nodes reaches some target (see
+/// [`IkSolver`] docs for more info).
 ///
 /// Summarizing everything of this, we can describe animation blending state machine as a state graph, where each state has its
 /// own sub-graph (tree) that provides animation for blending. States can be connected via transitions.
@@ -173,9 +184,23 @@ pub struct Machine {
     #[visit(optional)]
     layers: Vec<MachineLayer>,
 
+    #[visit(optional)]
+    ik_solvers: Vec<IkSolver>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     final_pose: AnimationPose,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    evaluation_order: Option<EvaluationOrder>,
+}
+
+// Order of evaluation of layers. It is cached, because it depends only on the sub-machines of states.
+#[derive(Default, Debug, Clone, PartialEq)]
+struct EvaluationOrder {
+    layers: Vec<usize>,
+    is_sub_machine: Vec<bool>,
 }
 
 impl Machine {
@@ -185,7 +210,9 @@ impl Machine {
         Self {
             parameters: Default::default(),
             layers: vec![MachineLayer::new()],
+            ik_solvers: Default::default(),
             final_pose: Default::default(),
+            evaluation_order: None,
         }
     }
 
@@ -230,25 +257,56 @@ impl Machine {
     /// Adds a new layer to the animation blending state machine.
     #[inline]
     pub fn add_layer(&mut self, layer: MachineLayer) {
+        self.evaluation_order = None;
         self.layers.push(layer)
     }
 
-    /// Removes a layer at given index. Panics if index is out-of-bounds.
+    /// Removes a layer at given index. Panics if index is out-of-bounds. States, that use the layer as a
+    /// sub-machine, lose their sub-machine. Sub-machine indices of other states are shifted accordingly.
     #[inline]
     pub fn remove_layer(&mut self, index: usize) -> MachineLayer {
-        self.layers.remove(index)
+        self.evaluation_order = None;
+        let layer = self.layers.remove(index);
+        self.remap_sub_machines(|sub_machine| match sub_machine.cmp(&index) {
+            Ordering::Less => Some(sub_machine),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(sub_machine - 1),
+        });
+        layer
     }
 
-    /// Inserts a layer at given position, panics in index is out-of-bounds.
+    /// Inserts a layer at given position, panics in index is out-of-bounds. Sub-machine indices of the states
+    /// of existing layers are shifted accordingly.
     #[inline]
     pub fn insert_layer(&mut self, index: usize, layer: MachineLayer) {
+        self.evaluation_order = None;
+        self.remap_sub_machines(|sub_machine| {
+            if sub_machine >= index {
+                Some(sub_machine + 1)
+            } else {
+                Some(sub_machine)
+            }
+        });
         self.layers.insert(index, layer)
     }
 
-    /// Removes last layer from the list.
+    /// Removes last layer from the list. States, that use the layer as a sub-machine, lose their sub-machine.
     #[inline]
     pub fn pop_layer(&mut self) -> Option<MachineLayer> {
-        self.layers.pop()
+        self.evaluation_order = None;
+        let layer = self.layers.pop();
+        let count = self.layers.len();
+        self.remap_sub_machines(|sub_machine| (sub_machine < count).then_some(sub_machine));
+        layer
+    }
+
+    fn remap_sub_machines<F>(&mut self, mut remap: F)
+    where
+        F: FnMut(usize) -> Option<usize>,
+    {
+        for layer in self.layers.iter_mut() {
+            layer.remap_sub_machines(&mut remap);
+        }
     }
 
     /// Returns a shared reference to the list of layers.
@@ -260,6 +318,8 @@ impl Machine {
     /// Returns a mutable reference to the list of layers.
     #[inline]
     pub fn layers_mut(&mut self) -> &mut [MachineLayer] {
+        // States could be changed, so the evaluation order must be rebuilt.
+        self.evaluation_order = None;
         &mut self.layers
     }
 
@@ -278,13 +338,89 @@ impl Machine {
         utils::find_by_name_mut(self.layers.iter_mut().enumerate(), name)
     }
 
+    /// Adds a new IK solver to the machine. Solvers are applied in the order of addition.
+    #[inline]
+    pub fn add_ik_solver(&mut self, solver: IkSolver) {
+        self.ik_solvers.push(solver)
+    }
+
+    /// Removes an IK solver at the given index.
+    #[inline]
+    pub fn remove_ik_solver(&mut self, index: usize) -> IkSolver {
+        self.ik_solvers.remove(index)
+    }
+
+    /// Returns a shared reference to the list of IK solvers.
+    #[inline]
+    pub fn ik_solvers(&self) -> &[IkSolver] {
+        &self.ik_solvers
+    }
+
+    /// Returns a mutable reference to the list of IK solvers.
+    #[inline]
+    pub fn ik_solvers_mut(&mut self) -> &mut [IkSolver] {
+        &mut self.ik_solvers
+    }
+
+    /// Tries to find an IK solver by its name. Returns index of the solver and its reference.
+    #[inline]
+    pub fn find_ik_solver_by_name_mut<S: AsRef<str>>(
+        &mut self,
+        name: S,
+    ) -> Option<(usize, &mut IkSolver)> {
+        utils::find_by_name_mut(self.ik_solvers.iter_mut().enumerate(), name)
+    }
+
     /// Returns final pose of the machine.
     #[inline]
     pub fn pose(&self) -> &AnimationPose {
         &self.final_pose
     }
 
-    /// Computes final animation pose that could be then applied to a scene graph.
+    fn collect_evaluation_order(&self, index: usize, visited: &mut [bool], order: &mut Vec<usize>) {
+        visited[index] = true;
+
+        for state in self.layers[index].states().iter() {
+            if let Some(sub_machine) = state.sub_machine {
+                if sub_machine < visited.len() && !visited[sub_machine] {
+                    self.collect_evaluation_order(sub_machine, visited, order);
+                }
+            }
+        }
+
+        order.push(index);
+    }
+
+    // Sub-machine layers must be evaluated before the layers that use them.
+    fn build_evaluation_order(&self) -> EvaluationOrder {
+        let mut is_sub_machine = vec![false; self.layers.len()];
+        for layer in self.layers.iter() {
+            for state in layer.states().iter() {
+                if let Some(flag) = state
+                    .sub_machine
+                    .and_then(|sub_machine| is_sub_machine.get_mut(sub_machine))
+                {
+                    *flag = true;
+                }
+            }
+        }
+
+        let mut visited = vec![false; self.layers.len()];
+        let mut layers = Vec::with_capacity(self.layers.len());
+        for index in 0..self.layers.len() {
+            if !visited[index] && !is_sub_machine[index] {
+                self.collect_evaluation_order(index, &mut visited, &mut layers);
+            }
+        }
+
+        EvaluationOrder {
+            layers,
+            is_sub_machine,
+        }
+    }
+
+    /// Computes final animation pose that could be then applied to a scene graph. Layers, that are used as
+    /// sub-machines of some states (see [`State::sub_machine`]), do not contribute to the final pose directly.
     #[inline]
     pub fn evaluate_pose(
         &mut self,
@@ -293,13 +429,203 @@ impl Machine {
     ) -> &AnimationPose {
         self.final_pose.reset();
 
-        for layer in self.layers.iter_mut() {
+        // Sub-machines start from their entry states every time when a parent state is entered.
+        for index in 0..self.layers.len() {
+            for sub_machine in self.layers[index].take_entered_sub_machines() {
+                if let Some(layer) = self.layers.get_mut(sub_machine) {
+                    layer.reset();
+                }
+            }
+        }
+
+        // The layers could also be changed via reflection, so check the count as well.
+        let order = match self.evaluation_order.take() {
+            Some(order) if order.is_sub_machine.len() == self.layers.len() => order,
+            _ => self.build_evaluation_order(),
+        };
+
+        for &index in order.layers.iter() {
+            let (before, rest) = self.layers.split_at_mut(index);
+            let (layer, after) = match rest.split_first_mut() {
+                Some(split) => split,
+                None => continue,
+            };
+
             let weight = layer.weight();
-            let pose = layer.evaluate_pose(animations, &self.parameters, dt);
+            let blend_mode = layer.blend_mode();
+            let pose = layer.evaluate_pose(
+                animations,
+                &self.parameters,
+                &SubMachinePoses::new(before, after),
+                dt,
+            );
+
+            // Poses of sub-machines are taken directly from their layers by the states that use them.
+            if !order.is_sub_machine[index] {
+                match blend_mode {
+                    LayerBlendMode::Override => self.final_pose.override_with(pose, weight),
+                    LayerBlendMode::Additive => self.final_pose.add_with(pose, weight),
+                }
+            }
+        }
+
+        self.evaluation_order = Some(order);
+
+        &self.final_pose
+    }
 
-            self.final_pose.blend_with(pose, weight);
+    /// Rebuilds feature databases of every [`MotionMatching`] node of the machine, which settings were changed since
+    /// the last update. Animation blending state machine scene node does this automatically on every update. The
+    /// databases are rebuilt synchronously, so a change of settings could cause a hitch (see
+    /// [`MotionMatching::update_database`] docs).
+    pub fn update_motion_databases(&mut self, nodes: &NodePool, animations: &AnimationContainer) {
+        for layer in self.layers.iter_mut() {
+            for node in layer.nodes_mut().iter_mut() {
+                if let PoseNode::MotionMatching(motion_matching) = node {
+                    motion_matching.update_database(nodes, animations);
+                }
+            }
+        }
+    }
+
+    /// Applies every IK solver of the machine to the final pose. This method must be called after
+    /// [`Self::evaluate_pose`] and before the pose is applied to the scene graph. Animation blending
+    /// state machine scene node does this automatically.
+    #[inline]
+    pub fn solve_ik(&mut self, nodes: &NodePool, physics: &PhysicsWorld) -> &AnimationPose {
+        for solver in self.ik_solvers.iter() {
+            solver.solve(&mut self.final_pose, nodes, physics, &self.parameters);
         }
 
         &self.final_pose
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            container::{TrackDataContainer, TrackValueKind},
+            machine::{
+                Machine, MachineLayer, Parameter, PlayAnimation, PoseNode, State, Transition,
+            },
+            track::Track,
+            value::ValueBinding,
+            Animation, AnimationContainer,
+        },
+        core::{
+            curve::{CurveKey, CurveKeyKind},
+            pool::Handle,
+        },
+        scene::node::Node,
+    };
+
+    fn add_state(
+        layer: &mut MachineLayer,
+        animations: &mut AnimationContainer,
+        name: &str,
+        target: Handle<Node>,
+    ) -> Handle<State> {
+        let mut position = TrackDataContainer::new(TrackValueKind::Vector3);
+        for curve in position.curves_mut() {
+            curve.add_key(CurveKey::new(0.0, 0.0, CurveKeyKind::Linear));
+            curve.add_key(CurveKey::new(1.0, 1.0, CurveKeyKind::Linear));
+        }
+        let mut track = Track::new(position, ValueBinding::Position);
+        track.set_target(target);
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation.fit_length_to_content();
+
+        let animation = animations.add(animation);
+        let node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(animation)));
+        let state = layer.add_state(State::new(name, node));
+        layer.node_mut(node).parent_state = state;
+        state
+    }
+
+    fn step(machine: &mut Machine, animations: &mut AnimationContainer) -> Vec<Handle<Node>> {
+        for animation in animations.iter_mut() {
+            animation.tick(0.3);
+        }
+        let mut nodes = machine
+            .evaluate_pose(animations, 0.3)
+            .poses()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|node| node.index());
+        nodes
+    }
+
+    #[test]
+    fn test_sub_machine() {
+        let stand_node = Handle::new(1, 1);
+        let kneel_node = Handle::new(2, 1);
+        let other_node = Handle::new(3, 1);
+
+        let mut animations = AnimationContainer::new();
+        let mut machine = Machine::new();
+
+        // The parent layer goes first to check that sub-machines are evaluated before their users.
+        let base = &mut machine.layers_mut()[0];
+        base.set_name("Base");
+        let locomotion = base.add_state(State::new_sub_machine("Locomotion", 1));
+        let other = add_state(base, &mut animations, "Other", other_node);
+        base.set_entry_state(locomotion);
+        base.add_transition(Transition::new("ToOther", locomotion, other, 0.1, "Other"));
+        base.add_transition(Transition::new("Back", other, locomotion, 0.1, "Back"));
+
+        let mut legs = MachineLayer::new();
+        legs.set_name("Legs");
+        let stand = add_state(&mut legs, &mut animations, "Stand", stand_node);
+        let kneel = add_state(&mut legs, &mut animations, "Kneel", kneel_node);
+        legs.set_entry_state(stand);
+        legs.add_transition(Transition::new("Kneel", stand, kneel, 0.1, "Kneel"));
+        machine.add_layer(legs);
+
+        machine
+            .set_parameter("Other", Parameter::Rule(false))
+            .set_parameter("Back", Parameter::Rule(false))
+            .set_parameter("Kneel", Parameter::Rule(false));
+
+        assert_eq!(step(&mut machine, &mut animations), vec![stand_node]);
+
+        machine.set_parameter("Kneel", Parameter::Rule(true));
+        step(&mut machine, &mut animations);
+        assert_eq!(step(&mut machine, &mut animations), vec![kneel_node]);
+
+        // Sub-machine layer does not contribute to the final pose directly.
+        machine
+            .set_parameter("Kneel", Parameter::Rule(false))
+            .set_parameter("Other", Parameter::Rule(true));
+        step(&mut machine, &mut animations);
+        assert_eq!(step(&mut machine, &mut animations), vec![other_node]);
+
+        // Sub-machine must start from its entry state when the parent state is entered again.
+        machine
+            .set_parameter("Other", Parameter::Rule(false))
+            .set_parameter("Back", Parameter::Rule(true));
+        step(&mut machine, &mut animations);
+        assert_eq!(step(&mut machine, &mut animations), vec![stand_node]);
+        let legs = machine.find_layer_by_name_ref("Legs").unwrap().1;
+        assert_eq!(legs.active_state(), stand);
+    }
+
+    #[test]
+    fn test_sub_machine_index_remapping() {
+        let mut machine = Machine::new();
+        let user = machine.layers_mut()[0].add_state(State::new_sub_machine("User", 1));
+        machine.add_layer(MachineLayer::new());
+        let sub_machine_of = |machine: &Machine| machine.layers()[0].states()[user].sub_machine;
+
+        machine.insert_layer(1, MachineLayer::new());
+        assert_eq!(sub_machine_of(&machine), Some(2));
+
+        machine.remove_layer(1);
+        assert_eq!(sub_machine_of(&machine), Some(1));
+
+        machine.pop_layer();
+        assert_eq!(sub_machine_of(&machine), None);
+    }
+}
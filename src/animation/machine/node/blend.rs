@@ -147,6 +147,112 @@ impl EvaluatePose for BlendAnimations {
     }
 }
 
+/// A node that adds an additive pose on top of a base pose. The additive pose must be produced by additive animations
+/// (see [`crate::animation::Animation::make_additive`]), it contains only differences relative to some reference pose.
+/// For example a breathing animation could be added on top of idle, walk and run animations without creating separate
+/// versions of each of them. The weight of the additive pose could be parametrized, for example to control intensity
+/// of a weapon recoil.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct BlendAdditive {
+    /// Base node.
+    pub base: BasePoseNode,
+
+    /// A source of the base animation pose.
+    #[reflect(hidden)]
+    pub base_pose: Handle<PoseNode>,
+
+    /// A source of the additive animation pose.
+    #[reflect(hidden)]
+    pub additive_pose: Handle<PoseNode>,
+
+    /// Weight of the additive pose.
+    pub weight: PoseWeight,
+
+    /// Output pose of the node.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub output_pose: RefCell<AnimationPose>,
+}
+
+impl Deref for BlendAdditive {
+    type Target = BasePoseNode;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for BlendAdditive {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl BlendAdditive {
+    /// Creates new additive blend node.
+    pub fn new(
+        base_pose: Handle<PoseNode>,
+        additive_pose: Handle<PoseNode>,
+        weight: PoseWeight,
+    ) -> Self {
+        Self {
+            base: Default::default(),
+            base_pose,
+            additive_pose,
+            weight,
+            output_pose: Default::default(),
+        }
+    }
+
+    /// Returns a set of handles to children pose nodes. The first one is the base pose, the second one is the
+    /// additive pose.
+    pub fn children(&self) -> Vec<Handle<PoseNode>> {
+        vec![self.base_pose, self.additive_pose]
+    }
+}
+
+impl EvaluatePose for BlendAdditive {
+    fn eval_pose(
+        &self,
+        nodes: &Pool<PoseNode>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer,
+        dt: f32,
+    ) -> Ref<AnimationPose> {
+        let weight = match self.weight {
+            PoseWeight::Constant(value) => value,
+            PoseWeight::Parameter(ref param_id) => {
+                if let Some(Parameter::Weight(weight)) = params.get(param_id) {
+                    *weight
+                } else {
+                    0.0
+                }
+            }
+        };
+
+        self.output_pose.borrow_mut().reset();
+        if let Some(base_pose) = nodes
+            .try_borrow(self.base_pose)
+            .map(|base_pose| base_pose.eval_pose(nodes, params, animations, dt))
+        {
+            base_pose.clone_into(&mut self.output_pose.borrow_mut());
+        }
+        if let Some(additive_pose) = nodes
+            .try_borrow(self.additive_pose)
+            .map(|additive_pose| additive_pose.eval_pose(nodes, params, animations, dt))
+        {
+            self.output_pose
+                .borrow_mut()
+                .add_with(&additive_pose, weight);
+        }
+        self.output_pose.borrow()
+    }
+
+    fn pose(&self) -> Ref<AnimationPose> {
+        self.output_pose.borrow()
+    }
+}
+
 /// An animation pose with specific blend time. Blend time tells the engine how many time it should use to perform
 /// blending to this pose.
 #[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
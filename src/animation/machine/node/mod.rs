@@ -4,8 +4,14 @@
 use crate::{
     animation::{
         machine::{
-            node::{blend::BlendAnimations, blendspace::BlendSpace, play::PlayAnimation},
-            BlendAnimationsByIndex, BlendPose, IndexedBlendInput, ParameterContainer, State,
+            node::{
+                blend::{BlendAdditive, BlendAnimations},
+                blendspace::BlendSpace,
+                motion::MotionMatching,
+                play::PlayAnimation,
+            },
+            BlendAnimationsByIndex, BlendPose, IndexedBlendInput, ParameterContainer, PoseWeight,
+            State,
         },
         Animation, AnimationContainer, AnimationPose,
     },
@@ -15,6 +21,7 @@ use crate::{
         reflect::prelude::*,
         visitor::prelude::*,
     },
+    scene::node::Node,
 };
 use std::{
     cell::Ref,
@@ -23,6 +30,7 @@ use std::{
 
 pub mod blend;
 pub mod blendspace;
+pub mod motion;
 pub mod play;
 
 /// A set of common data fields that is used in every node.
@@ -50,6 +58,12 @@ pub enum PoseNode {
 
     /// See doc for [`BlendSpace`]
     BlendSpace(BlendSpace),
+
+    /// See docs for [`BlendAdditive`].
+    BlendAdditive(BlendAdditive),
+
+    /// See docs for [`MotionMatching`].
+    MotionMatching(MotionMatching),
 }
 
 impl Default for PoseNode {
@@ -78,16 +92,35 @@ impl PoseNode {
         Self::BlendAnimationsByIndex(BlendAnimationsByIndex::new(index_parameter, inputs))
     }
 
+    /// Creates new node that adds an additive pose on top of a base pose.
+    pub fn make_blend_additive(
+        base_pose: Handle<PoseNode>,
+        additive_pose: Handle<PoseNode>,
+        weight: PoseWeight,
+    ) -> Self {
+        Self::BlendAdditive(BlendAdditive::new(base_pose, additive_pose, weight))
+    }
+
+    /// Creates new node that picks the best matching frame from the given set of animations.
+    pub fn make_motion_matching(
+        animations: Vec<Handle<Animation>>,
+        root: Handle<Node>,
+        bones: Vec<Handle<Node>>,
+    ) -> Self {
+        Self::MotionMatching(MotionMatching::new(animations, root, bones))
+    }
+
     /// Returns a set of handles to children pose nodes.
     pub fn children(&self) -> Vec<Handle<PoseNode>> {
         match self {
-            Self::PlayAnimation(_) => {
+            Self::PlayAnimation(_) | Self::MotionMatching(_) => {
                 // No children nodes.
                 vec![]
             }
             Self::BlendAnimations(blend_animations) => blend_animations.children(),
             Self::BlendAnimationsByIndex(blend_by_index) => blend_by_index.children(),
             Self::BlendSpace(blend_space) => blend_space.children(),
+            Self::BlendAdditive(blend_additive) => blend_additive.children(),
         }
     }
 }
@@ -99,6 +132,8 @@ macro_rules! static_dispatch {
             PoseNode::BlendAnimations(v) => v.$func($($args),*),
             PoseNode::BlendAnimationsByIndex(v) => v.$func($($args),*),
             PoseNode::BlendSpace(v) => v.$func($($args),*),
+            PoseNode::BlendAdditive(v) => v.$func($($args),*),
+            PoseNode::MotionMatching(v) => v.$func($($args),*),
         }
     };
 }
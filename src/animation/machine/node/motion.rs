@@ -0,0 +1,869 @@
+//! Motion matching is a technique, that picks a frame from a set of animations that best matches current pose of
+//! a character and a desired trajectory. See [`MotionMatching`] docs for more info.
+
+use crate::{
+    animation::{
+        machine::{
+            ik::rotation_of, node::BasePoseNode, EvaluatePose, Parameter, ParameterContainer,
+            PoseNode,
+        },
+        value::{TrackValue, ValueBinding},
+        Animation, AnimationContainer, AnimationPose,
+    },
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{graph::NodePool, node::Node, transform::Transform},
+};
+use std::{
+    cell::{Ref, RefCell},
+    ops::{Deref, DerefMut},
+};
+
+/// Time offsets (in seconds) of the future trajectory points, that are used for matching.
+pub const TRAJECTORY_TIMES: [f32; 3] = [0.2, 0.4, 0.6];
+
+/// Weights of the groups of features, that are used to find the best matching frame. Larger weight of a group
+/// means that it is more important to match it.
+#[derive(Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct MotionMatchingWeights {
+    /// Weight of the positions of the feature bones.
+    pub bone_position: f32,
+    /// Weight of the velocities of the feature bones.
+    pub bone_velocity: f32,
+    /// Weight of the positions of the future trajectory points.
+    pub trajectory_position: f32,
+    /// Weight of the facing directions of the future trajectory points.
+    pub trajectory_direction: f32,
+}
+
+impl Default for MotionMatchingWeights {
+    fn default() -> Self {
+        Self {
+            bone_position: 0.75,
+            bone_velocity: 1.0,
+            trajectory_position: 1.0,
+            trajectory_direction: 1.5,
+        }
+    }
+}
+
+/// A frame of the feature database.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionFrame {
+    /// Index of the animation in the list of animations of the motion matching node.
+    pub animation: usize,
+    /// Time position of the frame in the animation.
+    pub time: f32,
+}
+
+/// Feature database is a set of normalized feature vectors, one per each sampled frame of every animation of a
+/// motion matching node. Every feature vector contains positions and velocities of the feature bones followed by
+/// positions and facing directions of the future trajectory points. Every value is expressed in "character space"
+/// of the frame: it has an origin at the root bone projected on the ground and it is rotated by the facing direction
+/// of the root bone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MotionDatabase {
+    // A copy of the settings of the node, that were used to build the database.
+    animations: Vec<Handle<Animation>>,
+    bones: Vec<Handle<Node>>,
+    root: Handle<Node>,
+    sample_rate: f32,
+    weights: Option<MotionMatchingWeights>,
+
+    frames: Vec<MotionFrame>,
+    features: Vec<f32>,
+    offset: Vec<f32>,
+    scale: Vec<f32>,
+}
+
+// Character space transform of a frame.
+#[derive(Clone, Copy)]
+struct CharacterFrame {
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+}
+
+impl CharacterFrame {
+    fn new(root: &Matrix4<f32>, bind_rotation: UnitQuaternion<f32>) -> Self {
+        let position = root.column(3).xyz();
+        let forward = facing_direction(root, bind_rotation);
+        Self {
+            position: Vector3::new(position.x, 0.0, position.z),
+            rotation: UnitQuaternion::face_towards(
+                &Vector3::new(forward.x, 0.0, forward.y),
+                &Vector3::y(),
+            ),
+        }
+    }
+
+    fn to_local_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        self.rotation
+            .inverse_transform_vector(&(point - self.position))
+    }
+
+    fn to_local_vector(&self, vector: Vector3<f32>) -> Vector3<f32> {
+        self.rotation.inverse_transform_vector(&vector)
+    }
+}
+
+// Facing direction of the root bone on the ground plane. Rotation of the root is taken relative to its bind pose, so
+// the facing direction of the bind pose is always +Z.
+fn facing_direction(root: &Matrix4<f32>, bind_rotation: UnitQuaternion<f32>) -> Vector2<f32> {
+    let rotation = rotation_of(root) * bind_rotation.inverse();
+    let forward = rotation.transform_vector(&Vector3::z());
+    Vector2::new(forward.x, forward.z)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(|| Vector2::new(0.0, 1.0))
+}
+
+fn sampled_local_transform(
+    nodes: &NodePool,
+    animation: &Animation,
+    handle: Handle<Node>,
+    time: f32,
+) -> Option<Transform> {
+    let mut transform = nodes.try_borrow(handle)?.local_transform().clone();
+    for track in animation.tracks_of(handle) {
+        match (track.binding(), track.fetch(time).map(|v| v.value)) {
+            (ValueBinding::Position, Some(TrackValue::Vector3(position))) => {
+                transform.set_position(position);
+            }
+            (ValueBinding::Scale, Some(TrackValue::Vector3(scale))) => {
+                transform.set_scale(scale);
+            }
+            (ValueBinding::Rotation, Some(TrackValue::UnitQuaternion(rotation))) => {
+                transform.set_rotation(rotation);
+            }
+            _ => (),
+        }
+    }
+    Some(transform)
+}
+
+// Transform of a node relative to the parent of the root bone ("model space") at the given time of the animation.
+fn sampled_model_transform(
+    nodes: &NodePool,
+    animation: &Animation,
+    handle: Handle<Node>,
+    model: Handle<Node>,
+    time: f32,
+) -> Matrix4<f32> {
+    let mut transform = Matrix4::identity();
+    let mut current = handle;
+    while current != model {
+        match nodes.try_borrow(current) {
+            Some(node) => {
+                if let Some(local) = sampled_local_transform(nodes, animation, current, time) {
+                    transform = local.matrix() * transform;
+                }
+                current = node.parent();
+            }
+            None => break,
+        }
+    }
+    transform
+}
+
+// Wraps (for looped animations) or clamps the given time into the time slice of the animation.
+fn fit_time(animation: &Animation, time: f32) -> f32 {
+    let slice = animation.time_slice();
+    let length = slice.end - slice.start;
+    if animation.is_loop() && length > 0.0 {
+        slice.start + (time - slice.start).rem_euclid(length)
+    } else {
+        time.clamp(slice.start, slice.end)
+    }
+}
+
+// Position of a node in model space at the given (unwrapped) time. Displacement of the root bone is accumulated for
+// every loop of looped animations, this way trajectories and velocities stay continuous at the loop boundary.
+fn sampled_model_position(
+    nodes: &NodePool,
+    animation: &Animation,
+    handle: Handle<Node>,
+    model: Handle<Node>,
+    loop_offset: Vector3<f32>,
+    time: f32,
+) -> Vector3<f32> {
+    let slice = animation.time_slice();
+    let length = slice.end - slice.start;
+    let loops = if animation.is_loop() && length > 0.0 {
+        ((time - slice.start) / length).floor()
+    } else {
+        0.0
+    };
+    sampled_model_transform(nodes, animation, handle, model, fit_time(animation, time))
+        .column(3)
+        .xyz()
+        + loop_offset.scale(loops)
+}
+
+fn sample_pose(animation: &Animation, time: f32, pose: &mut AnimationPose) {
+    pose.reset();
+    for track in animation.tracks() {
+        if track.is_enabled() {
+            if let Some(bound_value) = track.fetch(time) {
+                pose.add_to_node_pose(track.target(), bound_value);
+            }
+        }
+    }
+}
+
+impl MotionDatabase {
+    /// Amount of features per frame for the given amount of feature bones.
+    pub fn feature_count(bone_count: usize) -> usize {
+        bone_count * 6 + TRAJECTORY_TIMES.len() * 4
+    }
+
+    /// Builds new feature database for the given motion matching node. Current local transforms of the nodes are
+    /// used for nodes, that are not animated.
+    pub fn new(node: &MotionMatching, nodes: &NodePool, animations: &AnimationContainer) -> Self {
+        let mut database = Self {
+            animations: node.animations.clone(),
+            bones: node.bones.clone(),
+            root: node.root,
+            sample_rate: node.sample_rate,
+            weights: Some(node.weights.clone()),
+            ..Default::default()
+        };
+
+        let root = match nodes.try_borrow(node.root) {
+            Some(root) => root,
+            None => return database,
+        };
+        let model = root.parent();
+        let bind_rotation = **root.local_transform().rotation();
+
+        let dt = 1.0 / node.sample_rate.max(1.0);
+        let feature_count = Self::feature_count(node.bones.len());
+
+        for (animation_index, animation) in node
+            .animations
+            .iter()
+            .enumerate()
+            .filter_map(|(i, a)| animations.try_get(*a).map(|a| (i, a)))
+        {
+            let slice = animation.time_slice();
+            let frame_count = ((slice.end - slice.start) * node.sample_rate.max(1.0))
+                .floor()
+                .max(0.0) as usize
+                + 1;
+
+            let loop_offset = if animation.is_loop() {
+                let start =
+                    sampled_model_transform(nodes, animation, node.root, model, slice.start);
+                let end = sampled_model_transform(nodes, animation, node.root, model, slice.end);
+                let offset = end.column(3).xyz() - start.column(3).xyz();
+                Vector3::new(offset.x, 0.0, offset.z)
+            } else {
+                Vector3::default()
+            };
+
+            for frame_index in 0..frame_count {
+                let time = (slice.start + frame_index as f32 * dt).min(slice.end);
+
+                let root_transform =
+                    sampled_model_transform(nodes, animation, node.root, model, time);
+                let frame = CharacterFrame::new(&root_transform, bind_rotation);
+
+                for bone in node.bones.iter() {
+                    let position =
+                        sampled_model_position(nodes, animation, *bone, model, loop_offset, time);
+                    let next_position = sampled_model_position(
+                        nodes,
+                        animation,
+                        *bone,
+                        model,
+                        loop_offset,
+                        time + dt,
+                    );
+
+                    let local_position = frame.to_local_point(position);
+                    let velocity =
+                        frame.to_local_vector((next_position - position).scale(1.0 / dt));
+                    database.features.extend_from_slice(&[
+                        local_position.x,
+                        local_position.y,
+                        local_position.z,
+                    ]);
+                    database
+                        .features
+                        .extend_from_slice(&[velocity.x, velocity.y, velocity.z]);
+                }
+
+                for offset in TRAJECTORY_TIMES.iter() {
+                    let future_time = time + offset;
+                    let future = sampled_model_transform(
+                        nodes,
+                        animation,
+                        node.root,
+                        model,
+                        fit_time(animation, future_time),
+                    );
+                    let position = frame.to_local_point(sampled_model_position(
+                        nodes,
+                        animation,
+                        node.root,
+                        model,
+                        loop_offset,
+                        future_time,
+                    ));
+                    let direction = facing_direction(&future, bind_rotation);
+                    let direction =
+                        frame.to_local_vector(Vector3::new(direction.x, 0.0, direction.y));
+                    database.features.extend_from_slice(&[
+                        position.x,
+                        position.z,
+                        direction.x,
+                        direction.z,
+                    ]);
+                }
+
+                database.frames.push(MotionFrame {
+                    animation: animation_index,
+                    time,
+                });
+            }
+        }
+
+        database.normalize(feature_count, &node.weights);
+
+        database
+    }
+
+    fn normalize(&mut self, feature_count: usize, weights: &MotionMatchingWeights) {
+        self.offset = vec![0.0; feature_count];
+        self.scale = vec![1.0; feature_count];
+
+        if self.frames.is_empty() || feature_count == 0 {
+            return;
+        }
+
+        let frame_count = self.frames.len() as f32;
+        for frame in self.features.chunks(feature_count) {
+            for (offset, value) in self.offset.iter_mut().zip(frame) {
+                *offset += *value / frame_count;
+            }
+        }
+
+        let mut deviation = vec![0.0; feature_count];
+        for frame in self.features.chunks(feature_count) {
+            for (i, value) in frame.iter().enumerate() {
+                deviation[i] += (value - self.offset[i]).powi(2) / frame_count;
+            }
+        }
+
+        // Every group of features is scaled by its average deviation, this way the relative magnitude of the values
+        // in a group is preserved.
+        let bone_features = feature_count - TRAJECTORY_TIMES.len() * 4;
+        let mut groups = Vec::new();
+        for bone in 0..bone_features / 6 {
+            groups.push((bone * 6, 3, weights.bone_position));
+            groups.push((bone * 6 + 3, 3, weights.bone_velocity));
+        }
+        for point in 0..TRAJECTORY_TIMES.len() {
+            groups.push((bone_features + point * 4, 2, weights.trajectory_position));
+            groups.push((
+                bone_features + point * 4 + 2,
+                2,
+                weights.trajectory_direction,
+            ));
+        }
+
+        for (start, count, weight) in groups {
+            let deviation = deviation[start..start + count]
+                .iter()
+                .map(|d| d.sqrt())
+                .sum::<f32>()
+                / count as f32;
+            let scale = if deviation > f32::EPSILON {
+                weight / deviation
+            } else {
+                weight
+            };
+            for s in self.scale[start..start + count].iter_mut() {
+                *s = scale;
+            }
+        }
+
+        for frame in self.features.chunks_mut(feature_count) {
+            for (i, value) in frame.iter_mut().enumerate() {
+                *value = (*value - self.offset[i]) * self.scale[i];
+            }
+        }
+    }
+
+    fn is_built_for(&self, node: &MotionMatching) -> bool {
+        self.animations == node.animations
+            && self.bones == node.bones
+            && self.root == node.root
+            && self.sample_rate == node.sample_rate
+            && self.weights.as_ref() == Some(&node.weights)
+    }
+
+    /// Returns a list of sampled frames.
+    pub fn frames(&self) -> &[MotionFrame] {
+        &self.frames
+    }
+
+    /// Returns normalized features of a frame with the given index.
+    pub fn features_of(&self, frame: usize) -> Option<&[f32]> {
+        let feature_count = Self::feature_count(self.bones.len());
+        self.features
+            .get(frame * feature_count..(frame + 1) * feature_count)
+    }
+
+    /// Returns an index of the frame that is the closest to the given time position of the given animation.
+    pub fn nearest_frame(&self, animation: usize, time: f32) -> Option<usize> {
+        self.frames
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.animation == animation)
+            .min_by(|(_, a), (_, b)| (a.time - time).abs().total_cmp(&(b.time - time).abs()))
+            .map(|(i, _)| i)
+    }
+
+    /// Normalizes a desired trajectory, so it could be used in a query. The trajectory must be defined in character
+    /// space (see [`MotionDatabase`] docs) and it must contain a point per each time offset from [`TRAJECTORY_TIMES`].
+    pub fn normalize_trajectory(&self, trajectory: &[TrajectoryPoint]) -> Vec<f32> {
+        let start = Self::feature_count(self.bones.len()) - TRAJECTORY_TIMES.len() * 4;
+        let mut normalized = Vec::with_capacity(trajectory.len() * 4);
+        for point in trajectory {
+            normalized.extend_from_slice(&[
+                point.position.x,
+                point.position.y,
+                point.direction.x,
+                point.direction.y,
+            ]);
+        }
+        for (i, value) in normalized.iter_mut().enumerate() {
+            if let (Some(offset), Some(scale)) =
+                (self.offset.get(start + i), self.scale.get(start + i))
+            {
+                *value = (*value - offset) * scale;
+            }
+        }
+        normalized
+    }
+
+    /// Searches for a frame, which features are the closest to the given normalized query.
+    pub fn find_best_frame(&self, query: &[f32]) -> Option<usize> {
+        let feature_count = Self::feature_count(self.bones.len());
+        if feature_count == 0 || query.len() != feature_count {
+            return None;
+        }
+
+        self.features
+            .chunks(feature_count)
+            .map(|features| {
+                features
+                    .iter()
+                    .zip(query)
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<f32>()
+            })
+            .enumerate()
+            // NaN features (for example, from broken animations) must not break the search.
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+}
+
+/// A point of a trajectory in character space.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TrajectoryPoint {
+    /// Position of the point on the ground plane (X and Z axes).
+    pub position: Vector2<f32>,
+    /// Facing direction at the point on the ground plane (X and Z axes).
+    pub direction: Vector2<f32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Playback {
+    current: Option<MotionFrame>,
+    previous: Option<MotionFrame>,
+    blend_elapsed: f32,
+    search_timer: f32,
+}
+
+/// Motion matching node picks a frame, that matches current pose of a character and the desired trajectory the best,
+/// from a set of animations. It allows you to create responsive locomotion without hand-authored transitions between
+/// states - it is enough to record a large set of animations ("dance card") with various movements and the node will
+/// find the most suitable frame automatically.
+///
+/// # Feature database
+///
+/// The node builds a feature database (see [`MotionDatabase`]) from the given animations. Every animation is sampled
+/// with the given sample rate and for every sample the node extracts positions and velocities of the feature `bones`
+/// (usually feet and hips) and the future trajectory of the `root` bone. The database is (re)built by the animation
+/// blending state machine scene node automatically, when the settings of the node change. Keep in mind, that the
+/// current local transforms of the scene nodes are used as a bind pose of the skeleton.
+///
+/// # Desired trajectory
+///
+/// The desired trajectory is defined by the desired velocity (`velocity_x` and `velocity_z` weight parameters) in
+/// character space (+Z is the current facing direction) and the desired facing angle (`facing_angle` weight parameter,
+/// in radians, relative to the current facing direction). If the facing parameter is not specified, the facing
+/// direction follows the velocity.
+///
+/// # Playback
+///
+/// The node plays the selected animation by itself, using the time position of the matched frame (animation player
+/// still ticks the animations, but their time positions are ignored). Every `search_interval` seconds it performs
+/// a search and, if a better frame is found, crossfades to it using `blend_time`. When `in_place` is set, horizontal
+/// motion of the root bone is removed from the pose, so the character could be moved by a character controller.
+#[derive(Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct MotionMatching {
+    /// Base node.
+    pub base: BasePoseNode,
+
+    /// A set of animations, that will be used to build the feature database.
+    pub animations: Vec<Handle<Animation>>,
+
+    /// A set of bones, that will be used to match the pose of a character.
+    pub bones: Vec<Handle<Node>>,
+
+    /// A root bone (usually hips), its motion defines the trajectory of a character.
+    pub root: Handle<Node>,
+
+    /// Amount of samples per second of an animation.
+    #[reflect(min_value = 1.0)]
+    pub sample_rate: f32,
+
+    /// Weights of the feature groups.
+    pub weights: MotionMatchingWeights,
+
+    /// Time interval (in seconds) between searches of the best matching frame.
+    #[reflect(min_value = 0.0)]
+    pub search_interval: f32,
+
+    /// Time (in seconds) of the crossfade between the current and the new frame.
+    #[reflect(min_value = 0.0)]
+    pub blend_time: f32,
+
+    /// A name of weight parameter, that defines desired velocity along X axis of the character space.
+    pub velocity_x: String,
+
+    /// A name of weight parameter, that defines desired velocity along Z axis of the character space.
+    pub velocity_z: String,
+
+    /// A name of weight parameter, that defines desired facing angle relative to the current facing direction.
+    pub facing_angle: String,
+
+    /// Whether to remove horizontal motion of the root bone or not.
+    pub in_place: bool,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    database: MotionDatabase,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    playback: RefCell<Playback>,
+
+    /// Output pose of the node.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub output_pose: RefCell<AnimationPose>,
+}
+
+impl Deref for MotionMatching {
+    type Target = BasePoseNode;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for MotionMatching {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Default for MotionMatching {
+    fn default() -> Self {
+        Self::new(Default::default(), Default::default(), Default::default())
+    }
+}
+
+impl MotionMatching {
+    /// Creates new motion matching node, that uses the given animations and feature bones.
+    pub fn new(
+        animations: Vec<Handle<Animation>>,
+        root: Handle<Node>,
+        bones: Vec<Handle<Node>>,
+    ) -> Self {
+        Self {
+            base: Default::default(),
+            animations,
+            bones,
+            root,
+            sample_rate: 30.0,
+            weights: Default::default(),
+            search_interval: 0.1,
+            blend_time: 0.2,
+            velocity_x: "VelocityX".to_string(),
+            velocity_z: "VelocityZ".to_string(),
+            facing_angle: Default::default(),
+            in_place: true,
+            database: Default::default(),
+            playback: Default::default(),
+            output_pose: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the feature database of the node.
+    pub fn database(&self) -> &MotionDatabase {
+        &self.database
+    }
+
+    /// Rebuilds the feature database if it does not match current settings of the node. Returns `true` if the
+    /// database was rebuilt.
+    ///
+    /// # Performance
+    ///
+    /// The database is built synchronously, every animation of the node is sampled at every frame. For long motion
+    /// capture clips it could take a noticeable amount of time and cause a hitch, so it is better to call this
+    /// method (or [`crate::animation::machine::Machine::update_motion_databases`]) when loading a level, instead of
+    /// letting the engine do this on the first update.
+    pub fn update_database(&mut self, nodes: &NodePool, animations: &AnimationContainer) -> bool {
+        if self.database.is_built_for(self) {
+            false
+        } else {
+            self.database = MotionDatabase::new(self, nodes, animations);
+            *self.playback.borrow_mut() = Default::default();
+            true
+        }
+    }
+
+    /// Returns currently played frame (if any).
+    pub fn current_frame(&self) -> Option<MotionFrame> {
+        self.playback.borrow().current
+    }
+
+    fn weight_parameter(params: &ParameterContainer, name: &str) -> Option<f32> {
+        match params.get(name) {
+            Some(Parameter::Weight(weight)) => Some(*weight),
+            _ => None,
+        }
+    }
+
+    /// Calculates desired trajectory in character space using the values of the parameters.
+    pub fn desired_trajectory(&self, params: &ParameterContainer) -> Vec<TrajectoryPoint> {
+        let velocity = Vector2::new(
+            Self::weight_parameter(params, &self.velocity_x).unwrap_or_default(),
+            Self::weight_parameter(params, &self.velocity_z).unwrap_or_default(),
+        );
+
+        let direction = match Self::weight_parameter(params, &self.facing_angle) {
+            Some(angle) => Vector2::new(angle.sin(), angle.cos()),
+            None => velocity
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(|| Vector2::new(0.0, 1.0)),
+        };
+
+        TRAJECTORY_TIMES
+            .iter()
+            .map(|time| TrajectoryPoint {
+                position: velocity.scale(*time),
+                direction,
+            })
+            .collect()
+    }
+
+    fn animation_of<'a>(
+        &self,
+        animations: &'a AnimationContainer,
+        frame: &MotionFrame,
+    ) -> Option<&'a Animation> {
+        self.animations
+            .get(frame.animation)
+            .and_then(|a| animations.try_get(*a))
+    }
+
+    fn advance(&self, animations: &AnimationContainer, frame: &mut MotionFrame, dt: f32) {
+        if let Some(animation) = self.animation_of(animations, frame) {
+            frame.time = fit_time(animation, frame.time + dt * animation.speed());
+        }
+    }
+
+    fn search(&self, params: &ParameterContainer, current: Option<MotionFrame>) -> Option<usize> {
+        let trajectory = self
+            .database
+            .normalize_trajectory(&self.desired_trajectory(params));
+
+        // Current pose is taken from the database, this way the features of the query are always consistent with
+        // the features of the frames.
+        let mut query = current
+            .and_then(|c| self.database.nearest_frame(c.animation, c.time))
+            .and_then(|f| self.database.features_of(f))
+            .map(|features| features.to_vec())
+            .unwrap_or_else(|| vec![0.0; MotionDatabase::feature_count(self.bones.len())]);
+
+        let start = query.len() - trajectory.len();
+        query[start..].copy_from_slice(&trajectory);
+
+        self.database.find_best_frame(&query)
+    }
+}
+
+impl EvaluatePose for MotionMatching {
+    fn eval_pose(
+        &self,
+        _nodes: &Pool<PoseNode>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer,
+        dt: f32,
+    ) -> Ref<AnimationPose> {
+        let mut playback = self.playback.borrow_mut();
+
+        if let Some(current) = playback.current.as_mut() {
+            self.advance(animations, current, dt);
+        }
+        if let Some(previous) = playback.previous.as_mut() {
+            self.advance(animations, previous, dt);
+        }
+        playback.blend_elapsed += dt;
+        if playback.blend_elapsed >= self.blend_time {
+            playback.previous = None;
+        }
+
+        playback.search_timer -= dt;
+        if playback.search_timer <= 0.0 || playback.current.is_none() {
+            playback.search_timer = self.search_interval;
+
+            if let Some(best) = self
+                .search(params, playback.current)
+                .and_then(|i| self.database.frames().get(i).cloned())
+            {
+                // Do not jump to almost the same frame of the same animation, it would break the motion.
+                let is_same = playback.current.map_or(false, |current| {
+                    current.animation == best.animation
+                        && (current.time - best.time).abs() < 2.0 / self.sample_rate.max(1.0)
+                });
+
+                if !is_same {
+                    playback.previous = playback.current;
+                    playback.blend_elapsed = 0.0;
+                    playback.current = Some(best);
+                }
+            }
+        }
+
+        let mut output_pose = self.output_pose.borrow_mut();
+        output_pose.reset();
+
+        if let Some(current) = playback.current {
+            if let Some(animation) = self.animation_of(animations, &current) {
+                sample_pose(animation, current.time, &mut output_pose);
+            }
+
+            if let Some(previous) = playback.previous {
+                if let Some(animation) = self.animation_of(animations, &previous) {
+                    let mut previous_pose = AnimationPose::default();
+                    sample_pose(animation, previous.time, &mut previous_pose);
+
+                    let weight = if self.blend_time > 0.0 {
+                        (playback.blend_elapsed / self.blend_time).min(1.0)
+                    } else {
+                        1.0
+                    };
+                    previous_pose.blend_with(&output_pose, weight);
+                    previous_pose.clone_into(&mut output_pose);
+                }
+            }
+        }
+
+        if self.in_place {
+            if let Some(root_pose) = output_pose.poses_mut().get_mut(&self.root) {
+                for bound_value in root_pose.values.values.iter_mut() {
+                    if let (ValueBinding::Position, TrackValue::Vector3(position)) =
+                        (&bound_value.binding, &mut bound_value.value)
+                    {
+                        position.x = 0.0;
+                        position.z = 0.0;
+                    }
+                }
+            }
+        }
+
+        drop(output_pose);
+
+        self.output_pose.borrow()
+    }
+
+    fn pose(&self) -> Ref<AnimationPose> {
+        self.output_pose.borrow()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            container::{TrackDataContainer, TrackValueKind},
+            machine::{node::motion::MotionMatching, EvaluatePose, Parameter, ParameterContainer},
+            track::Track,
+            value::ValueBinding,
+            Animation, AnimationContainer,
+        },
+        core::{
+            curve::{CurveKey, CurveKeyKind},
+            pool::{Handle, Pool},
+        },
+        scene::{base::BaseBuilder, graph::Graph, node::Node, pivot::PivotBuilder},
+    };
+
+    fn make_locomotion(speed: f32, root: Handle<Node>) -> Animation {
+        let mut position = TrackDataContainer::new(TrackValueKind::Vector3);
+        for (i, curve) in position.curves_mut().iter_mut().enumerate() {
+            curve.add_key(CurveKey::new(0.0, [0.0, 1.0, 0.0][i], CurveKeyKind::Linear));
+            curve.add_key(CurveKey::new(
+                1.0,
+                [0.0, 1.0, speed][i],
+                CurveKeyKind::Linear,
+            ));
+        }
+        let mut track = Track::new(position, ValueBinding::Position);
+        track.set_target(root);
+
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation.set_loop(true);
+        animation
+    }
+
+    #[test]
+    fn test_motion_matching_picks_matching_velocity() {
+        let mut graph = Graph::new();
+        let hips = PivotBuilder::new(BaseBuilder::new().with_name("Hips")).build(&mut graph);
+
+        let mut animations = AnimationContainer::new();
+        let idle = animations.add(make_locomotion(0.0, hips));
+        let run = animations.add(make_locomotion(4.0, hips));
+
+        let mut node = MotionMatching::new(vec![idle, run], hips, vec![hips]);
+        assert!(node.update_database(graph.node_pool(), &animations));
+        assert!(!node.update_database(graph.node_pool(), &animations));
+        assert_eq!(node.database().frames().len(), 62);
+
+        let mut params = ParameterContainer::default();
+        params.add("VelocityX", Parameter::Weight(0.0));
+        params.add("VelocityZ", Parameter::Weight(4.0));
+
+        let nodes = Pool::new();
+        node.eval_pose(&nodes, &params, &animations, 0.016);
+        assert_eq!(node.current_frame().unwrap().animation, 1);
+
+        *params.get_mut("VelocityZ").unwrap() = Parameter::Weight(0.0);
+        for _ in 0..20 {
+            node.eval_pose(&nodes, &params, &animations, 0.016);
+        }
+        assert_eq!(node.current_frame().unwrap().animation, 0);
+    }
+}
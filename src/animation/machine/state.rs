@@ -134,6 +134,14 @@ pub struct State {
     /// Root node of the state that provides the state with animation data.
     #[reflect(read_only)]
     pub root: Handle<PoseNode>,
+
+    /// Index of a layer of the parent state machine, that will be used as a nested state machine (sub-machine) of
+    /// the state. If the index is set, the state takes its pose from the sub-machine and its root node is ignored.
+    /// Sub-machine layers do not contribute to the final pose of the state machine directly. A sub-machine is reset
+    /// to its entry state every time its state is entered. The index is kept valid by the state machine when its
+    /// layers are inserted or removed.
+    #[visit(optional)]
+    pub sub_machine: Option<usize>,
 }
 
 impl NameProvider for State {
@@ -151,9 +159,24 @@ impl State {
             on_enter_actions: Default::default(),
             on_leave_actions: Default::default(),
             root,
+            sub_machine: Default::default(),
+        }
+    }
+
+    /// Creates new instance of state, that uses a layer with the given index as a nested state machine. See
+    /// [`Self::sub_machine`] docs for more info.
+    pub fn new_sub_machine(name: &str, sub_machine: usize) -> Self {
+        Self {
+            sub_machine: Some(sub_machine),
+            ..Self::new(name, Handle::NONE)
         }
     }
 
+    /// Returns `true` if the state uses a nested state machine, `false` - otherwise.
+    pub fn is_sub_machine(&self) -> bool {
+        self.sub_machine.is_some()
+    }
+
     /// Returns a final pose of the state.
     pub fn pose<'a>(&self, nodes: &'a Pool<PoseNode>) -> Option<Ref<'a, AnimationPose>> {
         nodes.try_borrow(self.root).map(|root| root.pose())
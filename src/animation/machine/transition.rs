@@ -345,8 +345,50 @@ impl LogicNode {
     }
 }
 
+/// Defines which transitions can interrupt an active transition. An interrupting transition starts blending from the
+/// current (blended) pose of the interrupted transition, so there will be no sudden jumps.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Visit,
+    Reflect,
+    EnumVariantNames,
+    EnumString,
+    AsRefStr,
+)]
+pub enum TransitionInterruption {
+    /// The transition cannot be interrupted.
+    #[default]
+    None,
+    /// The transition can be interrupted by transitions from its source state.
+    SourceState,
+    /// The transition can be interrupted by transitions from its destination state.
+    DestinationState,
+    /// The transition can be interrupted by transitions from its source state first, then by transitions from its
+    /// destination state.
+    SourceThenDestination,
+}
+
 /// Transition is a connection between two states with a rule that defines possibility of actual transition with blending.
-#[derive(Default, Debug, Clone, Reflect, PartialEq)]
+///
+/// # Any-state transitions
+///
+/// A transition with [`Self::is_from_any_state`] flag set can be activated from any state of a layer (except its
+/// destination state), its source state is used only for visualization purposes. Such transitions are useful to
+/// reduce the amount of transitions, for example a transition to "Death" state could be made only once.
+///
+/// # Exit time
+///
+/// A transition with [`Self::has_exit_time`] flag set can be activated only when every animation of the source state
+/// has reached the given normalized time position (see [`Self::exit_time`]). Normalized time of looping animations is
+/// counted from the moment of entering the source state and it is not wrapped, so the exit time of `1.0` means the end
+/// of the first cycle. If the condition of such transition is [`LogicNode::Parameter`] with an empty name, then the
+/// transition is activated by the exit time only.
+#[derive(Debug, Clone, Reflect, PartialEq)]
 pub struct Transition {
     /// The name of the transition, it is used for debug output.
     #[reflect(description = "The name of the transition, it is used for debug output.")]
@@ -372,6 +414,34 @@ pub struct Transition {
 
     /// 0 - evaluates `src` pose, 1 - `dest`, 0..1 - blends `src` and `dest`
     pub(crate) blend_factor: f32,
+
+    #[reflect(
+        description = "Whether the transition can be activated from any state of the layer or not."
+    )]
+    pub(crate) from_any_state: bool,
+
+    #[reflect(description = "Defines which transitions can interrupt this transition.")]
+    pub(crate) interruption: TransitionInterruption,
+
+    #[reflect(
+        description = "Whether the transition must wait until the animations of the source state \
+        reach the exit time or not."
+    )]
+    pub(crate) has_exit_time: bool,
+
+    #[reflect(
+        description = "Normalized (0..1) time position of the animations of the source state, at which \
+        the transition can be activated.",
+        min_value = 0.0,
+        max_value = 1.0
+    )]
+    pub(crate) exit_time: f32,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self::new("", Handle::NONE, Handle::NONE, 0.0, "")
+    }
 }
 
 impl Visit for Transition {
@@ -405,6 +475,12 @@ impl Visit for Transition {
             self.condition.visit("Condition", &mut guard)?;
         }
 
+        // Backward compatibility.
+        let _ = self.from_any_state.visit("FromAnyState", &mut guard);
+        let _ = self.interruption.visit("Interruption", &mut guard);
+        let _ = self.has_exit_time.visit("HasExitTime", &mut guard);
+        let _ = self.exit_time.visit("ExitTime", &mut guard);
+
         Ok(())
     }
 }
@@ -433,6 +509,10 @@ impl Transition {
             dest,
             blend_factor: 0.0,
             condition: LogicNode::Parameter(rule.to_owned()),
+            from_any_state: false,
+            interruption: Default::default(),
+            has_exit_time: false,
+            exit_time: 1.0,
         }
     }
 
@@ -470,6 +550,69 @@ impl Transition {
         &self.condition
     }
 
+    /// Allows or disallows the transition to be activated from any state of the layer. See [`Transition`] docs
+    /// for more info.
+    pub fn set_from_any_state(&mut self, from_any_state: bool) {
+        self.from_any_state = from_any_state;
+    }
+
+    /// Returns `true` if the transition can be activated from any state of the layer, `false` - otherwise.
+    pub fn is_from_any_state(&self) -> bool {
+        self.from_any_state
+    }
+
+    /// Sets new interruption rule of the transition. See [`TransitionInterruption`] docs for more info.
+    pub fn set_interruption(&mut self, interruption: TransitionInterruption) {
+        self.interruption = interruption;
+    }
+
+    /// Returns current interruption rule of the transition.
+    pub fn interruption(&self) -> TransitionInterruption {
+        self.interruption
+    }
+
+    /// Enables or disables exit time of the transition. See [`Transition`] docs for more info.
+    pub fn set_has_exit_time(&mut self, has_exit_time: bool) {
+        self.has_exit_time = has_exit_time;
+    }
+
+    /// Returns `true` if the transition waits for the exit time, `false` - otherwise.
+    pub fn has_exit_time(&self) -> bool {
+        self.has_exit_time
+    }
+
+    /// Sets new normalized (`0..1`) exit time of the transition. See [`Transition`] docs for more info.
+    pub fn set_exit_time(&mut self, exit_time: f32) {
+        self.exit_time = exit_time.clamp(0.0, 1.0);
+    }
+
+    /// Returns current normalized exit time of the transition.
+    pub fn exit_time(&self) -> f32 {
+        self.exit_time
+    }
+
+    /// Returns `true` if the transition can be activated from the given state.
+    pub fn can_start_from(&self, state: Handle<State>) -> bool {
+        (self.from_any_state || self.source == state) && self.dest != state
+    }
+
+    /// Checks whether the transition is ready to be activated. `source_time` is the normalized time position of the
+    /// animations of the state from which the transition will be activated. It is not wrapped for looping animations
+    /// (see [`Transition`] docs).
+    pub fn is_ready(
+        &self,
+        parameters: &ParameterContainer,
+        animations: &AnimationContainer,
+        source_time: f32,
+    ) -> bool {
+        let condition = match self.condition {
+            LogicNode::Parameter(ref name) if name.is_empty() && self.has_exit_time => true,
+            _ => self.condition.calculate_value(parameters, animations),
+        };
+
+        condition && (!self.has_exit_time || source_time >= self.exit_time)
+    }
+
     /// Returns true if the transition from the source to the destination state was finished.
     #[inline]
     pub fn is_done(&self) -> bool {
@@ -495,3 +638,45 @@ impl Transition {
         self.blend_factor = self.elapsed_time / self.transition_time;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            machine::{Parameter, ParameterContainer, State, Transition},
+            AnimationContainer,
+        },
+        core::pool::Handle,
+    };
+
+    #[test]
+    fn test_any_state_transition_with_exit_time() {
+        let idle = Handle::<State>::new(1, 1);
+        let walk = Handle::<State>::new(2, 1);
+        let death = Handle::<State>::new(3, 1);
+
+        let mut transition = Transition::new("AnyToDeath", Handle::NONE, death, 0.2, "Dead");
+        assert!(!transition.can_start_from(idle));
+
+        transition.set_from_any_state(true);
+        assert!(transition.can_start_from(idle));
+        assert!(transition.can_start_from(walk));
+        assert!(!transition.can_start_from(death));
+
+        let animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Dead", Parameter::Rule(true));
+        assert!(transition.is_ready(&parameters, &animations, 0.1));
+
+        transition.set_has_exit_time(true);
+        transition.set_exit_time(0.75);
+        assert!(!transition.is_ready(&parameters, &animations, 0.5));
+        assert!(transition.is_ready(&parameters, &animations, 0.8));
+
+        // Transitions without a condition are driven purely by exit time.
+        let exit = Transition::new("WalkToIdle", walk, idle, 0.2, "");
+        assert!(!exit.is_ready(&parameters, &animations, 1.0));
+
+        assert_eq!(Transition::default().exit_time(), exit.exit_time());
+    }
+}
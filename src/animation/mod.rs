@@ -4,10 +4,12 @@
 #![warn(missing_docs)]
 
 use crate::{
-    animation::track::Track,
+    animation::{
+        compression::AnimationCompressionSettings, machine::ik::rotation_of, track::Track,
+    },
     core::{
-        algebra::{UnitQuaternion, Vector3},
-        math::wrapf,
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        math::{wrapf, Matrix4Ext},
         pool::{Handle, Pool, Ticket},
         reflect::prelude::*,
         uuid::Uuid,
@@ -27,11 +29,16 @@ use std::{
 
 use crate::animation::value::{TrackValue, ValueBinding};
 pub use pose::{AnimationPose, NodePose};
-pub use signal::{AnimationEvent, AnimationSignal};
+pub use signal::{
+    AnimationEvent, AnimationSignal, AnimationSignalAction, AnimationSignalMessage,
+    AnimationSignalPayload,
+};
 
+pub mod compression;
 pub mod container;
 pub mod machine;
 pub mod pose;
+pub mod retarget;
 pub mod signal;
 pub mod spritesheet;
 pub mod track;
@@ -203,6 +210,10 @@ pub struct Animation {
     #[reflect(hidden)]
     #[visit(skip)]
     events: VecDeque<AnimationEvent>,
+    // Non-serialized
+    #[reflect(hidden)]
+    #[visit(skip)]
+    dispatch_queue: Vec<AnimationEvent>,
 }
 
 /// Root motion settings. It allows you to set a node (root) from which the motion will be taken
@@ -243,6 +254,21 @@ impl RootMotion {
         self.delta_position = self.delta_position.lerp(&other.delta_position, weight);
         self.delta_rotation = self.delta_rotation.nlerp(&other.delta_rotation, weight);
     }
+
+    /// Converts the motion into world-space linear and angular velocities. `transform` is a global transform of the
+    /// space, in which the motion is defined - usually it is the global transform of the model root, that contains
+    /// the animated bones. The resulting velocities could be directly applied to a rigid body or any other kind of
+    /// character controller.
+    pub fn velocities(&self, transform: &Matrix4<f32>, dt: f32) -> (Vector3<f32>, Vector3<f32>) {
+        if dt <= 0.0 {
+            return Default::default();
+        }
+
+        let linear = transform.basis() * self.delta_position.scale(1.0 / dt);
+        let angular = rotation_of(transform) * self.delta_rotation.scaled_axis().scale(1.0 / dt);
+
+        (linear, angular)
+    }
 }
 
 impl NameProvider for Animation {
@@ -264,6 +290,7 @@ impl Clone for Animation {
             signals: self.signals.clone(),
             root_motion_settings: self.root_motion_settings.clone(),
             events: Default::default(),
+            dispatch_queue: Default::default(),
             time_slice: self.time_slice.clone(),
             root_motion: self.root_motion.clone(),
         }
@@ -315,6 +342,56 @@ impl Animation {
         }
     }
 
+    /// Converts every track of the animation to additive form, relative to the values of the tracks at the given
+    /// time. Additive animations store differences between some pose and a reference pose, such animations are
+    /// meant to be added on top of other animations (breathing, recoil, leaning, etc.) using additive layers
+    /// ([`machine::LayerBlendMode::Additive`]) or [`machine::BlendAdditive`] nodes. Playing an additive
+    /// animation alone makes little sense.
+    pub fn make_additive(&mut self, reference_time: f32) {
+        for track in self.tracks.iter_mut() {
+            track.make_additive(reference_time);
+        }
+    }
+
+    /// Compresses the animation using the given settings. Every track is reduced using a tolerance that corresponds
+    /// to its binding, rotation tracks are also quantized if [`AnimationCompressionSettings::quantize_rotations`]
+    /// is set. See [`AnimationCompressionSettings`] docs for more info.
+    pub fn compress(&mut self, settings: &AnimationCompressionSettings) {
+        for track in self.tracks.iter_mut() {
+            let (tolerance, is_rotation) = match track.binding() {
+                ValueBinding::Position => (settings.position_tolerance, false),
+                ValueBinding::Scale => (settings.scale_tolerance, false),
+                ValueBinding::Rotation => (settings.rotation_tolerance, true),
+                ValueBinding::Property { .. } => (settings.property_tolerance, false),
+            };
+
+            let container = track.data_container_mut();
+            if is_rotation && settings.quantize_rotations {
+                // Quantized track is interpolated in quaternion space, so the reduction must be done in the
+                // same space to keep the error within the tolerance.
+                container.dequantize_rotations();
+                container.compress_rotations(tolerance);
+            } else if is_rotation {
+                // Errors of Euler angles are accumulated in the final rotation, each angle gets its share.
+                container.reduce_keys(tolerance / 3.0);
+            } else {
+                container.reduce_keys(tolerance);
+            }
+        }
+    }
+
+    /// Returns sorted list of unique locations of keys of every curve of every track of the animation.
+    pub fn key_locations(&self) -> Vec<f32> {
+        let mut locations = self
+            .tracks
+            .iter()
+            .flat_map(|t| t.data_container().key_locations())
+            .collect::<Vec<_>>();
+        locations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        locations.dedup();
+        locations
+    }
+
     /// Returns a reference to tracks container.
     pub fn tracks(&self) -> &[Track] {
         &self.tracks
@@ -381,11 +458,22 @@ impl Animation {
                     && (current_time_position > signal.time && new_time_position <= signal.time)
             {
                 // TODO: Make this configurable.
+                let event = AnimationEvent {
+                    signal_id: signal.id,
+                    name: signal.name.clone(),
+                    payload: signal.payload.clone(),
+                };
+
+                // The queue is drained only by the engine, limit its size the same way as the events
+                // queue, so it won't grow indefinitely when the animation is used outside of a scene.
+                if (signal.dispatch_to_scripts || signal.action != AnimationSignalAction::None)
+                    && self.dispatch_queue.len() < 32
+                {
+                    self.dispatch_queue.push(event.clone());
+                }
+
                 if self.events.len() < 32 {
-                    self.events.push_back(AnimationEvent {
-                        signal_id: signal.id,
-                        name: signal.name.clone(),
-                    });
+                    self.events.push_back(event);
                 }
             }
         }
@@ -588,6 +676,12 @@ impl Animation {
         std::mem::take(&mut self.events)
     }
 
+    /// Takes events of signals, that have a built-in action or must be dispatched to scripts. The engine
+    /// calls this method for you.
+    pub(crate) fn take_dispatch_queue(&mut self) -> Vec<AnimationEvent> {
+        std::mem::take(&mut self.dispatch_queue)
+    }
+
     /// Returns current time position of the animation. The time position is guaranteed to be in the range of
     /// current time slice of the animation.
     pub fn time_position(&self) -> f32 {
@@ -794,6 +888,7 @@ impl Default for Animation {
             signals: Default::default(),
             root_motion_settings: None,
             events: Default::default(),
+            dispatch_queue: Default::default(),
             time_slice: Default::default(),
             root_motion: None,
         }
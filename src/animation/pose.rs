@@ -37,6 +37,12 @@ impl NodePose {
     pub fn blend_with(&mut self, other: &NodePose, weight: f32) {
         self.values.blend_with(&other.values, weight)
     }
+
+    /// Adds the other (additive) pose to the current using the given weight. See [`super::value::TrackValue::add_with`]
+    /// docs for more info.
+    pub fn add_with(&mut self, other: &NodePose, weight: f32) {
+        self.values.add_with(&other.values, weight)
+    }
 }
 
 /// Animations pose is a set of node poses. See [`NodePose`] docs for more info.
@@ -44,6 +50,9 @@ impl NodePose {
 pub struct AnimationPose {
     poses: FxHashMap<Handle<Node>, NodePose>,
     root_motion: Option<RootMotion>,
+    // Total weight of the poses, that were blended into this pose. It is used to blend root motion as a weighted
+    // average, so it won't depend on the order in which the poses are blended.
+    root_motion_weight: f32,
 }
 
 impl AnimationPose {
@@ -54,11 +63,13 @@ impl AnimationPose {
             dest.poses.insert(*handle, local_pose.clone());
         }
         dest.root_motion = self.root_motion.clone();
+        dest.root_motion_weight = self.root_motion_weight;
     }
 
     /// Sets root motion for the animation pose; the root motion will be blended with other motions
     /// and the result can be obtained on a final pose.
     pub fn set_root_motion(&mut self, root_motion: Option<RootMotion>) {
+        self.root_motion_weight = if root_motion.is_some() { 1.0 } else { 0.0 };
         self.root_motion = root_motion;
     }
 
@@ -67,9 +78,7 @@ impl AnimationPose {
         self.root_motion.as_ref()
     }
 
-    /// Blends current animation pose with another using a weight coefficient. Missing node poses (from either animation poses)
-    /// will become a simple copies of a respective node pose.
-    pub fn blend_with(&mut self, other: &AnimationPose, weight: f32) {
+    fn blend_node_poses(&mut self, other: &AnimationPose, weight: f32) {
         for (handle, other_pose) in other.poses.iter() {
             if let Some(current_pose) = self.poses.get_mut(handle) {
                 current_pose.blend_with(other_pose, weight);
@@ -77,10 +86,56 @@ impl AnimationPose {
                 self.add_node_pose(other_pose.clone());
             }
         }
+    }
+
+    /// Blends current animation pose with another using a weight coefficient. Missing node poses (from either animation poses)
+    /// will become a simple copies of a respective node pose.
+    ///
+    /// Root motion is blended as a weighted average of root motions of every pose blended into the current pose since the
+    /// last [`Self::reset`] call. Poses without root motion are treated as poses with zero motion.
+    pub fn blend_with(&mut self, other: &AnimationPose, weight: f32) {
+        self.blend_node_poses(other, weight);
+
+        let total_weight = self.root_motion_weight + weight;
+        if total_weight > f32::EPSILON
+            && (self.root_motion.is_some() || other.root_motion.is_some())
+        {
+            self.root_motion
+                .get_or_insert_with(Default::default)
+                .blend_with(
+                    &other.root_motion.clone().unwrap_or_default(),
+                    weight / total_weight,
+                );
+        }
+        self.root_motion_weight = total_weight;
+    }
+
+    /// Blends current animation pose with a pose of an overriding layer of a state machine using a weight coefficient.
+    /// Node poses are blended the same way as in [`Self::blend_with`], but root motion of the current pose stays intact
+    /// if the other pose has no root motion. This way layers without root motion (for example upper body layers) won't
+    /// slow down the character.
+    pub fn override_with(&mut self, other: &AnimationPose, weight: f32) {
+        self.blend_node_poses(other, weight);
 
-        self.root_motion
-            .get_or_insert_with(Default::default)
-            .blend_with(&other.root_motion.clone().unwrap_or_default(), weight);
+        if let Some(other_root_motion) = other.root_motion.as_ref() {
+            match self.root_motion.as_mut() {
+                Some(root_motion) => root_motion.blend_with(other_root_motion, weight),
+                None => self.root_motion = Some(other_root_motion.clone()),
+            }
+            self.root_motion_weight = 1.0;
+        }
+    }
+
+    /// Adds an additive animation pose on top of the current animation pose using a weight coefficient. The other pose
+    /// must contain differences relative to some reference pose (see [`super::Animation::make_additive`]). Node poses that
+    /// are missing in the current pose are ignored, because there's nothing to add the differences to. Root motion is
+    /// not affected.
+    pub fn add_with(&mut self, other: &AnimationPose, weight: f32) {
+        for (handle, other_pose) in other.poses.iter() {
+            if let Some(current_pose) = self.poses.get_mut(handle) {
+                current_pose.add_with(other_pose, weight);
+            }
+        }
     }
 
     fn add_node_pose(&mut self, local_pose: NodePose) {
@@ -106,6 +161,8 @@ impl AnimationPose {
     /// Clears the pose.
     pub fn reset(&mut self) {
         self.poses.clear();
+        self.root_motion = None;
+        self.root_motion_weight = 0.0;
     }
 
     /// Returns a reference to inner node pose map.
@@ -154,3 +211,46 @@ impl AnimationPose {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{AnimationPose, RootMotion},
+        core::algebra::Vector3,
+    };
+
+    fn pose_with_motion(delta_position: Vector3<f32>) -> AnimationPose {
+        let mut pose = AnimationPose::default();
+        pose.set_root_motion(Some(RootMotion {
+            delta_position,
+            ..Default::default()
+        }));
+        pose
+    }
+
+    #[test]
+    fn test_root_motion_blending() {
+        let walk = pose_with_motion(Vector3::new(0.0, 0.0, 1.0));
+        let run = pose_with_motion(Vector3::new(0.0, 0.0, 3.0));
+
+        // Equal weights must produce an average motion regardless of the order of blending.
+        let mut blended = AnimationPose::default();
+        blended.blend_with(&walk, 0.5);
+        blended.blend_with(&run, 0.5);
+        let delta = blended.root_motion().unwrap().delta_position;
+        assert!((delta.z - 2.0).abs() < 1e-5);
+
+        // Transition-like blending (1 - f, f) must be a linear interpolation.
+        blended.reset();
+        assert!(blended.root_motion().is_none());
+        blended.blend_with(&walk, 0.75);
+        blended.blend_with(&run, 0.25);
+        let delta = blended.root_motion().unwrap().delta_position;
+        assert!((delta.z - 1.5).abs() < 1e-5);
+
+        // Overriding layer without root motion keeps the motion intact.
+        blended.override_with(&AnimationPose::default(), 1.0);
+        let delta = blended.root_motion().unwrap().delta_position;
+        assert!((delta.z - 1.5).abs() < 1e-5);
+    }
+}
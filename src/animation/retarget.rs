@@ -0,0 +1,311 @@
+//! Animation retargeting allows you to play an animation, that was made for one skeleton, on another skeleton
+//! with different proportions and bone names. See [`retarget_animation`] docs for more info.
+
+use crate::{
+    animation::{
+        machine::ik::rotation_of,
+        track::Track,
+        value::{TrackValue, ValueBinding},
+        Animation,
+    },
+    core::{
+        algebra::{Matrix3, Matrix4, UnitQuaternion, Vector3},
+        log::Log,
+        math::Matrix4Ext,
+        pool::Handle,
+    },
+    resource::bone_mapping::BoneMapping,
+    scene::{graph::Graph, node::Node, transform::Transform},
+};
+
+/// Bind pose of a bone - its local transform and global transform of its parent.
+struct BindPose {
+    local: Transform,
+    parent_global: Matrix4<f32>,
+}
+
+impl BindPose {
+    fn new(graph: &Graph, handle: Handle<Node>) -> Option<Self> {
+        let node = graph.try_get(handle)?;
+
+        let mut parent_global = Matrix4::identity();
+        let mut current = node.parent();
+        while let Some(parent) = graph.try_get(current) {
+            parent_global = parent.local_transform().matrix() * parent_global;
+            current = parent.parent();
+        }
+
+        Some(Self {
+            local: node.local_transform().clone(),
+            parent_global,
+        })
+    }
+
+    // Length of the bone in world space.
+    fn length(&self) -> f32 {
+        (self.parent_global.basis() * **self.local.position()).norm()
+    }
+
+    // A frame, in which local rotation of the bone is applied.
+    fn rotation_frame(&self) -> UnitQuaternion<f32> {
+        rotation_of(&self.parent_global) * **self.local.pre_rotation()
+    }
+}
+
+fn retarget_track(track: &mut Track, source: &BindPose, target: &BindPose, skeleton_ratio: f32) {
+    match track.binding() {
+        ValueBinding::Position => {
+            let source_position = **source.local.position();
+            let target_position = **target.local.position();
+
+            let source_length = source.length();
+            let ratio = if source_length > f32::EPSILON {
+                target.length() / source_length
+            } else {
+                skeleton_ratio
+            };
+
+            // Offsets are converted to world space of the source skeleton and then back to local space of
+            // the target skeleton, this way the difference of axes and scale of parent bones is compensated.
+            let to_target = target
+                .parent_global
+                .basis()
+                .try_inverse()
+                .unwrap_or_else(Matrix3::identity)
+                * source.parent_global.basis()
+                * ratio;
+
+            track.data_container_mut().map_values(|value| match value {
+                TrackValue::Vector3(position) => {
+                    TrackValue::Vector3(target_position + to_target * (position - source_position))
+                }
+                _ => value,
+            });
+        }
+        ValueBinding::Rotation => {
+            let source_frame = source.rotation_frame();
+            let target_frame = target.rotation_frame();
+            let source_rotation = **source.local.rotation();
+            let target_rotation = **target.local.rotation();
+
+            track.data_container_mut().map_values(|value| match value {
+                TrackValue::UnitQuaternion(rotation) => {
+                    // A rotation of the bone relative to its bind pose, in world space.
+                    let delta = source_frame
+                        * rotation
+                        * source_rotation.inverse()
+                        * source_frame.inverse();
+                    TrackValue::UnitQuaternion(
+                        target_frame.inverse() * delta * target_frame * target_rotation,
+                    )
+                }
+                _ => value,
+            });
+        }
+        ValueBinding::Scale => {
+            let source_scale = **source.local.scale();
+            let target_scale = **target.local.scale();
+
+            track.data_container_mut().map_values(|value| match value {
+                TrackValue::Vector3(scale) => TrackValue::Vector3(Vector3::from_fn(|i, _| {
+                    if source_scale[i].abs() > f32::EPSILON {
+                        target_scale[i] * scale[i] / source_scale[i]
+                    } else {
+                        target_scale[i]
+                    }
+                })),
+                _ => value,
+            });
+        }
+        ValueBinding::Property { .. } => {
+            // Arbitrary properties do not depend on proportions of skeletons.
+        }
+    }
+}
+
+/// Creates a copy of the given animation, that is retargeted from a skeleton in `source_graph` (the graph tracks of the
+/// animation are bound to) to a skeleton in `target_graph` starting from `target_root` node.
+///
+/// # Details
+///
+/// Bones of the target skeleton are searched by names using the given bone mapping (see [`BoneMapping`] docs). Tracks
+/// of bones, that have no correspondence in the target skeleton, will be bound to [`Handle::NONE`].
+///
+/// Current local transforms of the bones of both skeletons are treated as bind poses, so both skeletons must be in
+/// the same pose (usually T-pose or A-pose) when the method is called. Then every track is converted as follows:
+///
+/// - Rotations are converted to rotations relative to the bind pose in world space, and then applied to the bind pose
+/// of the target bone. This way bones with different orientation of local axes are handled correctly.
+/// - Translations are kept relative to the bind pose and scaled by the ratio of lengths of the target and the source
+/// bones. This way, for example, the height of hips of a short character will be adjusted correctly.
+/// - Scale is kept relative to the bind pose.
+///
+/// Keep in mind, that converted tracks are re-sampled at every key location with linear interpolation.
+pub fn retarget_animation(
+    animation: &Animation,
+    source_graph: &Graph,
+    target_graph: &Graph,
+    target_root: Handle<Node>,
+    mapping: &BoneMapping,
+) -> Animation {
+    let mut retargeted = animation.clone();
+
+    let mut pairs = Vec::new();
+    for (i, track) in retargeted.tracks_mut().iter_mut().enumerate() {
+        let source_node = match source_graph.try_get(track.target()) {
+            Some(source_node) => source_node,
+            None => {
+                track.set_target(Handle::NONE);
+                continue;
+            }
+        };
+
+        match mapping
+            .target_name(source_node.name())
+            .and_then(|name| target_graph.find_by_name(target_root, name))
+        {
+            Some((target, _)) => {
+                if let (Some(source_bind), Some(target_bind)) = (
+                    BindPose::new(source_graph, track.target()),
+                    BindPose::new(target_graph, target),
+                ) {
+                    pairs.push((i, source_bind, target_bind));
+                }
+                track.set_target(target);
+            }
+            None => {
+                Log::warn(format!(
+                    "Failed to retarget animation {} for node {}: there is no matching bone.",
+                    animation.name(),
+                    source_node.name()
+                ));
+                track.set_target(Handle::NONE);
+            }
+        }
+    }
+
+    // Overall ratio of sizes of skeletons, it is used for bones with zero length.
+    let (source_size, target_size) = pairs.iter().fold((0.0, 0.0), |(s, t), (_, sb, tb)| {
+        (s + sb.length(), t + tb.length())
+    });
+    let skeleton_ratio = if source_size > f32::EPSILON {
+        target_size / source_size
+    } else {
+        1.0
+    };
+
+    for (i, source_bind, target_bind) in pairs {
+        retarget_track(
+            &mut retargeted.tracks_mut()[i],
+            &source_bind,
+            &target_bind,
+            skeleton_ratio,
+        );
+    }
+
+    retargeted
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            container::{TrackDataContainer, TrackValueKind},
+            retarget::retarget_animation,
+            track::Track,
+            value::{TrackValue, ValueBinding},
+            Animation,
+        },
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            curve::{CurveKey, CurveKeyKind},
+            pool::Handle,
+        },
+        resource::bone_mapping::BoneMapping,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            transform::{Transform, TransformBuilder},
+        },
+    };
+
+    fn make_bone(graph: &mut Graph, name: &str, transform: Transform) -> Handle<Node> {
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name(name)
+                .with_local_transform(transform),
+        )
+        .build(graph)
+    }
+
+    #[test]
+    fn test_retarget_position_and_rotation() {
+        let mut source_graph = Graph::new();
+        let source_hips = make_bone(
+            &mut source_graph,
+            "mixamorig:Hips",
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                .build(),
+        );
+
+        // Target skeleton is twice as tall and its hips bone is rotated by 90 degrees in bind pose.
+        let bind_rotation = UnitQuaternion::from_scaled_axis(Vector3::new(0.0, 1.5707964, 0.0));
+        let mut target_graph = Graph::new();
+        make_bone(
+            &mut target_graph,
+            "Pelvis",
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+                .with_local_rotation(bind_rotation)
+                .build(),
+        );
+
+        let mut position = TrackDataContainer::new(TrackValueKind::Vector3);
+        for (i, curve) in position.curves_mut().iter_mut().enumerate() {
+            curve.add_key(CurveKey::new(0.0, [0.0, 1.0, 0.0][i], CurveKeyKind::Linear));
+            curve.add_key(CurveKey::new(1.0, [0.0, 1.5, 0.0][i], CurveKeyKind::Linear));
+        }
+        let mut position_track = Track::new(position, ValueBinding::Position);
+        position_track.set_target(source_hips);
+
+        let mut rotation = TrackDataContainer::new(TrackValueKind::UnitQuaternion);
+        for curve in rotation.curves_mut().iter_mut() {
+            curve.add_key(CurveKey::new(0.0, 0.0, CurveKeyKind::Linear));
+        }
+        rotation.curves_mut()[0].add_key(CurveKey::new(1.0, 0.5, CurveKeyKind::Linear));
+        let mut rotation_track = Track::new(rotation, ValueBinding::Rotation);
+        rotation_track.set_target(source_hips);
+
+        let mut animation = Animation::default();
+        animation.add_track(position_track);
+        animation.add_track(rotation_track);
+
+        let mut mapping = BoneMapping::default();
+        mapping.add_bone("mixamorig:Hips", "Pelvis");
+
+        let root = target_graph.get_root();
+        let retargeted =
+            retarget_animation(&animation, &source_graph, &target_graph, root, &mapping);
+
+        let position = retargeted.tracks()[0].data_container();
+        assert_eq!(
+            position.fetch(1.0),
+            Some(TrackValue::Vector3(Vector3::new(0.0, 3.0, 0.0)))
+        );
+
+        let rotation = retargeted.tracks()[1].data_container();
+        match (rotation.fetch(0.0), rotation.fetch(1.0)) {
+            (Some(TrackValue::UnitQuaternion(start)), Some(TrackValue::UnitQuaternion(end))) => {
+                // Bind pose is preserved and the motion is the same rotation around world X axis.
+                assert!(start.angle_to(&bind_rotation) < 0.001);
+                let expected =
+                    UnitQuaternion::from_scaled_axis(Vector3::new(0.5, 0.0, 0.0)) * bind_rotation;
+                assert!(end.angle_to(&expected) < 0.001);
+            }
+            _ => panic!("Rotation track must produce rotations!"),
+        }
+    }
+}
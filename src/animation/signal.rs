@@ -1,18 +1,81 @@
 //! Signal is a named marker on specific time position on the animation timeline. See [`AnimationSignal`] docs for more info.
 
 use crate::{
-    core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
+    animation::Animation,
+    core::{algebra::Vector3, pool::Handle, reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
+    resource::model::ModelResource,
+    scene::{node::Node, sound::SoundBufferResource},
     utils::NameProvider,
 };
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 /// An event happened in an animation.
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct AnimationEvent {
     /// An id of an animation event.
     pub signal_id: Uuid,
 
     /// Name of the signal emitted the event.
     pub name: String,
+
+    /// A payload of the signal emitted the event.
+    pub payload: AnimationSignalPayload,
+}
+
+/// A typed value, that is attached to a signal and passed with every event produced by the signal. It allows
+/// you to share the same handling code for multiple signals, for example a footstep signal could carry an index
+/// of a foot.
+#[derive(
+    Clone, Debug, Default, PartialEq, Visit, Reflect, EnumVariantNames, EnumString, AsRefStr,
+)]
+pub enum AnimationSignalPayload {
+    /// No payload.
+    #[default]
+    None,
+    /// A boolean value.
+    Bool(bool),
+    /// An integer value.
+    Integer(i32),
+    /// A real number.
+    Float(f32),
+    /// A string.
+    String(String),
+    /// A 3-dimensional vector.
+    Vector3(Vector3<f32>),
+}
+
+/// A built-in action, that will be performed by the engine every time when a signal is raised. Built-in
+/// actions are performed for animations of animation players in scenes, that have scripts enabled.
+///
+/// Bones are searched by names in the hierarchy of the parent node of an animation player (which is usually
+/// a root of an instance of a character model), this way the same animation could be used on multiple
+/// characters. If a bone name is empty or there's no such bone, the animation player node is used instead.
+#[derive(
+    Clone, Debug, Default, PartialEq, Visit, Reflect, EnumVariantNames, EnumString, AsRefStr,
+)]
+pub enum AnimationSignalAction {
+    /// No action.
+    #[default]
+    None,
+    /// Plays a sound buffer at the position of a bone. A new play-once sound node is created and attached to the
+    /// bone, it will be removed automatically when the playback is finished.
+    PlaySound {
+        /// A sound buffer to play.
+        buffer: Option<SoundBufferResource>,
+        /// Name of a bone, that will be used as a sound source.
+        bone: String,
+        /// Gain of the sound.
+        gain: f32,
+    },
+    /// Instantiates a prefab at the position of a bone.
+    SpawnPrefab {
+        /// A prefab to instantiate.
+        prefab: Option<ModelResource>,
+        /// Name of a bone, that will be used as a spawn point.
+        bone: String,
+        /// If set, the instance will be attached to the bone and will follow it.
+        attach: bool,
+    },
 }
 
 /// Signal is a named marker on specific time position on the animation timeline. Signal will emit an event if the animation playback
@@ -34,6 +97,69 @@ pub struct AnimationSignal {
 
     /// The flag defines whether the signal is enabled or not. Disabled signals won't produce any events.
     pub enabled: bool,
+
+    /// A payload that will be passed with every event produced by the signal.
+    #[visit(optional)]
+    pub payload: AnimationSignalPayload,
+
+    /// If set, every event of the signal will be sent to scripts of an animation player node and of its ancestors
+    /// as [`AnimationSignalMessage`]. Scripts must subscribe to this message type to receive such messages.
+    #[visit(optional)]
+    pub dispatch_to_scripts: bool,
+
+    /// A built-in action, that will be performed every time the signal is raised. See [`AnimationSignalAction`]
+    /// docs for more info.
+    #[visit(optional)]
+    pub action: AnimationSignalAction,
+}
+
+/// A script message, that is sent by the engine for every event of a signal with
+/// [`AnimationSignal::dispatch_to_scripts`] flag set.
+///
+/// ```rust
+/// # use fyrox::{
+/// #     animation::signal::AnimationSignalMessage,
+/// #     core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
+/// #     impl_component_provider,
+/// #     script::{ScriptContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait},
+/// # };
+/// #
+/// #[derive(Clone, Debug, Default, Reflect, Visit)]
+/// struct Character {}
+/// #
+/// # impl_component_provider!(Character);
+///
+/// impl ScriptTrait for Character {
+///     fn on_start(&mut self, ctx: &mut ScriptContext) {
+///         ctx.message_dispatcher
+///             .subscribe_to::<AnimationSignalMessage>(ctx.handle);
+///     }
+///
+///     fn on_message(
+///         &mut self,
+///         message: &mut dyn ScriptMessagePayload,
+///         _ctx: &mut ScriptMessageContext,
+///     ) {
+///         if let Some(signal) = message.downcast_ref::<AnimationSignalMessage>() {
+///             if signal.event.name == "Footstep" {
+///                 // Do something.
+///             }
+///         }
+///     }
+///
+///     fn id(&self) -> Uuid {
+///         todo!()
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct AnimationSignalMessage {
+    /// A handle of an animation player, that owns the animation.
+    pub animation_player: Handle<Node>,
+    /// A handle of the animation, that raised the signal.
+    pub animation: Handle<Animation>,
+    /// The event produced by the signal.
+    pub event: AnimationEvent,
 }
 
 impl NameProvider for AnimationSignal {
@@ -50,6 +176,9 @@ impl AnimationSignal {
             name: name.to_owned(),
             time,
             enabled: true,
+            payload: Default::default(),
+            dispatch_to_scripts: false,
+            action: Default::default(),
         }
     }
 }
@@ -61,6 +190,9 @@ impl Default for AnimationSignal {
             name: Default::default(),
             time: 0.0,
             enabled: true,
+            payload: Default::default(),
+            dispatch_to_scripts: false,
+            action: Default::default(),
         }
     }
 }
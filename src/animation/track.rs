@@ -136,6 +136,12 @@ impl Track {
         })
     }
 
+    /// Converts the track to additive form, relative to its value at the given time. See
+    /// [`TrackDataContainer::make_additive`] for more info.
+    pub fn make_additive(&mut self, reference_time: f32) {
+        self.frames.make_additive(reference_time)
+    }
+
     /// Enables or disables the track. Disabled tracks won't animate their nodes/properties.
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
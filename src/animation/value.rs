@@ -1,18 +1,32 @@
-//! A module that contains everything related to numeric values of animation tracks. See [`TrackValue`] docs
+//! A module that contains everything related to values of animation tracks. See [`TrackValue`] docs
 //! for more info.
 
 use crate::{
     core::{
         algebra::{UnitQuaternion, Vector2, Vector3, Vector4},
+        color::Color,
         log::Log,
         math::lerpf,
         num_traits::AsPrimitive,
-        reflect::{prelude::*, SetFieldByPathError},
+        parking_lot::Mutex,
+        pool::Handle,
+        reflect::{prelude::*, ResolvePath, SetFieldByPathError},
         visitor::prelude::*,
     },
-    scene::node::Node,
+    fxhash::FxHashMap,
+    material::SharedMaterial,
+    resource::{model::ModelResource, texture::TextureResource},
+    scene::{node::Node, sound::SoundBufferResource},
 };
-use std::fmt::{Debug, Display, Formatter};
+use lazy_static::lazy_static;
+use std::{
+    any::TypeId,
+    cell::RefCell,
+    fmt::{Debug, Display, Formatter},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 /// An actual type of a property value.
 #[derive(Visit, Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -113,6 +127,167 @@ pub enum ValueType {
     UnitQuaternionF32,
     /// `UnitQuaternion<f64>`
     UnitQuaternionF64,
+
+    /// `Color`, the values are taken from a 4-dimensional vector with components in `[0; 1]` range.
+    Color,
+
+    /// Any type, that could be produced by [`DiscreteValue`].
+    Discrete,
+}
+
+type EnumConstructor = fn(&str) -> Option<Box<dyn Reflect>>;
+
+lazy_static! {
+    static ref ANIMATED_ENUMS: Mutex<FxHashMap<TypeId, EnumConstructor>> =
+        Mutex::new(Default::default());
+}
+
+// Incremented on every registration, so threads know when to refresh their copies of the registry.
+static ANIMATED_ENUMS_REVISION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Animations are applied on every frame, they use a per-thread copy of the registry instead of locking
+    // the global one for every value.
+    static LOCAL_ANIMATED_ENUMS: RefCell<(usize, FxHashMap<TypeId, EnumConstructor>)> =
+        Default::default();
+}
+
+fn find_enum_constructor(type_id: TypeId) -> Option<EnumConstructor> {
+    LOCAL_ANIMATED_ENUMS.with(|local| {
+        let mut local = local.borrow_mut();
+        let revision = ANIMATED_ENUMS_REVISION.load(Ordering::Acquire);
+        if local.0 != revision {
+            *local = (revision, ANIMATED_ENUMS.lock().clone());
+        }
+        local.1.get(&type_id).cloned()
+    })
+}
+
+fn construct_enum<T: FromStr + Reflect>(variant: &str) -> Option<Box<dyn Reflect>> {
+    T::from_str(variant)
+        .ok()
+        .map(|value| Box::new(value) as Box<dyn Reflect>)
+}
+
+/// Registers an enumeration, so its variants could be set by animation tracks using [`DiscreteValue::EnumVariant`].
+/// Enumerations, that derive `EnumString` from `strum`, already implement [`FromStr`].
+///
+/// ```rust
+/// use fyrox::{animation::value::register_animated_enum, core::reflect::prelude::*};
+/// use strum_macros::EnumString;
+///
+/// #[derive(Debug, Reflect, EnumString)]
+/// enum DoorState {
+///     Open,
+///     Closed,
+/// }
+///
+/// register_animated_enum::<DoorState>();
+/// ```
+pub fn register_animated_enum<T: FromStr + Reflect>() {
+    ANIMATED_ENUMS
+        .lock()
+        .insert(TypeId::of::<T>(), construct_enum::<T>);
+    ANIMATED_ENUMS_REVISION.fetch_add(1, Ordering::Release);
+}
+
+/// A value of a non-numeric property. Such values cannot be interpolated, so they're switched instantly (see
+/// [`crate::animation::container::TrackValueKind::Discrete`] for more info). The type of the target property
+/// defines how the value is converted, for example a texture could be assigned to both `TextureResource` and
+/// `Option<TextureResource>` properties.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames)]
+pub enum DiscreteValue {
+    /// A boolean value.
+    Bool(bool),
+
+    /// A string value.
+    String(String),
+
+    /// A name of a variant of an enumeration without fields. The enumeration must be registered using
+    /// [`register_animated_enum`].
+    EnumVariant(String),
+
+    /// A handle of a scene node.
+    Node(Handle<Node>),
+
+    /// A material.
+    Material(SharedMaterial),
+
+    /// A texture resource.
+    Texture(Option<TextureResource>),
+
+    /// A model resource.
+    Model(Option<ModelResource>),
+
+    /// A sound buffer resource.
+    SoundBuffer(Option<SoundBufferResource>),
+}
+
+impl Default for DiscreteValue {
+    fn default() -> Self {
+        Self::Bool(false)
+    }
+}
+
+fn boxed_resource<T>(resource: &Option<T>, target: TypeId) -> Option<Box<dyn Reflect>>
+where
+    T: Reflect + Clone,
+    Option<T>: Reflect,
+{
+    if target == TypeId::of::<Option<T>>() {
+        Some(Box::new(resource.clone()))
+    } else if target == TypeId::of::<T>() {
+        resource
+            .clone()
+            .map(|resource| Box::new(resource) as Box<dyn Reflect>)
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if a property of the given type could be animated using discrete values (see [`DiscreteValue`]),
+/// `false` - otherwise.
+pub fn is_discrete_type(type_id: TypeId) -> bool {
+    [
+        TypeId::of::<bool>(),
+        TypeId::of::<String>(),
+        TypeId::of::<Handle<Node>>(),
+        TypeId::of::<SharedMaterial>(),
+        TypeId::of::<TextureResource>(),
+        TypeId::of::<Option<TextureResource>>(),
+        TypeId::of::<ModelResource>(),
+        TypeId::of::<Option<ModelResource>>(),
+        TypeId::of::<SoundBufferResource>(),
+        TypeId::of::<Option<SoundBufferResource>>(),
+    ]
+    .contains(&type_id)
+        || find_enum_constructor(type_id).is_some()
+}
+
+impl DiscreteValue {
+    /// Tries to convert the value to a boxed value of the given type, that can be used to set the value using
+    /// reflection.
+    pub fn type_cast(&self, target: TypeId) -> Option<Box<dyn Reflect>> {
+        match self {
+            DiscreteValue::Bool(value) if target == TypeId::of::<bool>() => Some(Box::new(*value)),
+            DiscreteValue::String(value) if target == TypeId::of::<String>() => {
+                Some(Box::new(value.clone()))
+            }
+            DiscreteValue::EnumVariant(variant) => {
+                find_enum_constructor(target).and_then(|constructor| constructor(variant))
+            }
+            DiscreteValue::Node(handle) if target == TypeId::of::<Handle<Node>>() => {
+                Some(Box::new(*handle))
+            }
+            DiscreteValue::Material(material) if target == TypeId::of::<SharedMaterial>() => {
+                Some(Box::new(material.clone()))
+            }
+            DiscreteValue::Texture(texture) => boxed_resource(texture, target),
+            DiscreteValue::Model(model) => boxed_resource(model, target),
+            DiscreteValue::SoundBuffer(buffer) => boxed_resource(buffer, target),
+            _ => None,
+        }
+    }
 }
 
 impl Default for ValueType {
@@ -123,7 +298,8 @@ impl Default for ValueType {
 
 /// A real value that can be produced by an animation track. Animations always operate on real numbers (`f32`) for any kind
 /// of machine numeric types (including `bool`). This is needed to be able to blend values; final blending result is then
-/// converted to an actual machine type of a target property.
+/// converted to an actual machine type of a target property. The only exception is [`TrackValue::Discrete`], that holds
+/// a non-numeric value.
 #[derive(Clone, Debug, PartialEq)]
 pub enum TrackValue {
     /// A real number.
@@ -140,11 +316,14 @@ pub enum TrackValue {
 
     /// A quaternion that represents some rotation.
     UnitQuaternion(UnitQuaternion<f32>),
+
+    /// A non-numeric value, see [`DiscreteValue`] docs for more info.
+    Discrete(DiscreteValue),
 }
 
 impl TrackValue {
     /// Mixes (blends) the current value with an other value using the given weight. Blending is possible only if the types
-    /// are the same.
+    /// are the same. Discrete values are switched to the other value when the weight reaches `0.5`.
     pub fn blend_with(&mut self, other: &Self, weight: f32) {
         match (self, other) {
             (Self::Real(a), Self::Real(b)) => *a = lerpf(*a, *b, weight),
@@ -152,10 +331,52 @@ impl TrackValue {
             (Self::Vector3(a), Self::Vector3(b)) => *a = a.lerp(b, weight),
             (Self::Vector4(a), Self::Vector4(b)) => *a = a.lerp(b, weight),
             (Self::UnitQuaternion(a), Self::UnitQuaternion(b)) => *a = a.nlerp(b, weight),
+            (Self::Discrete(a), Self::Discrete(b)) => {
+                if weight >= 0.5 {
+                    *a = b.clone();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Adds the other value, scaled by the given weight, to the current value. It is used for additive blending, where
+    /// the other value is a difference between some pose and a reference pose (see [`Self::difference`]). Rotations are
+    /// combined by multiplication. The types must be the same, otherwise the method does nothing.
+    pub fn add_with(&mut self, other: &Self, weight: f32) {
+        match (self, other) {
+            (Self::Real(a), Self::Real(b)) => *a += *b * weight,
+            (Self::Vector2(a), Self::Vector2(b)) => *a += b.scale(weight),
+            (Self::Vector3(a), Self::Vector3(b)) => *a += b.scale(weight),
+            (Self::Vector4(a), Self::Vector4(b)) => *a += b.scale(weight),
+            (Self::UnitQuaternion(a), Self::UnitQuaternion(b)) => {
+                // Make sure that the shortest arc is used for interpolation.
+                let delta = if b.w < 0.0 {
+                    UnitQuaternion::new_unchecked(-b.into_inner())
+                } else {
+                    *b
+                };
+                *a *= UnitQuaternion::identity().nlerp(&delta, weight);
+            }
             _ => (),
         }
     }
 
+    /// Calculates a difference between the current value and the reference value, so `reference.add_with(difference, 1.0)`
+    /// will produce the current value. Returns `None` if the types are different.
+    pub fn difference(&self, reference: &Self) -> Option<Self> {
+        match (self, reference) {
+            (Self::Real(a), Self::Real(b)) => Some(Self::Real(*a - *b)),
+            (Self::Vector2(a), Self::Vector2(b)) => Some(Self::Vector2(a - b)),
+            (Self::Vector3(a), Self::Vector3(b)) => Some(Self::Vector3(a - b)),
+            (Self::Vector4(a), Self::Vector4(b)) => Some(Self::Vector4(a - b)),
+            (Self::UnitQuaternion(a), Self::UnitQuaternion(b)) => {
+                Some(Self::UnitQuaternion(b.inverse() * a))
+            }
+            _ => None,
+        }
+    }
+
     /// Tries to perform a numeric type casting of the current value to some other and returns a boxed value, that can
     /// be used to set the value using reflection.
     pub fn numeric_type_cast(&self, value_type: ValueType) -> Option<Box<dyn Reflect>> {
@@ -249,6 +470,7 @@ impl TrackValue {
                 ValueType::Vector4I16 => Some(Box::new(convert_vec4::<i16>(vec4))),
                 ValueType::Vector4U8 => Some(Box::new(convert_vec4::<u8>(vec4))),
                 ValueType::Vector4I8 => Some(Box::new(convert_vec4::<i8>(vec4))),
+                ValueType::Color => Some(Box::new(Color::from(*vec4))),
                 _ => None,
             },
             TrackValue::UnitQuaternion(quat) => match value_type {
@@ -256,6 +478,7 @@ impl TrackValue {
                 ValueType::UnitQuaternionF64 => Some(Box::new(quat.cast::<f64>())),
                 _ => None,
             },
+            TrackValue::Discrete(_) => None,
         }
     }
 }
@@ -276,7 +499,7 @@ pub enum ValueBinding {
     Property {
         /// A path to a property (`foo.bar.baz[1].foobar@EnumVariant.stuff`)
         name: String,
-        /// Actual property type (only numeric properties are supported).
+        /// Actual property type.
         value_type: ValueType,
     },
 }
@@ -308,6 +531,12 @@ impl BoundValue {
         assert_eq!(self.binding, other.binding);
         self.value.blend_with(&other.value, weight);
     }
+
+    /// Adds the other value to the current value using the given weight. See [`TrackValue::add_with`] for more info.
+    pub fn add_with(&mut self, other: &Self, weight: f32) {
+        assert_eq!(self.binding, other.binding);
+        self.value.add_with(&other.value, weight);
+    }
 }
 
 /// A collection of values that are bounds to some properties.
@@ -328,6 +557,16 @@ impl BoundValueCollection {
         }
     }
 
+    /// Tries to add each value of the other collection to a respective (by binding) value in the current collection.
+    /// See [`TrackValue::add_with`] docs for more info.
+    pub fn add_with(&mut self, other: &Self, weight: f32) {
+        for value in self.values.iter_mut() {
+            if let Some(other_value) = other.values.iter().find(|v| v.binding == value.binding) {
+                value.add_with(other_value, weight);
+            }
+        }
+    }
+
     /// Tries to set each value from the collection to the respective property (by binding) of the given scene node.
     pub fn apply(&self, node_ref: &mut Node) {
         for bound_value in self.values.iter() {
@@ -359,7 +598,23 @@ impl BoundValueCollection {
                     name: ref property_name,
                     value_type,
                 } => {
-                    if let Some(casted) = bound_value.value.numeric_type_cast(value_type) {
+                    let casted = match bound_value.value {
+                        TrackValue::Discrete(ref discrete) => {
+                            // Actual type of the property defines the type of the value.
+                            let mut target = None;
+                            node_ref.as_reflect(&mut |node_ref| {
+                                node_ref.resolve_path(property_name, &mut |result| {
+                                    if let Ok(field) = result {
+                                        field.as_any(&mut |any| target = Some(any.type_id()));
+                                    }
+                                })
+                            });
+                            target.and_then(|target| discrete.type_cast(target))
+                        }
+                        _ => bound_value.value.numeric_type_cast(value_type),
+                    };
+
+                    if let Some(casted) = casted {
                         let mut casted = Some(casted);
                         node_ref.as_reflect_mut(&mut |node_ref| {
                             node_ref.set_field_by_path(
@@ -391,3 +646,85 @@ impl BoundValueCollection {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::value::{
+            register_animated_enum, BoundValue, BoundValueCollection, DiscreteValue, TrackValue,
+            ValueBinding, ValueType,
+        },
+        core::{algebra::Vector4, color::Color},
+        resource::texture::{TextureResource, TextureResourceExtension},
+        scene::{
+            base::BaseBuilder,
+            mesh::{Mesh, MeshBuilder, RenderPath},
+            sprite::{Sprite, SpriteBuilder},
+        },
+    };
+
+    fn property(name: &str, value_type: ValueType, value: TrackValue) -> BoundValue {
+        BoundValue {
+            binding: ValueBinding::Property {
+                name: name.to_owned(),
+                value_type,
+            },
+            value,
+        }
+    }
+
+    #[test]
+    fn test_apply_discrete_values() {
+        let texture = TextureResource::new_render_target(1, 1);
+        let mut sprite = SpriteBuilder::new(BaseBuilder::new()).build_node();
+        BoundValueCollection {
+            values: vec![property(
+                "texture",
+                ValueType::Discrete,
+                TrackValue::Discrete(DiscreteValue::Texture(Some(texture.clone()))),
+            )],
+        }
+        .apply(&mut sprite);
+        assert_eq!(sprite.cast::<Sprite>().unwrap().texture(), Some(texture));
+
+        let render_path = BoundValueCollection {
+            values: vec![property(
+                "render_path",
+                ValueType::Discrete,
+                TrackValue::Discrete(DiscreteValue::EnumVariant("Forward".to_owned())),
+            )],
+        };
+        let mut mesh = MeshBuilder::new(BaseBuilder::new()).build_node();
+
+        // Unregistered enumerations are ignored.
+        render_path.apply(&mut mesh);
+        assert_eq!(
+            mesh.cast::<Mesh>().unwrap().render_path(),
+            RenderPath::Deferred
+        );
+
+        register_animated_enum::<RenderPath>();
+        render_path.apply(&mut mesh);
+        assert_eq!(
+            mesh.cast::<Mesh>().unwrap().render_path(),
+            RenderPath::Forward
+        );
+    }
+
+    #[test]
+    fn test_apply_color() {
+        let mut sprite = SpriteBuilder::new(BaseBuilder::new()).build_node();
+        BoundValueCollection {
+            values: vec![property(
+                "color",
+                ValueType::Color,
+                TrackValue::Vector4(Vector4::new(1.0, 0.0, 1.0, 1.0)),
+            )],
+        }
+        .apply(&mut sprite);
+        assert_eq!(
+            sprite.cast::<Sprite>().unwrap().color(),
+            Color::from_rgba(255, 0, 255, 255)
+        );
+    }
+}
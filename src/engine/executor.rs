@@ -5,6 +5,7 @@ use crate::{
     core::{
         instant::Instant,
         log::{Log, MessageKind},
+        profiler,
     },
     engine::{
         Engine, EngineInitParams, GraphicsContext, GraphicsContextParams, SerializationContext,
@@ -22,6 +23,11 @@ use std::{
 };
 use winit::window::WindowAttributes;
 
+#[cfg(feature = "hot_reload")]
+use crate::plugin::dynamic::DynamicPluginError;
+#[cfg(feature = "hot_reload")]
+use std::path::Path;
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
@@ -35,7 +41,6 @@ pub struct Executor {
     engine: Engine,
     desired_update_rate: f32,
     loader: Option<AsyncSceneLoader>,
-    headless: bool,
 }
 
 impl Deref for Executor {
@@ -81,7 +86,6 @@ impl Executor {
             engine,
             desired_update_rate: Self::DEFAULT_UPDATE_RATE,
             loader: None,
-            headless: false,
         }
     }
 
@@ -103,17 +107,18 @@ impl Executor {
 
     /// Defines whether the executor should initialize graphics context or not. Headless mode could
     /// be useful for game servers, where you don't need to have a window, renderer, sound, etc.
-    /// By default, headless mode is off.
+    /// By default, headless mode is off. See [`Engine::set_headless`] for more info.
     pub fn set_headless(&mut self, headless: bool) {
-        self.headless = headless;
+        self.engine.set_headless(headless);
     }
 
     /// Returns `true` if the headless mode is turned on, `false` - otherwise.
     pub fn is_headless(&self) -> bool {
-        self.headless
+        self.engine.is_headless()
     }
 
-    /// Sets the desired update rate in frames per second.
+    /// Sets the desired update rate in frames per second. Physics simulation and fixed update
+    /// callbacks have their own rate, see [`Engine::set_fixed_update_rate`].
     pub fn set_desired_update_rate(&mut self, update_rate: f32) {
         self.desired_update_rate = update_rate.abs();
     }
@@ -131,11 +136,22 @@ impl Executor {
         self.engine.add_plugin_constructor(plugin)
     }
 
+    /// Adds new plugin from a dynamic library to the executor, the plugin will be enabled only on
+    /// [`Executor::run`]. The plugin will be reloaded automatically when the library changes, see
+    /// [`crate::plugin::dynamic::DynamicPlugin`] docs for more info.
+    #[cfg(feature = "hot_reload")]
+    pub fn add_dynamic_plugin<P>(&mut self, path: P) -> Result<(), DynamicPluginError>
+    where
+        P: AsRef<Path>,
+    {
+        self.engine.add_dynamic_plugin(path)
+    }
+
     /// Runs the executor - starts your game. This function is never returns.
     pub fn run(mut self) -> ! {
         let mut engine = self.engine;
         let event_loop = self.event_loop;
-        let headless = self.headless;
+        let headless = engine.is_headless();
 
         let args = Args::parse();
 
@@ -156,6 +172,8 @@ impl Executor {
         let mut lag = 0.0;
 
         event_loop.run(move |event, window_target, control_flow| {
+            engine.input.handle_os_event(&event);
+
             engine.handle_os_event_by_plugins(&event, fixed_time_step, control_flow, &mut lag);
 
             let scenes = engine
@@ -212,6 +230,9 @@ impl Executor {
                         }
                     }
 
+                    #[cfg(feature = "hot_reload")]
+                    engine.reload_dynamic_plugins();
+
                     let elapsed = previous.elapsed();
                     previous = Instant::now();
                     lag += elapsed.as_secs_f32();
@@ -223,6 +244,9 @@ impl Executor {
 
                     if let GraphicsContext::Initialized(ref ctx) = engine.graphics_context {
                         ctx.window.request_redraw();
+                    } else if headless {
+                        // There's no rendering in headless mode, so frames are finished here.
+                        profiler::next_frame();
                     }
                 }
                 Event::RedrawRequested(_) => {
@@ -5,24 +5,41 @@
 
 pub mod error;
 pub mod executor;
+pub mod task;
 
 use crate::material::shader::{ShaderResource, ShaderResourceExtension};
+#[cfg(feature = "hot_reload")]
+use crate::{
+    asset::ResourceStateRefMut,
+    core::{uuid::Uuid, visitor::prelude::*},
+    plugin::dynamic::{DynamicPlugin, DynamicPluginError},
+    scene::{base::visit_opt_script, graph::Graph},
+};
 use crate::{
     asset::{manager::ResourceManager, manager::ResourceWaitContext},
-    core::{algebra::Vector2, futures::executor::block_on, instant, log::Log, pool::Handle},
-    engine::error::EngineError,
+    core::{
+        algebra::Vector2, futures::executor::block_on, instant, log::Log, pool::Handle, profiler,
+        task::AsyncTaskResult,
+    },
+    engine::{
+        error::EngineError,
+        task::{NodeTaskHandler, TaskPoolHandler},
+    },
     event::Event,
     event_loop::ControlFlow,
     gui::UserInterface,
+    input::InputSystem,
     material::shader::{loader::ShaderLoader, Shader},
     plugin::{Plugin, PluginConstructor, PluginContext, PluginRegistrationContext},
     renderer::{framework::error::FrameworkError, Renderer},
     resource::{
+        bone_mapping::{loader::BoneMappingLoader, BoneMapping},
         curve::{loader::CurveLoader, CurveResourceState},
         model::{loader::ModelLoader, Model, ModelResource},
         texture::{loader::TextureLoader, Texture, TextureKind},
     },
     scene::{
+        animation::{process_animation_signals, sequencer::process_sequencer_events},
         base::NodeScriptMessage,
         graph::GraphUpdateSwitches,
         node::{constructor::NodeConstructorContainer, Node},
@@ -54,6 +71,8 @@ use glutin::{
 use glutin_winit::{DisplayBuilder, GlWindow};
 #[cfg(not(target_arch = "wasm32"))]
 use raw_window_handle::HasRawWindowHandle;
+#[cfg(feature = "hot_reload")]
+use std::path::Path;
 use std::{
     any::TypeId,
     collections::{HashSet, VecDeque},
@@ -91,9 +110,14 @@ impl Default for SerializationContext {
 impl SerializationContext {
     /// Creates default serialization context.
     pub fn new() -> Self {
+        let script_constructors = ScriptConstructorContainer::new();
+
+        #[cfg(feature = "rhai_scripting")]
+        script_constructors.add::<crate::script::rhai::RhaiScript>("Rhai Script");
+
         Self {
             node_constructors: NodeConstructorContainer::new(),
-            script_constructors: ScriptConstructorContainer::new(),
+            script_constructors,
         }
     }
 }
@@ -213,16 +237,37 @@ pub struct Engine {
     // A set of plugins used by the engine.
     plugins: Vec<Box<dyn Plugin>>,
 
+    // A set of plugins loaded from dynamic libraries. Must be declared after plugin instances,
+    // so the libraries will be unloaded after the instances are destroyed.
+    #[cfg(feature = "hot_reload")]
+    dynamic_plugins: Vec<DynamicPlugin>,
+
     plugins_enabled: bool,
 
     // Amount of time (in seconds) that passed from creation of the engine.
     elapsed_time: f32,
 
+    // Rate (in ticks per second) of fixed update and physics simulation.
+    fixed_update_rate: f32,
+
+    // Amount of time (in seconds) that wasn't yet consumed by fixed update steps.
+    fixed_time_accumulator: f32,
+
+    physics_interpolation: bool,
+
+    headless: bool,
+
     /// A special container that is able to create nodes by their type UUID. Use a copy of this
     /// value whenever you need it as a parameter in other parts of the engine.
     pub serialization_context: Arc<SerializationContext>,
 
     script_processor: ScriptProcessor,
+
+    task_pool: TaskPoolHandler,
+
+    /// Input system maps physical inputs to named actions and axes. See [`InputSystem`] docs for
+    /// more info.
+    pub input: InputSystem,
 }
 
 /// Performs dispatch of script messages.
@@ -410,11 +455,130 @@ impl ScriptProcessor {
             .push(resource_manager.state().get_wait_context());
     }
 
+    // Discards pending script messages, that are sent to the given nodes or which payload is defined
+    // in the given dynamic plugin, and removes message subscriptions of the given nodes. Such messages
+    // could hold objects, which code is located in a dynamic library that is about to be unloaded.
+    // Every other message is kept in its original order.
+    #[cfg(feature = "hot_reload")]
+    fn discard_messages(
+        &mut self,
+        nodes: &[(Handle<Scene>, Handle<Node>)],
+        dynamic_plugin: &DynamicPlugin,
+    ) {
+        for scripted_scene in self.scripted_scenes.iter_mut() {
+            let scene_handle = scripted_scene.handle;
+            let dispatcher = &mut scripted_scene.message_dispatcher;
+
+            let messages = dispatcher.message_receiver.try_iter().collect::<Vec<_>>();
+            for message in messages {
+                let is_sent_to_reloaded = match message.kind {
+                    ScriptMessageKind::Targeted(target) => nodes.contains(&(scene_handle, target)),
+                    ScriptMessageKind::Hierarchical { .. } | ScriptMessageKind::Global => false,
+                };
+
+                if !is_sent_to_reloaded
+                    && !dynamic_plugin.contains_code(message.payload.code_address())
+                {
+                    scripted_scene.message_sender.send(message);
+                }
+            }
+
+            for (scene, node) in nodes {
+                if *scene == scene_handle {
+                    dispatcher.unsubscribe(*node);
+                }
+            }
+        }
+    }
+
+    fn handle_task_result(
+        &mut self,
+        handler: NodeTaskHandler,
+        payload: Box<dyn AsyncTaskResult>,
+        scenes: &mut SceneContainer,
+        plugins: &mut [Box<dyn Plugin>],
+        resource_manager: &ResourceManager,
+        task_pool: &mut TaskPoolHandler,
+        input: &InputSystem,
+        dt: f32,
+        elapsed_time: f32,
+    ) {
+        if let Some(scripted_scene) = self
+            .scripted_scenes
+            .iter_mut()
+            .find(|s| s.handle == handler.scene_handle)
+        {
+            if let Some(scene) = scenes.try_get_mut(handler.scene_handle) {
+                let mut context = ScriptContext {
+                    dt,
+                    elapsed_time,
+                    plugins,
+                    handle: handler.node_handle,
+                    scene,
+                    scene_handle: handler.scene_handle,
+                    resource_manager,
+                    task_pool,
+                    input,
+                    message_sender: &scripted_scene.message_sender,
+                    message_dispatcher: &mut scripted_scene.message_dispatcher,
+                };
+
+                let mut callback = Some((handler.closure, payload));
+                process_node(&mut context, &mut |script, context| {
+                    if let Some((closure, payload)) = callback.take() {
+                        closure(payload, script, context);
+                    }
+                });
+            }
+        }
+    }
+
+    fn handle_fixed_update(
+        &mut self,
+        scenes: &mut SceneContainer,
+        plugins: &mut [Box<dyn Plugin>],
+        resource_manager: &ResourceManager,
+        task_pool: &mut TaskPoolHandler,
+        input: &InputSystem,
+        dt: f32,
+        elapsed_time: f32,
+    ) {
+        if !self.wait_list.is_empty() {
+            return;
+        }
+
+        for scripted_scene in self.scripted_scenes.iter_mut() {
+            if let Some(scene) = scenes.try_get_mut(scripted_scene.handle) {
+                if scene.enabled {
+                    process_scripts(
+                        scene,
+                        scripted_scene.handle,
+                        plugins,
+                        resource_manager,
+                        task_pool,
+                        input,
+                        &scripted_scene.message_sender,
+                        &mut scripted_scene.message_dispatcher,
+                        dt,
+                        elapsed_time,
+                        |script, context| {
+                            if script.initialized && script.started {
+                                script.on_fixed_update(context);
+                            }
+                        },
+                    );
+                }
+            }
+        }
+    }
+
     fn handle_scripts(
         &mut self,
         scenes: &mut SceneContainer,
         plugins: &mut Vec<Box<dyn Plugin>>,
         resource_manager: &ResourceManager,
+        task_pool: &mut TaskPoolHandler,
+        input: &InputSystem,
         dt: f32,
         elapsed_time: f32,
     ) {
@@ -436,6 +600,11 @@ impl ScriptProcessor {
                 continue 'scene_loop;
             }
 
+            // Perform built-in actions of animation signals and send signal messages to scripts before
+            // the update, so the scripts could handle them on this frame.
+            process_animation_signals(scene, &scripted_scene.message_sender);
+            process_sequencer_events(scene, &scripted_scene.message_sender);
+
             // Fill in initial handles to nodes to update.
             let mut update_queue = VecDeque::new();
             for (handle, node) in scene.graph.pair_iter() {
@@ -459,7 +628,10 @@ impl ScriptProcessor {
                     plugins,
                     handle: Default::default(),
                     scene,
+                    scene_handle: scripted_scene.handle,
                     resource_manager,
+                    task_pool,
+                    input,
                     message_sender: &scripted_scene.message_sender,
                     message_dispatcher: &mut scripted_scene.message_dispatcher,
                 };
@@ -756,8 +928,11 @@ define_process_node!(process_node_message, ScriptMessageContext);
 
 pub(crate) fn process_scripts<T>(
     scene: &mut Scene,
+    scene_handle: Handle<Scene>,
     plugins: &mut [Box<dyn Plugin>],
     resource_manager: &ResourceManager,
+    task_pool: &mut TaskPoolHandler,
+    input: &InputSystem,
     message_sender: &ScriptMessageSender,
     message_dispatcher: &mut ScriptMessageDispatcher,
     dt: f32,
@@ -772,7 +947,10 @@ pub(crate) fn process_scripts<T>(
         plugins,
         handle: Default::default(),
         scene,
+        scene_handle,
         resource_manager,
+        task_pool,
+        input,
         message_sender,
         message_dispatcher,
     };
@@ -807,6 +985,11 @@ pub(crate) fn initialize_resource_manager_loaders(
     state.constructors_container.add::<Model>();
     state.constructors_container.add::<CurveResourceState>();
     state.constructors_container.add::<SoundBuffer>();
+    state.constructors_container.add::<BoneMapping>();
+    #[cfg(feature = "rhai_scripting")]
+    state
+        .constructors_container
+        .add::<crate::script::rhai::resource::RhaiScriptSource>();
 
     let loaders = &mut state.loaders;
     loaders.set(model_loader);
@@ -818,9 +1001,19 @@ pub(crate) fn initialize_resource_manager_loaders(
     });
     loaders.set(ShaderLoader);
     loaders.set(CurveLoader);
+    loaders.set(BoneMappingLoader);
+    #[cfg(feature = "rhai_scripting")]
+    loaders.set(crate::script::rhai::resource::RhaiScriptLoader);
 }
 
 impl Engine {
+    /// Default rate (in ticks per second) of fixed update and physics simulation.
+    pub const DEFAULT_FIXED_UPDATE_RATE: f32 = 60.0;
+
+    /// Maximum amount of fixed update steps per single update. If the simulation can't keep up with
+    /// the real time, the remaining time is dropped.
+    pub const MAX_FIXED_STEPS_PER_UPDATE: usize = 8;
+
     /// Creates new instance of engine from given initialization parameters. Automatically creates all sub-systems
     /// (sound, ui, resource manager, etc.) **except** graphics context. Graphics context should be created manually
     /// only on [`Event::Resumed`] by calling [`Engine::initialize_graphics_context`] and destroyed on [`Event::Suspended`]
@@ -881,11 +1074,19 @@ impl Engine {
             user_interface: UserInterface::new(Vector2::new(100.0, 100.0)),
             performance_statistics: Default::default(),
             plugins: Default::default(),
+            #[cfg(feature = "hot_reload")]
+            dynamic_plugins: Default::default(),
             serialization_context,
             script_processor: Default::default(),
+            task_pool: Default::default(),
+            input: InputSystem::new(),
             plugins_enabled: false,
             plugin_constructors: Default::default(),
             elapsed_time: 0.0,
+            fixed_update_rate: Self::DEFAULT_FIXED_UPDATE_RATE,
+            fixed_time_accumulator: 0.0,
+            physics_interpolation: true,
+            headless: false,
         })
     }
 
@@ -1176,14 +1377,17 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
-        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
-            let inner_size = ctx.window.inner_size();
-            let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
+        self.input.update();
 
+        if let Some(window_size) = self.window_size() {
             self.resource_manager.state().update(dt);
-            ctx.renderer.update_caches(dt);
+            if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+                ctx.renderer.update_caches(dt);
+            }
             self.handle_model_events();
 
+            self.fixed_update(dt, control_flow, lag, &switches);
+
             for (handle, scene) in self.scenes.pair_iter_mut().filter(|(_, s)| s.enabled) {
                 let frame_size = scene.render_target.as_ref().map_or(window_size, |rt| {
                     if let TextureKind::Rectangle { width, height } = rt.data_ref().kind() {
@@ -1193,13 +1397,17 @@ impl Engine {
                     }
                 });
 
-                scene.update(
-                    frame_size,
-                    dt,
-                    switches.get(&handle).cloned().unwrap_or_default(),
-                );
+                // Physics is simulated with fixed rate in `fixed_update`.
+                let switches = GraphUpdateSwitches {
+                    physics: false,
+                    physics2d: false,
+                    ..switches.get(&handle).cloned().unwrap_or_default()
+                };
+
+                scene.update(frame_size, dt, switches);
             }
 
+            self.handle_async_tasks(dt, lag);
             self.update_plugins(dt, control_flow, lag);
             self.handle_scripts(dt);
         }
@@ -1210,14 +1418,18 @@ impl Engine {
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
     pub fn post_update(&mut self, dt: f32) {
-        if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
-            let inner_size = ctx.window.inner_size();
-            let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
-
+        if let Some(window_size) = self.window_size() {
             let time = instant::Instant::now();
             self.user_interface.update(window_size, dt);
             self.performance_statistics.ui_time = instant::Instant::now() - time;
             self.elapsed_time += dt;
+
+            if self.physics_interpolation {
+                let alpha = self.fixed_update_interpolation_factor();
+                for scene in self.scenes.iter_mut().filter(|s| s.enabled) {
+                    scene.graph.interpolate_physics(alpha);
+                }
+            }
         }
     }
 
@@ -1241,12 +1453,178 @@ impl Engine {
             &mut self.scenes,
             &mut self.plugins,
             &self.resource_manager,
+            &mut self.task_pool,
+            &self.input,
             dt,
             self.elapsed_time,
         );
         self.performance_statistics.scripts_time = instant::Instant::now() - time;
     }
 
+    /// Sets the rate (in ticks per second) of fixed update and physics simulation. Physics is always
+    /// simulated with this rate, regardless of the frame rate, and [`Plugin::on_fixed_update`] with
+    /// [`crate::script::ScriptTrait::on_fixed_update`] are called right before each physics step.
+    /// Default rate is [`Self::DEFAULT_FIXED_UPDATE_RATE`].
+    pub fn set_fixed_update_rate(&mut self, rate: f32) {
+        self.fixed_update_rate = rate.max(1.0);
+    }
+
+    /// Returns the rate (in ticks per second) of fixed update and physics simulation.
+    pub fn fixed_update_rate(&self) -> f32 {
+        self.fixed_update_rate
+    }
+
+    /// Enables or disables interpolation of rendered transforms of rigid bodies between the last two
+    /// physics steps. It removes jittering of physical objects when the frame rate does not match
+    /// the fixed update rate. Interpolation is enabled by default.
+    pub fn set_physics_interpolation(&mut self, enabled: bool) {
+        self.physics_interpolation = enabled;
+    }
+
+    /// Returns `true` if interpolation of rendered transforms of rigid bodies is enabled.
+    pub fn is_physics_interpolation_enabled(&self) -> bool {
+        self.physics_interpolation
+    }
+
+    /// Defines whether the engine should be updated without graphics context or not. By default, the
+    /// engine is updated only when its graphics context is initialized, headless mode removes this
+    /// requirement, which is useful for game servers and tests. In headless mode, the size of the frame is
+    /// 1x1 pixels.
+    pub fn set_headless(&mut self, headless: bool) {
+        self.headless = headless;
+    }
+
+    /// Returns `true` if the engine is updated without graphics context.
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Returns a reference to the task pool handler. Its [`TaskPoolHandler::inner`] task pool could
+    /// be used to run heavy computations outside of plugins and scripts, for example in the editor.
+    pub fn task_pool(&self) -> &TaskPoolHandler {
+        &self.task_pool
+    }
+
+    // Size of the main window, or a unit size in headless mode. `None` means that the engine cannot be
+    // updated, because there's no graphics context yet.
+    fn window_size(&self) -> Option<Vector2<f32>> {
+        match self.graphics_context {
+            GraphicsContext::Initialized(ref ctx) => {
+                let inner_size = ctx.window.inner_size();
+                Some(Vector2::new(
+                    inner_size.width as f32,
+                    inner_size.height as f32,
+                ))
+            }
+            GraphicsContext::Uninitialized(_) if self.headless => Some(Vector2::new(1.0, 1.0)),
+            GraphicsContext::Uninitialized(_) => None,
+        }
+    }
+
+    /// Returns the interpolation factor in `[0; 1]` range between the last two fixed update steps.
+    /// It could be used to interpolate custom data, that is changed in fixed update callbacks.
+    pub fn fixed_update_interpolation_factor(&self) -> f32 {
+        (self.fixed_time_accumulator * self.fixed_update_rate).min(1.0)
+    }
+
+    fn fixed_update(
+        &mut self,
+        dt: f32,
+        control_flow: &mut ControlFlow,
+        lag: &mut f32,
+        switches: &FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
+    ) {
+        let fixed_dt = 1.0 / self.fixed_update_rate;
+
+        self.fixed_time_accumulator += dt;
+
+        let mut steps = 0;
+        while self.fixed_time_accumulator >= fixed_dt {
+            if steps >= Self::MAX_FIXED_STEPS_PER_UPDATE {
+                // The simulation can't keep up, drop the remaining time to prevent "spiral of death".
+                self.fixed_time_accumulator %= fixed_dt;
+                break;
+            }
+
+            if self.plugins_enabled {
+                let mut context = PluginContext {
+                    scenes: &mut self.scenes,
+                    resource_manager: &self.resource_manager,
+                    graphics_context: &mut self.graphics_context,
+                    dt: fixed_dt,
+                    lag,
+                    user_interface: &mut self.user_interface,
+                    serialization_context: &self.serialization_context,
+                    performance_statistics: &self.performance_statistics,
+                    task_pool: &mut self.task_pool,
+                    input: &mut self.input,
+                };
+
+                for plugin in self.plugins.iter_mut() {
+                    plugin.on_fixed_update(&mut context, control_flow);
+                }
+            }
+
+            self.script_processor.handle_fixed_update(
+                &mut self.scenes,
+                &mut self.plugins,
+                &self.resource_manager,
+                &mut self.task_pool,
+                &self.input,
+                fixed_dt,
+                self.elapsed_time,
+            );
+
+            for (handle, scene) in self.scenes.pair_iter_mut().filter(|(_, s)| s.enabled) {
+                scene.graph.fixed_physics_step(
+                    fixed_dt,
+                    &switches.get(&handle).cloned().unwrap_or_default(),
+                );
+            }
+
+            self.fixed_time_accumulator -= fixed_dt;
+            steps += 1;
+        }
+    }
+
+    fn handle_async_tasks(&mut self, dt: f32, lag: &mut f32) {
+        self.task_pool.update_local_tasks();
+
+        while let Some(result) = self.task_pool.next_task_result() {
+            if let Some(handler) = self.task_pool.pop_plugin_task_handler(result.id) {
+                // Results of the tasks of disabled plugins are discarded.
+                if self.plugins_enabled {
+                    let mut context = PluginContext {
+                        scenes: &mut self.scenes,
+                        resource_manager: &self.resource_manager,
+                        graphics_context: &mut self.graphics_context,
+                        dt,
+                        lag,
+                        user_interface: &mut self.user_interface,
+                        serialization_context: &self.serialization_context,
+                        performance_statistics: &self.performance_statistics,
+                        task_pool: &mut self.task_pool,
+                        input: &mut self.input,
+                    };
+
+                    handler(result.payload, &mut self.plugins, &mut context);
+                }
+            } else if let Some(handler) = self.task_pool.pop_node_task_handler(result.id) {
+                self.script_processor.handle_task_result(
+                    handler,
+                    result.payload,
+                    &mut self.scenes,
+                    &mut self.plugins,
+                    &self.resource_manager,
+                    &mut self.task_pool,
+                    &self.input,
+                    dt,
+                    self.elapsed_time,
+                );
+            }
+        }
+    }
+
     fn update_plugins(&mut self, dt: f32, control_flow: &mut ControlFlow, lag: &mut f32) {
         let time = instant::Instant::now();
 
@@ -1260,6 +1638,8 @@ impl Engine {
                 user_interface: &mut self.user_interface,
                 serialization_context: &self.serialization_context,
                 performance_statistics: &self.performance_statistics,
+                task_pool: &mut self.task_pool,
+                input: &mut self.input,
             };
 
             for plugin in self.plugins.iter_mut() {
@@ -1276,6 +1656,8 @@ impl Engine {
                     user_interface: &mut self.user_interface,
                     serialization_context: &self.serialization_context,
                     performance_statistics: &self.performance_statistics,
+                    task_pool: &mut self.task_pool,
+                    input: &mut self.input,
                 };
 
                 for plugin in self.plugins.iter_mut() {
@@ -1307,6 +1689,8 @@ impl Engine {
                         user_interface: &mut self.user_interface,
                         serialization_context: &self.serialization_context,
                         performance_statistics: &self.performance_statistics,
+                        task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     },
                     control_flow,
                 );
@@ -1332,6 +1716,8 @@ impl Engine {
                         user_interface: &mut self.user_interface,
                         serialization_context: &self.serialization_context,
                         performance_statistics: &self.performance_statistics,
+                        task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     },
                     control_flow,
                 );
@@ -1357,6 +1743,8 @@ impl Engine {
                         user_interface: &mut self.user_interface,
                         serialization_context: &self.serialization_context,
                         performance_statistics: &self.performance_statistics,
+                        task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     },
                     control_flow,
                 );
@@ -1382,6 +1770,8 @@ impl Engine {
                         user_interface: &mut self.user_interface,
                         serialization_context: &self.serialization_context,
                         performance_statistics: &self.performance_statistics,
+                        task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     },
                     control_flow,
                 );
@@ -1408,12 +1798,16 @@ impl Engine {
             .iter_mut()
             .find(|s| s.handle == scene)
         {
-            let scene = &mut self.scenes[scene];
+            let scene_handle = scene;
+            let scene = &mut self.scenes[scene_handle];
             if scene.enabled {
                 process_scripts(
                     scene,
+                    scene_handle,
                     &mut self.plugins,
                     &self.resource_manager,
+                    &mut self.task_pool,
+                    &self.input,
                     &scripted_scene.message_sender,
                     &mut scripted_scene.message_dispatcher,
                     dt,
@@ -1482,6 +1876,9 @@ impl Engine {
             }
         }
 
+        // Every scope captured since the previous frame (including all the updates) belongs to this frame.
+        profiler::next_frame();
+
         Ok(())
     }
 
@@ -1504,9 +1901,34 @@ impl Engine {
                             user_interface: &mut self.user_interface,
                             serialization_context: &self.serialization_context,
                             performance_statistics: &self.performance_statistics,
+                            task_pool: &mut self.task_pool,
+                            input: &mut self.input,
                         },
                     ));
                 }
+
+                #[cfg(feature = "hot_reload")]
+                for dynamic_plugin in self.dynamic_plugins.iter_mut() {
+                    if let Some(constructor) = dynamic_plugin.constructor() {
+                        let instance = constructor.create_instance(
+                            override_scene,
+                            PluginContext {
+                                scenes: &mut self.scenes,
+                                resource_manager: &self.resource_manager,
+                                graphics_context: &mut self.graphics_context,
+                                dt: 0.0,
+                                lag: &mut 0.0,
+                                user_interface: &mut self.user_interface,
+                                serialization_context: &self.serialization_context,
+                                performance_statistics: &self.performance_statistics,
+                                task_pool: &mut self.task_pool,
+                                input: &mut self.input,
+                            },
+                        );
+                        dynamic_plugin.instance_index = Some(self.plugins.len());
+                        self.plugins.push(instance);
+                    }
+                }
             } else {
                 self.handle_scripts(0.0);
 
@@ -1521,8 +1943,15 @@ impl Engine {
                         user_interface: &mut self.user_interface,
                         serialization_context: &self.serialization_context,
                         performance_statistics: &self.performance_statistics,
+                        task_pool: &mut self.task_pool,
+                        input: &mut self.input,
                     });
                 }
+
+                #[cfg(feature = "hot_reload")]
+                for dynamic_plugin in self.dynamic_plugins.iter_mut() {
+                    dynamic_plugin.instance_index = None;
+                }
             }
         }
     }
@@ -1538,6 +1967,335 @@ impl Engine {
 
         self.plugin_constructors.push(Box::new(constructor));
     }
+
+    /// Loads a plugin from a dynamic library at the given path. The plugin will be enabled together
+    /// with statically linked plugins and it will be reloaded with state preservation when the
+    /// library changes (see [`Self::reload_dynamic_plugins`]). See [`DynamicPlugin`] docs for more
+    /// info.
+    #[cfg(feature = "hot_reload")]
+    pub fn add_dynamic_plugin<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<(), DynamicPluginError> {
+        let dynamic_plugin = DynamicPlugin::load(path, &self.serialization_context)?;
+        self.dynamic_plugins.push(dynamic_plugin);
+        Ok(())
+    }
+
+    /// Returns a list of plugins loaded from dynamic libraries.
+    #[cfg(feature = "hot_reload")]
+    pub fn dynamic_plugins(&self) -> &[DynamicPlugin] {
+        &self.dynamic_plugins
+    }
+
+    /// Checks whether the libraries of dynamic plugins were changed and reloads changed ones. The
+    /// state of plugin instances and script instances is serialized before reloading and restored
+    /// after. Returns `true` if at least one plugin was reloaded. The method should be called once
+    /// per frame, [`crate::engine::executor::Executor`] does this automatically.
+    #[cfg(feature = "hot_reload")]
+    pub fn reload_dynamic_plugins(&mut self) -> bool {
+        let mut reloaded = false;
+
+        for index in 0..self.dynamic_plugins.len() {
+            if self.dynamic_plugins[index].is_changed() {
+                // Futures and task callbacks of the plugin have their code in the old library, so
+                // they must be finished first. Models that are being loaded could create script
+                // instances of the old library as well.
+                let dynamic_plugin = &self.dynamic_plugins[index];
+                let is_busy = self
+                    .task_pool
+                    .has_tasks_spawned_by(|address| dynamic_plugin.contains_code(address))
+                    || self.resource_manager.state().count_pending_resources() > 0;
+
+                if is_busy {
+                    if !dynamic_plugin.reload_postponed {
+                        Log::warn(format!(
+                            "Reloading of dynamic plugin {} is postponed until its async tasks are \
+                            finished and every resource is loaded.",
+                            dynamic_plugin.source_path().display()
+                        ));
+                        self.dynamic_plugins[index].reload_postponed = true;
+                    }
+                    continue;
+                }
+
+                self.dynamic_plugins[index].reload_postponed = false;
+
+                match self.reload_dynamic_plugin(index) {
+                    Ok(_) => reloaded = true,
+                    Err(e) => Log::err(format!(
+                        "Unable to reload dynamic plugin {}. Reason: {}",
+                        self.dynamic_plugins[index].source_path().display(),
+                        e
+                    )),
+                }
+            }
+        }
+
+        reloaded
+    }
+
+    // Returns every model in the resource manager, models hold script instances that are used as
+    // prototypes for instantiated nodes.
+    #[cfg(feature = "hot_reload")]
+    fn registered_models(&self) -> Vec<ModelResource> {
+        self.resource_manager
+            .state()
+            .resources()
+            .iter()
+            .filter_map(|resource| resource.try_cast::<Model>())
+            .collect()
+    }
+
+    #[cfg(feature = "hot_reload")]
+    fn save_dynamic_plugin_state(
+        &mut self,
+        instance_index: Option<usize>,
+        scripts: &[Uuid],
+        visitor: &mut Visitor,
+        reloaded_scripts: &mut Vec<(Handle<Scene>, Handle<Node>)>,
+        reloaded_model_scripts: &mut Vec<(ModelResource, Handle<Node>)>,
+    ) -> VisitResult {
+        if let Some(instance_index) = instance_index {
+            let mut region = visitor.enter_region("Plugin")?;
+            self.plugins[instance_index].visit_state(&mut region)?;
+        }
+
+        for (scene_handle, scene) in self.scenes.pair_iter_mut() {
+            for node_handle in save_graph_scripts(
+                &mut scene.graph,
+                scripts,
+                "Script",
+                reloaded_scripts.len(),
+                visitor,
+            )? {
+                reloaded_scripts.push((scene_handle, node_handle));
+            }
+        }
+
+        for model in self.registered_models() {
+            let mut state = model.state();
+            if let ResourceStateRefMut::Ok(data) = state.get_mut() {
+                for node_handle in save_graph_scripts(
+                    &mut data.get_scene_mut().graph,
+                    scripts,
+                    "ModelScript",
+                    reloaded_model_scripts.len(),
+                    visitor,
+                )? {
+                    reloaded_model_scripts.push((model.clone(), node_handle));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "hot_reload")]
+    fn reload_dynamic_plugin(&mut self, index: usize) -> Result<(), String> {
+        // Open the new library first, if it fails the old one will continue running.
+        let loaded = self.dynamic_plugins[index]
+            .open_library()
+            .map_err(|e| e.to_string())?;
+
+        // Process pending script messages, some of them can hold script instances.
+        self.handle_scripts(0.0);
+
+        let instance_index = self.dynamic_plugins[index].instance_index;
+        let scripts = self.dynamic_plugins[index].scripts().to_vec();
+
+        // Save the state first, nothing is changed at this stage so an error will just cancel the
+        // reload.
+        let mut visitor = Visitor::new();
+        let mut reloaded_scripts = Vec::new();
+        let mut reloaded_model_scripts = Vec::new();
+        let save_result = self.save_dynamic_plugin_state(
+            instance_index,
+            &scripts,
+            &mut visitor,
+            &mut reloaded_scripts,
+            &mut reloaded_model_scripts,
+        );
+
+        let data = match save_result.and_then(|_| visitor.save_binary_to_vec()) {
+            Ok(data) => data,
+            Err(e) => {
+                loaded.discard();
+                return Err(format!("Unable to save the state: {:?}", e));
+            }
+        };
+
+        // Parse the saved state back before destroying anything, so a malformed blob cancels the
+        // reload and keeps the old instances alive.
+        let mut visitor = match Visitor::load_from_memory(data) {
+            Ok(visitor) => visitor,
+            Err(e) => {
+                loaded.discard();
+                return Err(format!("Unable to read the saved state: {:?}", e));
+            }
+        };
+
+        // Destroy every object which code is located in the old library. Scripts are not
+        // de-initialized, because their state will be restored.
+        for (scene_handle, node_handle) in reloaded_scripts.iter() {
+            self.scenes[*scene_handle].graph[*node_handle].script = None;
+        }
+        for (model, node_handle) in reloaded_model_scripts.iter() {
+            if let ResourceStateRefMut::Ok(data) = model.state().get_mut() {
+                data.get_scene_mut().graph[*node_handle].script = None;
+            }
+        }
+        self.script_processor
+            .discard_messages(&reloaded_scripts, &self.dynamic_plugins[index]);
+        if let Some(instance_index) = instance_index {
+            self.plugins.remove(instance_index);
+        }
+
+        let dynamic_plugin = &mut self.dynamic_plugins[index];
+        dynamic_plugin.unload(&self.serialization_context);
+        dynamic_plugin.install(loaded, &self.serialization_context);
+
+        // Restore the state.
+        visitor
+            .blackboard
+            .register(self.serialization_context.clone());
+
+        if let Some(instance_index) = instance_index {
+            let constructor = dynamic_plugin
+                .constructor()
+                .expect("Constructor must exist after installation!");
+
+            let mut instance = constructor.create_blank_instance().unwrap_or_else(|| {
+                constructor.create_instance(
+                    Default::default(),
+                    PluginContext {
+                        scenes: &mut self.scenes,
+                        resource_manager: &self.resource_manager,
+                        graphics_context: &mut self.graphics_context,
+                        dt: 0.0,
+                        lag: &mut 0.0,
+                        user_interface: &mut self.user_interface,
+                        serialization_context: &self.serialization_context,
+                        performance_statistics: &self.performance_statistics,
+                        task_pool: &mut self.task_pool,
+                        input: &mut self.input,
+                    },
+                )
+            });
+
+            match visitor.enter_region("Plugin") {
+                Ok(mut region) => Log::verify(instance.visit_state(&mut region)),
+                Err(e) => Log::err(format!("Unable to restore plugin state: {:?}", e)),
+            }
+
+            self.plugins.insert(instance_index, instance);
+        }
+
+        for (n, (scene_handle, node_handle)) in reloaded_scripts.into_iter().enumerate() {
+            let mut script = None;
+            match visit_opt_script(&format!("Script{}", n), &mut script, &mut visitor) {
+                Ok(_) => {
+                    if let Some(script) = script.as_mut() {
+                        // Force the engine to call `on_start` again, so the script can subscribe to
+                        // messages. `on_init` won't be called, since the script was initialized.
+                        script.started = false;
+                    }
+
+                    self.scenes[scene_handle].graph[node_handle].set_script(script);
+                }
+                Err(e) => Log::err(format!(
+                    "Unable to restore script state of node {}: {:?}",
+                    node_handle, e
+                )),
+            }
+        }
+
+        for (n, (model, node_handle)) in reloaded_model_scripts.into_iter().enumerate() {
+            let mut script = None;
+            match visit_opt_script(&format!("ModelScript{}", n), &mut script, &mut visitor) {
+                Ok(_) => {
+                    if let ResourceStateRefMut::Ok(data) = model.state().get_mut() {
+                        data.get_scene_mut().graph[node_handle].script = script;
+                    }
+                }
+                Err(e) => Log::err(format!(
+                    "Unable to restore script state of node {} of model {}: {:?}",
+                    node_handle,
+                    model.path().display(),
+                    e
+                )),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Destroys every object of the given dynamic plugin, that could outlive the engine, and unloads
+    // the plugin. The library is kept loaded if the plugin still has unfinished async tasks, since
+    // their code is located in the library.
+    #[cfg(feature = "hot_reload")]
+    fn unload_dynamic_plugin(&mut self, index: usize) {
+        let dynamic_plugin = &self.dynamic_plugins[index];
+
+        for model in self.registered_models() {
+            if let ResourceStateRefMut::Ok(data) = model.state().get_mut() {
+                for node in data.get_scene_mut().graph.linear_iter_mut() {
+                    if node
+                        .script
+                        .as_ref()
+                        .map_or(false, |s| dynamic_plugin.scripts().contains(&s.id()))
+                    {
+                        node.script = None;
+                    }
+                }
+            }
+        }
+
+        let has_tasks = self
+            .task_pool
+            .has_tasks_spawned_by(|address| dynamic_plugin.contains_code(address));
+
+        let dynamic_plugin = &mut self.dynamic_plugins[index];
+        if has_tasks {
+            Log::warn(format!(
+                "Dynamic plugin {} has unfinished async tasks, its library will stay loaded.",
+                dynamic_plugin.source_path().display()
+            ));
+            dynamic_plugin.leak(&self.serialization_context);
+        } else {
+            dynamic_plugin.unload(&self.serialization_context);
+        }
+    }
+}
+
+// Visits every script of the graph, which type is one of the given, and returns handles of the nodes
+// of the visited scripts. Scripts are written as `{prefix}{n}`, where `n` starts from `first_index`.
+#[cfg(feature = "hot_reload")]
+fn save_graph_scripts(
+    graph: &mut Graph,
+    scripts: &[Uuid],
+    prefix: &str,
+    first_index: usize,
+    visitor: &mut Visitor,
+) -> Result<Vec<Handle<Node>>, VisitError> {
+    let mut handles = Vec::new();
+
+    for (node_handle, node) in graph.pair_iter_mut() {
+        if node
+            .script
+            .as_ref()
+            .map_or(false, |s| scripts.contains(&s.id()))
+        {
+            visit_opt_script(
+                &format!("{}{}", prefix, first_index + handles.len()),
+                &mut node.script,
+                visitor,
+            )?;
+            handles.push(node_handle);
+        }
+    }
+
+    Ok(handles)
 }
 
 impl Drop for Engine {
@@ -1557,6 +2315,16 @@ impl Drop for Engine {
 
         // Finally disable plugins.
         self.enable_plugins(Default::default(), false);
+
+        // Pending script messages could hold objects of dynamic plugins.
+        self.script_processor.scripted_scenes.clear();
+
+        // Unregister scripts of dynamic plugins, their code will be unloaded together with the
+        // libraries.
+        #[cfg(feature = "hot_reload")]
+        for index in 0..self.dynamic_plugins.len() {
+            self.unload_dynamic_plugin(index);
+        }
     }
 }
 
@@ -1564,9 +2332,17 @@ impl Drop for Engine {
 mod test {
     use crate::{
         asset::manager::ResourceManager,
-        core::{pool::Handle, reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
-        engine::ScriptProcessor,
+        core::{
+            algebra::Vector2, pool::Handle, reflect::prelude::*, uuid::Uuid, visitor::prelude::*,
+        },
+        engine::{
+            task::TaskPoolHandler, GraphicsContext, PerformanceStatistics, ScriptProcessor,
+            SerializationContext,
+        },
+        gui::UserInterface,
         impl_component_provider,
+        input::InputSystem,
+        plugin::{Plugin, PluginContext},
         scene::{base::BaseBuilder, node::Node, pivot::PivotBuilder, Scene, SceneContainer},
         script::{
             Script, ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload,
@@ -1574,7 +2350,13 @@ mod test {
         },
     };
 
-    use std::sync::mpsc::{self, Sender, TryRecvError};
+    use std::{
+        sync::{
+            mpsc::{self, Sender, TryRecvError},
+            Arc,
+        },
+        time::Duration,
+    };
 
     #[derive(PartialEq, Eq, Clone, Debug)]
     enum Event {
@@ -1710,6 +2492,8 @@ mod test {
                 &mut scene_container,
                 &mut Default::default(),
                 &resource_manager,
+                &mut Default::default(),
+                &InputSystem::without_gamepads(),
                 0.0,
                 0.0,
             );
@@ -1877,6 +2661,8 @@ mod test {
                 &mut scene_container,
                 &mut Default::default(),
                 &resource_manager,
+                &mut Default::default(),
+                &InputSystem::without_gamepads(),
                 0.0,
                 0.0,
             );
@@ -1894,4 +2680,119 @@ mod test {
             }
         }
     }
+
+    #[derive(Default)]
+    struct TaskPlugin {
+        answer: u32,
+    }
+
+    impl Plugin for TaskPlugin {}
+
+    #[derive(Debug, Clone, Default, Reflect, Visit)]
+    struct TaskScript {
+        answer: u32,
+    }
+
+    impl_component_provider!(TaskScript);
+
+    impl ScriptTrait for TaskScript {
+        fn id(&self) -> Uuid {
+            Uuid::new_v4()
+        }
+    }
+
+    #[test]
+    fn test_task_results_dispatch() {
+        let resource_manager = ResourceManager::new();
+        let mut scene = Scene::new();
+
+        let node_handle =
+            PivotBuilder::new(BaseBuilder::new().with_script(Script::new(TaskScript::default())))
+                .build(&mut scene.graph);
+
+        let mut scene_container = SceneContainer::new(Default::default());
+        let scene_handle = scene_container.add(scene);
+
+        let mut script_processor = ScriptProcessor::default();
+        script_processor.register_scripted_scene(
+            scene_handle,
+            &mut scene_container,
+            &resource_manager,
+        );
+
+        let mut plugins: Vec<Box<dyn Plugin>> = vec![Box::<TaskPlugin>::default()];
+        let mut task_pool = TaskPoolHandler::default();
+        let mut input = InputSystem::without_gamepads();
+        let mut graphics_context = GraphicsContext::Uninitialized(Default::default());
+        let mut user_interface = UserInterface::new(Vector2::new(100.0, 100.0));
+        let serialization_context = Arc::new(SerializationContext::new());
+        let performance_statistics = PerformanceStatistics::default();
+
+        // One task runs on the thread pool, the other one on the main thread.
+        task_pool.spawn_plugin_task(async { 42u32 }, |answer, plugin: &mut TaskPlugin, _| {
+            plugin.answer = answer;
+        });
+        task_pool.spawn_local_script_task(
+            scene_handle,
+            node_handle,
+            async { 123u32 },
+            |answer, script: &mut TaskScript, _| {
+                script.answer = answer;
+            },
+        );
+
+        let mut dispatched = 0;
+        for _ in 0..1000 {
+            task_pool.update_local_tasks();
+
+            while let Some(result) = task_pool.next_task_result() {
+                if let Some(handler) = task_pool.pop_plugin_task_handler(result.id) {
+                    let mut context = PluginContext {
+                        scenes: &mut scene_container,
+                        resource_manager: &resource_manager,
+                        graphics_context: &mut graphics_context,
+                        dt: 0.0,
+                        lag: &mut 0.0,
+                        user_interface: &mut user_interface,
+                        serialization_context: &serialization_context,
+                        performance_statistics: &performance_statistics,
+                        task_pool: &mut task_pool,
+                        input: &mut input,
+                    };
+
+                    handler(result.payload, &mut plugins, &mut context);
+                } else if let Some(handler) = task_pool.pop_node_task_handler(result.id) {
+                    script_processor.handle_task_result(
+                        handler,
+                        result.payload,
+                        &mut scene_container,
+                        &mut plugins,
+                        &resource_manager,
+                        &mut task_pool,
+                        &input,
+                        0.0,
+                        0.0,
+                    );
+                }
+
+                dispatched += 1;
+            }
+
+            if dispatched == 2 {
+                break;
+            }
+
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(dispatched, 2);
+        assert_eq!(plugins[0].cast_mut::<TaskPlugin>().unwrap().answer, 42);
+        assert_eq!(
+            scene_container[scene_handle].graph[node_handle]
+                .try_get_script::<TaskScript>()
+                .unwrap()
+                .answer,
+            123
+        );
+    }
 }
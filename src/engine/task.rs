@@ -0,0 +1,332 @@
+//! Task pool handler allows you to spawn async tasks from scripts and plugins and receive their
+//! results back in a callback with mutable access to the script (or plugin) that spawned the task.
+//! See [`TaskPoolHandler`] docs for more info.
+
+use crate::{
+    asset::{Resource, ResourceData, ResourceLoadError},
+    core::{
+        futures::{executor::LocalPool, task::LocalSpawnExt},
+        log::Log,
+        pool::Handle,
+        task::{AsyncTask, AsyncTaskResult, TaskPool, TaskResult},
+        uuid::Uuid,
+        TypeUuidProvider,
+    },
+    plugin::{Plugin, PluginContext},
+    scene::{node::Node, Scene},
+    script::{Script, ScriptContext, ScriptTrait},
+};
+use fxhash::FxHashMap;
+use std::{future::Future, sync::Arc};
+
+pub(crate) type PluginTaskHandler =
+    Box<dyn FnOnce(Box<dyn AsyncTaskResult>, &mut [Box<dyn Plugin>], &mut PluginContext)>;
+
+pub(crate) type NodeTaskHandlerClosure =
+    Box<dyn FnOnce(Box<dyn AsyncTaskResult>, &mut Script, &mut ScriptContext)>;
+
+pub(crate) struct NodeTaskHandler {
+    pub(crate) scene_handle: Handle<Scene>,
+    pub(crate) node_handle: Handle<Node>,
+    pub(crate) closure: NodeTaskHandlerClosure,
+}
+
+/// Task pool handler allows you to spawn async tasks (futures) either on a thread pool (or as JS
+/// micro-tasks on WebAssembly), or on the main thread, and receive their results back in a callback.
+/// The callback will be called on the main thread with mutable access to the script (or plugin)
+/// that spawned the task, so there is no need to poll anything manually in `on_update`.
+///
+/// # Examples
+///
+/// ```rust
+/// use fyrox::{
+///     core::{reflect::prelude::*, uuid::uuid, uuid::Uuid, visitor::prelude::*, TypeUuidProvider},
+///     impl_component_provider,
+///     resource::model::{Model, ModelResourceExtension},
+///     script::{ScriptContext, ScriptTrait},
+/// };
+///
+/// #[derive(Visit, Reflect, Default, Debug, Clone)]
+/// struct MyScript {
+///     answer: u32,
+/// }
+///
+/// impl_component_provider!(MyScript);
+///
+/// impl TypeUuidProvider for MyScript {
+///     fn type_uuid() -> Uuid {
+///         uuid!("4aa165aa-011b-479f-bc10-b90b2c4b5060")
+///     }
+/// }
+///
+/// impl ScriptTrait for MyScript {
+///     fn on_start(&mut self, ctx: &mut ScriptContext) {
+///         // Run some heavy computations on a thread pool.
+///         ctx.task_pool.spawn_script_task(
+///             ctx.scene_handle,
+///             ctx.handle,
+///             async move { 42 },
+///             |result, script: &mut MyScript, _ctx| {
+///                 script.answer = result;
+///             },
+///         );
+///
+///         // Wait until a resource is loaded and instantiate it.
+///         let model = ctx.resource_manager.request::<Model, _>("path/to/model.fbx");
+///         ctx.task_pool.wait_for_resource(
+///             ctx.scene_handle,
+///             ctx.handle,
+///             model,
+///             |result, _script: &mut MyScript, ctx| {
+///                 if let Ok(model) = result {
+///                     model.instantiate(ctx.scene);
+///                 }
+///             },
+///         );
+///     }
+///
+///     fn id(&self) -> Uuid {
+///         Self::type_uuid()
+///     }
+/// }
+/// ```
+pub struct TaskPoolHandler {
+    task_pool: Arc<TaskPool>,
+    local_pool: LocalPool,
+    plugin_task_handlers: FxHashMap<Uuid, PluginTaskHandler>,
+    node_task_handlers: FxHashMap<Uuid, NodeTaskHandler>,
+    // Addresses of the code that spawned unfinished tasks, they're used to find out whether there are
+    // tasks of a dynamic plugin.
+    #[cfg(feature = "hot_reload")]
+    code_addresses: FxHashMap<Uuid, usize>,
+}
+
+impl Default for TaskPoolHandler {
+    fn default() -> Self {
+        Self::new(Arc::new(TaskPool::new()))
+    }
+}
+
+impl TaskPoolHandler {
+    pub(crate) fn new(task_pool: Arc<TaskPool>) -> Self {
+        Self {
+            task_pool,
+            local_pool: LocalPool::new(),
+            plugin_task_handlers: Default::default(),
+            node_task_handlers: Default::default(),
+            #[cfg(feature = "hot_reload")]
+            code_addresses: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the underlying task pool. It could be used to spawn tasks which
+    /// results are not needed.
+    pub fn inner(&self) -> &Arc<TaskPool> {
+        &self.task_pool
+    }
+
+    /// Spawns a task on a thread pool (or as a JS micro-task on WebAssembly). When the task is
+    /// finished, `on_complete` will be called on the main thread with mutable access to the first
+    /// plugin of type `P`. The callback won't be called if there is no such plugin.
+    pub fn spawn_plugin_task<F, T, P, C>(&mut self, future: F, on_complete: C)
+    where
+        F: AsyncTask<T>,
+        T: AsyncTaskResult,
+        P: Plugin,
+        C: FnOnce(T, &mut P, &mut PluginContext) + 'static,
+    {
+        let id = self.task_pool.spawn_with_result(future);
+        self.add_plugin_task_handler(id, on_complete);
+    }
+
+    /// Spawns a task on the main thread, the task will be polled once per frame. It could be used
+    /// for the futures that cannot be sent between threads. When the task is finished, `on_complete`
+    /// will be called with mutable access to the first plugin of type `P`.
+    pub fn spawn_local_plugin_task<F, T, P, C>(&mut self, future: F, on_complete: C)
+    where
+        F: Future<Output = T> + 'static,
+        T: AsyncTaskResult,
+        P: Plugin,
+        C: FnOnce(T, &mut P, &mut PluginContext) + 'static,
+    {
+        let id = self.spawn_local_with_result(future);
+        self.add_plugin_task_handler(id, on_complete);
+    }
+
+    /// Spawns a task on a thread pool (or as a JS micro-task on WebAssembly). When the task is
+    /// finished, `on_complete` will be called on the main thread with mutable access to the script
+    /// of type `S` of the given node. The callback won't be called if the node was deleted or its
+    /// script was replaced by a script of another type.
+    pub fn spawn_script_task<F, T, S, C>(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        node_handle: Handle<Node>,
+        future: F,
+        on_complete: C,
+    ) where
+        F: AsyncTask<T>,
+        T: AsyncTaskResult,
+        S: ScriptTrait,
+        C: FnOnce(T, &mut S, &mut ScriptContext) + 'static,
+    {
+        let id = self.task_pool.spawn_with_result(future);
+        self.add_node_task_handler(id, scene_handle, node_handle, on_complete);
+    }
+
+    /// Spawns a task on the main thread, the task will be polled once per frame. It could be used
+    /// for the futures that cannot be sent between threads. When the task is finished, `on_complete`
+    /// will be called with mutable access to the script of type `S` of the given node.
+    pub fn spawn_local_script_task<F, T, S, C>(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        node_handle: Handle<Node>,
+        future: F,
+        on_complete: C,
+    ) where
+        F: Future<Output = T> + 'static,
+        T: AsyncTaskResult,
+        S: ScriptTrait,
+        C: FnOnce(T, &mut S, &mut ScriptContext) + 'static,
+    {
+        let id = self.spawn_local_with_result(future);
+        self.add_node_task_handler(id, scene_handle, node_handle, on_complete);
+    }
+
+    /// Waits until the given resource is loaded (or failed to load) and calls `on_complete` with
+    /// mutable access to the script of type `S` of the given node. Waiting is performed on the main
+    /// thread, the resource itself is loaded by the resource manager as usual.
+    pub fn wait_for_resource<R, S, C>(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        node_handle: Handle<Node>,
+        resource: Resource<R>,
+        on_complete: C,
+    ) where
+        R: ResourceData + TypeUuidProvider,
+        S: ScriptTrait,
+        C: FnOnce(
+                Result<Resource<R>, Option<Arc<dyn ResourceLoadError>>>,
+                &mut S,
+                &mut ScriptContext,
+            ) + 'static,
+    {
+        self.spawn_local_script_task(scene_handle, node_handle, resource, on_complete)
+    }
+
+    /// Waits until the given resource is loaded (or failed to load) and calls `on_complete` with
+    /// mutable access to the first plugin of type `P`.
+    pub fn wait_for_resource_in_plugin<R, P, C>(&mut self, resource: Resource<R>, on_complete: C)
+    where
+        R: ResourceData + TypeUuidProvider,
+        P: Plugin,
+        C: FnOnce(
+                Result<Resource<R>, Option<Arc<dyn ResourceLoadError>>>,
+                &mut P,
+                &mut PluginContext,
+            ) + 'static,
+    {
+        self.spawn_local_plugin_task(resource, on_complete)
+    }
+
+    fn spawn_local_with_result<F, T>(&mut self, future: F) -> Uuid
+    where
+        F: Future<Output = T> + 'static,
+        T: AsyncTaskResult,
+    {
+        let id = Uuid::new_v4();
+        let sender = self.task_pool.result_sender();
+        Log::verify(self.local_pool.spawner().spawn_local(async move {
+            let payload = Box::new(future.await);
+            // The receiver could be already destroyed, there's nothing to do in this case.
+            let _ = sender.send(TaskResult { id, payload });
+        }));
+        id
+    }
+
+    fn add_plugin_task_handler<T, P, C>(&mut self, id: Uuid, on_complete: C)
+    where
+        T: AsyncTaskResult,
+        P: Plugin,
+        C: FnOnce(T, &mut P, &mut PluginContext) + 'static,
+    {
+        // The method is instantiated in the crate that spawns the task.
+        #[cfg(feature = "hot_reload")]
+        self.code_addresses
+            .insert(id, Self::add_plugin_task_handler::<T, P, C> as usize);
+
+        self.plugin_task_handlers.insert(
+            id,
+            Box::new(move |payload, plugins, context| {
+                if let Some(plugin) = plugins.iter_mut().find_map(|p| p.cast_mut::<P>()) {
+                    if let Ok(payload) = payload.downcast::<T>() {
+                        on_complete(*payload, plugin, context);
+                    }
+                }
+            }),
+        );
+    }
+
+    fn add_node_task_handler<T, S, C>(
+        &mut self,
+        id: Uuid,
+        scene_handle: Handle<Scene>,
+        node_handle: Handle<Node>,
+        on_complete: C,
+    ) where
+        T: AsyncTaskResult,
+        S: ScriptTrait,
+        C: FnOnce(T, &mut S, &mut ScriptContext) + 'static,
+    {
+        #[cfg(feature = "hot_reload")]
+        self.code_addresses
+            .insert(id, Self::add_node_task_handler::<T, S, C> as usize);
+
+        self.node_task_handlers.insert(
+            id,
+            NodeTaskHandler {
+                scene_handle,
+                node_handle,
+                closure: Box::new(move |payload, script, context| {
+                    if let Some(script) = script.cast_mut::<S>() {
+                        if let Ok(payload) = payload.downcast::<T>() {
+                            on_complete(*payload, script, context);
+                        }
+                    }
+                }),
+            },
+        );
+    }
+
+    pub(crate) fn update_local_tasks(&mut self) {
+        self.local_pool.run_until_stalled();
+    }
+
+    pub(crate) fn next_task_result(&self) -> Option<TaskResult> {
+        self.task_pool.next_task_result()
+    }
+
+    pub(crate) fn pop_plugin_task_handler(&mut self, id: Uuid) -> Option<PluginTaskHandler> {
+        #[cfg(feature = "hot_reload")]
+        self.code_addresses.remove(&id);
+        self.plugin_task_handlers.remove(&id)
+    }
+
+    pub(crate) fn pop_node_task_handler(&mut self, id: Uuid) -> Option<NodeTaskHandler> {
+        #[cfg(feature = "hot_reload")]
+        self.code_addresses.remove(&id);
+        self.node_task_handlers.remove(&id)
+    }
+
+    /// Returns `true` if there is at least one unfinished task, that was spawned by the code at an
+    /// address that satisfies the given predicate. Tasks spawned directly on [`Self::inner`] are not
+    /// tracked.
+    #[cfg(feature = "hot_reload")]
+    pub(crate) fn has_tasks_spawned_by<F>(&self, mut predicate: F) -> bool
+    where
+        F: FnMut(usize) -> bool,
+    {
+        self.code_addresses
+            .values()
+            .any(|&address| predicate(address))
+    }
+}
@@ -0,0 +1,942 @@
+//! Input system maps physical inputs (keyboard, mouse, gamepads) to named actions and axes, so game
+//! logic does not depend on particular keys or buttons. See [`InputSystem`] docs for more info.
+
+#![warn(missing_docs)]
+
+use crate::{
+    core::{algebra::Vector2, log::Log},
+    event::{
+        DeviceEvent, ElementState, Event, MouseButton, MouseScrollDelta, VirtualKeyCode,
+        WindowEvent,
+    },
+    input::profile::{
+        AxisBinding, AxisMapping, GamepadAxis, GamepadButton, InputBinding, InputProfile,
+    },
+};
+use fxhash::{FxHashMap, FxHashSet};
+#[cfg(feature = "gamepad")]
+use gilrs::Gilrs;
+use std::hash::Hash;
+
+pub mod profile;
+
+/// Amount of a deflection of a gamepad axis, that is needed to consider an action, that is bound to
+/// the axis, pressed.
+pub const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+// Approximate amount of pixels in one "line" of mouse wheel scrolling, touchpads report scrolling in
+// pixels.
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
+/// State of an action.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ActionState {
+    /// `true` if any binding of the action is pressed.
+    pub pressed: bool,
+    /// `true` if the action became pressed in the current update.
+    pub just_pressed: bool,
+    /// `true` if the action became released in the current update.
+    pub just_released: bool,
+}
+
+#[derive(Debug)]
+struct ButtonSet<T> {
+    down: FxHashSet<T>,
+    // Buttons that were pressed since the last update. It is used to not miss short taps, that are
+    // pressed and released between two updates.
+    pressed: FxHashSet<T>,
+}
+
+impl<T> Default for ButtonSet<T> {
+    fn default() -> Self {
+        Self {
+            down: Default::default(),
+            pressed: Default::default(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash> ButtonSet<T> {
+    fn press(&mut self, button: T) {
+        if self.down.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    fn release(&mut self, button: T) {
+        self.down.remove(&button);
+    }
+
+    fn is_down(&self, button: T) -> bool {
+        self.down.contains(&button) || self.pressed.contains(&button)
+    }
+
+    fn release_all(&mut self) {
+        self.down.clear();
+    }
+
+    fn end_update(&mut self) {
+        self.pressed.clear();
+    }
+}
+
+#[derive(Default, Debug)]
+struct GamepadState {
+    buttons: ButtonSet<GamepadButton>,
+    axes: FxHashMap<GamepadAxis, f32>,
+}
+
+impl GamepadState {
+    fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes.get(&axis).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Default, Debug)]
+struct DeviceState {
+    keys: ButtonSet<VirtualKeyCode>,
+    mouse_buttons: ButtonSet<MouseButton>,
+    mouse_motion: Vector2<f32>,
+    mouse_wheel: f32,
+    gamepads: FxHashMap<usize, GamepadState>,
+}
+
+impl DeviceState {
+    fn gamepad(&self, devices: &PlayerDevices) -> Option<&GamepadState> {
+        devices.gamepad.and_then(|id| self.gamepads.get(&id))
+    }
+
+    fn is_binding_down(&self, devices: &PlayerDevices, binding: &InputBinding) -> bool {
+        match *binding {
+            InputBinding::Key(key) => devices.keyboard_and_mouse && self.keys.is_down(key),
+            InputBinding::MouseButton(button) => {
+                devices.keyboard_and_mouse && self.mouse_buttons.is_down(button)
+            }
+            InputBinding::GamepadButton(button) => self
+                .gamepad(devices)
+                .map_or(false, |gamepad| gamepad.buttons.is_down(button)),
+            InputBinding::GamepadAxis { axis, positive } => {
+                self.gamepad(devices).map_or(false, |gamepad| {
+                    let value = gamepad.axis(axis);
+                    if positive {
+                        value >= AXIS_PRESS_THRESHOLD
+                    } else {
+                        value <= -AXIS_PRESS_THRESHOLD
+                    }
+                })
+            }
+        }
+    }
+
+    fn axis_value(&self, devices: &PlayerDevices, mapping: &AxisMapping) -> f32 {
+        let mut value = 0.0;
+        for binding in mapping.bindings.iter() {
+            value += match *binding {
+                AxisBinding::Buttons { negative, positive } => {
+                    let mut value = 0.0;
+                    if self.is_binding_down(devices, &negative) {
+                        value -= 1.0;
+                    }
+                    if self.is_binding_down(devices, &positive) {
+                        value += 1.0;
+                    }
+                    value
+                }
+                AxisBinding::GamepadAxis { axis, inverted } => {
+                    let value = self.gamepad(devices).map_or(0.0, |gamepad| {
+                        apply_dead_zone(gamepad.axis(axis), mapping.dead_zone)
+                    });
+                    if inverted {
+                        -value
+                    } else {
+                        value
+                    }
+                }
+                AxisBinding::MouseX if devices.keyboard_and_mouse => self.mouse_motion.x,
+                AxisBinding::MouseY if devices.keyboard_and_mouse => self.mouse_motion.y,
+                AxisBinding::MouseWheel if devices.keyboard_and_mouse => self.mouse_wheel,
+                _ => 0.0,
+            }
+        }
+        value * mapping.scale
+    }
+
+    fn end_update(&mut self) {
+        self.keys.end_update();
+        self.mouse_buttons.end_update();
+        self.mouse_motion = Default::default();
+        self.mouse_wheel = 0.0;
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.buttons.end_update();
+        }
+    }
+}
+
+// Rescales the value, so it will smoothly grow from zero at the edge of the dead zone.
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let dead_zone = dead_zone.clamp(0.0, 0.99);
+    if value.abs() < dead_zone {
+        0.0
+    } else {
+        value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+    }
+}
+
+/// A set of input devices, that are used by a player.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerDevices {
+    /// Whether the player uses keyboard and mouse or not.
+    pub keyboard_and_mouse: bool,
+    /// An id of a gamepad used by the player. Newly connected gamepads are assigned automatically
+    /// to the first player without a gamepad.
+    pub gamepad: Option<usize>,
+}
+
+impl Default for PlayerDevices {
+    fn default() -> Self {
+        Self {
+            keyboard_and_mouse: true,
+            gamepad: None,
+        }
+    }
+}
+
+/// Input state of a single player. Every player has its own profile (bindings), devices and a stack
+/// of active contexts.
+#[derive(Debug, Default)]
+pub struct PlayerInput {
+    profile: InputProfile,
+    /// A set of devices used by the player.
+    pub devices: PlayerDevices,
+    active_contexts: Vec<String>,
+    actions: FxHashMap<String, ActionState>,
+    axes: FxHashMap<String, f32>,
+}
+
+impl PlayerInput {
+    /// Creates new player input with the given profile and devices. The first context of the profile
+    /// is activated.
+    pub fn new(profile: InputProfile, devices: PlayerDevices) -> Self {
+        let mut player = Self {
+            profile: Default::default(),
+            devices,
+            active_contexts: Default::default(),
+            actions: Default::default(),
+            axes: Default::default(),
+        };
+        player.set_profile(profile);
+        player
+    }
+
+    /// Returns current profile of the player.
+    pub fn profile(&self) -> &InputProfile {
+        &self.profile
+    }
+
+    /// Returns current profile of the player. It could be used to change bindings in runtime, changes
+    /// will take effect in the next update.
+    pub fn profile_mut(&mut self) -> &mut InputProfile {
+        &mut self.profile
+    }
+
+    /// Sets new profile of the player. If there's no active contexts, the first context of the profile
+    /// is activated.
+    pub fn set_profile(&mut self, profile: InputProfile) -> InputProfile {
+        if self.active_contexts.is_empty() {
+            if let Some(context) = profile.contexts.first() {
+                self.active_contexts.push(context.name.clone());
+            }
+        }
+        std::mem::replace(&mut self.profile, profile)
+    }
+
+    /// Activates a context with the given name. Actions and axes of the context have priority over the
+    /// ones with the same names from previously activated contexts.
+    pub fn push_context<S: AsRef<str>>(&mut self, name: S) {
+        self.active_contexts.push(name.as_ref().to_owned());
+    }
+
+    /// Deactivates the last activated context.
+    pub fn pop_context(&mut self) -> Option<String> {
+        self.active_contexts.pop()
+    }
+
+    /// Deactivates a context with the given name.
+    pub fn remove_context(&mut self, name: &str) {
+        self.active_contexts.retain(|c| c != name);
+    }
+
+    /// Sets new list of active contexts.
+    pub fn set_active_contexts(&mut self, contexts: Vec<String>) {
+        self.active_contexts = contexts;
+    }
+
+    /// Returns a list of active contexts, the last one has the highest priority.
+    pub fn active_contexts(&self) -> &[String] {
+        &self.active_contexts
+    }
+
+    /// Returns state of the action with the given name. If there's no such action in active contexts,
+    /// the action is considered released.
+    pub fn action(&self, name: &str) -> ActionState {
+        self.actions.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Returns `true` if the action with the given name is pressed.
+    pub fn is_action_pressed(&self, name: &str) -> bool {
+        self.action(name).pressed
+    }
+
+    /// Returns `true` if the action with the given name became pressed in the current update.
+    pub fn is_action_just_pressed(&self, name: &str) -> bool {
+        self.action(name).just_pressed
+    }
+
+    /// Returns `true` if the action with the given name became released in the current update.
+    pub fn is_action_just_released(&self, name: &str) -> bool {
+        self.action(name).just_released
+    }
+
+    /// Returns a value of the axis with the given name. If there's no such axis in active contexts,
+    /// the value is zero.
+    pub fn axis(&self, name: &str) -> f32 {
+        self.axes.get(name).cloned().unwrap_or_default()
+    }
+
+    fn update(&mut self, state: &DeviceState) {
+        let mut actions = FxHashMap::default();
+        let mut axes = FxHashMap::default();
+
+        // Contexts activated later have higher priority.
+        for context_name in self.active_contexts.iter().rev() {
+            let context = match self.profile.context(context_name) {
+                Some(context) => context,
+                None => continue,
+            };
+
+            for action in context.actions.iter() {
+                if actions.contains_key(&action.name) {
+                    continue;
+                }
+
+                let pressed = action
+                    .bindings
+                    .iter()
+                    .any(|binding| state.is_binding_down(&self.devices, binding));
+                let was_pressed = self.action(&action.name).pressed;
+
+                actions.insert(
+                    action.name.clone(),
+                    ActionState {
+                        pressed,
+                        just_pressed: pressed && !was_pressed,
+                        just_released: !pressed && was_pressed,
+                    },
+                );
+            }
+
+            for axis in context.axes.iter() {
+                if !axes.contains_key(&axis.name) {
+                    axes.insert(axis.name.clone(), state.axis_value(&self.devices, axis));
+                }
+            }
+        }
+
+        // Actions that are no longer available (for example, because of context switch) must be released.
+        for (name, action) in self.actions.iter() {
+            if action.pressed && !actions.contains_key(name) {
+                actions.insert(
+                    name.clone(),
+                    ActionState {
+                        pressed: false,
+                        just_pressed: false,
+                        just_released: true,
+                    },
+                );
+            }
+        }
+
+        self.actions = actions;
+        self.axes = axes;
+    }
+}
+
+/// A request to bind the next input of a player to an action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebindRequest {
+    /// Index of a player.
+    pub player: usize,
+    /// Name of a context in the profile of the player.
+    pub context: String,
+    /// Name of an action in the context.
+    pub action: String,
+    /// Index of a binding of the action, that should be replaced. If it is `None` or out of bounds, the
+    /// new binding will be added to the action.
+    pub binding_index: Option<usize>,
+}
+
+/// Input system maps physical inputs (keyboard, mouse, gamepads) to named actions (for example "Jump")
+/// and axes (for example "MoveForward"), so game logic does not depend on particular keys or buttons
+/// and players can change bindings as they want.
+///
+/// Bindings are defined by input profiles (see [`InputProfile`]), that consist of input contexts. Every
+/// player (see [`PlayerInput`]) has its own profile, set of devices and a stack of active contexts. By
+/// default, there is one player, that uses keyboard, mouse and the first connected gamepad. Gamepads
+/// are polled only with `gamepad` feature, without it gamepad bindings are never activated.
+///
+/// The state of actions and axes is updated once per [`crate::engine::Engine::update`] call. Methods
+/// of the input system without player index (like [`Self::is_action_pressed`]) are shortcuts for the
+/// first player.
+///
+/// # Examples
+///
+/// ```rust
+/// use fyrox::{
+///     event::VirtualKeyCode,
+///     input::{
+///         profile::{
+///             AxisBinding, GamepadAxis, GamepadButton, InputBinding, InputContext, InputProfile,
+///         },
+///         InputSystem,
+///     },
+/// };
+///
+/// fn setup(input: &mut InputSystem) {
+///     let profile = InputProfile::default().with_context(
+///         InputContext::new("Gameplay")
+///             .with_action(
+///                 "Jump",
+///                 vec![
+///                     InputBinding::Key(VirtualKeyCode::Space),
+///                     InputBinding::GamepadButton(GamepadButton::South),
+///                 ],
+///             )
+///             .with_axis(
+///                 "MoveForward",
+///                 vec![
+///                     AxisBinding::Buttons {
+///                         negative: InputBinding::Key(VirtualKeyCode::S),
+///                         positive: InputBinding::Key(VirtualKeyCode::W),
+///                     },
+///                     AxisBinding::GamepadAxis {
+///                         axis: GamepadAxis::LeftStickY,
+///                         inverted: false,
+///                     },
+///                 ],
+///             ),
+///     );
+///
+///     input.player_mut(0).unwrap().set_profile(profile);
+/// }
+///
+/// // Somewhere in a script or a plugin.
+/// fn update(input: &InputSystem) {
+///     if input.is_action_just_pressed("Jump") {
+///         // Jump.
+///     }
+///
+///     let speed = input.axis("MoveForward") * 5.0;
+/// }
+/// ```
+pub struct InputSystem {
+    state: DeviceState,
+    players: Vec<PlayerInput>,
+    rebind_request: Option<RebindRequest>,
+    rebind_result: Option<(RebindRequest, InputBinding)>,
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<Gilrs>,
+}
+
+impl Default for InputSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputSystem {
+    /// Creates new input system with a single player, that has an empty profile. Gamepads are
+    /// available only with `gamepad` feature.
+    pub fn new() -> Self {
+        #[allow(unused_mut)]
+        let mut input = Self::without_gamepads();
+
+        #[cfg(feature = "gamepad")]
+        input.init_gamepads();
+
+        input
+    }
+
+    pub(crate) fn without_gamepads() -> Self {
+        Self {
+            state: Default::default(),
+            players: vec![PlayerInput::default()],
+            rebind_request: None,
+            rebind_result: None,
+            #[cfg(feature = "gamepad")]
+            gilrs: None,
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn init_gamepads(&mut self) {
+        match Gilrs::new() {
+            Ok(gilrs) => {
+                let connected = gilrs
+                    .gamepads()
+                    .map(|(id, _)| usize::from(id))
+                    .collect::<Vec<_>>();
+                for id in connected {
+                    self.connect_gamepad(id);
+                }
+                self.gilrs = Some(gilrs);
+            }
+            Err(e) => Log::warn(format!(
+                "Unable to initialize gamepad support, gamepads won't be available. Reason: {e}"
+            )),
+        }
+    }
+
+    /// Returns a list of all players.
+    pub fn players(&self) -> &[PlayerInput] {
+        &self.players
+    }
+
+    /// Returns a reference to a player with the given index.
+    pub fn player(&self, index: usize) -> Option<&PlayerInput> {
+        self.players.get(index)
+    }
+
+    /// Returns a reference to a player with the given index.
+    pub fn player_mut(&mut self, index: usize) -> Option<&mut PlayerInput> {
+        self.players.get_mut(index)
+    }
+
+    /// Adds new player and returns its index.
+    pub fn add_player(&mut self, player: PlayerInput) -> usize {
+        self.players.push(player);
+        self.players.len() - 1
+    }
+
+    /// Removes a player with the given index.
+    pub fn remove_player(&mut self, index: usize) -> PlayerInput {
+        self.players.remove(index)
+    }
+
+    /// Returns state of the action with the given name of the first player.
+    pub fn action(&self, name: &str) -> ActionState {
+        self.players
+            .first()
+            .map(|p| p.action(name))
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if the action with the given name of the first player is pressed.
+    pub fn is_action_pressed(&self, name: &str) -> bool {
+        self.action(name).pressed
+    }
+
+    /// Returns `true` if the action with the given name of the first player became pressed in the
+    /// current update.
+    pub fn is_action_just_pressed(&self, name: &str) -> bool {
+        self.action(name).just_pressed
+    }
+
+    /// Returns `true` if the action with the given name of the first player became released in the
+    /// current update.
+    pub fn is_action_just_released(&self, name: &str) -> bool {
+        self.action(name).just_released
+    }
+
+    /// Returns a value of the axis with the given name of the first player.
+    pub fn axis(&self, name: &str) -> f32 {
+        self.players.first().map_or(0.0, |p| p.axis(name))
+    }
+
+    /// Returns `true` if the given key is pressed. It should be used only for the cases, when actions
+    /// are not suitable (for example, for debug shortcuts).
+    pub fn is_key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.state.keys.is_down(key)
+    }
+
+    /// Returns mouse movement (in pixels) since the last update.
+    pub fn mouse_motion(&self) -> Vector2<f32> {
+        self.state.mouse_motion
+    }
+
+    /// Returns an iterator over ids of all connected gamepads.
+    pub fn connected_gamepads(&self) -> impl Iterator<Item = usize> + '_ {
+        self.state.gamepads.keys().cloned()
+    }
+
+    /// Starts rebinding of an action, the next pressed key or button of the player will be bound to
+    /// the action. The input, that was used for rebinding, is not passed to the actions.
+    pub fn start_rebinding(&mut self, request: RebindRequest) {
+        self.rebind_request = Some(request);
+        self.rebind_result = None;
+    }
+
+    /// Cancels current rebinding (if any).
+    pub fn cancel_rebinding(&mut self) {
+        self.rebind_request = None;
+    }
+
+    /// Returns `true` if the input system waits for an input to rebind an action.
+    pub fn is_rebinding(&self) -> bool {
+        self.rebind_request.is_some()
+    }
+
+    /// Returns the result of the last finished rebinding (if any).
+    pub fn take_rebind_result(&mut self) -> Option<(RebindRequest, InputBinding)> {
+        self.rebind_result.take()
+    }
+
+    /// Handles an OS event. It is called automatically by [`crate::engine::executor::Executor`], you
+    /// need to call it manually only if you're using the engine as a framework.
+    pub fn handle_os_event(&mut self, event: &Event<()>) {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput { input, .. } => {
+                    if let Some(key) = input.virtual_keycode {
+                        match input.state {
+                            ElementState::Pressed => self.press(InputBinding::Key(key), None),
+                            ElementState::Released => self.state.keys.release(key),
+                        }
+                    }
+                }
+                WindowEvent::MouseInput { state, button, .. } => match state {
+                    ElementState::Pressed => self.press(InputBinding::MouseButton(*button), None),
+                    ElementState::Released => self.state.mouse_buttons.release(*button),
+                },
+                WindowEvent::MouseWheel { delta, .. } => {
+                    self.state.mouse_wheel += match delta {
+                        MouseScrollDelta::LineDelta(_, y) => *y,
+                        MouseScrollDelta::PixelDelta(position) => {
+                            position.y as f32 / PIXELS_PER_SCROLL_LINE
+                        }
+                    };
+                }
+                WindowEvent::Focused(false) => {
+                    // Release events won't be received while the window is not focused.
+                    self.state.keys.release_all();
+                    self.state.mouse_buttons.release_all();
+                }
+                _ => (),
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                self.state.mouse_motion += Vector2::new(delta.0 as f32, delta.1 as f32);
+            }
+            _ => (),
+        }
+    }
+
+    fn press(&mut self, binding: InputBinding, gamepad: Option<usize>) {
+        if self.try_rebind(binding, gamepad) {
+            return;
+        }
+
+        match binding {
+            InputBinding::Key(key) => self.state.keys.press(key),
+            InputBinding::MouseButton(button) => self.state.mouse_buttons.press(button),
+            InputBinding::GamepadButton(button) => {
+                if let Some(gamepad) = gamepad.and_then(|id| self.state.gamepads.get_mut(&id)) {
+                    gamepad.buttons.press(button);
+                }
+            }
+            InputBinding::GamepadAxis { .. } => (),
+        }
+    }
+
+    fn try_rebind(&mut self, binding: InputBinding, gamepad: Option<usize>) -> bool {
+        let request = match self.rebind_request.as_ref() {
+            Some(request) => request,
+            None => return false,
+        };
+
+        let player = match self.players.get_mut(request.player) {
+            Some(player) => player,
+            None => {
+                self.rebind_request = None;
+                return false;
+            }
+        };
+
+        // Only devices of the player can be used for rebinding.
+        let is_player_device = match gamepad {
+            Some(gamepad) => player.devices.gamepad == Some(gamepad),
+            None => player.devices.keyboard_and_mouse,
+        };
+        if !is_player_device {
+            return false;
+        }
+
+        if let Some(action) = player
+            .profile
+            .context_mut(&request.context)
+            .and_then(|c| c.action_mut(&request.action))
+        {
+            match request
+                .binding_index
+                .and_then(|i| action.bindings.get_mut(i))
+            {
+                Some(existing) => *existing = binding,
+                None => action.bindings.push(binding),
+            }
+        } else {
+            Log::warn(format!(
+                "Unable to rebind action {} in context {}, there's no such action!",
+                request.action, request.context
+            ));
+        }
+
+        self.rebind_result = self.rebind_request.take().map(|r| (r, binding));
+
+        true
+    }
+
+    #[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+    fn connect_gamepad(&mut self, id: usize) {
+        self.state.gamepads.entry(id).or_default();
+
+        let gamepads = &self.state.gamepads;
+        if !self.players.iter().any(|p| p.devices.gamepad == Some(id)) {
+            if let Some(player) = self.players.iter_mut().find(|p| {
+                p.devices
+                    .gamepad
+                    .map_or(true, |gamepad| !gamepads.contains_key(&gamepad))
+            }) {
+                player.devices.gamepad = Some(id);
+            }
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn poll_gamepads(&mut self) {
+        let mut events = Vec::new();
+        if let Some(gilrs) = self.gilrs.as_mut() {
+            while let Some(event) = gilrs.next_event() {
+                events.push(event);
+            }
+        }
+
+        for gilrs::Event { id, event, .. } in events {
+            let id = usize::from(id);
+            match event {
+                gilrs::EventType::Connected => self.connect_gamepad(id),
+                gilrs::EventType::Disconnected => {
+                    self.state.gamepads.remove(&id);
+                }
+                gilrs::EventType::ButtonPressed(button, _) => {
+                    if let Some(button) = map_gamepad_button(button) {
+                        self.press(InputBinding::GamepadButton(button), Some(id));
+                    }
+                }
+                gilrs::EventType::ButtonReleased(button, _) => {
+                    if let (Some(button), Some(gamepad)) =
+                        (map_gamepad_button(button), self.state.gamepads.get_mut(&id))
+                    {
+                        gamepad.buttons.release(button);
+                    }
+                }
+                gilrs::EventType::ButtonChanged(button, value, _) => {
+                    // Analog triggers are reported as buttons.
+                    let axis = match button {
+                        gilrs::Button::LeftTrigger2 => GamepadAxis::LeftTrigger,
+                        gilrs::Button::RightTrigger2 => GamepadAxis::RightTrigger,
+                        _ => continue,
+                    };
+                    self.set_gamepad_axis(id, axis, value);
+                }
+                gilrs::EventType::AxisChanged(axis, value, _) => {
+                    let axis = match axis {
+                        gilrs::Axis::LeftStickX => GamepadAxis::LeftStickX,
+                        gilrs::Axis::LeftStickY => GamepadAxis::LeftStickY,
+                        gilrs::Axis::RightStickX => GamepadAxis::RightStickX,
+                        gilrs::Axis::RightStickY => GamepadAxis::RightStickY,
+                        _ => continue,
+                    };
+                    self.set_gamepad_axis(id, axis, value);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+    fn set_gamepad_axis(&mut self, id: usize, axis: GamepadAxis, value: f32) {
+        if let Some(gamepad) = self.state.gamepads.get_mut(&id) {
+            gamepad.axes.insert(axis, value);
+        }
+    }
+
+    /// Updates the state of actions and axes of every player. It is called automatically by
+    /// [`crate::engine::Engine::update`].
+    pub(crate) fn update(&mut self) {
+        #[cfg(feature = "gamepad")]
+        self.poll_gamepads();
+
+        for player in self.players.iter_mut() {
+            player.update(&self.state);
+        }
+
+        self.state.end_update();
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn map_gamepad_button(button: gilrs::Button) -> Option<GamepadButton> {
+    match button {
+        gilrs::Button::South => Some(GamepadButton::South),
+        gilrs::Button::East => Some(GamepadButton::East),
+        gilrs::Button::North => Some(GamepadButton::North),
+        gilrs::Button::West => Some(GamepadButton::West),
+        gilrs::Button::LeftTrigger => Some(GamepadButton::LeftBumper),
+        gilrs::Button::LeftTrigger2 => Some(GamepadButton::LeftTrigger),
+        gilrs::Button::RightTrigger => Some(GamepadButton::RightBumper),
+        gilrs::Button::RightTrigger2 => Some(GamepadButton::RightTrigger),
+        gilrs::Button::Select => Some(GamepadButton::Select),
+        gilrs::Button::Start => Some(GamepadButton::Start),
+        gilrs::Button::Mode => Some(GamepadButton::Mode),
+        gilrs::Button::LeftThumb => Some(GamepadButton::LeftThumb),
+        gilrs::Button::RightThumb => Some(GamepadButton::RightThumb),
+        gilrs::Button::DPadUp => Some(GamepadButton::DPadUp),
+        gilrs::Button::DPadDown => Some(GamepadButton::DPadDown),
+        gilrs::Button::DPadLeft => Some(GamepadButton::DPadLeft),
+        gilrs::Button::DPadRight => Some(GamepadButton::DPadRight),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        event::VirtualKeyCode,
+        input::{
+            apply_dead_zone,
+            profile::{
+                AxisBinding, GamepadAxis, GamepadButton, InputBinding, InputContext, InputProfile,
+            },
+            InputSystem, PlayerDevices, PlayerInput, RebindRequest,
+        },
+    };
+    use std::str::FromStr;
+
+    fn profile() -> InputProfile {
+        InputProfile::default()
+            .with_context(
+                InputContext::new("Gameplay")
+                    .with_action("Jump", vec![InputBinding::Key(VirtualKeyCode::Space)])
+                    .with_axis(
+                        "MoveForward",
+                        vec![
+                            AxisBinding::Buttons {
+                                negative: InputBinding::Key(VirtualKeyCode::S),
+                                positive: InputBinding::Key(VirtualKeyCode::W),
+                            },
+                            AxisBinding::GamepadAxis {
+                                axis: GamepadAxis::LeftStickY,
+                                inverted: false,
+                            },
+                        ],
+                    ),
+            )
+            .with_context(InputContext::new("Menu").with_action(
+                "Jump",
+                vec![InputBinding::GamepadButton(GamepadButton::South)],
+            ))
+    }
+
+    #[test]
+    fn test_actions_and_axes() {
+        let mut input = InputSystem::without_gamepads();
+        input.player_mut(0).unwrap().set_profile(profile());
+
+        input.press(InputBinding::Key(VirtualKeyCode::Space), None);
+        input.press(InputBinding::Key(VirtualKeyCode::W), None);
+        input.update();
+        assert!(input.is_action_just_pressed("Jump"));
+        assert!(input.is_action_pressed("Jump"));
+        assert_eq!(input.axis("MoveForward"), 1.0);
+
+        input.update();
+        assert!(!input.is_action_just_pressed("Jump"));
+        assert!(input.is_action_pressed("Jump"));
+
+        input.state.keys.release(VirtualKeyCode::Space);
+        input.update();
+        assert!(input.is_action_just_released("Jump"));
+        assert!(!input.is_action_pressed("Jump"));
+
+        // Short tap between two updates must not be lost.
+        input.press(InputBinding::Key(VirtualKeyCode::Space), None);
+        input.state.keys.release(VirtualKeyCode::Space);
+        input.update();
+        assert!(input.is_action_just_pressed("Jump"));
+
+        // "Menu" context overrides "Jump" action.
+        input.player_mut(0).unwrap().push_context("Menu");
+        input.press(InputBinding::Key(VirtualKeyCode::Space), None);
+        input.update();
+        assert!(!input.is_action_pressed("Jump"));
+        // Axis is still available from "Gameplay" context.
+        assert_eq!(input.axis("MoveForward"), 1.0);
+    }
+
+    #[test]
+    fn test_gamepad_and_dead_zone() {
+        assert_eq!(apply_dead_zone(0.1, 0.2), 0.0);
+        assert!((apply_dead_zone(1.0, 0.2) - 1.0).abs() < 1e-5);
+        assert!((apply_dead_zone(-0.6, 0.2) + 0.5).abs() < 1e-5);
+
+        let mut input = InputSystem::without_gamepads();
+        input.player_mut(0).unwrap().set_profile(profile());
+        let second = input.add_player(PlayerInput::new(
+            profile(),
+            PlayerDevices {
+                keyboard_and_mouse: false,
+                gamepad: None,
+            },
+        ));
+
+        // The first player has no gamepad, so the first gamepad must be assigned to it.
+        input.connect_gamepad(0);
+        input.connect_gamepad(1);
+        assert_eq!(input.player(0).unwrap().devices.gamepad, Some(0));
+        assert_eq!(input.player(second).unwrap().devices.gamepad, Some(1));
+
+        input.set_gamepad_axis(1, GamepadAxis::LeftStickY, -0.575);
+        input.update();
+        assert_eq!(input.axis("MoveForward"), 0.0);
+        assert!((input.player(second).unwrap().axis("MoveForward") + 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_rebinding_and_serialization() {
+        let mut input = InputSystem::without_gamepads();
+        input.player_mut(0).unwrap().set_profile(profile());
+
+        input.start_rebinding(RebindRequest {
+            player: 0,
+            context: "Gameplay".to_string(),
+            action: "Jump".to_string(),
+            binding_index: Some(0),
+        });
+        input.press(InputBinding::Key(VirtualKeyCode::J), None);
+        assert!(!input.is_rebinding());
+        assert!(input.take_rebind_result().is_some());
+
+        // Rebinding input must not trigger actions.
+        input.update();
+        assert!(!input.is_action_pressed("Jump"));
+
+        input.press(InputBinding::Key(VirtualKeyCode::J), None);
+        input.update();
+        assert!(input.is_action_pressed("Jump"));
+
+        let profile = input.player(0).unwrap().profile().clone();
+        let serialized = ron::ser::to_string(&profile).unwrap();
+        assert_eq!(InputProfile::from_str(&serialized).unwrap(), profile);
+    }
+}
@@ -0,0 +1,354 @@
+//! Input profile is a serializable set of named actions and axes with their bindings to physical
+//! inputs. See [`InputProfile`] docs for more info.
+
+use crate::{
+    core::io::{self, FileLoadError},
+    event::{MouseButton, VirtualKeyCode},
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    io::Cursor,
+    path::Path,
+    str::FromStr,
+};
+
+/// Default dead zone of gamepad axes.
+pub const DEFAULT_DEAD_ZONE: f32 = 0.15;
+
+/// A button of a gamepad. Names are based on the position of the buttons, not on their labels, which
+/// are different for different gamepads.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    /// Bottom button of the right cluster (A on Xbox gamepads, Cross on PlayStation gamepads).
+    South,
+    /// Right button of the right cluster (B on Xbox gamepads, Circle on PlayStation gamepads).
+    East,
+    /// Top button of the right cluster (Y on Xbox gamepads, Triangle on PlayStation gamepads).
+    North,
+    /// Left button of the right cluster (X on Xbox gamepads, Square on PlayStation gamepads).
+    West,
+    /// Left bumper.
+    LeftBumper,
+    /// Left trigger. It is also available as an axis, see [`GamepadAxis::LeftTrigger`].
+    LeftTrigger,
+    /// Right bumper.
+    RightBumper,
+    /// Right trigger. It is also available as an axis, see [`GamepadAxis::RightTrigger`].
+    RightTrigger,
+    /// Select (Back, Share) button.
+    Select,
+    /// Start (Menu, Options) button.
+    Start,
+    /// Mode (Guide, PS) button.
+    Mode,
+    /// Left stick button.
+    LeftThumb,
+    /// Right stick button.
+    RightThumb,
+    /// Up button of the directional pad.
+    DPadUp,
+    /// Down button of the directional pad.
+    DPadDown,
+    /// Left button of the directional pad.
+    DPadLeft,
+    /// Right button of the directional pad.
+    DPadRight,
+}
+
+/// An axis of a gamepad. All axes have values in `[-1; 1]` range, except triggers, which have values in
+/// `[0; 1]` range. Positive direction of Y axes is up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    /// Horizontal axis of the left stick.
+    LeftStickX,
+    /// Vertical axis of the left stick.
+    LeftStickY,
+    /// Horizontal axis of the right stick.
+    RightStickX,
+    /// Vertical axis of the right stick.
+    RightStickY,
+    /// Left trigger.
+    LeftTrigger,
+    /// Right trigger.
+    RightTrigger,
+}
+
+/// A binding of an action to a physical input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputBinding {
+    /// A key of a keyboard.
+    Key(VirtualKeyCode),
+    /// A button of a mouse.
+    MouseButton(MouseButton),
+    /// A button of a gamepad.
+    GamepadButton(GamepadButton),
+    /// A gamepad axis, that is considered pressed if it is deflected more than a half in the given
+    /// direction. It could be used to bind actions to triggers.
+    GamepadAxis {
+        /// An axis of a gamepad.
+        axis: GamepadAxis,
+        /// Direction of the deflection, `true` - positive, `false` - negative.
+        positive: bool,
+    },
+}
+
+/// A binding of an axis to a physical input.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AxisBinding {
+    /// A pair of buttons, the value of the axis is `1.0` if the positive button is pressed and `-1.0`
+    /// if the negative one is pressed.
+    Buttons {
+        /// A button that decreases the value of the axis.
+        negative: InputBinding,
+        /// A button that increases the value of the axis.
+        positive: InputBinding,
+    },
+    /// An axis of a gamepad, dead zone of the axis mapping is applied to it.
+    GamepadAxis {
+        /// An axis of a gamepad.
+        axis: GamepadAxis,
+        /// Whether to invert the value of the axis or not.
+        #[serde(default)]
+        inverted: bool,
+    },
+    /// Horizontal mouse movement (in pixels) since the last update.
+    MouseX,
+    /// Vertical mouse movement (in pixels) since the last update. Positive direction is down.
+    MouseY,
+    /// Mouse wheel scrolling (in lines) since the last update.
+    MouseWheel,
+}
+
+/// A named action (for example "Jump" or "Fire") with a set of bindings. An action is pressed, if any
+/// of its bindings is pressed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActionMapping {
+    /// Name of the action.
+    pub name: String,
+    /// A set of bindings of the action.
+    #[serde(default)]
+    pub bindings: Vec<InputBinding>,
+}
+
+/// A named axis (for example "MoveForward" or "LookAround") with a set of bindings. The value of the
+/// axis is a sum of the values of its bindings, multiplied by the scale.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AxisMapping {
+    /// Name of the axis.
+    pub name: String,
+    /// A set of bindings of the axis.
+    #[serde(default)]
+    pub bindings: Vec<AxisBinding>,
+    /// Values of gamepad axes, that are less than this value, are considered zero. It prevents
+    /// "drifting" of worn-out sticks.
+    #[serde(default = "default_dead_zone")]
+    pub dead_zone: f32,
+    /// A value that is used to multiply the value of the axis. It could be used to change sensitivity
+    /// of mouse, for example.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+}
+
+fn default_dead_zone() -> f32 {
+    DEFAULT_DEAD_ZONE
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// Input context is a named set of actions and axes, that are active at the same time. For example a game
+/// could have "Gameplay" context with movement and shooting, "Vehicle" context with driving controls and
+/// "Menu" context with navigation controls.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputContext {
+    /// Name of the context.
+    pub name: String,
+    /// A set of actions of the context.
+    #[serde(default)]
+    pub actions: Vec<ActionMapping>,
+    /// A set of axes of the context.
+    #[serde(default)]
+    pub axes: Vec<AxisMapping>,
+}
+
+impl InputContext {
+    /// Creates new empty context with the given name.
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            actions: Default::default(),
+            axes: Default::default(),
+        }
+    }
+
+    /// Adds an action with the given bindings to the context.
+    pub fn with_action<S: AsRef<str>>(mut self, name: S, bindings: Vec<InputBinding>) -> Self {
+        self.actions.push(ActionMapping {
+            name: name.as_ref().to_owned(),
+            bindings,
+        });
+        self
+    }
+
+    /// Adds an axis with the given bindings and default parameters to the context.
+    pub fn with_axis<S: AsRef<str>>(mut self, name: S, bindings: Vec<AxisBinding>) -> Self {
+        self.axes.push(AxisMapping {
+            name: name.as_ref().to_owned(),
+            bindings,
+            dead_zone: DEFAULT_DEAD_ZONE,
+            scale: 1.0,
+        });
+        self
+    }
+
+    /// Tries to find an action with the given name.
+    pub fn action(&self, name: &str) -> Option<&ActionMapping> {
+        self.actions.iter().find(|a| a.name == name)
+    }
+
+    /// Tries to find an action with the given name.
+    pub fn action_mut(&mut self, name: &str) -> Option<&mut ActionMapping> {
+        self.actions.iter_mut().find(|a| a.name == name)
+    }
+
+    /// Tries to find an axis with the given name.
+    pub fn axis(&self, name: &str) -> Option<&AxisMapping> {
+        self.axes.iter().find(|a| a.name == name)
+    }
+
+    /// Tries to find an axis with the given name.
+    pub fn axis_mut(&mut self, name: &str) -> Option<&mut AxisMapping> {
+        self.axes.iter_mut().find(|a| a.name == name)
+    }
+}
+
+/// An error that may occur during input profile loading or saving.
+#[derive(Debug)]
+pub enum InputProfileError {
+    /// A file load error has occurred.
+    Io(FileLoadError),
+    /// A file write error has occurred.
+    Write(std::io::Error),
+    /// A parsing error has occurred.
+    ParseError(ron::error::SpannedError),
+    /// A serialization error has occurred.
+    SerializeError(ron::Error),
+}
+
+impl Display for InputProfileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputProfileError::Io(v) => write!(f, "A file load error has occurred {v:?}"),
+            InputProfileError::Write(v) => write!(f, "A file write error has occurred {v}"),
+            InputProfileError::ParseError(v) => write!(f, "A parsing error has occurred {v}"),
+            InputProfileError::SerializeError(v) => {
+                write!(f, "A serialization error has occurred {v}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for InputProfileError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<std::io::Error> for InputProfileError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Write(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for InputProfileError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+impl From<ron::Error> for InputProfileError {
+    fn from(e: ron::Error) -> Self {
+        Self::SerializeError(e)
+    }
+}
+
+/// Input profile is a set of input contexts. Every player has its own profile, so players can have
+/// different bindings. Profiles could be saved to and loaded from files, so your game can store
+/// bindings changed by a player.
+///
+/// # Format
+///
+/// Input profiles are stored in the following (RON) format:
+///
+/// ```text
+/// (
+///     contexts: [
+///         (
+///             name: "Gameplay",
+///             actions: [
+///                 (name: "Jump", bindings: [Key(Space), GamepadButton(South)]),
+///             ],
+///             axes: [
+///                 (
+///                     name: "MoveForward",
+///                     bindings: [
+///                         Buttons(negative: Key(S), positive: Key(W)),
+///                         GamepadAxis(axis: LeftStickY),
+///                     ],
+///                     dead_zone: 0.2,
+///                 ),
+///             ],
+///         ),
+///     ],
+/// )
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputProfile {
+    /// A set of contexts of the profile.
+    #[serde(default)]
+    pub contexts: Vec<InputContext>,
+}
+
+impl InputProfile {
+    /// Adds new context to the profile.
+    pub fn with_context(mut self, context: InputContext) -> Self {
+        self.contexts.push(context);
+        self
+    }
+
+    /// Tries to find a context with the given name.
+    pub fn context(&self, name: &str) -> Option<&InputContext> {
+        self.contexts.iter().find(|c| c.name == name)
+    }
+
+    /// Tries to find a context with the given name.
+    pub fn context_mut(&mut self, name: &str) -> Option<&mut InputContext> {
+        self.contexts.iter_mut().find(|c| c.name == name)
+    }
+
+    /// Loads an input profile from the given file.
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, InputProfileError> {
+        let content = io::load_file(path).await?;
+        Ok(ron::de::from_reader(Cursor::new(content))?)
+    }
+
+    /// Saves the input profile to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), InputProfileError> {
+        let content = ron::ser::to_string_pretty(self, PrettyConfig::default())?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+impl FromStr for InputProfile {
+    type Err = InputProfileError;
+
+    /// Creates an input profile from the given string, the string must have the format described in
+    /// [`InputProfile`] docs.
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        Ok(ron::de::from_str(str)?)
+    }
+}
@@ -11,7 +11,10 @@
 
 pub mod animation;
 pub mod engine;
+pub mod input;
 pub mod material;
+#[cfg(feature = "networking")]
+pub mod net;
 pub mod plugin;
 pub mod renderer;
 pub mod resource;
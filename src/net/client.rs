@@ -0,0 +1,737 @@
+//! Client, that receives replicated nodes from a server. See [`Client`] docs for more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{log::Log, pool::Handle},
+    fxhash::{FxHashMap, FxHashSet},
+    net::{
+        deliver_rpc,
+        interpolation::InterpolationBuffer,
+        packet::{
+            EntityDelta, Packet, ReliableMessage, SnapshotAssembler, SpawnSource, MAX_PACKET_SIZE,
+        },
+        rpc::{ReceivedRpc, ReliableChannel, Rpc},
+        take_outgoing_rpcs,
+        value::NetValue,
+        write_property, ClientId, NetworkId,
+    },
+    resource::model::{Model, ModelResource, ModelResourceExtension},
+    scene::{node::Node, Scene},
+};
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+/// Max amount of received snapshots, that are stored to decode delta-compressed snapshots.
+const MAX_HISTORY: usize = 64;
+
+/// Max amount of inputs, that were not acknowledged by the server yet. Older inputs are discarded.
+const MAX_PENDING_INPUTS: usize = 64;
+
+/// Interval between connection attempts in seconds.
+const CONNECT_INTERVAL: f32 = 0.25;
+
+/// State of the connection of a client.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The client is trying to connect to the server.
+    Connecting,
+    /// The client is connected and the server has assigned the given id to it.
+    Connected(ClientId),
+    /// The client was disconnected by the server or the connection has timed out.
+    Disconnected,
+}
+
+/// An event that happened on the client.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientEvent {
+    /// The client has connected to the server.
+    Connected(ClientId),
+    /// The client was disconnected by the server or the connection has timed out.
+    Disconnected,
+    /// A replicated entity has appeared on the client.
+    EntitySpawned {
+        /// Network id of the entity.
+        id: NetworkId,
+        /// A node, that represents the entity on the client.
+        node: Handle<Node>,
+    },
+    /// A replicated entity was removed by the server. Instances of prefabs are deleted automatically, other
+    /// nodes are left intact.
+    EntityDespawned {
+        /// Network id of the entity.
+        id: NetworkId,
+        /// A node, that represented the entity on the client.
+        node: Handle<Node>,
+    },
+    /// The server has sent a remote procedure call, that is not bound to any entity.
+    Rpc(Rpc),
+}
+
+/// A set of data, that is passed to [`PredictionHandler::reconcile`].
+pub struct PredictionContext<'a> {
+    /// A scene with the predicted node.
+    pub scene: &'a mut Scene,
+    /// A handle of the predicted node.
+    pub node: Handle<Node>,
+    /// Network id of the predicted entity.
+    pub id: NetworkId,
+    /// Paths of replicated properties of the entity.
+    pub properties: &'a [String],
+    /// Authoritative values of the properties (in the same order as `properties`). Values could be `None`
+    /// if the server has not sent them yet.
+    pub values: &'a [Option<NetValue>],
+    /// Sequence number of the last input, that was processed by the server. Authoritative values include
+    /// the result of this input.
+    pub last_processed_input: u32,
+    /// Inputs that were sent to the server, but not processed by it yet.
+    pub pending_inputs: &'a [(u32, Vec<u8>)],
+}
+
+impl<'a> PredictionContext<'a> {
+    /// Returns authoritative value of the property with the given path.
+    pub fn value(&self, path: &str) -> Option<&NetValue> {
+        self.properties
+            .iter()
+            .position(|p| p == path)
+            .and_then(|i| self.values.get(i))
+            .and_then(|v| v.as_ref())
+    }
+}
+
+/// Client-side prediction allows a client to apply its input to a local player immediately, without
+/// waiting for a round trip to the server. When an authoritative state arrives from the server, the
+/// handler should reset the node to the authoritative state and re-apply every input, that was not
+/// processed by the server yet.
+pub trait PredictionHandler: Send {
+    /// Reconciles the state of a predicted node with the authoritative state. The method is called once
+    /// for every predicted entity when a new snapshot is received.
+    fn reconcile(&mut self, context: PredictionContext);
+}
+
+struct RemoteEntity {
+    node: Handle<Node>,
+    source: SpawnSource,
+    properties: Vec<String>,
+    buffers: Vec<InterpolationBuffer>,
+    prefab: Option<ModelResource>,
+}
+
+type EntityState = Vec<Option<NetValue>>;
+
+/// Client receives replicated entities from a [`crate::net::server::Server`] and applies their state to a
+/// scene. Remote entities are interpolated, local entities could be predicted using
+/// [`PredictionHandler`]. See [module docs](crate::net) for an overview.
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     asset::manager::ResourceManager,
+///     net::client::{Client, ClientEvent},
+///     scene::Scene,
+/// };
+///
+/// fn run_client(scene: &mut Scene, resource_manager: &ResourceManager) {
+///     let mut client = Client::connect("127.0.0.1:7777").unwrap();
+///
+///     // Call this every frame.
+///     client.send_input(vec![1, 2, 3]);
+///     client.update(scene, resource_manager, 1.0 / 60.0);
+///     while let Some(event) = client.poll_event() {
+///         if let ClientEvent::EntitySpawned { id, node } = event {
+///             println!("Entity {id} is represented by {node} node.");
+///         }
+///     }
+/// }
+/// ```
+pub struct Client {
+    socket: UdpSocket,
+    receive_buffer: Vec<u8>,
+    state: ConnectionState,
+    challenge: Option<u64>,
+    events: VecDeque<ClientEvent>,
+    states: VecDeque<(u32, FxHashMap<NetworkId, EntityState>)>,
+    snapshot_assembler: SnapshotAssembler,
+    entities: FxHashMap<NetworkId, RemoteEntity>,
+    channel: ReliableChannel,
+    inputs: Vec<(u32, Vec<u8>)>,
+    next_input: u32,
+    last_processed_input: u32,
+    predicted: FxHashSet<NetworkId>,
+    prediction_handler: Option<Box<dyn PredictionHandler>>,
+    needs_reconcile: bool,
+    server_time: Option<f32>,
+    interpolation_delay: f32,
+    send_rate: f32,
+    send_timer: f32,
+    connect_timer: f32,
+    idle_time: f32,
+    timeout: f32,
+}
+
+impl Client {
+    /// Default delay of interpolated entities in seconds.
+    pub const DEFAULT_INTERPOLATION_DELAY: f32 = 0.1;
+
+    /// Default amount of packets per second, that are sent to the server.
+    pub const DEFAULT_SEND_RATE: f32 = 30.0;
+
+    /// Default amount of seconds without any packets from the server, after which the client is
+    /// disconnected.
+    pub const DEFAULT_TIMEOUT: f32 = 5.0;
+
+    /// Creates new client, that will be connecting to a server with the given address. Actual connection
+    /// happens in [`Self::update`], you'll receive [`ClientEvent::Connected`] event when it is established.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let server = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "No address to connect to!"))?;
+        let local: SocketAddr = if server.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(server)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            receive_buffer: vec![0; MAX_PACKET_SIZE],
+            state: ConnectionState::Connecting,
+            challenge: None,
+            events: Default::default(),
+            states: Default::default(),
+            snapshot_assembler: Default::default(),
+            entities: Default::default(),
+            channel: Default::default(),
+            inputs: Default::default(),
+            next_input: 1,
+            last_processed_input: 0,
+            predicted: Default::default(),
+            prediction_handler: None,
+            needs_reconcile: false,
+            server_time: None,
+            interpolation_delay: Self::DEFAULT_INTERPOLATION_DELAY,
+            send_rate: Self::DEFAULT_SEND_RATE,
+            send_timer: 0.0,
+            connect_timer: 0.0,
+            idle_time: 0.0,
+            timeout: Self::DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Returns current state of the connection.
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Sets a delay (in seconds) of interpolated entities. Larger values make the movement of remote
+    /// entities smoother when packets are lost, but increase the latency. Good value is at least two
+    /// intervals between snapshots of the server.
+    pub fn set_interpolation_delay(&mut self, delay: f32) {
+        self.interpolation_delay = delay.max(0.0);
+    }
+
+    /// Returns current delay of interpolated entities.
+    pub fn interpolation_delay(&self) -> f32 {
+        self.interpolation_delay
+    }
+
+    /// Sets amount of packets per second, that are sent to the server.
+    pub fn set_send_rate(&mut self, send_rate: f32) {
+        self.send_rate = send_rate.max(f32::EPSILON);
+    }
+
+    /// Returns amount of packets per second, that are sent to the server.
+    pub fn send_rate(&self) -> f32 {
+        self.send_rate
+    }
+
+    /// Sets amount of seconds without any packets from the server, after which the client is disconnected.
+    pub fn set_timeout(&mut self, timeout: f32) {
+        self.timeout = timeout;
+    }
+
+    /// Returns amount of seconds without any packets from the server, after which the client is
+    /// disconnected.
+    pub fn timeout(&self) -> f32 {
+        self.timeout
+    }
+
+    /// Returns a handle of the node, that represents the given entity. The handle could be
+    /// [`Handle::NONE`] if the entity is unknown or is still being spawned.
+    pub fn node(&self, id: NetworkId) -> Handle<Node> {
+        self.entities
+            .get(&id)
+            .map(|entity| entity.node)
+            .unwrap_or_default()
+    }
+
+    /// Returns network id of the entity, that is represented by the given node.
+    pub fn network_id(&self, node: Handle<Node>) -> Option<NetworkId> {
+        self.entities
+            .iter()
+            .find_map(|(id, entity)| if entity.node == node { Some(*id) } else { None })
+    }
+
+    /// Defines whether the given entity is predicted or not. Predicted entities are not interpolated,
+    /// instead [`PredictionHandler`] is used to reconcile their state with the authoritative state. If
+    /// there is no prediction handler, authoritative values are applied as is.
+    pub fn set_predicted(&mut self, id: NetworkId, predicted: bool) {
+        if predicted {
+            self.predicted.insert(id);
+        } else {
+            self.predicted.remove(&id);
+        }
+    }
+
+    /// Returns `true` if the given entity is predicted.
+    pub fn is_predicted(&self, id: NetworkId) -> bool {
+        self.predicted.contains(&id)
+    }
+
+    /// Sets new prediction handler.
+    pub fn set_prediction_handler(&mut self, handler: Option<Box<dyn PredictionHandler>>) {
+        self.prediction_handler = handler;
+    }
+
+    /// Sends input to the server. Returns sequence number of the input. The input is kept by the client
+    /// until the server confirms that the input was processed, see [`Self::pending_inputs`].
+    pub fn send_input(&mut self, data: Vec<u8>) -> u32 {
+        let sequence = self.next_input;
+        self.next_input += 1;
+        self.inputs.push((sequence, data));
+        if self.inputs.len() > MAX_PENDING_INPUTS {
+            self.inputs.remove(0);
+        }
+        sequence
+    }
+
+    /// Returns a list of inputs, that were not processed by the server yet.
+    pub fn pending_inputs(&self) -> &[(u32, Vec<u8>)] {
+        &self.inputs
+    }
+
+    /// Returns sequence number of the last input, that was processed by the server.
+    pub fn last_processed_input(&self) -> u32 {
+        self.last_processed_input
+    }
+
+    /// Sends a remote procedure call, that is not bound to any entity, to the server.
+    pub fn send_rpc(&mut self, rpc: Rpc) {
+        self.channel.send(None, rpc);
+    }
+
+    /// Disconnects from the server.
+    pub fn disconnect(&mut self) {
+        if self.state != ConnectionState::Disconnected {
+            self.send(&Packet::Disconnect);
+            self.set_disconnected();
+        }
+    }
+
+    /// Pops next event from the queue of events.
+    pub fn poll_event(&mut self) -> Option<ClientEvent> {
+        self.events.pop_front()
+    }
+
+    /// Receives snapshots from the server, applies them to the scene and sends input and RPCs to the
+    /// server. Must be called every frame.
+    pub fn update(&mut self, scene: &mut Scene, resource_manager: &ResourceManager, dt: f32) {
+        if self.state == ConnectionState::Disconnected {
+            return;
+        }
+
+        self.idle_time += dt;
+        if self.idle_time >= self.timeout {
+            Log::warn("Connection to the server has timed out.");
+            self.set_disconnected();
+            return;
+        }
+
+        if self.state == ConnectionState::Connecting {
+            self.connect_timer -= dt;
+            if self.connect_timer <= 0.0 {
+                self.connect_timer = CONNECT_INTERVAL;
+                match self.challenge {
+                    Some(token) => self.send(&Packet::ChallengeResponse { token }),
+                    None => self.send(&Packet::Connect),
+                }
+            }
+        }
+
+        self.receive(scene, resource_manager);
+
+        if self.state == ConnectionState::Disconnected {
+            return;
+        }
+
+        self.spawn_prefabs(scene);
+        self.interpolate(scene, dt);
+        self.reconcile(scene);
+
+        if let ConnectionState::Connected(_) = self.state {
+            for (id, entity) in self.entities.iter() {
+                for rpc in take_outgoing_rpcs(&mut scene.graph, entity.node) {
+                    self.channel.send(Some(*id), rpc);
+                }
+            }
+
+            self.send_timer += dt;
+            let interval = 1.0 / self.send_rate;
+            if self.send_timer >= interval {
+                self.send_timer = (self.send_timer - interval).min(interval);
+                self.send(&Packet::ClientUpdate {
+                    acked_snapshot: self.states.back().map(|(sequence, _)| *sequence),
+                    acked_reliable: self.channel.received(),
+                    reliable: self.channel.pending(),
+                    inputs: self.inputs.clone(),
+                });
+            }
+        }
+    }
+
+    fn set_disconnected(&mut self) {
+        self.state = ConnectionState::Disconnected;
+        self.events.push_back(ClientEvent::Disconnected);
+    }
+
+    fn receive(&mut self, scene: &mut Scene, resource_manager: &ResourceManager) {
+        loop {
+            match self.socket.recv(&mut self.receive_buffer) {
+                Ok(size) => {
+                    if let Some(packet) = Packet::decode(&self.receive_buffer[..size]) {
+                        self.handle_packet(scene, resource_manager, packet);
+                    }
+                }
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                // The server is not reachable (yet), keep trying until timeout.
+                Err(ref e) if e.kind() == ErrorKind::ConnectionRefused => break,
+                Err(ref e) if e.kind() == ErrorKind::ConnectionReset => break,
+                Err(e) => {
+                    Log::err(format!("Unable to receive a packet. Reason: {e}"));
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_packet(
+        &mut self,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+        packet: Packet,
+    ) {
+        match packet {
+            Packet::Challenge { token } => {
+                if self.state == ConnectionState::Connecting {
+                    self.challenge = Some(token);
+                    self.send(&Packet::ChallengeResponse { token });
+                }
+            }
+            Packet::Accepted { client } => {
+                self.idle_time = 0.0;
+                if self.state == ConnectionState::Connecting {
+                    Log::info(format!("Connected to the server as {client}."));
+                    self.state = ConnectionState::Connected(client);
+                    self.events.push_back(ClientEvent::Connected(client));
+                }
+            }
+            Packet::Disconnect => {
+                Log::info("Disconnected by the server.");
+                self.set_disconnected();
+            }
+            Packet::Snapshot {
+                sequence,
+                part,
+                part_count,
+                baseline,
+                time,
+                last_input,
+                acked_reliable,
+                reliable,
+                entities,
+            } => {
+                if let ConnectionState::Connected(_) = self.state {
+                    self.idle_time = 0.0;
+                    self.channel.acknowledge(acked_reliable);
+
+                    // Reliable messages of an incomplete snapshot are not acknowledged, so the server
+                    // will send them again.
+                    let (reliable, entities) = match self
+                        .snapshot_assembler
+                        .add(sequence, part, part_count, reliable, entities)
+                    {
+                        Some(snapshot) => snapshot,
+                        None => return,
+                    };
+
+                    self.handle_snapshot(
+                        scene,
+                        resource_manager,
+                        sequence,
+                        baseline,
+                        time,
+                        entities,
+                    );
+                    self.handle_reliable(scene, reliable);
+                    if last_input > self.last_processed_input {
+                        self.last_processed_input = last_input;
+                        self.inputs.retain(|(sequence, _)| *sequence > last_input);
+                    }
+                }
+            }
+            // Client-side packets are ignored.
+            Packet::Connect | Packet::ClientUpdate { .. } | Packet::ChallengeResponse { .. } => (),
+        }
+    }
+
+    fn handle_snapshot(
+        &mut self,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+        sequence: u32,
+        baseline: Option<u32>,
+        time: f32,
+        deltas: Vec<EntityDelta>,
+    ) {
+        // Late snapshots are useless, since newer state is already known.
+        if self
+            .states
+            .back()
+            .map_or(false, |(latest, _)| sequence <= *latest)
+        {
+            return;
+        }
+
+        let empty = FxHashMap::default();
+        let base_state = match baseline {
+            Some(baseline) => {
+                match self
+                    .states
+                    .iter()
+                    .find(|(sequence, _)| *sequence == baseline)
+                {
+                    Some((_, state)) => state,
+                    // The baseline is too old, wait for next snapshot.
+                    None => return,
+                }
+            }
+            None => &empty,
+        };
+
+        let mut state = FxHashMap::default();
+        for delta in deltas {
+            if let (false, Some(spawn)) = (self.entities.contains_key(&delta.id), delta.spawn) {
+                let mut entity = RemoteEntity {
+                    node: Handle::NONE,
+                    source: spawn.source.clone(),
+                    buffers: vec![Default::default(); spawn.properties.len()],
+                    properties: spawn.properties,
+                    prefab: None,
+                };
+                match spawn.source {
+                    SpawnSource::Existing(node) => {
+                        if scene.graph.is_valid_handle(node) {
+                            entity.node = node;
+                            self.events
+                                .push_back(ClientEvent::EntitySpawned { id: delta.id, node });
+                        } else {
+                            Log::warn(format!(
+                                "Entity {} refers to node {node}, that does not exist!",
+                                delta.id
+                            ));
+                        }
+                    }
+                    SpawnSource::Prefab(path) => {
+                        entity.prefab = Some(resource_manager.request::<Model, _>(path));
+                    }
+                }
+                self.entities.insert(delta.id, entity);
+            }
+
+            let entity = match self.entities.get_mut(&delta.id) {
+                Some(entity) => entity,
+                None => continue,
+            };
+
+            let mut values = base_state
+                .get(&delta.id)
+                .cloned()
+                .unwrap_or_else(|| vec![None; entity.properties.len()]);
+            for (index, value) in delta.properties {
+                if let Some(slot) = values.get_mut(index as usize) {
+                    *slot = Some(value);
+                }
+            }
+
+            for (buffer, value) in entity.buffers.iter_mut().zip(values.iter()) {
+                if let Some(value) = value {
+                    buffer.push(time, value.clone());
+                }
+            }
+
+            state.insert(delta.id, values);
+        }
+
+        let despawned = self
+            .entities
+            .keys()
+            .filter(|id| !state.contains_key(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in despawned {
+            let entity = self.entities.remove(&id).unwrap();
+            if let SpawnSource::Prefab(_) = entity.source {
+                if scene.graph.is_valid_handle(entity.node) {
+                    scene.graph.remove_node(entity.node);
+                }
+            }
+            if entity.node.is_some() {
+                self.events.push_back(ClientEvent::EntityDespawned {
+                    id,
+                    node: entity.node,
+                });
+            }
+        }
+
+        self.states.push_back((sequence, state));
+        if self.states.len() > MAX_HISTORY {
+            self.states.pop_front();
+        }
+
+        // Local clock must not go backwards, but it must catch up with the server.
+        self.server_time = Some(self.server_time.map_or(time, |t| t.max(time)));
+        self.needs_reconcile = true;
+    }
+
+    fn handle_reliable(&mut self, scene: &mut Scene, reliable: Vec<ReliableMessage>) {
+        for message in self.channel.receive(reliable) {
+            match message.target {
+                Some(target) => match self.entities.get(&target) {
+                    Some(entity) => deliver_rpc(
+                        &mut scene.graph,
+                        entity.node,
+                        ReceivedRpc {
+                            sender: None,
+                            rpc: message.rpc,
+                        },
+                    ),
+                    None => Log::warn(format!(
+                        "Remote procedure call {} was dropped, because entity {target} does not exist!",
+                        message.rpc.name
+                    )),
+                },
+                None => self.events.push_back(ClientEvent::Rpc(message.rpc)),
+            }
+        }
+    }
+
+    fn spawn_prefabs(&mut self, scene: &mut Scene) {
+        for (id, entity) in self.entities.iter_mut() {
+            if let Some(prefab) = entity.prefab.as_ref() {
+                if prefab.is_ok() {
+                    entity.node = prefab.instantiate(scene);
+                    entity.prefab = None;
+                    self.events.push_back(ClientEvent::EntitySpawned {
+                        id: *id,
+                        node: entity.node,
+                    });
+                } else if prefab.is_failed_to_load() {
+                    Log::err(format!(
+                        "Unable to spawn entity {id}, because its prefab {} failed to load!",
+                        prefab.path().display()
+                    ));
+                    entity.prefab = None;
+                }
+            }
+        }
+    }
+
+    fn interpolate(&mut self, scene: &mut Scene, dt: f32) {
+        let server_time = match self.server_time.as_mut() {
+            Some(server_time) => {
+                *server_time += dt;
+                *server_time
+            }
+            None => return,
+        };
+        let render_time = server_time - self.interpolation_delay;
+
+        for (id, entity) in self.entities.iter() {
+            if self.predicted.contains(id) {
+                continue;
+            }
+            if let Some(node) = scene.graph.try_get_mut(entity.node) {
+                for (path, buffer) in entity.properties.iter().zip(entity.buffers.iter()) {
+                    if let Some(value) = buffer.sample(render_time) {
+                        write_property(node, path, value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn reconcile(&mut self, scene: &mut Scene) {
+        if !self.needs_reconcile {
+            return;
+        }
+        self.needs_reconcile = false;
+
+        let state = match self.states.back() {
+            Some((_, state)) => state,
+            None => return,
+        };
+
+        for id in self.predicted.iter() {
+            let (entity, values) = match (self.entities.get(id), state.get(id)) {
+                (Some(entity), Some(values)) if scene.graph.is_valid_handle(entity.node) => {
+                    (entity, values)
+                }
+                _ => continue,
+            };
+
+            match self.prediction_handler.as_mut() {
+                Some(handler) => handler.reconcile(PredictionContext {
+                    scene: &mut *scene,
+                    node: entity.node,
+                    id: *id,
+                    properties: &entity.properties,
+                    values,
+                    last_processed_input: self.last_processed_input,
+                    pending_inputs: &self.inputs,
+                }),
+                None => {
+                    let node = &mut scene.graph[entity.node];
+                    for (path, value) in entity.properties.iter().zip(values.iter()) {
+                        if let Some(value) = value {
+                            write_property(node, path, value.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn send(&self, packet: &Packet) {
+        let data = packet.encode();
+        match self.socket.send(&data) {
+            Ok(_) => (),
+            // The packet is lost, like any other UDP packet.
+            Err(ref e)
+                if e.kind() == ErrorKind::WouldBlock
+                    || e.kind() == ErrorKind::ConnectionRefused => {}
+            Err(e) => Log::err(format!("Unable to send a packet. Reason: {e}")),
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if let ConnectionState::Connected(_) = self.state {
+            self.send(&Packet::Disconnect);
+        }
+    }
+}
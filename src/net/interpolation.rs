@@ -0,0 +1,82 @@
+//! Interpolation buffer smooths out values that come from the network at irregular intervals. See
+//! [`InterpolationBuffer`] docs for more info.
+
+use crate::net::value::NetValue;
+use std::collections::VecDeque;
+
+/// Interpolation buffer stores a few recent timestamped values of a property and allows you to sample
+/// the value at any point in time between them. Clients render replicated entities slightly in the past
+/// (see [`crate::net::client::Client::set_interpolation_delay`]), so there is almost always a pair of
+/// values to interpolate between, which hides network jitter and low send rate of a server.
+#[derive(Clone, Debug)]
+pub struct InterpolationBuffer {
+    samples: VecDeque<(f32, NetValue)>,
+    capacity: usize,
+}
+
+impl Default for InterpolationBuffer {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl InterpolationBuffer {
+    /// Default amount of samples, that is stored in the buffer.
+    pub const DEFAULT_CAPACITY: usize = 32;
+
+    /// Creates new interpolation buffer, that stores at most `capacity` samples.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: Default::default(),
+            capacity: capacity.max(2),
+        }
+    }
+
+    /// Adds new sample to the buffer. Samples that are older than the most recent one are ignored.
+    pub fn push(&mut self, time: f32, value: NetValue) {
+        if let Some((last_time, last_value)) = self.samples.back_mut() {
+            if time < *last_time {
+                return;
+            } else if time == *last_time {
+                *last_value = value;
+                return;
+            }
+        }
+
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back((time, value));
+    }
+
+    /// Returns the value at the given time. Times outside of the stored range are clamped to the first
+    /// or the last sample. Returns `None` if the buffer is empty.
+    pub fn sample(&self, time: f32) -> Option<NetValue> {
+        let (first_time, first_value) = self.samples.front()?;
+        if time <= *first_time {
+            return Some(first_value.clone());
+        }
+
+        for ((left_time, left), (right_time, right)) in
+            self.samples.iter().zip(self.samples.iter().skip(1))
+        {
+            if time >= *left_time && time <= *right_time {
+                let t = (time - left_time) / (right_time - left_time);
+                return Some(left.interpolate(right, t));
+            }
+        }
+
+        self.samples.back().map(|(_, value)| value.clone())
+    }
+
+    /// Returns the most recent value in the buffer.
+    pub fn latest(&self) -> Option<&NetValue> {
+        self.samples.back().map(|(_, value)| value)
+    }
+
+    /// Removes every sample from the buffer.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
@@ -0,0 +1,521 @@
+//! Optional networking module (requires `networking` feature), that replicates scene nodes from an
+//! authoritative server to its clients over UDP.
+//!
+//! ## Overview
+//!
+//! [`server::Server`] owns the actual game state. You tell the server which nodes should be replicated
+//! and which of their properties (using [`Reflect`](crate::core::reflect::Reflect) paths, see [`property`]
+//! module for common ones) must be sent to clients. Several times per second the server sends a snapshot
+//! of replicated properties to every connected client. Snapshots are delta-compressed - only properties
+//! that have changed since the last snapshot acknowledged by a client are sent.
+//!
+//! [`client::Client`] receives snapshots, creates nodes for replicated entities (either by taking an
+//! existing node of the same scene or by instantiating the same prefab as on the server) and applies
+//! received values to them. Values are smoothed using [`interpolation::InterpolationBuffer`]s, clients
+//! render remote entities slightly in the past to always have a pair of snapshots to interpolate
+//! between. Entities controlled by a local player could be predicted - see
+//! [`client::PredictionHandler`] docs.
+//!
+//! Clients send their input to the server as opaque byte buffers, the server receives them as
+//! [`server::ServerEvent::Input`] events and applies them to the game state. Scripts of replicated
+//! nodes can exchange remote procedure calls using [`rpc::RpcQueue`] component. RPCs and inputs are
+//! delivered reliably.
+//!
+//! Both the server and the client are completely independent of the engine - call their `update`
+//! methods from your plugin (usually in [`crate::plugin::Plugin::update`]). The server does not need a
+//! graphics context, so it could be run using headless [`crate::engine::executor::Executor`]. Nothing
+//! prevents you from running a server and a client (or several clients) in the same process - this is
+//! how "listen" servers are usually done.
+
+use crate::{
+    core::{log::Log, pool::Handle, reflect::prelude::*},
+    net::{
+        rpc::{ReceivedRpc, Rpc, RpcQueue},
+        value::NetValue,
+    },
+    scene::{graph::Graph, node::Node},
+};
+use std::fmt::{Display, Formatter};
+
+pub mod client;
+pub mod interpolation;
+pub mod rpc;
+pub mod server;
+pub mod value;
+
+mod codec;
+mod packet;
+
+/// Paths of the most commonly replicated properties of scene nodes.
+pub mod property {
+    /// Local position of a node.
+    pub const POSITION: &str = "base.local_transform.local_position";
+    /// Local rotation of a node.
+    pub const ROTATION: &str = "base.local_transform.local_rotation";
+    /// Local scale of a node.
+    pub const SCALE: &str = "base.local_transform.local_scale";
+    /// Visibility of a node.
+    pub const VISIBILITY: &str = "base.visibility";
+    /// Name of a node.
+    pub const NAME: &str = "base.name";
+}
+
+/// Unique identifier of a replicated entity, it is the same on the server and on every client (unlike
+/// handles of nodes).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NetworkId(pub u32);
+
+impl Display for NetworkId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Unique identifier of a client, it is assigned by the server when the client connects.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClientId(pub u32);
+
+impl Display for ClientId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "client {}", self.0)
+    }
+}
+
+fn read_property(node: &Node, path: &str) -> Option<NetValue> {
+    let mut result = None;
+    node.as_reflect(&mut |node| {
+        node.resolve_path(path, &mut |field| {
+            if let Ok(field) = field {
+                result = NetValue::from_reflect(field);
+            }
+        })
+    });
+    result
+}
+
+fn write_property(node: &mut Node, path: &str, value: NetValue) {
+    let mut value = Some(value.into_reflect());
+    node.as_reflect_mut(&mut |node| {
+        node.set_field_by_path(path, value.take().unwrap(), &mut |result| {
+            if result.is_err() {
+                Log::err(format!("Unable to set replicated property {path}!"));
+            }
+        })
+    });
+}
+
+fn take_outgoing_rpcs(graph: &mut Graph, node: Handle<Node>) -> Vec<Rpc> {
+    graph
+        .try_get_mut(node)
+        .and_then(|node| node.script_mut())
+        .and_then(|script| script.query_component_mut::<RpcQueue>())
+        .map(|queue| queue.take_outgoing())
+        .unwrap_or_default()
+}
+
+fn deliver_rpc(graph: &mut Graph, node: Handle<Node>, rpc: ReceivedRpc) {
+    match graph
+        .try_get_mut(node)
+        .and_then(|node| node.script_mut())
+        .and_then(|script| script.query_component_mut::<RpcQueue>())
+    {
+        Some(queue) => queue.push_incoming(rpc),
+        None => Log::warn(format!(
+            "Remote procedure call {} was dropped, because node {node} has no RpcQueue component!",
+            rpc.rpc.name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{
+            algebra::Vector3, pool::Handle, reflect::prelude::*, uuid::Uuid, visitor::prelude::*,
+            TypeUuidProvider,
+        },
+        engine::{Engine, EngineInitParams, GraphicsContextParams, SerializationContext},
+        event_loop::ControlFlow,
+        impl_component_provider,
+        net::{
+            client::{Client, ClientEvent, ConnectionState},
+            packet::{EntityDelta, Packet, ReliableMessage, SnapshotAssembler, MAX_PACKET_SIZE},
+            property,
+            rpc::{Rpc, RpcQueue},
+            server::{Server, ServerEvent},
+            value::NetValue,
+            NetworkId,
+        },
+        plugin::{Plugin, PluginConstructor, PluginContext},
+        scene::{base::BaseBuilder, node::Node, pivot::PivotBuilder, Scene},
+        script::{Script, ScriptTrait},
+    };
+    use std::{
+        net::UdpSocket,
+        sync::{
+            mpsc::{self, Sender},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+    use winit::window::WindowAttributes;
+
+    #[derive(Clone, Debug, Default, Reflect, Visit)]
+    struct Player {
+        #[visit(skip)]
+        #[reflect(hidden)]
+        rpc: RpcQueue,
+    }
+
+    impl_component_provider!(Player, rpc: RpcQueue);
+
+    impl TypeUuidProvider for Player {
+        fn type_uuid() -> Uuid {
+            Uuid::default()
+        }
+    }
+
+    impl ScriptTrait for Player {
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    fn player_rpc(scene: &mut Scene, player: Handle<Node>) -> &mut RpcQueue {
+        scene.graph[player]
+            .script_mut()
+            .unwrap()
+            .query_component_mut::<RpcQueue>()
+            .unwrap()
+    }
+
+    #[derive(Debug)]
+    enum TestEvent {
+        Started {
+            server_scene: Handle<Scene>,
+            client_scene: Handle<Scene>,
+            player: Handle<Node>,
+        },
+        Server(ServerEvent),
+        Client(ClientEvent),
+        NodeRpc(Rpc),
+    }
+
+    struct Loopback {
+        server: Server,
+        client: Client,
+        server_scene: Handle<Scene>,
+        client_scene: Handle<Scene>,
+        player: Handle<Node>,
+        sender: Sender<TestEvent>,
+    }
+
+    impl Plugin for Loopback {
+        fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+            // The server moves its player with constant speed, clients must follow it.
+            let server_scene = &mut context.scenes[self.server_scene];
+            let transform = server_scene.graph[self.player].local_transform_mut();
+            let position = **transform.position() + Vector3::new(context.dt, 0.0, 0.0);
+            transform.set_position(position);
+
+            self.server.update(server_scene, context.dt);
+            while let Some(event) = self.server.poll_event() {
+                if let ServerEvent::ClientConnected(_) = event {
+                    player_rpc(server_scene, self.player)
+                        .call(Rpc::new("ping", vec![NetValue::from(42u32)]));
+                }
+                self.sender.send(TestEvent::Server(event)).unwrap();
+            }
+
+            let client_scene = &mut context.scenes[self.client_scene];
+            if let ConnectionState::Connected(_) = self.client.state() {
+                self.client.send_input(vec![1, 2, 3]);
+            }
+            self.client
+                .update(client_scene, context.resource_manager, context.dt);
+            for received in player_rpc(client_scene, self.player).drain_incoming() {
+                self.sender.send(TestEvent::NodeRpc(received.rpc)).unwrap();
+            }
+            while let Some(event) = self.client.poll_event() {
+                if let ClientEvent::Connected(_) = event {
+                    self.client.send_rpc(Rpc::new("hello", vec![]));
+                }
+                self.sender.send(TestEvent::Client(event)).unwrap();
+            }
+        }
+    }
+
+    struct LoopbackConstructor {
+        sender: Mutex<Sender<TestEvent>>,
+    }
+
+    impl PluginConstructor for LoopbackConstructor {
+        fn create_instance(
+            &self,
+            _override_scene: Handle<Scene>,
+            context: PluginContext,
+        ) -> Box<dyn Plugin> {
+            // Both scenes are built the same way, so the player has the same handle in both of them.
+            let mut make_scene = || {
+                let mut scene = Scene::new();
+                let player = PivotBuilder::new(
+                    BaseBuilder::new().with_script(Script::new(Player::default())),
+                )
+                .build(&mut scene.graph);
+                (context.scenes.add(scene), player)
+            };
+            let (server_scene, player) = make_scene();
+            let (client_scene, client_player) = make_scene();
+            assert_eq!(player, client_player);
+
+            let mut server = Server::bind("127.0.0.1:0").unwrap();
+            server.replicate(player, &[property::POSITION, property::VISIBILITY]);
+            let client = Client::connect(server.local_addr().unwrap()).unwrap();
+
+            let sender = self.sender.lock().unwrap().clone();
+            sender
+                .send(TestEvent::Started {
+                    server_scene,
+                    client_scene,
+                    player,
+                })
+                .unwrap();
+
+            Box::new(Loopback {
+                server,
+                client,
+                server_scene,
+                client_scene,
+                player,
+                sender,
+            })
+        }
+    }
+
+    #[test]
+    fn test_loopback_replication() {
+        let mut engine = Engine::new(EngineInitParams {
+            graphics_context_params: GraphicsContextParams {
+                window_attributes: WindowAttributes::default(),
+                vsync: false,
+            },
+            resource_manager: ResourceManager::new(),
+            serialization_context: Arc::new(SerializationContext::new()),
+        })
+        .unwrap();
+        engine.set_headless(true);
+
+        let (sender, receiver) = mpsc::channel();
+        engine.add_plugin_constructor(LoopbackConstructor {
+            sender: Mutex::new(sender),
+        });
+        engine.enable_plugins(Handle::NONE, true);
+
+        let dt = 1.0 / 60.0;
+        let mut control_flow = ControlFlow::Poll;
+        for _ in 0..120 {
+            engine.update(dt, &mut control_flow, &mut 0.0, Default::default());
+        }
+
+        let events = receiver.try_iter().collect::<Vec<_>>();
+
+        let (server_scene, client_scene, player) = match events[0] {
+            TestEvent::Started {
+                server_scene,
+                client_scene,
+                player,
+            } => (server_scene, client_scene, player),
+            _ => unreachable!(),
+        };
+
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, TestEvent::Server(ServerEvent::ClientConnected(_)))));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, TestEvent::Client(ClientEvent::Connected(_)))));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            TestEvent::Client(ClientEvent::EntitySpawned { node, .. }) if *node == player
+        )));
+
+        // Every input must be delivered exactly once and in order.
+        let inputs = events
+            .iter()
+            .filter_map(|e| match e {
+                TestEvent::Server(ServerEvent::Input { sequence, data, .. }) => {
+                    assert_eq!(data, &[1, 2, 3]);
+                    Some(*sequence)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(!inputs.is_empty());
+        assert!(inputs.windows(2).all(|w| w[1] == w[0] + 1));
+
+        // RPCs must be delivered exactly once.
+        let node_rpcs = events
+            .iter()
+            .filter_map(|e| match e {
+                TestEvent::NodeRpc(rpc) => Some(rpc.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            node_rpcs,
+            vec![Rpc::new("ping", vec![NetValue::from(42u32)])]
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, TestEvent::Server(ServerEvent::Rpc { rpc, .. }) if rpc.name == "hello"))
+                .count(),
+            1
+        );
+
+        // Client renders the player slightly in the past, but it must follow the server.
+        let server_x = engine.scenes[server_scene].graph[player]
+            .local_transform()
+            .position()
+            .x;
+        let client_x = engine.scenes[client_scene].graph[player]
+            .local_transform()
+            .position()
+            .x;
+        assert!((server_x - 2.0).abs() < 0.01);
+        assert!(client_x > 1.0 && client_x < server_x);
+    }
+
+    // Sends the packet to the server and returns the first reply, that was received while the server
+    // was updated.
+    fn exchange(
+        server: &mut Server,
+        scene: &mut Scene,
+        socket: &UdpSocket,
+        packet: Packet,
+    ) -> Option<Packet> {
+        socket
+            .send_to(&packet.encode(), server.local_addr().unwrap())
+            .unwrap();
+        let mut buffer = [0; 64];
+        for _ in 0..50 {
+            server.update(scene, 0.0);
+            if let Ok((size, _)) = socket.recv_from(&mut buffer) {
+                return Packet::decode(&buffer[..size]);
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        None
+    }
+
+    #[test]
+    fn test_connection_challenge() {
+        let mut scene = Scene::new();
+        let mut server = Server::bind("127.0.0.1:0").unwrap();
+        server.set_max_connections(1);
+
+        let first = UdpSocket::bind("127.0.0.1:0").unwrap();
+        first.set_nonblocking(true).unwrap();
+
+        // Connection request must not create a connection until the token is echoed back.
+        let token = match exchange(&mut server, &mut scene, &first, Packet::Connect) {
+            Some(Packet::Challenge { token }) => token,
+            packet => panic!("Expected a challenge, got {packet:?}"),
+        };
+        assert_eq!(server.clients().count(), 0);
+
+        // Wrong token is ignored.
+        let wrong = Packet::ChallengeResponse {
+            token: token.wrapping_add(1),
+        };
+        assert_eq!(exchange(&mut server, &mut scene, &first, wrong), None);
+        assert_eq!(server.clients().count(), 0);
+
+        let response = Packet::ChallengeResponse { token };
+        assert!(matches!(
+            exchange(&mut server, &mut scene, &first, response.clone()),
+            Some(Packet::Accepted { .. })
+        ));
+        assert_eq!(server.clients().count(), 1);
+        assert!(matches!(
+            server.poll_event(),
+            Some(ServerEvent::ClientConnected(_))
+        ));
+
+        // The token is bound to the address it was sent to.
+        let second = UdpSocket::bind("127.0.0.1:0").unwrap();
+        second.set_nonblocking(true).unwrap();
+        assert_eq!(exchange(&mut server, &mut scene, &second, response), None);
+
+        // The server is full, so new clients don't receive challenges.
+        assert_eq!(
+            exchange(&mut server, &mut scene, &second, Packet::Connect),
+            None
+        );
+        assert_eq!(server.clients().count(), 1);
+    }
+
+    #[test]
+    fn test_large_snapshot_split() {
+        let reliable = vec![ReliableMessage {
+            sequence: 1,
+            target: None,
+            rpc: Rpc::new("ping", vec![]),
+        }];
+        let entities = (0..100)
+            .map(|i| EntityDelta {
+                id: NetworkId(i),
+                spawn: None,
+                properties: vec![(0, NetValue::from("x".repeat(2000)))],
+            })
+            .collect::<Vec<_>>();
+        let snapshot = Packet::Snapshot {
+            sequence: 5,
+            part: 0,
+            part_count: 1,
+            baseline: None,
+            time: 1.0,
+            last_input: 0,
+            acked_reliable: 0,
+            reliable: reliable.clone(),
+            entities: entities.clone(),
+        };
+        assert!(snapshot.encode().len() > MAX_PACKET_SIZE);
+
+        let parts = snapshot.split();
+        assert!(parts.len() > 1);
+        for part in parts.iter() {
+            let data = part.encode();
+            assert!(data.len() <= MAX_PACKET_SIZE);
+            assert_eq!(Packet::decode(&data).as_ref(), Some(part));
+        }
+
+        // Parts could arrive in any order and could be duplicated.
+        let mut assembler = SnapshotAssembler::default();
+        let mut assembled = None;
+        for part in parts.iter().rev().chain(parts.first()) {
+            if let Packet::Snapshot {
+                sequence,
+                part,
+                part_count,
+                reliable,
+                entities,
+                ..
+            } = part.clone()
+            {
+                if let Some(snapshot) =
+                    assembler.add(sequence, part, part_count, reliable, entities)
+                {
+                    assert!(assembled.is_none());
+                    assembled = Some(snapshot);
+                }
+            }
+        }
+
+        let (assembled_reliable, mut assembled_entities) = assembled.unwrap();
+        assembled_entities.sort_by_key(|entity| entity.id.0);
+        assert_eq!(assembled_reliable, reliable);
+        assert_eq!(assembled_entities, entities);
+    }
+}
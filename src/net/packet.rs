@@ -0,0 +1,462 @@
+//! Packets that are exchanged between a server and its clients.
+
+use crate::{
+    core::pool::Handle,
+    net::{
+        codec::{Reader, Writer},
+        rpc::Rpc,
+        value::NetValue,
+        ClientId, NetworkId,
+    },
+    scene::node::Node,
+};
+use std::path::PathBuf;
+
+/// Every packet starts with these bytes, other datagrams are ignored.
+const MAGIC: [u8; 4] = *b"FYRN";
+
+/// Max amount of bytes in a single datagram.
+pub(crate) const MAX_PACKET_SIZE: usize = 65507;
+
+/// Connect packets are padded to the size of a challenge, so the server never replies to an unverified
+/// (possibly spoofed) address with more data than it has received from it.
+const CONNECT_PADDING: usize = 8;
+
+/// Describes where a client should take a node for a replicated entity.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SpawnSource {
+    /// The node is a part of the scene, that is loaded on both sides.
+    Existing(Handle<Node>),
+    /// The node is an instance of a prefab.
+    Prefab(PathBuf),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SpawnInfo {
+    pub source: SpawnSource,
+    pub properties: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EntityDelta {
+    pub id: NetworkId,
+    /// Spawn info is sent only if the client does not know about the entity yet.
+    pub spawn: Option<SpawnInfo>,
+    /// Indices of changed properties with their new values.
+    pub properties: Vec<(u16, NetValue)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ReliableMessage {
+    pub sequence: u32,
+    pub target: Option<NetworkId>,
+    pub rpc: Rpc,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Packet {
+    Connect,
+    Disconnect,
+    /// The server replies to [`Packet::Connect`] with a token, that must be sent back by the client
+    /// to prove that it owns its address.
+    Challenge {
+        token: u64,
+    },
+    ChallengeResponse {
+        token: u64,
+    },
+    ClientUpdate {
+        acked_snapshot: Option<u32>,
+        acked_reliable: u32,
+        reliable: Vec<ReliableMessage>,
+        inputs: Vec<(u32, Vec<u8>)>,
+    },
+    Accepted {
+        client: ClientId,
+    },
+    /// Snapshots, that don't fit in a single datagram, are split into several parts (see [`Packet::split`]).
+    Snapshot {
+        sequence: u32,
+        part: u16,
+        part_count: u16,
+        baseline: Option<u32>,
+        time: f32,
+        last_input: u32,
+        acked_reliable: u32,
+        reliable: Vec<ReliableMessage>,
+        entities: Vec<EntityDelta>,
+    },
+}
+
+fn write_opt_u32(writer: &mut Writer, value: Option<u32>) {
+    writer.bool(value.is_some());
+    if let Some(value) = value {
+        writer.u32(value);
+    }
+}
+
+fn read_opt_u32(reader: &mut Reader) -> Option<Option<u32>> {
+    Some(if reader.bool()? {
+        Some(reader.u32()?)
+    } else {
+        None
+    })
+}
+
+fn write_reliable(writer: &mut Writer, messages: &[ReliableMessage]) {
+    writer.u16(messages.len() as u16);
+    for message in messages {
+        writer.u32(message.sequence);
+        write_opt_u32(writer, message.target.map(|id| id.0));
+        writer.string(&message.rpc.name);
+        writer.u16(message.rpc.args.len() as u16);
+        for arg in message.rpc.args.iter() {
+            arg.write(writer);
+        }
+    }
+}
+
+fn read_reliable(reader: &mut Reader) -> Option<Vec<ReliableMessage>> {
+    let count = reader.u16()?;
+    let mut messages = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let sequence = reader.u32()?;
+        let target = read_opt_u32(reader)?.map(NetworkId);
+        let name = reader.string()?;
+        let arg_count = reader.u16()?;
+        let mut args = Vec::with_capacity(arg_count as usize);
+        for _ in 0..arg_count {
+            args.push(NetValue::read(reader)?);
+        }
+        messages.push(ReliableMessage {
+            sequence,
+            target,
+            rpc: Rpc { name, args },
+        });
+    }
+    Some(messages)
+}
+
+fn write_entity(writer: &mut Writer, entity: &EntityDelta) {
+    writer.u32(entity.id.0);
+    writer.bool(entity.spawn.is_some());
+    if let Some(spawn) = entity.spawn.as_ref() {
+        match spawn.source {
+            SpawnSource::Existing(handle) => {
+                writer.u8(0);
+                writer.u32(handle.index());
+                writer.u32(handle.generation());
+            }
+            SpawnSource::Prefab(ref path) => {
+                writer.u8(1);
+                writer.string(&path.to_string_lossy());
+            }
+        }
+        writer.u16(spawn.properties.len() as u16);
+        for property in spawn.properties.iter() {
+            writer.string(property);
+        }
+    }
+    writer.u16(entity.properties.len() as u16);
+    for (index, value) in entity.properties.iter() {
+        writer.u16(*index);
+        value.write(writer);
+    }
+}
+
+fn read_entity(reader: &mut Reader) -> Option<EntityDelta> {
+    let id = NetworkId(reader.u32()?);
+    let spawn = if reader.bool()? {
+        let source = match reader.u8()? {
+            0 => SpawnSource::Existing(Handle::new(reader.u32()?, reader.u32()?)),
+            1 => SpawnSource::Prefab(reader.string()?.into()),
+            _ => return None,
+        };
+        let count = reader.u16()?;
+        let mut properties = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            properties.push(reader.string()?);
+        }
+        Some(SpawnInfo { source, properties })
+    } else {
+        None
+    };
+    let count = reader.u16()?;
+    let mut properties = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        properties.push((reader.u16()?, NetValue::read(reader)?));
+    }
+    Some(EntityDelta {
+        id,
+        spawn,
+        properties,
+    })
+}
+
+impl Packet {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.bytes(&MAGIC);
+        match self {
+            Packet::Connect => {
+                writer.u8(0);
+                writer.bytes(&[0; CONNECT_PADDING]);
+            }
+            Packet::Disconnect => writer.u8(1),
+            Packet::ClientUpdate {
+                acked_snapshot,
+                acked_reliable,
+                reliable,
+                inputs,
+            } => {
+                writer.u8(2);
+                write_opt_u32(&mut writer, *acked_snapshot);
+                writer.u32(*acked_reliable);
+                write_reliable(&mut writer, reliable);
+                writer.u16(inputs.len() as u16);
+                for (sequence, data) in inputs {
+                    writer.u32(*sequence);
+                    writer.u32(data.len() as u32);
+                    writer.bytes(data);
+                }
+            }
+            Packet::Accepted { client } => {
+                writer.u8(3);
+                writer.u32(client.0);
+            }
+            Packet::Snapshot {
+                sequence,
+                part,
+                part_count,
+                baseline,
+                time,
+                last_input,
+                acked_reliable,
+                reliable,
+                entities,
+            } => {
+                writer.u8(4);
+                writer.u32(*sequence);
+                writer.u16(*part);
+                writer.u16(*part_count);
+                write_opt_u32(&mut writer, *baseline);
+                writer.f32(*time);
+                writer.u32(*last_input);
+                writer.u32(*acked_reliable);
+                write_reliable(&mut writer, reliable);
+                writer.u32(entities.len() as u32);
+                for entity in entities {
+                    write_entity(&mut writer, entity);
+                }
+            }
+            Packet::Challenge { token } => {
+                writer.u8(5);
+                writer.u64(*token);
+            }
+            Packet::ChallengeResponse { token } => {
+                writer.u8(6);
+                writer.u64(*token);
+            }
+        }
+        writer.data
+    }
+
+    pub(crate) fn decode(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader::new(data);
+        if reader.array::<4>()? != MAGIC {
+            return None;
+        }
+        Some(match reader.u8()? {
+            0 => {
+                reader.take(CONNECT_PADDING)?;
+                Packet::Connect
+            }
+            1 => Packet::Disconnect,
+            2 => {
+                let acked_snapshot = read_opt_u32(&mut reader)?;
+                let acked_reliable = reader.u32()?;
+                let reliable = read_reliable(&mut reader)?;
+                let count = reader.u16()?;
+                let mut inputs = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let sequence = reader.u32()?;
+                    let len = reader.u32()? as usize;
+                    inputs.push((sequence, reader.take(len)?.to_vec()));
+                }
+                Packet::ClientUpdate {
+                    acked_snapshot,
+                    acked_reliable,
+                    reliable,
+                    inputs,
+                }
+            }
+            3 => Packet::Accepted {
+                client: ClientId(reader.u32()?),
+            },
+            4 => {
+                let sequence = reader.u32()?;
+                let part = reader.u16()?;
+                let part_count = reader.u16()?;
+                let baseline = read_opt_u32(&mut reader)?;
+                let time = reader.f32()?;
+                let last_input = reader.u32()?;
+                let acked_reliable = reader.u32()?;
+                let reliable = read_reliable(&mut reader)?;
+                let count = reader.u32()?;
+                let mut entities = Vec::new();
+                for _ in 0..count {
+                    entities.push(read_entity(&mut reader)?);
+                }
+                Packet::Snapshot {
+                    sequence,
+                    part,
+                    part_count,
+                    baseline,
+                    time,
+                    last_input,
+                    acked_reliable,
+                    reliable,
+                    entities,
+                }
+            }
+            5 => Packet::Challenge {
+                token: reader.u64()?,
+            },
+            6 => Packet::ChallengeResponse {
+                token: reader.u64()?,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Splits a snapshot into several parts, so every part fits in a single datagram. Entity deltas are
+    /// distributed between the parts, reliable messages are sent only with the first part. Other packets
+    /// are returned as is.
+    pub(crate) fn split(self) -> Vec<Packet> {
+        match self {
+            Packet::Snapshot {
+                sequence,
+                baseline,
+                time,
+                last_input,
+                acked_reliable,
+                reliable,
+                entities,
+                ..
+            } => {
+                let make_part =
+                    |part: u16,
+                     part_count: u16,
+                     reliable: Vec<ReliableMessage>,
+                     entities: Vec<EntityDelta>| Packet::Snapshot {
+                        sequence,
+                        part,
+                        part_count,
+                        baseline,
+                        time,
+                        last_input,
+                        acked_reliable,
+                        reliable,
+                        entities,
+                    };
+
+                let first_header_size =
+                    make_part(0, 0, reliable.clone(), Vec::new()).encode().len();
+                let header_size = make_part(0, 0, Vec::new(), Vec::new()).encode().len();
+
+                let mut parts = Vec::new();
+                let mut current = Vec::new();
+                let mut size = first_header_size;
+                for entity in entities {
+                    let mut writer = Writer::default();
+                    write_entity(&mut writer, &entity);
+                    let entity_size = writer.data.len();
+
+                    // An entity, that does not fit in a datagram by itself, is still sent in its own part,
+                    // such part will be rejected with an error message by the sender.
+                    if size + entity_size > MAX_PACKET_SIZE && !current.is_empty() {
+                        parts.push(std::mem::take(&mut current));
+                        size = header_size;
+                    }
+
+                    size += entity_size;
+                    current.push(entity);
+                }
+                parts.push(current);
+
+                let part_count = parts.len() as u16;
+                let mut reliable = Some(reliable);
+                parts
+                    .into_iter()
+                    .enumerate()
+                    .map(|(part, entities)| {
+                        make_part(
+                            part as u16,
+                            part_count,
+                            reliable.take().unwrap_or_default(),
+                            entities,
+                        )
+                    })
+                    .collect()
+            }
+            packet => vec![packet],
+        }
+    }
+}
+
+/// Collects parts of split snapshots (see [`Packet::split`]). Only the latest snapshot is assembled, parts
+/// of older snapshots are discarded.
+#[derive(Default)]
+pub(crate) struct SnapshotAssembler {
+    sequence: u32,
+    received: Vec<bool>,
+    reliable: Vec<ReliableMessage>,
+    entities: Vec<EntityDelta>,
+}
+
+impl SnapshotAssembler {
+    /// Adds a part of a snapshot. Returns reliable messages and entity deltas of the whole snapshot, when
+    /// every part of it was received.
+    pub(crate) fn add(
+        &mut self,
+        sequence: u32,
+        part: u16,
+        part_count: u16,
+        reliable: Vec<ReliableMessage>,
+        entities: Vec<EntityDelta>,
+    ) -> Option<(Vec<ReliableMessage>, Vec<EntityDelta>)> {
+        if part_count <= 1 {
+            return Some((reliable, entities));
+        }
+
+        if part >= part_count || (!self.received.is_empty() && sequence < self.sequence) {
+            return None;
+        }
+
+        if sequence != self.sequence || self.received.len() != part_count as usize {
+            self.sequence = sequence;
+            self.received = vec![false; part_count as usize];
+            self.reliable.clear();
+            self.entities.clear();
+        }
+
+        // Duplicated datagram.
+        if self.received[part as usize] {
+            return None;
+        }
+
+        self.received[part as usize] = true;
+        self.reliable.extend(reliable);
+        self.entities.extend(entities);
+
+        if self.received.iter().all(|received| *received) {
+            self.received.clear();
+            Some((
+                std::mem::take(&mut self.reliable),
+                std::mem::take(&mut self.entities),
+            ))
+        } else {
+            None
+        }
+    }
+}
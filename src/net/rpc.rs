@@ -0,0 +1,180 @@
+//! Remote procedure calls allow scripts of replicated nodes to exchange messages between a server and
+//! its clients. See [`RpcQueue`] docs for more info.
+
+use crate::net::{packet::ReliableMessage, value::NetValue, ClientId, NetworkId};
+use std::collections::VecDeque;
+
+/// Remote procedure call - a named message with a set of arguments. It is up to the receiver to decide
+/// what to do with the call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rpc {
+    /// Name of the procedure.
+    pub name: String,
+    /// Arguments of the procedure.
+    pub args: Vec<NetValue>,
+}
+
+impl Rpc {
+    /// Creates new remote procedure call.
+    pub fn new<S: Into<String>>(name: S, args: Vec<NetValue>) -> Self {
+        Self {
+            name: name.into(),
+            args,
+        }
+    }
+}
+
+/// Remote procedure call, that was received from the other side of a connection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceivedRpc {
+    /// A client that has sent the call. `None` means that the call was sent by the server.
+    pub sender: Option<ClientId>,
+    /// Actual call.
+    pub rpc: Rpc,
+}
+
+/// A queue of remote procedure calls of a script. Add the queue to your script and expose it as a
+/// component, so the [`crate::net::server::Server`] and the [`crate::net::client::Client`] will be able
+/// to find it. Calls are delivered reliably and in order to the same node on the other side of the
+/// connection. A call made on the server is sent to every client, a call made on a client is sent to
+/// the server only.
+///
+/// ```rust
+/// use fyrox::{
+///     core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider},
+///     impl_component_provider,
+///     net::rpc::{Rpc, RpcQueue},
+///     script::{ScriptContext, ScriptTrait},
+/// };
+///
+/// #[derive(Clone, Debug, Default, Reflect, Visit)]
+/// struct Door {
+///     open: bool,
+///     #[visit(skip)]
+///     #[reflect(hidden)]
+///     rpc: RpcQueue,
+/// }
+///
+/// impl_component_provider!(Door, rpc: RpcQueue);
+///
+/// impl TypeUuidProvider for Door {
+///     fn type_uuid() -> Uuid {
+///         Uuid::default()
+///     }
+/// }
+///
+/// impl ScriptTrait for Door {
+///     fn on_update(&mut self, _context: &mut ScriptContext) {
+///         for call in self.rpc.drain_incoming() {
+///             if call.rpc.name == "open" {
+///                 self.open = true;
+///             }
+///         }
+///         if !self.open {
+///             self.rpc.call(Rpc::new("knock", vec![]));
+///         }
+///     }
+///
+///     fn id(&self) -> Uuid {
+///         Self::type_uuid()
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RpcQueue {
+    outgoing: Vec<Rpc>,
+    incoming: Vec<ReceivedRpc>,
+}
+
+impl RpcQueue {
+    /// Schedules the call to be sent to the other side of the connection.
+    pub fn call(&mut self, rpc: Rpc) {
+        self.outgoing.push(rpc);
+    }
+
+    /// Returns an iterator over the received calls, the calls are removed from the queue.
+    pub fn drain_incoming(&mut self) -> impl Iterator<Item = ReceivedRpc> + '_ {
+        self.incoming.drain(..)
+    }
+
+    /// Returns `true` if there are received calls in the queue.
+    pub fn has_incoming(&self) -> bool {
+        !self.incoming.is_empty()
+    }
+
+    pub(crate) fn take_outgoing(&mut self) -> Vec<Rpc> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    pub(crate) fn push_incoming(&mut self, rpc: ReceivedRpc) {
+        self.incoming.push(rpc);
+    }
+}
+
+/// Max amount of reliable messages in a single packet, the rest is sent when these are acknowledged.
+const MAX_MESSAGES_PER_PACKET: usize = 64;
+
+/// Reliable in-order channel. Every packet carries all the messages that were not acknowledged yet by the
+/// other side, the receiver accepts messages strictly in order of their sequence numbers. This approach is
+/// quite wasteful in terms of bandwidth, but it is very simple and RPCs are usually rare.
+#[derive(Debug)]
+pub(crate) struct ReliableChannel {
+    next_sequence: u32,
+    outgoing: VecDeque<ReliableMessage>,
+    received: u32,
+}
+
+impl Default for ReliableChannel {
+    fn default() -> Self {
+        Self {
+            next_sequence: 1,
+            outgoing: Default::default(),
+            received: 0,
+        }
+    }
+}
+
+impl ReliableChannel {
+    pub(crate) fn send(&mut self, target: Option<NetworkId>, rpc: Rpc) {
+        self.outgoing.push_back(ReliableMessage {
+            sequence: self.next_sequence,
+            target,
+            rpc,
+        });
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+    }
+
+    pub(crate) fn pending(&self) -> Vec<ReliableMessage> {
+        self.outgoing
+            .iter()
+            .take(MAX_MESSAGES_PER_PACKET)
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn acknowledge(&mut self, acked: u32) {
+        while self
+            .outgoing
+            .front()
+            .map_or(false, |message| message.sequence <= acked)
+        {
+            self.outgoing.pop_front();
+        }
+    }
+
+    /// Returns messages that were not received yet, in order.
+    pub(crate) fn receive(&mut self, messages: Vec<ReliableMessage>) -> Vec<ReliableMessage> {
+        let mut result = Vec::new();
+        for message in messages {
+            if message.sequence == self.received.wrapping_add(1) {
+                self.received = message.sequence;
+                result.push(message);
+            }
+        }
+        result
+    }
+
+    pub(crate) fn received(&self) -> u32 {
+        self.received
+    }
+}
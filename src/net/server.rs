@@ -0,0 +1,622 @@
+//! Authoritative server, that replicates scene nodes to its clients. See [`Server`] docs for more info.
+
+use crate::{
+    core::{log::Log, pool::Handle},
+    fxhash::FxHashMap,
+    net::{
+        deliver_rpc,
+        packet::{EntityDelta, Packet, SpawnInfo, SpawnSource, MAX_PACKET_SIZE},
+        read_property,
+        rpc::{ReceivedRpc, ReliableChannel, Rpc},
+        take_outgoing_rpcs,
+        value::NetValue,
+        ClientId, NetworkId,
+    },
+    scene::{node::Node, Scene},
+};
+use std::{
+    collections::{hash_map::RandomState, VecDeque},
+    hash::{BuildHasher, Hash, Hasher},
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+/// Max amount of snapshots that is stored for every client. If a client has not acknowledged any of
+/// them, it will receive full state of the scene.
+const MAX_HISTORY: usize = 64;
+
+/// Amount of seconds after which a challenge token is replaced by a new one. Tokens of the previous
+/// period are still accepted, so every token is valid for at least this amount of time.
+const CHALLENGE_LIFETIME: f32 = 10.0;
+
+/// An event that happened on the server.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerEvent {
+    /// New client has connected.
+    ClientConnected(ClientId),
+    /// A client has disconnected or has timed out.
+    ClientDisconnected(ClientId),
+    /// A client has sent its input. Every input is delivered exactly once and in order.
+    Input {
+        /// A client that has sent the input.
+        client: ClientId,
+        /// Sequence number of the input, it is used by clients for reconciliation.
+        sequence: u32,
+        /// Input data, it is up to the game to decide how to interpret it.
+        data: Vec<u8>,
+    },
+    /// A client has sent a remote procedure call, that is not bound to any entity.
+    Rpc {
+        /// A client that has sent the call.
+        client: ClientId,
+        /// Actual call.
+        rpc: Rpc,
+    },
+}
+
+struct ReplicatedEntity {
+    node: Handle<Node>,
+    source: SpawnSource,
+    properties: Vec<String>,
+}
+
+type EntityState = Vec<Option<NetValue>>;
+
+struct Snapshot {
+    sequence: u32,
+    entities: FxHashMap<NetworkId, EntityState>,
+}
+
+struct Connection {
+    id: ClientId,
+    addr: SocketAddr,
+    idle_time: f32,
+    acked_snapshot: Option<u32>,
+    history: VecDeque<Snapshot>,
+    channel: ReliableChannel,
+    last_input: u32,
+}
+
+impl Connection {
+    fn acknowledge_snapshot(&mut self, sequence: u32) {
+        if self.acked_snapshot.map_or(true, |acked| sequence > acked) {
+            self.acked_snapshot = Some(sequence);
+            // Snapshots older than the baseline won't be needed anymore.
+            while self
+                .history
+                .front()
+                .map_or(false, |snapshot| snapshot.sequence < sequence)
+            {
+                self.history.pop_front();
+            }
+        }
+    }
+
+    fn baseline(&self) -> Option<&Snapshot> {
+        let acked = self.acked_snapshot?;
+        self.history
+            .iter()
+            .find(|snapshot| snapshot.sequence == acked)
+    }
+}
+
+/// Authoritative server, that replicates selected nodes of a scene and their properties to every
+/// connected client. See [module docs](crate::net) for an overview.
+///
+/// A client is not considered connected until it echoes a challenge token, that the server sends in
+/// response to its connection request. The token is derived from the address of the client, so the
+/// server never creates connections (and never sends snapshots) to addresses that were spoofed. The
+/// amount of connections is limited by [`Self::set_max_connections`].
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     core::pool::Handle,
+///     net::{
+///         property,
+///         server::{Server, ServerEvent},
+///     },
+///     scene::{node::Node, Scene},
+/// };
+///
+/// fn run_server(scene: &mut Scene, player: Handle<Node>) {
+///     let mut server = Server::bind("0.0.0.0:7777").unwrap();
+///     server.replicate(player, &[property::POSITION, property::ROTATION]);
+///
+///     // Call this every frame.
+///     server.update(scene, 1.0 / 60.0);
+///     while let Some(event) = server.poll_event() {
+///         if let ServerEvent::Input { data, .. } = event {
+///             // Apply the input to the player.
+///         }
+///     }
+/// }
+/// ```
+pub struct Server {
+    socket: UdpSocket,
+    receive_buffer: Vec<u8>,
+    token_keys: RandomState,
+    max_connections: usize,
+    entities: FxHashMap<NetworkId, ReplicatedEntity>,
+    next_network_id: u32,
+    connections: Vec<Connection>,
+    next_client_id: u32,
+    events: VecDeque<ServerEvent>,
+    time: f32,
+    send_rate: f32,
+    send_timer: f32,
+    timeout: f32,
+    snapshot_sequence: u32,
+}
+
+impl Server {
+    /// Default amount of snapshots per second.
+    pub const DEFAULT_SEND_RATE: f32 = 20.0;
+
+    /// Default amount of seconds without any packets from a client, after which the client is
+    /// disconnected.
+    pub const DEFAULT_TIMEOUT: f32 = 5.0;
+
+    /// Default max amount of connected clients.
+    pub const DEFAULT_MAX_CONNECTIONS: usize = 32;
+
+    /// Creates new server that listens on the given address. Use `0` port to let the OS to select a free
+    /// port, the actual address could be fetched using [`Self::local_addr`].
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            receive_buffer: vec![0; MAX_PACKET_SIZE],
+            token_keys: RandomState::new(),
+            max_connections: Self::DEFAULT_MAX_CONNECTIONS,
+            entities: Default::default(),
+            next_network_id: 1,
+            connections: Default::default(),
+            next_client_id: 1,
+            events: Default::default(),
+            time: 0.0,
+            send_rate: Self::DEFAULT_SEND_RATE,
+            send_timer: 0.0,
+            timeout: Self::DEFAULT_TIMEOUT,
+            snapshot_sequence: 0,
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Sets amount of snapshots per second, that will be sent to every client.
+    pub fn set_send_rate(&mut self, send_rate: f32) {
+        self.send_rate = send_rate.max(f32::EPSILON);
+    }
+
+    /// Returns amount of snapshots per second, that will be sent to every client.
+    pub fn send_rate(&self) -> f32 {
+        self.send_rate
+    }
+
+    /// Sets amount of seconds without any packets from a client, after which the client is disconnected.
+    pub fn set_timeout(&mut self, timeout: f32) {
+        self.timeout = timeout;
+    }
+
+    /// Returns amount of seconds without any packets from a client, after which the client is
+    /// disconnected.
+    pub fn timeout(&self) -> f32 {
+        self.timeout
+    }
+
+    /// Sets max amount of connected clients. Connection requests are ignored when the limit is reached,
+    /// already connected clients are not affected.
+    pub fn set_max_connections(&mut self, max_connections: usize) {
+        self.max_connections = max_connections;
+    }
+
+    /// Returns max amount of connected clients.
+    pub fn max_connections(&self) -> usize {
+        self.max_connections
+    }
+
+    /// Starts replicating the given node with the given set of properties. Properties must have types
+    /// supported by [`NetValue`], other properties are ignored. The node must be a part of the scene that is
+    /// loaded on both sides, clients will use the node with the same handle in their copy of the scene. Use
+    /// [`Self::replicate_instance`] for nodes that are created at runtime. Replication stops automatically
+    /// when the node is deleted.
+    pub fn replicate(&mut self, node: Handle<Node>, properties: &[&str]) -> NetworkId {
+        self.add_entity(node, SpawnSource::Existing(node), properties)
+    }
+
+    /// Starts replicating a prefab instance, that was created at runtime. Clients will instantiate the same
+    /// prefab. If the node is not a root of a prefab instance, it is replicated the same way as
+    /// [`Self::replicate`] does.
+    pub fn replicate_instance(
+        &mut self,
+        scene: &Scene,
+        node: Handle<Node>,
+        properties: &[&str],
+    ) -> NetworkId {
+        let source = match scene.graph.try_get(node) {
+            Some(node_ref) if node_ref.is_resource_instance_root() => node_ref
+                .resource()
+                .map(|resource| SpawnSource::Prefab(resource.path())),
+            _ => None,
+        };
+
+        match source {
+            Some(source) => self.add_entity(node, source, properties),
+            None => {
+                Log::warn(format!(
+                    "Node {node} is not a prefab instance, it will be replicated as a scene node!"
+                ));
+                self.replicate(node, properties)
+            }
+        }
+    }
+
+    fn add_entity(
+        &mut self,
+        node: Handle<Node>,
+        source: SpawnSource,
+        properties: &[&str],
+    ) -> NetworkId {
+        let id = NetworkId(self.next_network_id);
+        self.next_network_id += 1;
+        self.entities.insert(
+            id,
+            ReplicatedEntity {
+                node,
+                source,
+                properties: properties.iter().map(|p| p.to_string()).collect(),
+            },
+        );
+        id
+    }
+
+    /// Stops replicating the entity, clients will receive [`crate::net::client::ClientEvent::EntityDespawned`]
+    /// event.
+    pub fn stop_replicating(&mut self, id: NetworkId) {
+        self.entities.remove(&id);
+    }
+
+    /// Returns a network id of the given node, if the node is replicated.
+    pub fn network_id(&self, node: Handle<Node>) -> Option<NetworkId> {
+        self.entities
+            .iter()
+            .find_map(|(id, entity)| if entity.node == node { Some(*id) } else { None })
+    }
+
+    /// Returns a handle of the node of the given entity.
+    pub fn node(&self, id: NetworkId) -> Handle<Node> {
+        self.entities
+            .get(&id)
+            .map(|entity| entity.node)
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over connected clients.
+    pub fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.connections.iter().map(|connection| connection.id)
+    }
+
+    /// Sends a remote procedure call, that is not bound to any entity, to the given client or to every
+    /// client if `client` is `None`.
+    pub fn send_rpc(&mut self, client: Option<ClientId>, rpc: Rpc) {
+        for connection in self.connections.iter_mut() {
+            if client.map_or(true, |client| client == connection.id) {
+                connection.channel.send(None, rpc.clone());
+            }
+        }
+    }
+
+    /// Disconnects the given client.
+    pub fn disconnect(&mut self, client: ClientId) {
+        if let Some(index) = self.connections.iter().position(|c| c.id == client) {
+            let connection = self.connections.remove(index);
+            self.send(connection.addr, &Packet::Disconnect);
+            self.events
+                .push_back(ServerEvent::ClientDisconnected(connection.id));
+        }
+    }
+
+    /// Pops next event from the queue of events.
+    pub fn poll_event(&mut self) -> Option<ServerEvent> {
+        self.events.pop_front()
+    }
+
+    /// Receives packets from clients, delivers RPCs and sends snapshots. Must be called every frame.
+    pub fn update(&mut self, scene: &mut Scene, dt: f32) {
+        self.time += dt;
+
+        self.receive(scene);
+
+        for connection in self.connections.iter_mut() {
+            connection.idle_time += dt;
+        }
+        let timeout = self.timeout;
+        let events = &mut self.events;
+        self.connections.retain(|connection| {
+            let alive = connection.idle_time < timeout;
+            if !alive {
+                Log::info(format!("{} has timed out.", connection.id));
+                events.push_back(ServerEvent::ClientDisconnected(connection.id));
+            }
+            alive
+        });
+
+        self.entities
+            .retain(|_, entity| scene.graph.is_valid_handle(entity.node));
+
+        for (id, entity) in self.entities.iter() {
+            for rpc in take_outgoing_rpcs(&mut scene.graph, entity.node) {
+                for connection in self.connections.iter_mut() {
+                    connection.channel.send(Some(*id), rpc.clone());
+                }
+            }
+        }
+
+        self.send_timer += dt;
+        let interval = 1.0 / self.send_rate;
+        if self.send_timer >= interval {
+            self.send_timer = (self.send_timer - interval).min(interval);
+            self.send_snapshots(scene);
+        }
+    }
+
+    fn receive(&mut self, scene: &mut Scene) {
+        loop {
+            match self.socket.recv_from(&mut self.receive_buffer) {
+                Ok((size, addr)) => {
+                    if let Some(packet) = Packet::decode(&self.receive_buffer[..size]) {
+                        self.handle_packet(scene, addr, packet);
+                    }
+                }
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                // Some platforms report unreachable clients this way, it is safe to ignore.
+                Err(ref e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                Err(e) => {
+                    Log::err(format!("Unable to receive a packet. Reason: {e}"));
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, scene: &mut Scene, addr: SocketAddr, packet: Packet) {
+        let index = self.connections.iter().position(|c| c.addr == addr);
+        match packet {
+            Packet::Connect => match index {
+                // The client has missed its acceptance.
+                Some(index) => {
+                    let client = self.connections[index].id;
+                    self.send(addr, &Packet::Accepted { client });
+                }
+                None => {
+                    if self.connections.len() < self.max_connections {
+                        let token = self.challenge_token(addr, self.challenge_period());
+                        self.send(addr, &Packet::Challenge { token });
+                    }
+                }
+            },
+            Packet::ChallengeResponse { token } => {
+                let id = match index {
+                    Some(index) => self.connections[index].id,
+                    None => {
+                        if !self.is_valid_token(addr, token)
+                            || self.connections.len() >= self.max_connections
+                        {
+                            return;
+                        }
+
+                        let id = ClientId(self.next_client_id);
+                        self.next_client_id += 1;
+                        self.connections.push(Connection {
+                            id,
+                            addr,
+                            idle_time: 0.0,
+                            acked_snapshot: None,
+                            history: Default::default(),
+                            channel: Default::default(),
+                            last_input: 0,
+                        });
+                        Log::info(format!("{id} has connected from {addr}."));
+                        self.events.push_back(ServerEvent::ClientConnected(id));
+                        id
+                    }
+                };
+                self.send(addr, &Packet::Accepted { client: id });
+            }
+            Packet::Disconnect => {
+                if let Some(index) = index {
+                    let connection = self.connections.remove(index);
+                    Log::info(format!("{} has disconnected.", connection.id));
+                    self.events
+                        .push_back(ServerEvent::ClientDisconnected(connection.id));
+                }
+            }
+            Packet::ClientUpdate {
+                acked_snapshot,
+                acked_reliable,
+                reliable,
+                inputs,
+            } => {
+                let index = match index {
+                    Some(index) => index,
+                    None => return,
+                };
+                let connection = &mut self.connections[index];
+                connection.idle_time = 0.0;
+                if let Some(acked_snapshot) = acked_snapshot {
+                    connection.acknowledge_snapshot(acked_snapshot);
+                }
+                connection.channel.acknowledge(acked_reliable);
+
+                for (sequence, data) in inputs {
+                    if sequence > connection.last_input {
+                        connection.last_input = sequence;
+                        self.events.push_back(ServerEvent::Input {
+                            client: connection.id,
+                            sequence,
+                            data,
+                        });
+                    }
+                }
+
+                let client = connection.id;
+                for message in connection.channel.receive(reliable) {
+                    match message.target {
+                        Some(target) => {
+                            if let Some(entity) = self.entities.get(&target) {
+                                deliver_rpc(
+                                    &mut scene.graph,
+                                    entity.node,
+                                    ReceivedRpc {
+                                        sender: Some(client),
+                                        rpc: message.rpc,
+                                    },
+                                );
+                            }
+                        }
+                        None => self.events.push_back(ServerEvent::Rpc {
+                            client,
+                            rpc: message.rpc,
+                        }),
+                    }
+                }
+            }
+            // Server-side packets are ignored.
+            Packet::Accepted { .. } | Packet::Snapshot { .. } | Packet::Challenge { .. } => (),
+        }
+    }
+
+    fn challenge_period(&self) -> u64 {
+        (self.time / CHALLENGE_LIFETIME) as u64
+    }
+
+    fn challenge_token(&self, addr: SocketAddr, period: u64) -> u64 {
+        // The hasher is keyed with random keys, so tokens cannot be guessed by a client that does not
+        // receive packets sent to the address.
+        let mut hasher = self.token_keys.build_hasher();
+        addr.hash(&mut hasher);
+        period.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn is_valid_token(&self, addr: SocketAddr, token: u64) -> bool {
+        let period = self.challenge_period();
+        token == self.challenge_token(addr, period)
+            || (period > 0 && token == self.challenge_token(addr, period - 1))
+    }
+
+    fn send_snapshots(&mut self, scene: &Scene) {
+        let state = self
+            .entities
+            .iter()
+            .map(|(id, entity)| {
+                let node = &scene.graph[entity.node];
+                let values = entity
+                    .properties
+                    .iter()
+                    .map(|path| read_property(node, path))
+                    .collect::<EntityState>();
+                (*id, values)
+            })
+            .collect::<FxHashMap<_, _>>();
+
+        self.snapshot_sequence += 1;
+        let sequence = self.snapshot_sequence;
+
+        for connection in self.connections.iter_mut() {
+            let baseline = connection.baseline();
+
+            let entities = state
+                .iter()
+                .map(|(id, values)| {
+                    let base_values = baseline.and_then(|baseline| baseline.entities.get(id));
+                    let properties = values
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, value)| {
+                            let value = value.as_ref()?;
+                            let base = base_values.and_then(|b| b.get(i)).and_then(|b| b.as_ref());
+                            if base != Some(value) {
+                                Some((i as u16, value.clone()))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect();
+                    let spawn = if base_values.is_none() {
+                        let entity = &self.entities[id];
+                        Some(SpawnInfo {
+                            source: entity.source.clone(),
+                            properties: entity.properties.clone(),
+                        })
+                    } else {
+                        None
+                    };
+                    EntityDelta {
+                        id: *id,
+                        spawn,
+                        properties,
+                    }
+                })
+                .collect();
+
+            let packet = Packet::Snapshot {
+                sequence,
+                part: 0,
+                part_count: 1,
+                baseline: baseline.map(|baseline| baseline.sequence),
+                time: self.time,
+                last_input: connection.last_input,
+                acked_reliable: connection.channel.received(),
+                reliable: connection.channel.pending(),
+                entities,
+            };
+
+            // Large snapshots are sent in several datagrams.
+            for packet in packet.split() {
+                send_packet(&self.socket, connection.addr, &packet);
+            }
+
+            connection.history.push_back(Snapshot {
+                sequence,
+                entities: state.clone(),
+            });
+            if connection.history.len() > MAX_HISTORY {
+                connection.history.pop_front();
+            }
+        }
+    }
+
+    fn send(&self, addr: SocketAddr, packet: &Packet) {
+        send_packet(&self.socket, addr, packet)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        for connection in self.connections.iter() {
+            self.send(connection.addr, &Packet::Disconnect);
+        }
+    }
+}
+
+pub(super) fn send_packet(socket: &UdpSocket, addr: SocketAddr, packet: &Packet) {
+    let data = packet.encode();
+    if data.len() > MAX_PACKET_SIZE {
+        Log::err(format!(
+            "Packet is too large ({} bytes), reduce the amount of replicated data!",
+            data.len()
+        ));
+        return;
+    }
+    match socket.send_to(&data, addr) {
+        Ok(_) => (),
+        // Non-blocking socket could be full, the packet will be lost like any other UDP packet.
+        Err(ref e) if e.kind() == ErrorKind::WouldBlock => (),
+        Err(e) => Log::err(format!("Unable to send a packet to {addr}. Reason: {e}")),
+    }
+}
@@ -0,0 +1,260 @@
+//! Network values are used to transfer properties of replicated nodes and arguments of remote procedure
+//! calls. See [`NetValue`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector2, Vector3, Vector4},
+        reflect::prelude::*,
+    },
+    net::codec::{Reader, Writer},
+};
+use std::any::Any;
+
+/// A value, that could be sent over the network. Properties of replicated nodes must have one of these
+/// types, properties of other types are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetValue {
+    /// Boolean value.
+    Bool(bool),
+    /// Signed 32-bit integer.
+    I32(i32),
+    /// Signed 64-bit integer.
+    I64(i64),
+    /// Unsigned 8-bit integer.
+    U8(u8),
+    /// Unsigned 32-bit integer.
+    U32(u32),
+    /// Unsigned 64-bit integer.
+    U64(u64),
+    /// Pointer-sized unsigned integer, it is always transferred as 64-bit integer.
+    Usize(usize),
+    /// 32-bit floating point number.
+    F32(f32),
+    /// 64-bit floating point number.
+    F64(f64),
+    /// UTF-8 string.
+    String(String),
+    /// Two-dimensional vector.
+    Vector2(Vector2<f32>),
+    /// Three-dimensional vector.
+    Vector3(Vector3<f32>),
+    /// Four-dimensional vector.
+    Vector4(Vector4<f32>),
+    /// Rotation.
+    UnitQuaternion(UnitQuaternion<f32>),
+    /// Arbitrary bytes.
+    Bytes(Vec<u8>),
+}
+
+macro_rules! define_from {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl From<$ty> for NetValue {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+define_from!(
+    bool => Bool,
+    i32 => I32,
+    i64 => I64,
+    u8 => U8,
+    u32 => U32,
+    u64 => U64,
+    usize => Usize,
+    f32 => F32,
+    f64 => F64,
+    String => String,
+    Vector2<f32> => Vector2,
+    Vector3<f32> => Vector3,
+    Vector4<f32> => Vector4,
+    UnitQuaternion<f32> => UnitQuaternion,
+    Vec<u8> => Bytes
+);
+
+impl NetValue {
+    /// Tries to convert a value of a property to network value. Returns `None` if the type of the property
+    /// is not supported.
+    pub fn from_reflect(value: &dyn Reflect) -> Option<Self> {
+        let mut result = None;
+        value.as_any(&mut |any| result = Self::from_any(any));
+        result
+    }
+
+    fn from_any(any: &dyn Any) -> Option<Self> {
+        macro_rules! convert {
+            ($($ty:ty),*) => {
+                $(
+                    if let Some(value) = any.downcast_ref::<$ty>() {
+                        return Some(Self::from(value.clone()));
+                    }
+                )*
+            };
+        }
+
+        convert!(
+            bool,
+            i32,
+            i64,
+            u8,
+            u32,
+            u64,
+            usize,
+            f32,
+            f64,
+            String,
+            Vector2<f32>,
+            Vector3<f32>,
+            Vector4<f32>,
+            UnitQuaternion<f32>,
+            Vec<u8>
+        );
+
+        None
+    }
+
+    /// Converts the value to a boxed reflect value, that could be assigned to a property.
+    pub fn into_reflect(self) -> Box<dyn Reflect> {
+        match self {
+            NetValue::Bool(v) => Box::new(v),
+            NetValue::I32(v) => Box::new(v),
+            NetValue::I64(v) => Box::new(v),
+            NetValue::U8(v) => Box::new(v),
+            NetValue::U32(v) => Box::new(v),
+            NetValue::U64(v) => Box::new(v),
+            NetValue::Usize(v) => Box::new(v),
+            NetValue::F32(v) => Box::new(v),
+            NetValue::F64(v) => Box::new(v),
+            NetValue::String(v) => Box::new(v),
+            NetValue::Vector2(v) => Box::new(v),
+            NetValue::Vector3(v) => Box::new(v),
+            NetValue::Vector4(v) => Box::new(v),
+            NetValue::UnitQuaternion(v) => Box::new(v),
+            NetValue::Bytes(v) => Box::new(v),
+        }
+    }
+
+    /// Interpolates between two values. Numbers, vectors and rotations are interpolated smoothly, other
+    /// values are switched when `t` reaches `1.0`.
+    pub fn interpolate(&self, other: &NetValue, t: f32) -> NetValue {
+        match (self, other) {
+            (NetValue::F32(a), NetValue::F32(b)) => NetValue::F32(a + (b - a) * t),
+            (NetValue::F64(a), NetValue::F64(b)) => NetValue::F64(a + (b - a) * t as f64),
+            (NetValue::Vector2(a), NetValue::Vector2(b)) => NetValue::Vector2(a.lerp(b, t)),
+            (NetValue::Vector3(a), NetValue::Vector3(b)) => NetValue::Vector3(a.lerp(b, t)),
+            (NetValue::Vector4(a), NetValue::Vector4(b)) => NetValue::Vector4(a.lerp(b, t)),
+            (NetValue::UnitQuaternion(a), NetValue::UnitQuaternion(b)) => {
+                NetValue::UnitQuaternion(a.nlerp(b, t))
+            }
+            _ => {
+                if t < 1.0 {
+                    self.clone()
+                } else {
+                    other.clone()
+                }
+            }
+        }
+    }
+
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        match self {
+            NetValue::Bool(v) => {
+                writer.u8(0);
+                writer.bool(*v);
+            }
+            NetValue::I32(v) => {
+                writer.u8(1);
+                writer.bytes(&v.to_le_bytes());
+            }
+            NetValue::I64(v) => {
+                writer.u8(2);
+                writer.bytes(&v.to_le_bytes());
+            }
+            NetValue::U8(v) => {
+                writer.u8(3);
+                writer.u8(*v);
+            }
+            NetValue::U32(v) => {
+                writer.u8(4);
+                writer.u32(*v);
+            }
+            NetValue::U64(v) => {
+                writer.u8(5);
+                writer.u64(*v);
+            }
+            NetValue::Usize(v) => {
+                writer.u8(6);
+                writer.u64(*v as u64);
+            }
+            NetValue::F32(v) => {
+                writer.u8(7);
+                writer.f32(*v);
+            }
+            NetValue::F64(v) => {
+                writer.u8(8);
+                writer.bytes(&v.to_le_bytes());
+            }
+            NetValue::String(v) => {
+                writer.u8(9);
+                writer.string(v);
+            }
+            NetValue::Vector2(v) => {
+                writer.u8(10);
+                v.iter().for_each(|c| writer.f32(*c));
+            }
+            NetValue::Vector3(v) => {
+                writer.u8(11);
+                v.iter().for_each(|c| writer.f32(*c));
+            }
+            NetValue::Vector4(v) => {
+                writer.u8(12);
+                v.iter().for_each(|c| writer.f32(*c));
+            }
+            NetValue::UnitQuaternion(v) => {
+                writer.u8(13);
+                v.coords.iter().for_each(|c| writer.f32(*c));
+            }
+            NetValue::Bytes(v) => {
+                writer.u8(14);
+                writer.u32(v.len() as u32);
+                writer.bytes(v);
+            }
+        }
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Option<Self> {
+        Some(match reader.u8()? {
+            0 => NetValue::Bool(reader.bool()?),
+            1 => NetValue::I32(i32::from_le_bytes(reader.array()?)),
+            2 => NetValue::I64(i64::from_le_bytes(reader.array()?)),
+            3 => NetValue::U8(reader.u8()?),
+            4 => NetValue::U32(reader.u32()?),
+            5 => NetValue::U64(reader.u64()?),
+            6 => NetValue::Usize(reader.u64()? as usize),
+            7 => NetValue::F32(reader.f32()?),
+            8 => NetValue::F64(f64::from_le_bytes(reader.array()?)),
+            9 => NetValue::String(reader.string()?),
+            10 => NetValue::Vector2(Vector2::new(reader.f32()?, reader.f32()?)),
+            11 => NetValue::Vector3(Vector3::new(reader.f32()?, reader.f32()?, reader.f32()?)),
+            12 => NetValue::Vector4(Vector4::new(
+                reader.f32()?,
+                reader.f32()?,
+                reader.f32()?,
+                reader.f32()?,
+            )),
+            13 => {
+                let (i, j, k, w) = (reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?);
+                NetValue::UnitQuaternion(UnitQuaternion::new_unchecked(Quaternion::new(w, i, j, k)))
+            }
+            14 => {
+                let len = reader.u32()? as usize;
+                NetValue::Bytes(reader.take(len)?.to_vec())
+            }
+            _ => return None,
+        })
+    }
+}
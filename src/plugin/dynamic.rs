@@ -0,0 +1,396 @@
+//! Dynamic plugins are plugins that are loaded from dynamic libraries (`.dll`, `.so`, `.dylib`) and
+//! can be reloaded while the game is running. See [`DynamicPlugin`] docs for more info.
+
+use crate::{
+    core::{log::Log, uuid::Uuid},
+    engine::SerializationContext,
+    plugin::{PluginConstructor, PluginRegistrationContext},
+};
+use libloading::Library;
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+#[cfg(unix)]
+use std::{
+    mem::MaybeUninit,
+    os::raw::{c_char, c_int, c_void},
+};
+
+/// Name of a function that must be exported from a dynamic library to make it loadable as a plugin.
+/// Use [`crate::export_plugin`] macro to define it.
+pub const PLUGIN_ENTRY_POINT: &[u8] = b"fyrox_plugin";
+
+/// Signature of the function that is exported from a dynamic library. It must return a new instance
+/// of a plugin constructor. The function uses the C calling convention, so the way it is called does
+/// not depend on the Rust ABI. The returned trait object is still a Rust type, which is why the
+/// engine and the plugin must be built by the same compiler with the same dependencies.
+#[allow(improper_ctypes_definitions)]
+pub type PluginEntryPoint = extern "C" fn() -> Box<dyn PluginConstructor>;
+
+/// Exports a function with the name defined by [`PLUGIN_ENTRY_POINT`] from your game library, so it
+/// can be loaded by [`DynamicPlugin`]. The argument must be an expression that creates a plugin
+/// constructor.
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     core::pool::Handle,
+///     plugin::{Plugin, PluginConstructor, PluginContext},
+///     scene::Scene,
+/// };
+///
+/// struct Game;
+///
+/// impl Plugin for Game {}
+///
+/// struct GameConstructor;
+///
+/// impl PluginConstructor for GameConstructor {
+///     fn create_instance(&self, _: Handle<Scene>, _: PluginContext) -> Box<dyn Plugin> {
+///         Box::new(Game)
+///     }
+/// }
+///
+/// fyrox::export_plugin!(GameConstructor);
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($constructor:expr) => {
+        #[no_mangle]
+        #[allow(improper_ctypes_definitions)]
+        pub extern "C" fn fyrox_plugin() -> Box<dyn $crate::plugin::PluginConstructor> {
+            Box::new($constructor)
+        }
+    };
+}
+
+/// An error that may occur when loading a dynamic plugin.
+#[derive(Debug)]
+pub enum DynamicPluginError {
+    /// An i/o error, it may occur if the source library does not exist or could not be copied.
+    Io(std::io::Error),
+    /// Unable to load the library or to find the entry point in it.
+    Library(libloading::Error),
+}
+
+impl Display for DynamicPluginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DynamicPluginError::Io(v) => write!(f, "Dynamic plugin i/o error: {v}"),
+            DynamicPluginError::Library(v) => write!(f, "Unable to load dynamic plugin: {v}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for DynamicPluginError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<libloading::Error> for DynamicPluginError {
+    fn from(e: libloading::Error) -> Self {
+        Self::Library(e)
+    }
+}
+
+/// Dynamic plugin is a plugin constructor, that is loaded from a dynamic library. The library is
+/// copied next to the original file before loading, so the original file could be overwritten by
+/// the compiler while the game is running. When the original file changes, the engine serializes
+/// the state of the plugin instance (see [`crate::plugin::Plugin::visit_state`]) and every script
+/// instance registered by the plugin, unloads the old library, loads the new one and restores the
+/// state back.
+///
+/// # Safety
+///
+/// The engine and the library **must** be compiled with the same compiler version and with the
+/// same version of the engine, there is no stable ABI in Rust and mismatching versions will most
+/// likely lead to crashes. Hot reloading is a development tool and should not be used in release
+/// builds of your game.
+///
+/// # Limitations
+///
+/// - Only scripts are preserved, custom scene nodes registered by the plugin are not supported.
+///   Scripts of the nodes in loaded models (prefabs) are re-created as well.
+/// - Script messages that are in flight at the moment of reloading are discarded, if they're sent
+///   to the reloaded scripts or if their payload type is defined in the library. Message
+///   subscriptions of reloaded scripts are removed. [`crate::script::ScriptTrait::on_start`] is
+///   called again for every reloaded script, so it can subscribe again.
+/// - Reloading is postponed until every async task, that was spawned by the plugin using
+///   [`crate::engine::task::TaskPoolHandler`], is finished, and until every resource is loaded.
+///   Tasks that were spawned directly on the task pool are not tracked, make sure that they're
+///   finished before the library changes.
+/// - Any data that is not visited by the plugin or the scripts will be lost.
+pub struct DynamicPlugin {
+    source_path: PathBuf,
+    loaded_path: Option<PathBuf>,
+    modification_time: SystemTime,
+    generation: usize,
+    scripts: Vec<Uuid>,
+    // Base address of the loaded library, `None` if it is unknown.
+    module_base: Option<usize>,
+    pub(crate) instance_index: Option<usize>,
+    pub(crate) reload_postponed: bool,
+    // Must be dropped before the library, because its code is located in the library.
+    constructor: Option<Box<dyn PluginConstructor>>,
+    library: Option<Library>,
+}
+
+impl DynamicPlugin {
+    /// Loads a dynamic plugin from the given path and registers its scripts in the given
+    /// serialization context.
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        serialization_context: &Arc<SerializationContext>,
+    ) -> Result<Self, DynamicPluginError> {
+        let mut plugin = Self {
+            source_path: path.as_ref().to_owned(),
+            loaded_path: None,
+            modification_time: SystemTime::UNIX_EPOCH,
+            generation: 0,
+            scripts: Default::default(),
+            module_base: None,
+            instance_index: None,
+            reload_postponed: false,
+            constructor: None,
+            library: None,
+        };
+
+        let loaded = plugin.open_library()?;
+        plugin.install(loaded, serialization_context);
+
+        Ok(plugin)
+    }
+
+    /// Returns a path to the source library.
+    pub fn source_path(&self) -> &Path {
+        &self.source_path
+    }
+
+    /// Returns a list of type uuids of the scripts that were registered by the plugin.
+    pub fn scripts(&self) -> &[Uuid] {
+        &self.scripts
+    }
+
+    /// Returns a reference to the plugin constructor, it is `None` only while the plugin is being
+    /// reloaded.
+    pub fn constructor(&self) -> Option<&dyn PluginConstructor> {
+        self.constructor.as_deref()
+    }
+
+    /// Returns `true` if the source library was modified since the last load.
+    pub fn is_changed(&self) -> bool {
+        std::fs::metadata(&self.source_path)
+            .and_then(|m| m.modified())
+            .map_or(false, |time| time != self.modification_time)
+    }
+
+    /// Returns `true` if the code at the given address belongs to the currently loaded library. If
+    /// the library cannot be located in memory on the current platform, every address is considered
+    /// to be a part of it.
+    pub(crate) fn contains_code(&self, address: usize) -> bool {
+        match self.module_base {
+            Some(module_base) => module_base_of(address) == Some(module_base),
+            None => self.library.is_some(),
+        }
+    }
+
+    /// Copies the source library and opens the copy, but does not touch currently loaded library.
+    /// This allows to keep the old version running if the new one cannot be loaded (for example
+    /// if the compiler is still writing the file).
+    pub(crate) fn open_library(&mut self) -> Result<LoadedLibrary, DynamicPluginError> {
+        let modification_time = std::fs::metadata(&self.source_path)?.modified()?;
+
+        // Do not try to load the same file again and again if it has failed to load once, wait
+        // until it is changed.
+        self.modification_time = modification_time;
+
+        // Some platforms (Windows) lock the file of a loaded library, and some (Linux) cache it by
+        // its path. Use unique copy each time to make sure that the fresh library is loaded.
+        let mut path = self.source_path.clone().into_os_string();
+        path.push(format!(".hot_reload_{}", self.generation));
+        let path = PathBuf::from(path);
+        self.generation += 1;
+
+        std::fs::copy(&self.source_path, &path)?;
+
+        // SAFETY: Loading an arbitrary library is inherently unsafe, see the type docs.
+        let library = match unsafe { Library::new(&path) } {
+            Ok(library) => library,
+            Err(e) => {
+                Log::verify(std::fs::remove_file(&path));
+                return Err(e.into());
+            }
+        };
+
+        // SAFETY: The signature of the entry point is defined by `export_plugin` macro.
+        let entry_point = match unsafe { library.get::<PluginEntryPoint>(PLUGIN_ENTRY_POINT) } {
+            Ok(entry_point) => *entry_point,
+            Err(e) => {
+                Log::verify(library.close());
+                Log::verify(std::fs::remove_file(&path));
+                return Err(e.into());
+            }
+        };
+
+        Ok(LoadedLibrary {
+            library,
+            path,
+            entry_point,
+        })
+    }
+
+    /// Unregisters scripts of the current library and unloads it. Every object, that has its code
+    /// in the library, must be destroyed before calling this method.
+    pub(crate) fn unload(&mut self, serialization_context: &SerializationContext) {
+        self.constructor = None;
+
+        for script in self.scripts.drain(..) {
+            serialization_context.script_constructors.remove(script);
+        }
+
+        self.module_base = None;
+
+        if let Some(library) = self.library.take() {
+            Log::verify(library.close());
+        }
+
+        if let Some(loaded_path) = self.loaded_path.take() {
+            Log::verify(std::fs::remove_file(loaded_path));
+        }
+    }
+
+    /// Unregisters scripts of the current library, but keeps the library loaded until the process
+    /// exits. It is used when some objects of the library could not be destroyed.
+    pub(crate) fn leak(&mut self, serialization_context: &SerializationContext) {
+        self.constructor = None;
+
+        for script in self.scripts.drain(..) {
+            serialization_context.script_constructors.remove(script);
+        }
+
+        self.module_base = None;
+        std::mem::forget(self.library.take());
+        self.loaded_path = None;
+    }
+
+    /// Creates plugin constructor from the given library and registers its scripts. Previous library
+    /// must be unloaded first.
+    pub(crate) fn install(
+        &mut self,
+        loaded: LoadedLibrary,
+        serialization_context: &Arc<SerializationContext>,
+    ) {
+        debug_assert!(self.library.is_none());
+
+        let constructor = (loaded.entry_point)();
+
+        let scripts_before = serialization_context
+            .script_constructors
+            .map()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        constructor.register(PluginRegistrationContext {
+            serialization_context,
+        });
+
+        self.scripts = serialization_context
+            .script_constructors
+            .map()
+            .keys()
+            .filter(|uuid| !scripts_before.contains(uuid))
+            .cloned()
+            .collect();
+
+        self.module_base = module_base_of(loaded.entry_point as usize);
+        self.constructor = Some(constructor);
+        self.library = Some(loaded.library);
+        self.loaded_path = Some(loaded.path);
+
+        Log::info(format!(
+            "Dynamic plugin {} was loaded successfully!",
+            self.source_path.display()
+        ));
+    }
+}
+
+/// A library that was opened, but not yet installed.
+pub(crate) struct LoadedLibrary {
+    library: Library,
+    path: PathBuf,
+    entry_point: PluginEntryPoint,
+}
+
+impl LoadedLibrary {
+    /// Closes the library and removes its copy.
+    pub(crate) fn discard(self) {
+        Log::verify(self.library.close());
+        Log::verify(std::fs::remove_file(self.path));
+    }
+}
+
+// Returns base address of the module (executable or dynamic library) that contains the given address.
+#[cfg(unix)]
+fn module_base_of(address: usize) -> Option<usize> {
+    #[repr(C)]
+    struct DlInfo {
+        fname: *const c_char,
+        fbase: *mut c_void,
+        sname: *const c_char,
+        saddr: *mut c_void,
+    }
+
+    extern "C" {
+        fn dladdr(address: *const c_void, info: *mut DlInfo) -> c_int;
+    }
+
+    let mut info = MaybeUninit::<DlInfo>::uninit();
+    // SAFETY: `dladdr` only looks up the address and fills the info on success.
+    if unsafe { dladdr(address as *const c_void, info.as_mut_ptr()) } != 0 {
+        // SAFETY: The info is initialized, since the call succeeded.
+        Some(unsafe { info.assume_init() }.fbase as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(windows)]
+fn module_base_of(address: usize) -> Option<usize> {
+    const GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT: u32 = 0x2;
+    const GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS: u32 = 0x4;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleHandleExW(
+            flags: u32,
+            module_name: *const u16,
+            module: *mut *mut std::ffi::c_void,
+        ) -> i32;
+    }
+
+    let mut module = std::ptr::null_mut();
+    // SAFETY: With `FROM_ADDRESS` flag the name is treated as an address inside of a module, the
+    // reference counter of the module is not changed.
+    if unsafe {
+        GetModuleHandleExW(
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+            address as *const u16,
+            &mut module,
+        )
+    } != 0
+    {
+        // Module handle is the base address of the module.
+        Some(module as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn module_base_of(_address: usize) -> Option<usize> {
+    None
+}
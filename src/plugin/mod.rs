@@ -4,15 +4,19 @@
 
 use crate::{
     asset::manager::ResourceManager,
-    core::pool::Handle,
-    engine::{GraphicsContext, PerformanceStatistics, SerializationContext},
+    core::{pool::Handle, visitor::prelude::*},
+    engine::{task::TaskPoolHandler, GraphicsContext, PerformanceStatistics, SerializationContext},
     event::Event,
     event_loop::ControlFlow,
     gui::{message::UiMessage, UserInterface},
+    input::InputSystem,
     scene::{Scene, SceneContainer},
 };
 use std::{any::Any, sync::Arc};
 
+#[cfg(feature = "hot_reload")]
+pub mod dynamic;
+
 /// Plugin constructor is a first step of 2-stage plugin initialization. It is responsible for plugin script
 /// registration and for creating actual plugin instance.
 ///
@@ -38,6 +42,15 @@ pub trait PluginConstructor {
         #[allow(unused_variables)] override_scene: Handle<Scene>,
         context: PluginContext,
     ) -> Box<dyn Plugin>;
+
+    /// The method is called when a dynamic plugin was reloaded and the engine needs an instance of
+    /// the plugin to restore the state of the previous instance into (see [`Plugin::visit_state`]).
+    /// Unlike [`Self::create_instance`], it should not perform any initialization (load scenes, etc.)
+    /// since the state will be restored from the previous instance. Default implementation returns
+    /// `None`, which means that [`Self::create_instance`] will be used instead.
+    fn create_blank_instance(&self) -> Option<Box<dyn Plugin>> {
+        None
+    }
 }
 
 /// Contains plugin environment for the registration stage.
@@ -83,6 +96,14 @@ pub struct PluginContext<'a, 'b> {
 
     /// Performance statistics from the last frame.
     pub performance_statistics: &'a PerformanceStatistics,
+
+    /// A reference to the task pool, that allows you to spawn async tasks and receive their results
+    /// in a callback with mutable access to the plugin. See [`TaskPoolHandler`] docs for more info.
+    pub task_pool: &'a mut TaskPoolHandler,
+
+    /// A reference to the input system, use it to query the state of actions and axes or to change
+    /// input profiles of players. See [`InputSystem`] docs for more info.
+    pub input: &'a mut InputSystem,
 }
 
 /// Base plugin automatically implements type casting for plugins.
@@ -123,17 +144,16 @@ impl dyn Plugin {
 ///
 /// # Static vs dynamic plugins
 ///
-/// Every plugin must be linked statically to ensure that everything is memory safe. There was some
-/// long research about hot reloading and dynamic plugins (in DLLs) and it turned out that they're
-/// not guaranteed to be memory safe because Rust does not have stable ABI. When a plugin compiled
-/// into DLL, Rust compiler is free to reorder struct members in any way it needs to. It is not
-/// guaranteed that two projects that uses the same library will have compatible ABI. This fact
-/// indicates that you either have to use static linking of your plugins or provide C interface
-/// to every part of the engine and "communicate" with plugin using C interface with C ABI (which
-/// is standardized and guaranteed to be compatible). The main problem with C interface is
-/// boilerplate code and the need to mark every structure "visible" through C interface with
-/// `#[repr(C)]` attribute which is not always easy and even possible (because some structures could
-/// be re-exported from dependencies). These are the main reasons why the engine uses static plugins.
+/// By default, plugins are linked statically to ensure that everything is memory safe. Rust does not
+/// have stable ABI, when a plugin compiled into DLL, Rust compiler is free to reorder struct members
+/// in any way it needs to. It is not guaranteed that two projects that uses the same library will
+/// have compatible ABI. Dynamic plugins (available with `hot_reload` feature) export their entry point
+/// with the C calling convention, but they still exchange Rust types with the engine, so the engine
+/// and the plugin must be compiled by the same compiler with the same set of dependencies and this is
+/// not verified at load time. Such plugins can be reloaded while the game is running - it
+/// drastically reduces iteration times. See `dynamic::DynamicPlugin` docs for more info. Dynamic
+/// plugins are intended to be used only during development, release builds should always use static
+/// linking.
 ///
 /// # Example
 ///
@@ -180,6 +200,16 @@ pub trait Plugin: BasePlugin {
     ) {
     }
 
+    /// Updates the plugin internals with the fixed rate of the physics simulation, right before each
+    /// physics step (see [`crate::engine::Engine::set_fixed_update_rate`]). The method could be called
+    /// zero or multiple times per frame, [`PluginContext::dt`] contains fixed time step.
+    fn on_fixed_update(
+        &mut self,
+        #[allow(unused_variables)] context: &mut PluginContext,
+        #[allow(unused_variables)] control_flow: &mut ControlFlow,
+    ) {
+    }
+
     /// The method is called when the main window receives an event from the OS. The main use of
     /// the method is to respond to some external events, for example an event from keyboard or
     /// gamepad. See [`Event`] docs for more info.
@@ -226,4 +256,12 @@ pub trait Plugin: BasePlugin {
         #[allow(unused_variables)] control_flow: &mut ControlFlow,
     ) {
     }
+
+    /// The method is called when a dynamic plugin is about to be reloaded (with a visitor in writing
+    /// mode) and right after it was reloaded (with a visitor in reading mode). It should visit every
+    /// piece of the plugin's state that must survive hot reloading. The method is never called for
+    /// statically linked plugins.
+    fn visit_state(&mut self, #[allow(unused_variables)] visitor: &mut Visitor) -> VisitResult {
+        Ok(())
+    }
 }
@@ -219,6 +219,28 @@ impl FrameBuffer {
         self.fbo
     }
 
+    /// Reads RGBA8 pixels of the first color attachment (or of the back buffer). Rows are stored
+    /// from bottom to top.
+    pub fn read_pixels(&self, state: &mut PipelineState, width: usize, height: usize) -> Vec<u8> {
+        scope_profile!();
+
+        state.set_framebuffer(self.fbo);
+
+        let mut pixels = vec![0; width * height * 4];
+        unsafe {
+            state.gl.read_pixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut pixels),
+            );
+        }
+        pixels
+    }
+
     pub fn clear(
         &mut self,
         state: &mut PipelineState,
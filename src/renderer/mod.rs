@@ -49,6 +49,7 @@ use crate::{
         reflect::prelude::*,
         scope_profile,
         sstorage::ImmutableString,
+        visitor::{PodVecView, Visit, VisitResult, Visitor},
     },
     gui::{draw::DrawingContext, UserInterface},
     material::{shader::SamplerFallback, shader::Shader, Material, PropertyValue},
@@ -81,7 +82,9 @@ use crate::{
         sprite_renderer::{SpriteRenderContext, SpriteRenderer},
         ui_renderer::{UiRenderContext, UiRenderer},
     },
-    resource::texture::{Texture, TextureKind, TextureResource},
+    resource::texture::{
+        Texture, TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+    },
     scene::{camera::Camera, mesh::surface::SurfaceData, Scene, SceneContainer},
 };
 use fxhash::FxHashMap;
@@ -687,6 +690,69 @@ pub(crate) fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
     ))
 }
 
+/// A screenshot of a rendered frame, see [`Renderer::request_screenshot`].
+#[derive(Clone, Default, Debug)]
+pub struct Screenshot {
+    /// Width of the screenshot in pixels.
+    pub width: u32,
+    /// Height of the screenshot in pixels.
+    pub height: u32,
+    /// RGBA8 pixels, rows are stored from top to bottom.
+    pub pixels: Vec<u8>,
+}
+
+impl Visit for Screenshot {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.width.visit("Width", &mut region)?;
+        self.height.visit("Height", &mut region)?;
+        PodVecView::from_pod_vec(&mut self.pixels).visit("Pixels", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl Screenshot {
+    /// Creates a scaled copy of the screenshot using nearest neighbour filtering. It could be used
+    /// to create small thumbnails, for example for save files.
+    pub fn resized(&self, width: u32, height: u32) -> Self {
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        if self.width > 0 && self.height > 0 {
+            for y in 0..height {
+                let src_y = (y as u64 * self.height as u64 / height as u64) as usize;
+                for x in 0..width {
+                    let src_x = (x as u64 * self.width as u64 / width as u64) as usize;
+                    let i = (src_y * self.width as usize + src_x) * 4;
+                    pixels.extend_from_slice(&self.pixels[i..(i + 4)]);
+                }
+            }
+        } else {
+            pixels.resize((width * height * 4) as usize, 0);
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Creates a texture from the screenshot, it could be used to show the screenshot in the user
+    /// interface.
+    pub fn to_texture(&self) -> Option<TextureResource> {
+        TextureResource::from_bytes(
+            TextureKind::Rectangle {
+                width: self.width,
+                height: self.height,
+            },
+            TexturePixelKind::RGBA8,
+            self.pixels.clone(),
+            false,
+        )
+    }
+}
+
 /// See module docs.
 pub struct Renderer {
     backbuffer: FrameBuffer,
@@ -732,6 +798,8 @@ pub struct Renderer {
     // TextureId -> FrameBuffer mapping. This mapping is used for temporal frame buffers
     // like ones used to render UI instances.
     ui_frame_buffers: FxHashMap<usize, FrameBuffer>,
+    screenshot_requested: bool,
+    screenshot: Option<Screenshot>,
     // MUST BE LAST! Otherwise you'll get crash, because other parts of the renderer will
     // contain **pointer** to pipeline state. It must be dropped last!
     /// Pipeline state.
@@ -1245,6 +1313,8 @@ impl Renderer {
             shader_cache,
             scene_render_passes: Default::default(),
             matrix_storage: MatrixStorageCache::new(&mut state)?,
+            screenshot_requested: false,
+            screenshot: None,
             state,
         })
     }
@@ -1290,6 +1360,41 @@ impl Renderer {
         self.backbuffer_clear_color = color;
     }
 
+    /// Requests a screenshot of the next rendered frame (including the user interface). The screenshot
+    /// could be fetched using [`Self::take_screenshot`] after the frame is rendered.
+    pub fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
+    }
+
+    /// Returns a screenshot that was requested by [`Self::request_screenshot`], if it is ready.
+    pub fn take_screenshot(&mut self) -> Option<Screenshot> {
+        self.screenshot.take()
+    }
+
+    fn capture_screenshot(&mut self) {
+        if !self.screenshot_requested {
+            return;
+        }
+        self.screenshot_requested = false;
+
+        let (width, height) = (self.frame_size.0 as usize, self.frame_size.1 as usize);
+        let pixels = self.backbuffer.read_pixels(&mut self.state, width, height);
+
+        // OpenGL stores rows from bottom to top, flip them and discard alpha channel, which
+        // has no meaning for the back buffer.
+        let row_size = width * 4;
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for row in pixels.chunks_exact(row_size).rev() {
+            flipped.extend(row.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2], 255]));
+        }
+
+        self.screenshot = Some(Screenshot {
+            width: width as u32,
+            height: height as u32,
+            pixels: flipped,
+        });
+    }
+
     /// Returns a reference to current pipeline state.
     pub fn pipeline_state(&mut self) -> &mut PipelineState {
         &mut self.state
@@ -1858,6 +1963,7 @@ impl Renderer {
         context: &PossiblyCurrentContext,
     ) -> Result<(), FrameworkError> {
         self.render_frame(scenes, drawing_context)?;
+        self.capture_screenshot();
         self.statistics.end_frame();
         surface.swap_buffers(context)?;
         self.state.check_error();
@@ -1873,6 +1979,7 @@ impl Renderer {
         drawing_context: &DrawingContext,
     ) -> Result<(), FrameworkError> {
         self.render_frame(scenes, drawing_context)?;
+        self.capture_screenshot();
         self.statistics.end_frame();
         self.state.check_error();
         self.statistics.finalize();
@@ -0,0 +1,62 @@
+//! Bone mapping loader.
+
+use crate::{
+    asset::{
+        event::ResourceEventBroadcaster,
+        loader::{BoxedLoaderFuture, ResourceLoader},
+        untyped::UntypedResource,
+    },
+    core::log::Log,
+    resource::bone_mapping::BoneMapping,
+};
+use std::any::Any;
+
+/// Default implementation for bone mapping loading.
+pub struct BoneMappingLoader;
+
+impl ResourceLoader for BoneMappingLoader {
+    fn extensions(&self) -> &[&str] {
+        &["bonemap"]
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn load(
+        &self,
+        mapping: UntypedResource,
+        event_broadcaster: ResourceEventBroadcaster,
+        reload: bool,
+    ) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let path = mapping.0.lock().path().to_path_buf();
+
+            match BoneMapping::from_file(&path).await {
+                Ok(mapping_state) => {
+                    Log::info(format!("Bone mapping {:?} is loaded!", path));
+
+                    mapping.commit_ok(mapping_state);
+
+                    event_broadcaster.broadcast_loaded_or_reloaded(mapping, reload);
+                }
+                Err(error) => {
+                    Log::err(format!(
+                        "Unable to load bone mapping from {:?}! Reason {:?}",
+                        path, error
+                    ));
+
+                    mapping.commit_error(path, error);
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,209 @@
+//! Bone mapping resource defines correspondence between bones of two skeletons, it is used to retarget
+//! animations between skeletons with different bone names. See [`BoneMapping`] docs for more info.
+
+use crate::{
+    asset::{options::ImportOptions, Resource, ResourceData},
+    core::{
+        io::{self, FileLoadError},
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    io::Cursor,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+pub mod loader;
+
+/// Type UUID of bone mapping resource.
+pub const BONE_MAPPING_RESOURCE_UUID: Uuid = uuid!("3b0f6d3e-8f0a-4c57-9d5c-2a4b8e1f7c61");
+
+/// An error that may occur during bone mapping resource loading.
+#[derive(Debug)]
+pub enum BoneMappingError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// A parsing error has occurred.
+    ParseError(ron::error::SpannedError),
+}
+
+impl Display for BoneMappingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoneMappingError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            BoneMappingError::ParseError(v) => {
+                write!(f, "A parsing error has occurred {v:?}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for BoneMappingError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for BoneMappingError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+/// A pair of bone names, where `source` is a name of a bone in a skeleton an animation was made for and
+/// `target` is a name of the corresponding bone in a skeleton the animation will be retargeted to.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect, Visit)]
+pub struct BoneMappingEntry {
+    /// Name of a bone in the source skeleton.
+    pub source: String,
+    /// Name of a bone in the target skeleton.
+    pub target: String,
+}
+
+/// Bone mapping defines correspondence between bones of two skeletons, that could have different names
+/// of bones. It is used by animation retargeting (see [`crate::animation::retarget`] module docs).
+///
+/// # Format
+///
+/// Bone mapping is stored in `.bonemap` files, that have the following (RON) format:
+///
+/// ```text
+/// (
+///     bones: [
+///         (source: "mixamorig:Hips", target: "Pelvis"),
+///         (source: "mixamorig:Spine", target: "Spine_01"),
+///     ],
+///     map_unlisted_by_name: true,
+/// )
+/// ```
+///
+/// Usually it is enough to have a mapping per "family" of skeletons (for example, one for every character
+/// exported from a particular authoring tool), so a single animation library can be shared across all
+/// humanoid characters of a game.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize, Reflect, Visit)]
+pub struct BoneMapping {
+    #[serde(skip)]
+    #[reflect(hidden)]
+    pub(crate) path: PathBuf,
+
+    /// A list of bone pairs.
+    #[serde(default)]
+    pub bones: Vec<BoneMappingEntry>,
+
+    /// Whether to map bones, that are not listed in [`Self::bones`], by their names or not. If it is set
+    /// to `false`, such bones won't be animated.
+    #[serde(default)]
+    pub map_unlisted_by_name: bool,
+}
+
+impl BoneMapping {
+    /// Load a bone mapping from the specific file path.
+    pub async fn from_file(path: &Path) -> Result<Self, BoneMappingError> {
+        let content = io::load_file(path).await?;
+        let mut mapping: BoneMapping = ron::de::from_reader(Cursor::new(content))?;
+        mapping.path = path.to_path_buf();
+        Ok(mapping)
+    }
+
+    /// Adds a new pair of bones to the mapping.
+    pub fn add_bone<S: AsRef<str>, T: AsRef<str>>(&mut self, source: S, target: T) {
+        self.bones.push(BoneMappingEntry {
+            source: source.as_ref().to_owned(),
+            target: target.as_ref().to_owned(),
+        });
+    }
+
+    /// Returns a name of a bone in the target skeleton, that corresponds to a bone with the given name in
+    /// the source skeleton.
+    pub fn target_name<'a>(&'a self, source: &'a str) -> Option<&'a str> {
+        self.bones
+            .iter()
+            .find(|entry| entry.source == source)
+            .map(|entry| entry.target.as_str())
+            .or(if self.map_unlisted_by_name {
+                Some(source)
+            } else {
+                None
+            })
+    }
+}
+
+impl FromStr for BoneMapping {
+    type Err = BoneMappingError;
+
+    /// Creates a bone mapping from the given string, the string must have the format described in
+    /// [`BoneMapping`] docs.
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        Ok(ron::de::from_str(str)?)
+    }
+}
+
+impl ResourceData for BoneMapping {
+    fn path(&self) -> Cow<Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+impl TypeUuidProvider for BoneMapping {
+    fn type_uuid() -> Uuid {
+        BONE_MAPPING_RESOURCE_UUID
+    }
+}
+
+/// Type alias for bone mapping resources.
+pub type BoneMappingResource = Resource<BoneMapping>;
+
+/// Import options for bone mapping resource.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct BoneMappingImportOptions {}
+
+impl ImportOptions for BoneMappingImportOptions {}
+
+#[cfg(test)]
+mod test {
+    use crate::resource::bone_mapping::BoneMapping;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_bone_mapping_from_str() {
+        let mapping = BoneMapping::from_str(
+            r#"(
+                bones: [
+                    (source: "mixamorig:Hips", target: "Pelvis"),
+                ],
+                map_unlisted_by_name: true,
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(mapping.target_name("mixamorig:Hips"), Some("Pelvis"));
+        assert_eq!(mapping.target_name("Head"), Some("Head"));
+    }
+}
@@ -36,7 +36,7 @@ use crate::{
                 FbxComponent, FbxMapping, FbxScene,
             },
         },
-        model::{MaterialSearchOptions, ModelImportOptions},
+        model::{AdditiveAnimationOptions, MaterialSearchOptions, ModelImportOptions},
         texture::Texture,
     },
     scene::{
@@ -813,6 +813,25 @@ async fn convert(
         }
     }
 
+    let reference_frame = match model_import_options.additive_animation {
+        AdditiveAnimationOptions::Disabled => None,
+        AdditiveAnimationOptions::RelativeToFirstFrame => Some(0),
+        AdditiveAnimationOptions::RelativeToFrame(frame) => Some(frame as usize),
+    };
+    if let Some(reference_frame) = reference_frame {
+        let key_locations = animation.key_locations();
+        if let Some(reference_time) = key_locations
+            .get(reference_frame)
+            .or_else(|| key_locations.last())
+        {
+            animation.make_additive(*reference_time);
+        }
+    }
+
+    if model_import_options.compress_animations {
+        animation.compress(&model_import_options.animation_compression);
+    }
+
     // Do not create animation player if there's no animation content.
     if !animation.tracks().is_empty() {
         let mut animations_container = AnimationContainer::new();
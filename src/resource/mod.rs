@@ -2,6 +2,7 @@
 
 #![warn(missing_docs)]
 
+pub mod bone_mapping;
 pub mod curve;
 pub mod fbx;
 pub mod model;
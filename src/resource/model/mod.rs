@@ -19,7 +19,9 @@
 //! and RGS (native Fyroxed format) formats are supported.
 
 use crate::{
-    animation::Animation,
+    animation::{
+        compression::AnimationCompressionSettings, retarget::retarget_animation, Animation,
+    },
     asset::{
         manager::ResourceManager, options::ImportOptions, Resource, ResourceData,
         MODEL_RESOURCE_UUID,
@@ -35,7 +37,10 @@ use crate::{
         TypeUuidProvider,
     },
     engine::SerializationContext,
-    resource::fbx::{self, error::FbxError},
+    resource::{
+        bone_mapping::BoneMapping,
+        fbx::{self, error::FbxError},
+    },
     scene::{
         animation::AnimationPlayer,
         graph::{map::NodeHandleMap, Graph},
@@ -148,6 +153,17 @@ pub trait ModelResourceExtension: Sized {
     ///
     /// Panics if there's no animation player in the given hierarchy (descendant nodes of `root`).
     fn retarget_animations(&self, root: Handle<Node>, graph: &mut Graph) -> Vec<Handle<Animation>>;
+
+    /// Tries to retarget animations from given model resource to a skeleton with different proportions and bone
+    /// names, starting from `root` on a given scene. Unlike [`Self::retarget_animations_directly`], which only
+    /// remaps tracks by node names, it uses the given bone mapping to find corresponding bones and converts every
+    /// track relative to bind poses of both skeletons. See [`retarget_animation`] docs for more info.
+    fn retarget_animations_with_mapping(
+        &self,
+        root: Handle<Node>,
+        graph: &Graph,
+        mapping: &BoneMapping,
+    ) -> Vec<Animation>;
 }
 
 impl ModelResourceExtension for ModelResource {
@@ -301,6 +317,32 @@ impl ModelResourceExtension for ModelResource {
             Default::default()
         }
     }
+
+    fn retarget_animations_with_mapping(
+        &self,
+        root: Handle<Node>,
+        graph: &Graph,
+        mapping: &BoneMapping,
+    ) -> Vec<Animation> {
+        let data = self.data_ref();
+
+        let mut retargetted_animations = Vec::new();
+        for src_node_ref in data.scene.graph.linear_iter() {
+            if let Some(src_player) = src_node_ref.query_component_ref::<AnimationPlayer>() {
+                for src_anim in src_player.animations().iter() {
+                    retargetted_animations.push(retarget_animation(
+                        src_anim,
+                        &data.scene.graph,
+                        graph,
+                        root,
+                        mapping,
+                    ));
+                }
+            }
+        }
+
+        retargetted_animations
+    }
 }
 
 impl ResourceData for Model {
@@ -408,6 +450,36 @@ impl MaterialSearchOptions {
     }
 }
 
+/// Defines whether the animation of a model should be converted to additive form when importing it. See
+/// [`crate::animation::Animation::make_additive`] docs for more info about additive animations.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Visit,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum AdditiveAnimationOptions {
+    /// The animation is imported as is. This is **default** option.
+    #[default]
+    Disabled,
+
+    /// The animation is converted to additive form relative to the pose at its first frame.
+    RelativeToFirstFrame,
+
+    /// The animation is converted to additive form relative to the pose at the given frame. Frames are
+    /// counted by unique key locations of the animation, most of the exporters produce one key per frame.
+    /// If there is no such frame, the last one is used.
+    RelativeToFrame(u32),
+}
+
 /// A set of options that will be applied to a model resource when loading it from external source.
 ///
 /// # Details
@@ -423,11 +495,25 @@ impl MaterialSearchOptions {
 /// ```
 ///
 /// Check documentation of the field of the structure for more info about each parameter.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default, Reflect)]
 pub struct ModelImportOptions {
     /// See [`MaterialSearchOptions`] docs for more info.
     #[serde(default)]
     pub material_search_options: MaterialSearchOptions,
+
+    /// See [`AdditiveAnimationOptions`] docs for more info.
+    #[serde(default)]
+    pub additive_animation: AdditiveAnimationOptions,
+
+    /// Whether to compress imported animations or not. Compression is performed after conversion to additive
+    /// form (if any). See [`AnimationCompressionSettings`] docs for more info.
+    #[serde(default)]
+    pub compress_animations: bool,
+
+    /// A set of settings, that will be used to compress imported animations, if
+    /// [`Self::compress_animations`] is set.
+    #[serde(default)]
+    pub animation_compression: AnimationCompressionSettings,
 }
 
 impl ImportOptions for ModelImportOptions {}
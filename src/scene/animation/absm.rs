@@ -2,7 +2,7 @@
 //! mixes them in arbitrary way into one animation. See [`AnimationBlendingStateMachine`] docs for more info.
 
 use crate::{
-    animation::machine::Machine,
+    animation::{machine::Machine, RootMotion},
     core::{
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
@@ -15,13 +15,76 @@ use crate::{
     scene::{
         animation::AnimationPlayer,
         base::{Base, BaseBuilder},
-        graph::Graph,
+        graph::{Graph, NodePool},
         node::{Node, NodeTrait, UpdateContext},
+        rigidbody::RigidBody,
         Scene,
     },
 };
 use std::ops::{Deref, DerefMut};
 
+/// Defines how root motion of an animation blending state machine is applied to a rigid body. Root motion of every
+/// animation (see [`crate::animation::RootMotionSettings`]) is blended through the state machine the same way as the
+/// poses, and then it is converted to linear and angular velocities of the rigid body. This way a character moves
+/// exactly as its animations do, without sliding during blends.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct RootMotionTarget {
+    /// A handle of a rigid body, which velocity will be set from root motion. Root motion won't be applied if the
+    /// handle is not set.
+    pub rigid_body: Handle<Node>,
+    /// A handle of a node, which global transform defines the space of root motion. Usually it is the root of an
+    /// animated model. If the handle is not set, the rigid body is used instead.
+    pub reference: Handle<Node>,
+    /// If set, vertical part of linear velocity of the rigid body will be left intact, so the gravity will still
+    /// affect the body.
+    pub keep_vertical_velocity: bool,
+    /// If set, rotational part of root motion will be applied as angular velocity of the rigid body.
+    pub apply_rotation: bool,
+}
+
+impl Default for RootMotionTarget {
+    fn default() -> Self {
+        Self {
+            rigid_body: Default::default(),
+            reference: Default::default(),
+            keep_vertical_velocity: true,
+            apply_rotation: false,
+        }
+    }
+}
+
+impl RootMotionTarget {
+    /// Converts the given root motion into velocities and applies them to the rigid body.
+    pub(crate) fn apply(&self, nodes: &mut NodePool, root_motion: &RootMotion, dt: f32) {
+        let reference = if self.reference.is_some() {
+            self.reference
+        } else {
+            self.rigid_body
+        };
+
+        let transform = match nodes.try_borrow(reference) {
+            Some(reference) => reference.global_transform(),
+            None => return,
+        };
+
+        let (mut linear, angular) = root_motion.velocities(&transform, dt);
+
+        if let Some(rigid_body) = nodes
+            .try_borrow_mut(self.rigid_body)
+            .and_then(|n| n.query_component_mut::<RigidBody>())
+        {
+            if self.keep_vertical_velocity {
+                linear.y = rigid_body.lin_vel().y;
+            }
+            rigid_body.set_lin_vel(linear);
+
+            if self.apply_rotation {
+                rigid_body.set_ang_vel(angular);
+            }
+        }
+    }
+}
+
 /// Animation blending state machine (ABSM) is a node that takes multiple animations from an animation player and
 /// mixes them in arbitrary way into one animation. Usually, ABSMs are used to animate humanoid characters in games,
 /// by blending multiple states with one or more animations. More info about state machines can be found in
@@ -108,6 +171,8 @@ pub struct AnimationBlendingStateMachine {
     base: Base,
     machine: InheritableVariable<Machine>,
     animation_player: InheritableVariable<Handle<Node>>,
+    #[visit(optional)]
+    root_motion_target: InheritableVariable<RootMotionTarget>,
 }
 
 impl AnimationBlendingStateMachine {
@@ -137,6 +202,22 @@ impl AnimationBlendingStateMachine {
     pub fn animation_player(&self) -> Handle<Node> {
         *self.animation_player
     }
+
+    /// Sets new root motion target of the node. See [`RootMotionTarget`] docs for more info.
+    pub fn set_root_motion_target(&mut self, root_motion_target: RootMotionTarget) {
+        self.root_motion_target
+            .set_value_and_mark_modified(root_motion_target);
+    }
+
+    /// Returns current root motion target of the node.
+    pub fn root_motion_target(&self) -> &RootMotionTarget {
+        &self.root_motion_target
+    }
+
+    /// Returns root motion of the final pose of the state machine, blended from root motions of every active animation.
+    pub fn root_motion(&self) -> Option<&RootMotion> {
+        self.machine.pose().root_motion()
+    }
 }
 
 impl TypeUuidProvider for AnimationBlendingStateMachine {
@@ -175,6 +256,18 @@ impl NodeTrait for AnimationBlendingStateMachine {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        let machine = self.machine.get_value_mut_silent();
+
+        if let Some(animation_player) = context
+            .nodes
+            .try_borrow(*self.animation_player)
+            .and_then(|n| n.query_component_ref::<AnimationPlayer>())
+        {
+            // Does nothing, unless the settings of motion matching nodes were changed. Otherwise, the databases are
+            // rebuilt synchronously, which could cause a hitch on the first update of a scene.
+            machine.update_motion_databases(context.nodes, &animation_player.animations);
+        }
+
         if let Some(animation_player) = context
             .nodes
             .try_borrow_mut(*self.animation_player)
@@ -184,12 +277,22 @@ impl NodeTrait for AnimationBlendingStateMachine {
             // do than instead.
             animation_player.set_auto_apply(false);
 
-            let pose = self.machine.get_value_mut_silent().evaluate_pose(
+            machine.evaluate_pose(
                 animation_player.animations.get_value_mut_silent(),
                 context.dt,
             );
+        } else {
+            return;
+        }
+
+        // IK solvers are applied on top of the animation, right before it is applied to the nodes.
+        let pose = machine.solve_ik(context.nodes, context.physics);
 
-            pose.apply_internal(context.nodes);
+        pose.apply_internal(context.nodes);
+
+        if let Some(root_motion) = pose.root_motion() {
+            self.root_motion_target
+                .apply(context.nodes, root_motion, context.dt);
         }
     }
 
@@ -216,6 +319,7 @@ pub struct AnimationBlendingStateMachineBuilder {
     base_builder: BaseBuilder,
     machine: Machine,
     animation_player: Handle<Node>,
+    root_motion_target: RootMotionTarget,
 }
 
 impl AnimationBlendingStateMachineBuilder {
@@ -225,6 +329,7 @@ impl AnimationBlendingStateMachineBuilder {
             base_builder,
             machine: Default::default(),
             animation_player: Default::default(),
+            root_motion_target: Default::default(),
         }
     }
 
@@ -240,12 +345,19 @@ impl AnimationBlendingStateMachineBuilder {
         self
     }
 
+    /// Sets the desired root motion target. See [`RootMotionTarget`] docs for more info.
+    pub fn with_root_motion_target(mut self, root_motion_target: RootMotionTarget) -> Self {
+        self.root_motion_target = root_motion_target;
+        self
+    }
+
     /// Creates new node.
     pub fn build_node(self) -> Node {
         Node::new(AnimationBlendingStateMachine {
             base: self.base_builder.build_base(),
             machine: self.machine.into(),
             animation_player: self.animation_player.into(),
+            root_motion_target: self.root_motion_target.into(),
         })
     }
 
@@ -2,8 +2,9 @@
 //! See [`AnimationPlayer`] docs for more info.
 
 use crate::{
-    animation::AnimationContainer,
+    animation::{AnimationContainer, AnimationSignalAction, AnimationSignalMessage},
     core::{
+        log::Log,
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
@@ -12,15 +13,20 @@ use crate::{
         visitor::prelude::*,
         TypeUuidProvider,
     },
+    resource::model::ModelResourceExtension,
     scene::{
         base::{Base, BaseBuilder},
         graph::Graph,
         node::{Node, NodeTrait, UpdateContext},
+        sound::{SoundBuilder, Status},
+        Scene,
     },
+    script::{RoutingStrategy, ScriptMessageSender},
 };
 use std::ops::{Deref, DerefMut};
 
 pub mod absm;
+pub mod sequencer;
 
 /// Animation player is a node that contains multiple animations. It updates and plays all the animations.
 /// The node could be a source of animations for animation blending state machines. To learn more about
@@ -184,6 +190,113 @@ impl NodeTrait for AnimationPlayer {
     }
 }
 
+// Searches for a bone in the hierarchy of the parent of the animation player. Falls back to the animation player
+// itself if there is no such bone.
+fn find_signal_bone(graph: &Graph, animation_player: Handle<Node>, name: &str) -> Handle<Node> {
+    if name.is_empty() {
+        return animation_player;
+    }
+
+    let root = graph
+        .try_get(animation_player)
+        .map(|player| player.parent())
+        .filter(|parent| parent.is_some())
+        .unwrap_or(animation_player);
+
+    graph
+        .find_by_name(root, name)
+        .map(|(bone, _)| bone)
+        .unwrap_or(animation_player)
+}
+
+/// Performs built-in actions of animation signals (see [`AnimationSignalAction`]) raised since the last call and
+/// sends [`AnimationSignalMessage`] to scripts for every signal that has
+/// [`crate::animation::AnimationSignal::dispatch_to_scripts`] flag set.
+pub(crate) fn process_animation_signals(scene: &mut Scene, message_sender: &ScriptMessageSender) {
+    let mut dispatches = Vec::new();
+    for (animation_player, node) in scene.graph.pair_iter_mut() {
+        if let Some(player) = node.query_component_mut::<AnimationPlayer>() {
+            for (animation, animation_ref) in
+                player.animations.get_value_mut_silent().pair_iter_mut()
+            {
+                for event in animation_ref.take_dispatch_queue() {
+                    if let Some(signal) = animation_ref
+                        .signals()
+                        .iter()
+                        .find(|signal| signal.id == event.signal_id)
+                    {
+                        dispatches.push((
+                            signal.action.clone(),
+                            signal.dispatch_to_scripts,
+                            AnimationSignalMessage {
+                                animation_player,
+                                animation,
+                                event,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    for (action, dispatch_to_scripts, message) in dispatches {
+        match action {
+            AnimationSignalAction::None => (),
+            AnimationSignalAction::PlaySound { buffer, bone, gain } => {
+                if buffer.is_some() {
+                    let bone = find_signal_bone(&scene.graph, message.animation_player, &bone);
+                    let sound = SoundBuilder::new(BaseBuilder::new())
+                        .with_buffer(buffer)
+                        .with_gain(gain)
+                        .with_play_once(true)
+                        .with_status(Status::Playing)
+                        .build(&mut scene.graph);
+                    scene.graph.link_nodes(sound, bone);
+                }
+            }
+            AnimationSignalAction::SpawnPrefab {
+                prefab,
+                bone,
+                attach,
+            } => {
+                if let Some(prefab) = prefab {
+                    // Instantiation requires the prefab to be loaded, signals must not block the
+                    // update waiting for it.
+                    if !prefab.is_ok() {
+                        Log::warn(format!(
+                            "Unable to spawn prefab {} for animation signal {}, \
+                            because the prefab is not loaded.",
+                            prefab.path().display(),
+                            message.event.name
+                        ));
+                    } else {
+                        let bone = find_signal_bone(&scene.graph, message.animation_player, &bone);
+                        if let Some(bone_ref) = scene.graph.try_get(bone) {
+                            let position = bone_ref.global_position();
+                            let rotation = scene.graph.global_rotation(bone);
+                            let instance = prefab.instantiate_at(scene, position, rotation);
+                            if attach {
+                                scene
+                                    .graph
+                                    .link_nodes_keep_global_position_rotation(instance, bone);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if dispatch_to_scripts {
+            message_sender.send_hierarchical(
+                message.animation_player,
+                RoutingStrategy::Up,
+                message,
+            );
+        }
+    }
+}
+
 /// A builder for [`AnimationPlayer`] node.
 pub struct AnimationPlayerBuilder {
     base_builder: BaseBuilder,
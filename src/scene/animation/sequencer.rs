@@ -0,0 +1,697 @@
+//! Sequencer is a node that orchestrates camera cuts, sound cues, events and animations of multiple animation
+//! players on a single timeline. See [`Sequencer`] docs for more info.
+
+use crate::{
+    animation::Animation,
+    core::{
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        animation::AnimationPlayer,
+        base::{Base, BaseBuilder},
+        camera::Camera,
+        graph::{Graph, NodePool},
+        node::{Node, NodeTrait, UpdateContext},
+        sound::Sound,
+        Scene,
+    },
+    script::{RoutingStrategy, ScriptMessageSender},
+};
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Switches the view to the given camera at the given time. The camera stays active until the next cut on the
+/// same track.
+#[derive(Visit, Reflect, Clone, Debug, Default, PartialEq)]
+pub struct CameraCut {
+    /// Time (in seconds) at which the cut happens.
+    pub time: f32,
+    /// A handle of a camera, that will be enabled at the cut. Every other camera of the track will be disabled.
+    pub camera: Handle<Node>,
+}
+
+/// Starts playback of a sound node at the given time.
+#[derive(Visit, Reflect, Clone, Debug, Default, PartialEq)]
+pub struct AudioCue {
+    /// Time (in seconds) at which the sound starts playing.
+    pub time: f32,
+    /// A handle of a sound node.
+    pub sound: Handle<Node>,
+}
+
+/// A named event, that is raised when the playback position of a sequencer passes its time. Events could be
+/// used to call arbitrary game logic from scripts, see [`SequencerEventMessage`] docs.
+#[derive(Visit, Reflect, Clone, Debug, Default, PartialEq)]
+pub struct SequencerEvent {
+    /// Time (in seconds) of the event.
+    pub time: f32,
+    /// Name of the event.
+    pub name: String,
+}
+
+/// Plays an animation of an animation player, starting at the given time. The sequencer takes full control over
+/// the animation while the clip is active - it disables the animation in its animation player and sets its playback
+/// position on its own.
+#[derive(Visit, Reflect, Clone, Debug, Default, PartialEq)]
+pub struct AnimationClip {
+    /// Time (in seconds) at which the animation starts.
+    pub time: f32,
+    /// A handle of an animation player, that contains the animation.
+    pub animation_player: Handle<Node>,
+    /// A handle of an animation in the animation player.
+    pub animation: Handle<Animation>,
+}
+
+/// Kind of a sequencer track with its keys. Keys of every track must be sorted by time.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum SequencerTrackKind {
+    /// A track with camera cuts, see [`CameraCut`].
+    CameraCut(Vec<CameraCut>),
+    /// A track with sound cues, see [`AudioCue`].
+    Audio(Vec<AudioCue>),
+    /// A track with events, see [`SequencerEvent`].
+    Event(Vec<SequencerEvent>),
+    /// A track with animation clips, see [`AnimationClip`].
+    Animation(Vec<AnimationClip>),
+}
+
+impl Default for SequencerTrackKind {
+    fn default() -> Self {
+        Self::Event(Default::default())
+    }
+}
+
+impl SequencerTrackKind {
+    /// Returns an iterator over times of every key of the track.
+    pub fn key_times(&self) -> Box<dyn Iterator<Item = f32> + '_> {
+        match self {
+            SequencerTrackKind::CameraCut(cuts) => Box::new(cuts.iter().map(|c| c.time)),
+            SequencerTrackKind::Audio(cues) => Box::new(cues.iter().map(|c| c.time)),
+            SequencerTrackKind::Event(events) => Box::new(events.iter().map(|e| e.time)),
+            SequencerTrackKind::Animation(clips) => Box::new(clips.iter().map(|c| c.time)),
+        }
+    }
+}
+
+/// A named track of a sequencer.
+#[derive(Visit, Reflect, Clone, Debug, Default, PartialEq)]
+pub struct SequencerTrack {
+    /// Name of the track, it is used only in the editor.
+    pub name: String,
+    /// Disabled tracks are ignored by the sequencer.
+    pub enabled: bool,
+    /// Kind of the track with its keys.
+    pub kind: SequencerTrackKind,
+}
+
+impl SequencerTrack {
+    /// Creates new enabled track of the given kind.
+    pub fn new<N: AsRef<str>>(name: N, kind: SequencerTrackKind) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            enabled: true,
+            kind,
+        }
+    }
+}
+
+/// A message, that is sent to scripts of a sequencer node and its ancestors when an event of the sequencer
+/// is raised. Scripts should subscribe to the message using `ctx.message_dispatcher.subscribe_to::<SequencerEventMessage>(..)`.
+#[derive(Clone, Debug)]
+pub struct SequencerEventMessage {
+    /// A handle of the sequencer, that raised the event.
+    pub sequencer: Handle<Node>,
+    /// The event.
+    pub event: SequencerEvent,
+}
+
+/// Sequencer is a node that orchestrates camera switches, sound cues, script events and animations of multiple
+/// animation players on a single timeline. It is mainly used for cutscenes and other scripted sequences.
+///
+/// # Tracks
+///
+/// A sequencer consists of multiple tracks (see [`SequencerTrack`]) of the following kinds:
+///
+/// - Camera cuts - enables one camera of the track at a time, see [`CameraCut`].
+/// - Audio - starts sound nodes, see [`AudioCue`].
+/// - Events - raises named events, that are sent to scripts as [`SequencerEventMessage`] and could be also fetched
+/// using [`Sequencer::pop_event`].
+/// - Animations - plays animations of animation players, see [`AnimationClip`].
+///
+/// # Playback
+///
+/// The sequencer advances its playback position only while it is playing (see [`Sequencer::play`]). Sound cues and
+/// events are raised only when the playback position passes their time while playing, changing the playback position
+/// manually (for example by scrubbing it in the editor) only updates camera cuts and animation clips.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::pool::Handle,
+///     scene::{
+///         animation::sequencer::{
+///             CameraCut, SequencerBuilder, SequencerEvent, SequencerTrack, SequencerTrackKind,
+///         },
+///         base::BaseBuilder,
+///         graph::Graph,
+///         node::Node,
+///     },
+/// };
+///
+/// fn create_cutscene(
+///     first_camera: Handle<Node>,
+///     second_camera: Handle<Node>,
+///     graph: &mut Graph,
+/// ) -> Handle<Node> {
+///     SequencerBuilder::new(BaseBuilder::new())
+///         .with_tracks(vec![
+///             SequencerTrack::new(
+///                 "Cameras",
+///                 SequencerTrackKind::CameraCut(vec![
+///                     CameraCut {
+///                         time: 0.0,
+///                         camera: first_camera,
+///                     },
+///                     CameraCut {
+///                         time: 2.5,
+///                         camera: second_camera,
+///                     },
+///                 ]),
+///             ),
+///             SequencerTrack::new(
+///                 "Events",
+///                 SequencerTrackKind::Event(vec![SequencerEvent {
+///                     time: 4.0,
+///                     name: "OpenGate".to_string(),
+///                 }]),
+///             ),
+///         ])
+///         .with_length(5.0)
+///         .with_playing(true)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Visit, Reflect, Clone, Debug)]
+pub struct Sequencer {
+    base: Base,
+    tracks: InheritableVariable<Vec<SequencerTrack>>,
+    length: InheritableVariable<f32>,
+    looped: InheritableVariable<bool>,
+    speed: InheritableVariable<f32>,
+    playing: InheritableVariable<bool>,
+    time_position: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    applied_time: Option<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    events: VecDeque<SequencerEvent>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    dispatch_queue: Vec<SequencerEvent>,
+}
+
+impl Default for Sequencer {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            tracks: Default::default(),
+            length: 10.0.into(),
+            looped: false.into(),
+            speed: 1.0.into(),
+            playing: false.into(),
+            time_position: 0.0,
+            applied_time: None,
+            events: Default::default(),
+            dispatch_queue: Default::default(),
+        }
+    }
+}
+
+// Checks whether a key was passed in the given time interval. If the interval is wrapped (`to < from`), it is
+// treated as two intervals: `from..length` and `0..to`.
+fn is_passed(key: f32, from: f32, to: f32, inclusive_end: bool) -> bool {
+    let before_end = |key: f32| {
+        if inclusive_end {
+            key <= to
+        } else {
+            key < to
+        }
+    };
+
+    if from <= to {
+        key >= from && before_end(key)
+    } else {
+        key >= from || before_end(key)
+    }
+}
+
+fn apply_animation_clip(nodes: &mut NodePool, clip: &AnimationClip, time: f32, last_time: f32) {
+    let pose = if let Some(animation) = nodes
+        .try_borrow_mut(clip.animation_player)
+        .and_then(|n| n.query_component_mut::<AnimationPlayer>())
+        .and_then(|p| {
+            p.animations_mut()
+                .get_value_mut_silent()
+                .try_get_mut(clip.animation)
+        }) {
+        let length = animation.length();
+        let is_active = |local_time: f32| local_time >= 0.0 && local_time <= length;
+
+        let local_time = time - clip.time;
+        if !is_active(local_time) && !is_active(last_time - clip.time) {
+            return;
+        }
+
+        // The clip was active on previous update, but now it is out of its bounds. In this case the pose
+        // at the closest bound is applied, so the animated nodes will be left in their final state.
+        let local_time = local_time.clamp(0.0, length);
+
+        animation.set_enabled(false);
+        animation.set_time_position(animation.time_slice().start + local_time);
+        // Zero time step calculates the pose at current position without advancing the animation.
+        animation.tick(0.0);
+        animation.pose().clone()
+    } else {
+        return;
+    };
+
+    pose.apply_internal(nodes);
+}
+
+impl Sequencer {
+    /// Returns a reference to the tracks of the sequencer.
+    pub fn tracks(&self) -> &[SequencerTrack] {
+        &self.tracks
+    }
+
+    /// Returns a reference to the tracks of the sequencer. Keep in mind that mutable access to [`InheritableVariable`]
+    /// may have side effects if used inappropriately. Checks docs for [`InheritableVariable`] for more info.
+    pub fn tracks_mut(&mut self) -> &mut InheritableVariable<Vec<SequencerTrack>> {
+        &mut self.tracks
+    }
+
+    /// Sets new tracks of the sequencer.
+    pub fn set_tracks(&mut self, tracks: Vec<SequencerTrack>) {
+        self.tracks.set_value_and_mark_modified(tracks);
+    }
+
+    /// Sets total length of the sequence in seconds.
+    pub fn set_length(&mut self, length: f32) {
+        self.length.set_value_and_mark_modified(length.max(0.0));
+    }
+
+    /// Returns total length of the sequence in seconds.
+    pub fn length(&self) -> f32 {
+        *self.length
+    }
+
+    /// Defines whether the sequence should start from the beginning when it reaches its end or not.
+    pub fn set_looped(&mut self, looped: bool) {
+        self.looped.set_value_and_mark_modified(looped);
+    }
+
+    /// Returns `true` if the sequence is looped, `false` - otherwise.
+    pub fn is_looped(&self) -> bool {
+        *self.looped
+    }
+
+    /// Sets playback speed of the sequence. Negative values are clamped to zero.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed.set_value_and_mark_modified(speed.max(0.0));
+    }
+
+    /// Returns playback speed of the sequence.
+    pub fn speed(&self) -> f32 {
+        *self.speed
+    }
+
+    /// Enables or disables playback of the sequence.
+    pub fn set_playing(&mut self, playing: bool) {
+        self.playing.set_value_and_mark_modified(playing);
+    }
+
+    /// Returns `true` if the sequence is playing, `false` - otherwise.
+    pub fn is_playing(&self) -> bool {
+        *self.playing
+    }
+
+    /// Starts playback of the sequence from current position.
+    pub fn play(&mut self) {
+        self.set_playing(true);
+    }
+
+    /// Stops playback of the sequence and rewinds it to the beginning.
+    pub fn stop(&mut self) {
+        self.set_playing(false);
+        self.rewind();
+    }
+
+    /// Rewinds the sequence to the beginning.
+    pub fn rewind(&mut self) {
+        self.set_time_position(0.0);
+    }
+
+    /// Sets new playback position of the sequence. Sound cues and events are not raised, the state of camera cuts
+    /// and animation clips will be updated on next update of the node.
+    pub fn set_time_position(&mut self, time: f32) {
+        self.time_position = time.clamp(0.0, *self.length);
+    }
+
+    /// Returns current playback position of the sequence.
+    pub fn time_position(&self) -> f32 {
+        self.time_position
+    }
+
+    /// Returns `true` if the sequence has reached its end and it is not looped.
+    pub fn has_ended(&self) -> bool {
+        !*self.looped && self.time_position >= *self.length
+    }
+
+    /// Pops an event from the queue of raised events. The queue is limited to 32 events, so events should be
+    /// fetched every frame if this method is used.
+    pub fn pop_event(&mut self) -> Option<SequencerEvent> {
+        self.events.pop_front()
+    }
+
+    /// Takes events, that should be sent to scripts.
+    pub(crate) fn take_dispatch_queue(&mut self) -> Vec<SequencerEvent> {
+        std::mem::take(&mut self.dispatch_queue)
+    }
+
+    fn apply(&mut self, nodes: &mut NodePool, prev_time: Option<f32>, inclusive_end: bool) {
+        let time = self.time_position;
+
+        for track in self.tracks.iter().filter(|t| t.enabled) {
+            match track.kind {
+                SequencerTrackKind::CameraCut(ref cuts) => {
+                    let active_camera = cuts
+                        .iter()
+                        .rev()
+                        .find(|c| c.time <= time)
+                        .map(|c| c.camera)
+                        .unwrap_or_default();
+
+                    for cut in cuts {
+                        if let Some(camera) = nodes
+                            .try_borrow_mut(cut.camera)
+                            .and_then(|n| n.query_component_mut::<Camera>())
+                        {
+                            let enabled = cut.camera == active_camera;
+                            if camera.is_enabled() != enabled {
+                                camera.set_enabled(enabled);
+                            }
+                        }
+                    }
+                }
+                SequencerTrackKind::Audio(ref cues) => {
+                    if let Some(prev_time) = prev_time {
+                        for cue in cues {
+                            if is_passed(cue.time, prev_time, time, inclusive_end) {
+                                if let Some(sound) = nodes
+                                    .try_borrow_mut(cue.sound)
+                                    .and_then(|n| n.query_component_mut::<Sound>())
+                                {
+                                    sound.stop();
+                                    sound.play();
+                                }
+                            }
+                        }
+                    }
+                }
+                SequencerTrackKind::Event(ref events) => {
+                    if let Some(prev_time) = prev_time {
+                        for event in events {
+                            if is_passed(event.time, prev_time, time, inclusive_end) {
+                                // Both queues are limited, the dispatch queue is drained only by the
+                                // engine and it must not grow when nothing consumes it.
+                                if self.dispatch_queue.len() < 32 {
+                                    self.dispatch_queue.push(event.clone());
+                                }
+                                if self.events.len() < 32 {
+                                    self.events.push_back(event.clone());
+                                }
+                            }
+                        }
+                    }
+                }
+                SequencerTrackKind::Animation(ref clips) => {
+                    let last_time = self.applied_time.unwrap_or(time);
+                    for clip in clips {
+                        apply_animation_clip(nodes, clip, time, last_time);
+                    }
+                }
+            }
+        }
+
+        self.applied_time = Some(time);
+    }
+
+    /// Advances the sequence by the given time step and applies its state to the nodes in the given pool. This
+    /// method is low level, you should not use it in normal circumstances - the engine will call it for you.
+    pub fn tick(&mut self, nodes: &mut NodePool, dt: f32) {
+        if *self.playing {
+            let prev_time = self.time_position;
+            let length = *self.length;
+            let mut new_time = prev_time + dt * *self.speed;
+            let mut inclusive_end = false;
+
+            if new_time >= length {
+                if *self.looped && length > 0.0 {
+                    new_time %= length;
+                } else {
+                    new_time = length;
+                    inclusive_end = true;
+                    self.playing.set_value_silent(false);
+                }
+            }
+
+            self.time_position = new_time;
+
+            self.apply(nodes, Some(prev_time), inclusive_end);
+        } else if self.applied_time != Some(self.time_position) {
+            self.apply(nodes, None, false);
+        }
+    }
+}
+
+impl TypeUuidProvider for Sequencer {
+    fn type_uuid() -> Uuid {
+        uuid!("0f7e0d5b-6a39-4a3c-8f0a-9a3f0c0fd2a1")
+    }
+}
+
+impl Deref for Sequencer {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Sequencer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl NodeTrait for Sequencer {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        self.tick(context.nodes, context.dt);
+    }
+}
+
+/// Sends [`SequencerEventMessage`] to scripts for every sequencer event raised since the last call.
+pub(crate) fn process_sequencer_events(scene: &mut Scene, message_sender: &ScriptMessageSender) {
+    for (sequencer, node) in scene.graph.pair_iter_mut() {
+        if let Some(sequencer_ref) = node.query_component_mut::<Sequencer>() {
+            for event in sequencer_ref.take_dispatch_queue() {
+                message_sender.send_hierarchical(
+                    sequencer,
+                    RoutingStrategy::Up,
+                    SequencerEventMessage { sequencer, event },
+                );
+            }
+        }
+    }
+}
+
+/// A builder for [`Sequencer`] node.
+pub struct SequencerBuilder {
+    base_builder: BaseBuilder,
+    tracks: Vec<SequencerTrack>,
+    length: f32,
+    looped: bool,
+    speed: f32,
+    playing: bool,
+}
+
+impl SequencerBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            tracks: Default::default(),
+            length: 10.0,
+            looped: false,
+            speed: 1.0,
+            playing: false,
+        }
+    }
+
+    /// Sets desired tracks.
+    pub fn with_tracks(mut self, tracks: Vec<SequencerTrack>) -> Self {
+        self.tracks = tracks;
+        self
+    }
+
+    /// Sets total length of the sequence in seconds.
+    pub fn with_length(mut self, length: f32) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Sets whether the sequence is looped or not.
+    pub fn with_looped(mut self, looped: bool) -> Self {
+        self.looped = looped;
+        self
+    }
+
+    /// Sets playback speed of the sequence.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets whether the sequence should play or not.
+    pub fn with_playing(mut self, playing: bool) -> Self {
+        self.playing = playing;
+        self
+    }
+
+    /// Creates an instance of [`Sequencer`] node.
+    pub fn build_node(self) -> Node {
+        Node::new(Sequencer {
+            base: self.base_builder.build_base(),
+            tracks: self.tracks.into(),
+            length: self.length.max(0.0).into(),
+            looped: self.looped.into(),
+            speed: self.speed.max(0.0).into(),
+            playing: self.playing.into(),
+            time_position: 0.0,
+            applied_time: None,
+            events: Default::default(),
+            dispatch_queue: Default::default(),
+        })
+    }
+
+    /// Creates an instance of [`Sequencer`] node and adds it to the given scene graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{
+        animation::sequencer::{
+            CameraCut, Sequencer, SequencerBuilder, SequencerEvent, SequencerTrack,
+            SequencerTrackKind,
+        },
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder},
+        graph::Graph,
+    };
+
+    #[test]
+    fn test_sequencer_camera_cuts_and_events() {
+        let mut graph = Graph::new();
+        let first = CameraBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let second = CameraBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut sequencer = SequencerBuilder::new(BaseBuilder::new())
+            .with_tracks(vec![
+                SequencerTrack::new(
+                    "Cameras",
+                    SequencerTrackKind::CameraCut(vec![
+                        CameraCut {
+                            time: 0.0,
+                            camera: first,
+                        },
+                        CameraCut {
+                            time: 1.0,
+                            camera: second,
+                        },
+                    ]),
+                ),
+                SequencerTrack::new(
+                    "Events",
+                    SequencerTrackKind::Event(vec![
+                        SequencerEvent {
+                            time: 0.0,
+                            name: "Start".to_string(),
+                        },
+                        SequencerEvent {
+                            time: 2.0,
+                            name: "End".to_string(),
+                        },
+                    ]),
+                ),
+            ])
+            .with_length(2.0)
+            .with_playing(true)
+            .build_node();
+
+        let sequencer = sequencer.query_component_mut::<Sequencer>().unwrap();
+
+        let is_enabled =
+            |graph: &Graph, camera| graph[camera].cast::<Camera>().unwrap().is_enabled();
+
+        sequencer.tick(graph.node_pool_mut(), 0.5);
+        assert!(is_enabled(&graph, first));
+        assert!(!is_enabled(&graph, second));
+        assert_eq!(sequencer.pop_event().unwrap().name, "Start");
+        assert!(sequencer.pop_event().is_none());
+
+        sequencer.tick(graph.node_pool_mut(), 1.0);
+        assert!(!is_enabled(&graph, first));
+        assert!(is_enabled(&graph, second));
+        assert!(sequencer.pop_event().is_none());
+
+        // The end of a non-looped sequence is inclusive, playback stops there.
+        sequencer.tick(graph.node_pool_mut(), 1.0);
+        assert_eq!(sequencer.pop_event().unwrap().name, "End");
+        assert!(sequencer.has_ended());
+        assert!(!sequencer.is_playing());
+
+        // Scrubbing back does not raise events, but updates camera cuts.
+        sequencer.set_time_position(0.25);
+        sequencer.tick(graph.node_pool_mut(), 1.0);
+        assert!(is_enabled(&graph, first));
+        assert!(sequencer.pop_event().is_none());
+    }
+}
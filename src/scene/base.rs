@@ -318,6 +318,14 @@ pub struct Base {
     #[reflect(hidden)]
     pub(crate) script_message_sender: Option<Sender<NodeScriptMessage>>,
 
+    // Used to notify component indices of the graph, that the set of script components was changed.
+    #[reflect(hidden)]
+    pub(crate) component_change_sender: Option<Sender<Handle<Node>>>,
+
+    // Used to notify tag and group indices of the graph, that tags or groups of the node were changed.
+    #[reflect(hidden)]
+    pub(crate) tag_change_sender: Option<Sender<Handle<Node>>>,
+
     // Name is not inheritable, because property inheritance works bad with external 3D models.
     // They use names to search "original" nodes.
     #[reflect(setter = "set_name_internal")]
@@ -346,6 +354,19 @@ pub struct Base {
     #[reflect(setter = "set_tag")]
     tag: InheritableVariable<String>,
 
+    #[reflect(
+        setter = "set_tags",
+        description = "A set of tags of the node. Tags are inherited from prefabs and can be used to find nodes in a graph."
+    )]
+    tags: InheritableVariable<Vec<String>>,
+
+    // Groups are not inheritable, they're assigned per scene so a group can contain nodes of any prefab.
+    #[reflect(
+        setter = "set_groups",
+        description = "A set of named groups the node belongs to. Groups can be used to find nodes in a graph."
+    )]
+    groups: Vec<String>,
+
     #[reflect(setter = "set_cast_shadows")]
     cast_shadows: InheritableVariable<bool>,
 
@@ -411,6 +432,12 @@ pub struct Base {
 
     #[reflect(hidden)]
     pub(crate) global_enabled: Cell<bool>,
+
+    #[reflect(
+        setter = "set_saveable",
+        description = "Whether the state of the node should be stored in save files or not."
+    )]
+    saveable: InheritableVariable<bool>,
 }
 
 impl Drop for Base {
@@ -692,7 +719,111 @@ impl Base {
     /// Sets new tag.
     #[inline]
     pub fn set_tag(&mut self, tag: String) -> String {
-        self.tag.set_value_and_mark_modified(tag)
+        let prev = self.tag.set_value_and_mark_modified(tag);
+        self.notify_tags_changed();
+        prev
+    }
+
+    /// Returns a set of tags of the node. Unlike [`Self::tag`], a node can have any number of tags.
+    /// Use [`crate::scene::graph::Graph::nodes_with_tag`] to find every node with a particular tag.
+    #[inline]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Sets a new set of tags of the node. Duplicated tags are removed.
+    #[inline]
+    pub fn set_tags(&mut self, mut tags: Vec<String>) -> Vec<String> {
+        dedup(&mut tags);
+        let prev = self.tags.set_value_and_mark_modified(tags);
+        self.notify_tags_changed();
+        prev
+    }
+
+    /// Adds a new tag to the node. Does nothing if the node already has the tag.
+    #[inline]
+    pub fn add_tag<T: AsRef<str>>(&mut self, tag: T) {
+        let tag = tag.as_ref();
+        if !self.tags.iter().any(|t| t == tag) {
+            self.tags
+                .get_value_mut_and_mark_modified()
+                .push(tag.to_owned());
+            self.notify_tags_changed();
+        }
+    }
+
+    /// Removes the tag from the node. Returns `true` if the node had the tag.
+    #[inline]
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        match self.tags.iter().position(|t| t == tag) {
+            Some(position) => {
+                self.tags.get_value_mut_and_mark_modified().remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the node has the tag. Single [`Self::tag`] is checked as well.
+    #[inline]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        (!tag.is_empty() && *self.tag == tag) || self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns a set of groups the node belongs to. Use [`crate::scene::graph::Graph::nodes_in_group`]
+    /// to find every node of a group.
+    #[inline]
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+
+    /// Sets a new set of groups of the node. Duplicated groups are removed.
+    ///
+    /// # Important notes
+    ///
+    /// Groups are not inherited from prefabs, it allows you to put nodes of different prefab
+    /// instances into the same group.
+    #[inline]
+    pub fn set_groups(&mut self, mut groups: Vec<String>) -> Vec<String> {
+        dedup(&mut groups);
+        let prev = std::mem::replace(&mut self.groups, groups);
+        self.notify_tags_changed();
+        prev
+    }
+
+    /// Adds the node to a group. Does nothing if the node is already in the group.
+    #[inline]
+    pub fn add_to_group<G: AsRef<str>>(&mut self, group: G) {
+        let group = group.as_ref();
+        if !self.is_in_group(group) {
+            self.groups.push(group.to_owned());
+            self.notify_tags_changed();
+        }
+    }
+
+    /// Removes the node from the group. Returns `true` if the node was in the group.
+    #[inline]
+    pub fn remove_from_group(&mut self, group: &str) -> bool {
+        match self.groups.iter().position(|g| g == group) {
+            Some(position) => {
+                self.groups.remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the node belongs to the group.
+    #[inline]
+    pub fn is_in_group(&self, group: &str) -> bool {
+        self.groups.iter().any(|g| g == group)
+    }
+
+    fn notify_tags_changed(&self) {
+        if let Some(sender) = self.tag_change_sender.as_ref() {
+            // The graph could be destroyed already, nothing to notify then.
+            let _ = sender.send(self.self_handle);
+        }
     }
 
     /// Return the frustum_culling flag
@@ -766,10 +897,22 @@ impl Base {
                 }));
             }
         }
+        self.notify_components_changed();
     }
 
     fn set_script_internal(&mut self, script: Option<Script>) -> Option<Script> {
-        std::mem::replace(&mut self.script, script)
+        let prev = std::mem::replace(&mut self.script, script);
+        self.notify_components_changed();
+        prev
+    }
+
+    fn notify_components_changed(&self) {
+        if self.script.is_some() {
+            if let Some(sender) = self.component_change_sender.as_ref() {
+                // The graph could be destroyed already, nothing to notify then.
+                let _ = sender.send(self.self_handle);
+            }
+        }
     }
 
     /// Checks if the node has a script of a particular type. Returns `false` if there is no script
@@ -865,6 +1008,19 @@ impl Base {
         *self.enabled
     }
 
+    /// Defines whether the state of the node should be stored in save files or not. See
+    /// [`crate::scene::save`] module docs for more info.
+    #[inline]
+    pub fn set_saveable(&mut self, saveable: bool) -> bool {
+        self.saveable.set_value_and_mark_modified(saveable)
+    }
+
+    /// Returns `true` if the state of the node should be stored in save files, `false` - otherwise.
+    #[inline]
+    pub fn is_saveable(&self) -> bool {
+        *self.saveable
+    }
+
     /// Returns `true` if the node and every parent up in hierarchy is enabled, `false` - otherwise. This method
     /// returns "true" `enabled` flag. Its value could be different from the value returned by [`Self::is_enabled`].
     #[inline]
@@ -899,6 +1055,18 @@ impl Base {
     }
 }
 
+// Removes duplicates and empty strings while preserving the order.
+fn dedup(names: &mut Vec<String>) {
+    let mut i = 0;
+    while i < names.len() {
+        if names[i].is_empty() || names[..i].contains(&names[i]) {
+            names.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
 impl Default for Base {
     fn default() -> Self {
         BaseBuilder::new().build_base()
@@ -906,7 +1074,11 @@ impl Default for Base {
 }
 
 // Serializes Option<Script> using given serializer.
-fn visit_opt_script(name: &str, script: &mut Option<Script>, visitor: &mut Visitor) -> VisitResult {
+pub(crate) fn visit_opt_script(
+    name: &str,
+    script: &mut Option<Script>,
+    visitor: &mut Visitor,
+) -> VisitResult {
     let mut region = visitor.enter_region(name)?;
 
     let mut script_type_uuid = script.as_ref().map(|s| s.id()).unwrap_or_default();
@@ -968,11 +1140,14 @@ impl Visit for Base {
         self.original_handle_in_resource
             .visit("Original", &mut region)?;
         self.tag.visit("Tag", &mut region)?;
+        let _ = self.tags.visit("Tags", &mut region);
+        let _ = self.groups.visit("Groups", &mut region);
         let _ = self.properties.visit("Properties", &mut region);
         let _ = self.frustum_culling.visit("FrustumCulling", &mut region);
         let _ = self.cast_shadows.visit("CastShadows", &mut region);
         let _ = self.instance_id.visit("InstanceId", &mut region);
         let _ = self.enabled.visit("Enabled", &mut region);
+        let _ = self.saveable.visit("Saveable", &mut region);
 
         // Script visiting may fail for various reasons:
         //
@@ -1006,11 +1181,14 @@ pub struct BaseBuilder {
     mobility: Mobility,
     inv_bind_pose_transform: Matrix4<f32>,
     tag: String,
+    tags: Vec<String>,
+    groups: Vec<String>,
     frustum_culling: bool,
     cast_shadows: bool,
     script: Option<Script>,
     instance_id: InstanceId,
     enabled: bool,
+    saveable: bool,
 }
 
 impl Default for BaseBuilder {
@@ -1034,11 +1212,14 @@ impl BaseBuilder {
             mobility: Mobility::Dynamic,
             inv_bind_pose_transform: Matrix4::identity(),
             tag: Default::default(),
+            tags: Default::default(),
+            groups: Default::default(),
             frustum_culling: true,
             cast_shadows: true,
             script: None,
             instance_id: InstanceId(Uuid::new_v4()),
             enabled: true,
+            saveable: false,
         }
     }
 
@@ -1083,6 +1264,12 @@ impl BaseBuilder {
         self
     }
 
+    /// Defines whether the state of the node should be stored in save files or not.
+    pub fn with_saveable(mut self, saveable: bool) -> Self {
+        self.saveable = saveable;
+        self
+    }
+
     /// Sets desired list of children nodes.
     #[inline]
     pub fn with_children<'a, I: IntoIterator<Item = &'a Handle<Node>>>(
@@ -1125,6 +1312,20 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets desired set of tags.
+    #[inline]
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets desired set of groups.
+    #[inline]
+    pub fn with_groups(mut self, groups: Vec<String>) -> Self {
+        self.groups = groups;
+        self
+    }
+
     /// Sets desired frustum_culling flag.
     #[inline]
     pub fn with_frustum_culling(mut self, frustum_culling: bool) -> Self {
@@ -1158,6 +1359,8 @@ impl BaseBuilder {
         Base {
             self_handle: Default::default(),
             script_message_sender: None,
+            component_change_sender: None,
+            tag_change_sender: None,
             name: self.name,
             children: self.children,
             local_transform: self.local_transform,
@@ -1174,6 +1377,16 @@ impl BaseBuilder {
             lod_group: self.lod_group.into(),
             mobility: self.mobility.into(),
             tag: self.tag.into(),
+            tags: {
+                let mut tags = self.tags;
+                dedup(&mut tags);
+                tags.into()
+            },
+            groups: {
+                let mut groups = self.groups;
+                dedup(&mut groups);
+                groups
+            },
             properties: Default::default(),
             transform_modified: Cell::new(false),
             frustum_culling: self.frustum_culling.into(),
@@ -1182,6 +1395,7 @@ impl BaseBuilder {
             instance_id: InstanceId(Uuid::new_v4()),
             enabled: self.enabled.into(),
             global_enabled: Cell::new(true),
+            saveable: self.saveable.into(),
         }
     }
 }
@@ -200,6 +200,7 @@ pub struct IntersectionPair {
     pub has_any_active_contact: bool,
 }
 
+#[derive(Clone)]
 pub(super) struct Container<S, A>
 where
     A: Hash + Eq + Clone,
@@ -352,6 +353,28 @@ pub struct PhysicsWorld {
     debug_render_pipeline: Mutex<DebugRenderPipeline>,
 }
 
+/// An in-memory copy of the complete state of a 2D [`PhysicsWorld`].
+#[derive(Clone)]
+pub struct PhysicsWorldSnapshot {
+    enabled: bool,
+    integration_parameters: IntegrationParameters,
+    gravity: Vector2<f32>,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    ccd_solver: CCDSolver,
+    islands: IslandManager,
+    bodies: Container<RigidBodySet, RigidBodyHandle>,
+    colliders: Container<ColliderSet, ColliderHandle>,
+    joints: Container<ImpulseJointSet, ImpulseJointHandle>,
+    multibody_joints: Container<MultibodyJointSet, MultibodyJointHandle>,
+}
+
+impl Debug for PhysicsWorldSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PhysicsWorldSnapshot")
+    }
+}
+
 fn isometry_from_global_transform(transform: &Matrix4<f32>) -> Isometry2<f32> {
     Isometry2 {
         translation: Translation2::new(transform[12], transform[13]),
@@ -416,11 +439,17 @@ impl PhysicsWorld {
     }
 
     pub(crate) fn update(&mut self, dt: f32) {
+        self.step(self.integration_parameters.dt.unwrap_or(dt));
+    }
+
+    /// Performs exactly one simulation step using the given time step, ignoring
+    /// [`IntegrationParameters::dt`].
+    pub(crate) fn step(&mut self, dt: f32) {
         let time = instant::Instant::now();
 
         if self.enabled {
             let integration_parameters = rapier2d::dynamics::IntegrationParameters {
-                dt: self.integration_parameters.dt.unwrap_or(dt),
+                dt,
                 min_ccd_dt: self.integration_parameters.min_ccd_dt,
                 erp: self.integration_parameters.erp,
                 damping_ratio: self.integration_parameters.damping_ratio,
@@ -468,6 +497,42 @@ impl PhysicsWorld {
         self.performance_statistics.step_time += instant::Instant::now() - time;
     }
 
+    /// Creates a snapshot of the entire simulation state. See
+    /// [`crate::scene::graph::physics::PhysicsWorld::snapshot`] for more info.
+    pub fn snapshot(&self) -> PhysicsWorldSnapshot {
+        PhysicsWorldSnapshot {
+            enabled: self.enabled,
+            integration_parameters: self.integration_parameters,
+            gravity: self.gravity,
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            islands: self.islands.clone(),
+            bodies: self.bodies.clone(),
+            colliders: self.colliders.clone(),
+            joints: self.joints.clone(),
+            multibody_joints: self.multibody_joints.clone(),
+        }
+    }
+
+    /// Restores the simulation state from the given snapshot. See
+    /// [`crate::scene::graph::physics::PhysicsWorld::restore_snapshot`] for more info.
+    pub fn restore_snapshot(&mut self, snapshot: &PhysicsWorldSnapshot) {
+        self.enabled = snapshot.enabled;
+        self.integration_parameters = snapshot.integration_parameters;
+        self.gravity = snapshot.gravity;
+        self.broad_phase = snapshot.broad_phase.clone();
+        self.narrow_phase = snapshot.narrow_phase.clone();
+        self.ccd_solver = snapshot.ccd_solver.clone();
+        self.islands = snapshot.islands.clone();
+        self.bodies = snapshot.bodies.clone();
+        self.colliders = snapshot.colliders.clone();
+        self.joints = snapshot.joints.clone();
+        self.multibody_joints = snapshot.multibody_joints.clone();
+        self.pipeline = PhysicsPipeline::new();
+        *self.query.borrow_mut() = Default::default();
+    }
+
     pub(crate) fn add_body(&mut self, owner: Handle<Node>, body: RigidBody) -> RigidBodyHandle {
         let handle = self.bodies.set.insert(body);
         self.bodies.map.insert(handle, owner);
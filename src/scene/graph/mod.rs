@@ -25,7 +25,7 @@
 use crate::{
     asset::ResourceStateRef,
     core::{
-        algebra::{Matrix4, Rotation3, UnitQuaternion, Vector2, Vector3},
+        algebra::{Matrix3, Matrix4, Rotation3, UnitQuaternion, Vector2, Vector3},
         instant,
         log::{Log, MessageKind},
         math::Matrix4Ext,
@@ -38,17 +38,20 @@ use crate::{
     resource::model::{ModelResource, ModelResourceExtension, NodeMapping},
     scene::{
         self,
-        base::NodeScriptMessage,
+        base::{Base, NodeScriptMessage},
         camera::Camera,
         dim2::{self},
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
             map::NodeHandleMap,
-            physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            physics::{PhysicsPerformanceStatistics, PhysicsWorld, PhysicsWorldSnapshot},
+            query::{ComponentIndex, Query, QueryIterator},
+            tags::TagIndex,
         },
         mesh::Mesh,
         node::{container::NodeContainer, Node, NodeTrait, SyncContext, UpdateContext},
         pivot::Pivot,
+        rigidbody::{RigidBody, RigidBodyType},
         sound::context::SoundContext,
         transform::TransformBuilder,
     },
@@ -57,7 +60,7 @@ use crate::{
 use fxhash::FxHashSet;
 use rapier3d::geometry::ColliderHandle;
 use std::{
-    any::Any,
+    any::{Any, TypeId},
     fmt::Debug,
     ops::{Index, IndexMut},
     sync::mpsc::{channel, Receiver, Sender},
@@ -67,6 +70,8 @@ use std::{
 pub mod event;
 pub mod map;
 pub mod physics;
+pub mod query;
+pub mod tags;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
 /// parts takes the most time to update.
@@ -137,6 +142,18 @@ pub struct Graph {
     pub(crate) script_message_sender: Sender<NodeScriptMessage>,
     #[reflect(hidden)]
     pub(crate) script_message_receiver: Receiver<NodeScriptMessage>,
+
+    // Global transforms of rigid bodies before the last fixed physics step.
+    #[reflect(hidden)]
+    physics_interpolation: Vec<(Handle<Node>, Matrix4<f32>)>,
+
+    // Per-type indices of node components, see `Graph::query`.
+    #[reflect(hidden)]
+    pub(crate) component_index: ComponentIndex,
+
+    // Indices of node tags and groups, see `Graph::nodes_with_tag` and `Graph::nodes_in_group`.
+    #[reflect(hidden)]
+    pub(crate) tag_index: TagIndex,
 }
 
 impl Default for Graph {
@@ -154,6 +171,9 @@ impl Default for Graph {
             event_broadcaster: Default::default(),
             script_message_receiver: rx,
             script_message_sender: tx,
+            physics_interpolation: Default::default(),
+            component_index: Default::default(),
+            tag_index: Default::default(),
         }
     }
 }
@@ -213,6 +233,15 @@ fn clear_links(mut node: Node) -> Node {
     node
 }
 
+/// A snapshot of both physics worlds of a graph. See [`Graph::physics_snapshot`] for more info.
+#[derive(Clone, Debug)]
+pub struct GraphPhysicsSnapshot {
+    /// A snapshot of the 3D physics world.
+    pub physics: PhysicsWorldSnapshot,
+    /// A snapshot of the 2D physics world.
+    pub physics2d: dim2::physics::PhysicsWorldSnapshot,
+}
+
 /// A set of switches that allows you to disable a particular step of graph update pipeline.
 #[derive(Clone, PartialEq, Eq)]
 pub struct GraphUpdateSwitches {
@@ -243,10 +272,14 @@ impl Graph {
     #[inline]
     pub fn new() -> Self {
         let (tx, rx) = channel();
+        let component_index = ComponentIndex::default();
+        let tag_index = TagIndex::default();
 
         // Create root node.
         let mut root_node = Pivot::default();
         root_node.script_message_sender = Some(tx.clone());
+        root_node.component_change_sender = Some(component_index.sender.clone());
+        root_node.tag_change_sender = Some(tag_index.sender.clone());
         root_node.set_name("__ROOT__");
 
         // Add it to the pool.
@@ -265,6 +298,9 @@ impl Graph {
             event_broadcaster: Default::default(),
             script_message_receiver: rx,
             script_message_sender: tx,
+            physics_interpolation: Default::default(),
+            component_index,
+            tag_index,
         }
     }
 
@@ -331,9 +367,15 @@ impl Graph {
         }
 
         let sender = self.script_message_sender.clone();
-        let node = &mut self[handle];
+        let component_change_sender = self.component_index.sender.clone();
+        let tag_change_sender = self.tag_index.sender.clone();
+        let node = &mut self.pool[handle];
         node.self_handle = handle;
         node.script_message_sender = Some(sender);
+        node.component_change_sender = Some(component_change_sender);
+        node.tag_change_sender = Some(tag_change_sender);
+        self.component_index.on_node_added(handle, node);
+        self.tag_index.mark_changed(handle);
 
         handle
     }
@@ -371,6 +413,18 @@ impl Graph {
         self.root
     }
 
+    /// Returns a reference to the internal pool of nodes.
+    #[inline]
+    pub(crate) fn node_pool(&self) -> &NodePool {
+        &self.pool
+    }
+
+    /// Returns a reference to the internal pool of nodes.
+    #[inline]
+    pub(crate) fn node_pool_mut(&mut self) -> &mut NodePool {
+        &mut self.pool
+    }
+
     /// Tries to borrow a node, returns Some(node) if the handle is valid, None - otherwise.
     #[inline]
     pub fn try_get(&self, handle: Handle<Node>) -> Option<&Node> {
@@ -452,6 +506,7 @@ impl Graph {
     pub fn remove_node(&mut self, node_handle: Handle<Node>) {
         self.unlink_internal(node_handle);
 
+        let mut removed = Vec::new();
         self.stack.clear();
         self.stack.push(node_handle);
         while let Some(handle) = self.stack.pop() {
@@ -465,7 +520,12 @@ impl Graph {
 
             self.event_broadcaster
                 .broadcast(GraphEvent::Removed(handle));
+
+            removed.push(handle);
         }
+
+        self.component_index.on_nodes_removed(&removed);
+        self.tag_index.on_nodes_removed(&removed);
     }
 
     fn unlink_internal(&mut self, node_handle: Handle<Node>) {
@@ -659,6 +719,45 @@ impl Graph {
         self.find_by_name(self.root, name)
     }
 
+    /// Returns handles of every node that has the given tag (see [`Base::has_tag`]). The graph keeps an
+    /// index of tags, so the method does not check every node of the graph. Handles are sorted by their
+    /// indices.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fyrox::{
+    ///     core::pool::Handle,
+    ///     scene::{graph::Graph, node::Node},
+    /// };
+    ///
+    /// fn spawn_points(graph: &Graph) -> Vec<Handle<Node>> {
+    ///     graph.nodes_with_tag("SpawnPoint")
+    /// }
+    /// ```
+    #[inline]
+    pub fn nodes_with_tag(&self, tag: &str) -> Vec<Handle<Node>> {
+        self.tag_index.nodes_with_tag(tag, &self.pool)
+    }
+
+    /// Returns handles of every node that belongs to the given group (see [`Base::is_in_group`]). Unlike
+    /// tags, groups are not inherited from prefabs, so a group could contain nodes of different prefab
+    /// instances. Handles are sorted by their indices.
+    #[inline]
+    pub fn nodes_in_group(&self, group: &str) -> Vec<Handle<Node>> {
+        self.tag_index.nodes_in_group(group, &self.pool)
+    }
+
+    /// Tells the indices of the graph (component indices as well as tag and group indices), that the
+    /// node might have been changed bypassing its setters, for example by writing its tags using
+    /// reflection. The node will be indexed again on the next lookup. The method could be called
+    /// before or after the change, but before the next lookup.
+    #[inline]
+    pub fn mark_node_changed(&self, handle: Handle<Node>) {
+        self.component_index.mark_changed(handle);
+        self.tag_index.mark_changed(handle);
+    }
+
     /// Searches for a **first** node with a script of the given type `S` in the hierarchy starting from the
     /// given `root_node`.
     #[inline]
@@ -1038,6 +1137,8 @@ impl Graph {
         for (handle, node) in self.pool.pair_iter_mut() {
             node.self_handle = handle;
             node.script_message_sender = Some(self.script_message_sender.clone());
+            node.component_change_sender = Some(self.component_index.sender.clone());
+            node.tag_change_sender = Some(self.tag_index.sender.clone());
         }
     }
 
@@ -1060,6 +1161,8 @@ impl Graph {
         self.update_hierarchical_data();
         let instances = self.restore_integrity();
         self.remap_handles(&instances);
+        // Tags could be inherited from prefabs.
+        self.tag_index.invalidate();
 
         // Update cube maps for sky boxes.
         for node in self.linear_iter_mut() {
@@ -1260,6 +1363,151 @@ impl Graph {
         }
     }
 
+    /// Performs a single fixed step of both physics worlds respecting the given update switches. It
+    /// is used by the engine to simulate physics with a fixed rate that does not depend on the frame
+    /// rate (see [`crate::engine::Engine::set_fixed_update_rate`]). Global transforms of the rigid
+    /// bodies before the step are remembered, so the rendered transforms could be interpolated
+    /// between the last two steps using [`Self::interpolate_physics`].
+    pub fn fixed_physics_step(&mut self, dt: f32, switches: &GraphUpdateSwitches) {
+        self.update_hierarchical_data();
+        self.sync_native(switches);
+
+        // Only dynamic bodies are interpolated, other bodies are moved by user code.
+        self.physics_interpolation.clear();
+        if switches.physics || switches.physics2d {
+            for (handle, node) in self.pool.pair_iter() {
+                let body_type = if let Some(body) = node.cast::<RigidBody>() {
+                    Some(body.body_type())
+                } else {
+                    node.cast::<dim2::rigidbody::RigidBody>()
+                        .map(|body| body.body_type())
+                };
+
+                if body_type == Some(RigidBodyType::Dynamic) {
+                    self.physics_interpolation
+                        .push((handle, node.global_transform()));
+                }
+            }
+        }
+
+        if switches.physics {
+            self.physics.performance_statistics.reset();
+            self.physics.update(dt);
+            self.performance_statistics.physics = self.physics.performance_statistics.clone();
+        }
+
+        if switches.physics2d {
+            self.physics2d.performance_statistics.reset();
+            self.physics2d.update(dt);
+            self.performance_statistics.physics2d = self.physics2d.performance_statistics.clone();
+        }
+
+        self.sync_rigid_body_nodes();
+    }
+
+    /// Interpolates global transforms of the rigid bodies (and their descendants) between the states
+    /// before and after the last fixed physics step (see [`Self::fixed_physics_step`]). `alpha` is an
+    /// interpolation factor in `[0; 1]` range, where `0.0` means the state before the step and `1.0`
+    /// means the current state. Only global transforms are modified, they will be re-calculated from
+    /// local transforms on the next update, so the interpolation does not affect the simulation.
+    pub fn interpolate_physics(&mut self, alpha: f32) {
+        let alpha = alpha.clamp(0.0, 1.0);
+
+        for &(handle, previous) in self.physics_interpolation.iter() {
+            if let Some(node) = self.pool.try_borrow(handle) {
+                let current = node.global_transform();
+                let interpolated = interpolate_transform(&previous, &current, alpha);
+                if let Some(inv_current) = current.try_inverse() {
+                    let correction = interpolated * inv_current;
+
+                    self.stack.clear();
+                    self.stack.push(handle);
+                    while let Some(handle) = self.stack.pop() {
+                        let node = &self.pool[handle];
+                        node.global_transform
+                            .set(correction * node.global_transform());
+                        self.stack.extend_from_slice(node.children());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Creates a snapshot of the state of both physics worlds of the graph. It could be used to
+    /// implement rollback netcode or replays, when the state of the simulation must be restored to
+    /// some point in the past and re-simulated again using [`Self::step_physics`].
+    ///
+    /// # Notes
+    ///
+    /// The snapshot contains physics state only, any other state of the scene nodes is not stored
+    /// in it. Keep in mind that the set of physical entities must be the same when restoring the
+    /// snapshot.
+    pub fn physics_snapshot(&self) -> GraphPhysicsSnapshot {
+        GraphPhysicsSnapshot {
+            physics: self.physics.snapshot(),
+            physics2d: self.physics2d.snapshot(),
+        }
+    }
+
+    /// Restores the state of both physics worlds of the graph from the given snapshot and syncs
+    /// the state (transform, velocities, etc.) of the rigid body nodes with the restored state.
+    pub fn restore_physics_snapshot(&mut self, snapshot: &GraphPhysicsSnapshot) {
+        self.physics.restore_snapshot(&snapshot.physics);
+        self.physics2d.restore_snapshot(&snapshot.physics2d);
+        self.sync_rigid_body_nodes();
+    }
+
+    /// Performs the given amount of physics simulation steps with the fixed time step, regardless
+    /// of [`physics::IntegrationParameters::dt`] and the frame rate. Rigid body nodes are synced
+    /// with the native objects before and after each step, other nodes are not updated. Unlike
+    /// [`Self::update`], the simulation is stepped even if the respective update switches are off,
+    /// but it still respects the `enabled` flag of each physics world.
+    ///
+    /// The method is deterministic, which means that the same initial state (see
+    /// [`Self::restore_physics_snapshot`]) with the same sequence of inputs produces the same
+    /// results on the same platform.
+    pub fn step_physics(&mut self, dt: f32, steps: usize) {
+        self.physics.performance_statistics.reset();
+        self.physics2d.performance_statistics.reset();
+
+        for _ in 0..steps {
+            self.update_hierarchical_data();
+            self.sync_native(&Default::default());
+            self.physics.step(dt);
+            self.physics2d.step(dt);
+            self.sync_rigid_body_nodes();
+        }
+
+        self.performance_statistics.physics = self.physics.performance_statistics.clone();
+        self.performance_statistics.physics2d = self.physics2d.performance_statistics.clone();
+    }
+
+    fn sync_rigid_body_nodes(&mut self) {
+        for i in 0..self.pool.get_capacity() {
+            let handle = self.pool.handle_from_index(i);
+
+            let parent_transform = if let Some(node) = self.pool.try_borrow(handle) {
+                self.pool
+                    .try_borrow(node.parent())
+                    .map(|p| p.global_transform())
+                    .unwrap_or_else(Matrix4::identity)
+            } else {
+                continue;
+            };
+
+            let node = &mut self.pool[handle];
+            if let Some(rigid_body) = node.cast_mut::<RigidBody>() {
+                self.physics
+                    .sync_rigid_body_node(rigid_body, parent_transform);
+            } else if let Some(rigid_body) = node.cast_mut::<dim2::rigidbody::RigidBody>() {
+                self.physics2d
+                    .sync_rigid_body_node(rigid_body, parent_transform);
+            }
+        }
+
+        self.update_hierarchical_data();
+    }
+
     /// Returns capacity of internal pool. Can be used to iterate over all **potentially**
     /// available indices and try to convert them to handles.
     ///
@@ -1332,6 +1580,43 @@ impl Graph {
         self.pool.pair_iter_mut()
     }
 
+    /// Creates new iterator over every node that has all the components of the query. Components are
+    /// searched in the node itself (including [`Base`] and [`crate::scene::transform::Transform`]) and
+    /// in its script. The graph keeps an index of nodes per component type, so the query does not check
+    /// every node of the graph. An index is created on the first query for a component type.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if the query tries to borrow the same component twice and one of the
+    /// borrows is mutable (for example, `(&mut Base, &Transform)`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fyrox::scene::{graph::Graph, transform::Transform};
+    ///
+    /// struct Health(f32);
+    ///
+    /// fn move_alive(graph: &mut Graph) {
+    ///     for (_handle, (health, transform)) in graph.query::<(&Health, &mut Transform)>() {
+    ///         if health.0 > 0.0 {
+    ///             transform.offset(fyrox::core::algebra::Vector3::new(0.0, 0.1, 0.0));
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn query<'a, Q: Query<'a>>(&'a mut self) -> QueryIterator<'a, Q> {
+        let mut types = Vec::new();
+        Q::required_types(&mut types);
+        if types.is_empty() {
+            // Every node has Base.
+            types.push(TypeId::of::<Base>());
+        }
+        let handles = self.component_index.prepare(&types, &self.pool);
+        QueryIterator::new(handles, &mut self.pool)
+    }
+
     /// Extracts node from graph and reserves its handle. It is used to temporarily take
     /// ownership over node, and then put node back using given ticket. Extracted node is
     /// detached from its parent!
@@ -1356,13 +1641,19 @@ impl Graph {
     }
 
     pub(crate) fn put_back_internal(&mut self, ticket: Ticket<Node>, node: Node) -> Handle<Node> {
-        self.pool.put_back(ticket, node)
+        let handle = self.pool.put_back(ticket, node);
+        // The node could be changed while it was taken out.
+        self.component_index.mark_changed(handle);
+        self.tag_index.mark_changed(handle);
+        handle
     }
 
     /// Makes node handle vacant again.
     #[inline]
     pub fn forget_ticket(&mut self, ticket: Ticket<Node>, node: Node) -> Node {
         self.pool.forget_ticket(ticket);
+        self.component_index.on_nodes_removed(&[node.self_handle]);
+        self.tag_index.on_nodes_removed(&[node.self_handle]);
         node
     }
 
@@ -1396,7 +1687,7 @@ impl Graph {
     #[inline]
     pub fn put_sub_graph_back(&mut self, sub_graph: SubGraph) -> Handle<Node> {
         for (ticket, node) in sub_graph.descendants {
-            self.pool.put_back(ticket, node);
+            self.put_back_internal(ticket, node);
         }
 
         let (ticket, node) = sub_graph.root;
@@ -1708,6 +1999,35 @@ impl<'a> Iterator for GraphHandleTraverseIterator<'a> {
     }
 }
 
+fn interpolate_transform(a: &Matrix4<f32>, b: &Matrix4<f32>, t: f32) -> Matrix4<f32> {
+    fn decompose(m: &Matrix4<f32>) -> (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>) {
+        let basis = m.basis();
+        let scale = Vector3::new(
+            basis.column(0).norm(),
+            basis.column(1).norm(),
+            basis.column(2).norm(),
+        );
+        let rotation = UnitQuaternion::from_matrix_eps(
+            &Matrix3::from_columns(&[
+                basis.column(0) / scale.x.max(f32::EPSILON),
+                basis.column(1) / scale.y.max(f32::EPSILON),
+                basis.column(2) / scale.z.max(f32::EPSILON),
+            ]),
+            f32::EPSILON,
+            16,
+            UnitQuaternion::identity(),
+        );
+        (m.position(), rotation, scale)
+    }
+
+    let (a_position, a_rotation, _) = decompose(a);
+    let (b_position, b_rotation, b_scale) = decompose(b);
+
+    Matrix4::new_translation(&a_position.lerp(&b_position, t))
+        * a_rotation.nlerp(&b_rotation, t).to_homogeneous()
+        * Matrix4::new_nonuniform_scaling(&b_scale)
+}
+
 impl Visit for Graph {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         // Pool must be empty, otherwise handles will be invalid and everything will blow up.
@@ -1715,6 +2035,11 @@ impl Visit for Graph {
             panic!("Graph pool must be empty on load!")
         }
 
+        if visitor.is_reading() {
+            self.component_index.clear();
+            self.tag_index.invalidate();
+        }
+
         let mut region = visitor.enter_region(name)?;
 
         self.root.visit("Root", &mut region)?;
@@ -1732,8 +2057,21 @@ mod test {
     use crate::scene::base::BaseBuilder;
     use crate::scene::pivot::PivotBuilder;
     use crate::{
-        core::pool::Handle,
-        scene::{graph::Graph, node::Node, pivot::Pivot},
+        core::{
+            algebra::Vector3, pool::Handle, reflect::prelude::*, uuid::Uuid, visitor::prelude::*,
+            TypeUuidProvider,
+        },
+        impl_component_provider,
+        scene::{
+            base::Base,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::{Graph, GraphUpdateSwitches},
+            node::Node,
+            pivot::Pivot,
+            rigidbody::RigidBodyBuilder,
+            transform::Transform,
+        },
+        script::{Script, ScriptTrait},
     };
 
     #[test]
@@ -1862,4 +2200,195 @@ mod test {
 
         assert!(graph[b].children.is_empty());
     }
+
+    #[test]
+    fn test_physics_snapshot_rollback() {
+        let mut graph = Graph::new();
+
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new().with_children(&[ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(ColliderShape::ball(0.5))
+                .build(&mut graph)]),
+        )
+        .build(&mut graph);
+
+        let dt = 1.0 / 60.0;
+
+        graph.step_physics(dt, 1);
+        let snapshot = graph.physics_snapshot();
+        let initial_position = graph[body].global_position();
+
+        graph.step_physics(dt, 10);
+        let expected_position = graph[body].global_position();
+        assert_ne!(initial_position, expected_position);
+
+        graph.restore_physics_snapshot(&snapshot);
+        assert_eq!(graph[body].global_position(), initial_position);
+
+        graph.step_physics(dt, 10);
+        assert_eq!(graph[body].global_position(), expected_position);
+    }
+
+    #[test]
+    fn test_physics_interpolation() {
+        let mut graph = Graph::new();
+
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new().with_children(&[ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(ColliderShape::ball(0.5))
+                .build(&mut graph)]),
+        )
+        .build(&mut graph);
+
+        let dt = 1.0 / 60.0;
+        let switches = GraphUpdateSwitches::default();
+
+        graph.fixed_physics_step(dt, &switches);
+        let previous_position = graph[body].global_position();
+
+        graph.fixed_physics_step(dt, &switches);
+        let current_position = graph[body].global_position();
+        assert_ne!(previous_position, current_position);
+
+        graph.interpolate_physics(0.0);
+        assert!((graph[body].global_position() - previous_position).norm() < 1.0e-5);
+
+        graph.update_hierarchical_data();
+        graph.interpolate_physics(0.5);
+        let middle = previous_position.lerp(&current_position, 0.5);
+        assert!((graph[body].global_position() - middle).norm() < 1.0e-5);
+
+        // Interpolation must not affect the actual state.
+        graph.update_hierarchical_data();
+        assert_eq!(graph[body].global_position(), current_position);
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Health(f32);
+
+    #[derive(Clone, Debug, Default, Reflect, Visit)]
+    struct Unit {
+        #[visit(skip)]
+        #[reflect(hidden)]
+        health: Health,
+    }
+
+    impl_component_provider!(Unit, health: Health);
+
+    impl TypeUuidProvider for Unit {
+        fn type_uuid() -> Uuid {
+            Uuid::default()
+        }
+    }
+
+    impl ScriptTrait for Unit {
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    fn unit(graph: &mut Graph, health: f32) -> Handle<Node> {
+        PivotBuilder::new(BaseBuilder::new().with_script(Script::new(Unit {
+            health: Health(health),
+        })))
+        .build(graph)
+    }
+
+    fn query_health(graph: &mut Graph) -> Vec<(Handle<Node>, f32)> {
+        let mut result = graph
+            .query::<(&Health, &mut Transform)>()
+            .map(|(handle, (health, transform))| {
+                transform.set_position(Vector3::new(health.0, 0.0, 0.0));
+                (handle, health.0)
+            })
+            .collect::<Vec<_>>();
+        result.sort_by(|a, b| a.1.total_cmp(&b.1));
+        result
+    }
+
+    #[test]
+    fn test_component_query() {
+        let mut graph = Graph::new();
+
+        let a = unit(&mut graph, 1.0);
+        let b = unit(&mut graph, 2.0);
+        let other = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        assert_eq!(query_health(&mut graph), vec![(a, 1.0), (b, 2.0)]);
+        assert_eq!(
+            **graph[b].local_transform().position(),
+            Vector3::new(2.0, 0.0, 0.0)
+        );
+
+        // Nodes added after the index was created.
+        let c = unit(&mut graph, 3.0);
+        // Scripts assigned to existing nodes.
+        graph[other].set_script(Some(Script::new(Unit {
+            health: Health(4.0),
+        })));
+        // Removed nodes.
+        graph.remove_node(a);
+
+        assert_eq!(
+            query_health(&mut graph),
+            vec![(b, 2.0), (c, 3.0), (other, 4.0)]
+        );
+
+        // Optional components.
+        let count = graph
+            .query::<(&Base, Option<&Health>)>()
+            .filter(|(_, (_, health))| health.is_none())
+            .count();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_component_query_aliasing() {
+        let mut graph = Graph::new();
+        unit(&mut graph, 1.0);
+        for _ in graph.query::<(&mut Base, &Transform)>() {}
+    }
+
+    #[test]
+    fn test_tags_and_groups() {
+        let mut graph = Graph::new();
+
+        let a = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_tags(vec!["SpawnPoint".to_string(), "Red".to_string()])
+                .with_groups(vec!["Wave1".to_string()]),
+        )
+        .build(&mut graph);
+        let b = PivotBuilder::new(BaseBuilder::new().with_tag("SpawnPoint".to_string()))
+            .build(&mut graph);
+        let c = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        assert_eq!(graph.nodes_with_tag("SpawnPoint"), vec![a, b]);
+        assert_eq!(graph.nodes_with_tag("Red"), vec![a]);
+        assert_eq!(graph.nodes_in_group("Wave1"), vec![a]);
+        assert!(graph.nodes_with_tag("Blue").is_empty());
+
+        // Changes after the index was built.
+        graph[c].add_tag("SpawnPoint");
+        graph[c].add_to_group("Wave1");
+        graph[a].remove_tag("SpawnPoint");
+        graph.remove_node(b);
+        let d = PivotBuilder::new(BaseBuilder::new().with_groups(vec!["Wave1".to_string()]))
+            .build(&mut graph);
+
+        assert_eq!(graph.nodes_with_tag("SpawnPoint"), vec![c]);
+        assert_eq!(graph.nodes_in_group("Wave1"), vec![a, c, d]);
+
+        // Item-level reflection writes bypass the setters.
+        graph[d].as_reflect_mut(&mut |node| {
+            node.resolve_path_mut("base.groups[0]", &mut |field| {
+                assert!(field.unwrap().set(Box::new("Wave2".to_string())).is_ok());
+            })
+        });
+        graph.mark_node_changed(d);
+
+        assert_eq!(graph.nodes_in_group("Wave1"), vec![a, c]);
+        assert_eq!(graph.nodes_in_group("Wave2"), vec![d]);
+    }
 }
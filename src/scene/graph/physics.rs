@@ -41,7 +41,7 @@ use rapier3d::{
     },
     geometry::{
         BroadPhase, Collider, ColliderBuilder, ColliderHandle, ColliderSet, Cuboid,
-        InteractionGroups, NarrowPhase, Ray, SharedShape,
+        InteractionGroups, NarrowPhase, Ray, Shape, SharedShape, TypedShape,
     },
     pipeline::{DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryFilter, QueryPipeline},
     prelude::JointAxis,
@@ -329,6 +329,7 @@ pub struct IntersectionPair {
     pub has_any_active_contact: bool,
 }
 
+#[derive(Clone)]
 pub(super) struct Container<S, A>
 where
     A: Hash + Eq + Clone,
@@ -896,6 +897,65 @@ pub struct PhysicsWorld {
     debug_render_pipeline: Mutex<DebugRenderPipeline>,
 }
 
+/// An in-memory copy of the complete state of a [`PhysicsWorld`]. See [`PhysicsWorld::snapshot`]
+/// and [`PhysicsWorld::restore_snapshot`] for more info.
+#[derive(Clone)]
+pub struct PhysicsWorldSnapshot {
+    enabled: bool,
+    integration_parameters: IntegrationParameters,
+    gravity: Vector3<f32>,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    ccd_solver: CCDSolver,
+    islands: IslandManager,
+    bodies: Container<RigidBodySet, RigidBodyHandle>,
+    colliders: Container<ColliderSet, ColliderHandle>,
+    joints: Container<ImpulseJointSet, ImpulseJointHandle>,
+    multibody_joints: Container<MultibodyJointSet, MultibodyJointHandle>,
+}
+
+impl Debug for PhysicsWorldSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PhysicsWorldSnapshot")
+    }
+}
+
+fn shape_triangles(
+    shape: &dyn Shape,
+    transform: &Isometry3<f32>,
+    triangles: &mut Vec<[Vector3<f32>; 3]>,
+) {
+    let (vertices, indices) = match shape.as_typed_shape() {
+        TypedShape::Ball(ball) => ball.to_trimesh(8, 8),
+        TypedShape::Cuboid(cuboid) => cuboid.to_trimesh(),
+        TypedShape::RoundCuboid(cuboid) => cuboid.inner_shape.to_trimesh(),
+        TypedShape::Capsule(capsule) => capsule.to_trimesh(8, 8),
+        TypedShape::Cylinder(cylinder) => cylinder.to_trimesh(8),
+        TypedShape::RoundCylinder(cylinder) => cylinder.inner_shape.to_trimesh(8),
+        TypedShape::Cone(cone) => cone.to_trimesh(8),
+        TypedShape::RoundCone(cone) => cone.inner_shape.to_trimesh(8),
+        TypedShape::ConvexPolyhedron(polyhedron) => polyhedron.to_trimesh(),
+        TypedShape::RoundConvexPolyhedron(polyhedron) => polyhedron.inner_shape.to_trimesh(),
+        TypedShape::HeightField(heightfield) => heightfield.to_trimesh(),
+        TypedShape::TriMesh(trimesh) => (trimesh.vertices().to_vec(), trimesh.indices().to_vec()),
+        TypedShape::Triangle(triangle) => {
+            (vec![triangle.a, triangle.b, triangle.c], vec![[0, 1, 2]])
+        }
+        TypedShape::Compound(compound) => {
+            for (local_transform, sub_shape) in compound.shapes() {
+                shape_triangles(&**sub_shape, &(transform * local_transform), triangles);
+            }
+            return;
+        }
+        // Segments, polylines and half-spaces do not have any area that could be triangulated.
+        _ => return,
+    };
+
+    for index in indices {
+        triangles.push(index.map(|i| transform.transform_point(&vertices[i as usize]).coords));
+    }
+}
+
 fn isometry_from_global_transform(transform: &Matrix4<f32>) -> Isometry3<f32> {
     Isometry3 {
         translation: Translation3::new(transform[12], transform[13], transform[14]),
@@ -961,11 +1021,18 @@ impl PhysicsWorld {
     }
 
     pub(super) fn update(&mut self, dt: f32) {
+        self.step(self.integration_parameters.dt.unwrap_or(dt));
+    }
+
+    /// Performs exactly one simulation step using the given time step, ignoring
+    /// [`IntegrationParameters::dt`]. Use this method when you need deterministic stepping that
+    /// does not depend on frame rate (for example, when re-simulating after a rollback).
+    pub(super) fn step(&mut self, dt: f32) {
         let time = instant::Instant::now();
 
         if self.enabled {
             let integration_parameters = rapier3d::dynamics::IntegrationParameters {
-                dt: self.integration_parameters.dt.unwrap_or(dt),
+                dt,
                 min_ccd_dt: self.integration_parameters.min_ccd_dt,
                 erp: self.integration_parameters.erp,
                 damping_ratio: self.integration_parameters.damping_ratio,
@@ -1013,6 +1080,52 @@ impl PhysicsWorld {
         self.performance_statistics.step_time += instant::Instant::now() - time;
     }
 
+    /// Creates a snapshot of the entire simulation state: rigid bodies, colliders, joints, contacts,
+    /// solver caches and simulation parameters. The snapshot can later be applied back using
+    /// [`Self::restore_snapshot`], which is the basis for rollback and replays.
+    pub fn snapshot(&self) -> PhysicsWorldSnapshot {
+        PhysicsWorldSnapshot {
+            enabled: self.enabled,
+            integration_parameters: self.integration_parameters,
+            gravity: self.gravity,
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            islands: self.islands.clone(),
+            bodies: self.bodies.clone(),
+            colliders: self.colliders.clone(),
+            joints: self.joints.clone(),
+            multibody_joints: self.multibody_joints.clone(),
+        }
+    }
+
+    /// Restores the simulation state from the given snapshot. The snapshot must be made from the
+    /// same physics world, and the set of physical entities (rigid bodies, colliders, joints)
+    /// should not change between snapshot creation and restoration, otherwise scene nodes might
+    /// reference wrong (or non-existent) native objects.
+    ///
+    /// # Notes
+    ///
+    /// This method restores the state of the physics world only, use
+    /// [`super::Graph::restore_physics_snapshot`] to also synchronize the state of the rigid body
+    /// nodes of the graph.
+    pub fn restore_snapshot(&mut self, snapshot: &PhysicsWorldSnapshot) {
+        self.enabled = snapshot.enabled;
+        self.integration_parameters = snapshot.integration_parameters;
+        self.gravity = snapshot.gravity;
+        self.broad_phase = snapshot.broad_phase.clone();
+        self.narrow_phase = snapshot.narrow_phase.clone();
+        self.ccd_solver = snapshot.ccd_solver.clone();
+        self.islands = snapshot.islands.clone();
+        self.bodies = snapshot.bodies.clone();
+        self.colliders = snapshot.colliders.clone();
+        self.joints = snapshot.joints.clone();
+        self.multibody_joints = snapshot.multibody_joints.clone();
+        // Pipeline holds only temporary data between steps, so it is enough to re-create it.
+        self.pipeline = PhysicsPipeline::new();
+        *self.query.borrow_mut() = Default::default();
+    }
+
     pub(super) fn add_body(&mut self, owner: Handle<Node>, body: RigidBody) -> RigidBodyHandle {
         let handle = self.bodies.set.insert(body);
         self.bodies.map.insert(handle, owner);
@@ -1154,6 +1267,19 @@ impl PhysicsWorld {
         );
     }
 
+    /// Appends world-space triangles of a collider with the given native handle to the given
+    /// buffer. Convex shapes are tessellated with a fixed amount of subdivisions. Does nothing
+    /// if there is no such collider.
+    pub(crate) fn collider_triangles(
+        &self,
+        handle: ColliderHandle,
+        triangles: &mut Vec<[Vector3<f32>; 3]>,
+    ) {
+        if let Some(collider) = self.colliders.set.get(handle) {
+            shape_triangles(collider.shape(), collider.position(), triangles);
+        }
+    }
+
     pub(crate) fn set_rigid_body_position(
         &mut self,
         rigid_body: &scene::rigidbody::RigidBody,
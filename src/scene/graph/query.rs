@@ -0,0 +1,438 @@
+//! Component queries allow you to iterate over every node of a graph that has a particular set of
+//! components. See [`Graph::query`](super::Graph::query) docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    fxhash::{FxHashMap, FxHashSet},
+    scene::{
+        base::Base,
+        graph::NodePool,
+        node::{Node, NodeTrait},
+        transform::Transform,
+    },
+    script::ScriptTrait,
+    utils::component::ComponentProvider,
+};
+use std::{
+    any::{type_name, Any, TypeId},
+    marker::PhantomData,
+    mem::{size_of, size_of_val},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+/// Returns `true` if the node provides a component with the given type id. Every node provides
+/// [`Base`] and [`Transform`] components, the rest is provided by the node itself (see
+/// [`NodeTrait::query_component_ref`]) or by its script (see [`ComponentProvider`]).
+pub fn has_component(node: &Node, type_id: TypeId) -> bool {
+    type_id == TypeId::of::<Base>()
+        || type_id == TypeId::of::<Transform>()
+        || NodeTrait::query_component_ref(&**node, type_id).is_some()
+        || node.script().map_or(false, |s| {
+            ComponentProvider::query_component_ref(&**s, type_id).is_some()
+        })
+}
+
+// Converts a reference to a part of an object into a pointer, that is derived from the pointer to the
+// whole object. Returns `None` if the part is not stored inside the object (for example, if it is
+// stored in a `Box` owned by the object).
+fn project<T>(root: *mut u8, root_size: usize, part: &T) -> Option<*mut T> {
+    let offset = (part as *const T as usize).checked_sub(root as usize)?;
+    if offset + size_of::<T>() <= root_size {
+        // SAFETY: The part is located within the object.
+        Some(unsafe { root.add(offset) }.cast::<T>())
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Default)]
+struct TypeIndex {
+    handles: Vec<Handle<Node>>,
+    set: FxHashSet<Handle<Node>>,
+}
+
+impl TypeIndex {
+    fn insert(&mut self, handle: Handle<Node>) {
+        if self.set.insert(handle) {
+            self.handles.push(handle);
+        }
+    }
+}
+
+/// Per-type indices of components. An index of a type is created on the first query for the type and
+/// then kept in sync with the graph. Indices could contain nodes that do not have the component
+/// anymore (for example, if a script was replaced), queries check every node anyway.
+#[derive(Debug)]
+pub(crate) struct ComponentIndex {
+    indices: FxHashMap<TypeId, TypeIndex>,
+    pub(crate) sender: Sender<Handle<Node>>,
+    receiver: Receiver<Handle<Node>>,
+}
+
+impl Default for ComponentIndex {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            indices: Default::default(),
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl ComponentIndex {
+    pub(crate) fn clear(&mut self) {
+        self.indices.clear();
+        while self.receiver.try_recv().is_ok() {}
+    }
+
+    /// Tells the index, that the set of components of the node might have changed. The node will be
+    /// checked on the next query.
+    pub(crate) fn mark_changed(&self, handle: Handle<Node>) {
+        // The receiver is owned by the index, so sending never fails.
+        let _ = self.sender.send(handle);
+    }
+
+    pub(crate) fn on_node_added(&mut self, handle: Handle<Node>, node: &Node) {
+        for (type_id, index) in self.indices.iter_mut() {
+            if has_component(node, *type_id) {
+                index.insert(handle);
+            }
+        }
+    }
+
+    pub(crate) fn on_nodes_removed(&mut self, handles: &[Handle<Node>]) {
+        if handles.is_empty() {
+            return;
+        }
+        for index in self.indices.values_mut() {
+            for handle in handles {
+                index.set.remove(handle);
+            }
+            let set = &index.set;
+            index.handles.retain(|h| set.contains(h));
+        }
+    }
+
+    fn sync(&mut self, pool: &NodePool) {
+        while let Ok(handle) = self.receiver.try_recv() {
+            if let Some(node) = pool.try_borrow(handle) {
+                self.on_node_added(handle, node);
+            }
+        }
+    }
+
+    fn ensure(&mut self, type_id: TypeId, pool: &NodePool) {
+        self.indices.entry(type_id).or_insert_with(|| {
+            let mut index = TypeIndex::default();
+            for (handle, node) in pool.pair_iter() {
+                if has_component(node, type_id) {
+                    index.insert(handle);
+                }
+            }
+            index
+        });
+    }
+
+    fn handles(&self, type_id: TypeId) -> &[Handle<Node>] {
+        self.indices
+            .get(&type_id)
+            .map(|index| index.handles.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn prepare(&mut self, types: &[TypeId], pool: &NodePool) -> &[Handle<Node>] {
+        self.sync(pool);
+        for type_id in types {
+            self.ensure(*type_id, pool);
+        }
+        // Iterate over the smallest set of nodes, the rest of components will be checked per node.
+        types
+            .iter()
+            .map(|type_id| self.handles(*type_id))
+            .min_by_key(|handles| handles.len())
+            .unwrap_or_default()
+    }
+}
+
+struct AccessRecord {
+    type_name: &'static str,
+    start: usize,
+    end: usize,
+    mutable: bool,
+    from_script: bool,
+}
+
+/// A list of components, that were fetched from a node by a query. It is used to ensure that a query does
+/// not produce aliasing references (for example `(&mut Base, &Transform)`).
+///
+/// The node is mutably borrowed only once, when the access is created. Pointers to components are
+/// derived from the pointers to the node and its script, that are taken at this moment, so fetching a
+/// component does not invalidate previously fetched ones.
+pub struct ComponentAccess {
+    node: *mut dyn NodeTrait,
+    script: Option<*mut dyn ScriptTrait>,
+    base_start: usize,
+    base_end: usize,
+    records: Vec<AccessRecord>,
+}
+
+impl ComponentAccess {
+    fn new(node: &mut Node) -> Self {
+        let node: *mut dyn NodeTrait = &mut **node;
+        // SAFETY: The pointer was just created from a unique reference. The script is stored in its own
+        // allocation, so the pointer to it stays valid when the node is accessed through `node`.
+        let script = unsafe {
+            (*node)
+                .script
+                .as_mut()
+                .map(|script| &mut **script as *mut dyn ScriptTrait)
+        };
+        // SAFETY: The shared reference is used only to get the address of `Base`.
+        let base_start = unsafe { &**node as *const Base as usize };
+        Self {
+            node,
+            script,
+            base_start,
+            base_end: base_start + size_of::<Base>(),
+            records: Default::default(),
+        }
+    }
+
+    // Returns a pointer to a component of the node and a flag, that tells whether the component belongs
+    // to the script of the node or not.
+    //
+    // SAFETY: The pointers of the access must be valid and there must be no live references to the node
+    // or its script.
+    unsafe fn find_component<T: Any>(&self, mutable: bool) -> Option<(*mut T, bool)> {
+        let type_id = TypeId::of::<T>();
+
+        // Shared references are used only to find where the component is stored, the actual pointer is
+        // derived from the pointer to the node.
+        let node = &*self.node;
+        let base: &Base = node;
+        let part = if type_id == TypeId::of::<Base>() {
+            (base as &dyn Any).downcast_ref::<T>()
+        } else if type_id == TypeId::of::<Transform>() {
+            if mutable {
+                base.transform_modified.set(true);
+            }
+            (&base.local_transform as &dyn Any).downcast_ref::<T>()
+        } else {
+            NodeTrait::query_component_ref(node, type_id).and_then(|c| c.downcast_ref::<T>())
+        };
+        if let Some(part) = part {
+            return project(self.node as *mut u8, size_of_val(node), part).map(|c| (c, false));
+        }
+
+        let script_ptr = self.script?;
+        let script = &*script_ptr;
+        ComponentProvider::query_component_ref(script, type_id)
+            .and_then(|c| c.downcast_ref::<T>())
+            .and_then(|part| project(script_ptr as *mut u8, size_of_val(script), part))
+            .map(|c| (c, true))
+    }
+
+    fn record<T>(&mut self, ptr: *mut T, mutable: bool, from_script: bool) {
+        let start = ptr as usize;
+        self.records.push(AccessRecord {
+            type_name: type_name::<T>(),
+            start,
+            end: start + std::mem::size_of::<T>(),
+            mutable,
+            from_script,
+        })
+    }
+
+    fn validate(&self) {
+        for (i, a) in self.records.iter().enumerate() {
+            for b in self.records[(i + 1)..].iter() {
+                if !a.mutable && !b.mutable {
+                    continue;
+                }
+
+                let overlap = a.start < b.end && b.start < a.end;
+                // Base owns the script, so any component that contains Base gives access to the
+                // components of the script.
+                let owns_script = |r: &AccessRecord, other: &AccessRecord| {
+                    other.from_script && r.start <= self.base_start && r.end >= self.base_end
+                };
+
+                if overlap || a.type_name == b.type_name || owns_script(a, b) || owns_script(b, a) {
+                    panic!(
+                        "A query must not borrow components {} and {} at the same time, \
+                        because one of them is borrowed mutably and they overlap!",
+                        a.type_name, b.type_name
+                    );
+                }
+            }
+        }
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A set of components, that is fetched from a node. It is implemented for `&T`, `&mut T`,
+/// `Option<Q>` (where `Q` is a query) and for tuples of queries (up to 8 elements). Components must
+/// be stored inside the node (or its script), components that are stored elsewhere (for example, in a
+/// `Box`) are not fetched.
+///
+/// # Safety
+///
+/// The trait is sealed and cannot be implemented outside of the engine. Implementations must register
+/// every component they fetch in [`ComponentAccess`].
+pub unsafe trait Query<'a>: Sized + private::Sealed {
+    /// Raw pointers to fetched components.
+    #[doc(hidden)]
+    type Raw;
+
+    /// Collects type ids of components, that every node must have to match the query.
+    #[doc(hidden)]
+    fn required_types(types: &mut Vec<TypeId>);
+
+    /// Fetches pointers to the components of the node of the access.
+    ///
+    /// # Safety
+    ///
+    /// The node of the access must be alive and must not be borrowed.
+    #[doc(hidden)]
+    unsafe fn fetch(access: &mut ComponentAccess) -> Option<Self::Raw>;
+
+    /// Converts raw pointers to references.
+    ///
+    /// # Safety
+    ///
+    /// Access must be validated before the call.
+    #[doc(hidden)]
+    unsafe fn from_raw(raw: Self::Raw) -> Self;
+}
+
+impl<'a, T: Any> private::Sealed for &'a T {}
+
+unsafe impl<'a, T: Any> Query<'a> for &'a T {
+    type Raw = *mut T;
+
+    fn required_types(types: &mut Vec<TypeId>) {
+        types.push(TypeId::of::<T>())
+    }
+
+    unsafe fn fetch(access: &mut ComponentAccess) -> Option<Self::Raw> {
+        let (ptr, from_script) = access.find_component::<T>(false)?;
+        access.record(ptr, false, from_script);
+        Some(ptr)
+    }
+
+    unsafe fn from_raw(raw: Self::Raw) -> Self {
+        &*raw
+    }
+}
+
+impl<'a, T: Any> private::Sealed for &'a mut T {}
+
+unsafe impl<'a, T: Any> Query<'a> for &'a mut T {
+    type Raw = *mut T;
+
+    fn required_types(types: &mut Vec<TypeId>) {
+        types.push(TypeId::of::<T>())
+    }
+
+    unsafe fn fetch(access: &mut ComponentAccess) -> Option<Self::Raw> {
+        let (ptr, from_script) = access.find_component::<T>(true)?;
+        access.record(ptr, true, from_script);
+        Some(ptr)
+    }
+
+    unsafe fn from_raw(raw: Self::Raw) -> Self {
+        &mut *raw
+    }
+}
+
+impl<'a, Q: Query<'a>> private::Sealed for Option<Q> {}
+
+unsafe impl<'a, Q: Query<'a>> Query<'a> for Option<Q> {
+    type Raw = Option<Q::Raw>;
+
+    fn required_types(_types: &mut Vec<TypeId>) {}
+
+    unsafe fn fetch(access: &mut ComponentAccess) -> Option<Self::Raw> {
+        Some(Q::fetch(access))
+    }
+
+    unsafe fn from_raw(raw: Self::Raw) -> Self {
+        raw.map(|raw| Q::from_raw(raw))
+    }
+}
+
+macro_rules! impl_query_for_tuple {
+    ($($name:ident),+) => {
+        impl<'a, $($name: Query<'a>),+> private::Sealed for ($($name,)+) {}
+
+        #[allow(non_snake_case)]
+        unsafe impl<'a, $($name: Query<'a>),+> Query<'a> for ($($name,)+) {
+            type Raw = ($($name::Raw,)+);
+
+            fn required_types(types: &mut Vec<TypeId>) {
+                $($name::required_types(types);)+
+            }
+
+            unsafe fn fetch(access: &mut ComponentAccess) -> Option<Self::Raw> {
+                Some(($($name::fetch(access)?,)+))
+            }
+
+            unsafe fn from_raw(raw: Self::Raw) -> Self {
+                let ($($name,)+) = raw;
+                ($($name::from_raw($name),)+)
+            }
+        }
+    };
+}
+
+impl_query_for_tuple!(A);
+impl_query_for_tuple!(A, B);
+impl_query_for_tuple!(A, B, C);
+impl_query_for_tuple!(A, B, C, D);
+impl_query_for_tuple!(A, B, C, D, E);
+impl_query_for_tuple!(A, B, C, D, E, F);
+impl_query_for_tuple!(A, B, C, D, E, F, G);
+impl_query_for_tuple!(A, B, C, D, E, F, G, H);
+
+/// An iterator over nodes that match a query, it yields handles of the nodes with their components.
+/// See [`Graph::query`](super::Graph::query) docs for more info.
+pub struct QueryIterator<'a, Q> {
+    handles: std::slice::Iter<'a, Handle<Node>>,
+    pool: *mut NodePool,
+    phantom: PhantomData<(&'a mut NodePool, Q)>,
+}
+
+impl<'a, Q: Query<'a>> QueryIterator<'a, Q> {
+    pub(crate) fn new(handles: &'a [Handle<Node>], pool: &'a mut NodePool) -> Self {
+        Self {
+            handles: handles.iter(),
+            pool,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, Q: Query<'a>> Iterator for QueryIterator<'a, Q> {
+    type Item = (Handle<Node>, Q);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for &handle in self.handles.by_ref() {
+            // SAFETY: Handles in an index are unique, so every node is borrowed at most once during
+            // iteration. The pool is mutably borrowed by the iterator for its whole lifetime.
+            unsafe {
+                if let Some(node) = (*self.pool).try_borrow_mut(handle) {
+                    let mut access = ComponentAccess::new(node);
+                    if let Some(raw) = Q::fetch(&mut access) {
+                        access.validate();
+                        return Some((handle, Q::from_raw(raw)));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,153 @@
+//! Indices of node tags and groups. See [`Graph::nodes_with_tag`](super::Graph::nodes_with_tag) and
+//! [`Graph::nodes_in_group`](super::Graph::nodes_in_group) docs for more info.
+
+use crate::{
+    core::{parking_lot::Mutex, pool::Handle},
+    fxhash::{FxHashMap, FxHashSet},
+    scene::{graph::NodePool, node::Node},
+};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+#[derive(Debug, Default)]
+struct NameIndex {
+    map: FxHashMap<String, FxHashSet<Handle<Node>>>,
+}
+
+impl NameIndex {
+    fn insert(&mut self, name: &str, handle: Handle<Node>) {
+        if let Some(handles) = self.map.get_mut(name) {
+            handles.insert(handle);
+        } else {
+            self.map
+                .insert(name.to_owned(), FxHashSet::from_iter([handle]));
+        }
+    }
+
+    fn remove(&mut self, handle: Handle<Node>) {
+        for handles in self.map.values_mut() {
+            handles.remove(&handle);
+        }
+    }
+
+    // Returns every node from the index that satisfies the predicate, stale entries are removed.
+    fn collect<P>(&mut self, name: &str, pool: &NodePool, predicate: P) -> Vec<Handle<Node>>
+    where
+        P: Fn(&Node) -> bool,
+    {
+        let mut result = Vec::new();
+        if let Some(handles) = self.map.get_mut(name) {
+            handles.retain(|handle| pool.try_borrow(*handle).map_or(false, &predicate));
+            result.extend(handles.iter().cloned());
+            if handles.is_empty() {
+                self.map.remove(name);
+            }
+        }
+        // Keep the order stable, it does not depend on the order of insertion then.
+        result.sort_unstable_by_key(|handle| (handle.index(), handle.generation()));
+        result
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    built: bool,
+    tags: NameIndex,
+    groups: NameIndex,
+    receiver: Receiver<Handle<Node>>,
+}
+
+impl State {
+    fn add(&mut self, handle: Handle<Node>, node: &Node) {
+        if !node.tag().is_empty() {
+            self.tags.insert(node.tag(), handle);
+        }
+        for tag in node.tags() {
+            self.tags.insert(tag, handle);
+        }
+        for group in node.groups() {
+            self.groups.insert(group, handle);
+        }
+    }
+
+    fn sync(&mut self, pool: &NodePool) {
+        if self.built {
+            while let Ok(handle) = self.receiver.try_recv() {
+                if let Some(node) = pool.try_borrow(handle) {
+                    self.add(handle, node);
+                }
+            }
+        } else {
+            while self.receiver.try_recv().is_ok() {}
+            for (handle, node) in pool.pair_iter() {
+                self.add(handle, node);
+            }
+            self.built = true;
+        }
+    }
+}
+
+/// Indices of tags and groups of nodes. Indices are built on the first lookup and then kept in sync
+/// with the graph. An index could contain nodes that do not have a tag (or group) anymore, every node
+/// is checked on lookup and such entries are removed.
+#[derive(Debug)]
+pub(crate) struct TagIndex {
+    pub(crate) sender: Sender<Handle<Node>>,
+    // Lookups do not require mutable access to the graph, so the state is behind a mutex.
+    state: Mutex<State>,
+}
+
+impl Default for TagIndex {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sender,
+            state: Mutex::new(State {
+                built: false,
+                tags: Default::default(),
+                groups: Default::default(),
+                receiver,
+            }),
+        }
+    }
+}
+
+impl TagIndex {
+    /// Drops the indices, they will be rebuilt on the next lookup. It must be called when tags of nodes
+    /// could be changed without notification (for example, on property inheritance).
+    pub(crate) fn invalidate(&self) {
+        let mut state = self.state.lock();
+        state.built = false;
+        state.tags = Default::default();
+        state.groups = Default::default();
+    }
+
+    /// Tells the index, that tags or groups of the node might have changed.
+    pub(crate) fn mark_changed(&self, handle: Handle<Node>) {
+        // The receiver is owned by the index, so sending never fails.
+        let _ = self.sender.send(handle);
+    }
+
+    pub(crate) fn on_nodes_removed(&self, handles: &[Handle<Node>]) {
+        let mut state = self.state.lock();
+        if state.built {
+            for handle in handles {
+                state.tags.remove(*handle);
+                state.groups.remove(*handle);
+            }
+        }
+    }
+
+    pub(crate) fn nodes_with_tag(&self, tag: &str, pool: &NodePool) -> Vec<Handle<Node>> {
+        let mut state = self.state.lock();
+        state.sync(pool);
+        state.tags.collect(tag, pool, |node| node.has_tag(tag))
+    }
+
+    pub(crate) fn nodes_in_group(&self, group: &str, pool: &NodePool) -> Vec<Handle<Node>> {
+        let mut state = self.state.lock();
+        state.sync(pool);
+        state
+            .groups
+            .collect(group, pool, |node| node.is_in_group(group))
+    }
+}
@@ -22,10 +22,12 @@ pub mod node;
 pub mod particle_system;
 pub mod pivot;
 pub mod rigidbody;
+pub mod save;
 pub mod sound;
 pub mod sprite;
 pub mod terrain;
 pub mod transform;
+pub mod vehicle;
 
 use crate::{
     asset::{self, manager::ResourceManager, untyped::UntypedResource},
@@ -10,7 +10,10 @@ use crate::{
         TypeUuidProvider,
     },
     scene::{base::Base, base::BaseBuilder, graph::Graph, node::Node, node::NodeTrait},
-    utils::navmesh::Navmesh,
+    utils::navmesh::{
+        bake::{NavmeshBakeError, NavmeshBakeSettings},
+        Navmesh,
+    },
 };
 use std::ops::{Deref, DerefMut};
 
@@ -19,6 +22,8 @@ pub struct NavigationalMesh {
     base: Base,
     #[reflect(hidden)]
     navmesh: Navmesh,
+    #[visit(optional)]
+    bake_settings: NavmeshBakeSettings,
 }
 
 impl TypeUuidProvider for NavigationalMesh {
@@ -65,11 +70,34 @@ impl NavigationalMesh {
     pub fn navmesh_mut(&mut self) -> &mut Navmesh {
         &mut self.navmesh
     }
+
+    /// Replaces current navigational mesh with a new one, returns the old one.
+    pub fn set_navmesh(&mut self, navmesh: Navmesh) -> Navmesh {
+        std::mem::replace(&mut self.navmesh, navmesh)
+    }
+
+    /// Returns current bake settings, that will be used by [`Self::bake`].
+    pub fn bake_settings(&self) -> &NavmeshBakeSettings {
+        &self.bake_settings
+    }
+
+    /// Sets new bake settings, that will be used by [`Self::bake`].
+    pub fn set_bake_settings(&mut self, settings: NavmeshBakeSettings) {
+        self.bake_settings = settings;
+    }
+
+    /// Bakes a new navigational mesh from the geometry of the given graph, using current bake
+    /// settings. The navigational mesh of the node is not changed, use [`Self::set_navmesh`] to
+    /// apply the result.
+    pub fn bake(&self, graph: &Graph) -> Result<Navmesh, NavmeshBakeError> {
+        Navmesh::bake(graph, &self.bake_settings)
+    }
 }
 
 pub struct NavigationalMeshBuilder {
     base_builder: BaseBuilder,
     navmesh: Navmesh,
+    bake_settings: NavmeshBakeSettings,
 }
 
 impl NavigationalMeshBuilder {
@@ -77,6 +105,7 @@ impl NavigationalMeshBuilder {
         Self {
             base_builder,
             navmesh: Default::default(),
+            bake_settings: Default::default(),
         }
     }
 
@@ -85,10 +114,16 @@ impl NavigationalMeshBuilder {
         self
     }
 
+    pub fn with_bake_settings(mut self, settings: NavmeshBakeSettings) -> Self {
+        self.bake_settings = settings;
+        self
+    }
+
     fn build_navigational_mesh(self) -> NavigationalMesh {
         NavigationalMesh {
             base: self.base_builder.build_base(),
             navmesh: self.navmesh,
+            bake_settings: self.bake_settings,
         }
     }
 
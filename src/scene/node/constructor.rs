@@ -4,7 +4,7 @@ use crate::{
     core::{parking_lot::Mutex, uuid::Uuid, TypeUuidProvider},
     scene::{
         self,
-        animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
+        animation::{absm::AnimationBlendingStateMachine, sequencer::Sequencer, AnimationPlayer},
         camera::Camera,
         decal::Decal,
         dim2::{self, rectangle::Rectangle},
@@ -17,6 +17,7 @@ use crate::{
         sound::{listener::Listener, Sound},
         sprite::Sprite,
         terrain::Terrain,
+        vehicle::Vehicle,
     },
 };
 use fxhash::FxHashMap;
@@ -56,7 +57,9 @@ impl NodeConstructorContainer {
         container.add::<Terrain>();
         container.add::<AnimationPlayer>();
         container.add::<AnimationBlendingStateMachine>();
+        container.add::<Sequencer>();
         container.add::<NavigationalMesh>();
+        container.add::<Vehicle>();
 
         container
     }
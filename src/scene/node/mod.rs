@@ -16,7 +16,7 @@ use crate::{
     renderer::batch::RenderContext,
     scene::{
         self,
-        animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
+        animation::{absm::AnimationBlendingStateMachine, sequencer::Sequencer, AnimationPlayer},
         base::Base,
         camera::Camera,
         debug::SceneDrawingContext,
@@ -31,6 +31,7 @@ use crate::{
         sound::{context::SoundContext, listener::Listener, Sound},
         sprite::Sprite,
         terrain::Terrain,
+        vehicle::Vehicle,
         Scene,
     },
 };
@@ -489,6 +490,8 @@ impl Node {
     define_is_as!(NavigationalMesh => fn is_navigational_mesh, fn as_navigational_mesh, fn as_navigational_mesh_mut);
     define_is_as!(AnimationBlendingStateMachine => fn is_absm, fn as_absm, fn as_absm_mut);
     define_is_as!(AnimationPlayer => fn is_animation_player, fn as_animation_player, fn as_animation_player_mut);
+    define_is_as!(Sequencer => fn is_sequencer, fn as_sequencer, fn as_sequencer_mut);
+    define_is_as!(Vehicle => fn is_vehicle, fn as_vehicle, fn as_vehicle_mut);
 }
 
 impl Visit for Node {
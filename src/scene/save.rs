@@ -0,0 +1,804 @@
+//! Save game system. It allows you to store the state of a game level in a compact form and restore it
+//! later. See [`SaveGame`] docs for more info.
+//!
+//! # How it works
+//!
+//! Instead of storing whole scene (which includes a lot of static content, like level geometry), save
+//! game stores only the difference between the current state of the scene and its original resource
+//! (a scene file made in the editor). Only the nodes that are marked as saveable (see
+//! [`crate::scene::base::Base::set_saveable`]) are taken into account:
+//!
+//! - Saveable nodes of the original scene, that were removed, are stored as a list of handles.
+//! - Saveable nodes of the original scene, that still exist, are stored as a set of changed properties
+//!   (local position, rotation, scale, visibility, enabled state, parent) and a state of their scripts.
+//!   Script decides which fields should be stored, see [`crate::script::ScriptTrait::visit_save_state`].
+//! - Saveable nodes, that were created at runtime, are stored completely (including all their descendant
+//!   nodes), because there is nothing to compare them with.
+//!
+//! Restoring is done by loading the original scene and applying the difference to it.
+//!
+//! # Limitations
+//!
+//! - Other properties of the nodes (for example velocity of rigid bodies) are not stored, if you need
+//!   to save them, do it in a script.
+//! - Nodes created at runtime get new handles when restored. Handles, that are stored in the state of
+//!   the scripts of other nodes, are not remapped - use a map returned by [`SaveGame::apply`] or
+//!   search for such nodes in [`crate::script::ScriptTrait::on_start`].
+//! - [`crate::script::ScriptTrait::on_init`] is called for the scripts of the nodes of the original
+//!   scene after restoring, because their state is restored only partially. It should not overwrite
+//!   the fields that are stored in save files.
+
+use crate::{
+    asset::{manager::ResourceManager, ResourceStateRef},
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        uuid::Uuid,
+        visitor::{PodVecView, Visit, VisitError, VisitResult, Visitor},
+    },
+    engine::SerializationContext,
+    renderer::Screenshot,
+    resource::model::ModelResource,
+    scene::{
+        graph::{map::NodeHandleMap, Graph},
+        node::Node,
+        Scene, SceneLoader,
+    },
+    script::Script,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Extension of save files that are managed by [`SaveSlots`].
+pub const SAVE_FILE_EXTENSION: &str = "save";
+
+/// Additional information about a save game, that could be shown in a menu of your game.
+#[derive(Visit, Default, Clone, Debug)]
+pub struct SaveGameMetadata {
+    /// Human-readable name of the save game.
+    pub name: String,
+    /// Custom description, for example name of current quest or location.
+    pub description: String,
+    /// Time (in seconds since UNIX epoch) when the save game was created.
+    pub timestamp: u64,
+    /// Total play time (in seconds).
+    pub play_time: f32,
+    /// Optional screenshot of the game, see [`crate::renderer::Renderer::request_screenshot`]. It is
+    /// a good idea to make it small (see [`Screenshot::resized`]), otherwise it will take most of the
+    /// save file.
+    pub screenshot: Option<Screenshot>,
+}
+
+#[derive(Default, Debug)]
+struct ScriptState {
+    type_uuid: Uuid,
+    data: Vec<u8>,
+}
+
+impl Visit for ScriptState {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.type_uuid.visit("TypeUuid", &mut region)?;
+        PodVecView::from_pod_vec(&mut self.data).visit("Data", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl ScriptState {
+    fn capture(script: &mut Script) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::new();
+        {
+            let mut region = visitor.enter_region("State")?;
+            script.visit_save_state(&mut region)?;
+        }
+        Ok(Self {
+            type_uuid: script.id(),
+            data: visitor.save_binary_to_vec()?,
+        })
+    }
+
+    fn apply(&self, script: &mut Script) -> VisitResult {
+        // Script could be replaced in the original scene since the save game was made.
+        if script.id() != self.type_uuid {
+            return Ok(());
+        }
+
+        let mut visitor = Visitor::load_from_memory(self.data.clone())?;
+        let mut region = visitor.enter_region("State")?;
+        script.visit_save_state(&mut region)
+    }
+}
+
+#[derive(Visit, Default, Debug)]
+struct NodeDiff {
+    handle: Handle<Node>,
+    parent: Option<Handle<Node>>,
+    position: Option<Vector3<f32>>,
+    rotation: Option<UnitQuaternion<f32>>,
+    scale: Option<Vector3<f32>>,
+    visibility: Option<bool>,
+    enabled: Option<bool>,
+    script: Option<ScriptState>,
+}
+
+impl NodeDiff {
+    fn capture(handle: Handle<Node>, node: &mut Node, original: &Node) -> Result<Self, VisitError> {
+        fn changed<T: PartialEq + Clone>(current: &T, original: &T) -> Option<T> {
+            if current != original {
+                Some(current.clone())
+            } else {
+                None
+            }
+        }
+
+        let transform = node.local_transform();
+        let original_transform = original.local_transform();
+
+        Ok(Self {
+            handle,
+            parent: changed(&node.parent(), &original.parent()),
+            position: changed(&**transform.position(), &**original_transform.position()),
+            rotation: changed(&**transform.rotation(), &**original_transform.rotation()),
+            scale: changed(&**transform.scale(), &**original_transform.scale()),
+            visibility: changed(&node.visibility(), &original.visibility()),
+            enabled: changed(&node.is_enabled(), &original.is_enabled()),
+            script: node.script_mut().map(ScriptState::capture).transpose()?,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.parent.is_none()
+            && self.position.is_none()
+            && self.rotation.is_none()
+            && self.scale.is_none()
+            && self.visibility.is_none()
+            && self.enabled.is_none()
+            && self.script.is_none()
+    }
+}
+
+#[derive(Default, Debug)]
+struct SpawnedNode {
+    parent: Handle<Node>,
+    // Handles of the nodes in the source scene and in the stored scene.
+    source_handles: Vec<Handle<Node>>,
+    stored_handles: Vec<Handle<Node>>,
+    scene: Vec<u8>,
+}
+
+impl Visit for SpawnedNode {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.parent.visit("Parent", &mut region)?;
+        self.source_handles.visit("SourceHandles", &mut region)?;
+        self.stored_handles.visit("StoredHandles", &mut region)?;
+        PodVecView::from_pod_vec(&mut self.scene).visit("Scene", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl SpawnedNode {
+    fn capture(scene: &Scene, handle: Handle<Node>, original: &Graph) -> Result<Self, VisitError> {
+        // Nodes of the original scene could be attached to a spawned node, they're stored as a part of
+        // the diff.
+        let (mut stored, map) = scene.clone(handle, &mut |h, _| original.try_get(h).is_none());
+
+        copy_script_flags(&scene.graph, &mut stored.graph, &map);
+
+        let (source_handles, stored_handles) = map
+            .inner()
+            .iter()
+            .map(|(source, stored)| (*source, *stored))
+            .unzip();
+
+        let mut visitor = Visitor::new();
+        stored.save("Scene", &mut visitor)?;
+
+        Ok(Self {
+            parent: scene.graph[handle].parent(),
+            source_handles,
+            stored_handles,
+            scene: visitor.save_binary_to_vec()?,
+        })
+    }
+}
+
+// Cloning of scripts resets their flags, but spawned nodes are stored with their full state and must
+// not be initialized again after restoring.
+fn copy_script_flags(source: &Graph, dest: &mut Graph, map: &NodeHandleMap) {
+    for (&source_handle, &dest_handle) in map.inner().iter() {
+        if let (Some(source_script), Some(dest_script)) = (
+            source[source_handle].script(),
+            dest[dest_handle].script_mut(),
+        ) {
+            dest_script.initialized = source_script.initialized;
+        }
+    }
+}
+
+/// Save game is a compact snapshot of the state of a game level. See [module docs](self) for
+/// more info.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     asset::manager::ResourceManager,
+///     engine::SerializationContext,
+///     resource::model::ModelResource,
+///     scene::{
+///         save::{SaveGame, SaveSlots},
+///         Scene,
+///     },
+/// };
+/// use std::sync::Arc;
+///
+/// fn save(scene: &mut Scene, level: &ModelResource) {
+///     let mut save_game = SaveGame::capture(scene, level).unwrap();
+///     save_game.metadata.name = "Quick Save".to_string();
+///
+///     SaveSlots::new("saves").save("quick", &mut save_game).unwrap();
+/// }
+///
+/// async fn load(
+///     resource_manager: ResourceManager,
+///     serialization_context: Arc<SerializationContext>,
+/// ) -> Scene {
+///     let save_game = SaveSlots::new("saves").load("quick").await.unwrap();
+///
+///     // Loads the original level and applies stored changes to it. Add the scene to the
+///     // engine after that.
+///     save_game
+///         .restore(resource_manager, serialization_context)
+///         .await
+///         .unwrap()
+/// }
+/// ```
+#[derive(Visit, Default, Debug)]
+pub struct SaveGame {
+    /// Metadata of the save game.
+    pub metadata: SaveGameMetadata,
+    level: PathBuf,
+    removed: Vec<Handle<Node>>,
+    modified: Vec<NodeDiff>,
+    spawned: Vec<SpawnedNode>,
+}
+
+impl SaveGame {
+    /// Captures the state of the given scene, that was loaded from the given level resource. The
+    /// resource must be fully loaded. Usually, the resource could be requested from the resource
+    /// manager using the path of the scene file of the level, it will be loaded only once.
+    pub fn capture(scene: &mut Scene, level: &ModelResource) -> Result<Self, VisitError> {
+        let path = level.path();
+        let state = level.state();
+        match state.get() {
+            ResourceStateRef::Ok(model) => {
+                Self::capture_with_original(scene, model.get_scene(), path)
+            }
+            _ => Err(VisitError::User(format!(
+                "Unable to capture save game, because level {} is not loaded!",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Captures the state of the given scene, using the other scene as the original. The original
+    /// scene must be loaded from the given path and must not be modified.
+    pub fn capture_with_original(
+        scene: &mut Scene,
+        original: &Scene,
+        level: PathBuf,
+    ) -> Result<Self, VisitError> {
+        let original = &original.graph;
+
+        let mut save_game = Self {
+            level,
+            ..Default::default()
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            save_game.metadata.timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+        }
+
+        for (handle, node) in original.pair_iter() {
+            if node.is_saveable() && !scene.graph.is_valid_handle(handle) {
+                save_game.removed.push(handle);
+            }
+        }
+
+        let handles = scene
+            .graph
+            .traverse_handle_iter(scene.graph.get_root())
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            if !scene.graph[handle].is_saveable() {
+                continue;
+            }
+
+            if let Some(original_node) = original.try_get(handle) {
+                let diff = NodeDiff::capture(handle, &mut scene.graph[handle], original_node)?;
+                if !diff.is_empty() {
+                    save_game.modified.push(diff);
+                }
+            } else if !is_inside_spawned(&scene.graph, original, handle) {
+                save_game
+                    .spawned
+                    .push(SpawnedNode::capture(scene, handle, original)?);
+            }
+        }
+
+        Ok(save_game)
+    }
+
+    /// Returns a path of the level, that was used to create the save game.
+    pub fn level(&self) -> &Path {
+        &self.level
+    }
+
+    /// Loads the original level and applies the save game to it. The scene should be added to the
+    /// engine after that.
+    pub async fn restore(
+        &self,
+        resource_manager: ResourceManager,
+        serialization_context: Arc<SerializationContext>,
+    ) -> Result<Scene, VisitError> {
+        let mut scene = SceneLoader::from_file(
+            &self.level,
+            serialization_context.clone(),
+            resource_manager.clone(),
+        )
+        .await?
+        .finish()
+        .await;
+
+        self.apply(&mut scene, resource_manager, serialization_context)
+            .await?;
+
+        Ok(scene)
+    }
+
+    /// Applies the save game to the given scene, that must be loaded from the original level. Returns
+    /// a map of the handles of spawned nodes at the moment of saving to their new handles.
+    pub async fn apply(
+        &self,
+        scene: &mut Scene,
+        resource_manager: ResourceManager,
+        serialization_context: Arc<SerializationContext>,
+    ) -> Result<NodeHandleMap, VisitError> {
+        let mut map = FxHashMap::default();
+        let mut created = FxHashSet::default();
+        let mut roots = Vec::with_capacity(self.spawned.len());
+
+        for spawned in self.spawned.iter() {
+            let mut visitor = Visitor::load_from_memory(spawned.scene.clone())?;
+            let stored = SceneLoader::load(
+                "Scene",
+                serialization_context.clone(),
+                resource_manager.clone(),
+                &mut visitor,
+                None,
+            )?
+            .finish()
+            .await;
+
+            let (root, stored_map) =
+                stored
+                    .graph
+                    .copy_node(stored.graph.get_root(), &mut scene.graph, &mut |_, _| true);
+
+            copy_script_flags(&stored.graph, &mut scene.graph, &stored_map);
+
+            created.extend(stored_map.inner().values().cloned());
+            roots.push((root, spawned.parent));
+
+            for (source, stored) in spawned
+                .source_handles
+                .iter()
+                .zip(spawned.stored_handles.iter())
+            {
+                if let Some(new) = stored_map.inner().get(stored) {
+                    map.insert(*source, *new);
+                }
+            }
+        }
+
+        // Parents are linked when every spawned node is restored, because a parent could be restored after
+        // its children. Handles of the spawned nodes at the moment of saving could be already taken by other
+        // restored nodes, so they must be remapped first.
+        for (root, parent) in roots {
+            let parent = match map.get(&parent) {
+                Some(new_parent) => *new_parent,
+                // Parent is a node of the original level.
+                None if scene.graph.is_valid_handle(parent) && !created.contains(&parent) => parent,
+                // Parent was not saveable, there is no better place for the node.
+                None => scene.graph.get_root(),
+            };
+            scene.graph.link_nodes(root, parent);
+        }
+
+        for &handle in self.removed.iter() {
+            if scene.graph.is_valid_handle(handle) {
+                scene.graph.remove_node(handle);
+            }
+        }
+
+        for diff in self.modified.iter() {
+            if !scene.graph.is_valid_handle(diff.handle) {
+                continue;
+            }
+
+            if let Some(mut parent) = diff.parent {
+                if let Some(new_parent) = map.get(&parent) {
+                    parent = *new_parent;
+                }
+                if scene.graph.is_valid_handle(parent) {
+                    scene.graph.link_nodes(diff.handle, parent);
+                }
+            }
+
+            let node = &mut scene.graph[diff.handle];
+
+            let transform = node.local_transform_mut();
+            if let Some(position) = diff.position {
+                transform.set_position(position);
+            }
+            if let Some(rotation) = diff.rotation {
+                transform.set_rotation(rotation);
+            }
+            if let Some(scale) = diff.scale {
+                transform.set_scale(scale);
+            }
+            if let Some(visibility) = diff.visibility {
+                node.set_visibility(visibility);
+            }
+            if let Some(enabled) = diff.enabled {
+                node.set_enabled(enabled);
+            }
+            if let (Some(state), Some(script)) = (diff.script.as_ref(), node.script_mut()) {
+                state.apply(script)?;
+            }
+        }
+
+        scene.graph.update_hierarchical_data();
+
+        Ok(NodeHandleMap { map })
+    }
+
+    /// Writes the save game to the given file.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.visit("SaveGame", &mut visitor)?;
+        visitor.save_binary(path)
+    }
+
+    /// Loads a save game from the given file.
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        let mut save_game = Self::default();
+        save_game.visit("SaveGame", &mut visitor)?;
+        Ok(save_game)
+    }
+
+    /// Writes the save game to a vector of bytes. It could be used to store save games in a custom
+    /// storage (for example in web storage on WebAssembly).
+    pub fn save_to_vec(&mut self) -> Result<Vec<u8>, VisitError> {
+        let mut visitor = Visitor::new();
+        self.visit("SaveGame", &mut visitor)?;
+        visitor.save_binary_to_vec()
+    }
+
+    /// Loads a save game from the given bytes, that were produced by [`Self::save_to_vec`].
+    pub fn load_from_memory(data: Vec<u8>) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_from_memory(data)?;
+        let mut save_game = Self::default();
+        save_game.visit("SaveGame", &mut visitor)?;
+        Ok(save_game)
+    }
+}
+
+// Checks whether the node is a descendant of a saveable node, that was created at runtime. Such
+// nodes are stored as a part of the ancestor.
+fn is_inside_spawned(graph: &Graph, original: &Graph, handle: Handle<Node>) -> bool {
+    let mut ancestor = graph[handle].parent();
+    while let Some(node) = graph.try_get(ancestor) {
+        if node.is_saveable() && original.try_get(ancestor).is_none() {
+            return true;
+        }
+        ancestor = node.parent();
+    }
+    false
+}
+
+/// A save slot with its metadata.
+#[derive(Clone, Debug)]
+pub struct SaveSlot {
+    /// Name of the slot, it is the name of the save file without extension.
+    pub name: String,
+    /// Metadata of the save game in the slot.
+    pub metadata: SaveGameMetadata,
+}
+
+/// Manages a set of save files in a directory, every save game is stored in a separate file (slot)
+/// with [`SAVE_FILE_EXTENSION`] extension.
+#[derive(Clone, Debug)]
+pub struct SaveSlots {
+    directory: PathBuf,
+}
+
+impl SaveSlots {
+    /// Creates new save slots manager for the given directory. The directory will be created on the
+    /// first save.
+    pub fn new<P: AsRef<Path>>(directory: P) -> Self {
+        Self {
+            directory: directory.as_ref().to_owned(),
+        }
+    }
+
+    /// Returns a directory with save files.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns a path of the save file of the given slot.
+    pub fn slot_path(&self, slot: &str) -> PathBuf {
+        self.directory
+            .join(slot)
+            .with_extension(SAVE_FILE_EXTENSION)
+    }
+
+    /// Writes the save game to the given slot, previous content of the slot is overwritten.
+    pub fn save(&self, slot: &str, save_game: &mut SaveGame) -> VisitResult {
+        std::fs::create_dir_all(&self.directory)?;
+        save_game.save(self.slot_path(slot))
+    }
+
+    /// Loads a save game from the given slot.
+    pub async fn load(&self, slot: &str) -> Result<SaveGame, VisitError> {
+        SaveGame::load(self.slot_path(slot)).await
+    }
+
+    /// Removes the given slot.
+    pub fn delete(&self, slot: &str) -> std::io::Result<()> {
+        std::fs::remove_file(self.slot_path(slot))
+    }
+
+    /// Returns a list of all slots with their metadata sorted by time of creation, the most recent
+    /// slot is the first. Only metadata is read, the rest of save games is ignored.
+    pub async fn list(&self) -> Vec<SaveSlot> {
+        let mut slots = Vec::new();
+
+        if let Ok(dir) = std::fs::read_dir(&self.directory) {
+            for entry in dir.flatten() {
+                let path = entry.path();
+                if path
+                    .extension()
+                    .map_or(true, |ext| ext != SAVE_FILE_EXTENSION)
+                {
+                    continue;
+                }
+
+                if let Ok(metadata) = Self::read_metadata(&path).await {
+                    slots.push(SaveSlot {
+                        name: path
+                            .file_stem()
+                            .map(|s| s.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        metadata,
+                    });
+                }
+            }
+        }
+
+        slots.sort_by(|a, b| b.metadata.timestamp.cmp(&a.metadata.timestamp));
+
+        slots
+    }
+
+    async fn read_metadata(path: &Path) -> Result<SaveGameMetadata, VisitError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        let mut region = visitor.enter_region("SaveGame")?;
+        let mut metadata = SaveGameMetadata::default();
+        metadata.visit("Metadata", &mut region)?;
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{
+            algebra::Vector3, futures::executor::block_on, reflect::prelude::*, uuid::uuid,
+            uuid::Uuid, visitor::prelude::*, TypeUuidProvider,
+        },
+        engine::{self, SerializationContext},
+        impl_component_provider,
+        resource::model::Model,
+        scene::{
+            base::BaseBuilder, pivot::PivotBuilder, save::SaveGame, transform::TransformBuilder,
+            Scene, SceneLoader,
+        },
+        script::{Script, ScriptTrait},
+    };
+    use std::{fs, path::Path, sync::Arc};
+
+    #[derive(Visit, Reflect, Default, Debug, Clone)]
+    struct Health {
+        max: f32,
+        current: f32,
+    }
+
+    impl_component_provider!(Health);
+
+    impl TypeUuidProvider for Health {
+        fn type_uuid() -> Uuid {
+            uuid!("7b9ba5c8-5d5a-4f1a-a3a0-5b3f4f2e7a61")
+        }
+    }
+
+    impl ScriptTrait for Health {
+        fn visit_save_state(&mut self, visitor: &mut Visitor) -> VisitResult {
+            self.current.visit("Current", visitor)
+        }
+
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    fn create_level() -> Scene {
+        let mut scene = Scene::new();
+
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Player")
+                .with_saveable(true)
+                .with_script(Script::new(Health {
+                    max: 100.0,
+                    current: 100.0,
+                })),
+        )
+        .build(&mut scene.graph);
+
+        PivotBuilder::new(BaseBuilder::new().with_name("Enemy").with_saveable(true))
+            .build(&mut scene.graph);
+
+        PivotBuilder::new(BaseBuilder::new().with_name("Static")).build(&mut scene.graph);
+
+        scene
+    }
+
+    #[test]
+    fn test_save_game() {
+        if !Path::new("test_output").exists() {
+            fs::create_dir_all("test_output").unwrap();
+        }
+
+        let level_path = Path::new("test_output/save_game_level.rgs");
+
+        {
+            let mut level = create_level();
+            let mut visitor = Visitor::new();
+            level.save("Scene", &mut visitor).unwrap();
+            visitor.save_binary(level_path).unwrap();
+        }
+
+        let resource_manager = ResourceManager::new();
+        let serialization_context = SerializationContext::new();
+        serialization_context
+            .script_constructors
+            .add::<Health>("Health");
+        let serialization_context = Arc::new(serialization_context);
+        engine::initialize_resource_manager_loaders(
+            &resource_manager,
+            serialization_context.clone(),
+        );
+
+        let level = block_on(resource_manager.request::<Model, _>(level_path)).unwrap();
+
+        let mut scene = block_on(
+            block_on(SceneLoader::from_file(
+                level_path,
+                serialization_context.clone(),
+                resource_manager.clone(),
+            ))
+            .unwrap()
+            .finish(),
+        );
+
+        let player = scene.graph.find_by_name_from_root("Player").unwrap().0;
+        let enemy = scene.graph.find_by_name_from_root("Enemy").unwrap().0;
+        let static_node = scene.graph.find_by_name_from_root("Static").unwrap().0;
+
+        // Change the state of the level.
+        scene.graph[player]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 2.0, 3.0));
+        scene.graph[player]
+            .try_get_script_mut::<Health>()
+            .unwrap()
+            .current = 50.0;
+        scene.graph.remove_node(enemy);
+        // Non-saveable nodes must be ignored.
+        scene.graph[static_node]
+            .local_transform_mut()
+            .set_position(Vector3::new(3.0, 2.0, 1.0));
+        let item = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Item")
+                .with_saveable(true)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .build(),
+                )
+                .with_children(
+                    &[PivotBuilder::new(BaseBuilder::new().with_name("ItemMesh"))
+                        .build(&mut scene.graph)],
+                ),
+        )
+        .build(&mut scene.graph);
+        scene.graph.link_nodes(item, player);
+        // Parent of a spawned node is not saveable, so the node will be attached to the root.
+        let loot = PivotBuilder::new(BaseBuilder::new().with_name("Loot").with_saveable(true))
+            .build(&mut scene.graph);
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Holder")
+                .with_children(&[loot]),
+        )
+        .build(&mut scene.graph);
+
+        let mut save_game = SaveGame::capture(&mut scene, &level).unwrap();
+        save_game.metadata.name = "Test".to_string();
+
+        assert_eq!(save_game.removed, vec![enemy]);
+        assert_eq!(save_game.modified.len(), 1);
+        assert_eq!(save_game.spawned.len(), 2);
+
+        let data = save_game.save_to_vec().unwrap();
+        let save_game = SaveGame::load_from_memory(data).unwrap();
+        assert_eq!(save_game.metadata.name, "Test");
+
+        let restored =
+            block_on(save_game.restore(resource_manager.clone(), serialization_context.clone()))
+                .unwrap();
+
+        let graph = &restored.graph;
+        let player = graph.find_by_name_from_root("Player").unwrap();
+        assert_eq!(
+            **player.1.local_transform().position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        let health = player.1.try_get_script::<Health>().unwrap();
+        assert_eq!(health.current, 50.0);
+        assert_eq!(health.max, 100.0);
+        assert!(graph.find_by_name_from_root("Enemy").is_none());
+        let static_node = graph.find_by_name_from_root("Static").unwrap().1;
+        assert_eq!(
+            **static_node.local_transform().position(),
+            Vector3::default()
+        );
+        let item = graph.find_by_name_from_root("Item").unwrap();
+        assert_eq!(item.1.parent(), player.0);
+        assert_eq!(
+            **item.1.local_transform().position(),
+            Vector3::new(0.0, 1.0, 0.0)
+        );
+        assert!(graph.find_by_name_from_root("ItemMesh").is_some());
+        assert!(graph.find_by_name_from_root("Holder").is_none());
+        let loot = graph.find_by_name_from_root("Loot").unwrap();
+        assert_eq!(loot.1.parent(), graph.get_root());
+    }
+}
@@ -0,0 +1,1143 @@
+//! Vehicle is a node that turns a rigid body into a wheeled vehicle. See [`Vehicle`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Unit, UnitQuaternion, Vector3},
+        color::Color,
+        curve::{Curve, CurveKey, CurveKeyKind},
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        debug::{Line, SceneDrawingContext},
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph, NodePool,
+        },
+        node::{Node, NodeTrait, UpdateContext},
+        rigidbody::RigidBody,
+        Scene,
+    },
+};
+use std::ops::{Deref, DerefMut};
+
+// Longitudinal speed (in m/s) below which the vehicle is considered stopped, the automatic gearbox
+// switches between forward gears and reverse only at lower speeds.
+const REVERSE_SPEED_THRESHOLD: f32 = 0.5;
+
+fn default_longitudinal_friction() -> Curve {
+    Curve::from(vec![
+        CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+        CurveKey::new(0.1, 1.0, CurveKeyKind::Linear),
+        CurveKey::new(1.0, 0.75, CurveKeyKind::Linear),
+    ])
+}
+
+fn default_lateral_friction() -> Curve {
+    Curve::from(vec![
+        CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+        CurveKey::new(8.0f32.to_radians(), 1.0, CurveKeyKind::Linear),
+        CurveKey::new(90.0f32.to_radians(), 0.7, CurveKeyKind::Linear),
+    ])
+}
+
+fn default_torque_curve() -> Curve {
+    Curve::from(vec![
+        CurveKey::new(0.0, 0.6, CurveKeyKind::Linear),
+        CurveKey::new(0.7, 1.0, CurveKeyKind::Linear),
+        CurveKey::new(1.0, 0.8, CurveKeyKind::Linear),
+    ])
+}
+
+/// Wheel is a part of a vehicle, that defines where the suspension is attached to the chassis, how stiff
+/// the suspension is, and how the tire interacts with the ground.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct Wheel {
+    /// A handle to a node that will be used as a visual representation of the wheel. The node should be
+    /// a direct child of the vehicle node, its local transform will be set automatically on every frame.
+    pub node: Handle<Node>,
+
+    /// A point (in local coordinates of the vehicle) where the top of the suspension is attached to the
+    /// chassis.
+    pub connection_point: Vector3<f32>,
+
+    /// Radius of the wheel.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub radius: f32,
+
+    /// Mass of the wheel, it is used to calculate moment of inertia of the wheel.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub mass: f32,
+
+    /// Length of the suspension when it is not loaded.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub suspension_rest_length: f32,
+
+    /// Stiffness of the suspension spring (N/m).
+    #[reflect(min_value = 0.0)]
+    pub suspension_stiffness: f32,
+
+    /// Damping coefficient of the suspension (N*s/m).
+    #[reflect(min_value = 0.0)]
+    pub suspension_damping: f32,
+
+    /// Max force the suspension can apply to the chassis.
+    #[reflect(min_value = 0.0)]
+    pub max_suspension_force: f32,
+
+    /// Whether the wheel is steered by [`Vehicle::set_steering`] or not.
+    pub steerable: bool,
+
+    /// Whether the engine torque is applied to the wheel or not.
+    pub driven: bool,
+
+    /// Max brake torque of the wheel (N*m).
+    #[reflect(min_value = 0.0)]
+    pub max_brake_torque: f32,
+
+    /// Tire friction curve that maps absolute slip ratio (`0.0` - no slip, `1.0` - full lock or spin) to
+    /// a friction coefficient along the wheel's rolling direction.
+    pub longitudinal_friction: Curve,
+
+    /// Tire friction curve that maps absolute slip angle (in radians) to a friction coefficient in the
+    /// direction perpendicular to the wheel's rolling direction.
+    pub lateral_friction: Curve,
+
+    /// Multiplier for both friction curves, it could be used to simulate different kinds of tires.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub friction_scale: f32,
+}
+
+impl Default for Wheel {
+    fn default() -> Self {
+        Self {
+            node: Default::default(),
+            connection_point: Default::default(),
+            radius: 0.35,
+            mass: 20.0,
+            suspension_rest_length: 0.3,
+            suspension_stiffness: 35000.0,
+            suspension_damping: 3500.0,
+            max_suspension_force: 60000.0,
+            steerable: false,
+            driven: false,
+            max_brake_torque: 1500.0,
+            longitudinal_friction: default_longitudinal_friction(),
+            lateral_friction: default_lateral_friction(),
+            friction_scale: 1.0,
+        }
+    }
+}
+
+/// Runtime state of a wheel. It is updated on every frame and can be used to drive sounds, particle
+/// effects, skid marks, etc.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct WheelState {
+    /// `true` if the wheel touches the ground, `false` - otherwise.
+    pub in_contact: bool,
+    /// A handle of a collider the wheel is touching.
+    pub ground: Handle<Node>,
+    /// World-space contact point with the ground.
+    pub contact_point: Vector3<f32>,
+    /// World-space normal of the ground at the contact point.
+    pub contact_normal: Vector3<f32>,
+    /// Current length of the suspension.
+    pub suspension_length: f32,
+    /// Amount of force the suspension applies to the chassis.
+    pub suspension_force: f32,
+    /// Angular velocity of the wheel (rad/s).
+    pub angular_velocity: f32,
+    /// Current rotation angle of the wheel around its axle (radians).
+    pub rotation_angle: f32,
+    /// Current steering angle of the wheel (radians).
+    pub steering_angle: f32,
+    /// Current slip ratio of the tire.
+    pub slip_ratio: f32,
+    /// Current slip angle of the tire (radians).
+    pub slip_angle: f32,
+}
+
+/// Engine produces torque that is transmitted to driven wheels through a [`Gearbox`].
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct Engine {
+    /// Peak torque of the engine (N*m).
+    #[reflect(min_value = 0.0)]
+    pub max_torque: f32,
+
+    /// Rotations per minute at which the engine stops producing torque.
+    #[reflect(min_value = 0.0)]
+    pub max_rpm: f32,
+
+    /// Rotations per minute of the engine when it is idle.
+    #[reflect(min_value = 0.0)]
+    pub idle_rpm: f32,
+
+    /// A curve that maps normalized rpm (`rpm / max_rpm`) to a fraction of the peak torque.
+    pub torque_curve: Curve,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self {
+            max_torque: 400.0,
+            max_rpm: 7000.0,
+            idle_rpm: 900.0,
+            torque_curve: default_torque_curve(),
+        }
+    }
+}
+
+impl Engine {
+    /// Calculates torque of the engine at the given rpm and throttle.
+    pub fn torque(&self, rpm: f32, throttle: f32) -> f32 {
+        if rpm >= self.max_rpm || self.max_rpm <= 0.0 {
+            0.0
+        } else {
+            self.max_torque * self.torque_curve.value_at(rpm / self.max_rpm) * throttle
+        }
+    }
+}
+
+/// Gearbox transmits engine torque to driven wheels. Gear index `0` is neutral, `-1` is reverse, and
+/// positive indices are forward gears.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct Gearbox {
+    /// Ratios of forward gears, starting from the first gear.
+    pub forward_ratios: Vec<f32>,
+
+    /// Ratio of the reverse gear.
+    #[reflect(min_value = 0.0)]
+    pub reverse_ratio: f32,
+
+    /// Ratio of the final drive (differential).
+    #[reflect(min_value = 0.0)]
+    pub final_drive_ratio: f32,
+
+    /// If `true`, gears will be switched automatically using the shift thresholds.
+    pub automatic: bool,
+
+    /// Engine rpm at which automatic gearbox switches to the next gear.
+    #[reflect(min_value = 0.0)]
+    pub shift_up_rpm: f32,
+
+    /// Engine rpm at which automatic gearbox switches to the previous gear.
+    #[reflect(min_value = 0.0)]
+    pub shift_down_rpm: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    current_gear: i32,
+}
+
+impl Default for Gearbox {
+    fn default() -> Self {
+        Self {
+            forward_ratios: vec![3.5, 2.2, 1.5, 1.1, 0.9],
+            reverse_ratio: 3.2,
+            final_drive_ratio: 3.4,
+            automatic: true,
+            shift_up_rpm: 6000.0,
+            shift_down_rpm: 2500.0,
+            current_gear: 1,
+        }
+    }
+}
+
+impl Gearbox {
+    /// Returns current gear index. `0` is neutral, `-1` is reverse, and positive indices are forward gears.
+    pub fn current_gear(&self) -> i32 {
+        self.current_gear
+    }
+
+    /// Sets current gear index. The index is clamped to the range of available gears.
+    pub fn set_current_gear(&mut self, gear: i32) {
+        self.current_gear = gear.clamp(-1, self.forward_ratios.len() as i32);
+    }
+
+    /// Returns the total ratio (including final drive) of the current gear. The ratio is negative for
+    /// the reverse gear and zero for neutral.
+    pub fn total_ratio(&self) -> f32 {
+        let ratio = match self.current_gear {
+            0 => 0.0,
+            -1 => -self.reverse_ratio,
+            gear => self
+                .forward_ratios
+                .get(gear as usize - 1)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        ratio * self.final_drive_ratio
+    }
+
+    // `speed` is a longitudinal speed of the chassis (in m/s), it is positive when the vehicle moves
+    // forward.
+    fn update_automatic(&mut self, rpm: f32, throttle: f32, speed: f32) {
+        if !self.automatic {
+            return;
+        }
+
+        if throttle < 0.0 && self.current_gear >= 0 && speed <= REVERSE_SPEED_THRESHOLD {
+            // Allow switching to reverse only when the vehicle is almost stopped.
+            self.current_gear = -1;
+        } else if throttle > 0.0 && self.current_gear <= 0 && speed >= -REVERSE_SPEED_THRESHOLD {
+            self.current_gear = 1;
+        } else if self.current_gear > 0 {
+            if rpm > self.shift_up_rpm && (self.current_gear as usize) < self.forward_ratios.len() {
+                self.current_gear += 1;
+            } else if rpm < self.shift_down_rpm && self.current_gear > 1 {
+                self.current_gear -= 1;
+            }
+        }
+    }
+}
+
+// Checks whether the node is the chassis or any of its descendants. The vehicle node is taken out of the pool
+// while it is being updated, so its parent is passed explicitly.
+fn is_part_of_chassis(
+    nodes: &NodePool,
+    mut handle: Handle<Node>,
+    chassis: Handle<Node>,
+    vehicle: Handle<Node>,
+    vehicle_parent: Handle<Node>,
+) -> bool {
+    while handle.is_some() {
+        if handle == chassis {
+            return true;
+        }
+
+        handle = if handle == vehicle {
+            vehicle_parent
+        } else {
+            match nodes.try_borrow(handle) {
+                Some(node) => node.parent(),
+                None => return false,
+            }
+        };
+    }
+    false
+}
+
+/// Vehicle is a node that turns a rigid body (chassis) into a wheeled vehicle. It uses ray casts to
+/// find contact points of the wheels with the ground, simulates suspension springs, tire friction,
+/// engine with a gearbox, brakes and steering. All the forces are applied to the chassis rigid body.
+///
+/// # Setup
+///
+/// The vehicle node should be a child of a rigid body that will be used as a chassis (or the chassis
+/// could be set explicitly using [`Vehicle::set_chassis`]). Wheel nodes (usually meshes) should be
+/// direct children of the vehicle node, their local transform will be set automatically, so you don't
+/// need to position them manually. It is recommended to disable sleeping of the chassis rigid body,
+/// otherwise the vehicle won't react to the input after it was put to sleep.
+///
+/// Vehicle uses "forward" vector of its global transform (local Z axis) as the driving direction, "up"
+/// vector (local Y axis) as the suspension direction, and "side" vector (local X axis) as the axle
+/// direction.
+///
+/// # Controls
+///
+/// Use [`Vehicle::set_throttle`], [`Vehicle::set_brake`] and [`Vehicle::set_steering`] to control the
+/// vehicle, typically from a script.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector3, pool::Handle},
+///     scene::{
+///         base::BaseBuilder,
+///         collider::{ColliderBuilder, ColliderShape},
+///         graph::Graph,
+///         node::Node,
+///         pivot::PivotBuilder,
+///         rigidbody::RigidBodyBuilder,
+///         vehicle::{VehicleBuilder, Wheel},
+///     },
+/// };
+///
+/// fn create_vehicle(graph: &mut Graph) -> Handle<Node> {
+///     let mut wheels = Vec::new();
+///     let mut wheel_nodes = Vec::new();
+///     for (x, z, front) in [
+///         (-0.8, 1.3, true),
+///         (0.8, 1.3, true),
+///         (-0.8, -1.3, false),
+///         (0.8, -1.3, false),
+///     ] {
+///         let node = PivotBuilder::new(BaseBuilder::new()).build(graph);
+///         wheel_nodes.push(node);
+///         wheels.push(Wheel {
+///             node,
+///             connection_point: Vector3::new(x, 0.0, z),
+///             steerable: front,
+///             driven: !front,
+///             ..Default::default()
+///         });
+///     }
+///
+///     let vehicle = VehicleBuilder::new(BaseBuilder::new().with_children(&wheel_nodes))
+///         .with_wheels(wheels)
+///         .build(graph);
+///
+///     let collider = ColliderBuilder::new(BaseBuilder::new())
+///         .with_shape(ColliderShape::cuboid(1.0, 0.5, 2.0))
+///         .build(graph);
+///
+///     RigidBodyBuilder::new(BaseBuilder::new().with_children(&[vehicle, collider]))
+///         .with_mass(1200.0)
+///         .with_can_sleep(false)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Visit, Reflect, Clone, Debug)]
+pub struct Vehicle {
+    base: Base,
+
+    #[reflect(setter = "set_chassis")]
+    chassis: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_wheels")]
+    wheels: InheritableVariable<Vec<Wheel>>,
+
+    #[reflect(setter = "set_engine")]
+    engine: InheritableVariable<Engine>,
+
+    #[reflect(setter = "set_gearbox")]
+    gearbox: InheritableVariable<Gearbox>,
+
+    #[reflect(min_value = 0.0, max_value = 90.0)]
+    #[reflect(setter = "set_max_steering_angle")]
+    max_steering_angle: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_steering_speed")]
+    steering_speed: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    wheel_states: Vec<WheelState>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    brake: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    steering: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rpm: f32,
+}
+
+impl Default for Vehicle {
+    fn default() -> Self {
+        VehicleBuilder::new(BaseBuilder::new()).build_vehicle()
+    }
+}
+
+impl Deref for Vehicle {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Vehicle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Vehicle {
+    fn type_uuid() -> Uuid {
+        uuid!("0a4a1b1c-f0b6-4d3a-9b53-5a8d2e0f3c71")
+    }
+}
+
+impl Vehicle {
+    /// Sets a handle of a rigid body that will be used as the chassis. If the handle is
+    /// [`Handle::NONE`], the parent node of the vehicle will be used.
+    pub fn set_chassis(&mut self, chassis: Handle<Node>) -> Handle<Node> {
+        self.chassis.set_value_and_mark_modified(chassis)
+    }
+
+    /// Returns a handle of the chassis rigid body. See [`Self::set_chassis`] for more info.
+    pub fn chassis(&self) -> Handle<Node> {
+        *self.chassis
+    }
+
+    /// Sets new set of wheels of the vehicle.
+    pub fn set_wheels(&mut self, wheels: Vec<Wheel>) -> Vec<Wheel> {
+        self.wheel_states.clear();
+        self.wheels.set_value_and_mark_modified(wheels)
+    }
+
+    /// Returns a slice of wheels of the vehicle.
+    pub fn wheels(&self) -> &[Wheel] {
+        &self.wheels
+    }
+
+    /// Returns a runtime state of a wheel at the given index (if any).
+    pub fn wheel_state(&self, index: usize) -> Option<&WheelState> {
+        self.wheel_states.get(index)
+    }
+
+    /// Sets new engine of the vehicle.
+    pub fn set_engine(&mut self, engine: Engine) -> Engine {
+        self.engine.set_value_and_mark_modified(engine)
+    }
+
+    /// Returns a reference to the engine of the vehicle.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Sets new gearbox of the vehicle.
+    pub fn set_gearbox(&mut self, gearbox: Gearbox) -> Gearbox {
+        self.gearbox.set_value_and_mark_modified(gearbox)
+    }
+
+    /// Returns a reference to the gearbox of the vehicle.
+    pub fn gearbox(&self) -> &Gearbox {
+        &self.gearbox
+    }
+
+    /// Returns a mutable reference to the gearbox of the vehicle. Could be used to switch gears
+    /// manually.
+    pub fn gearbox_mut(&mut self) -> &mut Gearbox {
+        self.gearbox.get_value_mut_silent()
+    }
+
+    /// Sets max steering angle (in degrees) of the steerable wheels.
+    pub fn set_max_steering_angle(&mut self, angle: f32) -> f32 {
+        self.max_steering_angle.set_value_and_mark_modified(angle)
+    }
+
+    /// Returns max steering angle (in degrees) of the steerable wheels.
+    pub fn max_steering_angle(&self) -> f32 {
+        *self.max_steering_angle
+    }
+
+    /// Sets the speed (in degrees per second) with which the steering angle of the wheels changes.
+    pub fn set_steering_speed(&mut self, speed: f32) -> f32 {
+        self.steering_speed.set_value_and_mark_modified(speed)
+    }
+
+    /// Returns the speed (in degrees per second) with which the steering angle of the wheels changes.
+    pub fn steering_speed(&self) -> f32 {
+        *self.steering_speed
+    }
+
+    /// Sets throttle of the engine in `[-1; 1]` range. Negative values are used to drive in reverse
+    /// when the gearbox is automatic.
+    pub fn set_throttle(&mut self, throttle: f32) {
+        self.throttle = throttle.clamp(-1.0, 1.0);
+    }
+
+    /// Returns current throttle.
+    pub fn throttle(&self) -> f32 {
+        self.throttle
+    }
+
+    /// Sets brake strength in `[0; 1]` range.
+    pub fn set_brake(&mut self, brake: f32) {
+        self.brake = brake.clamp(0.0, 1.0);
+    }
+
+    /// Returns current brake strength.
+    pub fn brake(&self) -> f32 {
+        self.brake
+    }
+
+    /// Sets steering in `[-1; 1]` range, where `-1` is full left and `1` is full right.
+    pub fn set_steering(&mut self, steering: f32) {
+        self.steering = steering.clamp(-1.0, 1.0);
+    }
+
+    /// Returns current steering.
+    pub fn steering(&self) -> f32 {
+        self.steering
+    }
+
+    /// Returns current rotations per minute of the engine.
+    pub fn rpm(&self) -> f32 {
+        self.rpm
+    }
+
+    /// Returns current speed of the vehicle (in m/s) along its forward direction.
+    pub fn forward_speed(&self, graph: &Graph) -> f32 {
+        graph
+            .try_get_of_type::<RigidBody>(self.chassis_handle())
+            .map(|chassis| chassis.lin_vel().dot(&self.forward_axis()))
+            .unwrap_or_default()
+    }
+
+    fn chassis_handle(&self) -> Handle<Node> {
+        if self.chassis.is_some() {
+            *self.chassis
+        } else {
+            self.parent()
+        }
+    }
+
+    fn forward_axis(&self) -> Vector3<f32> {
+        self.global_transform()
+            .look()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::z)
+    }
+}
+
+impl NodeTrait for Vehicle {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let dt = context.dt;
+        if dt <= 0.0 {
+            return;
+        }
+
+        let chassis_handle = self.chassis_handle();
+        let (lin_vel, ang_vel, center) = if let Some(chassis) = context
+            .nodes
+            .try_borrow(chassis_handle)
+            .and_then(|n| n.cast::<RigidBody>())
+        {
+            (
+                chassis.lin_vel(),
+                chassis.ang_vel(),
+                chassis.global_position(),
+            )
+        } else {
+            return;
+        };
+
+        self.wheel_states
+            .resize(self.wheels.len(), Default::default());
+
+        let transform = self.global_transform();
+        let up = transform
+            .up()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+        let forward = self.forward_axis();
+        let side = up.cross(&forward);
+
+        // Engine rpm is derived from the average angular velocity of the driven wheels.
+        let total_ratio = self.gearbox.total_ratio();
+        let driven_count = self.wheels.iter().filter(|w| w.driven).count();
+        let avg_driven_angular_velocity = if driven_count > 0 {
+            self.wheels
+                .iter()
+                .zip(self.wheel_states.iter())
+                .filter(|(w, _)| w.driven)
+                .map(|(_, s)| s.angular_velocity)
+                .sum::<f32>()
+                / driven_count as f32
+        } else {
+            0.0
+        };
+        self.rpm =
+            (avg_driven_angular_velocity * total_ratio).abs() * 60.0 / (2.0 * std::f32::consts::PI);
+        self.rpm = self.rpm.max(self.engine.idle_rpm);
+
+        self.gearbox.get_value_mut_silent().update_automatic(
+            self.rpm,
+            self.throttle,
+            lin_vel.dot(&forward),
+        );
+        // Throttle that opposes current gear direction does not produce any torque, brakes should be
+        // used to stop the vehicle first.
+        let throttle = match self.gearbox.current_gear().signum() {
+            1 => self.throttle.max(0.0),
+            -1 => (-self.throttle).max(0.0),
+            _ => 0.0,
+        };
+        let total_ratio = self.gearbox.total_ratio();
+        let drive_torque = if driven_count > 0 {
+            self.engine.torque(self.rpm, throttle) * total_ratio / driven_count as f32
+        } else {
+            0.0
+        };
+
+        let target_steering_angle = self.steering * self.max_steering_angle.to_radians();
+        let max_steering_delta = self.steering_speed.to_radians() * dt;
+
+        let mut query_buffer = Vec::<Intersection>::new();
+        let mut forces = Vec::new();
+        let (vehicle_handle, vehicle_parent) = (self.self_handle, self.parent());
+
+        for (wheel, state) in self.wheels.iter().zip(self.wheel_states.iter_mut()) {
+            if wheel.steerable {
+                state.steering_angle += (target_steering_angle - state.steering_angle)
+                    .clamp(-max_steering_delta, max_steering_delta);
+            } else {
+                state.steering_angle = 0.0;
+            }
+
+            let steering_rotation =
+                UnitQuaternion::from_axis_angle(&Unit::new_normalize(up), state.steering_angle);
+            let wheel_forward = steering_rotation * forward;
+            let wheel_side = steering_rotation * side;
+
+            let connection_point = transform
+                .transform_point(&Point3::from(wheel.connection_point))
+                .coords;
+            let max_len = wheel.suspension_rest_length + wheel.radius;
+
+            context.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(connection_point),
+                    ray_direction: -up,
+                    max_len,
+                    groups: Default::default(),
+                    sort_results: true,
+                },
+                &mut query_buffer,
+            );
+
+            // Ignore colliders of the chassis and of any node attached to it.
+            let hit = query_buffer.iter().find(|i| {
+                !is_part_of_chassis(
+                    context.nodes,
+                    i.collider,
+                    chassis_handle,
+                    vehicle_handle,
+                    vehicle_parent,
+                )
+            });
+
+            let inertia = (0.5 * wheel.mass * wheel.radius * wheel.radius).max(f32::EPSILON);
+            let wheel_drive_torque = if wheel.driven { drive_torque } else { 0.0 };
+            let brake_torque = wheel.max_brake_torque * self.brake;
+
+            if let Some(hit) = hit {
+                let previous_length = if state.in_contact {
+                    state.suspension_length
+                } else {
+                    wheel.suspension_rest_length
+                };
+
+                state.in_contact = true;
+                state.ground = hit.collider;
+                state.contact_point = hit.position.coords;
+                state.contact_normal = hit.normal;
+                state.suspension_length =
+                    (hit.toi - wheel.radius).clamp(0.0, wheel.suspension_rest_length);
+
+                let compression = wheel.suspension_rest_length - state.suspension_length;
+                let compression_velocity = (previous_length - state.suspension_length) / dt;
+                state.suspension_force = (wheel.suspension_stiffness * compression
+                    + wheel.suspension_damping * compression_velocity)
+                    .clamp(0.0, wheel.max_suspension_force);
+
+                let contact_velocity = lin_vel + ang_vel.cross(&(state.contact_point - center));
+                let longitudinal_velocity = contact_velocity.dot(&wheel_forward);
+                let lateral_velocity = contact_velocity.dot(&wheel_side);
+
+                let wheel_velocity = state.angular_velocity * wheel.radius;
+                let reference_velocity = longitudinal_velocity.abs().max(1.0);
+                state.slip_ratio = (wheel_velocity - longitudinal_velocity) / reference_velocity;
+                state.slip_angle = lateral_velocity.atan2(longitudinal_velocity.abs().max(0.5));
+
+                let load = state.suspension_force;
+                let longitudinal_force = wheel
+                    .longitudinal_friction
+                    .value_at(state.slip_ratio.abs().min(1.0))
+                    * state.slip_ratio.signum()
+                    * load
+                    * wheel.friction_scale;
+                let lateral_force = -wheel.lateral_friction.value_at(state.slip_angle.abs())
+                    * state.slip_angle.signum()
+                    * load
+                    * wheel.friction_scale;
+
+                let force = up * state.suspension_force
+                    + wheel_forward * longitudinal_force
+                    + wheel_side * lateral_force;
+                forces.push((force, state.contact_point));
+
+                state.angular_velocity += wheel_drive_torque / inertia * dt;
+
+                // Tire force acts against the wheel's rotation, pulling its velocity towards the
+                // velocity of the ground. Clamp the change to prevent overshooting, which otherwise
+                // leads to oscillations on large time steps.
+                let friction_delta = longitudinal_force * wheel.radius / inertia * dt;
+                let rolling_angular_velocity = longitudinal_velocity / wheel.radius;
+                if (state.angular_velocity - rolling_angular_velocity).abs() <= friction_delta.abs()
+                {
+                    state.angular_velocity = rolling_angular_velocity;
+                } else {
+                    state.angular_velocity -= friction_delta;
+                }
+            } else {
+                state.in_contact = false;
+                state.ground = Handle::NONE;
+                state.suspension_length = wheel.suspension_rest_length;
+                state.suspension_force = 0.0;
+                state.slip_ratio = 0.0;
+                state.slip_angle = 0.0;
+                state.angular_velocity += wheel_drive_torque / inertia * dt;
+            }
+
+            // Brakes cannot reverse rotation of the wheel.
+            let brake_delta = brake_torque / inertia * dt;
+            if state.angular_velocity.abs() <= brake_delta {
+                state.angular_velocity = 0.0;
+            } else {
+                state.angular_velocity -= brake_delta * state.angular_velocity.signum();
+            }
+
+            state.rotation_angle =
+                (state.rotation_angle + state.angular_velocity * dt) % (2.0 * std::f32::consts::PI);
+
+            // Position visual representation of the wheel.
+            if let Some(wheel_node) = context.nodes.try_borrow_mut(wheel.node) {
+                let local_position =
+                    wheel.connection_point - Vector3::y() * state.suspension_length;
+                let local_rotation =
+                    UnitQuaternion::from_axis_angle(&Vector3::y_axis(), state.steering_angle)
+                        * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), state.rotation_angle);
+                wheel_node
+                    .local_transform_mut()
+                    .set_position(local_position)
+                    .set_rotation(local_rotation);
+            }
+        }
+
+        if let Some(chassis) = context
+            .nodes
+            .try_borrow_mut(chassis_handle)
+            .and_then(|n| n.cast_mut::<RigidBody>())
+        {
+            for (force, point) in forces {
+                chassis.apply_force_at_point(force, point);
+            }
+        }
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        let transform = self.global_transform();
+        for (wheel, state) in self.wheels.iter().zip(self.wheel_states.iter()) {
+            let begin = transform
+                .transform_point(&Point3::from(wheel.connection_point))
+                .coords;
+            let end = transform
+                .transform_point(&Point3::from(
+                    wheel.connection_point - Vector3::y() * state.suspension_length,
+                ))
+                .coords;
+            ctx.add_line(Line {
+                begin,
+                end,
+                color: if state.in_contact {
+                    Color::GREEN
+                } else {
+                    Color::RED
+                },
+            });
+            ctx.draw_circle(
+                Default::default(),
+                wheel.radius,
+                16,
+                transform
+                    * Matrix4::new_translation(
+                        &(wheel.connection_point - Vector3::y() * state.suspension_length),
+                    )
+                    * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), state.steering_angle)
+                        .to_homogeneous()
+                    * UnitQuaternion::from_axis_angle(
+                        &Vector3::y_axis(),
+                        std::f32::consts::FRAC_PI_2,
+                    )
+                    .to_homogeneous(),
+                Color::ORANGE,
+            );
+        }
+    }
+
+    fn validate(&self, scene: &Scene) -> Result<(), String> {
+        if scene
+            .graph
+            .try_get_of_type::<RigidBody>(self.chassis_handle())
+            .is_none()
+        {
+            return Err(
+                "The vehicle must have a rigid body as a chassis! Either make the \
+            vehicle a child of a rigid body or set the chassis explicitly."
+                    .to_string(),
+            );
+        }
+
+        for (i, wheel) in self.wheels.iter().enumerate() {
+            if wheel.node.is_some() && !self.children().contains(&wheel.node) {
+                return Err(format!(
+                    "The node of the wheel {i} must be a direct child of the vehicle!"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Allows you to create a vehicle node in declarative manner.
+pub struct VehicleBuilder {
+    base_builder: BaseBuilder,
+    chassis: Handle<Node>,
+    wheels: Vec<Wheel>,
+    engine: Engine,
+    gearbox: Gearbox,
+    max_steering_angle: f32,
+    steering_speed: f32,
+}
+
+impl VehicleBuilder {
+    /// Creates new vehicle builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            chassis: Handle::NONE,
+            wheels: Default::default(),
+            engine: Default::default(),
+            gearbox: Default::default(),
+            max_steering_angle: 35.0,
+            steering_speed: 180.0,
+        }
+    }
+
+    /// Sets desired chassis. See [`Vehicle::set_chassis`] for more info.
+    pub fn with_chassis(mut self, chassis: Handle<Node>) -> Self {
+        self.chassis = chassis;
+        self
+    }
+
+    /// Sets desired wheels.
+    pub fn with_wheels(mut self, wheels: Vec<Wheel>) -> Self {
+        self.wheels = wheels;
+        self
+    }
+
+    /// Sets desired engine.
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Sets desired gearbox.
+    pub fn with_gearbox(mut self, gearbox: Gearbox) -> Self {
+        self.gearbox = gearbox;
+        self
+    }
+
+    /// Sets desired max steering angle (in degrees).
+    pub fn with_max_steering_angle(mut self, angle: f32) -> Self {
+        self.max_steering_angle = angle;
+        self
+    }
+
+    /// Sets desired steering speed (in degrees per second).
+    pub fn with_steering_speed(mut self, speed: f32) -> Self {
+        self.steering_speed = speed;
+        self
+    }
+
+    /// Creates new vehicle instance.
+    pub fn build_vehicle(self) -> Vehicle {
+        Vehicle {
+            base: self.base_builder.build_base(),
+            chassis: self.chassis.into(),
+            wheels: self.wheels.into(),
+            engine: self.engine.into(),
+            gearbox: self.gearbox.into(),
+            max_steering_angle: self.max_steering_angle.into(),
+            steering_speed: self.steering_speed.into(),
+            wheel_states: Default::default(),
+            throttle: 0.0,
+            brake: 0.0,
+            steering: 0.0,
+            rpm: 0.0,
+        }
+    }
+
+    /// Creates new vehicle node, but does not add it to the graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_vehicle())
+    }
+
+    /// Creates new vehicle node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            pivot::PivotBuilder,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            vehicle::{Engine, Gearbox, Vehicle, VehicleBuilder, Wheel},
+        },
+    };
+
+    #[test]
+    fn test_gearbox_ratios() {
+        let mut gearbox = Gearbox::default();
+
+        gearbox.set_current_gear(0);
+        assert_eq!(gearbox.total_ratio(), 0.0);
+
+        gearbox.set_current_gear(-1);
+        assert!(gearbox.total_ratio() < 0.0);
+
+        gearbox.set_current_gear(100);
+        assert_eq!(gearbox.current_gear(), gearbox.forward_ratios.len() as i32);
+    }
+
+    #[test]
+    fn test_automatic_gearbox() {
+        let mut gearbox = Gearbox::default();
+
+        gearbox.update_automatic(gearbox.shift_up_rpm + 1.0, 1.0, 10.0);
+        assert_eq!(gearbox.current_gear(), 2);
+
+        gearbox.update_automatic(gearbox.shift_down_rpm - 1.0, 1.0, 5.0);
+        assert_eq!(gearbox.current_gear(), 1);
+
+        // Braking while moving forward must not engage reverse.
+        gearbox.update_automatic(gearbox.shift_down_rpm - 1.0, -1.0, 5.0);
+        assert_eq!(gearbox.current_gear(), 1);
+
+        gearbox.update_automatic(gearbox.shift_down_rpm - 1.0, -1.0, 0.0);
+        assert_eq!(gearbox.current_gear(), -1);
+
+        // Same for the throttle while moving backwards.
+        gearbox.update_automatic(1000.0, 1.0, -5.0);
+        assert_eq!(gearbox.current_gear(), -1);
+
+        gearbox.update_automatic(1000.0, 1.0, 0.0);
+        assert_eq!(gearbox.current_gear(), 1);
+    }
+
+    #[test]
+    fn test_vehicle_throttle_moves_chassis() {
+        let mut graph = Graph::new();
+
+        let ground_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(100.0, 0.5, 100.0))
+            .build(&mut graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -0.5, 0.0))
+                        .build(),
+                )
+                .with_children(&[ground_collider]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+
+        let mut wheels = Vec::new();
+        let mut wheel_nodes = Vec::new();
+        for (x, z, front) in [
+            (-0.8, 1.3, true),
+            (0.8, 1.3, true),
+            (-0.8, -1.3, false),
+            (0.8, -1.3, false),
+        ] {
+            let node = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+            wheel_nodes.push(node);
+            wheels.push(Wheel {
+                node,
+                connection_point: Vector3::new(x, 0.0, z),
+                steerable: front,
+                driven: !front,
+                ..Default::default()
+            });
+        }
+        let vehicle = VehicleBuilder::new(BaseBuilder::new().with_children(&wheel_nodes))
+            .with_wheels(wheels)
+            .build(&mut graph);
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(1.0, 0.25, 2.0))
+            .build(&mut graph);
+        let chassis = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.7, 0.0))
+                        .build(),
+                )
+                .with_children(&[vehicle, collider]),
+        )
+        .with_mass(1200.0)
+        .with_can_sleep(false)
+        .build(&mut graph);
+
+        let dt = 1.0 / 60.0;
+        let frame_size = Vector2::new(100.0, 100.0);
+
+        // Let the suspension settle first.
+        for _ in 0..60 {
+            graph.update(frame_size, dt, Default::default());
+        }
+        let start = graph[chassis].global_position();
+
+        graph[vehicle]
+            .cast_mut::<Vehicle>()
+            .unwrap()
+            .set_throttle(1.0);
+        for _ in 0..120 {
+            graph.update(frame_size, dt, Default::default());
+        }
+
+        let vehicle_ref = graph[vehicle].cast::<Vehicle>().unwrap();
+        assert_eq!(vehicle_ref.gearbox().current_gear(), 1);
+        assert!(vehicle_ref
+            .wheel_states
+            .iter()
+            .all(|s| s.in_contact && s.ground == ground_collider));
+
+        let travelled = graph[chassis].global_position() - start;
+        assert!(travelled.z > 1.0);
+        assert!(travelled.x.abs() < travelled.z * 0.1);
+    }
+
+    #[test]
+    fn test_engine_torque() {
+        let engine = Engine::default();
+        assert_eq!(engine.torque(engine.max_rpm, 1.0), 0.0);
+        assert_eq!(engine.torque(1000.0, 0.0), 0.0);
+        assert!(engine.torque(engine.max_rpm * 0.7, 1.0) > engine.torque(0.0, 1.0));
+    }
+}
@@ -11,8 +11,9 @@ use crate::{
         uuid::Uuid,
         visitor::{Visit, VisitResult, Visitor},
     },
-    engine::ScriptMessageDispatcher,
+    engine::{task::TaskPoolHandler, ScriptMessageDispatcher},
     event::Event,
+    input::InputSystem,
     plugin::Plugin,
     scene::{node::Node, Scene},
     utils::component::ComponentProvider,
@@ -25,6 +26,8 @@ use std::{
 };
 
 pub mod constructor;
+#[cfg(feature = "rhai_scripting")]
+pub mod rhai;
 
 /// A script message's payload.
 pub trait ScriptMessagePayload: Any + Send {
@@ -33,6 +36,12 @@ pub trait ScriptMessagePayload: Any + Send {
 
     /// Returns `self` as `&dyn Any`
     fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Returns an address of the code of the payload type, it is used to find out whether the
+    /// type belongs to a dynamic plugin. See [`crate::plugin::dynamic::DynamicPlugin`] docs.
+    #[cfg(feature = "hot_reload")]
+    #[doc(hidden)]
+    fn code_address(&self) -> usize;
 }
 
 impl dyn ScriptMessagePayload {
@@ -58,6 +67,13 @@ where
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+
+    #[cfg(feature = "hot_reload")]
+    fn code_address(&self) -> usize {
+        // The method is instantiated in the crate that boxes the payload, so the address points to
+        // the code of that crate.
+        <T as ScriptMessagePayload>::as_any_ref as usize
+    }
 }
 
 /// Defines how a script message will be delivered for each node in a hierarchy.
@@ -209,9 +225,20 @@ pub struct ScriptContext<'a, 'b, 'c> {
     /// in most of the script methods.
     pub scene: &'b mut Scene,
 
+    /// A handle of a scene the script instance belongs to.
+    pub scene_handle: Handle<Scene>,
+
     /// A reference to resource manager, use it to load resources.
     pub resource_manager: &'a ResourceManager,
 
+    /// A reference to the task pool, that allows you to spawn async tasks and receive their results
+    /// in a callback with mutable access to the script. See [`TaskPoolHandler`] docs for more info.
+    pub task_pool: &'a mut TaskPoolHandler,
+
+    /// A reference to the input system, use it to query the state of actions and axes. See [`InputSystem`]
+    /// docs for more info.
+    pub input: &'a InputSystem,
+
     /// An message sender. Every message sent via this sender will be then passed to every [`ScriptTrait::on_message`]
     /// method of every script.
     pub message_sender: &'c ScriptMessageSender,
@@ -324,6 +351,60 @@ pub trait ScriptTrait: BaseScript + ComponentProvider {
     /// 60 times per second (this may change in future releases).
     fn on_update(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
 
+    /// Performs a single fixed update tick of the script. Unlike [`Self::on_update`], the method is
+    /// called with the fixed rate of the physics simulation (see [`crate::engine::Engine::set_fixed_update_rate`]),
+    /// right before each physics step. It could be called zero or multiple times per frame, depending on
+    /// the frame rate. [`ScriptContext::dt`] contains fixed time step. Use this method for the
+    /// gameplay logic that interacts with physics (applies forces, changes velocities, etc.), so it will
+    /// behave the same regardless of the frame rate.
+    fn on_fixed_update(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
+
+    /// Visits the part of the script state that should be stored in save files. Only the fields that are
+    /// visited here will be saved and restored, the rest of the script will have the values from the original
+    /// scene. The method is called only for scripts of the scene nodes that are marked as saveable (see
+    /// [`crate::scene::base::Base::set_saveable`]), see [`crate::scene::save`] module docs for more info.
+    ///
+    /// ```rust
+    /// use fyrox::{
+    ///     core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider},
+    ///     impl_component_provider,
+    ///     script::ScriptTrait,
+    /// };
+    ///
+    /// #[derive(Reflect, Visit, Debug, Clone)]
+    /// struct Enemy {
+    ///     // Does not change in runtime, there is no need to save it.
+    ///     max_health: f32,
+    ///     health: f32,
+    ///     ammo: u32,
+    /// }
+    ///
+    /// # impl TypeUuidProvider for Enemy {
+    /// #     fn type_uuid() -> Uuid {
+    /// #         todo!();
+    /// #     }
+    /// # }
+    ///
+    /// # impl_component_provider!(Enemy);
+    ///
+    /// impl ScriptTrait for Enemy {
+    ///     fn visit_save_state(&mut self, visitor: &mut Visitor) -> VisitResult {
+    ///         self.health.visit("Health", visitor)?;
+    ///         self.ammo.visit("Ammo", visitor)
+    ///     }
+    ///
+    ///     # fn id(&self) -> Uuid {
+    ///     #     Self::type_uuid()
+    ///     # }
+    /// }
+    /// ```
+    fn visit_save_state(
+        &mut self,
+        #[allow(unused_variables)] visitor: &mut Visitor,
+    ) -> VisitResult {
+        Ok(())
+    }
+
     /// Allows you to react to certain script messages. It could be used for communication between scripts; to
     /// bypass borrowing issues. If you need to receive messages of a particular type, you must subscribe to a type
     /// explicitly. Usually it is done in [`ScriptTrait::on_start`] method:
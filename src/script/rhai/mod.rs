@@ -0,0 +1,1024 @@
+//! Optional support of scripts written in [Rhai](https://rhai.rs) embedded scripting language. It allows
+//! writing game logic without Rust toolchain, the scripts are loaded as resources and are reloaded
+//! automatically when their files are changed (if the resource manager has a file system watcher).
+//! The module is available only with `rhai_scripting` feature.
+//!
+//! # Writing scripts
+//!
+//! A script is a `.rhai` file with a set of optional functions, that will be called by the engine:
+//!
+//! - `on_init(ctx)` - called once, when the script is initialized.
+//! - `on_start(ctx)` - called once, after every script in the scene is initialized.
+//! - `on_update(ctx)` - called every frame.
+//! - `on_fixed_update(ctx)` - called with the fixed rate of the physics simulation.
+//! - `on_message(ctx, message)` - called when the node receives a message sent by some other script.
+//! - `on_reload(ctx)` - called when the script file was changed and reloaded.
+//! - `on_deinit(ctx)` - called when the script is about to be destroyed.
+//!
+//! The state of a script instance is stored in `this` object map. The state is preserved when the script
+//! is reloaded and it is saved together with the scene (only numbers, strings, booleans, arrays and maps
+//! are serializable). Top-level statements of the script are not executed.
+//!
+//! `ctx` gives access to the engine, see [`RhaiContext`] for the full list of available functions. Nodes are
+//! referenced by handles, node properties could be read and written by their paths (see [`Reflect`]).
+//! Numbers are represented by `f32` and `i64` types, keep in mind that Rhai does not convert integers to
+//! floats implicitly - use float literals (`1.0` instead of `1`) in arguments.
+//!
+//! ```text
+//! fn on_init(ctx) {
+//!     this.speed = 5.0;
+//!     this.health = 100.0;
+//!     ctx.subscribe();
+//! }
+//!
+//! fn on_update(ctx) {
+//!     let offset = vec3(0.0, 0.0, ctx.axis("MoveForward") * this.speed * ctx.dt);
+//!     ctx.set_position(ctx.handle, ctx.position(ctx.handle) + offset);
+//!
+//!     if ctx.is_action_just_pressed("Fire") {
+//!         ctx.send_global(#{ kind: "shot", from: ctx.handle });
+//!     }
+//!
+//!     if ctx.get_property(ctx.handle, "base.visibility") && this.health <= 0.0 {
+//!         ctx.set_property(ctx.handle, "base.visibility", false);
+//!     }
+//! }
+//!
+//! fn on_message(ctx, message) {
+//!     if message.kind == "damage" {
+//!         this.health -= message.amount;
+//!     }
+//! }
+//! ```
+//!
+//! # Using scripts
+//!
+//! Rhai scripts are assigned to scene nodes as any other scripts:
+//!
+//! ```rust
+//! use fyrox::{
+//!     asset::manager::ResourceManager,
+//!     scene::node::Node,
+//!     script::{
+//!         rhai::{resource::RhaiScriptSource, RhaiScript},
+//!         Script,
+//!     },
+//! };
+//!
+//! fn assign_script(node: &mut Node, resource_manager: &ResourceManager) {
+//!     let source = resource_manager.request::<RhaiScriptSource, _>("data/scripts/player.rhai");
+//!     node.set_script(Some(Script::new(RhaiScript::new(source))));
+//! }
+//! ```
+//!
+//! Rhai scripts communicate with each other (and with Rust scripts) by [`RhaiMessage`]s.
+
+use crate::{
+    asset::{manager::ResourceManager, ResourceStateRef},
+    core::{
+        algebra::{Vector2, Vector3},
+        futures::executor::block_on,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    engine::ScriptMessageDispatcher,
+    impl_component_provider,
+    input::InputSystem,
+    resource::model::{Model, ModelResourceExtension},
+    scene::{node::Node, Scene},
+    script::{
+        rhai::resource::RhaiScriptResource, ScriptContext, ScriptDeinitContext,
+        ScriptMessageContext, ScriptMessagePayload, ScriptMessageSender, ScriptTrait,
+    },
+};
+use lazy_static::lazy_static;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, FLOAT, INT};
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Arc,
+    },
+};
+
+pub mod resource;
+
+lazy_static! {
+    static ref ENGINE: Engine = create_engine();
+}
+
+/// Returns a shared instance of Rhai engine, that is used to compile and execute scripts.
+pub fn engine() -> &'static Engine {
+    &ENGINE
+}
+
+type RhaiResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// A payload of script messages sent by Rhai scripts. Rust scripts could also send messages of this type
+/// to communicate with Rhai scripts, a Rhai script receives them in `on_message` function if it is
+/// subscribed to messages (`ctx.subscribe()`).
+#[derive(Clone, Debug)]
+pub struct RhaiMessage(pub Dynamic);
+
+struct ContextData<'a> {
+    handle: Handle<Node>,
+    scene: &'a mut Scene,
+    dt: f32,
+    elapsed_time: f32,
+    resource_manager: &'a ResourceManager,
+    message_sender: &'a ScriptMessageSender,
+    message_dispatcher: Option<&'a mut ScriptMessageDispatcher>,
+    input: Option<&'a InputSystem>,
+}
+
+impl<'a> ContextData<'a> {
+    fn from_script_context(ctx: &'a mut ScriptContext) -> Self {
+        Self {
+            handle: ctx.handle,
+            scene: &mut *ctx.scene,
+            dt: ctx.dt,
+            elapsed_time: ctx.elapsed_time,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            message_dispatcher: Some(&mut *ctx.message_dispatcher),
+            input: Some(ctx.input),
+        }
+    }
+
+    fn from_message_context(ctx: &'a mut ScriptMessageContext) -> Self {
+        Self {
+            handle: ctx.handle,
+            scene: &mut *ctx.scene,
+            dt: ctx.dt,
+            elapsed_time: ctx.elapsed_time,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            message_dispatcher: None,
+            input: None,
+        }
+    }
+
+    fn from_deinit_context(ctx: &'a mut ScriptDeinitContext) -> Self {
+        Self {
+            handle: ctx.node_handle,
+            scene: &mut *ctx.scene,
+            dt: 0.0,
+            elapsed_time: ctx.elapsed_time,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            message_dispatcher: None,
+            input: None,
+        }
+    }
+
+    fn node(&self, handle: Handle<Node>) -> RhaiResult<&Node> {
+        self.scene
+            .graph
+            .try_get(handle)
+            .ok_or_else(|| invalid_handle(handle))
+    }
+
+    fn node_mut(&mut self, handle: Handle<Node>) -> RhaiResult<&mut Node> {
+        self.scene
+            .graph
+            .try_get_mut(handle)
+            .ok_or_else(|| invalid_handle(handle))
+    }
+}
+
+fn invalid_handle(handle: Handle<Node>) -> Box<EvalAltResult> {
+    format!("Node handle {handle} is invalid!").into()
+}
+
+/// A context of a Rhai script function, it is passed as the first argument to every function of a script.
+/// The context is valid only during the function call, it must not be stored in the state of the script.
+///
+/// # Properties
+///
+/// - `dt` - amount of time (in seconds) that passed from the last call.
+/// - `elapsed_time` - amount of time (in seconds) that passed from creation of the engine.
+/// - `handle` - a handle of the node, to which the script belongs to.
+///
+/// # Functions
+///
+/// - `find_node(name)` - searches for a node with the given name, returns none handle if there's no such node.
+/// - `is_valid(handle)` - checks whether the handle points to an existing node.
+/// - `name(handle)`, `parent(handle)`, `children(handle)` - returns name, parent and children of a node.
+/// - `position(handle)`, `set_position(handle, vec3)` - returns or sets local position of a node.
+/// - `global_position(handle)` - returns global position of a node.
+/// - `set_visibility(handle, bool)` - sets local visibility of a node.
+/// - `get_property(handle, path)`, `set_property(handle, path, value)` - reads or writes a node property by
+/// its path (for example `"base.visibility"`). Numbers, strings, booleans, vectors and handles are supported.
+/// - `instantiate(path)` - instantiates a model resource, the call blocks until the model is loaded.
+/// - `remove_node(handle)` - removes a node with all its descendants.
+/// - `send(handle, value)`, `send_global(value)` - sends a [`RhaiMessage`] to a node or to every subscribed node.
+/// - `subscribe()` - subscribes the node to receive [`RhaiMessage`]s.
+/// - `is_action_pressed(name)`, `is_action_just_pressed(name)`, `is_action_just_released(name)`, `axis(name)` -
+/// queries the state of actions and axes of the first player (see [`InputSystem`]).
+#[derive(Clone)]
+pub struct RhaiContext {
+    data: Arc<AtomicPtr<ContextData<'static>>>,
+}
+
+// Invalidates the context when the script function returns (or panics), so the scripts can't access
+// the data that is no longer alive.
+struct ContextGuard(Arc<AtomicPtr<ContextData<'static>>>);
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        self.0.store(std::ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl RhaiContext {
+    fn with<T>(&self, func: impl FnOnce(&mut ContextData) -> RhaiResult<T>) -> RhaiResult<T> {
+        let data = self.data.load(Ordering::Acquire);
+        if data.is_null() {
+            return Err("Script context is used outside of a script function!".into());
+        }
+        // SAFETY: The pointer is non-null only while the script function, that received the context, is
+        // executed (see `ContextGuard`), the data outlives the call. Scripts are executed on a single thread
+        // and context functions do not call scripts, so there is no aliasing.
+        func(unsafe { &mut *data })
+    }
+}
+
+fn register_context(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<RhaiContext>("Context")
+        .register_get("dt", |ctx: &mut RhaiContext| ctx.with(|data| Ok(data.dt)))
+        .register_get("elapsed_time", |ctx: &mut RhaiContext| {
+            ctx.with(|data| Ok(data.elapsed_time))
+        })
+        .register_get("handle", |ctx: &mut RhaiContext| {
+            ctx.with(|data| Ok(data.handle))
+        })
+        .register_fn("find_node", |ctx: &mut RhaiContext, name: &str| {
+            ctx.with(|data| {
+                Ok(data
+                    .scene
+                    .graph
+                    .find_by_name_from_root(name)
+                    .map(|(handle, _)| handle)
+                    .unwrap_or_default())
+            })
+        })
+        .register_fn("is_valid", |ctx: &mut RhaiContext, handle: Handle<Node>| {
+            ctx.with(|data| Ok(data.scene.graph.is_valid_handle(handle)))
+        })
+        .register_fn("name", |ctx: &mut RhaiContext, handle: Handle<Node>| {
+            ctx.with(|data| Ok(data.node(handle)?.name_owned()))
+        })
+        .register_fn("parent", |ctx: &mut RhaiContext, handle: Handle<Node>| {
+            ctx.with(|data| Ok(data.node(handle)?.parent()))
+        })
+        .register_fn("children", |ctx: &mut RhaiContext, handle: Handle<Node>| {
+            ctx.with(|data| {
+                Ok(data
+                    .node(handle)?
+                    .children()
+                    .iter()
+                    .map(|child| Dynamic::from(*child))
+                    .collect::<Array>())
+            })
+        })
+        .register_fn("position", |ctx: &mut RhaiContext, handle: Handle<Node>| {
+            ctx.with(|data| Ok(**data.node(handle)?.local_transform().position()))
+        })
+        .register_fn(
+            "set_position",
+            |ctx: &mut RhaiContext, handle: Handle<Node>, position: Vector3<f32>| {
+                ctx.with(|data| {
+                    data.node_mut(handle)?
+                        .local_transform_mut()
+                        .set_position(position);
+                    Ok(())
+                })
+            },
+        )
+        .register_fn(
+            "global_position",
+            |ctx: &mut RhaiContext, handle: Handle<Node>| {
+                ctx.with(|data| Ok(data.node(handle)?.global_position()))
+            },
+        )
+        .register_fn(
+            "set_visibility",
+            |ctx: &mut RhaiContext, handle: Handle<Node>, visibility: bool| {
+                ctx.with(|data| {
+                    data.node_mut(handle)?.set_visibility(visibility);
+                    Ok(())
+                })
+            },
+        )
+        .register_fn("get_property", get_property)
+        .register_fn("set_property", set_property)
+        .register_fn("instantiate", |ctx: &mut RhaiContext, path: &str| {
+            ctx.with(
+                |data| match block_on(data.resource_manager.request::<Model, _>(path)) {
+                    Ok(model) => Ok(model.instantiate(data.scene)),
+                    Err(error) => {
+                        Err(format!("Unable to instantiate {path} model! Reason: {error:?}").into())
+                    }
+                },
+            )
+        })
+        .register_fn(
+            "remove_node",
+            |ctx: &mut RhaiContext, handle: Handle<Node>| {
+                ctx.with(|data| {
+                    data.node(handle)?;
+                    data.scene.graph.remove_node(handle);
+                    Ok(())
+                })
+            },
+        )
+        .register_fn(
+            "send",
+            |ctx: &mut RhaiContext, target: Handle<Node>, value: Dynamic| {
+                ctx.with(|data| {
+                    data.message_sender
+                        .send_to_target(target, RhaiMessage(value));
+                    Ok(())
+                })
+            },
+        )
+        .register_fn("send_global", |ctx: &mut RhaiContext, value: Dynamic| {
+            ctx.with(|data| {
+                data.message_sender.send_global(RhaiMessage(value));
+                Ok(())
+            })
+        })
+        .register_fn("subscribe", |ctx: &mut RhaiContext| {
+            ctx.with(|data| match data.message_dispatcher.as_mut() {
+                Some(dispatcher) => {
+                    dispatcher.subscribe_to::<RhaiMessage>(data.handle);
+                    Ok(())
+                }
+                None => Err("Subscription is not available in this function!".into()),
+            })
+        })
+        .register_fn("is_action_pressed", |ctx: &mut RhaiContext, name: &str| {
+            ctx.with(|data| Ok(data.input.map_or(false, |i| i.is_action_pressed(name))))
+        })
+        .register_fn(
+            "is_action_just_pressed",
+            |ctx: &mut RhaiContext, name: &str| {
+                ctx.with(|data| Ok(data.input.map_or(false, |i| i.is_action_just_pressed(name))))
+            },
+        )
+        .register_fn(
+            "is_action_just_released",
+            |ctx: &mut RhaiContext, name: &str| {
+                ctx.with(|data| {
+                    Ok(data
+                        .input
+                        .map_or(false, |i| i.is_action_just_released(name)))
+                })
+            },
+        )
+        .register_fn("axis", |ctx: &mut RhaiContext, name: &str| {
+            ctx.with(|data| Ok(data.input.map_or(0.0, |i| i.axis(name))))
+        });
+}
+
+fn get_property(ctx: &mut RhaiContext, handle: Handle<Node>, path: &str) -> RhaiResult<Dynamic> {
+    ctx.with(|data| {
+        let mut result = Err(format!("There's no property {path}!"));
+        data.node(handle)?.as_reflect(&mut |node| {
+            node.resolve_path(path, &mut |field| {
+                result = match field {
+                    Ok(field) => reflect_to_dynamic(field).ok_or_else(|| {
+                        format!(
+                            "Property {path} has type {}, that is not supported!",
+                            field.type_name()
+                        )
+                    }),
+                    Err(error) => Err(format!("Invalid property path {path}: {error}")),
+                }
+            })
+        });
+        result.map_err(Into::into)
+    })
+}
+
+fn set_property(
+    ctx: &mut RhaiContext,
+    handle: Handle<Node>,
+    path: &str,
+    value: Dynamic,
+) -> RhaiResult<()> {
+    ctx.with(|data| {
+        let node = data.node_mut(handle)?;
+
+        // Actual type of the property defines the type of the value.
+        let mut converted = Err(format!("There's no property {path}!"));
+        node.as_reflect(&mut |node| {
+            node.resolve_path(path, &mut |field| {
+                converted = match field {
+                    Ok(field) => dynamic_to_reflect(field, &value).ok_or_else(|| {
+                        format!(
+                            "Unable to assign a value of {} type to property {path}!",
+                            value.type_name()
+                        )
+                    }),
+                    Err(error) => Err(format!("Invalid property path {path}: {error}")),
+                }
+            })
+        });
+
+        let mut converted = Some(converted?);
+        let mut result = Ok(());
+        node.as_reflect_mut(&mut |node| {
+            node.set_field_by_path(path, converted.take().unwrap(), &mut |r| {
+                if r.is_err() {
+                    result = Err(format!("Unable to set property {path}!"));
+                }
+            })
+        });
+        result.map_err(Into::into)
+    })
+}
+
+fn reflect_to_dynamic(value: &dyn Reflect) -> Option<Dynamic> {
+    let mut result = None;
+    value.as_any(&mut |any| result = any_to_dynamic(any));
+    result
+}
+
+fn any_to_dynamic(any: &dyn Any) -> Option<Dynamic> {
+    macro_rules! convert_int {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(value) = any.downcast_ref::<$ty>() {
+                    return INT::try_from(*value).ok().map(Dynamic::from_int);
+                }
+            )*
+        };
+    }
+
+    macro_rules! convert_custom {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(value) = any.downcast_ref::<$ty>() {
+                    return Some(Dynamic::from(value.clone()));
+                }
+            )*
+        };
+    }
+
+    if let Some(value) = any.downcast_ref::<f32>() {
+        return Some(Dynamic::from_float(*value));
+    }
+    if let Some(value) = any.downcast_ref::<f64>() {
+        return Some(Dynamic::from_float(*value as FLOAT));
+    }
+    if let Some(value) = any.downcast_ref::<INT>() {
+        return Some(Dynamic::from_int(*value));
+    }
+    convert_int!(i8, i16, i32, u8, u16, u32, u64, usize, isize);
+    if let Some(value) = any.downcast_ref::<bool>() {
+        return Some(Dynamic::from_bool(*value));
+    }
+    if let Some(value) = any.downcast_ref::<String>() {
+        return Some(Dynamic::from(value.clone()));
+    }
+    convert_custom!(Vector2<f32>, Vector3<f32>, Handle<Node>);
+
+    None
+}
+
+fn dynamic_to_reflect(field: &dyn Reflect, value: &Dynamic) -> Option<Box<dyn Reflect>> {
+    let mut result = None;
+    field.as_any(&mut |any| result = dynamic_to_typed(any, value));
+    result
+}
+
+fn dynamic_to_typed(target: &dyn Any, value: &Dynamic) -> Option<Box<dyn Reflect>> {
+    macro_rules! convert_int {
+        ($($ty:ty),*) => {
+            $(
+                if target.is::<$ty>() {
+                    return value
+                        .as_int()
+                        .ok()
+                        .and_then(|v| <$ty>::try_from(v).ok())
+                        .map(|v| Box::new(v) as Box<dyn Reflect>);
+                }
+            )*
+        };
+    }
+
+    macro_rules! convert_custom {
+        ($($ty:ty),*) => {
+            $(
+                if target.is::<$ty>() {
+                    return value
+                        .clone()
+                        .try_cast::<$ty>()
+                        .map(|v| Box::new(v) as Box<dyn Reflect>);
+                }
+            )*
+        };
+    }
+
+    let float = || {
+        value
+            .as_float()
+            .ok()
+            .or_else(|| value.as_int().ok().map(|v| v as FLOAT))
+    };
+
+    if target.is::<f32>() {
+        return float().map(|v| Box::new(v) as Box<dyn Reflect>);
+    }
+    if target.is::<f64>() {
+        return float().map(|v| Box::new(v as f64) as Box<dyn Reflect>);
+    }
+    if target.is::<INT>() {
+        return value.as_int().ok().map(|v| Box::new(v) as Box<dyn Reflect>);
+    }
+    convert_int!(i8, i16, i32, u8, u16, u32, u64, usize, isize);
+    if target.is::<bool>() {
+        return value
+            .as_bool()
+            .ok()
+            .map(|v| Box::new(v) as Box<dyn Reflect>);
+    }
+    if target.is::<String>() {
+        return value
+            .clone()
+            .into_string()
+            .ok()
+            .map(|v| Box::new(v) as Box<dyn Reflect>);
+    }
+    convert_custom!(Vector2<f32>, Vector3<f32>, Handle<Node>);
+
+    None
+}
+
+fn register_math(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Vector2<f32>>("Vector2")
+        .register_fn("vec2", |x: FLOAT, y: FLOAT| Vector2::new(x, y))
+        .register_get_set(
+            "x",
+            |v: &mut Vector2<f32>| v.x,
+            |v: &mut Vector2<f32>, x: FLOAT| v.x = x,
+        )
+        .register_get_set(
+            "y",
+            |v: &mut Vector2<f32>| v.y,
+            |v: &mut Vector2<f32>, y: FLOAT| v.y = y,
+        )
+        .register_fn("+", |a: Vector2<f32>, b: Vector2<f32>| a + b)
+        .register_fn("-", |a: Vector2<f32>, b: Vector2<f32>| a - b)
+        .register_fn("-", |a: Vector2<f32>| -a)
+        .register_fn("*", |a: Vector2<f32>, b: FLOAT| a * b)
+        .register_fn("==", |a: Vector2<f32>, b: Vector2<f32>| a == b)
+        .register_fn("len", |v: &mut Vector2<f32>| v.norm())
+        .register_fn("normalize", |v: &mut Vector2<f32>| {
+            v.try_normalize(FLOAT::EPSILON).unwrap_or_default()
+        })
+        .register_fn("dot", |a: Vector2<f32>, b: Vector2<f32>| a.dot(&b))
+        .register_fn("to_string", |v: &mut Vector2<f32>| format!("{v:?}"))
+        .register_fn("to_debug", |v: &mut Vector2<f32>| format!("{v:?}"));
+
+    engine
+        .register_type_with_name::<Vector3<f32>>("Vector3")
+        .register_fn("vec3", |x: FLOAT, y: FLOAT, z: FLOAT| Vector3::new(x, y, z))
+        .register_get_set(
+            "x",
+            |v: &mut Vector3<f32>| v.x,
+            |v: &mut Vector3<f32>, x: FLOAT| v.x = x,
+        )
+        .register_get_set(
+            "y",
+            |v: &mut Vector3<f32>| v.y,
+            |v: &mut Vector3<f32>, y: FLOAT| v.y = y,
+        )
+        .register_get_set(
+            "z",
+            |v: &mut Vector3<f32>| v.z,
+            |v: &mut Vector3<f32>, z: FLOAT| v.z = z,
+        )
+        .register_fn("+", |a: Vector3<f32>, b: Vector3<f32>| a + b)
+        .register_fn("-", |a: Vector3<f32>, b: Vector3<f32>| a - b)
+        .register_fn("-", |a: Vector3<f32>| -a)
+        .register_fn("*", |a: Vector3<f32>, b: FLOAT| a * b)
+        .register_fn("==", |a: Vector3<f32>, b: Vector3<f32>| a == b)
+        .register_fn("len", |v: &mut Vector3<f32>| v.norm())
+        .register_fn("normalize", |v: &mut Vector3<f32>| {
+            v.try_normalize(FLOAT::EPSILON).unwrap_or_default()
+        })
+        .register_fn("dot", |a: Vector3<f32>, b: Vector3<f32>| a.dot(&b))
+        .register_fn("cross", |a: Vector3<f32>, b: Vector3<f32>| a.cross(&b))
+        .register_fn("to_string", |v: &mut Vector3<f32>| format!("{v:?}"))
+        .register_fn("to_debug", |v: &mut Vector3<f32>| format!("{v:?}"));
+
+    engine
+        .register_type_with_name::<Handle<Node>>("Handle")
+        .register_fn("is_some", |h: &mut Handle<Node>| h.is_some())
+        .register_fn("is_none", |h: &mut Handle<Node>| h.is_none())
+        .register_fn("==", |a: Handle<Node>, b: Handle<Node>| a == b)
+        .register_fn("!=", |a: Handle<Node>, b: Handle<Node>| a != b)
+        .register_fn("to_string", |h: &mut Handle<Node>| h.to_string())
+        .register_fn("to_debug", |h: &mut Handle<Node>| h.to_string());
+}
+
+fn create_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine.on_print(|text| Log::info(text));
+    engine.on_debug(|text, source, position| {
+        Log::info(format!("{} {position}: {text}", source.unwrap_or_default()))
+    });
+
+    register_math(&mut engine);
+    register_context(&mut engine);
+
+    engine
+}
+
+// Only plain data (numbers, strings, arrays, maps) could be serialized.
+fn visit_state(state: &mut Dynamic, name: &str, visitor: &mut Visitor) -> VisitResult {
+    let mut data = if visitor.is_reading() {
+        String::new()
+    } else {
+        ron::to_string(state).unwrap_or_else(|error| {
+            Log::warn(format!(
+                "Unable to serialize state of a Rhai script. Reason: {error}"
+            ));
+            String::new()
+        })
+    };
+
+    data.visit(name, visitor)?;
+
+    if visitor.is_reading() {
+        *state = ron::from_str(&data).unwrap_or_else(|_| Dynamic::from_map(Map::new()));
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct CompiledScript {
+    revision: u64,
+    ast: AST,
+}
+
+/// A script that executes functions of a Rhai script resource, see [module docs](self) for more info.
+#[derive(Clone, Debug, Reflect)]
+pub struct RhaiScript {
+    /// A Rhai script, which functions will be called by the engine.
+    pub script: Option<RhaiScriptResource>,
+
+    #[reflect(hidden)]
+    state: Dynamic,
+
+    #[reflect(hidden)]
+    compiled: Option<CompiledScript>,
+
+    // The script resource could be still loading when the engine initializes the script, in this case
+    // the respective functions will be called as soon as the resource is loaded.
+    #[reflect(hidden)]
+    init_pending: bool,
+
+    #[reflect(hidden)]
+    start_pending: bool,
+}
+
+impl Default for RhaiScript {
+    fn default() -> Self {
+        Self {
+            script: None,
+            state: Dynamic::from_map(Map::new()),
+            compiled: None,
+            init_pending: false,
+            start_pending: false,
+        }
+    }
+}
+
+impl Visit for RhaiScript {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.script.visit("Script", &mut region)?;
+        let _ = visit_state(&mut self.state, "State", &mut region);
+
+        Ok(())
+    }
+}
+
+impl TypeUuidProvider for RhaiScript {
+    fn type_uuid() -> Uuid {
+        uuid!("1a1c50f2-8f18-4fd4-9469-591a39e337b5")
+    }
+}
+
+impl_component_provider!(RhaiScript);
+
+impl RhaiScript {
+    /// Creates new script instance, that will execute functions of the given script.
+    pub fn new(script: RhaiScriptResource) -> Self {
+        Self {
+            script: Some(script),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the state (`this` object) of the script instance.
+    pub fn state(&self) -> &Dynamic {
+        &self.state
+    }
+
+    /// Returns the state (`this` object) of the script instance.
+    pub fn state_mut(&mut self) -> &mut Dynamic {
+        &mut self.state
+    }
+
+    // Picks up the latest version of the script resource. Returns `true` if the script was reloaded.
+    fn sync(&mut self) -> bool {
+        let script = match self.script.as_ref() {
+            Some(script) => script,
+            None => {
+                self.compiled = None;
+                return false;
+            }
+        };
+
+        let state = script.state();
+        if let ResourceStateRef::Ok(source) = state.get() {
+            if self
+                .compiled
+                .as_ref()
+                .map_or(true, |c| c.revision != source.revision)
+            {
+                let reloaded = self.compiled.is_some();
+                self.compiled = Some(CompiledScript {
+                    revision: source.revision,
+                    ast: source.ast.clone(),
+                });
+                return reloaded;
+            }
+        }
+
+        false
+    }
+
+    fn call(&mut self, name: &str, data: &mut ContextData, message: Option<Dynamic>) {
+        let compiled = match self.compiled.as_ref() {
+            Some(compiled) => compiled,
+            None => return,
+        };
+
+        let arity = if message.is_some() { 2 } else { 1 };
+        if !compiled
+            .ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == arity)
+        {
+            return;
+        }
+
+        let ptr = Arc::new(AtomicPtr::new(
+            data as *mut ContextData as *mut ContextData<'static>,
+        ));
+        let _guard = ContextGuard(ptr.clone());
+        let ctx = RhaiContext { data: ptr };
+
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        let mut scope = Scope::new();
+        let result = match message {
+            Some(message) => engine().call_fn_with_options::<Dynamic>(
+                options,
+                &mut scope,
+                &compiled.ast,
+                name,
+                (ctx, message),
+            ),
+            None => engine().call_fn_with_options::<Dynamic>(
+                options,
+                &mut scope,
+                &compiled.ast,
+                name,
+                (ctx,),
+            ),
+        };
+
+        if let Err(error) = result {
+            Log::err(format!(
+                "Rhai script {:?} failed in {name} function. Reason: {error}",
+                self.script.as_ref().map(|s| s.path()).unwrap_or_default()
+            ));
+        }
+    }
+
+    // Picks up the latest version of the script and calls the functions that were postponed because
+    // the script was still loading, must be done before every update (regular or fixed).
+    fn prepare(&mut self, data: &mut ContextData) {
+        let reloaded = self.sync();
+        if self.init_pending {
+            self.init_pending = false;
+            self.call("on_init", data, None);
+        }
+        if self.start_pending {
+            self.start_pending = false;
+            self.call("on_start", data, None);
+        }
+        if reloaded {
+            self.call("on_reload", data, None);
+        }
+    }
+}
+
+impl ScriptTrait for RhaiScript {
+    fn on_init(&mut self, ctx: &mut ScriptContext) {
+        self.sync();
+        if self.compiled.is_some() {
+            self.call("on_init", &mut ContextData::from_script_context(ctx), None);
+        } else {
+            self.init_pending = true;
+        }
+    }
+
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.sync();
+        if self.compiled.is_some() {
+            self.call("on_start", &mut ContextData::from_script_context(ctx), None);
+        } else {
+            self.start_pending = true;
+        }
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        self.call(
+            "on_deinit",
+            &mut ContextData::from_deinit_context(ctx),
+            None,
+        );
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let mut data = ContextData::from_script_context(ctx);
+        self.prepare(&mut data);
+        self.call("on_update", &mut data, None);
+    }
+
+    fn on_fixed_update(&mut self, ctx: &mut ScriptContext) {
+        let mut data = ContextData::from_script_context(ctx);
+        self.prepare(&mut data);
+        self.call("on_fixed_update", &mut data, None);
+    }
+
+    fn visit_save_state(&mut self, visitor: &mut Visitor) -> VisitResult {
+        visit_state(&mut self.state, "State", visitor)
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        if let Some(message) = message.downcast_ref::<RhaiMessage>() {
+            self.call(
+                "on_message",
+                &mut ContextData::from_message_context(ctx),
+                Some(message.0.clone()),
+            );
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{manager::ResourceManager, Resource},
+        core::{algebra::Vector3, pool::Handle},
+        scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+        script::{
+            rhai::{resource::RhaiScriptSource, ContextData, RhaiMessage, RhaiScript},
+            ScriptMessageSender,
+        },
+    };
+    use std::sync::mpsc::channel;
+
+    const SOURCE: &str = r#"
+        fn on_init(ctx) {
+            this.counter = 0;
+        }
+
+        fn on_update(ctx) {
+            this.counter += 1;
+            let target = ctx.find_node("Target");
+            ctx.set_position(target, ctx.position(target) + vec3(1.0, 2.0, 3.0));
+            ctx.set_property(target, "base.name", "Moved");
+            ctx.set_property(target, "base.visibility", !ctx.get_property(target, "base.visibility"));
+            ctx.send(target, this.counter);
+        }
+    "#;
+
+    #[test]
+    fn test_rhai_script() {
+        let mut scene = Scene::new();
+        let target =
+            PivotBuilder::new(BaseBuilder::new().with_name("Target")).build(&mut scene.graph);
+
+        let resource_manager = ResourceManager::new();
+        let (sender, receiver) = channel();
+        let message_sender = ScriptMessageSender { sender };
+
+        let mut script = RhaiScript::new(Resource::new_ok(
+            RhaiScriptSource::from_string(SOURCE.to_string(), "test.rhai").unwrap(),
+        ));
+        script.sync();
+
+        let mut data = ContextData {
+            handle: Handle::NONE,
+            scene: &mut scene,
+            dt: 0.0,
+            elapsed_time: 0.0,
+            resource_manager: &resource_manager,
+            message_sender: &message_sender,
+            message_dispatcher: None,
+            input: None,
+        };
+        script.call("on_init", &mut data, None);
+        script.call("on_update", &mut data, None);
+        script.call("on_update", &mut data, None);
+
+        let node = &scene.graph[target];
+        assert_eq!(node.name(), "Moved");
+        assert!(node.visibility());
+        assert_eq!(
+            **node.local_transform().position(),
+            Vector3::new(2.0, 4.0, 6.0)
+        );
+
+        let counter = script.state().clone_cast::<rhai::Map>()["counter"].as_int();
+        assert_eq!(counter, Ok(2));
+
+        let messages = receiver
+            .try_iter()
+            .filter_map(|m| m.payload.downcast_ref::<RhaiMessage>().cloned())
+            .map(|m| m.0.as_int().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_pending_init_before_fixed_update() {
+        let mut scene = Scene::new();
+        let resource_manager = ResourceManager::new();
+        let (sender, _receiver) = channel();
+        let message_sender = ScriptMessageSender { sender };
+
+        let mut script = RhaiScript::new(Resource::new_ok(
+            RhaiScriptSource::from_string(
+                r#"
+                fn on_init(ctx) {
+                    this.counter = 10;
+                }
+
+                fn on_fixed_update(ctx) {
+                    this.counter += 1;
+                }
+                "#
+                .to_string(),
+                "test.rhai",
+            )
+            .unwrap(),
+        ));
+        // Emulate the script that was initialized while its resource was still loading.
+        script.init_pending = true;
+
+        let mut data = ContextData {
+            handle: Handle::NONE,
+            scene: &mut scene,
+            dt: 0.0,
+            elapsed_time: 0.0,
+            resource_manager: &resource_manager,
+            message_sender: &message_sender,
+            message_dispatcher: None,
+            input: None,
+        };
+        script.prepare(&mut data);
+        script.call("on_fixed_update", &mut data, None);
+
+        assert!(!script.init_pending);
+        let counter = script.state().clone_cast::<rhai::Map>()["counter"].as_int();
+        assert_eq!(counter, Ok(11));
+    }
+}
@@ -0,0 +1,196 @@
+//! Rhai script resource holds source code of a script and its compiled form. See [`RhaiScriptSource`]
+//! docs for more info.
+
+use crate::{
+    asset::{
+        event::ResourceEventBroadcaster,
+        loader::{BoxedLoaderFuture, ResourceLoader},
+        untyped::UntypedResource,
+        Resource, ResourceData,
+    },
+    core::{
+        io::{self, FileLoadError},
+        log::Log,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    script::rhai::engine,
+};
+use rhai::{ParseError, AST};
+use std::{
+    any::Any,
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    string::FromUtf8Error,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Type UUID of Rhai script resource.
+pub const RHAI_SCRIPT_RESOURCE_UUID: Uuid = uuid!("1a8f4257-b1c4-4d6e-8616-ffee4c2b28f0");
+
+static REVISION: AtomicU64 = AtomicU64::new(1);
+
+/// An error that may occur during Rhai script loading.
+#[derive(Debug)]
+pub enum RhaiScriptError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+    /// The file is not a valid UTF-8 text.
+    Utf8(FromUtf8Error),
+    /// The script has syntax errors.
+    Parse(ParseError),
+}
+
+impl Display for RhaiScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RhaiScriptError::Io(v) => write!(f, "A file load error has occurred {v:?}"),
+            RhaiScriptError::Utf8(v) => write!(f, "The file is not a valid UTF-8 text {v}"),
+            RhaiScriptError::Parse(v) => write!(f, "A parsing error has occurred {v}"),
+        }
+    }
+}
+
+impl From<FileLoadError> for RhaiScriptError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<FromUtf8Error> for RhaiScriptError {
+    fn from(e: FromUtf8Error) -> Self {
+        Self::Utf8(e)
+    }
+}
+
+impl From<ParseError> for RhaiScriptError {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// Source code of a Rhai script with its compiled form. The source is compiled once when the resource is
+/// loaded, so every script instance that uses the resource shares the compiled code. Every (re)load of the
+/// resource gets unique revision number, script instances use it to detect that the resource was reloaded
+/// (for example, by the file system watcher of the resource manager) and pick up new code.
+#[derive(Debug, Default, Visit, Reflect)]
+pub struct RhaiScriptSource {
+    #[reflect(hidden)]
+    pub(crate) path: PathBuf,
+    #[reflect(read_only)]
+    source: String,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) revision: u64,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) ast: AST,
+}
+
+impl RhaiScriptSource {
+    /// Compiles the given source code.
+    pub fn from_string<P: AsRef<Path>>(source: String, path: P) -> Result<Self, RhaiScriptError> {
+        let ast = engine().compile(&source)?;
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            source,
+            revision: REVISION.fetch_add(1, Ordering::Relaxed),
+            ast,
+        })
+    }
+
+    /// Loads and compiles a script from the given file.
+    pub async fn from_file(path: &Path) -> Result<Self, RhaiScriptError> {
+        let source = String::from_utf8(io::load_file(path).await?)?;
+        Self::from_string(source, path)
+    }
+
+    /// Returns source code of the script.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl ResourceData for RhaiScriptSource {
+    fn path(&self) -> Cow<Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+impl TypeUuidProvider for RhaiScriptSource {
+    fn type_uuid() -> Uuid {
+        RHAI_SCRIPT_RESOURCE_UUID
+    }
+}
+
+/// Type alias for Rhai script resources.
+pub type RhaiScriptResource = Resource<RhaiScriptSource>;
+
+/// Default implementation for Rhai script loading.
+pub struct RhaiScriptLoader;
+
+impl ResourceLoader for RhaiScriptLoader {
+    fn extensions(&self) -> &[&str] {
+        &["rhai"]
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn load(
+        &self,
+        script: UntypedResource,
+        event_broadcaster: ResourceEventBroadcaster,
+        reload: bool,
+    ) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let path = script.0.lock().path().to_path_buf();
+
+            match RhaiScriptSource::from_file(&path).await {
+                Ok(script_state) => {
+                    Log::info(format!("Rhai script {:?} is loaded!", path));
+
+                    script.commit_ok(script_state);
+
+                    event_broadcaster.broadcast_loaded_or_reloaded(script, reload);
+                }
+                Err(error) => {
+                    Log::err(format!(
+                        "Unable to load Rhai script from {:?}! Reason {}",
+                        path, error
+                    ));
+
+                    script.commit_error(path, error);
+                }
+            }
+        })
+    }
+}
@@ -292,6 +292,22 @@ impl PathFinder {
         to: usize,
         path: &mut Vec<Vector3<f32>>,
     ) -> Result<PathKind, PathError> {
+        self.build_with_cost(from, to, path, |_, _| Some(1.0))
+    }
+
+    /// Same as [`Self::build`], but allows you to specify traversal cost of every edge of the graph.
+    /// `edge_cost` closure must return cost multiplier for a move from a vertex with `from` index to
+    /// a vertex with `to` index, or `None` if such move is not possible.
+    pub fn build_with_cost<F>(
+        &mut self,
+        from: usize,
+        to: usize,
+        path: &mut Vec<Vector3<f32>>,
+        mut edge_cost: F,
+    ) -> Result<PathKind, PathError>
+    where
+        F: FnMut(usize, usize) -> Option<f32>,
+    {
         if self.vertices.is_empty() {
             return Ok(PathKind::Empty);
         }
@@ -358,9 +374,15 @@ impl PathFinder {
                     .get_mut(*neighbour_index as usize)
                     .ok_or(PathError::InvalidIndex(*neighbour_index as usize))?;
 
+                let cost = match edge_cost(current_index, *neighbour_index as usize) {
+                    Some(cost) => cost,
+                    None => continue,
+                };
+
                 let g_score = current_vertex.g_score
                     + ((current_vertex.position - neighbour.position).norm_squared()
-                        * neighbour.g_penalty);
+                        * neighbour.g_penalty
+                        * cost);
                 if g_score < neighbour.g_score {
                     neighbour.parent = Some(current_index);
                     neighbour.g_score = g_score;
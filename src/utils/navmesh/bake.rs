@@ -0,0 +1,1194 @@
+//! Navigational mesh baking from scene geometry.
+//!
+//! Baking is done in a few steps (the same approach is used by many other navmesh generators):
+//!
+//! 1) Scene geometry (meshes and colliders) is collected into a single triangle soup.
+//! 2) Triangles are voxelized into a grid of columns, each column holds a set of solid spans.
+//! Every span is marked as walkable or not, depending on the slope of the triangles it was
+//! produced from.
+//! 3) Walkable spans are filtered: low obstacles that can be stepped on becomes walkable, while
+//! ledges and spans with not enough free space above them becomes non-walkable.
+//! 4) Walkable spans are connected with its neighbours and eroded by agent radius, so an agent
+//! will never come too close to walls and edges.
+//! 5) Connected spans are grouped into islands, tiny islands are discarded. Remaining spans are
+//! split into monotone regions, such regions have no holes.
+//! 6) The border of every region is traced into a contour, the contour is simplified - walls are
+//! approximated with the given max error (Douglas-Peucker), while borders between regions are kept
+//! straight, so neighbouring regions share their vertices.
+//! 7) Every contour is triangulated, the triangles form the final navigational mesh.
+
+use crate::{
+    core::{
+        algebra::Vector3, math::aabb::AxisAlignedBoundingBox, math::TriangleDefinition,
+        reflect::prelude::*, visitor::prelude::*,
+    },
+    scene::{
+        collider::Collider,
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexFetchError, VertexReadTrait},
+            Mesh,
+        },
+        rigidbody::{RigidBody, RigidBodyType},
+    },
+    utils::{lightmap::CancellationToken, navmesh::Navmesh},
+};
+use fxhash::FxHashMap;
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    ops::Range,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+/// A set of parameters that defines how a navigational mesh will be baked.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct NavmeshBakeSettings {
+    /// Horizontal size of a voxel (in meters). Smaller values gives more precise navmesh, but
+    /// significantly increases baking time and amount of polygons.
+    #[reflect(min_value = 0.01, step = 0.05)]
+    pub cell_size: f32,
+
+    /// Vertical size of a voxel (in meters).
+    #[reflect(min_value = 0.01, step = 0.05)]
+    pub cell_height: f32,
+
+    /// Radius of an agent (in meters). Walkable area will be shrunk by this value, so an agent
+    /// won't come too close to walls and edges.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub agent_radius: f32,
+
+    /// Height of an agent (in meters). Areas with less free space above them won't be walkable.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub agent_height: f32,
+
+    /// Maximum slope (in radians) of a surface that is still considered walkable.
+    #[reflect(min_value = 0.0, max_value = 1.57, step = 0.05)]
+    pub max_slope: f32,
+
+    /// Maximum height of an obstacle (in meters) that an agent can step on.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub max_step_height: f32,
+
+    /// Minimal amount of cells in an isolated region. Smaller regions will be discarded.
+    pub min_region_area: u32,
+
+    /// Maximum distance (in meters) between simplified walls of the navmesh and the actual borders
+    /// of walkable area. Larger values gives less polygons, but less precise walls.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[visit(optional)]
+    pub max_edge_error: f32,
+
+    /// Whether to use mesh nodes as a source geometry or not.
+    pub use_meshes: bool,
+
+    /// Whether to use colliders as a source geometry or not. Sensors and colliders attached to
+    /// dynamic rigid bodies are always ignored.
+    pub use_colliders: bool,
+}
+
+impl Default for NavmeshBakeSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: 0.3,
+            cell_height: 0.2,
+            agent_radius: 0.4,
+            agent_height: 2.0,
+            max_slope: 45.0f32.to_radians(),
+            max_step_height: 0.6,
+            min_region_area: 8,
+            max_edge_error: 0.4,
+            use_meshes: true,
+            use_colliders: true,
+        }
+    }
+}
+
+/// Small helper that allows you to track progress of navmesh baking.
+#[derive(Clone, Default)]
+pub struct NavmeshBakeProgress(pub Arc<AtomicU32>);
+
+impl NavmeshBakeProgress {
+    /// Creates new progress indicator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns progress percentage in [0; 100] range.
+    pub fn progress_percent(&self) -> u32 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn set_progress_percent(&self, percent: u32) {
+        self.0.store(percent.min(100), Ordering::SeqCst)
+    }
+}
+
+/// An error that may occur during navmesh baking.
+#[derive(Debug)]
+pub enum NavmeshBakeError {
+    /// Baking was cancelled by user.
+    Cancelled,
+    /// Cell size or cell height is not a positive number.
+    InvalidCellSize,
+    /// Vertex buffer of a mesh lacks required data.
+    InvalidData(VertexFetchError),
+}
+
+impl Display for NavmeshBakeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NavmeshBakeError::Cancelled => {
+                write!(f, "Navmesh baking was cancelled by the user.")
+            }
+            NavmeshBakeError::InvalidCellSize => {
+                write!(f, "Cell size and cell height must be positive numbers.")
+            }
+            NavmeshBakeError::InvalidData(v) => {
+                write!(f, "Vertex buffer of a mesh lacks required data {v}.")
+            }
+        }
+    }
+}
+
+impl From<VertexFetchError> for NavmeshBakeError {
+    fn from(e: VertexFetchError) -> Self {
+        Self::InvalidData(e)
+    }
+}
+
+/// Collects world-space triangles of every suitable node of the graph. See
+/// [`NavmeshBakeSettings::use_meshes`] and [`NavmeshBakeSettings::use_colliders`] for more info
+/// about which nodes will be used.
+pub fn collect_geometry(
+    graph: &Graph,
+    settings: &NavmeshBakeSettings,
+) -> Result<Vec<[Vector3<f32>; 3]>, NavmeshBakeError> {
+    let mut triangles = Vec::new();
+
+    for (_, node) in graph.pair_iter() {
+        if !node.is_globally_enabled() {
+            continue;
+        }
+
+        if settings.use_meshes {
+            if let Some(mesh) = node.cast::<Mesh>() {
+                if mesh.global_visibility() {
+                    let global_transform = mesh.global_transform();
+                    for surface in mesh.surfaces() {
+                        let data = surface.data();
+                        let data = data.lock();
+                        for triangle in data.geometry_buffer.iter() {
+                            let mut points = [Vector3::default(); 3];
+                            for (point, index) in points.iter_mut().zip(triangle.0) {
+                                let position = data
+                                    .vertex_buffer
+                                    .get(index as usize)
+                                    .ok_or(VertexFetchError::NoSuchAttribute(
+                                        VertexAttributeUsage::Position,
+                                    ))?
+                                    .read_3_f32(VertexAttributeUsage::Position)?;
+                                *point = global_transform.transform_point(&position.into()).coords;
+                            }
+                            triangles.push(points);
+                        }
+                    }
+                }
+            }
+        }
+
+        if settings.use_colliders {
+            if let Some(collider) = node.cast::<Collider>() {
+                if collider.is_sensor() {
+                    continue;
+                }
+
+                if let Some(body) = graph
+                    .try_get(collider.parent())
+                    .and_then(|p| p.cast::<RigidBody>())
+                {
+                    if body.body_type() == RigidBodyType::Dynamic {
+                        continue;
+                    }
+                }
+
+                graph
+                    .physics
+                    .collider_triangles(collider.native.get(), &mut triangles);
+            }
+        }
+    }
+
+    Ok(triangles)
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Span {
+    min: i32,
+    max: i32,
+    walkable: bool,
+}
+
+#[derive(Clone, Debug)]
+struct OpenSpan {
+    // Height of the floor (in cells).
+    y: i32,
+    // Height of the ceiling (in cells).
+    top: i32,
+    // Indices of connected spans in each direction (see `DIRECTIONS`).
+    neighbours: [Option<usize>; 4],
+}
+
+// Stand-in for an infinite height, small enough to not overflow on subtraction.
+const MAX_HEIGHT: i32 = i32::MAX / 4;
+
+const DIRECTIONS: [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+struct Heightfield {
+    origin: Vector3<f32>,
+    width: usize,
+    depth: usize,
+    columns: Vec<Vec<Span>>,
+}
+
+impl Heightfield {
+    fn neighbour_column(&self, x: usize, z: usize, dir: usize) -> Option<usize> {
+        let (dx, dz) = DIRECTIONS[dir];
+        let nx = x as i32 + dx;
+        let nz = z as i32 + dz;
+        if nx < 0 || nz < 0 || nx >= self.width as i32 || nz >= self.depth as i32 {
+            None
+        } else {
+            Some(nz as usize * self.width + nx as usize)
+        }
+    }
+}
+
+fn add_span(column: &mut Vec<Span>, mut new: Span, merge_threshold: i32) {
+    let mut i = 0;
+    while i < column.len() {
+        let current = column[i];
+        if current.min > new.max {
+            break;
+        } else if current.max < new.min {
+            i += 1;
+            continue;
+        }
+
+        // Spans are overlapping - merge them. Walkability is taken from the top-most surface.
+        if (current.max - new.max).abs() <= merge_threshold {
+            new.walkable |= current.walkable;
+        } else if current.max > new.max {
+            new.walkable = current.walkable;
+        }
+        new.min = new.min.min(current.min);
+        new.max = new.max.max(current.max);
+        column.remove(i);
+    }
+    column.insert(i, new);
+}
+
+fn clip_polygon(
+    polygon: &[Vector3<f32>],
+    axis: usize,
+    value: f32,
+    keep_greater: bool,
+) -> Vec<Vector3<f32>> {
+    let distance = |p: &Vector3<f32>| {
+        if keep_greater {
+            p[axis] - value
+        } else {
+            value - p[axis]
+        }
+    };
+
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[(i + 1) % polygon.len()];
+        let da = distance(a);
+        let db = distance(b);
+        if da >= 0.0 {
+            result.push(*a);
+        }
+        if (da >= 0.0) != (db >= 0.0) {
+            let t = da / (da - db);
+            result.push(a.lerp(b, t));
+        }
+    }
+    result
+}
+
+fn voxelize(
+    triangles: &[[Vector3<f32>; 3]],
+    settings: &NavmeshBakeSettings,
+    cancellation_token: &CancellationToken,
+    progress: &NavmeshBakeProgress,
+) -> Result<Heightfield, NavmeshBakeError> {
+    let mut bounds = AxisAlignedBoundingBox::default();
+    for triangle in triangles {
+        for point in triangle {
+            bounds.add_point(*point);
+        }
+    }
+
+    let cs = settings.cell_size;
+    let ch = settings.cell_height;
+    let origin = bounds.min;
+    let width = (((bounds.max.x - origin.x) / cs).ceil() as usize).max(1);
+    let depth = (((bounds.max.z - origin.z) / cs).ceil() as usize).max(1);
+    let climb = (settings.max_step_height / ch).floor() as i32;
+    let walkable_cos = settings.max_slope.cos();
+
+    let mut heightfield = Heightfield {
+        origin,
+        width,
+        depth,
+        columns: vec![Vec::new(); width * depth],
+    };
+
+    let to_cell = |v: f32, origin: f32, count: usize| {
+        (((v - origin) / cs).floor() as i32).clamp(0, count as i32 - 1) as usize
+    };
+
+    for (n, triangle) in triangles.iter().enumerate() {
+        if n % 1024 == 0 {
+            if cancellation_token.is_cancelled() {
+                return Err(NavmeshBakeError::Cancelled);
+            }
+
+            progress.set_progress_percent((n * VOXELIZATION_PROGRESS / triangles.len()) as u32);
+        }
+
+        let [a, b, c] = triangle;
+        let normal = (b - a).cross(&(c - a));
+        let length = normal.norm();
+        if length <= f32::EPSILON {
+            continue;
+        }
+        let walkable = normal.y / length >= walkable_cos;
+
+        let x0 = to_cell(a.x.min(b.x).min(c.x), origin.x, width);
+        let x1 = to_cell(a.x.max(b.x).max(c.x), origin.x, width);
+        let z0 = to_cell(a.z.min(b.z).min(c.z), origin.z, depth);
+        let z1 = to_cell(a.z.max(b.z).max(c.z), origin.z, depth);
+
+        for z in z0..=z1 {
+            let cz = origin.z + z as f32 * cs;
+            let row = clip_polygon(triangle, 2, cz, true);
+            let row = clip_polygon(&row, 2, cz + cs, false);
+            if row.len() < 3 {
+                continue;
+            }
+
+            for x in x0..=x1 {
+                let cx = origin.x + x as f32 * cs;
+                let cell = clip_polygon(&row, 0, cx, true);
+                let cell = clip_polygon(&cell, 0, cx + cs, false);
+                if cell.len() < 3 {
+                    continue;
+                }
+
+                let (y_min, y_max) = cell.iter().fold((f32::MAX, f32::MIN), |(min, max), p| {
+                    (min.min(p.y), max.max(p.y))
+                });
+
+                let min = ((y_min - origin.y) / ch).floor() as i32;
+                let max = (((y_max - origin.y) / ch).ceil() as i32).max(min + 1);
+
+                add_span(
+                    &mut heightfield.columns[z * width + x],
+                    Span { min, max, walkable },
+                    climb,
+                );
+            }
+        }
+    }
+
+    Ok(heightfield)
+}
+
+fn filter_spans(heightfield: &mut Heightfield, height: i32, climb: i32) {
+    // Low obstacles (curbs, stairs, etc.) on top of walkable surfaces are walkable too.
+    for column in heightfield.columns.iter_mut() {
+        let mut prev_walkable = false;
+        let mut prev_max = -MAX_HEIGHT;
+        for span in column.iter_mut() {
+            let was_walkable = span.walkable;
+            if !span.walkable && prev_walkable && span.max - prev_max <= climb {
+                span.walkable = true;
+            }
+            prev_walkable = was_walkable;
+            prev_max = span.max;
+        }
+    }
+
+    let mut non_walkable = Vec::new();
+    for z in 0..heightfield.depth {
+        for x in 0..heightfield.width {
+            let column = &heightfield.columns[z * heightfield.width + x];
+            for (i, span) in column.iter().enumerate() {
+                if !span.walkable {
+                    continue;
+                }
+
+                let bottom = span.max;
+                let top = column.get(i + 1).map_or(MAX_HEIGHT, |s| s.min);
+
+                // Not enough free space above the span.
+                if top - bottom < height {
+                    non_walkable.push((z * heightfield.width + x, i));
+                    continue;
+                }
+
+                // Find the lowest reachable floor among the neighbours to detect ledges.
+                let mut min_height = MAX_HEIGHT;
+                for dir in 0..4 {
+                    let neighbour = match heightfield.neighbour_column(x, z, dir) {
+                        Some(neighbour) => neighbour,
+                        None => {
+                            min_height = -MAX_HEIGHT;
+                            break;
+                        }
+                    };
+
+                    let neighbour = &heightfield.columns[neighbour];
+                    let mut floor = -MAX_HEIGHT;
+                    let mut ceiling = neighbour.first().map_or(MAX_HEIGHT, |s| s.min);
+                    if top.min(ceiling) - bottom.max(floor) >= height {
+                        min_height = min_height.min(floor - bottom);
+                    }
+                    for (j, neighbour_span) in neighbour.iter().enumerate() {
+                        floor = neighbour_span.max;
+                        ceiling = neighbour.get(j + 1).map_or(MAX_HEIGHT, |s| s.min);
+                        if top.min(ceiling) - bottom.max(floor) >= height {
+                            min_height = min_height.min(floor - bottom);
+                        }
+                    }
+                }
+
+                if min_height < -climb {
+                    non_walkable.push((z * heightfield.width + x, i));
+                }
+            }
+        }
+    }
+
+    for (column, span) in non_walkable {
+        heightfield.columns[column][span].walkable = false;
+    }
+}
+
+fn build_open_spans(
+    heightfield: &Heightfield,
+    height: i32,
+    climb: i32,
+) -> (Vec<OpenSpan>, Vec<Range<usize>>) {
+    let mut spans = Vec::new();
+    let mut ranges = Vec::with_capacity(heightfield.columns.len());
+    for column in heightfield.columns.iter() {
+        let start = spans.len();
+        for (i, span) in column.iter().enumerate() {
+            if span.walkable {
+                spans.push(OpenSpan {
+                    y: span.max,
+                    top: column.get(i + 1).map_or(MAX_HEIGHT, |s| s.min),
+                    neighbours: Default::default(),
+                });
+            }
+        }
+        ranges.push(start..spans.len());
+    }
+
+    for z in 0..heightfield.depth {
+        for x in 0..heightfield.width {
+            for i in ranges[z * heightfield.width + x].clone() {
+                for dir in 0..4 {
+                    if let Some(neighbour) = heightfield.neighbour_column(x, z, dir) {
+                        spans[i].neighbours[dir] = ranges[neighbour].clone().find(|&j| {
+                            let (a, b) = (&spans[i], &spans[j]);
+                            (a.y - b.y).abs() <= climb && a.top.min(b.top) - a.y.max(b.y) >= height
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    (spans, ranges)
+}
+
+// Removes every span that is closer than `radius` cells to a border, returns a set of flags
+// for every span that tells whether a span is still alive or not.
+fn erode(spans: &mut [OpenSpan], radius: u32) -> Vec<bool> {
+    if radius == 0 {
+        return vec![true; spans.len()];
+    }
+
+    // Calculate distance to the nearest border for every span.
+    let mut distances = vec![u32::MAX; spans.len()];
+    let mut queue = VecDeque::new();
+    for (i, span) in spans.iter().enumerate() {
+        if span.neighbours.iter().any(|n| n.is_none()) {
+            distances[i] = 0;
+            queue.push_back(i);
+        }
+    }
+    while let Some(i) = queue.pop_front() {
+        for neighbour in spans[i].neighbours.iter().flatten() {
+            if distances[*neighbour] > distances[i] + 1 {
+                distances[*neighbour] = distances[i] + 1;
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+
+    let alive = distances.iter().map(|d| *d >= radius).collect::<Vec<_>>();
+    disconnect(spans, |i| !alive[i]);
+    alive
+}
+
+fn disconnect<F: Fn(usize) -> bool>(spans: &mut [OpenSpan], removed: F) {
+    for i in 0..spans.len() {
+        if removed(i) {
+            spans[i].neighbours = Default::default();
+        } else {
+            for neighbour in spans[i].neighbours.iter_mut() {
+                if neighbour.map_or(false, &removed) {
+                    *neighbour = None;
+                }
+            }
+        }
+    }
+}
+
+// Assigns island index to every span, index 0 means that a span does not belong to any island.
+fn build_islands(spans: &[OpenSpan], alive: &[bool], min_region_area: u32) -> Vec<u32> {
+    let mut regions = vec![0; spans.len()];
+    let mut region_sizes = vec![0];
+    let mut stack = Vec::new();
+    for start in 0..spans.len() {
+        if !alive[start] || regions[start] != 0 {
+            continue;
+        }
+
+        let region = region_sizes.len() as u32;
+        let mut size = 0;
+        regions[start] = region;
+        stack.push(start);
+        while let Some(i) = stack.pop() {
+            size += 1;
+            for &neighbour in spans[i].neighbours.iter().flatten() {
+                if regions[neighbour] == 0 {
+                    regions[neighbour] = region;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        region_sizes.push(size);
+    }
+
+    for region in regions.iter_mut() {
+        if region_sizes[*region as usize] < min_region_area {
+            *region = 0;
+        }
+    }
+
+    regions
+}
+
+// A sequence of connected spans of a row.
+#[derive(Default)]
+struct Sweep {
+    id: u32,
+    // A region of the previous row, that is connected to the sweep, or `MANY_REGIONS`.
+    neighbour: u32,
+    // Amount of spans of the sweep connected to the neighbour region.
+    samples: u32,
+}
+
+const MANY_REGIONS: u32 = u32::MAX;
+
+// Splits connected spans into monotone regions. Every row of such region is a single sequence of
+// spans connected with the previous row, so a region never has holes and its border could be
+// described by a single contour. Index 0 means that a span does not belong to any region.
+fn build_monotone_regions(
+    spans: &[OpenSpan],
+    ranges: &[Range<usize>],
+    islands: &[u32],
+    width: usize,
+    depth: usize,
+) -> Vec<u32> {
+    let mut regions = vec![0; spans.len()];
+    let mut region_count = 1;
+    let mut sweeps = Vec::new();
+    let mut previous_row_samples = Vec::new();
+
+    for z in 0..depth {
+        let row = &ranges[z * width..(z + 1) * width];
+
+        // Regions of the row are temporary indices of sweeps at this stage.
+        sweeps.clear();
+        sweeps.push(Sweep::default());
+        previous_row_samples.clear();
+        previous_row_samples.resize(region_count as usize, 0);
+
+        for i in row.iter().flat_map(|range| range.clone()) {
+            if islands[i] == 0 {
+                continue;
+            }
+
+            let span = &spans[i];
+            let mut sweep = span.neighbours[0].map_or(0, |n| regions[n]);
+            if sweep == 0 {
+                sweep = sweeps.len() as u32;
+                sweeps.push(Sweep::default());
+            }
+
+            if let Some(neighbour) = span.neighbours[3] {
+                let neighbour_region = regions[neighbour];
+                if neighbour_region != 0 {
+                    let sweep = &mut sweeps[sweep as usize];
+                    if sweep.neighbour == 0 || sweep.neighbour == neighbour_region {
+                        sweep.neighbour = neighbour_region;
+                        sweep.samples += 1;
+                        previous_row_samples[neighbour_region as usize] += 1;
+                    } else {
+                        sweep.neighbour = MANY_REGIONS;
+                    }
+                }
+            }
+
+            regions[i] = sweep;
+        }
+
+        // A sweep continues the region of the previous row only if it is the only sweep connected
+        // to the region, otherwise it starts a new region.
+        for sweep in sweeps.iter_mut().skip(1) {
+            if sweep.neighbour != 0
+                && sweep.neighbour != MANY_REGIONS
+                && previous_row_samples[sweep.neighbour as usize] == sweep.samples
+            {
+                sweep.id = sweep.neighbour;
+            } else {
+                sweep.id = region_count;
+                region_count += 1;
+            }
+        }
+
+        for i in row.iter().flat_map(|range| range.clone()) {
+            if regions[i] != 0 {
+                regions[i] = sweeps[regions[i] as usize].id;
+            }
+        }
+    }
+
+    regions
+}
+
+// Height of the corner between `dir` and the next direction of the span. It is the max height of
+// every span that shares the corner, so neighbouring regions will have the same vertex.
+fn corner_height(spans: &[OpenSpan], i: usize, dir: usize) -> i32 {
+    let next_dir = (dir + 1) % 4;
+    let span = &spans[i];
+
+    let side = span.neighbours[dir];
+    let next_side = span.neighbours[next_dir];
+    let diagonal = side
+        .and_then(|n| spans[n].neighbours[next_dir])
+        .or_else(|| next_side.and_then(|n| spans[n].neighbours[dir]));
+
+    [side, next_side, diagonal]
+        .into_iter()
+        .flatten()
+        .fold(span.y, |y, n| y.max(spans[n].y))
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ContourVertex {
+    x: i32,
+    y: i32,
+    z: i32,
+    // A region on the other side of the edge that ends at the vertex, 0 - for walls.
+    neighbour: u32,
+}
+
+// Limits the amount of steps of contour tracing, the limit can only be reached if connections of
+// the spans are malformed.
+const MAX_CONTOUR_STEPS: usize = 1 << 20;
+
+// Walks along the border of the region of the given span and collects corners of every border
+// edge. `borders` holds a bit for every border edge of every span, the bits of the visited edges
+// are cleared.
+fn trace_contour(
+    spans: &[OpenSpan],
+    regions: &[u32],
+    borders: &mut [u8],
+    mut x: i32,
+    mut z: i32,
+    mut i: usize,
+) -> Vec<ContourVertex> {
+    let mut dir = borders[i].trailing_zeros() as usize;
+    let start = i;
+    let start_dir = dir;
+
+    let mut contour = Vec::new();
+    for _ in 0..MAX_CONTOUR_STEPS {
+        if borders[i] & (1 << dir) != 0 {
+            let (cx, cz) = match dir {
+                0 => (x, z + 1),
+                1 => (x + 1, z + 1),
+                2 => (x + 1, z),
+                _ => (x, z),
+            };
+            contour.push(ContourVertex {
+                x: cx,
+                y: corner_height(spans, i, dir),
+                z: cz,
+                neighbour: spans[i].neighbours[dir].map_or(0, |n| regions[n]),
+            });
+            borders[i] &= !(1 << dir);
+            dir = (dir + 1) % 4;
+        } else {
+            // An edge without border always leads to a span of the same region.
+            match spans[i].neighbours[dir] {
+                Some(neighbour) => {
+                    let (dx, dz) = DIRECTIONS[dir];
+                    x += dx;
+                    z += dz;
+                    i = neighbour;
+                    dir = (dir + 3) % 4;
+                }
+                None => break,
+            }
+        }
+
+        if i == start && dir == start_dir {
+            break;
+        }
+    }
+
+    contour
+}
+
+fn distance_to_segment_sqr(p: &ContourVertex, a: &ContourVertex, b: &ContourVertex) -> f32 {
+    let (px, pz) = ((p.x - a.x) as f32, (p.z - a.z) as f32);
+    let (dx, dz) = ((b.x - a.x) as f32, (b.z - a.z) as f32);
+    let length_sqr = dx * dx + dz * dz;
+    let t = if length_sqr > 0.0 {
+        ((px * dx + pz * dz) / length_sqr).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (ex, ez) = (px - t * dx, pz - t * dz);
+    ex * ex + ez * ez
+}
+
+// Simplifies the contour and returns indices of the remaining vertices. Vertices where the
+// neighbour region changes are always kept, walls are simplified using Douglas-Peucker algorithm
+// with the given max error (in cells), borders between regions are kept straight, this way both
+// regions will have the same vertices along their border.
+fn simplify_contour(contour: &[ContourVertex], max_error: f32) -> Vec<usize> {
+    let count = contour.len();
+
+    let mut simplified = (0..count)
+        .filter(|&i| contour[i].neighbour != contour[(i + 1) % count].neighbour)
+        .collect::<Vec<_>>();
+
+    // The contour is a wall all around, start from the lower-left and upper-right vertices.
+    if simplified.is_empty() {
+        let key = |i: &usize| (contour[*i].x, contour[*i].z);
+        if let (Some(lower_left), Some(upper_right)) =
+            ((0..count).min_by_key(key), (0..count).max_by_key(key))
+        {
+            simplified.push(lower_left.min(upper_right));
+            simplified.push(lower_left.max(upper_right));
+        }
+    }
+
+    let max_error_sqr = max_error * max_error;
+    let mut n = 0;
+    while n < simplified.len() {
+        let a = simplified[n];
+        let b = simplified[(n + 1) % simplified.len()];
+
+        let mut max_distance = 0.0;
+        let mut farthest = None;
+        if contour[(a + 1) % count].neighbour == 0 {
+            let mut i = (a + 1) % count;
+            while i != b {
+                let distance = distance_to_segment_sqr(&contour[i], &contour[a], &contour[b]);
+                if distance > max_distance {
+                    max_distance = distance;
+                    farthest = Some(i);
+                }
+                i = (i + 1) % count;
+            }
+        }
+
+        match farthest {
+            Some(farthest) if max_distance > max_error_sqr => simplified.insert(n + 1, farthest),
+            _ => n += 1,
+        }
+    }
+
+    simplified
+}
+
+fn cross(a: &ContourVertex, b: &ContourVertex, c: &ContourVertex) -> i64 {
+    (b.x - a.x) as i64 * (c.z - a.z) as i64 - (b.z - a.z) as i64 * (c.x - a.x) as i64
+}
+
+// Triangulates the polygon using ear clipping, the polygon must be oriented counter-clockwise
+// (on XZ plane). Ears with the shortest diagonal are clipped first, this gives better shaped
+// triangles.
+fn triangulate(polygon: &[ContourVertex]) -> Vec<[usize; 3]> {
+    let mut indices = (0..polygon.len()).collect::<Vec<_>>();
+    let mut triangles = Vec::with_capacity(polygon.len().saturating_sub(2));
+
+    while indices.len() > 3 {
+        let count = indices.len();
+        let corner = |k: usize| {
+            (
+                indices[(k + count - 1) % count],
+                indices[k],
+                indices[(k + 1) % count],
+            )
+        };
+
+        let mut best = None;
+        let mut best_diagonal = i64::MAX;
+        for k in 0..count {
+            let (prev, current, next) = corner(k);
+            let (a, b, c) = (&polygon[prev], &polygon[current], &polygon[next]);
+            if cross(a, b, c) <= 0 {
+                continue;
+            }
+
+            let is_ear = indices.iter().all(|&other| {
+                let p = &polygon[other];
+                other == prev
+                    || other == current
+                    || other == next
+                    || cross(a, b, p) < 0
+                    || cross(b, c, p) < 0
+                    || cross(c, a, p) < 0
+            });
+            if !is_ear {
+                continue;
+            }
+
+            let (dx, dz) = ((c.x - a.x) as i64, (c.z - a.z) as i64);
+            let diagonal = dx * dx + dz * dz;
+            if diagonal < best_diagonal {
+                best_diagonal = diagonal;
+                best = Some(k);
+            }
+        }
+
+        // The polygon could become self-intersecting after simplification, clip the most convex
+        // corner in this case to keep going.
+        let k = best.unwrap_or_else(|| {
+            (0..count)
+                .max_by_key(|&k| {
+                    let (prev, current, next) = corner(k);
+                    cross(&polygon[prev], &polygon[current], &polygon[next])
+                })
+                .unwrap_or_default()
+        });
+
+        let (prev, current, next) = corner(k);
+        if cross(&polygon[prev], &polygon[current], &polygon[next]) > 0 {
+            triangles.push([prev, current, next]);
+        }
+        indices.remove(k);
+    }
+
+    if let [a, b, c] = indices[..] {
+        if cross(&polygon[a], &polygon[b], &polygon[c]) > 0 {
+            triangles.push([a, b, c]);
+        }
+    }
+
+    triangles
+}
+
+// Portions of the total progress (in percents), voxelization is the most time-consuming step.
+const VOXELIZATION_PROGRESS: usize = 60;
+const REGIONS_PROGRESS: usize = 15;
+
+/// Bakes navigational mesh from the given set of world-space triangles. Baking could be cancelled
+/// at any time using `cancellation_token`, `progress` allows you to track the progress of baking.
+pub fn bake_triangles(
+    triangles: &[[Vector3<f32>; 3]],
+    settings: &NavmeshBakeSettings,
+    cancellation_token: CancellationToken,
+    progress: NavmeshBakeProgress,
+) -> Result<Navmesh, NavmeshBakeError> {
+    if settings.cell_size <= 0.0 || settings.cell_height <= 0.0 {
+        return Err(NavmeshBakeError::InvalidCellSize);
+    }
+
+    if triangles.is_empty() {
+        return Ok(Navmesh::default());
+    }
+
+    let height = (settings.agent_height / settings.cell_height).ceil() as i32;
+    let climb = (settings.max_step_height / settings.cell_height).floor() as i32;
+    let radius = (settings.agent_radius / settings.cell_size).ceil() as u32;
+
+    let mut heightfield = voxelize(triangles, settings, &cancellation_token, &progress)?;
+
+    filter_spans(&mut heightfield, height, climb);
+
+    if cancellation_token.is_cancelled() {
+        return Err(NavmeshBakeError::Cancelled);
+    }
+
+    let (mut spans, ranges) = build_open_spans(&heightfield, height, climb);
+
+    let alive = erode(&mut spans, radius);
+
+    let islands = build_islands(&spans, &alive, settings.min_region_area);
+    disconnect(&mut spans, |i| islands[i] == 0);
+
+    let regions = build_monotone_regions(
+        &spans,
+        &ranges,
+        &islands,
+        heightfield.width,
+        heightfield.depth,
+    );
+
+    if cancellation_token.is_cancelled() {
+        return Err(NavmeshBakeError::Cancelled);
+    }
+
+    let contours_start = VOXELIZATION_PROGRESS + REGIONS_PROGRESS;
+    progress.set_progress_percent(contours_start as u32);
+
+    // Mark every edge of every span that separates it from other regions or from the void.
+    let mut borders = vec![0u8; spans.len()];
+    for (i, span) in spans.iter().enumerate() {
+        if regions[i] != 0 {
+            for (dir, neighbour) in span.neighbours.iter().enumerate() {
+                if neighbour.map_or(true, |n| regions[n] != regions[i]) {
+                    borders[i] |= 1 << dir;
+                }
+            }
+        }
+    }
+
+    let max_error = settings.max_edge_error / settings.cell_size;
+    let mut vertices = Vec::new();
+    let mut vertex_map = FxHashMap::default();
+    let mut navmesh_triangles = Vec::new();
+    for z in 0..heightfield.depth {
+        if cancellation_token.is_cancelled() {
+            return Err(NavmeshBakeError::Cancelled);
+        }
+
+        progress.set_progress_percent(
+            (contours_start + z * (100 - contours_start) / heightfield.depth) as u32,
+        );
+
+        for x in 0..heightfield.width {
+            for i in ranges[z * heightfield.width + x].clone() {
+                if borders[i] == 0 {
+                    continue;
+                }
+
+                let contour = trace_contour(&spans, &regions, &mut borders, x as i32, z as i32, i);
+                let mut polygon = simplify_contour(&contour, max_error)
+                    .into_iter()
+                    .map(|i| contour[i])
+                    .collect::<Vec<_>>();
+
+                let area = (0..polygon.len())
+                    .map(|i| {
+                        let (a, b) = (&polygon[i], &polygon[(i + 1) % polygon.len()]);
+                        a.x as i64 * b.z as i64 - b.x as i64 * a.z as i64
+                    })
+                    .sum::<i64>();
+                if area < 0 {
+                    polygon.reverse();
+                }
+
+                let indices = polygon
+                    .iter()
+                    .map(|v| {
+                        *vertex_map.entry((v.x, v.z, v.y)).or_insert_with(|| {
+                            vertices.push(Vector3::new(
+                                heightfield.origin.x + v.x as f32 * settings.cell_size,
+                                heightfield.origin.y + v.y as f32 * settings.cell_height,
+                                heightfield.origin.z + v.z as f32 * settings.cell_size,
+                            ));
+                            vertices.len() as u32 - 1
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                // Counter-clockwise triangles on XZ plane are facing down, flip them.
+                for [a, b, c] in triangulate(&polygon) {
+                    navmesh_triangles
+                        .push(TriangleDefinition([indices[a], indices[c], indices[b]]));
+                }
+            }
+        }
+    }
+
+    let navmesh = Navmesh::new(&navmesh_triangles, &vertices);
+
+    progress.set_progress_percent(100);
+
+    Ok(navmesh)
+}
+
+impl Navmesh {
+    /// Bakes navigational mesh from the geometry of the given graph. See [module docs](self) for
+    /// more info about baking process.
+    ///
+    /// # Performance
+    ///
+    /// This method could be quite slow on large scenes, consider using [`collect_geometry`] and
+    /// [`bake_triangles`] on a separate thread.
+    pub fn bake(graph: &Graph, settings: &NavmeshBakeSettings) -> Result<Self, NavmeshBakeError> {
+        let triangles = collect_geometry(graph, settings)?;
+        bake_triangles(
+            &triangles,
+            settings,
+            CancellationToken::new(),
+            NavmeshBakeProgress::new(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        utils::{
+            astar::PathKind,
+            lightmap::CancellationToken,
+            navmesh::bake::{bake_triangles, NavmeshBakeSettings},
+        },
+    };
+
+    fn quad(
+        a: Vector3<f32>,
+        b: Vector3<f32>,
+        c: Vector3<f32>,
+        d: Vector3<f32>,
+    ) -> Vec<[Vector3<f32>; 3]> {
+        vec![[a, b, c], [a, c, d]]
+    }
+
+    #[test]
+    fn test_bake_flat_plane() {
+        let settings = NavmeshBakeSettings {
+            cell_size: 0.5,
+            agent_radius: 0.5,
+            ..Default::default()
+        };
+
+        let triangles = quad(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 10.0),
+            Vector3::new(10.0, 0.0, 10.0),
+            Vector3::new(10.0, 0.0, 0.0),
+        );
+
+        let navmesh = bake_triangles(
+            &triangles,
+            &settings,
+            CancellationToken::new(),
+            Default::default(),
+        )
+        .unwrap();
+
+        // 20x20 cells, the outer ring is a ledge and the next one is eroded by agent radius. The
+        // rest is a single square region.
+        assert_eq!(navmesh.triangles().len(), 2);
+        assert_eq!(navmesh.vertices().len(), 4);
+        for vertex in navmesh.vertices() {
+            let position = vertex.position;
+            assert!(position.y >= 0.0 && position.y <= settings.cell_height);
+            assert!(position.x >= 1.0 && position.x <= 9.0);
+            assert!(position.z >= 1.0 && position.z <= 9.0);
+        }
+    }
+
+    #[test]
+    fn test_bake_plane_with_hole() {
+        let settings = NavmeshBakeSettings {
+            cell_size: 0.25,
+            agent_radius: 0.25,
+            ..Default::default()
+        };
+
+        // 12x12 plane with 2x2 hole in the middle.
+        let mut triangles = Vec::new();
+        for (x0, z0, x1, z1) in [
+            (0.0, 0.0, 12.0, 5.0),
+            (0.0, 7.0, 12.0, 12.0),
+            (0.0, 5.0, 5.0, 7.0),
+            (7.0, 5.0, 12.0, 7.0),
+        ] {
+            triangles.extend(quad(
+                Vector3::new(x0, 0.0, z0),
+                Vector3::new(x0, 0.0, z1),
+                Vector3::new(x1, 0.0, z1),
+                Vector3::new(x1, 0.0, z0),
+            ));
+        }
+
+        let mut navmesh = bake_triangles(
+            &triangles,
+            &settings,
+            CancellationToken::new(),
+            Default::default(),
+        )
+        .unwrap();
+
+        // A few regions around the hole, instead of a pair of triangles per cell.
+        assert!(!navmesh.triangles().is_empty());
+        assert!(navmesh.triangles().len() < 64);
+        for vertex in navmesh.vertices() {
+            let position = vertex.position;
+            assert!(
+                !(position.x > 5.0 && position.x < 7.0 && position.z > 5.0 && position.z < 7.0)
+            );
+        }
+
+        // Regions share their vertices, so the hole could be bypassed.
+        let from = navmesh.query_closest(Vector3::new(2.0, 0.0, 6.0)).unwrap();
+        let to = navmesh.query_closest(Vector3::new(10.0, 0.0, 6.0)).unwrap();
+        let mut path = Vec::new();
+        assert_eq!(
+            navmesh.build_path(from, to, &mut path).unwrap(),
+            PathKind::Full
+        );
+    }
+
+    #[test]
+    fn test_bake_steep_slope() {
+        let triangles = quad(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 10.0, 1.0),
+            Vector3::new(10.0, 10.0, 1.0),
+            Vector3::new(10.0, 0.0, 0.0),
+        );
+
+        let navmesh = bake_triangles(
+            &triangles,
+            &Default::default(),
+            CancellationToken::new(),
+            Default::default(),
+        )
+        .unwrap();
+
+        assert!(navmesh.triangles().is_empty());
+    }
+}
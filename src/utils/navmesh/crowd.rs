@@ -0,0 +1,833 @@
+//! Crowd simulation for navmesh agents.
+//!
+//! [`Crowd`] updates a set of agents together: every agent follows its own path on a navmesh, while
+//! avoiding collisions with other agents using Optimal Reciprocal Collision Avoidance (ORCA). ORCA
+//! works in the horizontal (XZ) plane, vertical position of agents is always taken from navmesh
+//! surface. Agents never step on triangles excluded by their query filters or into temporary
+//! obstacles, and re-plan their paths when avoidance pushes them too far away from the paths.
+//! Off-mesh links are traversed instantly by crowd agents.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        math::ray::Ray,
+        pool::{Handle, Pool},
+        visitor::prelude::*,
+    },
+    utils::navmesh::{Navmesh, NavmeshAgent, NavmeshFilter},
+};
+use fxhash::FxHashMap;
+
+/// An agent of a crowd. It is a wrapper over [`NavmeshAgent`] with additional data needed for
+/// local avoidance. Speed of the inner agent is used as the maximum speed of the crowd agent.
+#[derive(Visit, Clone, Debug)]
+pub struct CrowdAgent {
+    agent: NavmeshAgent,
+    radius: f32,
+    velocity: Vector3<f32>,
+    #[visit(skip)]
+    preferred_velocity: Vector2<f32>,
+    #[visit(skip)]
+    new_velocity: Vector2<f32>,
+}
+
+impl Default for CrowdAgent {
+    fn default() -> Self {
+        Self::new(Default::default(), 0.4)
+    }
+}
+
+impl CrowdAgent {
+    /// Creates new crowd agent with given radius.
+    pub fn new(agent: NavmeshAgent, radius: f32) -> Self {
+        Self {
+            agent,
+            radius,
+            velocity: Default::default(),
+            preferred_velocity: Default::default(),
+            new_velocity: Default::default(),
+        }
+    }
+
+    /// Returns a reference to inner navmesh agent.
+    pub fn agent(&self) -> &NavmeshAgent {
+        &self.agent
+    }
+
+    /// Returns a reference to inner navmesh agent. Could be used to change target or position
+    /// of the agent.
+    pub fn agent_mut(&mut self) -> &mut NavmeshAgent {
+        &mut self.agent
+    }
+
+    /// Returns current position of the agent.
+    pub fn position(&self) -> Vector3<f32> {
+        self.agent.position
+    }
+
+    /// Returns current velocity of the agent.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    /// Sets new radius of the agent.
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius.max(0.0);
+    }
+
+    /// Returns current radius of the agent.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn position_2d(&self) -> Vector2<f32> {
+        Vector2::new(self.agent.position.x, self.agent.position.z)
+    }
+
+    fn velocity_2d(&self) -> Vector2<f32> {
+        Vector2::new(self.velocity.x, self.velocity.z)
+    }
+
+    fn update_preferred_velocity(&mut self, dt: f32) {
+        self.preferred_velocity = Vector2::default();
+
+        // Skip path points that were already reached.
+        loop {
+            // Crowd agents traverse off-mesh links instantly.
+            if self.agent.current_link().is_some() {
+                self.agent.current += 1;
+                if let Some(end) = self.agent.path.get(self.agent.current as usize) {
+                    self.agent.position = *end;
+                }
+                continue;
+            }
+
+            let position = self.position_2d();
+            let path = &self.agent.path;
+            match path.get(self.agent.current as usize + 1) {
+                Some(point) => {
+                    let point = Vector2::new(point.x, point.z);
+                    let is_last = self.agent.current as usize + 2 >= path.len();
+                    let reach_distance = if is_last { 0.01 } else { self.radius.max(0.01) };
+                    if point.metric_distance(&position) > reach_distance {
+                        break;
+                    }
+                    self.agent.current += 1;
+                }
+                None => break,
+            }
+        }
+
+        let path = &self.agent.path;
+        let position = self.position_2d();
+
+        if let Some(point) = path.get(self.agent.current as usize + 1) {
+            let delta = Vector2::new(point.x, point.z) - position;
+            if let Some(direction) = delta.try_normalize(f32::EPSILON) {
+                // Slow down at the end of the path to not overshoot the target.
+                let is_last = self.agent.current as usize + 2 >= path.len();
+                let speed = if is_last && dt > 0.0 {
+                    self.agent.speed.min(delta.norm() / dt)
+                } else {
+                    self.agent.speed
+                };
+                self.preferred_velocity = direction.scale(speed);
+            }
+        }
+    }
+
+    // Checks whether the agent is too far away from the current segment of its path, or the next
+    // point of the path cannot be reached directly, because of a temporary obstacle.
+    fn is_path_invalid(&self, navmesh: &Navmesh, max_distance: f32) -> bool {
+        let current = self.agent.current as usize;
+        let (begin, end) = match (
+            self.agent.path.get(current),
+            self.agent.path.get(current + 1),
+        ) {
+            (Some(begin), Some(end)) => (*begin, *end),
+            _ => return false,
+        };
+
+        let position = self.position_2d();
+        let a = Vector2::new(begin.x, begin.z);
+        let ab = Vector2::new(end.x, end.z) - a;
+        let t = if ab.norm_squared() > f32::EPSILON {
+            ((position - a).dot(&ab) / ab.norm_squared()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        (a + ab.scale(t)).metric_distance(&position) > max_distance
+            || is_move_blocked(navmesh, self.agent.position, end)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Line {
+    point: Vector2<f32>,
+    direction: Vector2<f32>,
+}
+
+fn det(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+// Linear programming routines are taken from RVO2 library, see
+// https://gamma.cs.unc.edu/RVO2/ for more info.
+
+fn linear_program1(
+    lines: &[Line],
+    line_no: usize,
+    radius: f32,
+    opt_velocity: Vector2<f32>,
+    direction_opt: bool,
+    result: &mut Vector2<f32>,
+) -> bool {
+    let line = lines[line_no];
+    let dot_product = line.point.dot(&line.direction);
+    let discriminant = dot_product * dot_product + radius * radius - line.point.norm_squared();
+
+    if discriminant < 0.0 {
+        // Max speed circle fully invalidates the line.
+        return false;
+    }
+
+    let sqrt_discriminant = discriminant.sqrt();
+    let mut t_left = -dot_product - sqrt_discriminant;
+    let mut t_right = -dot_product + sqrt_discriminant;
+
+    for other in lines[..line_no].iter() {
+        let denominator = det(line.direction, other.direction);
+        let numerator = det(other.direction, line.point - other.point);
+
+        if denominator.abs() <= f32::EPSILON {
+            // Lines are parallel.
+            if numerator < 0.0 {
+                return false;
+            } else {
+                continue;
+            }
+        }
+
+        let t = numerator / denominator;
+        if denominator >= 0.0 {
+            t_right = t_right.min(t);
+        } else {
+            t_left = t_left.max(t);
+        }
+
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    let t = if direction_opt {
+        if opt_velocity.dot(&line.direction) > 0.0 {
+            t_right
+        } else {
+            t_left
+        }
+    } else {
+        line.direction
+            .dot(&(opt_velocity - line.point))
+            .clamp(t_left, t_right)
+    };
+
+    *result = line.point + line.direction.scale(t);
+
+    true
+}
+
+fn linear_program2(
+    lines: &[Line],
+    radius: f32,
+    opt_velocity: Vector2<f32>,
+    direction_opt: bool,
+    result: &mut Vector2<f32>,
+) -> usize {
+    *result = if direction_opt {
+        opt_velocity.scale(radius)
+    } else if opt_velocity.norm_squared() > radius * radius {
+        opt_velocity.normalize().scale(radius)
+    } else {
+        opt_velocity
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if det(line.direction, line.point - *result) > 0.0 {
+            // Result does not satisfy the constraint, compute new optimal result.
+            let temp_result = *result;
+            if !linear_program1(lines, i, radius, opt_velocity, direction_opt, result) {
+                *result = temp_result;
+                return i;
+            }
+        }
+    }
+
+    lines.len()
+}
+
+fn linear_program3(
+    lines: &[Line],
+    begin_line: usize,
+    radius: f32,
+    projected_lines: &mut Vec<Line>,
+    result: &mut Vector2<f32>,
+) {
+    let mut distance = 0.0;
+
+    for i in begin_line..lines.len() {
+        if det(lines[i].direction, lines[i].point - *result) > distance {
+            // Result does not satisfy constraint of line i.
+            projected_lines.clear();
+            for j in 0..i {
+                let determinant = det(lines[i].direction, lines[j].direction);
+
+                let point = if determinant.abs() <= f32::EPSILON {
+                    if lines[i].direction.dot(&lines[j].direction) > 0.0 {
+                        // Lines are in the same direction.
+                        continue;
+                    } else {
+                        // Lines are in opposite direction.
+                        (lines[i].point + lines[j].point).scale(0.5)
+                    }
+                } else {
+                    lines[i].point
+                        + lines[i].direction.scale(
+                            det(lines[j].direction, lines[i].point - lines[j].point) / determinant,
+                        )
+                };
+
+                projected_lines.push(Line {
+                    point,
+                    direction: (lines[j].direction - lines[i].direction)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_default(),
+                });
+            }
+
+            let temp_result = *result;
+            if linear_program2(
+                projected_lines,
+                radius,
+                Vector2::new(-lines[i].direction.y, lines[i].direction.x),
+                true,
+                result,
+            ) < projected_lines.len()
+            {
+                // This should in principle not happen. The result is by definition already in
+                // the feasible region of this linear program. If it fails, it is due to small
+                // floating point error, and the current result is kept.
+                *result = temp_result;
+            }
+
+            distance = det(lines[i].direction, lines[i].point - *result);
+        }
+    }
+}
+
+/// Crowd is a set of navmesh agents, that are updated together and avoid collisions with each other.
+/// It also has a budget for maximum amount of agents, to keep performance predictable.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::algebra::Vector3,
+///     utils::navmesh::{crowd::Crowd, Navmesh},
+/// };
+///
+/// fn update_enemies(crowd: &mut Crowd, navmesh: &mut Navmesh, player: Vector3<f32>, dt: f32) {
+///     for agent in crowd.iter_mut() {
+///         agent.agent_mut().set_target(player);
+///     }
+///
+///     crowd.update(dt, navmesh);
+/// }
+/// ```
+#[derive(Visit, Clone, Debug)]
+pub struct Crowd {
+    agents: Pool<CrowdAgent>,
+    max_agents: u32,
+    neighbour_distance: f32,
+    max_neighbours: u32,
+    time_horizon: f32,
+    separation_weight: f32,
+    #[visit(optional)]
+    replan_distance: f32,
+    #[visit(skip)]
+    grid: FxHashMap<(i32, i32), Vec<Handle<CrowdAgent>>>,
+    #[visit(skip)]
+    lines: Vec<Line>,
+    #[visit(skip)]
+    projected_lines: Vec<Line>,
+    #[visit(skip)]
+    neighbours: Vec<(f32, Handle<CrowdAgent>)>,
+}
+
+impl Default for Crowd {
+    fn default() -> Self {
+        Self::new(128)
+    }
+}
+
+impl Crowd {
+    /// Creates new crowd with the given maximum amount of agents.
+    pub fn new(max_agents: u32) -> Self {
+        Self {
+            agents: Default::default(),
+            max_agents,
+            neighbour_distance: 5.0,
+            max_neighbours: 10,
+            time_horizon: 2.0,
+            separation_weight: 1.0,
+            replan_distance: 2.0,
+            grid: Default::default(),
+            lines: Default::default(),
+            projected_lines: Default::default(),
+            neighbours: Default::default(),
+        }
+    }
+
+    /// Adds new agent to the crowd. Returns the agent back, if maximum amount of agents was reached.
+    pub fn add_agent(&mut self, agent: CrowdAgent) -> Result<Handle<CrowdAgent>, CrowdAgent> {
+        if self.agents.alive_count() >= self.max_agents {
+            Err(agent)
+        } else {
+            Ok(self.agents.spawn(agent))
+        }
+    }
+
+    /// Removes an agent from the crowd.
+    pub fn remove_agent(&mut self, handle: Handle<CrowdAgent>) -> Option<CrowdAgent> {
+        self.agents.try_free(handle)
+    }
+
+    /// Returns a reference to an agent.
+    pub fn agent(&self, handle: Handle<CrowdAgent>) -> Option<&CrowdAgent> {
+        self.agents.try_borrow(handle)
+    }
+
+    /// Returns a reference to an agent.
+    pub fn agent_mut(&mut self, handle: Handle<CrowdAgent>) -> Option<&mut CrowdAgent> {
+        self.agents.try_borrow_mut(handle)
+    }
+
+    /// Returns an iterator over every agent of the crowd.
+    pub fn iter(&self) -> impl Iterator<Item = &CrowdAgent> {
+        self.agents.iter()
+    }
+
+    /// Returns an iterator over every agent of the crowd.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut CrowdAgent> {
+        self.agents.iter_mut()
+    }
+
+    /// Returns total amount of agents in the crowd.
+    pub fn agent_count(&self) -> usize {
+        self.agents.alive_count() as usize
+    }
+
+    /// Sets new maximum amount of agents. Does not remove existing agents, if there is more agents
+    /// than the new limit.
+    pub fn set_max_agents(&mut self, max_agents: u32) {
+        self.max_agents = max_agents;
+    }
+
+    /// Returns maximum amount of agents.
+    pub fn max_agents(&self) -> u32 {
+        self.max_agents
+    }
+
+    /// Sets maximum distance (in meters) at which agents take each other into account.
+    pub fn set_neighbour_distance(&mut self, distance: f32) {
+        self.neighbour_distance = distance.max(0.0);
+    }
+
+    /// Returns maximum distance (in meters) at which agents take each other into account.
+    pub fn neighbour_distance(&self) -> f32 {
+        self.neighbour_distance
+    }
+
+    /// Sets maximum amount of closest neighbours that will be taken into account by each agent.
+    pub fn set_max_neighbours(&mut self, max_neighbours: u32) {
+        self.max_neighbours = max_neighbours;
+    }
+
+    /// Returns maximum amount of closest neighbours that will be taken into account by each agent.
+    pub fn max_neighbours(&self) -> u32 {
+        self.max_neighbours
+    }
+
+    /// Sets time (in seconds) for which computed velocities are guaranteed to be collision-free.
+    /// Larger values makes agents to react earlier, but also makes them more "shy".
+    pub fn set_time_horizon(&mut self, time_horizon: f32) {
+        self.time_horizon = time_horizon.max(f32::EPSILON);
+    }
+
+    /// Returns time (in seconds) for which computed velocities are guaranteed to be collision-free.
+    pub fn time_horizon(&self) -> f32 {
+        self.time_horizon
+    }
+
+    /// Sets how strong overlapping agents are pushed apart from each other.
+    pub fn set_separation_weight(&mut self, weight: f32) {
+        self.separation_weight = weight.max(0.0);
+    }
+
+    /// Returns how strong overlapping agents are pushed apart from each other.
+    pub fn separation_weight(&self) -> f32 {
+        self.separation_weight
+    }
+
+    /// Sets maximum distance (in meters) between an agent and its path. If avoidance pushes an agent
+    /// further away, the path of the agent will be recalculated.
+    pub fn set_replan_distance(&mut self, distance: f32) {
+        self.replan_distance = distance.max(0.0);
+    }
+
+    /// Returns maximum distance (in meters) between an agent and its path.
+    pub fn replan_distance(&self) -> f32 {
+        self.replan_distance
+    }
+
+    fn cell(&self, position: Vector2<f32>) -> (i32, i32) {
+        let size = self.neighbour_distance.max(0.1);
+        (
+            (position.x / size).floor() as i32,
+            (position.y / size).floor() as i32,
+        )
+    }
+
+    fn collect_neighbours(&mut self, handle: Handle<CrowdAgent>) {
+        self.neighbours.clear();
+
+        let position = self.agents[handle].position_2d();
+        let (cx, cz) = self.cell(position);
+        for z in (cz - 1)..=(cz + 1) {
+            for x in (cx - 1)..=(cx + 1) {
+                if let Some(cell) = self.grid.get(&(x, z)) {
+                    for &other in cell {
+                        if other == handle {
+                            continue;
+                        }
+                        let distance = self.agents[other].position_2d().metric_distance(&position);
+                        if distance <= self.neighbour_distance {
+                            self.neighbours.push((distance, other));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.neighbours
+            .sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        self.neighbours.truncate(self.max_neighbours as usize);
+    }
+
+    fn compute_new_velocity(&mut self, handle: Handle<CrowdAgent>, dt: f32) {
+        self.collect_neighbours(handle);
+
+        self.lines.clear();
+
+        let agent = &self.agents[handle];
+        let position = agent.position_2d();
+        let velocity = agent.velocity_2d();
+        let inv_time_horizon = 1.0 / self.time_horizon;
+        let mut separation = Vector2::default();
+
+        for &(_, other) in self.neighbours.iter() {
+            let other = &self.agents[other];
+
+            let relative_position = other.position_2d() - position;
+            let relative_velocity = velocity - other.velocity_2d();
+            let dist_sq = relative_position.norm_squared();
+            let combined_radius = agent.radius + other.radius;
+            let combined_radius_sq = combined_radius * combined_radius;
+
+            let (direction, u) = if dist_sq > combined_radius_sq {
+                // No collision. Vector from cutoff center to relative velocity.
+                let w = relative_velocity - relative_position.scale(inv_time_horizon);
+                let w_length_sq = w.norm_squared();
+                let dot_product = w.dot(&relative_position);
+
+                if dot_product < 0.0 && dot_product * dot_product > combined_radius_sq * w_length_sq
+                {
+                    // Project on cut-off circle.
+                    let w_length = w_length_sq.sqrt();
+                    let unit_w = w.unscale(w_length);
+                    (
+                        Vector2::new(unit_w.y, -unit_w.x),
+                        unit_w.scale(combined_radius * inv_time_horizon - w_length),
+                    )
+                } else {
+                    // Project on legs.
+                    let leg = (dist_sq - combined_radius_sq).sqrt();
+                    let direction = if det(relative_position, w) > 0.0 {
+                        // Project on left leg.
+                        Vector2::new(
+                            relative_position.x * leg - relative_position.y * combined_radius,
+                            relative_position.x * combined_radius + relative_position.y * leg,
+                        )
+                        .unscale(dist_sq)
+                    } else {
+                        // Project on right leg.
+                        -Vector2::new(
+                            relative_position.x * leg + relative_position.y * combined_radius,
+                            -relative_position.x * combined_radius + relative_position.y * leg,
+                        )
+                        .unscale(dist_sq)
+                    };
+                    let dot_product = relative_velocity.dot(&direction);
+                    (direction, direction.scale(dot_product) - relative_velocity)
+                }
+            } else {
+                // Collision. Project on cut-off circle of time step.
+                let inv_time_step = 1.0 / dt;
+                let w = relative_velocity - relative_position.scale(inv_time_step);
+                let w_length = w.norm();
+                let unit_w = w
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(|| Vector2::new(1.0, 0.0));
+
+                // Push overlapping agents apart.
+                let distance = dist_sq.sqrt();
+                let away = (-relative_position)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(|| Vector2::new(-unit_w.y, unit_w.x));
+                separation +=
+                    away.scale((combined_radius - distance) / combined_radius.max(f32::EPSILON));
+
+                (
+                    Vector2::new(unit_w.y, -unit_w.x),
+                    unit_w.scale(combined_radius * inv_time_step - w_length),
+                )
+            };
+
+            // Both agents are responsible for the half of the avoidance.
+            self.lines.push(Line {
+                point: velocity + u.scale(0.5),
+                direction,
+            });
+        }
+
+        let max_speed = agent.agent.speed;
+        let preferred_velocity =
+            agent.preferred_velocity + separation.scale(self.separation_weight * max_speed);
+
+        let mut new_velocity = Vector2::default();
+        let line_fail = linear_program2(
+            &self.lines,
+            max_speed,
+            preferred_velocity,
+            false,
+            &mut new_velocity,
+        );
+        if line_fail < self.lines.len() {
+            linear_program3(
+                &self.lines,
+                line_fail,
+                max_speed,
+                &mut self.projected_lines,
+                &mut new_velocity,
+            );
+        }
+
+        self.agents[handle].new_velocity = new_velocity;
+    }
+
+    /// Performs single update tick of the crowd. Recalculates paths of the agents if needed,
+    /// calculates collision-free velocities for every agent and moves the agents along the
+    /// navmesh surface. A path is recalculated on the next update, if its agent was pushed too far
+    /// away from it (see [`Self::set_replan_distance`]) or if its next point became unreachable.
+    pub fn update(&mut self, dt: f32, navmesh: &mut Navmesh) {
+        if dt <= 0.0 {
+            return;
+        }
+
+        for agent in self.agents.iter_mut() {
+            let agent = &mut agent.agent;
+            if agent.path_dirty {
+                // An agent without a path just stays in place, there is nothing else to do
+                // with an error here.
+                let _ = agent.calculate_path(navmesh, agent.position, agent.target);
+                agent.path_dirty = false;
+            }
+        }
+
+        for agent in self.agents.iter_mut() {
+            agent.update_preferred_velocity(dt);
+        }
+
+        self.grid.clear();
+        for (handle, agent) in self.agents.pair_iter() {
+            let cell = self.cell(agent.position_2d());
+            self.grid.entry(cell).or_default().push(handle);
+        }
+
+        let handles = self.agents.pair_iter().map(|(h, _)| h).collect::<Vec<_>>();
+        for &handle in handles.iter() {
+            self.compute_new_velocity(handle, dt);
+        }
+
+        for agent in self.agents.iter_mut() {
+            let position = agent.agent.position;
+            let offset = agent.new_velocity.scale(dt);
+
+            // Keep the agent on navmesh surface: try to move it as is, if it leaves the navmesh,
+            // try to slide it along one of the axes.
+            let new_position = [
+                Vector3::new(offset.x, 0.0, offset.y),
+                Vector3::new(offset.x, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, offset.y),
+            ]
+            .into_iter()
+            .find_map(|offset| {
+                project_on_navmesh(navmesh, &agent.agent.filter, position, position + offset)
+            })
+            .unwrap_or(position);
+
+            agent.velocity = (new_position - position).unscale(dt);
+            agent.agent.position = new_position;
+
+            if agent.is_path_invalid(navmesh, self.replan_distance) {
+                agent.agent.path_dirty = true;
+            }
+        }
+    }
+}
+
+// An agent, that is already inside an obstacle (for example, when the obstacle was moved on it),
+// must be able to leave it.
+fn is_move_blocked(navmesh: &Navmesh, from: Vector3<f32>, to: Vector3<f32>) -> bool {
+    navmesh.is_segment_blocked(from, to) && !navmesh.is_segment_blocked(from, from)
+}
+
+// Projects a new position of an agent on navmesh surface. Triangles excluded by the filter of the
+// agent and moves through temporary obstacles are rejected.
+fn project_on_navmesh(
+    navmesh: &Navmesh,
+    filter: &NavmeshFilter,
+    position: Vector3<f32>,
+    new_position: Vector3<f32>,
+) -> Option<Vector3<f32>> {
+    navmesh
+        .ray_cast_filtered(
+            Ray::new(
+                new_position + Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(0.0, -10.0, 0.0),
+            ),
+            filter,
+        )
+        .map(|(point, _, _)| point)
+        .filter(|point| !is_move_blocked(navmesh, position, *point))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, math::TriangleDefinition},
+        utils::navmesh::{
+            crowd::{project_on_navmesh, Crowd, CrowdAgent},
+            Navmesh, NavmeshAgentBuilder, NavmeshFilter, NavmeshObstacle,
+        },
+    };
+
+    fn make_navmesh() -> Navmesh {
+        Navmesh::new(
+            &[TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 2, 3])],
+            &[
+                Vector3::new(-10.0, 0.0, -10.0),
+                Vector3::new(-10.0, 0.0, 10.0),
+                Vector3::new(10.0, 0.0, 10.0),
+                Vector3::new(10.0, 0.0, -10.0),
+            ],
+        )
+    }
+
+    fn make_agent(position: Vector3<f32>, target: Vector3<f32>) -> CrowdAgent {
+        CrowdAgent::new(
+            NavmeshAgentBuilder::new()
+                .with_position(position)
+                .with_target(target)
+                .with_speed(2.0)
+                .build(),
+            0.5,
+        )
+    }
+
+    #[test]
+    fn test_crowd_agents_budget() {
+        let mut crowd = Crowd::new(1);
+        assert!(crowd.add_agent(CrowdAgent::default()).is_ok());
+        assert!(crowd.add_agent(CrowdAgent::default()).is_err());
+        assert_eq!(crowd.agent_count(), 1);
+    }
+
+    #[test]
+    fn test_crowd_avoidance() {
+        let mut navmesh = make_navmesh();
+        let mut crowd = Crowd::default();
+
+        let a = Vector3::new(-5.0, 0.0, 0.0);
+        let b = Vector3::new(5.0, 0.0, 0.01);
+
+        let first = crowd.add_agent(make_agent(a, b)).unwrap();
+        let second = crowd.add_agent(make_agent(b, a)).unwrap();
+
+        for _ in 0..600 {
+            crowd.update(1.0 / 60.0, &mut navmesh);
+
+            let first = crowd.agent(first).unwrap().position();
+            let second = crowd.agent(second).unwrap().position();
+            assert!(first.metric_distance(&second) >= 0.95);
+        }
+
+        assert!(crowd.agent(first).unwrap().position().metric_distance(&b) < 0.1);
+        assert!(crowd.agent(second).unwrap().position().metric_distance(&a) < 0.1);
+    }
+
+    #[test]
+    fn test_projection_respects_filter_and_obstacles() {
+        let mut navmesh = make_navmesh();
+        let from = Vector3::new(-5.0, 0.0, 0.0);
+        let to = Vector3::new(0.0, 0.0, -5.0);
+
+        let mut filter = NavmeshFilter::default();
+        assert!(project_on_navmesh(&navmesh, &filter, from, to).is_some());
+
+        // The second triangle cannot be used by the agent.
+        navmesh.set_triangle_area(1, 1);
+        filter.exclude_area(1);
+        assert!(project_on_navmesh(&navmesh, &filter, from, to).is_none());
+
+        navmesh.add_obstacle(NavmeshObstacle {
+            position: Vector3::new(-2.5, 0.0, -2.5),
+            half_extents: Vector3::new(0.5, 1.0, 0.5),
+        });
+        assert!(project_on_navmesh(&navmesh, &NavmeshFilter::default(), from, to).is_none());
+    }
+
+    #[test]
+    fn test_crowd_replanning() {
+        let mut navmesh = make_navmesh();
+        let mut crowd = Crowd::default();
+
+        let agent = crowd
+            .add_agent(make_agent(
+                Vector3::new(-5.0, 0.0, 0.0),
+                Vector3::new(5.0, 0.0, 0.0),
+            ))
+            .unwrap();
+        crowd.update(1.0 / 60.0, &mut navmesh);
+
+        // Push the agent far away from its path.
+        let pushed = Vector3::new(0.0, 0.0, 5.0);
+        crowd.agent_mut(agent).unwrap().agent.position = pushed;
+        crowd.update(1.0 / 60.0, &mut navmesh);
+        crowd.update(1.0 / 60.0, &mut navmesh);
+
+        let path = crowd.agent(agent).unwrap().agent().path();
+        assert!(path[0].metric_distance(&pushed) < 0.1);
+    }
+}
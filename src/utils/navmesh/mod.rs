@@ -11,7 +11,7 @@ use crate::{
         arrayvec::ArrayVec,
         math::{self, ray::Ray, TriangleDefinition},
         octree::{Octree, OctreeNode},
-        pool::Handle,
+        pool::{Handle, Pool},
         visitor::{Visit, VisitResult, Visitor},
     },
     scene::mesh::{
@@ -23,7 +23,144 @@ use crate::{
         raw_mesh::{RawMeshBuilder, RawVertex},
     },
 };
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
+
+pub mod bake;
+pub mod crowd;
+
+/// Maximum amount of area types of a navmesh. See [`Navmesh::set_triangle_area`] for more info.
+pub const MAX_AREAS: u8 = 64;
+
+/// Off-mesh link is a connection between two points of a navmesh, that cannot be traversed by
+/// simple walking. It could be a jump, a ladder, a teleport, etc. Link end points are snapped to the
+/// closest vertices of the navmesh. See [`NavmeshAgent::update_with_link_callback`] for more info
+/// about link traversal.
+#[derive(Clone, Debug, Visit, PartialEq)]
+pub struct OffMeshLink {
+    /// Start point of the link.
+    pub start: Vector3<f32>,
+    /// End point of the link.
+    pub end: Vector3<f32>,
+    /// Whether the link can be traversed in both directions or not.
+    pub bidirectional: bool,
+    /// Cost multiplier of the link traversal.
+    pub cost: f32,
+    /// Area type of the link. It could be used to forbid some agents to use the link.
+    pub area: u8,
+    /// Arbitrary user data, that could be used to distinguish links (for example - to select
+    /// an animation for traversal).
+    pub user_data: u64,
+}
+
+impl Default for OffMeshLink {
+    fn default() -> Self {
+        Self {
+            start: Default::default(),
+            end: Default::default(),
+            bidirectional: true,
+            cost: 1.0,
+            area: 0,
+            user_data: 0,
+        }
+    }
+}
+
+/// Temporary obstacle (door, vehicle, etc.) in a form of axis-aligned box. Navmesh edges that
+/// intersect an obstacle cannot be traversed, this way obstacles "carve" the navmesh without
+/// rebuilding it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NavmeshObstacle {
+    /// Center of the obstacle.
+    pub position: Vector3<f32>,
+    /// Half-extents of the obstacle.
+    pub half_extents: Vector3<f32>,
+}
+
+impl NavmeshObstacle {
+    /// Checks whether the given segment intersects the obstacle or not.
+    pub fn is_intersects_segment(&self, a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        let min = self.position - self.half_extents;
+        let max = self.position + self.half_extents;
+        let dir = b - a;
+        let mut t_min = 0.0f32;
+        let mut t_max = 1.0f32;
+        for i in 0..3 {
+            if dir[i].abs() <= f32::EPSILON {
+                if a[i] < min[i] || a[i] > max[i] {
+                    return false;
+                }
+            } else {
+                let t0 = (min[i] - a[i]) / dir[i];
+                let t1 = (max[i] - a[i]) / dir[i];
+                t_min = t_min.max(t0.min(t1));
+                t_max = t_max.min(t0.max(t1));
+                if t_min > t_max {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Query filter defines which parts of a navmesh can be used by an agent.
+#[derive(Clone, Debug, Visit, PartialEq)]
+pub struct NavmeshFilter {
+    /// A bit mask of excluded areas, i-th bit corresponds to i-th area type.
+    pub excluded_areas: u64,
+    /// Whether off-mesh links can be used or not.
+    pub use_links: bool,
+}
+
+impl Default for NavmeshFilter {
+    fn default() -> Self {
+        Self {
+            excluded_areas: 0,
+            use_links: true,
+        }
+    }
+}
+
+impl NavmeshFilter {
+    /// Forbids an agent to travel across the given area type.
+    pub fn exclude_area(&mut self, area: u8) {
+        if area < MAX_AREAS {
+            self.excluded_areas |= 1 << area;
+        }
+    }
+
+    /// Allows an agent to travel across the given area type.
+    pub fn include_area(&mut self, area: u8) {
+        if area < MAX_AREAS {
+            self.excluded_areas &= !(1 << area);
+        }
+    }
+
+    /// Checks whether the given area type can be used or not.
+    pub fn is_area_allowed(&self, area: u8) -> bool {
+        area >= MAX_AREAS || self.excluded_areas & (1 << area) == 0
+    }
+}
+
+// Pair of sorted vertex indices of an edge.
+type EdgeKey = (u32, u32);
+
+fn edge_key(a: usize, b: usize) -> EdgeKey {
+    let (a, b) = (a as u32, b as u32);
+    (a.min(b), a.max(b))
+}
+
+// Data derived from triangles, off-mesh links and obstacles, which is used by the path search. It is
+// rebuilt lazily only when the source data changes, not on every query.
+#[derive(Clone, Debug, Default)]
+struct SearchCache {
+    // Bit masks of area types of the triangles that share an edge.
+    edge_areas: Option<FxHashMap<EdgeKey, u64>>,
+    // Vertex indices of end points of every off-mesh link.
+    link_vertices: Option<Vec<Option<(usize, usize)>>>,
+    // Edges that are intersected by at least one temporary obstacle.
+    blocked_edges: Option<FxHashSet<EdgeKey>>,
+}
 
 /// See module docs.
 #[derive(Clone, Debug, Default)]
@@ -32,6 +169,11 @@ pub struct Navmesh {
     triangles: Vec<TriangleDefinition>,
     pathfinder: PathFinder,
     query_buffer: Vec<u32>,
+    triangle_areas: Vec<u8>,
+    area_costs: Vec<f32>,
+    links: Vec<OffMeshLink>,
+    obstacles: Pool<NavmeshObstacle>,
+    search_cache: SearchCache,
 }
 
 impl Visit for Navmesh {
@@ -40,11 +182,17 @@ impl Visit for Navmesh {
 
         self.pathfinder.visit("PathFinder", &mut region)?;
         self.triangles.visit("Triangles", &mut region)?;
+        let _ = self.triangle_areas.visit("TriangleAreas", &mut region);
+        let _ = self.area_costs.visit("AreaCosts", &mut region);
+        let _ = self.links.visit("Links", &mut region);
 
         drop(region);
 
         // No need to save octree, we can restore it on load.
         if visitor.is_reading() {
+            self.triangle_areas.resize(self.triangles.len(), 0);
+            self.search_cache = Default::default();
+
             let vertices = self.pathfinder.vertices();
             let raw_triangles = self
                 .triangles
@@ -102,6 +250,11 @@ impl Navmesh {
             octree: Octree::new(&raw_triangles, 32),
             pathfinder,
             query_buffer: Default::default(),
+            triangle_areas: vec![0; triangles.len()],
+            area_costs: Default::default(),
+            links: Default::default(),
+            obstacles: Default::default(),
+            search_cache: Default::default(),
         }
     }
 
@@ -212,6 +365,8 @@ impl Navmesh {
                 .link_bidirect(edge.a as usize, edge.b as usize);
         }
         self.triangles.push(triangle);
+        self.triangle_areas.push(0);
+        self.invalidate_search_cache();
         index as u32
     }
 
@@ -219,6 +374,10 @@ impl Navmesh {
     /// internal navigational graph.
     pub fn remove_triangle(&mut self, index: usize) -> TriangleDefinition {
         let triangle = self.triangles.remove(index);
+        if index < self.triangle_areas.len() {
+            self.triangle_areas.remove(index);
+        }
+        self.invalidate_search_cache();
         for &vertex_index in triangle.indices() {
             let mut isolated = true;
             for other_triangle in self.triangles.iter() {
@@ -294,6 +453,7 @@ impl Navmesh {
             }
         }
 
+        self.invalidate_search_cache();
         self.pathfinder.remove_vertex(index)
     }
 
@@ -304,11 +464,13 @@ impl Navmesh {
 
     /// Returns a mutable reference to the internal array of vertices.
     pub fn vertices_mut(&mut self) -> &mut [PathVertex] {
+        self.invalidate_search_cache();
         self.pathfinder.vertices_mut()
     }
 
     /// Adds the vertex to the navigational mesh. The vertex will **not** be connected with any other vertex.
     pub fn add_vertex(&mut self, vertex: PathVertex) -> u32 {
+        self.invalidate_search_cache();
         self.pathfinder.add_vertex(vertex)
     }
 
@@ -324,6 +486,7 @@ impl Navmesh {
     /// Inserts the vertex at the given index. Automatically shift indices in triangles to preserve mesh structure.
     pub fn insert_vertex(&mut self, index: u32, vertex: PathVertex) {
         self.pathfinder.insert_vertex(index, vertex);
+        self.invalidate_search_cache();
 
         // Shift vertex indices in triangles. Example:
         //
@@ -374,11 +537,268 @@ impl Navmesh {
         to: usize,
         path: &mut Vec<Vector3<f32>>,
     ) -> Result<PathKind, PathError> {
-        self.pathfinder.build(from, to, path)
+        self.build_path_filtered(from, to, path, &Default::default())
+    }
+
+    /// Same as [`Self::build_path`], but uses the given filter to exclude some areas or off-mesh
+    /// links from the search. Path search takes area costs, off-mesh links and temporary obstacles
+    /// into account.
+    pub fn build_path_filtered(
+        &mut self,
+        from: usize,
+        to: usize,
+        path: &mut Vec<Vector3<f32>>,
+        filter: &NavmeshFilter,
+    ) -> Result<PathKind, PathError> {
+        let use_links = filter.use_links && !self.links.is_empty();
+        let has_obstacles = self.obstacles.alive_count() > 0;
+        if filter.excluded_areas == 0
+            && !use_links
+            && !has_obstacles
+            && self.area_costs.iter().all(|cost| *cost == 1.0)
+        {
+            // Fast path, every edge has the same cost.
+            return self.pathfinder.build(from, to, path);
+        }
+
+        self.update_search_cache();
+
+        let Self {
+            pathfinder,
+            area_costs,
+            links,
+            search_cache,
+            ..
+        } = self;
+        let area_cost = |area: u8| area_costs.get(area as usize).cloned().unwrap_or(1.0);
+        let edge_areas = search_cache.edge_areas.as_ref().unwrap();
+        let link_vertices = search_cache.link_vertices.as_ref().unwrap();
+        let blocked_edges = search_cache.blocked_edges.as_ref().unwrap();
+
+        // Off-mesh links are temporarily added to the graph for the time of the search.
+        let mut link_costs = FxHashMap::default();
+        let mut temporary_edges = Vec::new();
+        if use_links {
+            for (link, vertices) in links.iter().zip(link_vertices.iter()) {
+                if !filter.is_area_allowed(link.area) {
+                    continue;
+                }
+
+                if let Some((a, b)) = *vertices {
+                    let cost = link.cost * area_cost(link.area);
+                    let mut edges = vec![(a, b)];
+                    if link.bidirectional {
+                        edges.push((b, a));
+                    }
+
+                    for (a, b) in edges {
+                        link_costs.insert((a, b), cost);
+                        if pathfinder.vertices()[a]
+                            .neighbours
+                            .iter()
+                            .all(|n| *n as usize != b)
+                        {
+                            pathfinder.link_unidirect(a, b);
+                            temporary_edges.push((a, b));
+                        }
+                    }
+                }
+            }
+        }
+
+        let result = pathfinder.build_with_cost(from, to, path, |a, b| {
+            if let Some(cost) = link_costs.get(&(a, b)) {
+                return Some(*cost);
+            }
+
+            let key = edge_key(a, b);
+            if blocked_edges.contains(&key) {
+                return None;
+            }
+
+            // Edge cost is defined by the cheapest allowed triangle that shares the edge.
+            let mut areas = match edge_areas.get(&key) {
+                Some(areas) => *areas & !filter.excluded_areas,
+                None => return Some(1.0),
+            };
+            let mut cost = None;
+            while areas != 0 {
+                let area = areas.trailing_zeros() as u8;
+                areas &= areas - 1;
+                let current = area_cost(area);
+                cost = Some(cost.map_or(current, |cost: f32| cost.min(current)));
+            }
+            cost
+        });
+
+        for (a, b) in temporary_edges {
+            if let Some(vertex) = pathfinder.vertex_mut(a) {
+                vertex.neighbours.retain(|n| *n as usize != b);
+            }
+        }
+
+        result
+    }
+
+    fn invalidate_search_cache(&mut self) {
+        self.search_cache = Default::default();
+    }
+
+    fn update_search_cache(&mut self) {
+        if self.search_cache.edge_areas.is_none() {
+            let mut edge_areas = FxHashMap::<EdgeKey, u64>::default();
+            for (triangle, &area) in self.triangles.iter().zip(self.triangle_areas.iter()) {
+                for edge in triangle.edges() {
+                    *edge_areas
+                        .entry(edge_key(edge.a as usize, edge.b as usize))
+                        .or_default() |= 1 << area.min(MAX_AREAS - 1);
+                }
+            }
+            self.search_cache.edge_areas = Some(edge_areas);
+        }
+
+        if self.search_cache.link_vertices.is_none() {
+            self.search_cache.link_vertices = Some(
+                (0..self.links.len())
+                    .map(|index| self.link_vertices(index))
+                    .collect(),
+            );
+        }
+
+        if self.search_cache.blocked_edges.is_none() {
+            let mut blocked_edges = FxHashSet::default();
+            if self.obstacles.alive_count() > 0 {
+                let vertices = self.pathfinder.vertices();
+                for (a, vertex) in vertices.iter().enumerate() {
+                    for &b in vertex.neighbours.iter() {
+                        let key = edge_key(a, b as usize);
+                        if !blocked_edges.contains(&key)
+                            && self
+                                .is_segment_blocked(vertex.position, vertices[b as usize].position)
+                        {
+                            blocked_edges.insert(key);
+                        }
+                    }
+                }
+            }
+            self.search_cache.blocked_edges = Some(blocked_edges);
+        }
+    }
+
+    /// Sets area type of a triangle at the given index. Area type defines traversal cost of the
+    /// triangle (see [`Self::set_area_cost`]) and could be excluded from path search by
+    /// [`NavmeshFilter`]. Area type must be less than [`MAX_AREAS`], by default every triangle
+    /// has zero area type.
+    pub fn set_triangle_area(&mut self, triangle: usize, area: u8) {
+        if let Some(triangle_area) = self.triangle_areas.get_mut(triangle) {
+            *triangle_area = area.min(MAX_AREAS - 1);
+            self.search_cache.edge_areas = None;
+        }
+    }
+
+    /// Returns area type of a triangle at the given index.
+    pub fn triangle_area(&self, triangle: usize) -> u8 {
+        self.triangle_areas
+            .get(triangle)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Sets traversal cost multiplier of the given area type. Default cost of every area type is 1.0.
+    pub fn set_area_cost(&mut self, area: u8, cost: f32) {
+        let area = area.min(MAX_AREAS - 1) as usize;
+        if area >= self.area_costs.len() {
+            self.area_costs.resize(area + 1, 1.0);
+        }
+        self.area_costs[area] = cost.max(0.0);
+    }
+
+    /// Returns traversal cost multiplier of the given area type.
+    pub fn area_cost(&self, area: u8) -> f32 {
+        self.area_costs.get(area as usize).cloned().unwrap_or(1.0)
+    }
+
+    /// Adds new off-mesh link and returns its index.
+    pub fn add_link(&mut self, link: OffMeshLink) -> usize {
+        self.links.push(link);
+        self.search_cache.link_vertices = None;
+        self.links.len() - 1
+    }
+
+    /// Removes an off-mesh link at the given index.
+    pub fn remove_link(&mut self, index: usize) -> OffMeshLink {
+        self.search_cache.link_vertices = None;
+        self.links.remove(index)
+    }
+
+    /// Returns a reference to the array of off-mesh links.
+    pub fn links(&self) -> &[OffMeshLink] {
+        &self.links
+    }
+
+    /// Returns a reference to the array of off-mesh links.
+    pub fn links_mut(&mut self) -> &mut [OffMeshLink] {
+        self.search_cache.link_vertices = None;
+        &mut self.links
+    }
+
+    /// Returns indices of the navmesh vertices, that are used as start and end points of an
+    /// off-mesh link at the given index.
+    pub fn link_vertices(&self, index: usize) -> Option<(usize, usize)> {
+        let link = self.links.get(index)?;
+        let a = math::get_closest_point(self.pathfinder.vertices(), link.start)?;
+        let b = math::get_closest_point(self.pathfinder.vertices(), link.end)?;
+        if a == b {
+            None
+        } else {
+            Some((a, b))
+        }
+    }
+
+    /// Adds new temporary obstacle.
+    pub fn add_obstacle(&mut self, obstacle: NavmeshObstacle) -> Handle<NavmeshObstacle> {
+        self.search_cache.blocked_edges = None;
+        self.obstacles.spawn(obstacle)
+    }
+
+    /// Removes a temporary obstacle.
+    pub fn remove_obstacle(&mut self, handle: Handle<NavmeshObstacle>) -> Option<NavmeshObstacle> {
+        self.search_cache.blocked_edges = None;
+        self.obstacles.try_free(handle)
+    }
+
+    /// Returns a reference to a temporary obstacle.
+    pub fn obstacle(&self, handle: Handle<NavmeshObstacle>) -> Option<&NavmeshObstacle> {
+        self.obstacles.try_borrow(handle)
+    }
+
+    /// Returns a reference to a temporary obstacle. Could be used to move the obstacle.
+    pub fn obstacle_mut(
+        &mut self,
+        handle: Handle<NavmeshObstacle>,
+    ) -> Option<&mut NavmeshObstacle> {
+        self.search_cache.blocked_edges = None;
+        self.obstacles.try_borrow_mut(handle)
+    }
+
+    /// Checks whether the given segment intersects any temporary obstacle or not.
+    pub fn is_segment_blocked(&self, a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        self.obstacles
+            .iter()
+            .any(|obstacle| obstacle.is_intersects_segment(a, b))
     }
 
     /// Tries to pick a triangle by given ray. Returns closest result.
     pub fn ray_cast(&self, ray: Ray) -> Option<(Vector3<f32>, usize, TriangleDefinition)> {
+        self.ray_cast_filtered(ray, &Default::default())
+    }
+
+    /// Same as [`Self::ray_cast`], but ignores triangles with area types excluded by the given filter.
+    pub fn ray_cast_filtered(
+        &self,
+        ray: Ray,
+        filter: &NavmeshFilter,
+    ) -> Option<(Vector3<f32>, usize, TriangleDefinition)> {
         let mut buffer = ArrayVec::<Handle<OctreeNode>, 128>::new();
 
         self.octree.ray_query_static(&ray, &mut buffer);
@@ -388,6 +808,10 @@ impl Navmesh {
         for node in buffer.into_iter() {
             if let OctreeNode::Leaf { indices, .. } = self.octree.node(node) {
                 for &index in indices {
+                    if !filter.is_area_allowed(self.triangle_area(index as usize)) {
+                        continue;
+                    }
+
                     let triangle = self.triangles[index as usize].clone();
                     let a = self.pathfinder.vertices()[triangle[0] as usize].position;
                     let b = self.pathfinder.vertices()[triangle[1] as usize].position;
@@ -423,6 +847,13 @@ pub struct NavmeshAgent {
     recalculation_threshold: f32,
     speed: f32,
     path_dirty: bool,
+    #[visit(optional)]
+    filter: NavmeshFilter,
+    // Indices of off-mesh links for every segment of the path.
+    #[visit(skip)]
+    path_links: Vec<Option<usize>>,
+    #[visit(skip)]
+    link_time: f32,
 }
 
 impl Default for NavmeshAgent {
@@ -444,6 +875,9 @@ impl NavmeshAgent {
             recalculation_threshold: 0.25,
             speed: 1.5,
             path_dirty: true,
+            filter: Default::default(),
+            path_links: Default::default(),
+            link_time: 0.0,
         }
     }
 
@@ -466,6 +900,25 @@ impl NavmeshAgent {
     pub fn speed(&self) -> f32 {
         self.speed
     }
+
+    /// Sets new query filter of the agent. The path will be recalculated on next update.
+    pub fn set_filter(&mut self, filter: NavmeshFilter) {
+        self.filter = filter;
+        self.path_dirty = true;
+    }
+
+    /// Returns current query filter of the agent.
+    pub fn filter(&self) -> &NavmeshFilter {
+        &self.filter
+    }
+
+    /// Returns index of an off-mesh link that is currently traversed by the agent (if any).
+    pub fn current_link(&self) -> Option<usize> {
+        self.path_links
+            .get(self.current as usize)
+            .cloned()
+            .flatten()
+    }
 }
 
 fn closest_point_index_in_triangle_and_adjacent(
@@ -488,6 +941,8 @@ impl NavmeshAgent {
         to: Vector3<f32>,
     ) -> Result<PathKind, PathError> {
         self.path.clear();
+        self.path_links.clear();
+        self.link_time = 0.0;
 
         self.current = 0;
 
@@ -520,7 +975,12 @@ impl NavmeshAgent {
         };
 
         if let (Some(from_triangle), Some(to_triangle)) = (from_triangle, to_triangle) {
-            if from_triangle == to_triangle {
+            if from_triangle == to_triangle
+                && self
+                    .filter
+                    .is_area_allowed(navmesh.triangle_area(from_triangle))
+                && !navmesh.is_segment_blocked(from, to)
+            {
                 self.path.push(from);
                 self.path.push(to);
 
@@ -529,7 +989,7 @@ impl NavmeshAgent {
         }
 
         if let (Some(n_from), Some(n_to)) = (n_from, n_to) {
-            let result = navmesh.build_path(n_from, n_to, &mut self.path);
+            let result = navmesh.build_path_filtered(n_from, n_to, &mut self.path, &self.filter);
 
             if let Some(end) = end {
                 if self.path.is_empty() {
@@ -545,6 +1005,8 @@ impl NavmeshAgent {
 
             self.path.reverse();
 
+            self.find_path_links(navmesh);
+
             // Perform few smoothing passes to straighten computed path.
             for _ in 0..2 {
                 self.smooth_path(navmesh);
@@ -556,11 +1018,47 @@ impl NavmeshAgent {
         }
     }
 
+    fn find_path_links(&mut self, navmesh: &Navmesh) {
+        let vertices = navmesh.vertices();
+        let link_positions = (0..navmesh.links().len())
+            .filter_map(|i| {
+                navmesh
+                    .link_vertices(i)
+                    .map(|(a, b)| (i, vertices[a].position, vertices[b].position))
+            })
+            .collect::<Vec<_>>();
+
+        self.path_links = self
+            .path
+            .windows(2)
+            .map(|segment| {
+                link_positions.iter().find_map(|(i, a, b)| {
+                    let link = &navmesh.links()[*i];
+                    if (segment[0] == *a && segment[1] == *b)
+                        || (link.bidirectional && segment[0] == *b && segment[1] == *a)
+                    {
+                        Some(*i)
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect();
+    }
+
     fn smooth_path(&mut self, navmesh: &Navmesh) {
         let vertices = navmesh.vertices();
 
         let mut i = 0;
         while i < self.path.len().saturating_sub(2) {
+            // End points of off-mesh links must stay in place.
+            if self.path_links.get(i).cloned().flatten().is_some()
+                || self.path_links.get(i + 1).cloned().flatten().is_some()
+            {
+                i += 1;
+                continue;
+            }
+
             let begin = self.path[i];
             let end = self.path[i + 2];
             let delta = end - begin;
@@ -579,7 +1077,14 @@ impl NavmeshAgent {
 
             // And check if center is lying on navmesh or not. If so - replace i+1 vertex
             // with its projection on the triangle it belongs to.
-            for triangle in navmesh.triangles.iter() {
+            for (triangle_index, triangle) in navmesh.triangles.iter().enumerate() {
+                if !self
+                    .filter
+                    .is_area_allowed(navmesh.triangle_area(triangle_index))
+                {
+                    continue;
+                }
+
                 let a = vertices[triangle[0] as usize].position;
                 let b = vertices[triangle[1] as usize].position;
                 let c = vertices[triangle[2] as usize].position;
@@ -594,8 +1099,12 @@ impl NavmeshAgent {
                         // Project center on the triangle.
                         let center_projection = center - normal.scale(signed_distance);
 
-                        // And check if the projection lies inside the triangle.
-                        if math::is_point_inside_triangle(&center_projection, &[a, b, c]) {
+                        // And check if the projection lies inside the triangle and the new path
+                        // does not cross any obstacle.
+                        if math::is_point_inside_triangle(&center_projection, &[a, b, c])
+                            && !navmesh.is_segment_blocked(begin, center_projection)
+                            && !navmesh.is_segment_blocked(center_projection, end)
+                        {
                             self.path[i + 1] = center_projection;
                             break;
                         }
@@ -608,13 +1117,43 @@ impl NavmeshAgent {
     }
 
     /// Performs single update tick that moves agent to the target along the path (which is automatically
-    /// recalculated if target's position has changed).
+    /// recalculated if target's position has changed). Off-mesh links are traversed instantly, use
+    /// [`Self::update_with_link_callback`] if you need to control the traversal.
     pub fn update(&mut self, dt: f32, navmesh: &mut Navmesh) -> Result<PathKind, PathError> {
+        self.update_with_link_callback(dt, navmesh, |_, _| true)
+    }
+
+    /// Same as [`Self::update`], but calls the given callback every tick while the agent is
+    /// traversing an off-mesh link. The callback receives the link and time (in seconds) since the
+    /// beginning of the traversal, it must return `true` when the traversal is done (for example,
+    /// when jump animation has ended). The agent stays at the start point of the link until the
+    /// traversal is done and then it is moved to the end point of the link.
+    pub fn update_with_link_callback<F>(
+        &mut self,
+        dt: f32,
+        navmesh: &mut Navmesh,
+        mut callback: F,
+    ) -> Result<PathKind, PathError>
+    where
+        F: FnMut(&OffMeshLink, f32) -> bool,
+    {
         if self.path_dirty {
             self.calculate_path(navmesh, self.position, self.target)?;
             self.path_dirty = false;
         }
 
+        if let Some(link) = self.current_link().and_then(|i| navmesh.links().get(i)) {
+            self.link_time += dt;
+            if callback(link, self.link_time) {
+                self.link_time = 0.0;
+                self.current += 1;
+                if let Some(end) = self.path.get(self.current as usize) {
+                    self.position = *end;
+                }
+            }
+            return Ok(PathKind::Full);
+        }
+
         if let Some(source) = self.path.get(self.current as usize) {
             if let Some(destination) = self.path.get((self.current + 1) as usize) {
                 let ray = Ray::from_two_points(*source, *destination);
@@ -670,6 +1209,7 @@ pub struct NavmeshAgentBuilder {
     target: Vector3<f32>,
     recalculation_threshold: f32,
     speed: f32,
+    filter: NavmeshFilter,
 }
 
 impl Default for NavmeshAgentBuilder {
@@ -686,6 +1226,7 @@ impl NavmeshAgentBuilder {
             target: Default::default(),
             recalculation_threshold: 0.25,
             speed: 1.5,
+            filter: Default::default(),
         }
     }
 
@@ -713,6 +1254,12 @@ impl NavmeshAgentBuilder {
         self
     }
 
+    /// Sets new desired query filter of the agent being built.
+    pub fn with_filter(mut self, filter: NavmeshFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
     /// Build the agent.
     pub fn build(self) -> NavmeshAgent {
         NavmeshAgent {
@@ -722,6 +1269,7 @@ impl NavmeshAgentBuilder {
             last_target_position: self.target,
             recalculation_threshold: self.recalculation_threshold,
             speed: self.speed,
+            filter: self.filter,
             ..Default::default()
         }
     }
@@ -731,7 +1279,10 @@ impl NavmeshAgentBuilder {
 mod test {
     use crate::{
         core::{algebra::Vector3, math::TriangleDefinition},
-        utils::navmesh::Navmesh,
+        utils::{
+            astar::PathKind,
+            navmesh::{Navmesh, NavmeshAgentBuilder, NavmeshFilter, NavmeshObstacle, OffMeshLink},
+        },
     };
 
     fn make_navmesh() -> Navmesh {
@@ -853,4 +1404,116 @@ mod test {
         assert_eq!(navmesh.triangles().len(), 0);
         assert_eq!(navmesh.vertices().len(), 0);
     }
+
+    #[test]
+    fn test_area_filter() {
+        let mut navmesh = make_navmesh();
+        let mut path = Vec::new();
+
+        assert_eq!(navmesh.build_path(0, 4, &mut path).unwrap(), PathKind::Full);
+        assert_eq!(path.len(), 2);
+
+        // Make triangle C non-walkable, the only edge between 0 and 4 belongs to it.
+        navmesh.set_triangle_area(2, 1);
+        let mut filter = NavmeshFilter::default();
+        filter.exclude_area(1);
+
+        assert_eq!(
+            navmesh
+                .build_path_filtered(0, 4, &mut path, &filter)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(path.len(), 3);
+
+        // Changes of areas must be taken into account by subsequent queries.
+        navmesh.set_triangle_area(2, 0);
+        navmesh
+            .build_path_filtered(0, 4, &mut path, &filter)
+            .unwrap();
+        assert_eq!(path.len(), 2);
+    }
+
+    #[test]
+    fn test_obstacle() {
+        let mut navmesh = make_navmesh();
+        let mut path = Vec::new();
+
+        let obstacle = navmesh.add_obstacle(NavmeshObstacle {
+            position: Vector3::new(-1.5, 0.0, 1.5),
+            half_extents: Vector3::new(0.1, 0.1, 0.1),
+        });
+
+        navmesh.build_path(0, 4, &mut path).unwrap();
+        assert_eq!(path.len(), 3);
+
+        // Move the obstacle away and then back.
+        navmesh.obstacle_mut(obstacle).unwrap().position = Vector3::new(10.0, 0.0, 10.0);
+        navmesh.build_path(0, 4, &mut path).unwrap();
+        assert_eq!(path.len(), 2);
+
+        navmesh.obstacle_mut(obstacle).unwrap().position = Vector3::new(-1.5, 0.0, 1.5);
+        navmesh.build_path(0, 4, &mut path).unwrap();
+        assert_eq!(path.len(), 3);
+
+        navmesh.remove_obstacle(obstacle);
+
+        navmesh.build_path(0, 4, &mut path).unwrap();
+        assert_eq!(path.len(), 2);
+    }
+
+    #[test]
+    fn test_off_mesh_link() {
+        let mut navmesh = Navmesh::new(
+            &[TriangleDefinition([0, 1, 2]), TriangleDefinition([3, 4, 5])],
+            &[
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 1.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(3.0, 1.0, 0.0),
+                Vector3::new(3.0, 1.0, 1.0),
+                Vector3::new(4.0, 1.0, 0.0),
+            ],
+        );
+
+        let mut path = Vec::new();
+        assert_eq!(
+            navmesh.build_path(1, 5, &mut path).unwrap(),
+            PathKind::Partial
+        );
+
+        navmesh.add_link(OffMeshLink {
+            start: Vector3::new(1.0, 0.0, 0.0),
+            end: Vector3::new(3.0, 1.0, 0.0),
+            user_data: 123,
+            ..Default::default()
+        });
+
+        assert_eq!(navmesh.build_path(1, 5, &mut path).unwrap(), PathKind::Full);
+
+        let mut agent = NavmeshAgentBuilder::new()
+            .with_position(Vector3::new(0.1, 0.0, 0.1))
+            .with_target(Vector3::new(3.5, 1.0, 0.1))
+            .with_speed(1.0)
+            .build();
+
+        let mut traversed = false;
+        for _ in 0..600 {
+            agent
+                .update_with_link_callback(1.0 / 60.0, &mut navmesh, |link, time| {
+                    assert_eq!(link.user_data, 123);
+                    traversed = true;
+                    time >= 0.5
+                })
+                .unwrap();
+        }
+
+        assert!(traversed);
+        assert!(
+            agent
+                .position()
+                .metric_distance(&Vector3::new(3.5, 1.0, 0.1))
+                < 0.1
+        );
+    }
 }